@@ -0,0 +1,2 @@
+-- full-size album art is stored on disk in the art directory, named after this hash
+ALTER TABLE album ADD image_hash TEXT;
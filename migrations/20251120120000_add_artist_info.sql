@@ -0,0 +1,4 @@
+-- artist images are stored on disk in the art directory like album art, named after this hash
+ALTER TABLE artist ADD image_hash TEXT;
+-- when the artist's bio and image were last looked up online, whether or not any were found
+ALTER TABLE artist ADD info_fetched_at DATETIME;
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS quarantine (
+    location TEXT PRIMARY KEY,
+    error TEXT NOT NULL,
+    quarantined_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
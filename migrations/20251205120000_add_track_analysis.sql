@@ -0,0 +1,3 @@
+ALTER TABLE track ADD bpm REAL;
+ALTER TABLE track ADD musical_key TEXT;
+ALTER TABLE track ADD analyzed_at DATETIME;
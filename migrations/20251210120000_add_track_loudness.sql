@@ -0,0 +1,5 @@
+ALTER TABLE track ADD replaygain_track_gain REAL;
+ALTER TABLE track ADD replaygain_track_peak REAL;
+ALTER TABLE track ADD loudness REAL;
+ALTER TABLE track ADD true_peak REAL;
+ALTER TABLE track ADD loudness_analyzed_at DATETIME;
@@ -0,0 +1,2 @@
+ALTER TABLE track ADD audio_start REAL;
+ALTER TABLE track ADD audio_end REAL;
@@ -0,0 +1,3 @@
+-- the same expression is used by the year queries, so that they can use this index
+CREATE INDEX IF NOT EXISTS album_year_idx
+ON album (COALESCE(CAST(strftime('%Y', release_date) AS INTEGER), release_year));
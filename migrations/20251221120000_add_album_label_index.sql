@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS album_label_idx ON album (label COLLATE NOCASE);
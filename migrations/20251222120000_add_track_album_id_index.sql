@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS track_album_id_idx ON track (album_id);
@@ -0,0 +1 @@
+ALTER TABLE playlist ADD COLUMN image_hash TEXT;
@@ -0,0 +1 @@
+ALTER TABLE playlist ADD COLUMN description TEXT;
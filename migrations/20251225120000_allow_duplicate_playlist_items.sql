@@ -0,0 +1,3 @@
+-- a track can be added to a playlist more than once, so the index can no longer be unique
+DROP INDEX IF EXISTS playlist_item_playlist_id_track_id;
+CREATE INDEX IF NOT EXISTS playlist_item_playlist_id_track_id ON playlist_item(playlist_id, track_id);
@@ -0,0 +1,3 @@
+-- deleted playlists and removed items are kept for a while so that they can be restored
+ALTER TABLE playlist ADD COLUMN deleted_at DATETIME;
+ALTER TABLE playlist_item ADD COLUMN deleted_at DATETIME;
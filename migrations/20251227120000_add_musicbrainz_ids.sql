@@ -0,0 +1,5 @@
+-- release ids pasted in by the user are kept apart from the tagged id, which is part of how albums
+-- are matched during scans
+ALTER TABLE album ADD COLUMN mbid_linked TEXT;
+ALTER TABLE artist ADD COLUMN mbid TEXT;
+ALTER TABLE track ADD COLUMN mbid TEXT;
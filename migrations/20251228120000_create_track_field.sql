@@ -0,0 +1,10 @@
+-- tags that hummingbird doesn't otherwise read, such as MOOD or OCCASION
+CREATE TABLE IF NOT EXISTS track_field (
+    track_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    FOREIGN KEY (track_id) REFERENCES track(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS track_field_track_id ON track_field(track_id);
+CREATE INDEX IF NOT EXISTS track_field_name_value ON track_field(name, value);
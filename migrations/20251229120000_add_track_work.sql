@@ -0,0 +1,2 @@
+ALTER TABLE track ADD COLUMN work TEXT;
+ALTER TABLE track ADD COLUMN movement TEXT;
@@ -0,0 +1,33 @@
+CREATE TABLE IF NOT EXISTS podcast (
+    id INTEGER PRIMARY KEY,
+    url TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    description TEXT,
+    image_url TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    refreshed_at DATETIME
+);
+
+CREATE TABLE IF NOT EXISTS podcast_episode (
+    id INTEGER PRIMARY KEY,
+    podcast_id INTEGER NOT NULL,
+    -- the episode's guid in the feed, or its audio URL if it doesn't have one
+    guid TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT,
+    audio_url TEXT NOT NULL,
+    published_at DATETIME,
+    duration INTEGER,
+    -- where the episode was downloaded to, if it was
+    file_path TEXT,
+    -- where playback stopped, in seconds
+    position INTEGER NOT NULL DEFAULT 0,
+    listened BOOLEAN NOT NULL DEFAULT 0,
+    -- whether the episode has been seen since it was added, new episodes aren't
+    seen BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (podcast_id) REFERENCES podcast(id) ON DELETE CASCADE,
+    UNIQUE (podcast_id, guid)
+);
+
+CREATE INDEX IF NOT EXISTS podcast_episode_podcast_id ON podcast_episode(podcast_id);
+CREATE INDEX IF NOT EXISTS podcast_episode_file_path ON podcast_episode(file_path);
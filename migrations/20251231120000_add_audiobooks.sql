@@ -0,0 +1,11 @@
+-- tracks in the folders marked as holding audiobooks in the scan settings
+ALTER TABLE track ADD COLUMN audiobook BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS track_audiobook ON track(audiobook) WHERE audiobook = 1;
+
+-- where playback of each audiobook chapter was left, in seconds
+CREATE TABLE IF NOT EXISTS track_position (
+    track_id INTEGER PRIMARY KEY,
+    position INTEGER NOT NULL,
+    FOREIGN KEY (track_id) REFERENCES track(id) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- the audio format of each track, and whether its tags mark it as explicit
+ALTER TABLE track ADD COLUMN codec TEXT;
+ALTER TABLE track ADD COLUMN sample_rate INTEGER;
+ALTER TABLE track ADD COLUMN bit_depth INTEGER;
+ALTER TABLE track ADD COLUMN explicit BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- how each track entered the library, and whether its metadata is protected from rescans
+ALTER TABLE track ADD COLUMN source TEXT;
+ALTER TABLE track ADD COLUMN source_path TEXT;
+ALTER TABLE track ADD COLUMN modified INTEGER;
+ALTER TABLE track ADD COLUMN metadata_locked BOOLEAN NOT NULL DEFAULT 0;
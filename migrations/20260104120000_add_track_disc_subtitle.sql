@@ -0,0 +1,3 @@
+-- the title of the disc the track is on, and the number of discs in its release
+ALTER TABLE track ADD COLUMN disc_subtitle TEXT;
+ALTER TABLE track ADD COLUMN disc_total INTEGER;
@@ -0,0 +1,6 @@
+-- tracks of a file that's split by its embedded cue sheet, and where they start and end in it
+ALTER TABLE track ADD COLUMN cue_file TEXT;
+ALTER TABLE track ADD COLUMN cue_start REAL;
+ALTER TABLE track ADD COLUMN cue_end REAL;
+
+CREATE INDEX IF NOT EXISTS track_cue_file_idx ON track (cue_file);
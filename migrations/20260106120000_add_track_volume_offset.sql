@@ -0,0 +1,2 @@
+-- how much louder or quieter each track is played, in decibels, for badly mastered files
+ALTER TABLE track ADD COLUMN volume_offset REAL;
@@ -0,0 +1,6 @@
+SELECT id, title, bpm, musical_key, location FROM track
+WHERE analyzed_at IS NOT NULL
+    AND ($1 IS NULL OR bpm >= $1)
+    AND ($2 IS NULL OR bpm <= $2)
+    AND ($3 IS NULL OR musical_key = $3)
+ORDER BY bpm ASC, title ASC;
@@ -0,0 +1,4 @@
+SELECT id, location FROM track
+WHERE replaygain_track_gain IS NULL AND loudness_analyzed_at IS NULL
+ORDER BY id ASC
+LIMIT 1;
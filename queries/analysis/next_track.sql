@@ -0,0 +1,4 @@
+SELECT id, location FROM track
+WHERE analyzed_at IS NULL
+ORDER BY id ASC
+LIMIT 1;
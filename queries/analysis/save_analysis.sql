@@ -0,0 +1,3 @@
+UPDATE track SET bpm = $2, musical_key = $3, audio_start = $4, audio_end = $5,
+    analyzed_at = CURRENT_TIMESTAMP
+WHERE id = $1;
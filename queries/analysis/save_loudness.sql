@@ -0,0 +1,2 @@
+UPDATE track SET loudness = $2, true_peak = $3, loudness_analyzed_at = CURRENT_TIMESTAMP
+WHERE id = $1;
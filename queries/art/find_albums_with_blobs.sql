@@ -0,0 +1 @@
+SELECT id FROM album WHERE image IS NOT NULL;
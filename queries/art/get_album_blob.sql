@@ -0,0 +1 @@
+SELECT image FROM album WHERE id = $1;
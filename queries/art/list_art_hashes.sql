@@ -0,0 +1,5 @@
+SELECT DISTINCT image_hash FROM album WHERE image_hash IS NOT NULL
+UNION
+SELECT DISTINCT image_hash FROM artist WHERE image_hash IS NOT NULL
+UNION
+SELECT DISTINCT image_hash FROM playlist WHERE image_hash IS NOT NULL;
@@ -0,0 +1 @@
+UPDATE album SET image_hash = $1, image = NULL WHERE id = $2;
@@ -1 +1 @@
-SELECT image FROM album WHERE id = $1;
+SELECT image_hash, image FROM album WHERE id = $1;
@@ -0,0 +1,2 @@
+SELECT image_hash FROM artist
+WHERE id = $1;
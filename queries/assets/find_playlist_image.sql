@@ -0,0 +1,2 @@
+SELECT image_hash FROM playlist
+WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE track SET audiobook = 0 WHERE audiobook = 1;
@@ -0,0 +1 @@
+DELETE FROM track_position WHERE track_id = $1;
@@ -0,0 +1,3 @@
+SELECT track.id, COALESCE(track_position.position, 0) FROM track
+    LEFT JOIN track_position ON track_position.track_id = track.id
+    WHERE track.location = $1 AND track.audiobook = 1;
@@ -0,0 +1 @@
+SELECT audiobook FROM track WHERE location = $1;
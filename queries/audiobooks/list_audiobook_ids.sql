@@ -0,0 +1 @@
+SELECT id FROM track WHERE audiobook = 1;
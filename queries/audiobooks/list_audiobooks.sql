@@ -0,0 +1,25 @@
+-- books are grouped by their series, which comes from the SERIES tag or falls back to the author,
+-- and put in order by the SERIES-PART tag
+SELECT
+    album.id,
+    album.title,
+    artist.name AS author,
+    MAX(series_field.value) AS series,
+    MAX(part_field.value) AS series_part,
+    COUNT(DISTINCT track.id) AS chapter_count,
+    SUM(track.duration) AS duration,
+    COUNT(track_position.track_id) > 0 AS in_progress
+FROM album
+    JOIN track ON track.album_id = album.id
+    LEFT JOIN artist ON artist.id = album.artist_id
+    LEFT JOIN track_position ON track_position.track_id = track.id
+    LEFT JOIN track_field AS series_field
+        ON series_field.track_id = track.id AND series_field.name = 'SERIES'
+    LEFT JOIN track_field AS part_field
+        ON part_field.track_id = track.id AND part_field.name IN ('SERIES-PART', 'SERIESPART')
+WHERE track.audiobook = 1
+GROUP BY album.id
+ORDER BY
+    COALESCE(series, artist.name_sortable, artist.name) COLLATE NOCASE,
+    CAST(series_part AS REAL),
+    album.title_sortable COLLATE NOCASE;
@@ -0,0 +1 @@
+UPDATE track SET audiobook = 1 WHERE location LIKE $1 ESCAPE '\';
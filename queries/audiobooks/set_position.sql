@@ -0,0 +1,2 @@
+INSERT INTO track_position (track_id, position) VALUES ($1, $2)
+    ON CONFLICT (track_id) DO UPDATE SET position = excluded.position;
@@ -0,0 +1,5 @@
+SELECT
+    id,
+    name
+FROM playlist
+WHERE id = $1 AND deleted_at IS NULL;
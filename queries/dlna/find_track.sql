@@ -0,0 +1,13 @@
+SELECT
+    track.id,
+    track.title,
+    track.artist_names,
+    track.duration,
+    track.location,
+    track.album_id,
+    album.title AS album_title,
+    artist.name AS album_artist
+FROM track
+LEFT JOIN album ON track.album_id = album.id
+LEFT JOIN artist ON album.artist_id = artist.id
+WHERE track.id = $1 AND track.cue_file IS NULL;
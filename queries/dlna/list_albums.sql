@@ -0,0 +1,7 @@
+SELECT
+    album.id,
+    album.title,
+    artist.name AS artist
+FROM album
+LEFT JOIN artist ON album.artist_id = artist.id
+ORDER BY album.title_sortable COLLATE NOCASE ASC;
@@ -0,0 +1,16 @@
+SELECT
+    track.id,
+    track.title,
+    track.artist_names,
+    track.duration,
+    track.location,
+    track.album_id,
+    album.title AS album_title,
+    artist.name AS album_artist
+FROM playlist_item
+JOIN track ON playlist_item.track_id = track.id
+LEFT JOIN album ON track.album_id = album.id
+LEFT JOIN artist ON album.artist_id = artist.id
+WHERE playlist_item.playlist_id = $1 AND playlist_item.deleted_at IS NULL
+    AND track.cue_file IS NULL
+ORDER BY playlist_item.position ASC;
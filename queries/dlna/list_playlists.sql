@@ -0,0 +1,6 @@
+SELECT
+    id,
+    name
+FROM playlist
+WHERE deleted_at IS NULL
+ORDER BY type DESC, name COLLATE NOCASE ASC;
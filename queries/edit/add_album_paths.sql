@@ -0,0 +1,4 @@
+INSERT OR IGNORE INTO album_path (album_id, path, disc_num)
+    SELECT DISTINCT album_id, folder, IFNULL(disc_number, -1)
+    FROM track
+    WHERE album_id = $1 AND folder IS NOT NULL;
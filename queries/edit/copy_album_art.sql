@@ -0,0 +1,4 @@
+UPDATE album SET (image, thumb, image_hash) = (
+    SELECT image, thumb, image_hash FROM album WHERE id = $2
+)
+WHERE id = $1;
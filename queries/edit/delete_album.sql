@@ -0,0 +1 @@
+DELETE FROM album WHERE id = $1;
@@ -0,0 +1,12 @@
+SELECT
+    track.id,
+    track.location,
+    track.album_id,
+    track.genres,
+    album.release_date,
+    album.release_year,
+    artist.name AS album_artist
+FROM track
+LEFT JOIN album ON track.album_id = album.id
+LEFT JOIN artist ON album.artist_id = artist.id
+WHERE track.id = $1;
@@ -0,0 +1,9 @@
+-- albums by the removed artist ($2) that the kept artist ($1) has an album with the same title as,
+-- which can't be moved over without breaking album_title_artist_mbid
+SELECT removed.id, kept.id
+FROM album removed
+JOIN album kept
+    ON kept.title = removed.title
+    AND kept.mbid = removed.mbid
+    AND kept.artist_id = $1
+WHERE removed.artist_id = $2;
@@ -0,0 +1 @@
+UPDATE track SET metadata_locked = 1 WHERE album_id = $1;
@@ -0,0 +1,3 @@
+-- discs that are already stored in another folder for the album are left behind, and removed
+-- along with the album
+UPDATE OR IGNORE album_path SET album_id = $1 WHERE album_id = $2;
@@ -0,0 +1 @@
+UPDATE track SET album_id = $1 WHERE album_id = $2;
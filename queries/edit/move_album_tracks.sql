@@ -0,0 +1,2 @@
+UPDATE track SET album_id = $1, metadata_locked = 1
+WHERE album_id = $2 AND id IN (SELECT value FROM json_each($3));
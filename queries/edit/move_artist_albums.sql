@@ -0,0 +1 @@
+UPDATE album SET artist_id = $1 WHERE artist_id = $2;
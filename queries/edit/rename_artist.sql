@@ -0,0 +1,4 @@
+UPDATE artist
+    SET name = $2,
+        name_sortable = CASE WHEN name_sortable = name THEN $2 ELSE name_sortable END
+    WHERE id = $1;
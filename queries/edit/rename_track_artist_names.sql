@@ -0,0 +1 @@
+UPDATE track SET artist_names = $2 WHERE artist_names = $1;
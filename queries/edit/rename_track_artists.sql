@@ -0,0 +1,2 @@
+UPDATE track SET artist_names = (SELECT name FROM artist WHERE id = $1)
+    WHERE artist_names = (SELECT name FROM artist WHERE id = $2);
@@ -0,0 +1 @@
+UPDATE track SET metadata_locked = $2 WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE track SET volume_offset = $2 WHERE id = $1;
@@ -0,0 +1,5 @@
+INSERT INTO album (title, title_sortable, artist_id, release_date, release_year, label, catalog_number, isrc, image, thumb, image_hash)
+    SELECT $2, $2, artist_id, release_date, release_year, label, catalog_number, isrc, image, thumb, image_hash
+    FROM album
+    WHERE id = $1
+    RETURNING id;
@@ -0,0 +1 @@
+UPDATE album SET artist_id = $2 WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE album SET release_year = $2, release_date = NULL WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE track SET genres = $2 WHERE id = $1;
@@ -0,0 +1,2 @@
+SELECT DISTINCT genres FROM track
+    WHERE genres IS NOT NULL;
@@ -0,0 +1,2 @@
+UPDATE track SET genres = $2
+    WHERE genres = $1;
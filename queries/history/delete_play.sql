@@ -0,0 +1,2 @@
+DELETE FROM play_history
+    WHERE id = $1;
@@ -0,0 +1,8 @@
+-- albums that have been played, but not in over a year. datetime() is used so that plays recorded
+-- with and without a timezone compare correctly
+SELECT track.album_id
+    FROM play_history
+    JOIN track ON track.id = play_history.track_id
+    WHERE track.album_id IS NOT NULL
+    GROUP BY track.album_id
+    HAVING MAX(datetime(play_history.played_at)) < datetime('now', '-1 year');
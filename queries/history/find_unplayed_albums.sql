@@ -0,0 +1,8 @@
+SELECT album.id
+    FROM album
+    WHERE NOT EXISTS (
+        SELECT 1
+            FROM track
+            JOIN play_history ON play_history.track_id = track.id
+            WHERE track.album_id = album.id
+    );
@@ -0,0 +1,6 @@
+-- the number of plays of the album's tracks divided by the number of tracks, so that listening to
+-- every track once counts as playing the album once
+SELECT COALESCE(COUNT(play_history.id) / COUNT(DISTINCT track.id), 0)
+    FROM track
+    LEFT JOIN play_history ON play_history.track_id = track.id
+    WHERE track.album_id = $1;
@@ -0,0 +1,3 @@
+SELECT COUNT(*) as play_count, MAX(played_at) as last_played
+    FROM play_history
+    WHERE track_id = $1;
@@ -0,0 +1,11 @@
+-- the most recent plays first
+SELECT play_history.id,
+       play_history.track_id,
+       play_history.played_at,
+       track.title,
+       track.artist_names,
+       track.duration
+    FROM play_history
+    JOIN track ON track.id = play_history.track_id
+    ORDER BY datetime(play_history.played_at) DESC, play_history.id DESC
+    LIMIT $1;
@@ -0,0 +1,2 @@
+INSERT INTO play_history (track_id, played_at)
+    VALUES ($1, $2);
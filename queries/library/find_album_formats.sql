@@ -0,0 +1,12 @@
+-- $1 is a JSON array of album ids. hi_res matches is_hi_res in types.rs.
+SELECT
+    album_id,
+    CASE WHEN COUNT(codec) = COUNT(*) AND COUNT(DISTINCT codec) = 1 THEN MAX(codec) END AS codec,
+    COALESCE(MIN(bit_depth > 16 OR sample_rate > 48000), 0) AS hi_res,
+    MAX(explicit) AS explicit
+FROM
+    track
+WHERE
+    album_id IN (SELECT value FROM json_each($1))
+GROUP BY
+    album_id;
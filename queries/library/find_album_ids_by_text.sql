@@ -0,0 +1,5 @@
+SELECT album.id
+    FROM album
+    LEFT JOIN artist ON album.artist_id = artist.id
+    WHERE instr(lower(album.title), lower($1)) > 0
+        OR instr(lower(artist.name), lower($1)) > 0;
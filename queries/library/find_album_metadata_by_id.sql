@@ -0,0 +1,3 @@
+SELECT id, title, title_sortable, artist_id, release_date, release_year, created_at, image_hash, label, catalog_number, isrc, mbid, mbid_linked
+FROM album
+WHERE id = $1;
@@ -0,0 +1,15 @@
+SELECT
+    album.id,
+    album.title,
+    album.title_sortable,
+    artist.name AS artist_name,
+    artist.name_sortable AS artist_name_sortable,
+    COALESCE(CAST(strftime('%Y', album.release_date) AS INTEGER), album.release_year) AS year,
+    COUNT(track.id) AS track_count,
+    COALESCE(SUM(track.duration), 0) AS duration
+FROM
+    album
+    LEFT JOIN artist ON album.artist_id = artist.id
+    LEFT JOIN track ON track.album_id = album.id
+GROUP BY
+    album.id;
@@ -0,0 +1,5 @@
+SELECT id, title, title_sortable, artist_id, release_date, release_year, created_at, image_hash, label, catalog_number, isrc
+FROM album
+WHERE artist_id = $1
+ORDER BY COALESCE(CAST(strftime('%Y', release_date) AS INTEGER), release_year) ASC,
+    title_sortable COLLATE NOCASE ASC;
@@ -0,0 +1,6 @@
+SELECT id, title, title_sortable, artist_id, release_date, release_year, created_at, image_hash, label, catalog_number, isrc
+FROM album
+WHERE label = (SELECT label FROM album WHERE id = $1) COLLATE NOCASE
+ORDER BY catalog_number IS NULL, catalog_number COLLATE NOCASE ASC,
+    COALESCE(CAST(strftime('%Y', release_date) AS INTEGER), release_year) ASC,
+    title_sortable COLLATE NOCASE ASC;
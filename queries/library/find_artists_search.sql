@@ -0,0 +1,2 @@
+SELECT id, name, image_hash FROM artist
+WHERE EXISTS (SELECT 1 FROM album WHERE album.artist_id = artist.id);
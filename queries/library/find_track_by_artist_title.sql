@@ -0,0 +1,11 @@
+/*
+ Finds a track by its title and artist, ignoring case. The artist may either be the track's
+ artists or the album's artist.
+ */
+
+SELECT t.id FROM track t
+    LEFT JOIN album a ON t.album_id = a.id
+    LEFT JOIN artist art ON a.artist_id = art.id
+    WHERE t.title = $1 COLLATE NOCASE
+        AND (t.artist_names = $2 COLLATE NOCASE OR art.name = $2 COLLATE NOCASE)
+    LIMIT 1;
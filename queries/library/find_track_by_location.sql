@@ -0,0 +1,2 @@
+SELECT * FROM track
+WHERE location = $1;
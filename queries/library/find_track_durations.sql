@@ -0,0 +1,4 @@
+-- $1 is a JSON array of track ids
+SELECT id, duration
+    FROM track
+    WHERE id IN (SELECT value FROM json_each($1));
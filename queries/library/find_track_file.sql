@@ -0,0 +1,2 @@
+SELECT cue_file, cue_start, cue_end FROM track
+WHERE location = $1 AND cue_file IS NOT NULL AND cue_start IS NOT NULL AND cue_end IS NOT NULL;
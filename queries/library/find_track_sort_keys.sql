@@ -0,0 +1,11 @@
+-- $1 is a JSON array of track ids
+SELECT
+    track.id,
+    track.title,
+    track.artist_names,
+    track.duration,
+    (SELECT COUNT(*) FROM play_history WHERE play_history.track_id = track.id) AS play_count
+FROM
+    track
+WHERE
+    track.id IN (SELECT value FROM json_each($1));
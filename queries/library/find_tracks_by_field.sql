@@ -0,0 +1,5 @@
+SELECT track.id, track.title, track_field.value, track.location FROM track_field
+    JOIN track ON track.id = track_field.track_id
+WHERE track_field.name = $1
+    AND ($2 IS NULL OR track_field.value = $2 COLLATE NOCASE)
+ORDER BY track_field.value ASC, track.title_sortable ASC;
@@ -0,0 +1,2 @@
+UPDATE album SET mbid_linked = $2
+    WHERE id = $1;
@@ -0,0 +1,5 @@
+SELECT COALESCE(CAST(strftime('%Y', release_date) AS INTEGER), release_year) AS year, COUNT(*)
+FROM album
+WHERE COALESCE(CAST(strftime('%Y', release_date) AS INTEGER), release_year) IS NOT NULL
+GROUP BY COALESCE(CAST(strftime('%Y', release_date) AS INTEGER), release_year)
+ORDER BY year DESC;
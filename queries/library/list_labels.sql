@@ -0,0 +1,5 @@
+SELECT label, MIN(id), COUNT(*)
+FROM album
+WHERE label IS NOT NULL AND label != ''
+GROUP BY label COLLATE NOCASE
+ORDER BY label COLLATE NOCASE ASC;
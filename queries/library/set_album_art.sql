@@ -0,0 +1,2 @@
+UPDATE album SET image = NULL, image_hash = $2, thumb = $3
+    WHERE id = $1;
@@ -0,0 +1,5 @@
+UPDATE artist
+SET bio = COALESCE($2, bio),
+    image_hash = COALESCE($3, image_hash),
+    info_fetched_at = CURRENT_TIMESTAMP
+WHERE id = $1;
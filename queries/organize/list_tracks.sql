@@ -0,0 +1,19 @@
+SELECT
+    track.id,
+    track.location,
+    track.title,
+    track.track_number,
+    track.disc_number,
+    track.genres,
+    track.artist_names,
+    track.album_id,
+    album.title AS album_title,
+    album.release_date,
+    album.release_year,
+    artist.name AS album_artist
+FROM track
+LEFT JOIN album ON track.album_id = album.id
+LEFT JOIN artist ON album.artist_id = artist.id
+-- tracks split from a cue sheet share one file with the sheet, so they can't be moved one by one
+WHERE track.cue_file IS NULL
+ORDER BY track.location ASC;
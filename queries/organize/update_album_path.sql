@@ -0,0 +1,2 @@
+UPDATE album_path SET path = $4
+WHERE album_id = $1 AND disc_num = $2 AND path = $3;
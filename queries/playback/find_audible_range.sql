@@ -0,0 +1,2 @@
+SELECT audio_start, audio_end FROM track
+WHERE location = $1 AND audio_start IS NOT NULL AND audio_end IS NOT NULL;
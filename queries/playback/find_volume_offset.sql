@@ -0,0 +1,2 @@
+SELECT volume_offset FROM track
+WHERE location = $1 AND volume_offset IS NOT NULL;
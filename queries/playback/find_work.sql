@@ -0,0 +1,2 @@
+SELECT album_id, work FROM track
+WHERE location = $1 AND work IS NOT NULL;
@@ -0,0 +1,5 @@
+SELECT
+    (SELECT COUNT(*) FROM playlist WHERE deleted_at IS NOT NULL)
+    + (SELECT COUNT(*) FROM playlist_item AS pi
+        JOIN playlist AS p ON pi.playlist_id = p.id
+        WHERE pi.deleted_at IS NOT NULL AND p.deleted_at IS NULL);
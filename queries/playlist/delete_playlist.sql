@@ -1,2 +1 @@
-DELETE FROM playlist_item WHERE playlist_id = $1;
-DELETE FROM playlist WHERE id = $1;
+UPDATE playlist SET deleted_at = CURRENT_TIMESTAMP WHERE id = $1;
@@ -1 +1,4 @@
-SELECT playlist.*, COUNT(playlist_item.id) as track_count FROM playlist LEFT JOIN playlist_item ON playlist.id = playlist_item.playlist_id GROUP BY playlist.id;
+SELECT playlist.*, COUNT(playlist_item.id) as track_count FROM playlist
+    LEFT JOIN playlist_item ON playlist.id = playlist_item.playlist_id AND playlist_item.deleted_at IS NULL
+    WHERE playlist.deleted_at IS NULL
+    GROUP BY playlist.id;
@@ -1 +1 @@
-SELECT * FROM playlist WHERE id = $1;
+SELECT * FROM playlist WHERE id = $1 AND deleted_at IS NULL;
@@ -1,4 +1,4 @@
 SELECT t.location FROM playlist_item AS pi
     JOIN track AS t ON pi.track_id = t.id
-    WHERE pi.playlist_id = $1
+    WHERE pi.playlist_id = $1 AND pi.deleted_at IS NULL
     ORDER BY pi.position ASC;
@@ -1,4 +1,4 @@
 SELECT pl.id, pl.track_id, t.album_id FROM playlist_item as pl
     JOIN track t on pl.track_id = t.id
-    WHERE pl.playlist_id = $1
+    WHERE pl.playlist_id = $1 AND pl.deleted_at IS NULL
     ORDER BY pl.position ASC;
@@ -0,0 +1,10 @@
+-- the art of the first albums to appear in the playlist, in playlist order
+SELECT album.image_hash
+    FROM playlist_item
+    JOIN track ON track.id = playlist_item.track_id
+    JOIN album ON album.id = track.album_id
+    WHERE playlist_item.playlist_id = $1 AND playlist_item.deleted_at IS NULL
+        AND album.image_hash IS NOT NULL
+    GROUP BY album.id
+    ORDER BY MIN(playlist_item.position) ASC
+    LIMIT $2;
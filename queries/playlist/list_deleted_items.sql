@@ -0,0 +1,7 @@
+-- items of deleted playlists come back with the playlist, so they aren't listed on their own
+SELECT pi.id, pi.playlist_id, p.name AS playlist_name, t.title AS track_title, pi.deleted_at
+    FROM playlist_item AS pi
+    JOIN playlist AS p ON pi.playlist_id = p.id
+    JOIN track AS t ON pi.track_id = t.id
+    WHERE pi.deleted_at IS NOT NULL AND p.deleted_at IS NULL
+    ORDER BY pi.deleted_at DESC, pi.position ASC;
@@ -3,5 +3,5 @@ SELECT t.location AS location, t.duration AS duration, t.artist_names AS track_a
     JOIN track t ON pl.track_id = t.id
     JOIN album a ON t.album_id = a.id
     JOIN artist art ON a.artist_id = art.id
-    WHERE pl.playlist_id = $1
+    WHERE pl.playlist_id = $1 AND pl.deleted_at IS NULL
     ORDER BY pl.position;
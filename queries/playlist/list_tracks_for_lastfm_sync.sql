@@ -0,0 +1,7 @@
+SELECT pl.id AS item_id, COALESCE(t.artist_names, art.name) AS artist, t.title AS title
+    FROM playlist_item AS pl
+    JOIN track t ON pl.track_id = t.id
+    LEFT JOIN album a ON t.album_id = a.id
+    LEFT JOIN artist art ON a.artist_id = art.id
+    WHERE pl.playlist_id = $1 AND pl.deleted_at IS NULL
+        AND COALESCE(t.artist_names, art.name) IS NOT NULL;
@@ -1 +1 @@
-SELECT id FROM playlist_item WHERE playlist_id = $1 AND track_id = $2;
+SELECT id FROM playlist_item WHERE playlist_id = $1 AND track_id = $2 AND deleted_at IS NULL LIMIT 1;
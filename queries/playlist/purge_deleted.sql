@@ -0,0 +1,4 @@
+DELETE FROM playlist_item WHERE deleted_at < datetime('now', '-30 days') OR playlist_id IN (
+    SELECT id FROM playlist WHERE deleted_at < datetime('now', '-30 days')
+);
+DELETE FROM playlist WHERE deleted_at < datetime('now', '-30 days');
@@ -0,0 +1,5 @@
+-- playlist names are unique, so a deleted playlist has to make way for a new one with its name
+DELETE FROM playlist_item WHERE playlist_id IN (
+    SELECT id FROM playlist WHERE name = $1 AND deleted_at IS NOT NULL
+);
+DELETE FROM playlist WHERE name = $1 AND deleted_at IS NOT NULL;
@@ -0,0 +1,7 @@
+UPDATE playlist_item SET deleted_at = CURRENT_TIMESTAMP WHERE id IN (
+    SELECT id FROM (
+        SELECT id, ROW_NUMBER() OVER (PARTITION BY track_id ORDER BY position ASC) AS n
+            FROM playlist_item
+            WHERE playlist_id = $1 AND deleted_at IS NULL
+    ) WHERE n > 1
+);
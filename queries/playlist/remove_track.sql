@@ -1,2 +1 @@
-UPDATE playlist_item SET position = position - 1 WHERE position > $1;
-DELETE FROM playlist_item WHERE id = $2
+UPDATE playlist_item SET deleted_at = CURRENT_TIMESTAMP WHERE id = $1;
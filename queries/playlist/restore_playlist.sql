@@ -0,0 +1 @@
+UPDATE playlist SET deleted_at = NULL WHERE id = $1;
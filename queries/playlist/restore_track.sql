@@ -0,0 +1 @@
+UPDATE playlist_item SET deleted_at = NULL WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE playlist SET description = $2 WHERE id = $1;
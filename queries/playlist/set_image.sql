@@ -0,0 +1 @@
+UPDATE playlist SET image_hash = $2 WHERE id = $1;
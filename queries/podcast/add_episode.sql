@@ -0,0 +1,8 @@
+INSERT INTO podcast_episode (podcast_id, guid, title, description, audio_url, published_at, duration, seen)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    ON CONFLICT (podcast_id, guid) DO UPDATE SET
+        title = excluded.title,
+        description = excluded.description,
+        audio_url = excluded.audio_url,
+        published_at = excluded.published_at,
+        duration = excluded.duration;
@@ -0,0 +1 @@
+SELECT COUNT(*) FROM podcast_episode WHERE seen = 0;
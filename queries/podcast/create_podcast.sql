@@ -0,0 +1,7 @@
+INSERT INTO podcast (url, title, description, image_url)
+    VALUES ($1, $2, $3, $4)
+    ON CONFLICT (url) DO UPDATE SET
+        title = excluded.title,
+        description = excluded.description,
+        image_url = excluded.image_url
+    RETURNING id;
@@ -0,0 +1 @@
+DELETE FROM podcast WHERE id = $1;
@@ -0,0 +1 @@
+SELECT * FROM podcast_episode WHERE file_path = $1;
@@ -0,0 +1 @@
+SELECT * FROM podcast_episode WHERE id = $1;
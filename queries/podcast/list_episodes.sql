@@ -0,0 +1,3 @@
+SELECT * FROM podcast_episode
+    WHERE podcast_id = $1
+    ORDER BY published_at DESC, id DESC;
@@ -0,0 +1,4 @@
+SELECT podcast.*, COUNT(podcast_episode.id) AS new_count FROM podcast
+    LEFT JOIN podcast_episode ON podcast_episode.podcast_id = podcast.id AND podcast_episode.seen = 0
+    GROUP BY podcast.id
+    ORDER BY podcast.title COLLATE NOCASE;
@@ -0,0 +1 @@
+UPDATE podcast_episode SET seen = 1 WHERE podcast_id = $1 AND seen = 0;
@@ -0,0 +1 @@
+UPDATE podcast_episode SET file_path = $2 WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE podcast_episode SET listened = $2, position = 0 WHERE id = $1;
@@ -0,0 +1 @@
+UPDATE podcast_episode SET position = $2 WHERE id = $1;
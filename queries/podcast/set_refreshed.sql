@@ -0,0 +1 @@
+UPDATE podcast SET refreshed_at = CURRENT_TIMESTAMP WHERE id = $1;
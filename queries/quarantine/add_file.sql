@@ -0,0 +1,3 @@
+INSERT INTO quarantine (location, error)
+    VALUES ($1, $2)
+    ON CONFLICT (location) DO UPDATE SET error = $2, quarantined_at = CURRENT_TIMESTAMP;
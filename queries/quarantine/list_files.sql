@@ -0,0 +1 @@
+SELECT location, error, quarantined_at FROM quarantine ORDER BY location ASC;
@@ -0,0 +1 @@
+DELETE FROM quarantine WHERE location = $1;
@@ -0,0 +1,13 @@
+SELECT
+    track.id,
+    track.location,
+    track.album_id,
+    album.artist_id AS album_artist_id,
+    track.artist_names,
+    track.genres,
+    track.bpm,
+    track.musical_key,
+    track.audiobook
+FROM
+    track
+    LEFT JOIN album ON track.album_id = album.id;
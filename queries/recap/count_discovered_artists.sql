@@ -0,0 +1,12 @@
+-- the album artists that were first listened to during the year
+SELECT COUNT(*)
+    FROM (
+        SELECT album.artist_id
+            FROM play_history
+            JOIN track ON track.id = play_history.track_id
+            JOIN album ON album.id = track.album_id
+            WHERE album.artist_id IS NOT NULL
+                AND track.audiobook = 0
+            GROUP BY album.artist_id
+            HAVING strftime('%Y', MIN(datetime(play_history.played_at))) = $1
+    );
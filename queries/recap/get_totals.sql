@@ -0,0 +1,6 @@
+SELECT COUNT(*) AS play_count,
+       COALESCE(SUM(track.duration), 0) AS total_duration
+    FROM play_history
+    JOIN track ON track.id = play_history.track_id
+    WHERE strftime('%Y', datetime(play_history.played_at)) = $1
+        AND track.audiobook = 0;
@@ -0,0 +1,12 @@
+SELECT album.title AS name,
+       artist.name AS detail,
+       COUNT(*) AS play_count
+    FROM play_history
+    JOIN track ON track.id = play_history.track_id
+    JOIN album ON album.id = track.album_id
+    LEFT JOIN artist ON artist.id = album.artist_id
+    WHERE strftime('%Y', datetime(play_history.played_at)) = $1
+        AND track.audiobook = 0
+    GROUP BY album.id
+    ORDER BY play_count DESC, album.title_sortable
+    LIMIT $2;
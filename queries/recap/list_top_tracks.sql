@@ -0,0 +1,10 @@
+SELECT track.title AS name,
+       track.artist_names AS detail,
+       COUNT(*) AS play_count
+    FROM play_history
+    JOIN track ON track.id = play_history.track_id
+    WHERE strftime('%Y', datetime(play_history.played_at)) = $1
+        AND track.audiobook = 0
+    GROUP BY track.id
+    ORDER BY play_count DESC, track.title_sortable
+    LIMIT $2;
@@ -0,0 +1,6 @@
+-- the years with at least one play, most recent first
+SELECT DISTINCT CAST(strftime('%Y', datetime(play_history.played_at)) AS INTEGER) AS year
+    FROM play_history
+    JOIN track ON track.id = play_history.track_id
+    WHERE track.audiobook = 0
+    ORDER BY year DESC;
@@ -0,0 +1,3 @@
+SELECT album.title, track.title, track.duration, track.audiobook FROM track
+    JOIN album ON album.id = track.album_id
+    WHERE track.location = $1;
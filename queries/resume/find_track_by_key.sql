@@ -0,0 +1,5 @@
+SELECT track.id, track.location, track.album_id FROM track
+    JOIN album ON album.id = track.album_id
+    WHERE album.title = $1 AND track.title = $2
+    ORDER BY track.id
+    LIMIT 1;
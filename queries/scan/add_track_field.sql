@@ -0,0 +1,2 @@
+INSERT INTO track_field (track_id, name, value)
+    VALUES ($1, $2, $3);
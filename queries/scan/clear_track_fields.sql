@@ -0,0 +1 @@
+DELETE FROM track_field WHERE track_id = $1;
@@ -1,10 +1,11 @@
-INSERT INTO album (title, title_sortable, artist_id, image, thumb, release_date, release_year, label, catalog_number, isrc, mbid)
+INSERT INTO album (title, title_sortable, artist_id, image_hash, thumb, release_date, release_year, label, catalog_number, isrc, mbid)
     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
     ON CONFLICT (title, artist_id, mbid) DO UPDATE SET
         title = EXCLUDED.title,
         title_sortable = EXCLUDED.title_sortable,
         artist_id = EXCLUDED.artist_id,
-        image = EXCLUDED.image,
+        image = NULL,
+        image_hash = EXCLUDED.image_hash,
         thumb = EXCLUDED.thumb,
         release_date = EXCLUDED.release_date,
         release_year = EXCLUDED.release_year,
@@ -1,5 +1,5 @@
-INSERT INTO track (title, title_sortable, album_id, track_number, disc_number, duration, location, genres, artist_names, folder)
-    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+INSERT INTO track (title, title_sortable, album_id, track_number, disc_number, duration, location, genres, artist_names, folder, replaygain_track_gain, replaygain_track_peak, mbid, work, movement, codec, sample_rate, bit_depth, explicit, source, source_path, modified, disc_subtitle, disc_total, cue_file, cue_start, cue_end)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27)
     ON CONFLICT (location) DO UPDATE SET
         title = EXCLUDED.title,
         title_sortable = EXCLUDED.title_sortable,
@@ -10,5 +10,24 @@ INSERT INTO track (title, title_sortable, album_id, track_number, disc_number, d
         location = EXCLUDED.location,
         genres = EXCLUDED.genres,
         artist_names = EXCLUDED.artist_names,
-        folder = EXCLUDED.folder
+        folder = EXCLUDED.folder,
+        replaygain_track_gain = EXCLUDED.replaygain_track_gain,
+        replaygain_track_peak = EXCLUDED.replaygain_track_peak,
+        mbid = EXCLUDED.mbid,
+        work = EXCLUDED.work,
+        movement = EXCLUDED.movement,
+        codec = EXCLUDED.codec,
+        sample_rate = EXCLUDED.sample_rate,
+        bit_depth = EXCLUDED.bit_depth,
+        explicit = EXCLUDED.explicit,
+        disc_subtitle = EXCLUDED.disc_subtitle,
+        disc_total = EXCLUDED.disc_total,
+        cue_file = EXCLUDED.cue_file,
+        cue_start = EXCLUDED.cue_start,
+        cue_end = EXCLUDED.cue_end,
+        source = COALESCE(source, EXCLUDED.source),
+        source_path = COALESCE(source_path, EXCLUDED.source_path),
+        modified = EXCLUDED.modified,
+        analyzed_at = NULL,
+        loudness_analyzed_at = NULL
     RETURNING id;
@@ -0,0 +1,2 @@
+DELETE FROM track
+WHERE (location = $1 OR cue_file = $1) AND location NOT IN (SELECT value FROM json_each($2));
@@ -1 +1 @@
-DELETE FROM track WHERE location = $1;
+DELETE FROM track WHERE location = $1 OR cue_file = $1;
@@ -0,0 +1 @@
+SELECT metadata_locked, modified FROM track WHERE location = $1;
@@ -0,0 +1,2 @@
+UPDATE artist SET mbid = $2
+    WHERE id = $1 AND mbid IS NULL;
@@ -0,0 +1 @@
+UPDATE track SET modified = $2 WHERE location = $1;
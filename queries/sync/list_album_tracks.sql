@@ -0,0 +1,18 @@
+SELECT
+    track.id,
+    track.location,
+    track.title,
+    track.track_number,
+    track.disc_number,
+    track.genres,
+    track.artist_names,
+    track.album_id,
+    album.title AS album_title,
+    album.release_date,
+    album.release_year,
+    artist.name AS album_artist
+FROM track
+LEFT JOIN album ON track.album_id = album.id
+LEFT JOIN artist ON album.artist_id = artist.id
+WHERE track.album_id = $1
+ORDER BY track.disc_number ASC, track.track_number ASC;
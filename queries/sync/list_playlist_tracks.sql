@@ -0,0 +1,19 @@
+SELECT
+    track.id,
+    track.location,
+    track.title,
+    track.track_number,
+    track.disc_number,
+    track.genres,
+    track.artist_names,
+    track.album_id,
+    album.title AS album_title,
+    album.release_date,
+    album.release_year,
+    artist.name AS album_artist
+FROM playlist_item
+JOIN track ON playlist_item.track_id = track.id
+LEFT JOIN album ON track.album_id = album.id
+LEFT JOIN artist ON album.artist_id = artist.id
+WHERE playlist_item.playlist_id = $1 AND playlist_item.deleted_at IS NULL
+ORDER BY playlist_item.position ASC;
@@ -0,0 +1,2 @@
+INSERT INTO track_tag (track_id, tag_id) VALUES ($1, $2)
+    ON CONFLICT (track_id, tag_id) DO NOTHING;
@@ -0,0 +1,3 @@
+INSERT INTO tag (name) VALUES ($1)
+    ON CONFLICT (name) DO UPDATE SET name = tag.name
+    RETURNING id;
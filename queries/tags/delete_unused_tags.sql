@@ -0,0 +1 @@
+DELETE FROM tag WHERE id NOT IN (SELECT tag_id FROM track_tag);
@@ -0,0 +1,5 @@
+SELECT tag.id, tag.name, COUNT(track_tag.track_id) AS track_count
+FROM tag
+    LEFT JOIN track_tag ON track_tag.tag_id = tag.id
+WHERE tag.id = $1
+GROUP BY tag.id;
@@ -0,0 +1,8 @@
+SELECT track.id
+FROM track_tag
+    JOIN track ON track.id = track_tag.track_id
+    LEFT JOIN album ON album.id = track.album_id
+    LEFT JOIN artist ON artist.id = album.artist_id
+WHERE track_tag.tag_id = $1
+ORDER BY artist.name_sortable COLLATE NOCASE ASC, album.title_sortable COLLATE NOCASE ASC,
+    track.disc_number ASC, track.track_number ASC;
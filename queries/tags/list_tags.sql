@@ -0,0 +1,5 @@
+SELECT tag.id, tag.name, COUNT(track_tag.track_id) AS track_count
+FROM tag
+    JOIN track_tag ON track_tag.tag_id = tag.id
+GROUP BY tag.id
+ORDER BY tag.name COLLATE NOCASE ASC;
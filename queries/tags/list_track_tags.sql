@@ -0,0 +1,6 @@
+SELECT tag.id, tag.name,
+    (SELECT COUNT(*) FROM track_tag AS tt WHERE tt.tag_id = tag.id) AS track_count
+FROM tag
+    JOIN track_tag ON track_tag.tag_id = tag.id
+WHERE track_tag.track_id = $1
+ORDER BY tag.name COLLATE NOCASE ASC;
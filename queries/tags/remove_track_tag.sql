@@ -0,0 +1 @@
+DELETE FROM track_tag WHERE track_id = $1 AND tag_id = $2;
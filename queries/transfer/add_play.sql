@@ -0,0 +1,6 @@
+-- plays that were imported before (or recorded here and exported) aren't counted twice
+INSERT INTO play_history (track_id, played_at)
+    SELECT $1, $2
+    WHERE NOT EXISTS (
+        SELECT 1 FROM play_history WHERE track_id = $1 AND datetime(played_at) = datetime($2)
+    );
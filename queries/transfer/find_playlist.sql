@@ -0,0 +1 @@
+SELECT id FROM playlist WHERE name = $1 AND deleted_at IS NULL;
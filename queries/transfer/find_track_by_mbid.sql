@@ -0,0 +1 @@
+SELECT id FROM track WHERE mbid = $1 ORDER BY id LIMIT 1;
@@ -0,0 +1,3 @@
+SELECT track_id FROM playlist_item
+    WHERE playlist_id = $1 AND deleted_at IS NULL
+    ORDER BY position;
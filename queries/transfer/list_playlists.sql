@@ -0,0 +1 @@
+SELECT id, name, description FROM playlist WHERE deleted_at IS NULL ORDER BY id;
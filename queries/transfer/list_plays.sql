@@ -0,0 +1 @@
+SELECT track_id, played_at FROM play_history ORDER BY datetime(played_at), id;
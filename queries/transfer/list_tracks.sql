@@ -0,0 +1 @@
+SELECT id, location, mbid FROM track;
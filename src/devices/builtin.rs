@@ -1,4 +1,5 @@
 pub mod cpal;
 pub mod dummy;
+pub mod pcm;
 #[cfg(target_os = "windows")]
 pub mod win_audiograph;
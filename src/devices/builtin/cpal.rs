@@ -25,6 +25,9 @@ pub struct CpalProvider {
 
 impl Default for CpalProvider {
     fn default() -> Self {
+        #[cfg(target_os = "macos")]
+        default_output::watch();
+
         Self {
             host: cpal::default_host(),
         }
@@ -40,7 +43,7 @@ impl DeviceProvider for CpalProvider {
     fn get_devices(&mut self) -> Result<Vec<Box<dyn Device>>, ListError> {
         Ok(self
             .host
-            .devices()?
+            .output_devices()?
             .map(|dev| Box::new(CpalDevice::from(dev)) as Box<dyn Device>)
             .collect())
     }
@@ -59,6 +62,74 @@ impl DeviceProvider for CpalProvider {
             .ok_or(FindError::DeviceDoesNotExist)
             .map(|dev| Box::new(CpalDevice::from(dev)) as Box<dyn Device>)
     }
+
+    #[cfg(target_os = "macos")]
+    fn default_device_changed(&mut self) -> bool {
+        default_output::take_changed()
+    }
+}
+
+/// CoreAudio tells listeners when the default output device changes, which cpal doesn't pass on:
+/// its streams stay on the device they were opened on.
+#[cfg(target_os = "macos")]
+mod default_output {
+    use std::{
+        ffi::c_void,
+        ptr::{self, NonNull},
+        sync::{
+            Once,
+            atomic::{AtomicBool, Ordering},
+        },
+    };
+
+    use objc2_core_audio::{
+        AudioObjectAddPropertyListener, AudioObjectID, AudioObjectPropertyAddress,
+        kAudioHardwarePropertyDefaultOutputDevice, kAudioObjectPropertyElementMain,
+        kAudioObjectPropertyScopeGlobal, kAudioObjectSystemObject,
+    };
+    use tracing::warn;
+
+    static CHANGED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C-unwind" fn on_change(
+        _: AudioObjectID,
+        _: u32,
+        _: NonNull<AudioObjectPropertyAddress>,
+        _: *mut c_void,
+    ) -> i32 {
+        CHANGED.store(true, Ordering::Relaxed);
+        0
+    }
+
+    /// Starts listening for changes to the default output device, if that hasn't happened yet.
+    pub fn watch() {
+        static WATCHING: Once = Once::new();
+
+        WATCHING.call_once(|| {
+            let address = AudioObjectPropertyAddress {
+                mSelector: kAudioHardwarePropertyDefaultOutputDevice,
+                mScope: kAudioObjectPropertyScopeGlobal,
+                mElement: kAudioObjectPropertyElementMain,
+            };
+
+            let status = unsafe {
+                AudioObjectAddPropertyListener(
+                    kAudioObjectSystemObject as AudioObjectID,
+                    NonNull::from(&address),
+                    Some(on_change),
+                    ptr::null_mut(),
+                )
+            };
+
+            if status != 0 {
+                warn!("Failed to watch for default output device changes: {status}");
+            }
+        });
+    }
+
+    pub fn take_changed() -> bool {
+        CHANGED.swap(false, Ordering::Relaxed)
+    }
 }
 
 struct CpalDevice {
@@ -0,0 +1,426 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+    },
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use tracing::{debug, info, warn};
+
+use crate::{
+    devices::{
+        errors::{CloseError, InfoError, OpenError, ResetError, StateError, SubmissionError},
+        format::{BufferSize, ChannelSpec, FormatInfo, SampleFormat, SupportedFormat},
+        traits::{Device, OutputStream},
+        util::{Scale, interleave},
+    },
+    media::playback::{GetInnerSamples, PlaybackFrame},
+};
+
+/// The prefix used for the UIDs of PCM outputs, to tell them apart from the device provider's.
+pub const PCM_UID_PREFIX: &str = "pcm:";
+
+/// Snapcast's default sample format is 48000:16:2, so the output matches it unless told otherwise.
+pub const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+/// PCM outputs always write stereo audio. Tracks with more channels are reduced to their front
+/// left and right channels, and mono tracks are written to both.
+const CHANNELS: u16 = 2;
+
+/// How far ahead of real time audio is written. Readers like Snapcast expect audio to arrive at
+/// the rate it's played, and writing too far ahead would make pausing and seeking lag behind.
+const LEAD: Duration = Duration::from_millis(250);
+
+/// How long to wait before trying to reach a target that's gone away again.
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Targets are expected to be on the local network, so anything slower than this is unreachable.
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A slow reader shouldn't hold up playback (or every other client of a listening output).
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// An output that writes raw, interleaved signed 16-bit little-endian PCM somewhere other than a
+/// sound card, which is what multi-room audio servers like Snapcast take as input.
+///
+/// The target can be:
+/// - a path to a file or named pipe, such as Snapcast's `pipe` source (`/tmp/snapfifo`)
+/// - `tcp://host:port`, to connect to a server such as Snapcast's `tcp` source
+/// - `listen://address:port`, to accept connections and send the audio to every client
+pub struct PcmDevice {
+    name: String,
+    target: String,
+    sample_rate: u32,
+}
+
+impl PcmDevice {
+    pub fn new(name: String, target: String, sample_rate: u32) -> Self {
+        Self {
+            name,
+            target,
+            sample_rate,
+        }
+    }
+
+    fn format(&self, channels: ChannelSpec) -> FormatInfo {
+        FormatInfo {
+            originating_provider: "pcm",
+            sample_type: SampleFormat::Signed16,
+            sample_rate: self.sample_rate,
+            buffer_size: BufferSize::Unknown,
+            channels,
+            rate_channel_ratio: 2,
+            rate_channel_ratio_fixed: true,
+        }
+    }
+}
+
+impl Device for PcmDevice {
+    fn open_device(&mut self, format: FormatInfo) -> Result<Box<dyn OutputStream>, OpenError> {
+        if format.originating_provider != "pcm" {
+            return Err(OpenError::InvalidConfigProvider);
+        }
+        if format.sample_type != SampleFormat::Signed16 || format.sample_rate != self.sample_rate {
+            return Err(OpenError::InvalidSampleFormat);
+        }
+
+        info!("Opening PCM output {} ({})", self.name, self.target);
+        let sink = Sink::new(&self.target);
+
+        // any channel count is accepted, since they're mapped to stereo when written
+        Ok(Box::new(PcmStream {
+            format: self.format(format.channels),
+            sink,
+            volume: 1.0,
+            started: None,
+            written: 0,
+        }))
+    }
+
+    fn get_supported_formats(&self) -> Result<Vec<SupportedFormat>, InfoError> {
+        Ok(vec![SupportedFormat {
+            originating_provider: "pcm",
+            sample_type: SampleFormat::Signed16,
+            sample_rates: self.sample_rate..self.sample_rate,
+            buffer_size: BufferSize::Unknown,
+            channels: ChannelSpec::Count(CHANNELS),
+        }])
+    }
+
+    fn get_default_format(&self) -> Result<FormatInfo, InfoError> {
+        Ok(self.format(ChannelSpec::Count(CHANNELS)))
+    }
+
+    fn get_name(&self) -> Result<String, InfoError> {
+        Ok(self.name.clone())
+    }
+
+    fn get_uid(&self) -> Result<String, InfoError> {
+        Ok(format!("{PCM_UID_PREFIX}{}", self.name))
+    }
+
+    fn requires_matching_format(&self) -> bool {
+        true
+    }
+}
+
+enum Target {
+    File(PathBuf),
+    Connect(String),
+    Listen(String),
+}
+
+impl Target {
+    fn parse(target: &str) -> Self {
+        if let Some(address) = target.strip_prefix("tcp://") {
+            Target::Connect(address.to_string())
+        } else if let Some(address) = target.strip_prefix("listen://") {
+            Target::Listen(address.to_string())
+        } else {
+            Target::File(PathBuf::from(target))
+        }
+    }
+}
+
+enum Writer {
+    File(File),
+    Stream(TcpStream),
+    Clients {
+        clients: Arc<Mutex<Vec<TcpStream>>>,
+        address: SocketAddr,
+        /// Tells the listener thread to stop accepting clients.
+        closed: Arc<AtomicBool>,
+    },
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        let Writer::Clients {
+            address, closed, ..
+        } = self
+        else {
+            return;
+        };
+
+        // this has to be set before the listener is woken up, or it could take the connection
+        // below for a client and go back to waiting, keeping the address bound
+        closed.store(true, Ordering::SeqCst);
+
+        // wake the listener thread up so that it notices the output is gone
+        let mut address = *address;
+        if address.ip().is_unspecified() {
+            address.set_ip(Ipv4Addr::LOCALHOST.into());
+        }
+        TcpStream::connect(address).ok();
+    }
+}
+
+/// Where a [`PcmStream`] writes its audio. The target is opened when audio is first written, and
+/// if it goes away (a reader closing a pipe, or a server restarting) it's reopened by later
+/// writes, with the audio in between dropped. An unreachable target never fails the stream, since
+/// the playback thread can't recover from that.
+struct Sink {
+    target: Target,
+    writer: Option<Writer>,
+    /// A pipe that's being opened, which can take until something starts reading from it.
+    opening: Option<Receiver<std::io::Result<File>>>,
+    last_attempt: Option<Instant>,
+}
+
+impl Sink {
+    fn new(target: &str) -> Self {
+        Self {
+            target: Target::parse(target),
+            writer: None,
+            opening: None,
+            last_attempt: None,
+        }
+    }
+
+    fn open(&mut self) -> std::io::Result<()> {
+        match &self.target {
+            Target::File(path) => {
+                self.opening = Some(open_file(path.clone())?);
+            }
+            Target::Connect(address) => {
+                self.writer = Some(Writer::Stream(connect(address)?));
+            }
+            Target::Listen(address) => {
+                let listener = TcpListener::bind(address)?;
+                let address = listener.local_addr()?;
+                let clients = Arc::new(Mutex::new(Vec::new()));
+                let accepted = clients.clone();
+                let closed = Arc::new(AtomicBool::new(false));
+                let stop = closed.clone();
+
+                std::thread::Builder::new()
+                    .name("pcm_listener".to_string())
+                    .spawn(move || {
+                        for client in listener.incoming().flatten() {
+                            if stop.load(Ordering::SeqCst) {
+                                break;
+                            }
+
+                            info!("PCM client connected: {:?}", client.peer_addr());
+                            client.set_nodelay(true).ok();
+                            client.set_write_timeout(Some(WRITE_TIMEOUT)).ok();
+                            accepted.lock().unwrap().push(client);
+                        }
+                    })?;
+
+                info!("Listening for PCM clients on {address}");
+                self.writer = Some(Writer::Clients {
+                    clients,
+                    address,
+                    closed,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.writer.is_none()
+            && self.opening.is_none()
+            && self
+                .last_attempt
+                .is_none_or(|last_attempt| last_attempt.elapsed() > RETRY_INTERVAL)
+        {
+            self.last_attempt = Some(Instant::now());
+            if let Err(err) = self.open() {
+                warn!("Failed to open PCM output: {err}");
+            }
+        }
+
+        if let Some(receiver) = &self.opening {
+            match receiver.try_recv() {
+                Ok(Ok(file)) => {
+                    self.writer = Some(Writer::File(file));
+                    self.opening = None;
+                }
+                Ok(Err(err)) => {
+                    warn!("Failed to open PCM output: {err}");
+                    self.opening = None;
+                }
+                Err(TryRecvError::Disconnected) => self.opening = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+
+        let result = match &mut self.writer {
+            Some(Writer::File(file)) => file.write_all(bytes),
+            Some(Writer::Stream(stream)) => stream.write_all(bytes),
+            Some(Writer::Clients { clients, .. }) => {
+                clients.lock().unwrap().retain_mut(|client| {
+                    let result = client.write_all(bytes);
+                    if let Err(err) = &result {
+                        info!("PCM client disconnected: {err}");
+                    }
+                    result.is_ok()
+                });
+                Ok(())
+            }
+            None => Ok(()),
+        };
+
+        if let Err(err) = result {
+            warn!("Failed to write to PCM output: {err}");
+            self.writer = None;
+        }
+    }
+
+    fn close(&mut self) {
+        self.writer = None;
+        self.opening = None;
+    }
+}
+
+fn connect(address: &str) -> std::io::Result<TcpStream> {
+    let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "could not resolve the address",
+        )
+    })?;
+
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Opens a file for writing on another thread. If nothing is reading a named pipe this doesn't
+/// finish until something does, so the playback thread can't wait on it.
+fn open_file(path: PathBuf) -> std::io::Result<Receiver<std::io::Result<File>>> {
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::Builder::new()
+        .name("pcm_open".to_string())
+        .spawn(move || {
+            tx.send(OpenOptions::new().append(true).create(true).open(path))
+                .ok();
+        })?;
+
+    Ok(rx)
+}
+
+pub struct PcmStream {
+    format: FormatInfo,
+    sink: Sink,
+    volume: f64,
+    /// When the audio written so far started playing, in real time.
+    started: Option<Instant>,
+    /// The number of frames written since `started`.
+    written: u64,
+}
+
+impl PcmStream {
+    /// Sleeps until the audio written so far is no more than [`LEAD`] ahead of real time.
+    fn pace(&mut self, frames: usize) {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        self.written += frames as u64;
+
+        let written = Duration::from_secs_f64(self.written as f64 / self.format.sample_rate as f64);
+        let elapsed = started.elapsed();
+
+        if written > elapsed + LEAD {
+            sleep(written - elapsed - LEAD);
+        } else if elapsed > written + LEAD {
+            // the playback thread fell behind (or the track was paused), so catching up would
+            // just send a burst of audio
+            debug!("PCM output fell behind, resynchronizing");
+            self.started = Some(Instant::now() - written);
+        }
+    }
+}
+
+impl OutputStream for PcmStream {
+    fn submit_frame(&mut self, frame: PlaybackFrame) -> Result<(), SubmissionError> {
+        let samples = if self.volume > 0.98 {
+            i16::inner(frame.samples)
+        } else {
+            i16::inner(frame.samples).scale(self.volume)
+        };
+
+        let Some(length) = samples.first().map(|channel| channel.len()) else {
+            return Ok(());
+        };
+
+        let stereo = match samples.len() {
+            1 => vec![samples[0].clone(), samples[0].clone()],
+            _ => samples.into_iter().take(CHANNELS as usize).collect(),
+        };
+
+        let bytes: Vec<u8> = interleave(stereo)
+            .into_iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+
+        self.sink.write(&bytes);
+        self.pace(length);
+
+        Ok(())
+    }
+
+    fn close_stream(&mut self) -> Result<(), CloseError> {
+        self.sink.close();
+        Ok(())
+    }
+
+    fn needs_input(&self) -> bool {
+        true
+    }
+
+    fn get_current_format(&self) -> Result<&FormatInfo, InfoError> {
+        Ok(&self.format)
+    }
+
+    fn play(&mut self) -> Result<(), StateError> {
+        self.started = None;
+        self.written = 0;
+        Ok(())
+    }
+
+    fn pause(&mut self) -> Result<(), StateError> {
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), ResetError> {
+        self.started = None;
+        self.written = 0;
+        Ok(())
+    }
+
+    fn set_volume(&mut self, volume: f64) -> Result<(), StateError> {
+        self.volume = volume;
+        Ok(())
+    }
+}
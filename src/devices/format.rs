@@ -22,6 +22,28 @@ pub enum SampleFormat {
     Unsupported,
 }
 
+impl SampleFormat {
+    /// The number of bits in each sample, or `None` if the format isn't supported.
+    pub fn bits(&self) -> Option<u32> {
+        match self {
+            SampleFormat::Float64 => Some(64),
+            SampleFormat::Float32 | SampleFormat::Signed32 | SampleFormat::Unsigned32 => Some(32),
+            SampleFormat::Signed24
+            | SampleFormat::Unsigned24
+            | SampleFormat::Signed24Packed
+            | SampleFormat::Unsigned24Packed => Some(24),
+            SampleFormat::Signed16 | SampleFormat::Unsigned16 => Some(16),
+            SampleFormat::Signed8 | SampleFormat::Unsigned8 => Some(8),
+            SampleFormat::Dsd => Some(1),
+            SampleFormat::Unsupported => None,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, SampleFormat::Float64 | SampleFormat::Float32)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChannelSpec {
     Bitmask(Channels),
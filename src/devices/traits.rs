@@ -24,6 +24,11 @@ pub trait DeviceProvider {
     fn get_default_device(&mut self) -> Result<Box<dyn Device>, FindError>;
     /// Requests the device provider find and return a device by its UID.
     fn get_device_by_uid(&mut self, id: &str) -> Result<Box<dyn Device>, FindError>;
+    /// Returns true once each time the system's default output device changes, such as when an
+    /// AirPlay speaker is picked. Providers that can't tell should always return false.
+    fn default_device_changed(&mut self) -> bool {
+        false
+    }
 }
 
 pub trait Device {
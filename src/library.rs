@@ -1,4 +1,21 @@
+pub mod analysis;
+pub mod art;
+pub mod artists;
+pub mod audiobooks;
+pub mod bench;
+pub mod bundle;
 pub mod db;
+pub mod edit;
+pub mod genres;
+pub mod guess;
+pub mod headless;
+pub mod organize;
 pub mod playlist;
+pub mod radio;
+pub mod recap;
+pub mod remote;
+pub mod resume;
 pub mod scan;
+pub mod sync;
+pub mod transfer;
 pub mod types;
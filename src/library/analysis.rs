@@ -0,0 +1,385 @@
+mod decode;
+mod key;
+mod loudness;
+mod silence;
+mod tempo;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel},
+    time::Duration,
+};
+
+use gpui::Global;
+use sqlx::SqlitePool;
+use tracing::{debug, info, warn};
+
+use crate::{
+    media::tags::{TagChanges, TagField, write_tags},
+    settings::analysis::AnalysisSettings,
+};
+
+pub use self::loudness::Loudness;
+use self::{
+    decode::decode_file, key::KeyDetector, loudness::LoudnessMeter, silence::SilenceDetector,
+    tempo::TempoDetector,
+};
+
+/// Audio is reduced to roughly this rate before tempo and key detection. Neither needs anything
+/// above a few kilohertz, and it makes both much faster.
+const ANALYSIS_RATE: u32 = 11025;
+
+/// How long to wait before checking for new tracks once every track has been analyzed.
+const IDLE_POLL: Duration = Duration::from_secs(60);
+
+/// The loudness that ReplayGain 2.0 adjusts tracks to, in LUFS.
+const REPLAYGAIN_REFERENCE: f64 = -18.0;
+
+/// The results of analyzing a track.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TrackAnalysis {
+    pub bpm: Option<f64>,
+    /// The key, such as `C` for C major or `F#m` for F-sharp minor.
+    pub key: Option<String>,
+    /// Where the audible part of the track starts and ends, in seconds.
+    pub audio_start: Option<f64>,
+    pub audio_end: Option<f64>,
+}
+
+/// Mixes interleaved samples down to mono and reduces their rate by averaging groups of samples.
+struct Downmixer {
+    factor: usize,
+    sum: f32,
+    count: usize,
+    output: Vec<f32>,
+}
+
+impl Downmixer {
+    fn new(rate: u32) -> Self {
+        Downmixer {
+            factor: (rate / ANALYSIS_RATE).max(1) as usize,
+            sum: 0.0,
+            count: 0,
+            output: Vec::new(),
+        }
+    }
+
+    fn rate(&self, rate: u32) -> u32 {
+        rate / self.factor as u32
+    }
+
+    fn process(&mut self, samples: &[f32], channels: usize) -> &[f32] {
+        self.output.clear();
+
+        for frame in samples.chunks_exact(channels.max(1)) {
+            self.sum += frame.iter().sum::<f32>() / frame.len() as f32;
+            self.count += 1;
+
+            if self.count == self.factor {
+                self.output.push(self.sum / self.factor as f32);
+                self.sum = 0.0;
+                self.count = 0;
+            }
+        }
+
+        &self.output
+    }
+}
+
+struct Detectors {
+    downmixer: Downmixer,
+    tempo: TempoDetector,
+    key: KeyDetector,
+    silence: SilenceDetector,
+}
+
+/// Decodes the file and estimates its tempo and key, and finds the silence at either end of it.
+/// Samples quieter than the threshold (in dBFS) count as silence.
+pub fn analyze_file(path: &Path, silence_threshold: f64) -> anyhow::Result<TrackAnalysis> {
+    let mut detectors: Option<Detectors> = None;
+
+    decode_file(path, |samples, channels, rate| {
+        let detectors = detectors.get_or_insert_with(|| {
+            let downmixer = Downmixer::new(rate);
+            let analysis_rate = downmixer.rate(rate);
+            Detectors {
+                downmixer,
+                tempo: TempoDetector::new(analysis_rate),
+                key: KeyDetector::new(analysis_rate),
+                silence: SilenceDetector::new(silence_threshold, rate),
+            }
+        });
+
+        detectors.silence.process(samples, channels);
+
+        let mono = detectors.downmixer.process(samples, channels);
+        detectors.tempo.process(mono);
+        detectors.key.process(mono);
+    })?;
+
+    let Some(detectors) = detectors else {
+        anyhow::bail!("file has no samples");
+    };
+
+    let audible = detectors.silence.finish();
+
+    Ok(TrackAnalysis {
+        bpm: detectors.tempo.finish(),
+        key: detectors.key.finish(),
+        audio_start: audible.map(|v| v.0),
+        audio_end: audible.map(|v| v.1),
+    })
+}
+
+/// Decodes the file and measures its integrated loudness and true peak. Returns `None` if the
+/// file is silent.
+pub fn measure_loudness(path: &Path) -> anyhow::Result<Option<Loudness>> {
+    let mut meter: Option<LoudnessMeter> = None;
+
+    decode_file(path, |samples, channels, rate| {
+        meter
+            .get_or_insert_with(|| LoudnessMeter::new(channels, rate))
+            .process(samples);
+    })?;
+
+    let Some(meter) = meter else {
+        anyhow::bail!("file has no samples");
+    };
+
+    Ok(meter.finish())
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum AnalysisCommand {
+    UpdateSettings(AnalysisSettings),
+}
+
+/// Controls the analysis thread, which works through every track in the library that hasn't
+/// been analyzed yet, one at a time.
+pub struct AnalysisInterface {
+    cmd_tx: Sender<AnalysisCommand>,
+}
+
+impl AnalysisInterface {
+    /// Changes which kinds of analysis are done. Analysis stops once they're all turned off.
+    pub fn update_settings(&self, settings: AnalysisSettings) {
+        self.cmd_tx
+            .send(AnalysisCommand::UpdateSettings(settings))
+            .expect("could not send analysis settings");
+    }
+}
+
+impl Global for AnalysisInterface {}
+
+pub struct AnalysisThread {
+    pool: SqlitePool,
+    command_rx: Receiver<AnalysisCommand>,
+    settings: AnalysisSettings,
+    /// Whether the last check found nothing left to analyze.
+    idle: bool,
+}
+
+impl AnalysisThread {
+    pub fn start(pool: SqlitePool, settings: AnalysisSettings) -> AnalysisInterface {
+        let (cmd_tx, command_rx) = channel();
+
+        std::thread::Builder::new()
+            .name("analysis".to_string())
+            .spawn(move || {
+                let mut thread = AnalysisThread {
+                    pool,
+                    command_rx,
+                    settings,
+                    idle: false,
+                };
+
+                thread.run();
+            })
+            .expect("could not start analysis thread");
+
+        AnalysisInterface { cmd_tx }
+    }
+
+    fn run(&mut self) {
+        if self.settings.any_enabled() {
+            info!("Starting track analysis");
+        }
+
+        loop {
+            let command = if !self.settings.any_enabled() {
+                match self.command_rx.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                }
+            } else {
+                let timeout = if self.idle { IDLE_POLL } else { Duration::ZERO };
+
+                match self.command_rx.recv_timeout(timeout) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            };
+
+            if let Some(AnalysisCommand::UpdateSettings(settings)) = command {
+                match (self.settings.any_enabled(), settings.any_enabled()) {
+                    (false, true) => info!("Starting track analysis"),
+                    (true, false) => info!("Stopping track analysis"),
+                    _ => (),
+                }
+
+                self.settings = settings;
+                self.idle = false;
+            }
+
+            if self.settings.any_enabled() {
+                self.idle = !crate::RUNTIME.block_on(self.next());
+            }
+        }
+    }
+
+    /// Does the next piece of analysis that's turned on. Returns false if there's nothing left
+    /// to do.
+    async fn next(&self) -> bool {
+        (self.settings.enabled && analyze_next(&self.pool, self.settings.silence_threshold).await)
+            || (self.settings.loudness
+                && measure_next(&self.pool, self.settings.write_replaygain).await)
+    }
+}
+
+/// Analyzes the next track that hasn't been analyzed, and saves the results. Returns false if
+/// there are none left.
+pub async fn analyze_next(pool: &SqlitePool, silence_threshold: f64) -> bool {
+    let next: Result<Option<(i64, String)>, sqlx::Error> =
+        sqlx::query_as(include_str!("../../queries/analysis/next_track.sql"))
+            .fetch_optional(pool)
+            .await;
+
+    let (track_id, location) = match next {
+        Ok(Some(next)) => next,
+        Ok(None) => return false,
+        Err(err) => {
+            warn!(?err, "Could not find tracks to analyze: {err}");
+            return false;
+        }
+    };
+
+    let path = PathBuf::from(location);
+    debug!("Analyzing {:?}", path);
+
+    // failures are still saved, so that the track isn't tried again until it's rescanned
+    let analysis = analyze_file(&path, silence_threshold).unwrap_or_else(|err| {
+        warn!("Could not analyze {:?}: {err}", path);
+        TrackAnalysis::default()
+    });
+
+    debug!("Analyzed {:?}: {:?}", path, analysis);
+
+    let result = sqlx::query(include_str!("../../queries/analysis/save_analysis.sql"))
+        .bind(track_id)
+        .bind(analysis.bpm)
+        .bind(analysis.key)
+        .bind(analysis.audio_start)
+        .bind(analysis.audio_end)
+        .execute(pool)
+        .await;
+
+    if let Err(err) = result {
+        warn!(?err, "Could not save analysis of {:?}: {err}", path);
+        return false;
+    }
+
+    true
+}
+
+/// Measures the loudness of the next track that doesn't have ReplayGain tags and hasn't been
+/// measured, and saves the results. If `write_replaygain` is set, ReplayGain tags are also
+/// written to the file. Returns false if there are no tracks left.
+pub async fn measure_next(pool: &SqlitePool, write_replaygain: bool) -> bool {
+    let next: Result<Option<(i64, String)>, sqlx::Error> = sqlx::query_as(include_str!(
+        "../../queries/analysis/next_loudness_track.sql"
+    ))
+    .fetch_optional(pool)
+    .await;
+
+    let (track_id, location) = match next {
+        Ok(Some(next)) => next,
+        Ok(None) => return false,
+        Err(err) => {
+            warn!(?err, "Could not find tracks to measure: {err}");
+            return false;
+        }
+    };
+
+    let path = PathBuf::from(location);
+    debug!("Measuring loudness of {:?}", path);
+
+    // like with tempo and key, failures are saved so that the file isn't decoded again
+    let loudness = measure_loudness(&path).unwrap_or_else(|err| {
+        warn!("Could not measure loudness of {:?}: {err}", path);
+        None
+    });
+
+    debug!("Measured {:?}: {:?}", path, loudness);
+
+    let result = sqlx::query(include_str!("../../queries/analysis/save_loudness.sql"))
+        .bind(track_id)
+        .bind(loudness.map(|v| v.integrated))
+        .bind(loudness.map(|v| v.true_peak))
+        .execute(pool)
+        .await;
+
+    if let Err(err) = result {
+        warn!(?err, "Could not save loudness of {:?}: {err}", path);
+        return false;
+    }
+
+    if write_replaygain && let Some(loudness) = loudness {
+        let changes = TagChanges {
+            text: vec![
+                (
+                    TagField::ReplayGainTrackGain,
+                    format!("{:.2} dB", REPLAYGAIN_REFERENCE - loudness.integrated),
+                ),
+                (
+                    TagField::ReplayGainTrackPeak,
+                    format!("{:.6}", 10f64.powf(loudness.true_peak / 20.0)),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        // the tags are read back in on the next scan, since the file has changed
+        if let Err(err) = write_tags(&path, &changes) {
+            warn!("Could not write ReplayGain tags to {:?}: {err}", path);
+        }
+    }
+
+    true
+}
+
+/// A track found by [find_tracks].
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct AnalyzedTrack {
+    pub id: i64,
+    pub title: String,
+    pub bpm: Option<f64>,
+    pub musical_key: Option<String>,
+    pub location: String,
+}
+
+/// Finds analyzed tracks with a tempo in the range and the given key. Filters that are `None`
+/// match every track.
+pub async fn find_tracks(
+    pool: &SqlitePool,
+    min_bpm: Option<f64>,
+    max_bpm: Option<f64>,
+    key: Option<&str>,
+) -> Result<Vec<AnalyzedTrack>, sqlx::Error> {
+    sqlx::query_as(include_str!("../../queries/analysis/find_tracks.sql"))
+        .bind(min_bpm)
+        .bind(max_bpm)
+        .bind(key)
+        .fetch_all(pool)
+        .await
+}
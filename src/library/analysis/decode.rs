@@ -0,0 +1,78 @@
+use std::{fs::File, path::Path};
+
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{CODEC_TYPE_NULL, DecoderOptions},
+    errors::Error,
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+/// Decodes the whole file, passing each block of interleaved samples to the callback along with
+/// the number of channels and the sample rate. Packets that fail to decode are skipped.
+pub fn decode_file(
+    path: &Path,
+    mut on_samples: impl FnMut(&[f32], usize, u32),
+) -> anyhow::Result<()> {
+    let mss = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe().format(
+        &hint,
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| anyhow::anyhow!("file has no audio"))?;
+    let track_id = track.id;
+
+    let mut decoder =
+        symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Ok(());
+            }
+            Err(Error::ResetRequired) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(Error::DecodeError(_)) => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        let spec = *decoded.spec();
+        let capacity = decoded.capacity() as u64;
+
+        let buffer = match &mut buffer {
+            Some(buffer) if buffer.capacity() >= decoded.capacity() * spec.channels.count() => {
+                buffer
+            }
+            buffer => buffer.insert(SampleBuffer::new(capacity, spec)),
+        };
+
+        buffer.copy_interleaved_ref(decoded);
+        on_samples(buffer.samples(), spec.channels.count(), spec.rate);
+    }
+}
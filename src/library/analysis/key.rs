@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use realfft::{RealFftPlanner, RealToComplex};
+
+/// The number of samples in each spectrum.
+const FRAME: usize = 4096;
+
+/// Frequencies outside this range are mostly drums and overtones, which blur the pitch classes.
+const MIN_FREQ: f64 = 55.0;
+const MAX_FREQ: f64 = 2000.0;
+
+/// How strongly each pitch class is associated with a major or minor key, starting from the
+/// tonic (Krumhansl and Kessler).
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+
+/// Estimates the key of a track by adding up how much of each pitch class is heard over the
+/// whole track, and comparing the result to the typical shape of each major and minor key.
+pub struct KeyDetector {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    /// The pitch class of each frequency bin, if it's in the range that's used.
+    bin_classes: Vec<Option<usize>>,
+    frame: Vec<f32>,
+    chroma: [f64; 12],
+}
+
+impl KeyDetector {
+    /// Creates a detector for mono samples at the given rate.
+    pub fn new(rate: u32) -> Self {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FRAME);
+
+        let window = (0..FRAME)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / FRAME as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+
+        let bin_classes = (0..FRAME / 2 + 1)
+            .map(|bin| {
+                let freq = bin as f64 * rate as f64 / FRAME as f64;
+                (MIN_FREQ..=MAX_FREQ).contains(&freq).then(|| {
+                    // A4 is 440 Hz, and A is nine semitones above C
+                    let semitones = (12.0 * (freq / 440.0).log2()).round() as i64 + 9;
+                    semitones.rem_euclid(12) as usize
+                })
+            })
+            .collect();
+
+        KeyDetector {
+            fft,
+            window,
+            bin_classes,
+            frame: Vec::with_capacity(FRAME),
+            chroma: [0.0; 12],
+        }
+    }
+
+    pub fn process(&mut self, samples: &[f32]) {
+        for sample in samples {
+            self.frame.push(*sample);
+
+            if self.frame.len() == FRAME {
+                self.analyze_frame();
+                self.frame.clear();
+            }
+        }
+    }
+
+    fn analyze_frame(&mut self) {
+        let mut input: Vec<f32> = self
+            .frame
+            .iter()
+            .zip(&self.window)
+            .map(|(sample, window)| sample * window)
+            .collect();
+        let mut spectrum = self.fft.make_output_vec();
+
+        if self.fft.process(&mut input, &mut spectrum).is_err() {
+            return;
+        }
+
+        for (value, class) in spectrum.iter().zip(&self.bin_classes) {
+            if let Some(class) = class {
+                self.chroma[*class] += value.norm() as f64;
+            }
+        }
+    }
+
+    /// Returns the key, such as `C` for C major or `F#m` for F-sharp minor.
+    pub fn finish(self) -> Option<String> {
+        if self.chroma.iter().all(|v| *v == 0.0) {
+            return None;
+        }
+
+        (0..12)
+            .flat_map(|tonic| {
+                [
+                    (tonic, false, correlate(&self.chroma, &MAJOR_PROFILE, tonic)),
+                    (tonic, true, correlate(&self.chroma, &MINOR_PROFILE, tonic)),
+                ]
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(tonic, minor, _)| {
+                format!("{}{}", NOTE_NAMES[tonic], if minor { "m" } else { "" })
+            })
+    }
+}
+
+/// The Pearson correlation between the chroma and the profile moved up to start on the tonic.
+fn correlate(chroma: &[f64; 12], profile: &[f64; 12], tonic: usize) -> f64 {
+    let mean_chroma = chroma.iter().sum::<f64>() / 12.0;
+    let mean_profile = profile.iter().sum::<f64>() / 12.0;
+
+    let mut covariance = 0.0;
+    let mut var_chroma = 0.0;
+    let mut var_profile = 0.0;
+
+    for class in 0..12 {
+        let c = chroma[class] - mean_chroma;
+        let p = profile[(class + 12 - tonic) % 12] - mean_profile;
+        covariance += c * p;
+        var_chroma += c * c;
+        var_profile += p * p;
+    }
+
+    covariance / (var_chroma * var_profile).sqrt().max(f64::EPSILON)
+}
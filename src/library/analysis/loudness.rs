@@ -0,0 +1,279 @@
+use std::f64::consts::PI;
+
+/// Loudness is measured over 400 ms blocks which start 100 ms apart, so each block is made up of
+/// four steps and overlaps the next three blocks.
+const STEP_SECONDS: f64 = 0.1;
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Blocks quieter than this are silence, and aren't counted.
+const ABSOLUTE_GATE: f64 = -70.0;
+/// Blocks this much quieter than the rest of the track aren't counted either.
+const RELATIVE_GATE: f64 = -10.0;
+
+/// The number of input samples each interpolated sample is calculated from.
+const PEAK_TAPS: usize = 12;
+
+/// The integrated loudness and true peak of a track, as described by EBU R 128 and ITU-R BS.1770.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    /// The integrated loudness in LUFS.
+    pub integrated: f64,
+    /// The true peak in dBTP.
+    pub true_peak: f64,
+}
+
+/// A second order IIR filter.
+#[derive(Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Biquad {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, sample: f64) -> f64 {
+        let output = self.b[0] * sample + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+
+        self.x = [sample, self.x[0]];
+        self.y = [output, self.y[0]];
+
+        output
+    }
+}
+
+/// Creates the two filters of the K-weighting curve, which roughly matches how loud each
+/// frequency sounds. The coefficients in BS.1770 are only given for 48 kHz, so they're worked
+/// out from the analog filters for other rates.
+fn k_weighting(rate: u32) -> [Biquad; 2] {
+    let rate = rate as f64;
+
+    // a high shelf that boosts frequencies above about 1.5 kHz by 4 dB
+    let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    // a high pass that removes frequencies below about 40 Hz
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    [shelf, high_pass]
+}
+
+/// Finds the peak of a signal between its samples by oversampling it.
+struct PeakDetector {
+    /// The interpolation filter, split into one set of taps for each oversampled position.
+    phases: Vec<[f64; PEAK_TAPS]>,
+    history: [f64; PEAK_TAPS],
+    peak: f64,
+}
+
+impl PeakDetector {
+    fn new(rate: u32) -> Self {
+        // BS.1770 asks for 4x oversampling, but high rates already capture most peaks
+        let factor = match rate {
+            ..96000 => 4,
+            96000..192000 => 2,
+            _ => 1,
+        };
+
+        let length = PEAK_TAPS * factor;
+        let center = (length - 1) as f64 / 2.0;
+
+        let phases = (0..factor)
+            .map(|phase| {
+                let mut taps = [0.0; PEAK_TAPS];
+                for (tap, value) in taps.iter_mut().enumerate() {
+                    let i = tap * factor + phase;
+                    let x = (i as f64 - center) / factor as f64;
+                    let sinc = if x == 0.0 {
+                        1.0
+                    } else {
+                        (PI * x).sin() / (PI * x)
+                    };
+                    let window = 0.5 - 0.5 * (2.0 * PI * (i as f64 + 0.5) / length as f64).cos();
+                    *value = sinc * window;
+                }
+
+                let sum: f64 = taps.iter().sum();
+                taps.map(|v| v / sum)
+            })
+            .collect();
+
+        PeakDetector {
+            phases,
+            history: [0.0; PEAK_TAPS],
+            peak: 0.0,
+        }
+    }
+
+    fn process(&mut self, sample: f64) {
+        self.history.rotate_right(1);
+        self.history[0] = sample;
+        self.peak = self.peak.max(sample.abs());
+
+        for taps in &self.phases {
+            let value: f64 = taps.iter().zip(&self.history).map(|(t, x)| t * x).sum();
+            self.peak = self.peak.max(value.abs());
+        }
+    }
+}
+
+struct Channel {
+    filters: [Biquad; 2],
+    peak: PeakDetector,
+    /// How much the channel counts towards the loudness of the track.
+    weight: f64,
+    /// The sum of the squares of the filtered samples in the current step.
+    sum: f64,
+}
+
+/// Measures the integrated loudness and true peak of a track.
+pub struct LoudnessMeter {
+    channels: Vec<Channel>,
+    step_length: usize,
+    step_position: usize,
+    /// The weighted mean square of each of the most recent steps.
+    steps: [f64; STEPS_PER_BLOCK],
+    steps_seen: usize,
+    /// The mean square of each block.
+    blocks: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    /// Creates a meter for interleaved samples with the given number of channels and rate.
+    pub fn new(channels: usize, rate: u32) -> Self {
+        let channels = (0..channels)
+            .map(|channel| Channel {
+                filters: k_weighting(rate),
+                peak: PeakDetector::new(rate),
+                // in 5.1 audio, the fourth channel is the LFE and the last two are surrounds
+                weight: match (channels, channel) {
+                    (6.., 3) => 0.0,
+                    (5.., 4..) => 1.41,
+                    _ => 1.0,
+                },
+                sum: 0.0,
+            })
+            .collect();
+
+        LoudnessMeter {
+            channels,
+            step_length: ((rate as f64 * STEP_SECONDS) as usize).max(1),
+            step_position: 0,
+            steps: [0.0; STEPS_PER_BLOCK],
+            steps_seen: 0,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn process(&mut self, samples: &[f32]) {
+        let count = self.channels.len();
+        if count == 0 {
+            return;
+        }
+
+        for frame in samples.chunks_exact(count) {
+            for (channel, sample) in self.channels.iter_mut().zip(frame) {
+                let sample = *sample as f64;
+                channel.peak.process(sample);
+
+                let filtered = channel
+                    .filters
+                    .iter_mut()
+                    .fold(sample, |sample, filter| filter.process(sample));
+                channel.sum += filtered * filtered;
+            }
+
+            self.step_position += 1;
+            if self.step_position == self.step_length {
+                self.finish_step();
+            }
+        }
+    }
+
+    fn finish_step(&mut self) {
+        let power: f64 = self
+            .channels
+            .iter_mut()
+            .map(|channel| channel.weight * std::mem::take(&mut channel.sum))
+            .sum();
+
+        self.steps.rotate_left(1);
+        self.steps[STEPS_PER_BLOCK - 1] = power / self.step_length as f64;
+        self.step_position = 0;
+        self.steps_seen += 1;
+
+        if self.steps_seen >= STEPS_PER_BLOCK {
+            self.blocks
+                .push(self.steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64);
+        }
+    }
+
+    /// Returns the measurements, or `None` if the track is too short or too quiet to have a
+    /// loudness.
+    pub fn finish(self) -> Option<Loudness> {
+        let true_peak = self
+            .channels
+            .iter()
+            .map(|channel| channel.peak.peak)
+            .fold(0.0, f64::max);
+
+        let above_absolute: Vec<f64> = self
+            .blocks
+            .iter()
+            .copied()
+            .filter(|power| loudness(*power) > ABSOLUTE_GATE)
+            .collect();
+
+        if above_absolute.is_empty() {
+            return None;
+        }
+
+        let threshold = loudness(mean(&above_absolute)) + RELATIVE_GATE;
+        let gated: Vec<f64> = above_absolute
+            .into_iter()
+            .filter(|power| loudness(*power) > threshold)
+            .collect();
+
+        Some(Loudness {
+            integrated: loudness(mean(&gated)),
+            true_peak: 20.0 * true_peak.max(f64::MIN_POSITIVE).log10(),
+        })
+    }
+}
+
+/// Converts a weighted mean square to LUFS.
+fn loudness(power: f64) -> f64 {
+    -0.691 + 10.0 * power.max(f64::MIN_POSITIVE).log10()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
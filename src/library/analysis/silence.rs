@@ -0,0 +1,43 @@
+/// Finds where the audible part of a track starts and ends, so that silence padding at either end
+/// can be skipped during playback.
+pub struct SilenceDetector {
+    threshold: f32,
+    rate: u32,
+    frames: u64,
+    first_audible: Option<u64>,
+    last_audible: u64,
+}
+
+impl SilenceDetector {
+    /// Creates a detector that treats any sample quieter than the threshold (in dBFS) as silence.
+    pub fn new(threshold: f64, rate: u32) -> Self {
+        SilenceDetector {
+            threshold: 10f64.powf(threshold / 20.0) as f32,
+            rate,
+            frames: 0,
+            first_audible: None,
+            last_audible: 0,
+        }
+    }
+
+    /// Takes interleaved samples with the given number of channels.
+    pub fn process(&mut self, samples: &[f32], channels: usize) {
+        for frame in samples.chunks_exact(channels.max(1)) {
+            if frame.iter().any(|sample| sample.abs() > self.threshold) {
+                self.first_audible.get_or_insert(self.frames);
+                self.last_audible = self.frames;
+            }
+
+            self.frames += 1;
+        }
+    }
+
+    /// Returns the positions in seconds of the start and end of the audible part of the track, or
+    /// `None` if the whole track is silent.
+    pub fn finish(self) -> Option<(f64, f64)> {
+        let first = self.first_audible?;
+        let rate = self.rate as f64;
+
+        Some((first as f64 / rate, (self.last_audible + 1) as f64 / rate))
+    }
+}
@@ -0,0 +1,106 @@
+/// The number of samples between each point of the onset envelope.
+const HOP: usize = 64;
+/// The number of hops each energy measurement is taken over.
+const WINDOW_HOPS: usize = 4;
+
+const MIN_BPM: f64 = 60.0;
+const MAX_BPM: f64 = 200.0;
+/// Tempos near this are preferred, which stops most tracks from being detected at half or double
+/// their actual tempo.
+const PREFERRED_BPM: f64 = 120.0;
+
+/// Tracks shorter than this don't have enough beats for a reliable estimate.
+const MIN_SECONDS: f64 = 10.0;
+
+/// Estimates the tempo of a track from how often its loudness rises sharply, which is usually
+/// when a beat lands.
+pub struct TempoDetector {
+    rate: u32,
+    hop: Vec<f32>,
+    energies: [f32; WINDOW_HOPS],
+    last_energy: f32,
+    envelope: Vec<f32>,
+}
+
+impl TempoDetector {
+    pub fn new(rate: u32) -> Self {
+        TempoDetector {
+            rate,
+            hop: Vec::with_capacity(HOP),
+            energies: [0.0; WINDOW_HOPS],
+            last_energy: 0.0,
+            envelope: Vec::new(),
+        }
+    }
+
+    /// Takes mono samples at the rate given when the detector was created.
+    pub fn process(&mut self, samples: &[f32]) {
+        for sample in samples {
+            self.hop.push(*sample);
+
+            if self.hop.len() == HOP {
+                let energy = self.hop.iter().map(|v| v * v).sum::<f32>();
+                self.hop.clear();
+
+                self.energies.rotate_left(1);
+                self.energies[WINDOW_HOPS - 1] = energy;
+
+                let energy = (self.energies.iter().sum::<f32>() + 1e-6).ln();
+                self.envelope.push((energy - self.last_energy).max(0.0));
+                self.last_energy = energy;
+            }
+        }
+    }
+
+    /// Returns the tempo in beats per minute, rounded to one decimal place.
+    pub fn finish(self) -> Option<f64> {
+        let envelope_rate = self.rate as f64 / HOP as f64;
+        if (self.envelope.len() as f64) < envelope_rate * MIN_SECONDS {
+            return None;
+        }
+
+        let mean = self.envelope.iter().sum::<f32>() / self.envelope.len() as f32;
+        let envelope: Vec<f32> = self.envelope.iter().map(|v| v - mean).collect();
+
+        // one lag either side of the range is scored, so that peaks at its edges can be placed
+        let first_lag = (envelope_rate * 60.0 / MAX_BPM).floor() as usize - 1;
+        let last_lag = (envelope_rate * 60.0 / MIN_BPM).ceil() as usize + 1;
+
+        let correlation = |lag: usize| -> f64 {
+            let sum: f32 = envelope
+                .iter()
+                .zip(&envelope[lag..])
+                .map(|(a, b)| a * b)
+                .sum();
+            sum as f64 / (envelope.len() - lag) as f64
+        };
+
+        let scores: Vec<f64> = (first_lag..=last_lag).map(correlation).collect();
+
+        let weighted = |idx: usize| {
+            let bpm = envelope_rate * 60.0 / (first_lag + idx) as f64;
+            let octaves = (bpm / PREFERRED_BPM).log2();
+            scores[idx] * (-0.5 * octaves * octaves).exp()
+        };
+
+        let best = (1..scores.len() - 1).max_by(|a, b| weighted(*a).total_cmp(&weighted(*b)))?;
+
+        if scores[best] <= 0.0 {
+            return None;
+        }
+
+        // the peak usually falls between two lags, so it's placed using the scores either side
+        let (before, peak, after) = (scores[best - 1], scores[best], scores[best + 1]);
+        let denominator = before - 2.0 * peak + after;
+        let offset = if denominator.abs() > f64::EPSILON {
+            (0.5 * (before - after) / denominator).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+
+        let lag = (first_lag + best) as f64 + offset;
+        let bpm = envelope_rate * 60.0 / lag;
+
+        Some((bpm * 10.0).round() / 10.0)
+    }
+}
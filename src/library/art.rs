@@ -0,0 +1,273 @@
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use image::{
+    DynamicImage, EncodableLayout, ImageFormat,
+    codecs::jpeg::JpegEncoder,
+    imageops::{FilterType, thumbnail},
+};
+use rustc_hash::FxHashSet;
+use sqlx::SqlitePool;
+use tracing::{debug, info, warn};
+
+use crate::{
+    library::db::{AlbumMethod, get_album_by_id, list_tracks_in_album},
+    media::tags::{CoverArt, TagChanges, write_tags},
+};
+
+/// Returns the path of the art file with the given hash. Files are split into subdirectories by
+/// the first two characters of their hash to keep directory sizes reasonable.
+pub fn art_path(dir: &Path, hash: &str) -> PathBuf {
+    dir.join(&hash[..2]).join(hash)
+}
+
+/// Stores the given image in the art directory and returns its hash. Images are content-addressed,
+/// so storing the same image twice will only result in one file being written.
+pub fn store_art(dir: &Path, data: &[u8]) -> std::io::Result<String> {
+    let hash = format!("{:x}", md5::compute(data));
+    let path = art_path(dir, &hash);
+
+    if !path.exists() {
+        fs::create_dir_all(path.parent().unwrap())?;
+
+        // write to a temporary file first so that a crash can't leave a truncated image behind
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &path)?;
+    } else {
+        // an unused copy may be about to be cleaned up, which skips recently modified files
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(SystemTime::now())?;
+    }
+
+    Ok(hash)
+}
+
+/// Stores an album's art in the art directory, shrinking it first if it's very large. Returns the
+/// hash of the stored image and a small BMP thumbnail for lists.
+pub fn store_album_art(dir: &Path, image: &[u8]) -> anyhow::Result<(String, Vec<u8>)> {
+    let mut decoded = image::ImageReader::new(Cursor::new(image))
+        .with_guessed_format()?
+        .decode()?
+        .into_rgb8();
+
+    // for some reason, thumbnails don't load properly when saved as rgb8
+    // also, into_rgba8() causes the application to crash on certain images
+    //
+    // no, I don't no why, and no I can't fix it upstream
+    // this will have to do for now
+    let decoded_rgba = DynamicImage::ImageRgb8(decoded.clone()).into_rgba8();
+
+    let thumb = thumbnail(&decoded_rgba, 70, 70);
+
+    let mut buf: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+
+    thumb
+        .write_to(&mut buf, ImageFormat::Bmp)
+        .expect("i don't know how Cursor could fail");
+    buf.flush().expect("could not flush buffer");
+
+    let resized = if decoded.dimensions().0 <= 1024 || decoded.dimensions().1 <= 1024 {
+        image.to_vec()
+    } else {
+        decoded = image::imageops::resize(&decoded, 1024, 1024, FilterType::Lanczos3);
+        let mut buf: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let mut encoder = JpegEncoder::new_with_quality(&mut buf, 70);
+
+        encoder.encode(
+            decoded.as_bytes(),
+            decoded.width(),
+            decoded.height(),
+            image::ExtendedColorType::Rgb8,
+        )?;
+        buf.flush()?;
+
+        buf.into_inner()
+    };
+
+    Ok((store_art(dir, &resized)?, buf.into_inner()))
+}
+
+pub fn read_art(dir: &Path, hash: &str) -> std::io::Result<Vec<u8>> {
+    fs::read(art_path(dir, hash))
+}
+
+/// Moves album art that is still stored inside of the database (from before art was stored on
+/// disk) into the art directory.
+pub async fn migrate_album_art(pool: &SqlitePool, dir: &Path) -> anyhow::Result<()> {
+    let albums: Vec<(i64,)> =
+        sqlx::query_as(include_str!("../../queries/art/find_albums_with_blobs.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    if albums.is_empty() {
+        return Ok(());
+    }
+
+    info!("Moving art for {} albums out of the database", albums.len());
+
+    for (id,) in albums {
+        let (image,): (Vec<u8>,) =
+            sqlx::query_as(include_str!("../../queries/art/get_album_blob.sql"))
+                .bind(id)
+                .fetch_one(pool)
+                .await?;
+
+        let hash = store_art(dir, &image)?;
+
+        sqlx::query(include_str!("../../queries/art/move_album_blob.sql"))
+            .bind(hash)
+            .bind(id)
+            .execute(pool)
+            .await?;
+    }
+
+    // the space used by the images isn't reclaimed until the database is vacuumed
+    sqlx::query("VACUUM").execute(pool).await?;
+
+    info!("Album art migration complete");
+
+    Ok(())
+}
+
+/// Deletes any files in the art directory that are no longer referenced by an album or artist.
+///
+/// Files modified after `since` are kept, as art stored while the scan was running (such as an
+/// artist image or a playlist cover) may not have been written to the database yet. Temporary
+/// files are kept too, since [store_art] may still be writing them.
+pub async fn remove_unused_art(
+    pool: &SqlitePool,
+    dir: &Path,
+    since: SystemTime,
+) -> anyhow::Result<()> {
+    let hashes: Vec<(String,)> =
+        sqlx::query_as(include_str!("../../queries/art/list_art_hashes.sql"))
+            .fetch_all(pool)
+            .await?;
+    let hashes: FxHashSet<String> = hashes.into_iter().map(|(hash,)| hash).collect();
+
+    let Ok(subdirs) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+
+    for subdir in subdirs.filter_map(|v| v.ok()) {
+        let Ok(files) = fs::read_dir(subdir.path()) else {
+            continue;
+        };
+
+        for file in files.filter_map(|v| v.ok()) {
+            if file.path().extension().is_some_and(|ext| ext == "tmp") {
+                continue;
+            }
+
+            let old = file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified < since);
+
+            if !old {
+                continue;
+            }
+
+            let name = file.file_name();
+            let referenced = name.to_str().is_some_and(|name| hashes.contains(name));
+
+            if !referenced {
+                debug!("Removing unused art {:?}", file.path());
+
+                if let Err(e) = fs::remove_file(file.path()) {
+                    warn!("Could not remove unused art {:?}: {}", file.path(), e);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the album's art with the given image, such as one of the other images embedded in its
+/// files.
+pub async fn set_album_art(
+    pool: &SqlitePool,
+    dir: &Path,
+    album_id: i64,
+    image: &[u8],
+) -> anyhow::Result<()> {
+    let (hash, thumb) = store_album_art(dir, image)?;
+
+    sqlx::query(include_str!("../../queries/edit/set_album_art.sql"))
+        .bind(album_id)
+        .bind(hash)
+        .bind(thumb)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Prepares an image for embedding, scaling it down to fit within `max_size` pixels. Images that
+/// are already small enough are embedded as they are.
+fn prepare_cover(data: &[u8], max_size: u32) -> anyhow::Result<CoverArt> {
+    let format = image::guess_format(data)?;
+    let image = image::load_from_memory_with_format(data, format)?;
+
+    if image.width() <= max_size && image.height() <= max_size {
+        return Ok(CoverArt {
+            data: data.to_vec(),
+            mime_type: format.to_mime_type().to_string(),
+            width: image.width(),
+            height: image.height(),
+        });
+    }
+
+    let resized = image
+        .resize(max_size, max_size, FilterType::Lanczos3)
+        .into_rgb8();
+    let mut buf = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buf, 90).encode_image(&resized)?;
+
+    Ok(CoverArt {
+        data: buf.into_inner(),
+        mime_type: ImageFormat::Jpeg.to_mime_type().to_string(),
+        width: resized.width(),
+        height: resized.height(),
+    })
+}
+
+/// Embeds the album's art into the tags of each of its tracks, so that other players and devices
+/// can show it. Returns the number of files that were written to; files in formats that tags
+/// can't be written to are skipped.
+pub async fn embed_album_art(
+    pool: &SqlitePool,
+    dir: &Path,
+    album_id: i64,
+    max_size: u32,
+) -> anyhow::Result<usize> {
+    let album = get_album_by_id(pool, album_id, AlbumMethod::Metadata).await?;
+    let Some(hash) = &album.image_hash else {
+        anyhow::bail!("album has no art");
+    };
+
+    let cover = prepare_cover(&read_art(dir, hash)?, max_size)?;
+    let changes = TagChanges {
+        front_cover: Some(cover),
+        ..Default::default()
+    };
+
+    let mut written = 0;
+
+    for track in list_tracks_in_album(pool, album_id).await?.iter() {
+        match write_tags(&track.location, &changes) {
+            Ok(()) => written += 1,
+            Err(e) => warn!("Could not embed art into {:?}: {}", track.location, e),
+        }
+    }
+
+    Ok(written)
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use rustc_hash::FxHashMap;
+use sqlx::SqlitePool;
+use tracing::debug;
+
+use crate::library::db::merge_artist_into;
+
+/// Reduces an artist's name to the characters that matter when comparing it with another, so
+/// that `Guns N' Roses` and `guns n roses` are the same artist.
+fn artist_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Maps the names artists are tagged with to the name used in the library, according to the
+/// aliases in the scan settings.
+#[derive(Debug, Default, Clone)]
+pub struct ArtistAliases {
+    aliases: FxHashMap<String, String>,
+}
+
+impl ArtistAliases {
+    pub fn new(aliases: &BTreeMap<String, String>) -> Self {
+        ArtistAliases {
+            aliases: aliases
+                .iter()
+                .map(|(from, to)| (artist_key(from), to.trim().to_string()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Returns the name the artist is known by in the library.
+    pub fn apply<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases
+            .get(&artist_key(name))
+            .map(String::as_str)
+            .unwrap_or(name)
+    }
+}
+
+/// Moves artists that are already in the library under an alias to the artist the alias is for,
+/// renaming them when that artist isn't in the library yet. Returns the number of artists that
+/// were changed.
+pub async fn apply_artist_aliases(
+    pool: &SqlitePool,
+    aliases: &ArtistAliases,
+) -> anyhow::Result<u64> {
+    if aliases.is_empty() {
+        return Ok(0);
+    }
+
+    let artists: Vec<(i64, String)> =
+        sqlx::query_as(include_str!("../../queries/edit/list_artist_names.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut changed = 0;
+
+    for (id, name) in artists {
+        let canonical = aliases.apply(&name);
+        if canonical == name {
+            continue;
+        }
+
+        let existing: Option<(i64,)> =
+            sqlx::query_as(include_str!("../../queries/scan/get_artist_id.sql"))
+                .bind(canonical)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        match existing {
+            Some((kept,)) => {
+                debug!("Merging artist {name:?} into {canonical:?}");
+                merge_artist_into(&mut tx, kept, id).await?;
+            }
+            None => {
+                debug!("Renaming artist {name:?} to {canonical:?}");
+                sqlx::query(include_str!("../../queries/edit/rename_artist.sql"))
+                    .bind(id)
+                    .bind(canonical)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(include_str!(
+                    "../../queries/edit/rename_track_artist_names.sql"
+                ))
+                .bind(&name)
+                .bind(canonical)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        changed += 1;
+    }
+
+    tx.commit().await?;
+
+    Ok(changed)
+}
@@ -0,0 +1,78 @@
+use std::path::{MAIN_SEPARATOR, PathBuf};
+
+use sqlx::SqlitePool;
+
+/// Turns the folder into a `LIKE` pattern matching every file inside it.
+fn folder_pattern(folder: &std::path::Path) -> Option<String> {
+    let mut folder = folder.to_str()?.to_string();
+    if !folder.ends_with(MAIN_SEPARATOR) {
+        folder.push(MAIN_SEPARATOR);
+    }
+
+    let escaped = folder
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    Some(format!("{escaped}%"))
+}
+
+/// Marks the tracks in the folders as audiobook chapters, and every other track as not being one.
+pub async fn mark_audiobooks(pool: &SqlitePool, folders: &[PathBuf]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(include_str!(
+        "../../queries/audiobooks/clear_audiobooks.sql"
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    for pattern in folders.iter().filter_map(|folder| folder_pattern(folder)) {
+        sqlx::query(include_str!("../../queries/audiobooks/mark_audiobooks.sql"))
+            .bind(pattern)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Looks up the audiobook chapter in the file, returning its ID and where playback of it was left,
+/// in seconds. Returns `None` if the file isn't an audiobook chapter.
+pub async fn find_audiobook_track(
+    pool: &SqlitePool,
+    path: &str,
+) -> Result<Option<(i64, i64)>, sqlx::Error> {
+    sqlx::query_as(include_str!(
+        "../../queries/audiobooks/find_audiobook_track.sql"
+    ))
+    .bind(path)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_track_position(
+    pool: &SqlitePool,
+    track_id: i64,
+    position: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(include_str!("../../queries/audiobooks/set_position.sql"))
+        .bind(track_id)
+        .bind(position)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Forgets where playback of the chapter was left, once it has been listened to.
+pub async fn clear_track_position(pool: &SqlitePool, track_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(include_str!("../../queries/audiobooks/clear_position.sql"))
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
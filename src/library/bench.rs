@@ -0,0 +1,70 @@
+use std::{fs, path::Path, time::Instant};
+
+use tracing::warn;
+
+use crate::{
+    library::{db::create_pool, scan::ScanThread},
+    settings::scan::ScanSettings,
+};
+
+/// Scans the given directory into a temporary database and prints a report of how long the scan
+/// took, so that changes to the scanner can be measured.
+pub fn bench_scan(dir: &Path) -> anyhow::Result<()> {
+    let dir = dir.canonicalize()?;
+    let temp_dir = std::env::temp_dir().join(format!("hummingbird-bench-{}", std::process::id()));
+    fs::create_dir_all(&temp_dir)?;
+
+    let pool = crate::RUNTIME.block_on(create_pool(temp_dir.join("library.db")))?;
+    let settings = ScanSettings {
+        paths: vec![dir.clone().into()],
+        ..Default::default()
+    };
+
+    println!("Scanning {} into {}", dir.display(), temp_dir.display());
+
+    let start = Instant::now();
+    let (scanned, timings) = ScanThread::scan_blocking(
+        pool.clone(),
+        settings,
+        temp_dir.join("art"),
+        temp_dir.join("scan_record.json"),
+        false,
+    );
+    let total = start.elapsed();
+
+    crate::RUNTIME.block_on(pool.close());
+
+    if let Err(e) = fs::remove_dir_all(&temp_dir) {
+        warn!("Could not remove temporary directory {:?}: {}", temp_dir, e);
+    }
+
+    let files_per_sec = scanned as f64 / total.as_secs_f64();
+    let other = total.saturating_sub(timings.decode + timings.database);
+
+    println!("Scanned {scanned} files in {total:.2?} ({files_per_sec:.1} files/sec)");
+    println!("  decode:   {:.2?}", timings.decode);
+    println!("  database: {:.2?}", timings.database);
+    println!("  other:    {other:.2?}");
+
+    match peak_memory() {
+        Some(bytes) => println!("Peak memory: {:.1} MiB", bytes as f64 / 1024.0 / 1024.0),
+        None => println!("Peak memory: unavailable on this platform"),
+    }
+
+    Ok(())
+}
+
+/// Returns the peak resident set size of the process in bytes.
+#[cfg(target_os = "linux")]
+fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_memory() -> Option<u64> {
+    None
+}
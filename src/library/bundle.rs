@@ -0,0 +1,266 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, Local, Timelike};
+
+use anyhow::bail;
+
+use crate::{
+    media::{metadata::TrackFile, transcode::Transcoder},
+    settings::transcode::TranscodeProfile,
+};
+
+use super::{
+    organize::sanitize,
+    playlist::{PlaylistEntry, m3u_entry, m3u_header},
+};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+/// Zip 2.0, which is understood by every unzip tool.
+const ZIP_VERSION: u16 = 20;
+/// Marks the entry's name as UTF-8.
+const UTF8_FLAG: u16 = 1 << 11;
+
+/// An entry that has been written to the archive, kept for the central directory.
+struct ZipEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writes a zip archive without compressing anything. Audio files barely compress, so this saves
+/// a lot of time for very little space. ZIP64 isn't supported, so the archive is limited to 4 GB.
+struct ZipWriter {
+    file: BufWriter<File>,
+    entries: Vec<ZipEntry>,
+    /// The DOS date and time every entry is marked with.
+    modified: (u16, u16),
+}
+
+fn too_large() -> io::Error {
+    io::Error::other("the bundle is over 4 GB, convert the tracks to make it smaller")
+}
+
+impl ZipWriter {
+    fn create(path: &Path) -> io::Result<Self> {
+        let now = Local::now();
+        let time = ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16;
+        let year = (now.year().max(1980) - 1980) as u32;
+        let date = ((year << 9) | (now.month() << 5) | now.day()) as u16;
+
+        Ok(ZipWriter {
+            file: BufWriter::new(File::create(path)?),
+            entries: Vec::new(),
+            modified: (time, date),
+        })
+    }
+
+    fn position(&mut self) -> io::Result<u32> {
+        u32::try_from(self.file.stream_position()?).map_err(|_| too_large())
+    }
+
+    fn write_local_header(&mut self, name: &str, crc: u32, size: u32) -> io::Result<()> {
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        header.extend(ZIP_VERSION.to_le_bytes());
+        header.extend(UTF8_FLAG.to_le_bytes());
+        // stored, without compression
+        header.extend(0u16.to_le_bytes());
+        header.extend(self.modified.0.to_le_bytes());
+        header.extend(self.modified.1.to_le_bytes());
+        header.extend(crc.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(name.as_bytes());
+
+        self.file.write_all(&header)
+    }
+
+    /// Adds the contents of `reader` to the archive as `name`.
+    fn add(&mut self, name: &str, mut reader: impl Read) -> io::Result<()> {
+        if self.entries.len() == u16::MAX as usize {
+            return Err(io::Error::other("the bundle has too many files"));
+        }
+
+        // the checksum and size aren't known until the data has been written, so the header is
+        // filled in afterwards
+        let offset = self.position()?;
+        self.write_local_header(name, 0, 0)?;
+
+        let mut hasher = crc32fast::Hasher::new();
+        let mut size: u64 = 0;
+        let mut buffer = vec![0; 64 * 1024];
+
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+
+            hasher.update(&buffer[..read]);
+            self.file.write_all(&buffer[..read])?;
+            size += read as u64;
+        }
+
+        let size = u32::try_from(size).map_err(|_| too_large())?;
+        let crc = hasher.finalize();
+        let end = self.position()?;
+
+        self.file.seek(SeekFrom::Start(offset as u64))?;
+        self.write_local_header(name, crc, size)?;
+        self.file.seek(SeekFrom::Start(end as u64))?;
+
+        self.entries.push(ZipEntry {
+            name: name.to_string(),
+            crc,
+            size,
+            offset,
+        });
+
+        Ok(())
+    }
+
+    /// Writes the central directory, which lists every entry in the archive.
+    fn finish(mut self) -> io::Result<()> {
+        let start = self.position()?;
+
+        for entry in &self.entries {
+            let mut header = Vec::with_capacity(46 + entry.name.len());
+            header.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            header.extend(ZIP_VERSION.to_le_bytes());
+            header.extend(ZIP_VERSION.to_le_bytes());
+            header.extend(UTF8_FLAG.to_le_bytes());
+            header.extend(0u16.to_le_bytes());
+            header.extend(self.modified.0.to_le_bytes());
+            header.extend(self.modified.1.to_le_bytes());
+            header.extend(entry.crc.to_le_bytes());
+            header.extend(entry.size.to_le_bytes());
+            header.extend(entry.size.to_le_bytes());
+            header.extend((entry.name.len() as u16).to_le_bytes());
+            // extra field, comment, disk number, and internal and external attributes
+            header.extend([0; 12]);
+            header.extend(entry.offset.to_le_bytes());
+            header.extend(entry.name.as_bytes());
+
+            self.file.write_all(&header)?;
+        }
+
+        let end = self.position()?;
+        let count = self.entries.len() as u16;
+
+        let mut record = Vec::with_capacity(22);
+        record.extend(END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        record.extend([0; 4]);
+        record.extend(count.to_le_bytes());
+        record.extend(count.to_le_bytes());
+        record.extend((end - start).to_le_bytes());
+        record.extend(start.to_le_bytes());
+        record.extend(0u16.to_le_bytes());
+
+        self.file.write_all(&record)?;
+        self.file.flush()
+    }
+}
+
+/// Writes a playlist to a zip file holding its tracks and an M3U file that refers to them by
+/// relative paths, so that the playlist can be handed to someone else and played anywhere.
+pub struct BundleWriter {
+    zip: ZipWriter,
+    /// The transcoder and profile the tracks are converted with, if they're converted.
+    transcode: Option<(Transcoder, TranscodeProfile)>,
+    /// Where converted tracks are written before they're added to the bundle.
+    temp_dir: PathBuf,
+    m3u: String,
+    added: usize,
+    /// The number of digits the track numbers in file names are padded to.
+    width: usize,
+}
+
+impl BundleWriter {
+    pub fn create(
+        path: &Path,
+        description: Option<&str>,
+        track_count: usize,
+        transcode: Option<(Transcoder, TranscodeProfile)>,
+    ) -> anyhow::Result<Self> {
+        let temp_dir =
+            std::env::temp_dir().join(format!("hummingbird-bundle-{}", std::process::id()));
+        fs::create_dir_all(&temp_dir)?;
+
+        Ok(BundleWriter {
+            zip: ZipWriter::create(path)?,
+            transcode,
+            temp_dir,
+            m3u: m3u_header(description),
+            added: 0,
+            width: track_count.to_string().len().max(2),
+        })
+    }
+
+    /// Adds the track to the bundle, converting it first if the bundle has a profile. `file` is
+    /// where the entry's audio is; tracks split from a cue sheet can only be added by converting
+    /// them, since that's how they're cut out of their file.
+    pub fn add(&mut self, entry: &PlaylistEntry, file: &TrackFile) -> anyhow::Result<()> {
+        let from = file.path.as_path();
+        let convert_with = self
+            .transcode
+            .as_ref()
+            .filter(|(_, profile)| file.range.is_some() || !profile.format.matches(from));
+
+        if convert_with.is_none() && file.range.is_some() {
+            bail!("tracks split from a cue sheet can only be bundled by converting them");
+        }
+
+        let extension = match convert_with {
+            Some((_, profile)) => profile.format.extension().to_string(),
+            None => from
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+
+        let name = format!(
+            "{:0width$} {} - {}.{extension}",
+            self.added + 1,
+            sanitize(&entry.track_artist_names),
+            sanitize(&entry.track_title),
+            width = self.width,
+        );
+
+        if let Some((transcoder, profile)) = convert_with {
+            let converted = self.temp_dir.join(format!("{}.{extension}", self.added));
+            transcoder.transcode(file, &converted, profile)?;
+
+            let result = File::open(&converted).and_then(|file| self.zip.add(&name, file));
+            let _ = fs::remove_file(&converted);
+            result?;
+        } else {
+            self.zip.add(&name, File::open(from)?)?;
+        }
+
+        self.m3u.push_str(&m3u_entry(entry, &name));
+        self.added += 1;
+
+        Ok(())
+    }
+
+    /// Adds the M3U file, named after the playlist, and finishes the bundle. Returns the number of
+    /// tracks in it.
+    pub fn finish(mut self, playlist_name: &str) -> anyhow::Result<usize> {
+        let m3u_name = format!("{}.m3u8", sanitize(playlist_name));
+        self.zip.add(&m3u_name, self.m3u.as_bytes())?;
+        self.zip.finish()?;
+
+        let _ = fs::remove_dir_all(&self.temp_dir);
+
+        Ok(self.added)
+    }
+}
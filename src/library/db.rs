@@ -1,32 +1,58 @@
-use std::{path::Path, sync::Arc};
+use std::{path::Path, sync::Arc, time::Duration};
 
+use chrono::{DateTime, Utc};
 use gpui::App;
+use rustc_hash::FxHashMap;
 use sqlx::{
-    SqlitePool,
-    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous},
+    SqliteConnection, SqlitePool,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
 };
 use tracing::debug;
 
 use crate::{
-    library::types::{Playlist, PlaylistItem, PlaylistWithCount, TrackStats},
+    library::{
+        radio::{RADIO_LENGTH, RadioTrack, radio_tracks},
+        recap::{Recap, get_recap, list_recap_years},
+        types::{
+            AlbumFormat, AlbumSummary, Audiobook, DeletedPlaylistItem, Play, PlayStats, Playlist,
+            PlaylistItem, PlaylistWithCount, Podcast, PodcastEpisode, QuarantinedFile, Tag,
+            TaggedTrack, TrackSortKeys, TrackStats,
+        },
+    },
+    media::metadata::{TrackFile, custom_tag_name},
     ui::app::Pool,
 };
 
 use super::types::{Album, Artist, Track};
 
-pub async fn create_pool(path: impl AsRef<Path>) -> Result<SqlitePool, sqlx::Error> {
-    debug!("Creating database pool at {:?}", path.as_ref());
-    let options = SqliteConnectOptions::new()
+/// How long a connection will wait for another connection's write lock to be released before
+/// giving up with SQLITE_BUSY.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of connections in the read-only pool.
+const READ_CONNECTIONS: u32 = 4;
+
+fn connect_options(path: impl AsRef<Path>) -> SqliteConnectOptions {
+    SqliteConnectOptions::new()
         .filename(path)
-        .optimize_on_close(true, None)
         .synchronous(SqliteSynchronous::Normal)
         .journal_mode(SqliteJournalMode::Wal)
+        .busy_timeout(BUSY_TIMEOUT)
         .statement_cache_capacity(0)
-        .create_if_missing(true);
-    let pool = SqlitePool::connect_with(options).await?;
+        .pragma("mmap_size", "30000000000")
+}
 
-    sqlx::query("PRAGMA mmap_size = 30000000000")
-        .execute(&pool)
+/// Creates the pool used for writing to the database, and runs any pending migrations. SQLite
+/// only allows one writer at a time, so this pool only has a single connection - writes queue up
+/// in the pool instead of fighting over the database lock.
+pub async fn create_pool(path: impl AsRef<Path>) -> Result<SqlitePool, sqlx::Error> {
+    debug!("Creating database pool at {:?}", path.as_ref());
+    let options = connect_options(path)
+        .optimize_on_close(true, None)
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
         .await?;
 
     sqlx::migrate!("./migrations").run(&pool).await?;
@@ -34,6 +60,20 @@ pub async fn create_pool(path: impl AsRef<Path>) -> Result<SqlitePool, sqlx::Err
     Ok(pool)
 }
 
+/// Creates a read-only pool for the database. In WAL mode, readers are never blocked by the
+/// writer, so queries made through this pool won't wait on the scanner.
+///
+/// The database must already exist (see [create_pool]).
+pub async fn create_read_pool(path: impl AsRef<Path>) -> Result<SqlitePool, sqlx::Error> {
+    debug!("Creating read-only database pool at {:?}", path.as_ref());
+    let options = connect_options(path).read_only(true);
+
+    SqlitePoolOptions::new()
+        .max_connections(READ_CONNECTIONS)
+        .connect_with(options)
+        .await
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AlbumSortMethod {
     TitleAsc,
@@ -48,10 +88,24 @@ pub enum AlbumSortMethod {
     CatalogDesc,
 }
 
+/// Narrows the album list down using the play history.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AlbumFilter {
+    #[default]
+    All,
+    /// Albums with no plays of any of their tracks.
+    NeverPlayed,
+    /// Albums that have been played, but not in over a year.
+    Forgotten,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AlbumMethod {
     FullQuality,
     Thumbnail,
+    /// Skips loading (and decoding) the thumbnail entirely. Use this when the art is displayed
+    /// through an image path instead of the [Album::thumb] field.
+    Metadata,
 }
 
 pub async fn list_albums(
@@ -98,6 +152,193 @@ pub async fn list_albums(
     Ok(albums)
 }
 
+/// Lists the ids of the albums matching the filter, or `None` for [AlbumFilter::All].
+pub async fn list_album_ids_by_filter(
+    pool: &SqlitePool,
+    filter: AlbumFilter,
+) -> Result<Option<Vec<i64>>, sqlx::Error> {
+    let query = match filter {
+        AlbumFilter::All => return Ok(None),
+        AlbumFilter::NeverPlayed => include_str!("../../queries/history/find_unplayed_albums.sql"),
+        AlbumFilter::Forgotten => include_str!("../../queries/history/find_forgotten_albums.sql"),
+    };
+
+    let ids: Vec<i64> = sqlx::query_scalar(query).fetch_all(pool).await?;
+
+    Ok(Some(ids))
+}
+
+/// Lists the ids of the albums whose title or artist name contains the text, ignoring case.
+pub async fn list_album_ids_by_text(
+    pool: &SqlitePool,
+    text: &str,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_album_ids_by_text.sql");
+
+    let ids: Vec<i64> = sqlx::query_scalar(query).bind(text).fetch_all(pool).await?;
+
+    Ok(ids)
+}
+
+/// Lists every album along with its number of tracks and total duration, in no particular order.
+pub async fn list_album_summaries(pool: &SqlitePool) -> Result<Vec<AlbumSummary>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_album_summaries.sql");
+
+    let summaries = sqlx::query_as::<_, AlbumSummary>(query)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(summaries)
+}
+
+pub async fn get_album_summary_by_id(
+    pool: &SqlitePool,
+    album_id: i64,
+) -> Result<Arc<AlbumSummary>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_album_summary_by_id.sql");
+
+    let summary = sqlx::query_as::<_, AlbumSummary>(query)
+        .bind(album_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Arc::new(summary))
+}
+
+/// Lists the tags that at least one track has, in alphabetical order.
+pub async fn list_tags(pool: &SqlitePool) -> Result<Vec<Tag>, sqlx::Error> {
+    let query = include_str!("../../queries/tags/list_tags.sql");
+
+    sqlx::query_as::<_, Tag>(query).fetch_all(pool).await
+}
+
+pub async fn get_tag(pool: &SqlitePool, tag_id: i64) -> Result<Arc<Tag>, sqlx::Error> {
+    let query = include_str!("../../queries/tags/find_tag_by_id.sql");
+
+    let tag = sqlx::query_as::<_, Tag>(query)
+        .bind(tag_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Arc::new(tag))
+}
+
+pub async fn list_track_tags(pool: &SqlitePool, track_id: i64) -> Result<Vec<Tag>, sqlx::Error> {
+    let query = include_str!("../../queries/tags/list_track_tags.sql");
+
+    sqlx::query_as::<_, Tag>(query)
+        .bind(track_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Lists the tracks with the tag, by artist and then by album.
+pub async fn list_tag_track_ids(
+    pool: &SqlitePool,
+    tag_id: i64,
+) -> Result<Arc<Vec<i64>>, sqlx::Error> {
+    let query = include_str!("../../queries/tags/list_tag_track_ids.sql");
+
+    let ids: Vec<i64> = sqlx::query_scalar(query)
+        .bind(tag_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(Arc::new(ids))
+}
+
+/// Tags the track, creating the tag if there isn't one with the same name (ignoring case).
+pub async fn add_track_tag(
+    pool: &SqlitePool,
+    track_id: i64,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    let tag_id: i64 = sqlx::query_scalar(include_str!("../../queries/tags/create_tag.sql"))
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+
+    sqlx::query(include_str!("../../queries/tags/add_track_tag.sql"))
+        .bind(track_id)
+        .bind(tag_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Removes the tag from the track, and deletes the tag if no other track has it.
+pub async fn remove_track_tag(
+    pool: &SqlitePool,
+    track_id: i64,
+    tag_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(include_str!("../../queries/tags/remove_track_tag.sql"))
+        .bind(track_id)
+        .bind(tag_id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query(include_str!("../../queries/tags/delete_unused_tags.sql"))
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Looks up the durations of the tracks, in seconds. Tracks that aren't in the library are left
+/// out.
+pub async fn get_track_durations(
+    pool: &SqlitePool,
+    track_ids: &[i64],
+) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_track_durations.sql");
+    let ids = serde_json::to_string(track_ids).expect("track ids can be serialized");
+
+    let durations = sqlx::query_as::<_, (i64, i64)>(query)
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(durations)
+}
+
+/// Looks up what the tracks can be filtered and sorted by. Tracks that aren't in the library are
+/// left out.
+pub async fn list_track_sort_keys(
+    pool: &SqlitePool,
+    track_ids: &[i64],
+) -> Result<Vec<TrackSortKeys>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_track_sort_keys.sql");
+    let ids = serde_json::to_string(track_ids).expect("track ids can be serialized");
+
+    let keys = sqlx::query_as::<_, TrackSortKeys>(query)
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(keys)
+}
+
+/// Works out the badges shown for each of the albums.
+pub async fn list_album_formats(
+    pool: &SqlitePool,
+    album_ids: &[i64],
+) -> Result<FxHashMap<i64, AlbumFormat>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_album_formats.sql");
+    let ids = serde_json::to_string(album_ids).expect("album ids can be serialized");
+
+    let formats = sqlx::query_as::<_, AlbumFormat>(query)
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(formats
+        .into_iter()
+        .map(|format| (format.album_id, format))
+        .collect())
+}
+
 pub async fn list_tracks_in_album(
     pool: &SqlitePool,
     album_id: i64,
@@ -114,23 +355,39 @@ pub async fn list_tracks_in_album(
     Ok(albums)
 }
 
+pub async fn list_track_ids_in_album(
+    pool: &SqlitePool,
+    album_id: i64,
+) -> Result<Arc<Vec<i64>>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_track_ids_in_album.sql");
+
+    let ids = Arc::new(
+        sqlx::query_scalar::<_, i64>(query)
+            .bind(album_id)
+            .fetch_all(pool)
+            .await?,
+    );
+
+    Ok(ids)
+}
+
 pub async fn get_album_by_id(
     pool: &SqlitePool,
     album_id: i64,
     method: AlbumMethod,
 ) -> Result<Arc<Album>, sqlx::Error> {
-    let query = include_str!("../../queries/library/find_album_by_id.sql");
+    let query = match method {
+        AlbumMethod::Metadata => {
+            include_str!("../../queries/library/find_album_metadata_by_id.sql")
+        }
+        _ => include_str!("../../queries/library/find_album_by_id.sql"),
+    };
 
     let album: Arc<Album> = Arc::new({
         let mut data: Album = sqlx::query_as(query).bind(album_id).fetch_one(pool).await?;
 
-        match method {
-            AlbumMethod::FullQuality => {
-                data.thumb = None;
-            }
-            AlbumMethod::Thumbnail => {
-                data.image = None;
-            }
+        if method == AlbumMethod::FullQuality {
+            data.thumb = None;
         }
 
         data
@@ -171,6 +428,174 @@ pub async fn get_artist_by_id(
     Ok(artist)
 }
 
+/// Locks or unlocks the track's metadata. Rescans leave the metadata of locked tracks alone
+/// unless the file has changed.
+pub async fn set_metadata_locked(
+    pool: &SqlitePool,
+    track_id: i64,
+    locked: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(include_str!("../../queries/edit/set_metadata_locked.sql"))
+        .bind(track_id)
+        .bind(locked)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets how much louder or quieter the track is played, in decibels, or clears it if `None`.
+pub async fn set_volume_offset(
+    pool: &SqlitePool,
+    track_id: i64,
+    offset: Option<f64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(include_str!("../../queries/edit/set_volume_offset.sql"))
+        .bind(track_id)
+        .bind(offset)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Moves the tracks of the `removed` album into the `kept` one and deletes the `removed` album.
+/// The kept album's details stay as they are, and its art is replaced by the removed album's if
+/// `art_from_removed` is set. The tracks are locked, so that scanning them again doesn't split
+/// the albums back apart.
+pub async fn merge_albums(
+    pool: &SqlitePool,
+    kept: i64,
+    removed: i64,
+    art_from_removed: bool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    merge_album_into(&mut tx, kept, removed, art_from_removed).await?;
+
+    sqlx::query(include_str!("../../queries/edit/lock_album_tracks.sql"))
+        .bind(kept)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+async fn merge_album_into(
+    conn: &mut SqliteConnection,
+    kept: i64,
+    removed: i64,
+    art_from_removed: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(include_str!("../../queries/edit/merge_album_tracks.sql"))
+        .bind(kept)
+        .bind(removed)
+        .execute(&mut *conn)
+        .await?;
+
+    if art_from_removed {
+        sqlx::query(include_str!("../../queries/edit/copy_album_art.sql"))
+            .bind(kept)
+            .bind(removed)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    sqlx::query(include_str!("../../queries/edit/merge_album_paths.sql"))
+        .bind(kept)
+        .bind(removed)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(include_str!("../../queries/edit/delete_album.sql"))
+        .bind(removed)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Moves the albums of the `removed` artist to the `kept` one and deletes the `removed` artist.
+/// Albums that both artists have under the same title are merged, keeping the kept artist's.
+pub async fn merge_artists(pool: &SqlitePool, kept: i64, removed: i64) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    merge_artist_into(&mut tx, kept, removed).await?;
+    tx.commit().await
+}
+
+pub(super) async fn merge_artist_into(
+    conn: &mut SqliteConnection,
+    kept: i64,
+    removed: i64,
+) -> Result<(), sqlx::Error> {
+    // the removed artist's name is needed to find their tracks, so this is done before any of
+    // their albums are deleted
+    sqlx::query(include_str!("../../queries/edit/rename_track_artists.sql"))
+        .bind(kept)
+        .bind(removed)
+        .execute(&mut *conn)
+        .await?;
+
+    let shared: Vec<(i64, i64)> =
+        sqlx::query_as(include_str!("../../queries/edit/list_shared_albums.sql"))
+            .bind(kept)
+            .bind(removed)
+            .fetch_all(&mut *conn)
+            .await?;
+
+    for (removed_album, kept_album) in shared {
+        merge_album_into(conn, kept_album, removed_album, false).await?;
+    }
+
+    sqlx::query(include_str!("../../queries/edit/move_artist_albums.sql"))
+        .bind(kept)
+        .bind(removed)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(include_str!("../../queries/scan/delete_artist.sql"))
+        .bind(removed)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Moves the tracks out of the album into a new album with the given title, by the same artist
+/// and with the same details and art. The moved tracks are locked, so that scanning them again
+/// doesn't put them back. Returns the ID of the new album.
+pub async fn split_album(
+    pool: &SqlitePool,
+    album_id: i64,
+    track_ids: &[i64],
+    title: &str,
+) -> Result<i64, sqlx::Error> {
+    let ids = serde_json::to_string(track_ids).expect("track ids can be serialized");
+    let mut tx = pool.begin().await?;
+
+    let new_id: i64 = sqlx::query_scalar(include_str!("../../queries/edit/split_album.sql"))
+        .bind(album_id)
+        .bind(title)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    sqlx::query(include_str!("../../queries/edit/move_album_tracks.sql"))
+        .bind(new_id)
+        .bind(album_id)
+        .bind(ids)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(include_str!("../../queries/edit/add_album_paths.sql"))
+        .bind(new_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(new_id)
+}
+
 pub async fn get_track_by_id(pool: &SqlitePool, track_id: i64) -> Result<Arc<Track>, sqlx::Error> {
     let query = include_str!("../../queries/library/find_track_by_id.sql");
 
@@ -179,6 +604,122 @@ pub async fn get_track_by_id(pool: &SqlitePool, track_id: i64) -> Result<Arc<Tra
     Ok(track)
 }
 
+pub async fn get_track_by_location(
+    pool: &SqlitePool,
+    location: &Path,
+) -> Result<Option<Arc<Track>>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_track_by_location.sql");
+
+    let track: Option<Track> = sqlx::query_as(query)
+        .bind(location.to_str())
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(track.map(Arc::new))
+}
+
+/// Looks up where the audio of the track stored under the location is. Locations that aren't in
+/// the library are taken to be whole files.
+pub async fn find_track_file(pool: &SqlitePool, location: &Path) -> Result<TrackFile, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_track_file.sql");
+
+    let range: Option<(String, f64, f64)> = sqlx::query_as(query)
+        .bind(location.to_str())
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(match range {
+        Some((file, start, end)) => TrackFile {
+            location: location.to_path_buf(),
+            path: file.into(),
+            range: Some((start, end)),
+        },
+        None => TrackFile::whole(location),
+    })
+}
+
+/// Lists the albums by the given artist, oldest first.
+pub async fn list_albums_by_artist(
+    pool: &SqlitePool,
+    artist_id: i64,
+) -> Result<Arc<Vec<Album>>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_albums_by_artist.sql");
+
+    let albums = sqlx::query_as(query)
+        .bind(artist_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(Arc::new(albums))
+}
+
+/// Lists the years albums in the library were released in, newest first, with the number of
+/// albums released in each.
+pub async fn list_album_years(pool: &SqlitePool) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    let query = include_str!("../../queries/library/list_album_years.sql");
+
+    let years = sqlx::query_as::<_, (i64, i64)>(query)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(years)
+}
+
+/// Lists the albums released between the two years (inclusive), oldest first.
+pub async fn list_albums_in_years(
+    pool: &SqlitePool,
+    start: i64,
+    end: i64,
+) -> Result<Arc<Vec<Album>>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_albums_in_years.sql");
+
+    let albums = sqlx::query_as(query)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(Arc::new(albums))
+}
+
+/// Lists the record labels albums in the library were released on, with the id of one of the
+/// label's albums (which [list_albums_by_label] takes) and the number of albums on the label.
+pub async fn list_labels(pool: &SqlitePool) -> Result<Vec<(String, i64, i64)>, sqlx::Error> {
+    let query = include_str!("../../queries/library/list_labels.sql");
+
+    let labels = sqlx::query_as::<_, (String, i64, i64)>(query)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(labels)
+}
+
+/// Lists the albums released on the same record label as the given album, by catalog number.
+pub async fn list_albums_by_label(
+    pool: &SqlitePool,
+    album_id: i64,
+) -> Result<Arc<Vec<Album>>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_albums_by_label.sql");
+
+    let albums = sqlx::query_as(query).bind(album_id).fetch_all(pool).await?;
+
+    Ok(Arc::new(albums))
+}
+
+/// Lists all artists with at least one album for searching. Returns a vector of tuples containing
+/// the id, name, and image hash.
+pub async fn list_artists_search(
+    pool: &SqlitePool,
+) -> Result<Vec<(u32, String, Option<String>)>, sqlx::Error> {
+    let query = include_str!("../../queries/library/find_artists_search.sql");
+
+    let artists = sqlx::query_as::<_, (u32, String, Option<String>)>(query)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(artists)
+}
+
 /// Lists all albums for searching. Returns a vector of tuples containing the id, name, and artist
 /// name.
 pub async fn list_albums_search(
@@ -211,6 +752,9 @@ pub async fn add_playlist_item(
 }
 
 pub async fn create_playlist(pool: &SqlitePool, name: &str) -> Result<i64, sqlx::Error> {
+    let purge_query = include_str!("../../queries/playlist/purge_deleted_named.sql");
+    sqlx::query(purge_query).bind(name).execute(pool).await?;
+
     let query = include_str!("../../queries/playlist/create_playlist.sql");
 
     let playlist_id = sqlx::query(query)
@@ -222,6 +766,7 @@ pub async fn create_playlist(pool: &SqlitePool, name: &str) -> Result<i64, sqlx:
     Ok(playlist_id)
 }
 
+/// Moves the playlist to the recently deleted section.
 pub async fn delete_playlist(pool: &SqlitePool, playlist_id: i64) -> Result<(), sqlx::Error> {
     let query = include_str!("../../queries/playlist/delete_playlist.sql");
 
@@ -230,6 +775,23 @@ pub async fn delete_playlist(pool: &SqlitePool, playlist_id: i64) -> Result<(),
     Ok(())
 }
 
+/// Sets the playlist's description, or removes it if `None`.
+pub async fn set_playlist_description(
+    pool: &SqlitePool,
+    playlist_id: i64,
+    description: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/playlist/set_description.sql");
+
+    sqlx::query(query)
+        .bind(playlist_id)
+        .bind(description)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn get_all_playlists(
     pool: &SqlitePool,
 ) -> Result<Arc<Vec<PlaylistWithCount>>, sqlx::Error> {
@@ -314,15 +876,73 @@ pub async fn move_playlist_item(
     Ok(())
 }
 
+/// Moves the item to the recently deleted section. It keeps its position, so that it goes back to
+/// the same place in the playlist if it's restored.
 pub async fn remove_playlist_item(pool: &SqlitePool, item_id: i64) -> Result<(), sqlx::Error> {
     let query = include_str!("../../queries/playlist/remove_track.sql");
-    let item = get_playlist_item(pool, item_id).await?;
 
-    sqlx::query(query)
-        .bind(item.position)
-        .bind(item_id)
-        .execute(pool)
-        .await?;
+    sqlx::query(query).bind(item_id).execute(pool).await?;
+
+    Ok(())
+}
+
+pub async fn list_deleted_playlists(
+    pool: &SqlitePool,
+) -> Result<Vec<PlaylistWithCount>, sqlx::Error> {
+    let query = include_str!("../../queries/playlist/list_deleted_playlists.sql");
+
+    sqlx::query_as(query).fetch_all(pool).await
+}
+
+pub async fn list_deleted_playlist_items(
+    pool: &SqlitePool,
+) -> Result<Vec<DeletedPlaylistItem>, sqlx::Error> {
+    let query = include_str!("../../queries/playlist/list_deleted_items.sql");
+
+    sqlx::query_as(query).fetch_all(pool).await
+}
+
+/// The number of playlists and items in the recently deleted section.
+pub async fn count_deleted_playlist_entries(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    let query = include_str!("../../queries/playlist/count_deleted.sql");
+
+    sqlx::query_scalar(query).fetch_one(pool).await
+}
+
+pub async fn restore_playlist(pool: &SqlitePool, playlist_id: i64) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/playlist/restore_playlist.sql");
+
+    sqlx::query(query).bind(playlist_id).execute(pool).await?;
+
+    Ok(())
+}
+
+pub async fn restore_playlist_item(pool: &SqlitePool, item_id: i64) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/playlist/restore_track.sql");
+
+    sqlx::query(query).bind(item_id).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Permanently removes playlists and items that were deleted more than 30 days ago.
+pub async fn purge_deleted_playlists(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/playlist/purge_deleted.sql");
+
+    sqlx::query(query).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Removes every item in the playlist whose track appears earlier in the playlist. The removed
+/// items go to the recently deleted section like any other.
+pub async fn remove_playlist_duplicates(
+    pool: &SqlitePool,
+    playlist_id: i64,
+) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/playlist/remove_duplicates.sql");
+
+    sqlx::query(query).bind(playlist_id).execute(pool).await?;
 
     Ok(())
 }
@@ -362,9 +982,214 @@ pub async fn playlist_has_track(
     Ok(has_track)
 }
 
+pub async fn record_play(
+    pool: &SqlitePool,
+    track_id: i64,
+    played_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/history/record_play.sql");
+
+    sqlx::query(query)
+        .bind(track_id)
+        .bind(played_at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_track_play_stats(
+    pool: &SqlitePool,
+    track_id: i64,
+) -> Result<PlayStats, sqlx::Error> {
+    let query = include_str!("../../queries/history/get_track_play_stats.sql");
+
+    let stats: PlayStats = sqlx::query_as(query).bind(track_id).fetch_one(pool).await?;
+
+    Ok(stats)
+}
+
+/// Lists the most recent plays in the play history, most recent first.
+pub async fn list_plays(pool: &SqlitePool, limit: i64) -> Result<Vec<Play>, sqlx::Error> {
+    let query = include_str!("../../queries/history/list_plays.sql");
+
+    let plays: Vec<Play> = sqlx::query_as(query).bind(limit).fetch_all(pool).await?;
+
+    Ok(plays)
+}
+
+pub async fn delete_play(pool: &SqlitePool, play_id: i64) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/history/delete_play.sql");
+
+    sqlx::query(query).bind(play_id).execute(pool).await?;
+
+    Ok(())
+}
+
+pub async fn get_album_play_count(pool: &SqlitePool, album_id: i64) -> Result<i64, sqlx::Error> {
+    let query = include_str!("../../queries/history/get_album_play_count.sql");
+
+    let count: i64 = sqlx::query_scalar(query)
+        .bind(album_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
+}
+
+/// Lists the files that couldn't be read during scanning, sorted by location.
+pub async fn list_quarantined_files(
+    pool: &SqlitePool,
+) -> Result<Arc<Vec<QuarantinedFile>>, sqlx::Error> {
+    let query = include_str!("../../queries/quarantine/list_files.sql");
+
+    let files: Vec<QuarantinedFile> = sqlx::query_as(query).fetch_all(pool).await?;
+
+    Ok(Arc::new(files))
+}
+
+/// Finds tracks that have the custom tag, optionally only those where it has the given value.
+/// Values are compared ignoring case.
+pub async fn find_tracks_by_field(
+    pool: &SqlitePool,
+    name: &str,
+    value: Option<&str>,
+) -> Result<Vec<TaggedTrack>, sqlx::Error> {
+    sqlx::query_as(include_str!(
+        "../../queries/library/find_tracks_by_field.sql"
+    ))
+    .bind(custom_tag_name(name))
+    .bind(value)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn list_podcasts(pool: &SqlitePool) -> Result<Vec<Podcast>, sqlx::Error> {
+    let query = include_str!("../../queries/podcast/list_podcasts.sql");
+
+    sqlx::query_as(query).fetch_all(pool).await
+}
+
+pub async fn get_podcast(pool: &SqlitePool, podcast_id: i64) -> Result<Podcast, sqlx::Error> {
+    let query = include_str!("../../queries/podcast/get_podcast.sql");
+
+    sqlx::query_as(query).bind(podcast_id).fetch_one(pool).await
+}
+
+pub async fn list_podcast_episodes(
+    pool: &SqlitePool,
+    podcast_id: i64,
+) -> Result<Vec<PodcastEpisode>, sqlx::Error> {
+    let query = include_str!("../../queries/podcast/list_episodes.sql");
+
+    sqlx::query_as(query).bind(podcast_id).fetch_all(pool).await
+}
+
+pub async fn get_podcast_episode(
+    pool: &SqlitePool,
+    episode_id: i64,
+) -> Result<PodcastEpisode, sqlx::Error> {
+    let query = include_str!("../../queries/podcast/get_episode.sql");
+
+    sqlx::query_as(query).bind(episode_id).fetch_one(pool).await
+}
+
+/// Finds the episode that was downloaded to the file, if any.
+pub async fn find_podcast_episode_by_file(
+    pool: &SqlitePool,
+    path: &str,
+) -> Result<Option<PodcastEpisode>, sqlx::Error> {
+    let query = include_str!("../../queries/podcast/find_episode_by_file.sql");
+
+    sqlx::query_as(query).bind(path).fetch_optional(pool).await
+}
+
+pub async fn set_podcast_episode_position(
+    pool: &SqlitePool,
+    episode_id: i64,
+    position: i64,
+) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/podcast/set_episode_position.sql");
+
+    sqlx::query(query)
+        .bind(episode_id)
+        .bind(position)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Marks the episode as listened to or not. Either way, it starts from the beginning next time.
+pub async fn set_podcast_episode_listened(
+    pool: &SqlitePool,
+    episode_id: i64,
+    listened: bool,
+) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/podcast/set_episode_listened.sql");
+
+    sqlx::query(query)
+        .bind(episode_id)
+        .bind(listened)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Marks every episode of the podcast as seen, so that they're no longer counted as new.
+pub async fn mark_podcast_seen(pool: &SqlitePool, podcast_id: i64) -> Result<(), sqlx::Error> {
+    let query = include_str!("../../queries/podcast/mark_podcast_seen.sql");
+
+    sqlx::query(query).bind(podcast_id).execute(pool).await?;
+
+    Ok(())
+}
+
+pub async fn count_new_podcast_episodes(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    let query = include_str!("../../queries/podcast/count_new_episodes.sql");
+
+    sqlx::query_scalar(query).fetch_one(pool).await
+}
+
+pub async fn list_audiobooks(pool: &SqlitePool) -> Result<Vec<Audiobook>, sqlx::Error> {
+    let query = include_str!("../../queries/audiobooks/list_audiobooks.sql");
+
+    sqlx::query_as(query).fetch_all(pool).await
+}
+
+/// Whether the file is an audiobook chapter in the library.
+pub async fn is_audiobook(pool: &SqlitePool, path: &str) -> Result<bool, sqlx::Error> {
+    let query = include_str!("../../queries/audiobooks/is_audiobook.sql");
+
+    let audiobook: Option<bool> = sqlx::query_scalar(query)
+        .bind(path)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(audiobook.unwrap_or(false))
+}
+
 pub trait LibraryAccess {
     fn list_albums(&self, sort_method: AlbumSortMethod) -> Result<Vec<(u32, String)>, sqlx::Error>;
+    fn list_album_ids_by_filter(
+        &self,
+        filter: AlbumFilter,
+    ) -> Result<Option<Vec<i64>>, sqlx::Error>;
+    fn list_album_ids_by_text(&self, text: &str) -> Result<Vec<i64>, sqlx::Error>;
+    fn list_album_summaries(&self) -> Result<Vec<AlbumSummary>, sqlx::Error>;
+    fn list_radio_tracks(&self, track_id: i64) -> Result<Vec<RadioTrack>, sqlx::Error>;
+    fn list_recap_years(&self) -> Result<Vec<i64>, sqlx::Error>;
+    fn get_recap(&self, year: i64) -> Result<Recap, sqlx::Error>;
+    fn list_tags(&self) -> Result<Vec<Tag>, sqlx::Error>;
+    fn get_tag(&self, tag_id: i64) -> Result<Arc<Tag>, sqlx::Error>;
+    fn list_track_tags(&self, track_id: i64) -> Result<Vec<Tag>, sqlx::Error>;
+    fn list_tag_track_ids(&self, tag_id: i64) -> Result<Arc<Vec<i64>>, sqlx::Error>;
+    fn add_track_tag(&self, track_id: i64, name: &str) -> Result<(), sqlx::Error>;
+    fn remove_track_tag(&self, track_id: i64, tag_id: i64) -> Result<(), sqlx::Error>;
+    fn get_album_summary_by_id(&self, album_id: i64) -> Result<Arc<AlbumSummary>, sqlx::Error>;
     fn list_tracks_in_album(&self, album_id: i64) -> Result<Arc<Vec<Track>>, sqlx::Error>;
+    fn list_track_ids_in_album(&self, album_id: i64) -> Result<Arc<Vec<i64>>, sqlx::Error>;
     fn get_album_by_id(
         &self,
         album_id: i64,
@@ -373,10 +1198,44 @@ pub trait LibraryAccess {
     fn get_artist_name_by_id(&self, artist_id: i64) -> Result<Arc<String>, sqlx::Error>;
     fn get_artist_by_id(&self, artist_id: i64) -> Result<Arc<Artist>, sqlx::Error>;
     fn get_track_by_id(&self, track_id: i64) -> Result<Arc<Track>, sqlx::Error>;
+    fn set_metadata_locked(&self, track_id: i64, locked: bool) -> Result<(), sqlx::Error>;
+    fn set_volume_offset(&self, track_id: i64, offset: Option<f64>) -> Result<(), sqlx::Error>;
+    fn merge_albums(
+        &self,
+        kept: i64,
+        removed: i64,
+        art_from_removed: bool,
+    ) -> Result<(), sqlx::Error>;
+    fn split_album(
+        &self,
+        album_id: i64,
+        track_ids: &[i64],
+        title: &str,
+    ) -> Result<i64, sqlx::Error>;
+    fn merge_artists(&self, kept: i64, removed: i64) -> Result<(), sqlx::Error>;
+    fn get_track_durations(&self, track_ids: &[i64]) -> Result<Vec<(i64, i64)>, sqlx::Error>;
+    fn list_track_sort_keys(&self, track_ids: &[i64]) -> Result<Vec<TrackSortKeys>, sqlx::Error>;
+    fn list_album_formats(
+        &self,
+        album_ids: &[i64],
+    ) -> Result<FxHashMap<i64, AlbumFormat>, sqlx::Error>;
+    fn get_track_by_location(&self, location: &Path) -> Result<Option<Arc<Track>>, sqlx::Error>;
+    fn find_track_file(&self, location: &Path) -> Result<TrackFile, sqlx::Error>;
     fn list_albums_search(&self) -> Result<Vec<(u32, String, String)>, sqlx::Error>;
+    fn list_albums_by_artist(&self, artist_id: i64) -> Result<Arc<Vec<Album>>, sqlx::Error>;
+    fn list_album_years(&self) -> Result<Vec<(i64, i64)>, sqlx::Error>;
+    fn list_labels(&self) -> Result<Vec<(String, i64, i64)>, sqlx::Error>;
+    fn list_albums_by_label(&self, album_id: i64) -> Result<Arc<Vec<Album>>, sqlx::Error>;
+    fn list_albums_in_years(&self, start: i64, end: i64) -> Result<Arc<Vec<Album>>, sqlx::Error>;
+    fn list_artists_search(&self) -> Result<Vec<(u32, String, Option<String>)>, sqlx::Error>;
     fn add_playlist_item(&self, playlist_id: i64, track_id: i64) -> Result<i64, sqlx::Error>;
     fn create_playlist(&self, name: &str) -> Result<i64, sqlx::Error>;
     fn delete_playlist(&self, playlist_id: i64) -> Result<(), sqlx::Error>;
+    fn set_playlist_description(
+        &self,
+        playlist_id: i64,
+        description: Option<&str>,
+    ) -> Result<(), sqlx::Error>;
     fn get_all_playlists(&self) -> Result<Arc<Vec<PlaylistWithCount>>, sqlx::Error>;
     fn get_playlist(&self, playlist_id: i64) -> Result<Arc<Playlist>, sqlx::Error>;
     fn get_playlist_track_files(&self, playlist_id: i64) -> Result<Arc<Vec<String>>, sqlx::Error>;
@@ -386,6 +1245,12 @@ pub trait LibraryAccess {
     ) -> Result<Arc<Vec<(i64, i64, i64)>>, sqlx::Error>;
     fn move_playlist_item(&self, item_id: i64, new_position: i64) -> Result<(), sqlx::Error>;
     fn remove_playlist_item(&self, item_id: i64) -> Result<(), sqlx::Error>;
+    fn remove_playlist_duplicates(&self, playlist_id: i64) -> Result<(), sqlx::Error>;
+    fn list_deleted_playlists(&self) -> Result<Vec<PlaylistWithCount>, sqlx::Error>;
+    fn list_deleted_playlist_items(&self) -> Result<Vec<DeletedPlaylistItem>, sqlx::Error>;
+    fn count_deleted_playlist_entries(&self) -> Result<i64, sqlx::Error>;
+    fn restore_playlist(&self, playlist_id: i64) -> Result<(), sqlx::Error>;
+    fn restore_playlist_item(&self, item_id: i64) -> Result<(), sqlx::Error>;
     fn get_playlist_item(&self, item_id: i64) -> Result<PlaylistItem, sqlx::Error>;
     fn get_track_stats(&self) -> Result<Arc<TrackStats>, sqlx::Error>;
     fn playlist_has_track(
@@ -393,17 +1258,143 @@ pub trait LibraryAccess {
         playlist_id: i64,
         track_id: i64,
     ) -> Result<Option<i64>, sqlx::Error>;
+    fn get_track_play_stats(&self, track_id: i64) -> Result<PlayStats, sqlx::Error>;
+    fn get_album_play_count(&self, album_id: i64) -> Result<i64, sqlx::Error>;
+    fn list_plays(&self, limit: i64) -> Result<Vec<Play>, sqlx::Error>;
+    fn delete_play(&self, play_id: i64) -> Result<(), sqlx::Error>;
+    fn list_quarantined_files(&self) -> Result<Arc<Vec<QuarantinedFile>>, sqlx::Error>;
+    fn list_podcasts(&self) -> Result<Vec<Podcast>, sqlx::Error>;
+    fn get_podcast(&self, podcast_id: i64) -> Result<Podcast, sqlx::Error>;
+    fn list_podcast_episodes(&self, podcast_id: i64) -> Result<Vec<PodcastEpisode>, sqlx::Error>;
+    fn get_podcast_episode(&self, episode_id: i64) -> Result<PodcastEpisode, sqlx::Error>;
+    fn set_podcast_episode_listened(
+        &self,
+        episode_id: i64,
+        listened: bool,
+    ) -> Result<(), sqlx::Error>;
+    fn mark_podcast_seen(&self, podcast_id: i64) -> Result<(), sqlx::Error>;
+    fn count_new_podcast_episodes(&self) -> Result<i64, sqlx::Error>;
+    fn list_audiobooks(&self) -> Result<Vec<Audiobook>, sqlx::Error>;
+    fn is_audiobook(&self, path: &Path) -> Result<bool, sqlx::Error>;
 }
 
 impl LibraryAccess for App {
     fn list_albums(&self, sort_method: AlbumSortMethod) -> Result<Vec<(u32, String)>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(list_albums(&pool.0, sort_method))
+        crate::RUNTIME.block_on(list_albums(&pool.read, sort_method))
+    }
+
+    fn list_album_ids_by_filter(
+        &self,
+        filter: AlbumFilter,
+    ) -> Result<Option<Vec<i64>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_album_ids_by_filter(&pool.read, filter))
+    }
+
+    fn list_album_ids_by_text(&self, text: &str) -> Result<Vec<i64>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_album_ids_by_text(&pool.read, text))
+    }
+
+    fn list_album_summaries(&self) -> Result<Vec<AlbumSummary>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_album_summaries(&pool.read))
+    }
+
+    fn get_album_summary_by_id(&self, album_id: i64) -> Result<Arc<AlbumSummary>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_album_summary_by_id(&pool.read, album_id))
+    }
+
+    fn list_radio_tracks(&self, track_id: i64) -> Result<Vec<RadioTrack>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(radio_tracks(&pool.read, track_id, RADIO_LENGTH))
+    }
+
+    fn list_recap_years(&self) -> Result<Vec<i64>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_recap_years(&pool.read))
+    }
+
+    fn get_recap(&self, year: i64) -> Result<Recap, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_recap(&pool.read, year))
+    }
+
+    fn list_tags(&self) -> Result<Vec<Tag>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_tags(&pool.read))
+    }
+
+    fn get_tag(&self, tag_id: i64) -> Result<Arc<Tag>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_tag(&pool.read, tag_id))
+    }
+
+    fn list_track_tags(&self, track_id: i64) -> Result<Vec<Tag>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_track_tags(&pool.read, track_id))
+    }
+
+    fn list_tag_track_ids(&self, tag_id: i64) -> Result<Arc<Vec<i64>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_tag_track_ids(&pool.read, tag_id))
+    }
+
+    fn add_track_tag(&self, track_id: i64, name: &str) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(add_track_tag(&pool.write, track_id, name))
+    }
+
+    fn remove_track_tag(&self, track_id: i64, tag_id: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(remove_track_tag(&pool.write, track_id, tag_id))
+    }
+
+    fn set_metadata_locked(&self, track_id: i64, locked: bool) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(set_metadata_locked(&pool.write, track_id, locked))
+    }
+
+    fn set_volume_offset(&self, track_id: i64, offset: Option<f64>) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(set_volume_offset(&pool.write, track_id, offset))
+    }
+
+    fn merge_albums(
+        &self,
+        kept: i64,
+        removed: i64,
+        art_from_removed: bool,
+    ) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(merge_albums(&pool.write, kept, removed, art_from_removed))
+    }
+
+    fn split_album(
+        &self,
+        album_id: i64,
+        track_ids: &[i64],
+        title: &str,
+    ) -> Result<i64, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(split_album(&pool.write, album_id, track_ids, title))
+    }
+
+    fn merge_artists(&self, kept: i64, removed: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(merge_artists(&pool.write, kept, removed))
     }
 
     fn list_tracks_in_album(&self, album_id: i64) -> Result<Arc<Vec<Track>>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(list_tracks_in_album(&pool.0, album_id))
+        crate::RUNTIME.block_on(list_tracks_in_album(&pool.read, album_id))
+    }
+
+    fn list_track_ids_in_album(&self, album_id: i64) -> Result<Arc<Vec<i64>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_track_ids_in_album(&pool.read, album_id))
     }
 
     fn get_album_by_id(
@@ -412,59 +1403,132 @@ impl LibraryAccess for App {
         method: AlbumMethod,
     ) -> Result<Arc<Album>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_album_by_id(&pool.0, album_id, method))
+        crate::RUNTIME.block_on(get_album_by_id(&pool.read, album_id, method))
     }
 
     fn get_artist_name_by_id(&self, artist_id: i64) -> Result<Arc<String>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_artist_name_by_id(&pool.0, artist_id))
+        crate::RUNTIME.block_on(get_artist_name_by_id(&pool.read, artist_id))
     }
 
     fn get_artist_by_id(&self, artist_id: i64) -> Result<Arc<Artist>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_artist_by_id(&pool.0, artist_id))
+        crate::RUNTIME.block_on(get_artist_by_id(&pool.read, artist_id))
     }
 
     fn get_track_by_id(&self, track_id: i64) -> Result<Arc<Track>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_track_by_id(&pool.0, track_id))
+        crate::RUNTIME.block_on(get_track_by_id(&pool.read, track_id))
+    }
+
+    fn get_track_durations(&self, track_ids: &[i64]) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_track_durations(&pool.read, track_ids))
+    }
+
+    fn list_track_sort_keys(&self, track_ids: &[i64]) -> Result<Vec<TrackSortKeys>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_track_sort_keys(&pool.read, track_ids))
+    }
+
+    fn list_album_formats(
+        &self,
+        album_ids: &[i64],
+    ) -> Result<FxHashMap<i64, AlbumFormat>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_album_formats(&pool.read, album_ids))
+    }
+
+    fn get_track_by_location(&self, location: &Path) -> Result<Option<Arc<Track>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_track_by_location(&pool.read, location))
+    }
+
+    fn find_track_file(&self, location: &Path) -> Result<TrackFile, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(find_track_file(&pool.read, location))
     }
 
     /// Lists all albums for searching. Returns a vector of tuples containing the id, name, and artist
     /// name.
     fn list_albums_search(&self) -> Result<Vec<(u32, String, String)>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(list_albums_search(&pool.0))
+        crate::RUNTIME.block_on(list_albums_search(&pool.read))
+    }
+
+    fn list_albums_by_artist(&self, artist_id: i64) -> Result<Arc<Vec<Album>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_albums_by_artist(&pool.read, artist_id))
+    }
+
+    fn list_album_years(&self) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_album_years(&pool.read))
+    }
+
+    fn list_labels(&self) -> Result<Vec<(String, i64, i64)>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_labels(&pool.read))
+    }
+
+    fn list_albums_by_label(&self, album_id: i64) -> Result<Arc<Vec<Album>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_albums_by_label(&pool.read, album_id))
+    }
+
+    fn list_albums_in_years(&self, start: i64, end: i64) -> Result<Arc<Vec<Album>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_albums_in_years(&pool.read, start, end))
+    }
+
+    /// Lists all artists with at least one album for searching. Returns a vector of tuples
+    /// containing the id, name, and image hash.
+    fn list_artists_search(&self) -> Result<Vec<(u32, String, Option<String>)>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_artists_search(&pool.read))
     }
 
     fn add_playlist_item(&self, playlist_id: i64, track_id: i64) -> Result<i64, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(add_playlist_item(&pool.0, playlist_id, track_id))
+        crate::RUNTIME.block_on(add_playlist_item(&pool.write, playlist_id, track_id))
     }
 
     fn create_playlist(&self, name: &str) -> Result<i64, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(create_playlist(&pool.0, name))
+        crate::RUNTIME.block_on(create_playlist(&pool.write, name))
     }
 
     fn delete_playlist(&self, playlist_id: i64) -> Result<(), sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(delete_playlist(&pool.0, playlist_id))
+        crate::RUNTIME.block_on(delete_playlist(&pool.write, playlist_id))
+    }
+
+    fn set_playlist_description(
+        &self,
+        playlist_id: i64,
+        description: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(set_playlist_description(
+            &pool.write,
+            playlist_id,
+            description,
+        ))
     }
 
     fn get_all_playlists(&self) -> Result<Arc<Vec<PlaylistWithCount>>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_all_playlists(&pool.0))
+        crate::RUNTIME.block_on(get_all_playlists(&pool.read))
     }
 
     fn get_playlist(&self, playlist_id: i64) -> Result<Arc<Playlist>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_playlist(&pool.0, playlist_id))
+        crate::RUNTIME.block_on(get_playlist(&pool.read, playlist_id))
     }
 
     fn get_playlist_track_files(&self, playlist_id: i64) -> Result<Arc<Vec<String>>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_playlist_track_files(&pool.0, playlist_id))
+        crate::RUNTIME.block_on(get_playlist_track_files(&pool.read, playlist_id))
     }
 
     fn get_playlist_tracks(
@@ -472,27 +1536,57 @@ impl LibraryAccess for App {
         playlist_id: i64,
     ) -> Result<Arc<Vec<(i64, i64, i64)>>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_playlist_tracks(&pool.0, playlist_id))
+        crate::RUNTIME.block_on(get_playlist_tracks(&pool.read, playlist_id))
     }
 
     fn move_playlist_item(&self, item_id: i64, new_position: i64) -> Result<(), sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(move_playlist_item(&pool.0, item_id, new_position))
+        crate::RUNTIME.block_on(move_playlist_item(&pool.write, item_id, new_position))
     }
 
     fn remove_playlist_item(&self, item_id: i64) -> Result<(), sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(remove_playlist_item(&pool.0, item_id))
+        crate::RUNTIME.block_on(remove_playlist_item(&pool.write, item_id))
+    }
+
+    fn remove_playlist_duplicates(&self, playlist_id: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(remove_playlist_duplicates(&pool.write, playlist_id))
+    }
+
+    fn list_deleted_playlists(&self) -> Result<Vec<PlaylistWithCount>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_deleted_playlists(&pool.read))
+    }
+
+    fn list_deleted_playlist_items(&self) -> Result<Vec<DeletedPlaylistItem>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_deleted_playlist_items(&pool.read))
+    }
+
+    fn count_deleted_playlist_entries(&self) -> Result<i64, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(count_deleted_playlist_entries(&pool.read))
+    }
+
+    fn restore_playlist(&self, playlist_id: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(restore_playlist(&pool.write, playlist_id))
+    }
+
+    fn restore_playlist_item(&self, item_id: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(restore_playlist_item(&pool.write, item_id))
     }
 
     fn get_playlist_item(&self, item_id: i64) -> Result<PlaylistItem, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_playlist_item(&pool.0, item_id))
+        crate::RUNTIME.block_on(get_playlist_item(&pool.read, item_id))
     }
 
     fn get_track_stats(&self) -> Result<Arc<TrackStats>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(get_track_stats(&pool.0))
+        crate::RUNTIME.block_on(get_track_stats(&pool.read))
     }
 
     fn playlist_has_track(
@@ -501,6 +1595,86 @@ impl LibraryAccess for App {
         track_id: i64,
     ) -> Result<Option<i64>, sqlx::Error> {
         let pool: &Pool = self.global();
-        crate::RUNTIME.block_on(playlist_has_track(&pool.0, playlist_id, track_id))
+        crate::RUNTIME.block_on(playlist_has_track(&pool.read, playlist_id, track_id))
+    }
+
+    fn get_track_play_stats(&self, track_id: i64) -> Result<PlayStats, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_track_play_stats(&pool.read, track_id))
+    }
+
+    /// Returns the number of times the album has been played, counting a play of every track as
+    /// one play of the album.
+    fn get_album_play_count(&self, album_id: i64) -> Result<i64, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_album_play_count(&pool.read, album_id))
+    }
+
+    fn list_plays(&self, limit: i64) -> Result<Vec<Play>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_plays(&pool.read, limit))
+    }
+
+    fn delete_play(&self, play_id: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(delete_play(&pool.write, play_id))
+    }
+
+    fn list_quarantined_files(&self) -> Result<Arc<Vec<QuarantinedFile>>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_quarantined_files(&pool.read))
+    }
+
+    fn list_podcasts(&self) -> Result<Vec<Podcast>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_podcasts(&pool.read))
+    }
+
+    fn get_podcast(&self, podcast_id: i64) -> Result<Podcast, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_podcast(&pool.read, podcast_id))
+    }
+
+    fn list_podcast_episodes(&self, podcast_id: i64) -> Result<Vec<PodcastEpisode>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_podcast_episodes(&pool.read, podcast_id))
+    }
+
+    fn get_podcast_episode(&self, episode_id: i64) -> Result<PodcastEpisode, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(get_podcast_episode(&pool.read, episode_id))
+    }
+
+    fn set_podcast_episode_listened(
+        &self,
+        episode_id: i64,
+        listened: bool,
+    ) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(set_podcast_episode_listened(
+            &pool.write,
+            episode_id,
+            listened,
+        ))
+    }
+
+    fn mark_podcast_seen(&self, podcast_id: i64) -> Result<(), sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(mark_podcast_seen(&pool.write, podcast_id))
+    }
+
+    fn count_new_podcast_episodes(&self) -> Result<i64, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(count_new_podcast_episodes(&pool.read))
+    }
+
+    fn list_audiobooks(&self) -> Result<Vec<Audiobook>, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(list_audiobooks(&pool.read))
+    }
+
+    fn is_audiobook(&self, path: &Path) -> Result<bool, sqlx::Error> {
+        let pool: &Pool = self.global();
+        crate::RUNTIME.block_on(is_audiobook(&pool.read, &path.to_string_lossy()))
     }
 }
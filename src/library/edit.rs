@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, Utc};
+use rustc_hash::FxHashSet;
+use sqlx::{SqliteConnection, SqlitePool};
+use tracing::{debug, warn};
+
+use crate::media::tags::{TagChanges, TagField, write_tags};
+
+/// A field that can be changed across many tracks at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EditField {
+    Genre,
+    AlbumArtist,
+    Year,
+}
+
+impl EditField {
+    fn tag_field(self) -> TagField {
+        match self {
+            EditField::Genre => TagField::Genre,
+            EditField::AlbumArtist => TagField::AlbumArtist,
+            EditField::Year => TagField::Year,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct EditTrack {
+    id: i64,
+    location: String,
+    album_id: Option<i64>,
+    genres: Option<String>,
+    release_date: Option<DateTime<Utc>>,
+    release_year: Option<u16>,
+    album_artist: Option<String>,
+}
+
+impl EditTrack {
+    fn value(&self, field: EditField) -> Option<String> {
+        match field {
+            EditField::Genre => self.genres.clone(),
+            EditField::AlbumArtist => self.album_artist.clone(),
+            EditField::Year => self
+                .release_date
+                .map(|date| date.year().to_string())
+                .or(self.release_year.map(|year| year.to_string())),
+        }
+    }
+}
+
+/// A track whose field will be changed, along with its current value.
+#[derive(Debug, Clone)]
+pub struct PlannedEdit {
+    track_id: i64,
+    album_id: Option<i64>,
+    pub path: PathBuf,
+    pub old: Option<String>,
+}
+
+/// Works out which of the tracks would change if the field was set to the value. Nothing is
+/// written. Tracks that already have the value, or that are no longer in the library, are left
+/// out.
+pub async fn plan_edit(
+    pool: &SqlitePool,
+    track_ids: &[i64],
+    field: EditField,
+    value: &str,
+) -> anyhow::Result<Vec<PlannedEdit>> {
+    if field == EditField::Year && !value.is_empty() && value.parse::<u16>().is_err() {
+        anyhow::bail!("year must be a number, got {value:?}");
+    }
+
+    let mut seen = FxHashSet::default();
+    let mut edits = Vec::new();
+
+    for track_id in track_ids {
+        if !seen.insert(*track_id) {
+            continue;
+        }
+
+        let track: Option<EditTrack> =
+            sqlx::query_as(include_str!("../../queries/edit/get_track.sql"))
+                .bind(track_id)
+                .fetch_optional(pool)
+                .await?;
+
+        let Some(track) = track else {
+            warn!("Track {track_id} is not in the library, skipping");
+            continue;
+        };
+
+        let old = track.value(field);
+        if old.as_deref().unwrap_or_default() == value {
+            continue;
+        }
+
+        edits.push(PlannedEdit {
+            track_id: track.id,
+            album_id: track.album_id,
+            path: PathBuf::from(&track.location),
+            old,
+        });
+    }
+
+    Ok(edits)
+}
+
+async fn artist_id(conn: &mut SqliteConnection, name: &str) -> Result<i64, sqlx::Error> {
+    let result: Result<(i64,), sqlx::Error> =
+        sqlx::query_as(include_str!("../../queries/scan/create_artist.sql"))
+            .bind(name)
+            .bind(name)
+            .fetch_one(&mut *conn)
+            .await;
+
+    match result {
+        Ok(v) => Ok(v.0),
+        Err(sqlx::Error::RowNotFound) => {
+            sqlx::query_as(include_str!("../../queries/scan/get_artist_id.sql"))
+                .bind(name)
+                .fetch_one(&mut *conn)
+                .await
+                .map(|v: (i64,)| v.0)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn update_album(
+    pool: &SqlitePool,
+    album_id: i64,
+    field: EditField,
+    value: &str,
+) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+
+    match field {
+        EditField::Genre => unreachable!("genres are stored on tracks"),
+        EditField::AlbumArtist => {
+            let artist_id = if value.is_empty() {
+                None
+            } else {
+                Some(artist_id(&mut conn, value).await?)
+            };
+
+            sqlx::query(include_str!("../../queries/edit/update_album_artist.sql"))
+                .bind(album_id)
+                .bind(artist_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        EditField::Year => {
+            sqlx::query(include_str!("../../queries/edit/update_album_year.sql"))
+                .bind(album_id)
+                .bind(value.parse::<u16>().ok())
+                .execute(&mut *conn)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the value to the tags of each file, and then updates the library to match. The album
+/// artist and year are stored on albums rather than tracks, so they change for the whole album
+/// even when only some of its tracks were edited. Returns the number of files that were written;
+/// files that can't be written are logged and skipped.
+pub async fn apply_edit(
+    pool: &SqlitePool,
+    edits: &[PlannedEdit],
+    field: EditField,
+    value: &str,
+) -> usize {
+    let changes = TagChanges {
+        text: vec![(field.tag_field(), value.to_string())],
+        ..Default::default()
+    };
+
+    let mut written = 0;
+    let mut albums = FxHashSet::default();
+
+    for edit in edits {
+        debug!("setting {field:?} of {:?} to {value:?}", edit.path);
+
+        if let Err(err) = write_tags(&edit.path, &changes) {
+            warn!(?err, "Could not write tags to {:?}: {err}", edit.path);
+            continue;
+        }
+
+        written += 1;
+
+        if field == EditField::Genre {
+            let genre = (!value.is_empty()).then_some(value);
+            let result = sqlx::query(include_str!("../../queries/edit/update_track_genre.sql"))
+                .bind(edit.track_id)
+                .bind(genre)
+                .execute(pool)
+                .await;
+
+            if let Err(err) = result {
+                warn!(?err, "Could not update genre of {:?}: {err}", edit.path);
+            }
+        } else if let Some(album_id) = edit.album_id {
+            albums.insert(album_id);
+        }
+    }
+
+    for album_id in albums {
+        if let Err(err) = update_album(pool, album_id, field, value).await {
+            warn!(?err, "Could not update album {album_id}: {err}");
+        }
+    }
+
+    written
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use rustc_hash::FxHashMap;
+use sqlx::SqlitePool;
+use tracing::debug;
+
+/// Reduces a genre name to the characters that matter when comparing it with another, so that
+/// `Hip Hop`, `hip-hop` and `HipHop` are all the same genre.
+pub(super) fn genre_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Renames genres according to the aliases in the scan settings.
+#[derive(Debug, Default, Clone)]
+pub struct GenreAliases {
+    aliases: FxHashMap<String, String>,
+}
+
+impl GenreAliases {
+    pub fn new(aliases: &BTreeMap<String, String>) -> Self {
+        GenreAliases {
+            aliases: aliases
+                .iter()
+                .map(|(from, to)| (genre_key(from), to.trim().to_string()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    /// Renames each genre in a tag value. Values with several genres are separated by
+    /// semicolons, and genres that end up the same after renaming are only kept once.
+    pub fn apply(&self, genres: &str) -> String {
+        let mut renamed: Vec<&str> = Vec::new();
+
+        for genre in genres.split(';').map(str::trim) {
+            let genre = self
+                .aliases
+                .get(&genre_key(genre))
+                .map(String::as_str)
+                .unwrap_or(genre);
+
+            if !genre.is_empty() && !renamed.contains(&genre) {
+                renamed.push(genre);
+            }
+        }
+
+        renamed.join("; ")
+    }
+}
+
+/// Renames the genres of tracks that are already in the library. Returns the number of tracks
+/// that were changed.
+pub async fn apply_genre_aliases(pool: &SqlitePool, aliases: &GenreAliases) -> anyhow::Result<u64> {
+    if aliases.is_empty() {
+        return Ok(0);
+    }
+
+    let genres: Vec<(String,)> =
+        sqlx::query_as(include_str!("../../queries/genres/list_genres.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut changed = 0;
+
+    for (genre,) in genres {
+        let renamed = aliases.apply(&genre);
+        if renamed == genre || renamed.is_empty() {
+            continue;
+        }
+
+        debug!("Renaming genre {genre:?} to {renamed:?}");
+        changed += sqlx::query(include_str!("../../queries/genres/rename_genre.sql"))
+            .bind(&genre)
+            .bind(&renamed)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+
+    tx.commit().await?;
+
+    Ok(changed)
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use regex::Regex;
+use tracing::warn;
+
+use crate::media::metadata::Metadata;
+
+/// The patterns tried, in order, when none are set. Patterns starting with a track number come
+/// first so that `01 - Title` isn't read as an artist named `01`.
+pub const DEFAULT_PATTERNS: &[&str] =
+    &["%track% - %title%", "%artist% - %title%", "%track% %title%"];
+
+/// The fields that can be used in a pattern.
+const FIELDS: &[&str] = &["artist", "album", "title", "track", "disc"];
+
+/// Tags guessed from a file's path.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GuessedTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track: Option<u64>,
+    pub disc: Option<u64>,
+}
+
+/// Turns a pattern into a regex matching the end of a path. Each `/` in the pattern matches a
+/// directory separator, so `%album%/%track% %title%` also takes the album from the name of the
+/// file's folder.
+fn compile_pattern(pattern: &str) -> anyhow::Result<Regex> {
+    let mut regex = String::from(r"(?:^|/)");
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('%') {
+        let Some(end) = rest[start + 1..].find('%') else {
+            anyhow::bail!("unclosed % in pattern");
+        };
+        let field = &rest[start + 1..start + 1 + end];
+
+        if !FIELDS.contains(&field) {
+            anyhow::bail!(
+                "unknown field %{field}% in pattern, expected one of: {}",
+                FIELDS.join(", ")
+            );
+        }
+
+        regex.push_str(&regex::escape(&rest[..start]));
+        regex.push_str(&match field {
+            "track" | "disc" => format!(r"(?P<{field}>\d+)"),
+            _ => format!(r"(?P<{field}>[^/]+?)"),
+        });
+
+        rest = &rest[start + end + 2..];
+    }
+
+    regex.push_str(&regex::escape(rest));
+    regex.push('$');
+
+    Ok(Regex::new(&regex)?)
+}
+
+fn clean(value: &str) -> Option<String> {
+    let value = value.trim_matches(|c: char| c.is_whitespace() || c == '-' || c == '_');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Guesses tags from the file's path, using the first of the patterns that matches. The
+/// extension is ignored. Patterns that can't be compiled are logged and skipped.
+pub fn guess_tags(path: &Path, patterns: &[String]) -> Option<GuessedTags> {
+    let path = path.with_extension("");
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    patterns.iter().find_map(|pattern| {
+        let regex = compile_pattern(pattern)
+            .inspect_err(|err| warn!("Invalid filename pattern {pattern:?}: {err}"))
+            .ok()?;
+        let captures = regex.captures(&path)?;
+        let text = |name: &str| captures.name(name).and_then(|m| clean(m.as_str()));
+        let number = |name: &str| captures.name(name).and_then(|m| m.as_str().parse().ok());
+
+        Some(GuessedTags {
+            title: text("title"),
+            artist: text("artist"),
+            album: text("album"),
+            track: number("track"),
+            disc: number("disc"),
+        })
+    })
+}
+
+/// Whether the file has none of the tags needed to place it in the library.
+pub fn is_untagged(metadata: &Metadata) -> bool {
+    metadata.name.is_none() && metadata.artist.is_none() && metadata.album.is_none()
+}
+
+/// Fills in fields that are missing from the metadata with the guessed ones. Fields that are
+/// already set are left alone.
+pub fn fill_metadata(metadata: &mut Metadata, guess: GuessedTags) {
+    metadata.name = metadata.name.take().or(guess.title);
+    metadata.artist = metadata.artist.take().or(guess.artist);
+    metadata.album = metadata.album.take().or(guess.album);
+    metadata.track_current = metadata.track_current.or(guess.track);
+    metadata.disc_current = metadata.disc_current.or(guess.disc);
+}
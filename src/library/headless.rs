@@ -0,0 +1,689 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use clap::Subcommand;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::{
+    library::{
+        analysis::{AnalyzedTrack, analyze_next, find_tracks, measure_next},
+        art::migrate_album_art,
+        db::{
+            create_pool, find_track_file, find_tracks_by_field, get_track_by_id, get_track_stats,
+            list_albums_search, list_quarantined_files, list_tracks_in_album, set_metadata_locked,
+        },
+        edit::{EditField, PlannedEdit, apply_edit, plan_edit},
+        organize::{PlannedMove, apply_moves, plan_moves},
+        scan::ScanThread,
+        sync::{SyncDevice, SyncPlan, apply_sync, detect_devices, plan_sync},
+        transfer::{ExportSummary, ImportSummary, export_library_data, import_library_data},
+        types::{QuarantinedFile, TaggedTrack, Track, TrackStats},
+    },
+    media::transcode::Transcoder,
+    settings::{create_settings, libraries, storage::Storage, transcode::TranscodeProfile},
+};
+
+/// Provides access to the library without a window or GPU, so that scanning and querying can be
+/// done from scripts, CI, or on machines without a display.
+pub struct HeadlessLibrary {
+    pool: SqlitePool,
+    data_dir: PathBuf,
+    /// The directory the library's database, art and scan record are kept in.
+    library_dir: PathBuf,
+    settings_path: PathBuf,
+}
+
+impl HeadlessLibrary {
+    /// Opens (or creates) the library that was last opened in the app, using the settings in the
+    /// given config directory. The default library is stored in the data directory itself.
+    pub fn open(data_dir: &Path, config_dir: &Path) -> anyhow::Result<Self> {
+        let settings_path = config_dir.join("settings.json");
+        let settings = create_settings(&settings_path);
+        let storage_data = Storage::new(data_dir.join("app_data.json")).load_or_default();
+        let library_dir = libraries::find_profile(&settings, storage_data.library.as_deref())
+            .map(|profile| profile.directory(data_dir))
+            .unwrap_or_else(|| data_dir.to_path_buf());
+
+        fs::create_dir_all(&library_dir)?;
+
+        let pool = crate::RUNTIME.block_on(create_pool(library_dir.join("library.db")))?;
+        crate::RUNTIME.block_on(migrate_album_art(&pool, &library_dir.join("art")))?;
+
+        Ok(HeadlessLibrary {
+            pool,
+            data_dir: data_dir.to_path_buf(),
+            library_dir,
+            settings_path,
+        })
+    }
+
+    /// Scans the paths configured in the settings file, returning the number of files that were
+    /// added or updated.
+    pub fn scan(&self, force: bool) -> u64 {
+        let settings = create_settings(&self.settings_path);
+
+        let (scanned, _) = ScanThread::scan_blocking(
+            self.pool.clone(),
+            libraries::scan_settings(&settings, &self.library_dir, &self.data_dir),
+            self.library_dir.join("art"),
+            self.library_dir.join("scan_record.json"),
+            force,
+        );
+
+        scanned
+    }
+
+    /// Adds files and folders to the library without adding them to the scan paths, returning
+    /// the number of files that were added or updated.
+    pub fn import(&self, paths: &[PathBuf]) -> anyhow::Result<u64> {
+        let settings = create_settings(&self.settings_path);
+
+        ScanThread::import_blocking(
+            self.pool.clone(),
+            libraries::scan_settings(&settings, &self.library_dir, &self.data_dir),
+            self.library_dir.join("art"),
+            self.library_dir.join("scan_record.json"),
+            paths,
+        )
+    }
+
+    pub fn set_metadata_locked(&self, track_id: i64, locked: bool) -> anyhow::Result<()> {
+        Ok(crate::RUNTIME.block_on(set_metadata_locked(&self.pool, track_id, locked))?)
+    }
+
+    /// Lists every album in the library as tuples of id, title, and artist name.
+    pub fn albums(&self) -> anyhow::Result<Vec<(u32, String, String)>> {
+        Ok(crate::RUNTIME.block_on(list_albums_search(&self.pool))?)
+    }
+
+    pub fn tracks(&self, album_id: i64) -> anyhow::Result<Arc<Vec<Track>>> {
+        Ok(crate::RUNTIME.block_on(list_tracks_in_album(&self.pool, album_id))?)
+    }
+
+    pub fn stats(&self) -> anyhow::Result<Arc<TrackStats>> {
+        Ok(crate::RUNTIME.block_on(get_track_stats(&self.pool))?)
+    }
+
+    pub fn quarantined_files(&self) -> anyhow::Result<Arc<Vec<QuarantinedFile>>> {
+        Ok(crate::RUNTIME.block_on(list_quarantined_files(&self.pool))?)
+    }
+
+    /// Analyzes every track that hasn't been analyzed yet, returning the number of tracks that
+    /// were analyzed.
+    pub fn analyze(&self) -> usize {
+        let settings = create_settings(&self.settings_path);
+        let threshold = settings.analysis.silence_threshold;
+
+        let mut analyzed = 0;
+        while crate::RUNTIME.block_on(analyze_next(&self.pool, threshold)) {
+            analyzed += 1;
+        }
+        analyzed
+    }
+
+    /// Measures the loudness of every track that doesn't have ReplayGain tags and hasn't been
+    /// measured yet, returning the number of tracks that were measured.
+    pub fn measure_loudness(&self, write_tags: bool) -> usize {
+        let mut measured = 0;
+        while crate::RUNTIME.block_on(measure_next(&self.pool, write_tags)) {
+            measured += 1;
+        }
+        measured
+    }
+
+    pub fn find_tracks(
+        &self,
+        min_bpm: Option<f64>,
+        max_bpm: Option<f64>,
+        key: Option<&str>,
+    ) -> anyhow::Result<Vec<AnalyzedTrack>> {
+        Ok(crate::RUNTIME.block_on(find_tracks(&self.pool, min_bpm, max_bpm, key))?)
+    }
+
+    pub fn find_tagged(&self, name: &str, value: Option<&str>) -> anyhow::Result<Vec<TaggedTrack>> {
+        Ok(crate::RUNTIME.block_on(find_tracks_by_field(&self.pool, name, value))?)
+    }
+
+    /// Works out where files would be moved to by [HeadlessLibrary::organize]. The root defaults
+    /// to the first scan path in the settings.
+    pub fn plan_organize(
+        &self,
+        root: Option<&Path>,
+        pattern: &str,
+    ) -> anyhow::Result<(PathBuf, Vec<PlannedMove>)> {
+        let root = match root {
+            Some(root) => root.to_path_buf(),
+            None => create_settings(&self.settings_path)
+                .scanning
+                .paths
+                .first()
+                .map(|path| path.path.clone())
+                .ok_or_else(|| anyhow::anyhow!("no scan paths are set, pass --root"))?,
+        };
+
+        let moves = crate::RUNTIME.block_on(plan_moves(&self.pool, &root, pattern))?;
+
+        Ok((root, moves))
+    }
+
+    pub fn organize(&self, root: &Path, moves: &[PlannedMove]) -> usize {
+        crate::RUNTIME.block_on(apply_moves(&self.pool, root, moves))
+    }
+
+    pub fn devices(&self) -> Vec<SyncDevice> {
+        detect_devices()
+    }
+
+    /// Looks up a transcode profile in the settings by name.
+    pub fn transcode_profile(&self, name: &str) -> anyhow::Result<TranscodeProfile> {
+        let settings = create_settings(&self.settings_path).transcode;
+
+        settings.profile(name).cloned().ok_or_else(|| {
+            let names: Vec<&str> = settings.profiles.iter().map(|p| p.name.as_str()).collect();
+            anyhow::anyhow!(
+                "no transcode profile named {name:?}, expected one of: {}",
+                names.join(", ")
+            )
+        })
+    }
+
+    pub fn transcoder(&self) -> anyhow::Result<Transcoder> {
+        let settings = create_settings(&self.settings_path).transcode;
+        Ok(Transcoder::find(&settings)?)
+    }
+
+    pub fn plan_sync(
+        &self,
+        device: &Path,
+        pattern: &str,
+        albums: &[i64],
+        playlists: &[i64],
+        profile: Option<&TranscodeProfile>,
+    ) -> anyhow::Result<SyncPlan> {
+        crate::RUNTIME.block_on(plan_sync(
+            &self.pool, device, pattern, albums, playlists, profile,
+        ))
+    }
+
+    pub fn sync(&self, device: &Path, plan: &SyncPlan) -> anyhow::Result<usize> {
+        apply_sync(device, plan, self.transcoder().ok().as_ref())
+    }
+
+    /// Converts the tracks, along with the tracks of each album, into the directory. Returns the
+    /// number of files that were converted; files that fail to convert are logged and skipped.
+    pub fn convert(
+        &self,
+        tracks: &[i64],
+        albums: &[i64],
+        profile: &TranscodeProfile,
+        dir: &Path,
+    ) -> anyhow::Result<usize> {
+        let transcoder = self.transcoder()?;
+
+        let mut locations = Vec::new();
+        for track_id in tracks {
+            match crate::RUNTIME.block_on(get_track_by_id(&self.pool, *track_id)) {
+                Ok(track) => locations.push(track.location.clone()),
+                Err(err) => warn!("Could not find track {track_id}, skipping: {err}"),
+            }
+        }
+        for album_id in albums {
+            locations.extend(self.tracks(*album_id)?.iter().map(|t| t.location.clone()));
+        }
+
+        let mut converted = 0;
+        for location in locations {
+            let file = crate::RUNTIME.block_on(find_track_file(&self.pool, &location))?;
+
+            match transcoder.convert_into(&file, dir, profile) {
+                Ok(to) => {
+                    println!("{}\t{}", location.display(), to.display());
+                    converted += 1;
+                }
+                Err(err) => warn!("Could not convert {:?}: {err}", location),
+            }
+        }
+
+        Ok(converted)
+    }
+
+    /// Works out which tracks would change if the field was set to the value. The tracks of each
+    /// album are included along with the given tracks.
+    pub fn plan_edit(
+        &self,
+        tracks: &[i64],
+        albums: &[i64],
+        field: EditField,
+        value: &str,
+    ) -> anyhow::Result<Vec<PlannedEdit>> {
+        let mut track_ids = tracks.to_vec();
+        for album_id in albums {
+            track_ids.extend(self.tracks(*album_id)?.iter().map(|track| track.id));
+        }
+
+        crate::RUNTIME.block_on(plan_edit(&self.pool, &track_ids, field, value))
+    }
+
+    pub fn edit(&self, edits: &[PlannedEdit], field: EditField, value: &str) -> usize {
+        crate::RUNTIME.block_on(apply_edit(&self.pool, edits, field, value))
+    }
+
+    /// The scan folders of the library, which paths in exported files are made relative to.
+    fn scan_roots(&self) -> Vec<PathBuf> {
+        let settings = create_settings(&self.settings_path);
+
+        libraries::scan_settings(&settings, &self.library_dir, &self.data_dir)
+            .paths
+            .into_iter()
+            .map(|path| path.path)
+            .collect()
+    }
+
+    pub fn export_data(&self, output: &Path) -> anyhow::Result<ExportSummary> {
+        let roots = self.scan_roots();
+        crate::RUNTIME.block_on(export_library_data(&self.pool, &roots, output))
+    }
+
+    pub fn import_data(&self, input: &Path) -> anyhow::Result<ImportSummary> {
+        let roots = self.scan_roots();
+        crate::RUNTIME.block_on(import_library_data(&self.pool, &roots, input))
+    }
+
+    pub fn close(self) {
+        crate::RUNTIME.block_on(self.pool.close());
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LibraryCommand {
+    /// Scan the configured library paths for new, changed, and removed files
+    Scan {
+        /// Re-scan every file, ignoring the scan record
+        #[arg(long)]
+        force: bool,
+    },
+    /// Add files or folders to the library without adding them to the scan paths
+    Import {
+        /// A file or folder to add, can be given more than once
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Protect the metadata of tracks from being overwritten when their files are scanned again,
+    /// unless the files change
+    Lock {
+        /// A track to lock, can be given more than once
+        #[arg(required = true)]
+        tracks: Vec<i64>,
+        /// Let scans update the tracks' metadata again
+        #[arg(long)]
+        unlock: bool,
+    },
+    /// List every album in the library
+    Albums,
+    /// List the tracks in an album
+    Tracks { album_id: i64 },
+    /// Show the number of tracks in the library and their total duration
+    Stats,
+    /// List the files that couldn't be read during scanning, and why
+    Quarantine,
+    /// Find the tempo, key, and leading and trailing silence of every track that hasn't been
+    /// analyzed yet
+    Analyze,
+    /// Measure the loudness and true peak of every track that doesn't have ReplayGain tags
+    MeasureLoudness {
+        /// Write the results to each file as ReplayGain tags
+        #[arg(long)]
+        write_tags: bool,
+    },
+    /// List analyzed tracks by tempo, optionally limited to a range of tempos or a key
+    FindTracks {
+        #[arg(long)]
+        min_bpm: Option<f64>,
+        #[arg(long)]
+        max_bpm: Option<f64>,
+        /// A key such as C for C major or F#m for F-sharp minor
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// List tracks with a custom tag, such as MOOD or OCCASION
+    FindTagged {
+        /// The name of the tag, in any case
+        name: String,
+        /// Only list tracks where the tag has this value, ignoring case
+        value: Option<String>,
+    },
+    /// Move and rename files based on their tags. Without --apply, the planned moves are only
+    /// printed, so that they can be checked first
+    Organize {
+        /// Where files are placed, relative to the root. Available fields: {albumartist},
+        /// {artist}, {album}, {title}, {year}, {track}, {disc} and {genre}. The file's extension
+        /// is added to the end
+        #[arg(long, default_value = crate::library::organize::DEFAULT_PATTERN)]
+        pattern: String,
+        /// The directory files are organized into, defaults to the first scan path
+        #[arg(long)]
+        root: Option<PathBuf>,
+        /// Move the files instead of only printing what would be moved
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Set a field on many tracks at once, writing it to both the files' tags and the library.
+    /// Without --apply, the old and new value of each track are only printed
+    EditTags {
+        #[arg(long, value_enum)]
+        field: EditField,
+        /// The new value, an empty value removes the field
+        #[arg(long)]
+        value: String,
+        /// A track to edit, can be given more than once
+        #[arg(long = "track", value_name = "TRACK_ID")]
+        tracks: Vec<i64>,
+        /// An album whose tracks are all edited, can be given more than once
+        #[arg(long = "album", value_name = "ALBUM_ID")]
+        albums: Vec<i64>,
+        /// Write the changes instead of only printing them
+        #[arg(long)]
+        apply: bool,
+    },
+    /// List the removable drives and MTP devices that music can be synced to
+    Devices,
+    /// Copy albums and playlists to a device, removing anything synced before that's no longer
+    /// chosen. Without --apply, the planned changes are only printed
+    Sync {
+        /// Where the device is mounted, as listed by the devices command
+        device: PathBuf,
+        /// An album to put on the device, can be given more than once. If no albums or playlists
+        /// are given, the ones chosen last time are used
+        #[arg(long = "album", value_name = "ALBUM_ID")]
+        albums: Vec<i64>,
+        /// A playlist to put on the device, can be given more than once
+        #[arg(long = "playlist", value_name = "PLAYLIST_ID")]
+        playlists: Vec<i64>,
+        /// Where files are placed on the device, using the same fields as organize
+        #[arg(long, default_value = crate::library::sync::DEFAULT_PATTERN)]
+        pattern: String,
+        /// The transcode profile files are converted with, from the settings. Files that are
+        /// already in the profile's format are copied as they are
+        #[arg(long)]
+        profile: Option<String>,
+        /// Copy and remove the files instead of only printing what would change
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Convert tracks to another format with a transcode profile from the settings, writing the
+    /// converted files into a folder
+    Convert {
+        /// The name of the transcode profile
+        #[arg(long)]
+        profile: String,
+        /// The folder converted files are written to
+        #[arg(long)]
+        output: PathBuf,
+        /// A track to convert, can be given more than once
+        #[arg(long = "track", value_name = "TRACK_ID")]
+        tracks: Vec<i64>,
+        /// An album whose tracks are all converted, can be given more than once
+        #[arg(long = "album", value_name = "ALBUM_ID")]
+        albums: Vec<i64>,
+    },
+    /// Write the library's playlists, liked tracks and play history to a file, so that they can
+    /// be imported into the library on another computer
+    ExportData {
+        /// The file to write
+        output: PathBuf,
+    },
+    /// Merge the playlists, liked tracks and play history exported from another library into
+    /// this one. Nothing is removed, and importing the same file twice changes nothing
+    ImportData {
+        /// The file written by export-data
+        input: PathBuf,
+    },
+}
+
+/// Runs a library command from the command line, printing the results to stdout as tab-separated
+/// values.
+pub fn run(data_dir: &Path, config_dir: &Path, command: &LibraryCommand) -> anyhow::Result<()> {
+    let library = HeadlessLibrary::open(data_dir, config_dir)?;
+
+    match command {
+        LibraryCommand::Scan { force } => {
+            let scanned = library.scan(*force);
+            println!("Scanned {scanned} files");
+        }
+        LibraryCommand::Import { paths } => {
+            let scanned = library.import(paths)?;
+            println!("Imported {scanned} files");
+        }
+        LibraryCommand::Lock { tracks, unlock } => {
+            for track_id in tracks {
+                library.set_metadata_locked(*track_id, !unlock)?;
+            }
+
+            let state = if *unlock { "Unlocked" } else { "Locked" };
+            println!("{state} {} tracks", tracks.len());
+        }
+        LibraryCommand::Albums => {
+            for (id, title, artist) in library.albums()? {
+                println!("{id}\t{title}\t{artist}");
+            }
+        }
+        LibraryCommand::Tracks { album_id } => {
+            for track in library.tracks(*album_id)?.iter() {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    track.id,
+                    track.disc_number.map(|v| v.to_string()).unwrap_or_default(),
+                    track
+                        .track_number
+                        .map(|v| v.to_string())
+                        .unwrap_or_default(),
+                    track.title,
+                    track.duration,
+                    track.location.display()
+                );
+            }
+        }
+        LibraryCommand::Stats => {
+            let stats = library.stats()?;
+            println!("tracks\t{}", stats.track_count);
+            println!("duration\t{}", stats.total_duration);
+        }
+        LibraryCommand::Analyze => {
+            let analyzed = library.analyze();
+            println!("Analyzed {analyzed} tracks");
+        }
+        LibraryCommand::MeasureLoudness { write_tags } => {
+            let measured = library.measure_loudness(*write_tags);
+            println!("Measured {measured} tracks");
+        }
+        LibraryCommand::FindTracks {
+            min_bpm,
+            max_bpm,
+            key,
+        } => {
+            for track in library.find_tracks(*min_bpm, *max_bpm, key.as_deref())? {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}",
+                    track.id,
+                    track.bpm.map(|v| v.to_string()).unwrap_or_default(),
+                    track.musical_key.unwrap_or_default(),
+                    track.title,
+                    track.location
+                );
+            }
+        }
+        LibraryCommand::FindTagged { name, value } => {
+            for track in library.find_tagged(name, value.as_deref())? {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    track.id, track.value, track.title, track.location
+                );
+            }
+        }
+        LibraryCommand::Quarantine => {
+            for file in library.quarantined_files()?.iter() {
+                println!(
+                    "{}\t{}\t{}",
+                    file.location,
+                    file.quarantined_at.to_rfc3339(),
+                    file.error
+                );
+            }
+        }
+        LibraryCommand::Organize {
+            pattern,
+            root,
+            apply,
+        } => {
+            let (root, moves) = library.plan_organize(root.as_deref(), pattern)?;
+
+            for planned in &moves {
+                println!(
+                    "{}\t{}{}",
+                    planned.from.display(),
+                    planned.to.display(),
+                    if planned.renamed { "\trenamed" } else { "" }
+                );
+            }
+
+            if *apply {
+                let moved = library.organize(&root, &moves);
+                println!("Moved {moved} of {} files", moves.len());
+            } else {
+                println!(
+                    "{} files would be moved, run again with --apply",
+                    moves.len()
+                );
+            }
+        }
+        LibraryCommand::EditTags {
+            field,
+            value,
+            tracks,
+            albums,
+            apply,
+        } => {
+            if tracks.is_empty() && albums.is_empty() {
+                anyhow::bail!("no tracks selected, pass --track or --album");
+            }
+
+            let edits = library.plan_edit(tracks, albums, *field, value)?;
+
+            for edit in &edits {
+                println!(
+                    "{}\t-{}\t+{}",
+                    edit.path.display(),
+                    edit.old.as_deref().unwrap_or_default(),
+                    value
+                );
+            }
+
+            if *apply {
+                let written = library.edit(&edits, *field, value);
+                println!("Wrote {written} of {} files", edits.len());
+            } else {
+                println!(
+                    "{} files would be changed, run again with --apply",
+                    edits.len()
+                );
+            }
+        }
+        LibraryCommand::Devices => {
+            for device in library.devices() {
+                println!(
+                    "{}\t{}\t{}",
+                    device.kind.name(),
+                    device.name,
+                    device.path.display()
+                );
+            }
+        }
+        LibraryCommand::Sync {
+            device,
+            albums,
+            playlists,
+            pattern,
+            profile,
+            apply,
+        } => {
+            let profile = profile
+                .as_deref()
+                .map(|name| library.transcode_profile(name))
+                .transpose()?;
+            let plan = library.plan_sync(device, pattern, albums, playlists, profile.as_ref())?;
+
+            for path in &plan.removals {
+                println!("remove\t{}", path.display());
+            }
+            for copy in &plan.copies {
+                println!(
+                    "{}{}\t{}\t{}",
+                    if copy.update { "update" } else { "copy" },
+                    if copy.transcode { "+convert" } else { "" },
+                    copy.from.display(),
+                    copy.to.display()
+                );
+            }
+
+            if *apply {
+                let copied = library.sync(device, &plan)?;
+                println!(
+                    "Copied {copied} of {} files, removed {}, {} already up to date",
+                    plan.copies.len(),
+                    plan.removals.len(),
+                    plan.unchanged
+                );
+            } else {
+                println!(
+                    "{} files would be copied and {} removed, {} are up to date, run again with \
+                     --apply",
+                    plan.copies.len(),
+                    plan.removals.len(),
+                    plan.unchanged
+                );
+            }
+        }
+        LibraryCommand::Convert {
+            profile,
+            output,
+            tracks,
+            albums,
+        } => {
+            if tracks.is_empty() && albums.is_empty() {
+                anyhow::bail!("no tracks selected, pass --track or --album");
+            }
+
+            let profile = library.transcode_profile(profile)?;
+            let converted = library.convert(tracks, albums, &profile, output)?;
+            println!("Converted {converted} files");
+        }
+        LibraryCommand::ExportData { output } => {
+            let summary = library.export_data(output)?;
+            println!(
+                "Exported {} plays and {} playlists",
+                summary.plays, summary.playlists
+            );
+        }
+        LibraryCommand::ImportData { input } => {
+            let summary = library.import_data(input)?;
+            println!(
+                "Imported {} plays, added {} tracks to playlists and created {} playlists",
+                summary.plays, summary.playlist_items, summary.playlists_created
+            );
+            if summary.missing_tracks > 0 {
+                println!(
+                    "{} tracks in the file aren't in this library",
+                    summary.missing_tracks
+                );
+            }
+        }
+    }
+
+    library.close();
+
+    Ok(())
+}
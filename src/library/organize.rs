@@ -0,0 +1,316 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, Utc};
+use rustc_hash::FxHashSet;
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+/// The pattern used when none is given.
+pub const DEFAULT_PATTERN: &str = "{albumartist}/{year} - {album}/{track} {title}";
+
+/// The fields that can be used in a pattern.
+const FIELDS: &[&str] = &[
+    "albumartist",
+    "artist",
+    "album",
+    "title",
+    "year",
+    "track",
+    "disc",
+    "genre",
+];
+
+/// Used in place of fields that are missing from a track.
+const UNKNOWN: &str = "Unknown";
+
+#[derive(sqlx::FromRow)]
+pub(super) struct OrganizeTrack {
+    pub(super) id: i64,
+    pub(super) location: String,
+    title: String,
+    track_number: Option<i32>,
+    disc_number: Option<i32>,
+    genres: Option<String>,
+    artist_names: Option<String>,
+    album_id: Option<i64>,
+    album_title: Option<String>,
+    release_date: Option<DateTime<Utc>>,
+    release_year: Option<u16>,
+    album_artist: Option<String>,
+}
+
+impl OrganizeTrack {
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "albumartist" => self.album_artist.clone().or(self.artist_names.clone()),
+            "artist" => self.artist_names.clone().or(self.album_artist.clone()),
+            "album" => self.album_title.clone(),
+            "title" => Some(self.title.clone()),
+            "year" => self
+                .release_date
+                .map(|date| date.year().to_string())
+                .or(self.release_year.map(|year| year.to_string())),
+            "track" => self.track_number.map(|v| format!("{v:02}")),
+            "disc" => self.disc_number.map(|v| v.to_string()),
+            "genre" => self.genres.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// A file the organizer will move, and where it will be moved to.
+#[derive(Debug, Clone)]
+pub struct PlannedMove {
+    track_id: i64,
+    album_id: Option<i64>,
+    disc_number: Option<i32>,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Whether a number was added to the end of the file name, because another file already
+    /// had the name the pattern gave.
+    pub renamed: bool,
+}
+
+/// Checks that the pattern only uses known fields and that every brace is closed.
+pub fn validate_pattern(pattern: &str) -> anyhow::Result<()> {
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            anyhow::bail!("unclosed brace in pattern");
+        };
+        let field = &rest[start + 1..start + end];
+
+        if !FIELDS.contains(&field) {
+            anyhow::bail!(
+                "unknown field {{{field}}} in pattern, expected one of: {}",
+                FIELDS.join(", ")
+            );
+        }
+
+        rest = &rest[start + end + 1..];
+    }
+
+    Ok(())
+}
+
+/// Replaces characters that can't be used in file names on some platforms. Leading and trailing
+/// dots and spaces are removed so that fields can't produce hidden files or `..`.
+pub(super) fn sanitize(value: &str) -> String {
+    let replaced: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let trimmed = replaced.trim_matches(|c: char| c == '.' || c.is_whitespace());
+
+    if trimmed.is_empty() {
+        UNKNOWN.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Fills in the pattern for the track, giving the new path relative to the library root, without
+/// an extension.
+pub(super) fn render_pattern(pattern: &str, track: &OrganizeTrack) -> PathBuf {
+    pattern
+        .split('/')
+        .map(|component| {
+            let mut result = String::new();
+            let mut rest = component;
+
+            while let Some(start) = rest.find('{') {
+                let end = start + rest[start..].find('}').unwrap_or(rest.len() - start);
+                result.push_str(&rest[..start]);
+
+                let value = track.field(&rest[start + 1..end]);
+                result.push_str(&sanitize(value.as_deref().unwrap_or(UNKNOWN)));
+
+                rest = rest.get(end + 1..).unwrap_or_default();
+            }
+
+            result.push_str(rest);
+            sanitize(&result)
+        })
+        .collect()
+}
+
+/// Adds " (2)", " (3)", and so on to the file name until it doesn't collide with anything.
+fn resolve_collision(path: &Path, taken: &FxHashSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|ext| ext.to_string_lossy());
+
+    (2..)
+        .map(|n| {
+            let name = match &extension {
+                Some(ext) => format!("{stem} ({n}).{ext}"),
+                None => format!("{stem} ({n})"),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !taken.contains(candidate) && !candidate.exists())
+        .unwrap()
+}
+
+/// Works out where every track in the library would be moved to with the given pattern. Nothing
+/// is changed on disk or in the database. Tracks that are already in the right place are left
+/// out.
+pub async fn plan_moves(
+    pool: &SqlitePool,
+    root: &Path,
+    pattern: &str,
+) -> anyhow::Result<Vec<PlannedMove>> {
+    validate_pattern(pattern)?;
+
+    let tracks: Vec<OrganizeTrack> =
+        sqlx::query_as(include_str!("../../queries/organize/list_tracks.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let targets: Vec<(PathBuf, PathBuf)> = tracks
+        .iter()
+        .map(|track| {
+            let from = PathBuf::from(&track.location);
+            let mut to = root.join(render_pattern(pattern, track));
+            if let Some(ext) = from.extension() {
+                to.set_extension(ext);
+            }
+            (from, to)
+        })
+        .collect();
+
+    // files that are staying where they are, plus the destinations of files that have already
+    // been planned
+    let mut taken: FxHashSet<PathBuf> = targets
+        .iter()
+        .filter(|(from, to)| from == to)
+        .map(|(_, to)| to.clone())
+        .collect();
+    let mut moves = Vec::new();
+
+    for (track, (from, to)) in tracks.iter().zip(targets) {
+        if to == from {
+            continue;
+        }
+
+        let mut to = to;
+        let renamed = taken.contains(&to) || to.exists();
+        if renamed {
+            to = resolve_collision(&to, &taken);
+        }
+
+        taken.insert(to.clone());
+        moves.push(PlannedMove {
+            track_id: track.id,
+            album_id: track.album_id,
+            disc_number: track.disc_number,
+            from,
+            to,
+            renamed,
+        });
+    }
+
+    Ok(moves)
+}
+
+/// Moves a file, falling back to copying it when the destination is on another file system.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(_) => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)
+        }
+    }
+}
+
+/// Removes the directory and its parents, up to the root, for as long as they are empty.
+pub(super) fn remove_empty_dirs(dir: &Path, root: &Path) {
+    let mut current = Some(dir);
+
+    while let Some(dir) = current {
+        if dir == root || !dir.starts_with(root) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
+
+async fn update_location(pool: &SqlitePool, planned: &PlannedMove) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let old_folder = planned.from.parent().and_then(|p| p.to_str());
+    let new_folder = planned.to.parent().and_then(|p| p.to_str());
+
+    sqlx::query(include_str!(
+        "../../queries/organize/update_track_location.sql"
+    ))
+    .bind(planned.track_id)
+    .bind(planned.to.to_str())
+    .bind(new_folder)
+    .execute(&mut *tx)
+    .await?;
+
+    // the scanner skips files that aren't in their album's recorded folder, so it has to follow
+    // the files
+    if let Some(album_id) = planned.album_id {
+        sqlx::query(include_str!("../../queries/organize/update_album_path.sql"))
+            .bind(album_id)
+            .bind(planned.disc_number.unwrap_or(-1))
+            .bind(old_folder)
+            .bind(new_folder)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Moves the files and updates their locations in the database, so that playlists and play
+/// history still point at them. Directories left empty under the root are removed. Returns the
+/// number of files that were moved; files that fail to move are logged and skipped.
+pub async fn apply_moves(pool: &SqlitePool, root: &Path, moves: &[PlannedMove]) -> usize {
+    let mut moved = 0;
+
+    for planned in moves {
+        debug!("moving {:?} to {:?}", planned.from, planned.to);
+
+        if let Err(err) = move_file(&planned.from, &planned.to) {
+            warn!(?err, "Could not move {:?}: {err}", planned.from);
+            continue;
+        }
+
+        if let Err(err) = update_location(pool, planned).await {
+            warn!(
+                ?err,
+                "Could not update location of {:?}: {err}", planned.from
+            );
+
+            // put the file back so that the library still matches what's on disk
+            if let Err(err) = move_file(&planned.to, &planned.from) {
+                warn!(?err, "Could not move {:?} back: {err}", planned.to);
+            }
+            continue;
+        }
+
+        if let Some(parent) = planned.from.parent() {
+            remove_empty_dirs(parent, root);
+        }
+
+        moved += 1;
+    }
+
+    moved
+}
@@ -1,7 +1,12 @@
-use std::{ffi::OsStr, path::PathBuf};
+use std::{
+    ffi::OsStr,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
 
 use futures::future::join_all;
 use gpui::{App, PathPromptOptions};
+use image::{RgbImage, codecs::jpeg::JpegEncoder, imageops::FilterType};
 use sqlx::{Sqlite, SqlitePool};
 use tokio::{
     fs::File,
@@ -9,44 +14,84 @@ use tokio::{
 };
 use tracing::error;
 
-use crate::ui::{
-    app::Pool,
-    models::{Models, PlaylistEvent},
+use crate::{
+    library::{
+        art::{read_art, store_art},
+        db::{LibraryAccess, get_playlist},
+        types::Track,
+    },
+    ui::{
+        app::{ActiveLibrary, Pool},
+        locale::t,
+        models::{Models, PlaylistEvent, set_lastfm_loved},
+    },
 };
 
+/// The id of the system playlist holding the user's liked tracks.
+pub const LIKED_PLAYLIST_ID: i64 = 1;
+
+/// The width and height of playlist covers, in pixels.
+const COVER_SIZE: u32 = 600;
+
 #[cfg(windows)]
 const LINE_ENDING: &str = "\r\n";
 #[cfg(not(windows))]
 const LINE_ENDING: &str = "\n";
 
+/// A track in a playlist, with what's needed to describe it in an M3U file.
 #[derive(sqlx::FromRow)]
-struct PlaylistEntry {
-    location: String,
-    duration: i64,
-    track_artist_names: String,
-    artist_name: String,
-    track_title: String,
-    album_title: String,
+pub struct PlaylistEntry {
+    pub location: String,
+    pub duration: i64,
+    pub track_artist_names: String,
+    pub artist_name: String,
+    pub track_title: String,
+    pub album_title: String,
 }
 
-async fn make_m3u(pool: &SqlitePool, pl_id: i64) -> anyhow::Result<String> {
-    let mut output = String::new();
+pub async fn list_playlist_entries(
+    pool: &SqlitePool,
+    pl_id: i64,
+) -> Result<Vec<PlaylistEntry>, sqlx::Error> {
+    let query = include_str!("../../queries/playlist/list_tracks_for_export.sql");
+    sqlx::query_as(query).bind(pl_id).fetch_all(pool).await
+}
 
-    output.push_str(&format!("#EXTM3U{LINE_ENDING}"));
+/// The start of an M3U file. Players ignore lines starting with # that aren't directives, so the
+/// description is kept as comments.
+pub fn m3u_header(description: Option<&str>) -> String {
+    let mut output = format!("#EXTM3U{LINE_ENDING}");
 
-    let query = include_str!("../../queries/playlist/list_tracks_for_export.sql");
-    let data: Vec<PlaylistEntry> = sqlx::query_as(query).bind(pl_id).fetch_all(pool).await?;
-
-    data.iter().for_each(|entry| {
-        output.push_str(&format!(
-            "#EXTINF:{},{} - {}{LINE_ENDING}",
-            entry.duration, entry.track_artist_names, entry.track_title
-        ));
-        output.push_str(&format!("#EXTALB:{}{LINE_ENDING}", entry.album_title));
-        output.push_str(&format!("#EXTART:{}{LINE_ENDING}", entry.artist_name));
-        output.push_str(&format!("{}{LINE_ENDING}", entry.location));
+    if let Some(description) = description {
+        for line in description.lines() {
+            output.push_str(&format!("# {line}{LINE_ENDING}"));
+        }
         output.push_str(LINE_ENDING);
-    });
+    }
+
+    output
+}
+
+/// The lines describing the entry in an M3U file, pointing to the file at `location`.
+pub fn m3u_entry(entry: &PlaylistEntry, location: &str) -> String {
+    format!(
+        "#EXTINF:{},{} - {}{LINE_ENDING}#EXTALB:{}{LINE_ENDING}#EXTART:{}{LINE_ENDING}\
+         {location}{LINE_ENDING}{LINE_ENDING}",
+        entry.duration,
+        entry.track_artist_names,
+        entry.track_title,
+        entry.album_title,
+        entry.artist_name
+    )
+}
+
+async fn make_m3u(pool: &SqlitePool, pl_id: i64) -> anyhow::Result<String> {
+    let playlist = get_playlist(pool, pl_id).await?;
+    let mut output = m3u_header(playlist.description.as_deref());
+
+    for entry in list_playlist_entries(pool, pl_id).await? {
+        output.push_str(&m3u_entry(&entry, &entry.location));
+    }
 
     Ok(output)
 }
@@ -62,7 +107,7 @@ pub fn export_playlist(cx: &mut App, pl_id: i64, playlist_name: &str) -> anyhow:
 
     let path_future = cx.prompt_for_new_path(dir, Some(&suggested_name));
 
-    let pool = cx.global::<Pool>().0.clone();
+    let pool = cx.global::<Pool>().read.clone();
 
     crate::RUNTIME.spawn(async move {
         let result = async {
@@ -152,10 +197,11 @@ pub fn import_playlist(cx: &mut App, playlist_id: i64) -> anyhow::Result<()> {
         files: true,
         directories: false,
         multiple: false,
-        prompt: Some("Select a M3U file...".into()),
+        prompt: Some(t!(cx, "playlist.import_prompt")),
     });
 
-    let pool = cx.global::<Pool>().0.clone();
+    let read_pool = cx.global::<Pool>().read.clone();
+    let write_pool = cx.global::<Pool>().write.clone();
     let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
 
     cx.spawn(async move |cx| {
@@ -185,12 +231,12 @@ pub fn import_playlist(cx: &mut App, playlist_id: i64) -> anyhow::Result<()> {
                                         .flatten()
                                         .unwrap_or_default()
                                 ))
-                                .fetch_one(&pool)
+                                .fetch_one(&read_pool)
                         });
 
                         let ids = join_all(iter).await.into_iter().flatten();
 
-                        let mut tx = pool.begin().await?;
+                        let mut tx = write_pool.begin().await?;
 
                         let reset_query = include_str!("../../queries/playlist/empty_playlist.sql");
                         sqlx::query(reset_query)
@@ -230,3 +276,158 @@ pub fn import_playlist(cx: &mut App, playlist_id: i64) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Adds the track to (or removes it from) the liked tracks playlist, and mirrors the change to
+/// Last.fm as a love or unlove. Returns the id of the track's item in the playlist, if it is liked.
+pub fn set_track_liked(cx: &mut App, track: &Track, liked: bool) -> anyhow::Result<Option<i64>> {
+    let existing = cx.playlist_has_track(LIKED_PLAYLIST_ID, track.id)?;
+
+    let item = match (liked, existing) {
+        (true, None) => Some(cx.add_playlist_item(LIKED_PLAYLIST_ID, track.id)?),
+        (false, Some(item_id)) => {
+            cx.remove_playlist_item(item_id)?;
+            None
+        }
+        (_, existing) => return Ok(existing),
+    };
+
+    let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+    playlist_tracker.update(cx, |_, cx| {
+        cx.emit(PlaylistEvent::PlaylistUpdated(LIKED_PLAYLIST_ID));
+    });
+
+    if let Some(artist) = &track.artist_names {
+        set_lastfm_loved(cx, artist.to_string(), track.title.to_string(), liked);
+    }
+
+    Ok(item)
+}
+
+fn encode_cover(image: &RgbImage) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buf, 90).encode_image(image)?;
+    Ok(buf.into_inner())
+}
+
+/// Crops the image to a square and scales it to the cover size.
+fn square_cover(data: &[u8], size: u32) -> anyhow::Result<RgbImage> {
+    let image = image::load_from_memory(data)?;
+    Ok(image
+        .resize_to_fill(size, size, FilterType::Lanczos3)
+        .into_rgb8())
+}
+
+/// Makes a cover from the art of the first albums in the playlist: a 2×2 grid when there are at
+/// least four of them, or the first album's art otherwise. Returns `None` if none of the albums
+/// have art.
+async fn make_collage(
+    pool: &SqlitePool,
+    art_dir: &Path,
+    playlist_id: i64,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let query = include_str!("../../queries/playlist/list_album_art.sql");
+    let hashes: Vec<String> = sqlx::query_scalar(query)
+        .bind(playlist_id)
+        .bind(4)
+        .fetch_all(pool)
+        .await?;
+
+    let cover = match hashes.as_slice() {
+        [] => return Ok(None),
+        [a, b, c, d] => {
+            let half = COVER_SIZE / 2;
+            let mut cover = RgbImage::new(COVER_SIZE, COVER_SIZE);
+
+            for (i, hash) in [a, b, c, d].into_iter().enumerate() {
+                let tile = square_cover(&read_art(art_dir, hash)?, half)?;
+                let (x, y) = ((i as u32 % 2) * half, (i as u32 / 2) * half);
+                image::imageops::replace(&mut cover, &tile, x as i64, y as i64);
+            }
+
+            cover
+        }
+        [first, ..] => square_cover(&read_art(art_dir, first)?, COVER_SIZE)?,
+    };
+
+    Ok(Some(encode_cover(&cover)?))
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlaylistCover {
+    /// Asks for an image file to use as the cover.
+    Pick,
+    /// Makes a cover from the art of the albums in the playlist.
+    Collage,
+    /// Goes back to the playlist icon.
+    Remove,
+}
+
+/// Changes the playlist's cover, and lets the views showing it know once it has been changed.
+pub fn set_playlist_cover(cx: &mut App, playlist_id: i64, cover: PlaylistCover) {
+    let path_future = (cover == PlaylistCover::Pick).then(|| {
+        cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some(t!(cx, "playlist.cover_prompt")),
+        })
+    });
+
+    let read_pool = cx.global::<Pool>().read.clone();
+    let write_pool = cx.global::<Pool>().write.clone();
+    let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+    let art_dir = cx.global::<ActiveLibrary>().art_dir();
+
+    cx.spawn(async move |cx| {
+        let result = crate::RUNTIME
+            .spawn(async move {
+                let data = match cover {
+                    PlaylistCover::Pick => {
+                        let Some(path) = path_future
+                            .expect("a path is asked for when picking a cover")
+                            .await??
+                            .and_then(|paths| paths.into_iter().next())
+                        else {
+                            return anyhow::Ok(false);
+                        };
+
+                        let data = tokio::fs::read(path).await?;
+                        Some(encode_cover(&square_cover(&data, COVER_SIZE)?)?)
+                    }
+                    PlaylistCover::Collage => {
+                        let Some(data) = make_collage(&read_pool, &art_dir, playlist_id).await?
+                        else {
+                            anyhow::bail!("none of the albums in the playlist have art");
+                        };
+                        Some(data)
+                    }
+                    PlaylistCover::Remove => None,
+                };
+
+                let hash = data.map(|data| store_art(&art_dir, &data)).transpose()?;
+
+                sqlx::query(include_str!("../../queries/playlist/set_image.sql"))
+                    .bind(playlist_id)
+                    .bind(hash)
+                    .execute(&write_pool)
+                    .await?;
+
+                anyhow::Ok(true)
+            })
+            .await;
+
+        match result {
+            Ok(Ok(true)) => {
+                playlist_tracker
+                    .update(cx, |_, cx| {
+                        cx.emit(PlaylistEvent::PlaylistUpdated(playlist_id))
+                    })
+                    .ok();
+            }
+            Ok(Ok(false)) => (),
+            Ok(Err(err)) => error!("Failed to set the playlist cover: {err}"),
+            Err(err) => error!("Failed to set the playlist cover: {err}"),
+        }
+    })
+    .detach();
+}
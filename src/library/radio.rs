@@ -0,0 +1,254 @@
+use std::path::PathBuf;
+
+use rand::{Rng, rng};
+use rustc_hash::{FxHashMap, FxHashSet};
+use sqlx::SqlitePool;
+
+use super::genres::genre_key;
+
+/// The number of tracks picked to follow the track a radio is started from.
+pub const RADIO_LENGTH: usize = 50;
+
+/// At most this many tracks are picked from each album, so that one album doesn't take over.
+const MAX_PER_ALBUM: usize = 3;
+
+/// Tracks with a tempo further apart than this (as a fraction of the first track's tempo) don't
+/// count as having a similar tempo.
+const MAX_TEMPO_DIFFERENCE: f64 = 0.08;
+
+/// A track picked for a radio.
+#[derive(Debug, Clone)]
+pub struct RadioTrack {
+    pub id: i64,
+    pub location: PathBuf,
+    pub album_id: Option<i64>,
+}
+
+#[derive(sqlx::FromRow, Debug, Clone)]
+struct Candidate {
+    id: i64,
+    location: String,
+    album_id: Option<i64>,
+    album_artist_id: Option<i64>,
+    artist_names: Option<String>,
+    genres: Option<String>,
+    bpm: Option<f64>,
+    musical_key: Option<String>,
+    audiobook: bool,
+}
+
+/// Splits a track's artist tag into the names of its artists, so that `A feat. B` and `B & C`
+/// are both found to have B on them.
+fn artist_names(names: &str) -> FxHashSet<String> {
+    let mut names = names.to_lowercase();
+
+    for separator in [
+        " feat. ", " feat ", " ft. ", " with ", " & ", " x ", ";", "/",
+    ] {
+        names = names.replace(separator, ",");
+    }
+
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn genres(genres: &str) -> FxHashSet<String> {
+    genres
+        .split(';')
+        .map(genre_key)
+        .filter(|genre| !genre.is_empty())
+        .collect()
+}
+
+/// Returns the position of a key on the Camelot wheel, along with whether it's minor. Keys can be
+/// written as a note name (such as `F#m` or `Bb`) or in Camelot notation (such as `8A`).
+fn camelot(key: &str) -> Option<(u8, bool)> {
+    let key = key.trim();
+
+    if let Some(number) = key
+        .strip_suffix(['A', 'a', 'B', 'b'])
+        .and_then(|number| number.parse::<u8>().ok())
+        .filter(|number| (1..=12).contains(number))
+    {
+        return Some((number, key.ends_with(['A', 'a'])));
+    }
+
+    let (note, minor) = match key.strip_suffix("m").or(key.strip_suffix(" minor")) {
+        Some(note) => (note, true),
+        None => (key.strip_suffix(" major").unwrap_or(key), false),
+    };
+
+    let pitch: u8 = match note {
+        "C" | "B#" => 0,
+        "C#" | "Db" => 1,
+        "D" => 2,
+        "D#" | "Eb" => 3,
+        "E" | "Fb" => 4,
+        "F" | "E#" => 5,
+        "F#" | "Gb" => 6,
+        "G" => 7,
+        "G#" | "Ab" => 8,
+        "A" => 9,
+        "A#" | "Bb" => 10,
+        "B" | "Cb" => 11,
+        _ => return None,
+    };
+
+    // a minor key sits in the same place as the major key three semitones above it
+    let pitch = if minor { (pitch + 3) % 12 } else { pitch };
+
+    Some(((pitch * 7 + 7) % 12 + 1, minor))
+}
+
+/// How well two keys mix: the same key, its relative major or minor, and the keys next to it on
+/// the Camelot wheel all sound right together.
+fn key_score(a: (u8, bool), b: (u8, bool)) -> f64 {
+    let distance = a.0.abs_diff(b.0).min(12 - a.0.abs_diff(b.0));
+
+    match (distance, a.1 == b.1) {
+        (0, true) => 1.5,
+        (0, false) | (1, true) => 1.0,
+        _ => 0.0,
+    }
+}
+
+fn tempo_score(a: f64, b: f64) -> f64 {
+    if a <= 0.0 || b <= 0.0 {
+        return 0.0;
+    }
+
+    let difference = (a - b).abs() / a;
+
+    if difference < MAX_TEMPO_DIFFERENCE {
+        2.0 * (1.0 - difference / MAX_TEMPO_DIFFERENCE)
+    } else {
+        0.0
+    }
+}
+
+/// Picks up to `count` tracks from the library that are similar to the track, most similar
+/// first. Tracks are scored by the genres they share with it, whether they're by the same or a
+/// related artist (one the track's artists have appeared alongside), and, if both have been
+/// analyzed, how close their tempo and key are. Audiobook chapters are never picked.
+pub async fn radio_tracks(
+    pool: &SqlitePool,
+    track_id: i64,
+    count: usize,
+) -> Result<Vec<RadioTrack>, sqlx::Error> {
+    let tracks: Vec<Candidate> =
+        sqlx::query_as(include_str!("../../queries/radio/list_tracks.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let Some(seed) = tracks.iter().find(|track| track.id == track_id) else {
+        return Ok(Vec::new());
+    };
+
+    let seed_artists = seed
+        .artist_names
+        .as_deref()
+        .map(artist_names)
+        .unwrap_or_default();
+    let seed_genres = seed.genres.as_deref().map(genres).unwrap_or_default();
+    let seed_key = seed.musical_key.as_deref().and_then(camelot);
+
+    // artists who have shared a track with one of the track's artists
+    let mut related: FxHashSet<String> = FxHashSet::default();
+    for track in &tracks {
+        let names = track
+            .artist_names
+            .as_deref()
+            .map(artist_names)
+            .unwrap_or_default();
+
+        if !names.is_disjoint(&seed_artists) {
+            related.extend(
+                names
+                    .into_iter()
+                    .filter(|name| !seed_artists.contains(name)),
+            );
+        }
+    }
+
+    let mut rng = rng();
+    let mut scored: Vec<(f64, &Candidate)> = tracks
+        .iter()
+        .filter(|track| track.id != seed.id && !track.audiobook && track.location != seed.location)
+        .filter_map(|track| {
+            let mut score = 0.0;
+
+            let track_genres = track.genres.as_deref().map(genres).unwrap_or_default();
+            let shared = track_genres.intersection(&seed_genres).count();
+            if shared > 0 {
+                let total = track_genres.union(&seed_genres).count();
+                score += 4.0 * shared as f64 / total as f64;
+            }
+
+            let same_album_artist =
+                seed.album_artist_id.is_some() && track.album_artist_id == seed.album_artist_id;
+            if same_album_artist {
+                score += 2.0;
+            }
+
+            let names = track
+                .artist_names
+                .as_deref()
+                .map(artist_names)
+                .unwrap_or_default();
+            let same_artist = !names.is_disjoint(&seed_artists);
+            let related_artist = !names.is_disjoint(&related);
+            if same_artist {
+                score += 2.0;
+            } else if related_artist {
+                score += 1.0;
+            }
+
+            if let (Some(a), Some(b)) = (seed.bpm, track.bpm) {
+                score += tempo_score(a, b);
+            }
+
+            if let (Some(a), Some(b)) = (seed_key, track.musical_key.as_deref().and_then(camelot)) {
+                score += key_score(a, b);
+            }
+
+            // tempo and key alone aren't enough to call two tracks similar
+            if shared == 0 && !same_album_artist && !same_artist && !related_artist {
+                return None;
+            }
+
+            // a little randomness, so that starting a radio twice doesn't give the same tracks
+            Some((score * rng.random_range(0.8..1.2), track))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut per_album: FxHashMap<i64, usize> = FxHashMap::default();
+    if let Some(album_id) = seed.album_id {
+        per_album.insert(album_id, 1);
+    }
+
+    let picked = scored
+        .into_iter()
+        .filter(|(_, track)| match track.album_id {
+            Some(album_id) => {
+                let picked = per_album.entry(album_id).or_default();
+                *picked += 1;
+                *picked <= MAX_PER_ALBUM
+            }
+            None => true,
+        })
+        .take(count)
+        .map(|(_, track)| RadioTrack {
+            id: track.id,
+            location: PathBuf::from(&track.location),
+            album_id: track.album_id,
+        })
+        .collect();
+
+    Ok(picked)
+}
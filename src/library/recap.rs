@@ -0,0 +1,216 @@
+use std::fmt::Write;
+
+use resvg::{tiny_skia, usvg};
+use sqlx::SqlitePool;
+
+/// How many artists, albums and tracks are listed in a recap.
+pub const TOP_COUNT: i64 = 5;
+
+const CARD_WIDTH: u32 = 1080;
+const CARD_HEIGHT: u32 = 1400;
+
+/// An artist, album or track that was among the most played of the year.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct RecapEntry {
+    pub name: String,
+    /// The artist of an album or track.
+    pub detail: Option<String>,
+    pub play_count: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct Totals {
+    play_count: i64,
+    total_duration: i64,
+}
+
+/// A summary of a year of listening, made from the play history. Audiobook chapters aren't
+/// counted.
+#[derive(Debug, Clone)]
+pub struct Recap {
+    pub year: i64,
+    pub play_count: i64,
+    /// The time spent listening, in seconds.
+    pub total_duration: i64,
+    /// The number of album artists that were listened to for the first time during the year.
+    pub discovered_artists: i64,
+    pub top_artists: Vec<RecapEntry>,
+    pub top_albums: Vec<RecapEntry>,
+    pub top_tracks: Vec<RecapEntry>,
+}
+
+/// Lists the years that have plays in the play history, most recent first.
+pub async fn list_recap_years(pool: &SqlitePool) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(include_str!("../../queries/recap/list_years.sql"))
+        .fetch_all(pool)
+        .await
+}
+
+async fn top_entries(
+    pool: &SqlitePool,
+    query: &'static str,
+    year: &str,
+) -> Result<Vec<RecapEntry>, sqlx::Error> {
+    sqlx::query_as(query)
+        .bind(year)
+        .bind(TOP_COUNT)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_recap(pool: &SqlitePool, year: i64) -> Result<Recap, sqlx::Error> {
+    // plays are stored in UTC, and compared to the year as text
+    let year_text = format!("{year:04}");
+
+    let totals: Totals = sqlx::query_as(include_str!("../../queries/recap/get_totals.sql"))
+        .bind(&year_text)
+        .fetch_one(pool)
+        .await?;
+    let discovered_artists: i64 = sqlx::query_scalar(include_str!(
+        "../../queries/recap/count_discovered_artists.sql"
+    ))
+    .bind(&year_text)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(Recap {
+        year,
+        play_count: totals.play_count,
+        total_duration: totals.total_duration,
+        discovered_artists,
+        top_artists: top_entries(
+            pool,
+            include_str!("../../queries/recap/list_top_artists.sql"),
+            &year_text,
+        )
+        .await?,
+        top_albums: top_entries(
+            pool,
+            include_str!("../../queries/recap/list_top_albums.sql"),
+            &year_text,
+        )
+        .await?,
+        top_tracks: top_entries(
+            pool,
+            include_str!("../../queries/recap/list_top_tracks.sql"),
+            &year_text,
+        )
+        .await?,
+    })
+}
+
+/// A list on a recap card, such as the top artists.
+#[derive(Debug, Clone)]
+pub struct CardSection {
+    pub heading: String,
+    /// The name of each entry, with the line shown under it.
+    pub entries: Vec<(String, String)>,
+}
+
+/// The text shown on a recap card. The text is put together by the UI, so that the card is
+/// translated, and the same card can be shown in the app and exported as an image.
+#[derive(Debug, Clone)]
+pub struct RecapCard {
+    pub title: String,
+    /// Each figure, with the label shown under it.
+    pub stats: Vec<(String, String)>,
+    /// The top artists and albums, shown side by side, followed by the top tracks.
+    pub sections: [CardSection; 3],
+    pub footer: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Shortens the text to at most `max` characters, since SVG text doesn't wrap or truncate.
+fn fit(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        escape(text)
+    } else {
+        let shortened: String = text.chars().take(max - 1).collect();
+        escape(&format!("{}…", shortened.trim_end()))
+    }
+}
+
+fn write_section(svg: &mut String, section: &CardSection, x: u32, y: u32, max_chars: usize) {
+    let _ = write!(
+        svg,
+        r##"<text x="{x}" y="{y}" font-size="34" font-weight="800" fill="#ffffff">{}</text>"##,
+        fit(&section.heading, max_chars)
+    );
+
+    for (idx, (name, detail)) in section.entries.iter().enumerate() {
+        let top = y + 56 + idx as u32 * 70;
+
+        let _ = write!(
+            svg,
+            r##"<text x="{x}" y="{top}" font-size="28" font-weight="600" fill="#ffffff"><tspan fill="#a5b4fc">{}</tspan><tspan dx="12">{}</tspan></text>"##,
+            idx + 1,
+            fit(name, max_chars - 3)
+        );
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{}" font-size="22" fill="#cbd5e1">{}</text>"##,
+            x + 34,
+            top + 28,
+            fit(detail, max_chars + 4)
+        );
+    }
+}
+
+fn card_svg(card: &RecapCard) -> String {
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{CARD_WIDTH}" height="{CARD_HEIGHT}" viewBox="0 0 {CARD_WIDTH} {CARD_HEIGHT}" font-family="Inter">
+<defs><linearGradient id="background" x1="0" y1="0" x2="1" y2="1"><stop offset="0" stop-color="#312e81"/><stop offset="1" stop-color="#0f172a"/></linearGradient></defs>
+<rect width="{CARD_WIDTH}" height="{CARD_HEIGHT}" fill="url(#background)"/>
+<text x="80" y="170" font-size="80" font-weight="800" fill="#ffffff">{}</text>"##,
+        fit(&card.title, 24)
+    );
+
+    for (idx, (value, label)) in card.stats.iter().enumerate() {
+        let x = 80 + idx as u32 * 320;
+
+        let _ = write!(
+            svg,
+            r##"<text x="{x}" y="320" font-size="72" font-weight="800" fill="#a5b4fc">{}</text><text x="{x}" y="365" font-size="26" fill="#cbd5e1">{}</text>"##,
+            fit(value, 8),
+            fit(label, 20)
+        );
+    }
+
+    write_section(&mut svg, &card.sections[0], 80, 480, 26);
+    write_section(&mut svg, &card.sections[1], 560, 480, 26);
+    write_section(&mut svg, &card.sections[2], 80, 900, 56);
+
+    let _ = write!(
+        svg,
+        r##"<text x="80" y="{}" font-size="24" font-weight="600" fill="#94a3b8">{}</text></svg>"##,
+        CARD_HEIGHT - 40,
+        fit(&card.footer, 60)
+    );
+
+    svg
+}
+
+/// Draws the card as a PNG image, using the given fonts. The fonts should include Inter, which
+/// the card is laid out for.
+pub fn render_card(card: &RecapCard, fonts: Vec<Vec<u8>>) -> anyhow::Result<Vec<u8>> {
+    let mut options = usvg::Options {
+        font_family: "Inter".to_string(),
+        ..usvg::Options::default()
+    };
+    for font in fonts {
+        options.fontdb_mut().load_font_data(font);
+    }
+
+    let tree = usvg::Tree::from_str(&card_svg(card), &options)?;
+    let mut pixmap = tiny_skia::Pixmap::new(CARD_WIDTH, CARD_HEIGHT)
+        .ok_or_else(|| anyhow::anyhow!("could not allocate the image"))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    Ok(pixmap.encode_png()?)
+}
@@ -0,0 +1,316 @@
+mod sftp;
+mod stream;
+mod webdav;
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::SystemTime,
+};
+
+use anyhow::{Context, bail};
+use url::Url;
+
+use crate::{settings::scan::RemoteSource, ui::app::get_dirs};
+
+pub use stream::{Download, stream};
+
+/// The remote folders in the settings, so that their files can be downloaded when they're played.
+/// Kept up to date by the scanner.
+static SOURCES: RwLock<Vec<RemoteSource>> = RwLock::new(Vec::new());
+
+/// A file found in a remote folder.
+#[derive(Debug, Clone)]
+pub struct RemoteFile {
+    /// The address of the file, which is used as its location in the library.
+    pub location: PathBuf,
+    /// When the file was last changed, in seconds since the Unix epoch.
+    pub modified: u64,
+}
+
+/// The user name and password to sign in with: those in the settings, or else those in the
+/// address.
+fn credentials(source: &RemoteSource, url: &Url) -> (Option<String>, Option<String>) {
+    let decode = |value: &str| {
+        urlencoding::decode(value)
+            .map(|value| value.into_owned())
+            .unwrap_or_else(|_| value.to_string())
+    };
+
+    let username = source
+        .username
+        .clone()
+        .or_else(|| Some(decode(url.username())).filter(|name| !name.is_empty()));
+    let password = source
+        .password
+        .clone()
+        .or_else(|| url.password().map(decode));
+
+    (username, password)
+}
+
+/// Replaces the remote folders that files can be downloaded from.
+pub fn set_sources(sources: &[RemoteSource]) {
+    *SOURCES.write().unwrap_or_else(|err| err.into_inner()) = sources.to_vec();
+}
+
+/// Whether the location is the address of a file in a remote folder, rather than a path on this
+/// computer.
+pub fn is_remote(location: &Path) -> bool {
+    location.to_str().is_some_and(|location| {
+        ["http://", "https://", "sftp://"]
+            .iter()
+            .any(|scheme| location.starts_with(scheme))
+    })
+}
+
+/// The address of the remote folder, without its password. The locations of the folder's files
+/// start with it.
+pub fn root(source: &RemoteSource) -> anyhow::Result<Url> {
+    let mut url = Url::parse(&source.url)
+        .with_context(|| format!("{:?} isn't a valid address", source.url))?;
+
+    if !matches!(url.scheme(), "http" | "https" | "sftp") {
+        bail!(
+            "{} isn't a WebDAV (http or https) or SFTP address",
+            source.url
+        );
+    }
+
+    let _ = url.set_password(None);
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+
+    Ok(url)
+}
+
+/// Lists every file in the remote folder and the folders inside it. Blocks until the whole
+/// folder has been listed.
+pub fn list_files(source: &RemoteSource) -> anyhow::Result<Vec<RemoteFile>> {
+    let root = root(source)?;
+    let (username, password) = credentials(source, &Url::parse(&source.url)?);
+
+    if root.scheme() == "sftp" {
+        sftp::list_files(&root, username.as_deref())
+    } else {
+        crate::RUNTIME.block_on(webdav::list_files(
+            &root,
+            username.as_deref(),
+            password.as_deref(),
+        ))
+    }
+}
+
+/// The address of the remote file, along with the user name and password of the remote folder
+/// it's in.
+fn address(location: &Path) -> anyhow::Result<(Url, Option<String>, Option<String>)> {
+    let address = location.to_string_lossy();
+    let source = SOURCES
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .iter()
+        .find(|source| root(source).is_ok_and(|root| location.starts_with(root.as_str())))
+        .cloned()
+        .with_context(|| format!("{address} isn't in any of the remote folders"))?;
+
+    let url = Url::parse(&address)?;
+    let (username, password) = credentials(&source, &Url::parse(&source.url)?);
+
+    Ok((url, username, password))
+}
+
+/// Downloads the remote file to the path. The file is written under a temporary name first, so
+/// that an interrupted download doesn't leave a partial file behind. Blocks until the file has
+/// been downloaded.
+pub fn download(location: &Path, to: &Path) -> anyhow::Result<()> {
+    let (url, username, password) = address(location)?;
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let partial = to.with_extension("part");
+    let result = if url.scheme() == "sftp" {
+        sftp::download(&url, username.as_deref(), &partial)
+    } else {
+        crate::RUNTIME.block_on(webdav::download(
+            &url,
+            username.as_deref(),
+            password.as_deref(),
+            &partial,
+        ))
+    };
+
+    if let Err(err) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+
+    fs::rename(&partial, to)?;
+
+    Ok(())
+}
+
+/// The name the copies of the remote file are kept under.
+fn copy_name(location: &Path) -> PathBuf {
+    let name = PathBuf::from(format!(
+        "{:x}",
+        md5::compute(location.as_os_str().as_encoded_bytes())
+    ));
+
+    match location.extension() {
+        Some(extension) => name.with_extension(extension),
+        None => name,
+    }
+}
+
+fn cache_dir() -> PathBuf {
+    get_dirs().cache_dir().join("remote")
+}
+
+/// Where the copy of the remote file that's played is kept.
+pub fn cached_path(location: &Path) -> PathBuf {
+    cache_dir().join(copy_name(location))
+}
+
+/// Where the copy of the remote file that's kept for offline listening is stored. Unlike the
+/// cache, these copies are only removed when asked to be (see [remove_offline]).
+pub fn offline_path(location: &Path) -> PathBuf {
+    get_dirs()
+        .data_dir()
+        .join("offline")
+        .join(copy_name(location))
+}
+
+/// Removes the copies in the cache that were played the longest ago, until the cache takes up no
+/// more than `size` bytes. The copy at `keep` is never removed.
+fn trim_cache(size: u64, keep: &Path) {
+    let Ok(entries) = fs::read_dir(cache_dir()) else {
+        return;
+    };
+
+    let mut copies: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let path = entry.path();
+
+            // files that are still being downloaded
+            if !metadata.is_file() || path.extension().is_some_and(|ext| ext == "part") {
+                return None;
+            }
+
+            Some((metadata.modified().ok()?, metadata.len(), path))
+        })
+        .collect();
+
+    let mut total: u64 = copies.iter().map(|(_, len, _)| len).sum();
+    copies.sort_by_key(|(modified, _, _)| *modified);
+
+    for (_, len, path) in copies {
+        if total <= size {
+            break;
+        }
+
+        if path != keep && fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+/// The copy of the remote file on this computer, if there is one: the copy kept for offline
+/// listening, or else the copy in the cache.
+pub fn local_copy(location: &Path) -> Option<PathBuf> {
+    let offline = offline_path(location);
+    if offline.exists() {
+        return Some(offline);
+    }
+
+    let path = cached_path(location);
+    if !path.exists() {
+        return None;
+    }
+
+    // the cache is trimmed by when each copy was last played
+    if let Ok(file) = File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    Some(path)
+}
+
+/// Returns a copy of the remote file on this computer, downloading it into the cache if there
+/// isn't one already. The cache is then trimmed to `cache_size` bytes.
+pub fn fetch(location: &Path, cache_size: u64) -> anyhow::Result<PathBuf> {
+    if let Some(path) = local_copy(location) {
+        return Ok(path);
+    }
+
+    let path = cached_path(location);
+    download(location, &path)?;
+    trim_cache(cache_size, &path);
+
+    Ok(path)
+}
+
+/// Removes the copy of the remote file in the cache, if there is one, so that it's downloaded
+/// again the next time it's played.
+pub fn forget(location: &Path) {
+    let _ = fs::remove_file(cached_path(location));
+}
+
+/// Whether a copy of the remote file is kept for offline listening.
+pub fn is_offline(location: &Path) -> bool {
+    offline_path(location).exists()
+}
+
+/// Keeps a copy of the remote file for offline listening. The copy in the cache is used if there
+/// is one, and otherwise the file is downloaded. Blocks until the file has been downloaded.
+pub fn keep_offline(location: &Path) -> anyhow::Result<()> {
+    let offline = offline_path(location);
+    if offline.exists() {
+        return Ok(());
+    }
+
+    let cached = cached_path(location);
+    if cached.exists() {
+        if let Some(parent) = offline.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // the cache may be on another drive, in which case the file is downloaded again
+        if fs::rename(&cached, &offline).is_ok() {
+            return Ok(());
+        }
+    }
+
+    download(location, &offline)
+}
+
+/// Replaces the copy of the remote file kept for offline listening with the file at `from`,
+/// after the remote file has changed.
+pub fn replace_offline(location: &Path, from: &Path) -> anyhow::Result<()> {
+    let offline = offline_path(location);
+    let partial = offline.with_extension("part");
+
+    fs::copy(from, &partial)?;
+    fs::rename(&partial, &offline)?;
+
+    Ok(())
+}
+
+/// Stops keeping a copy of the remote file for offline listening.
+pub fn remove_offline(location: &Path) {
+    let _ = fs::remove_file(offline_path(location));
+}
+
+/// The location with the escaped characters in the address turned back into the characters they
+/// stand for, so that tags can be guessed from the names of the file and its folder.
+pub fn decode_location(location: &Path) -> PathBuf {
+    match urlencoding::decode(&location.to_string_lossy()) {
+        Ok(decoded) => PathBuf::from(decoded.into_owned()),
+        Err(_) => location.to_path_buf(),
+    }
+}
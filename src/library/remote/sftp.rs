@@ -0,0 +1,312 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+use chrono::{Datelike, NaiveDate, Utc};
+use url::Url;
+
+use super::RemoteFile;
+
+/// A file or folder in a folder listing.
+struct Entry<'a> {
+    name: &'a str,
+    folder: bool,
+    modified: u64,
+    size: u64,
+}
+
+/// Puts the path in quotes for an `sftp` command.
+fn quote(path: &str) -> String {
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The path on the server that the address points to.
+fn remote_path(url: &Url) -> String {
+    urlencoding::decode(url.path())
+        .map(|path| path.into_owned())
+        .unwrap_or_else(|_| url.path().to_string())
+}
+
+/// Sets up an `sftp` command for the server. `sftp` is run in batch mode, so it signs in with SSH
+/// keys (or an agent) rather than asking for a password.
+fn command(url: &Url, username: Option<&str>) -> anyhow::Result<Command> {
+    let host = url.host_str().context("the address has no server")?;
+
+    // `sftp` would read these as options, such as one that runs a command
+    if host.starts_with('-') || username.is_some_and(|username| username.starts_with('-')) {
+        bail!("{host} isn't a valid server");
+    }
+
+    let destination = match username {
+        Some(username) => format!("{username}@{host}"),
+        None => host.to_string(),
+    };
+
+    let mut command = Command::new("sftp");
+    command.args(["-q", "-b", "-", "-o", "ConnectTimeout=20"]);
+    if let Some(port) = url.port() {
+        command.args(["-P", &port.to_string()]);
+    }
+
+    command
+        .arg(destination)
+        // listings have English month names in the C locale, which is what's parsed
+        .env("LC_ALL", "C")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    Ok(command)
+}
+
+/// Starts running the commands with `sftp`.
+fn spawn(url: &Url, username: Option<&str>, commands: &str) -> anyhow::Result<Child> {
+    let mut child = command(url, username)?
+        .spawn()
+        .context("couldn't run sftp, which is installed with OpenSSH")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(commands.as_bytes())?;
+    }
+
+    Ok(child)
+}
+
+/// An `sftp` session that is given commands one at a time, so that a scan signs in once rather
+/// than for every folder it lists.
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Session {
+    fn start(url: &Url, username: Option<&str>) -> anyhow::Result<Self> {
+        let mut child = command(url, username)?
+            .spawn()
+            .context("couldn't run sftp, which is installed with OpenSSH")?;
+        let stdin = child.stdin.take().context("sftp has no input")?;
+        let stdout = child.stdout.take().context("sftp has no output")?;
+
+        Ok(Session {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    /// Lists the folder, returning the lines `sftp` prints for it, or nothing if `sftp` has
+    /// stopped. A folder that can't be listed has no lines, rather than stopping the session.
+    fn list(&mut self, path: &str) -> anyhow::Result<Option<Vec<String>>> {
+        // `sftp` carries on past commands starting with `-` when they fail, and `pwd` marks the
+        // end of the listing, as no line of a listing can look like what it prints
+        write!(self.stdin, "-ls -ln {}\npwd\n", quote(path))?;
+        self.stdin.flush()?;
+
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if line.starts_with("Remote working directory: ") {
+                return Ok(Some(lines));
+            }
+
+            lines.push(line.trim_end_matches(['\r', '\n']).to_string());
+        }
+    }
+
+    /// Ends the session, returning why `sftp` failed if it did.
+    fn close(self) -> anyhow::Result<()> {
+        drop(self.stdin);
+        finish(self.child)?;
+
+        Ok(())
+    }
+}
+
+/// Waits for `sftp` to finish running the commands, returning what it prints.
+fn finish(child: Child) -> anyhow::Result<String> {
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().last().unwrap_or_default().trim().to_string();
+
+        if message.is_empty() {
+            bail!("sftp failed: {}", output.status);
+        }
+        bail!("sftp failed: {message}");
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Runs the commands with `sftp`, returning what it prints.
+fn run(url: &Url, username: Option<&str>, commands: &str) -> anyhow::Result<String> {
+    finish(spawn(url, username, commands)?)
+}
+
+/// Works out when a file was changed from the date `sftp` lists it with, which has the time
+/// instead of the year for files changed in the last six months. The time is only to the minute,
+/// but that's enough to tell whether a file has changed.
+fn parse_date(month: &str, day: &str, time_or_year: &str) -> Option<u64> {
+    let now = Utc::now().naive_utc();
+
+    let (year, time) = match time_or_year.split_once(':') {
+        Some(_) => (now.year(), time_or_year),
+        None => (time_or_year.parse().ok()?, "00:00"),
+    };
+
+    let date = NaiveDate::parse_from_str(&format!("{year} {month} {day:0>2}"), "%Y %b %d").ok()?;
+    let mut modified = date
+        .and_time(chrono::NaiveTime::parse_from_str(time, "%H:%M").ok()?)
+        .and_utc();
+
+    // a time without a year is from the last six months, which may be last year
+    if modified.naive_utc() > now + chrono::Duration::days(1) {
+        modified = modified.with_year(year - 1)?;
+    }
+
+    Some(modified.timestamp().max(0) as u64)
+}
+
+/// Reads a line of a long listing (`ls -ln`), which looks like
+/// `-rw-r--r--    1 1000     1000      4613275 Jan  5 12:30 01 Track.flac`. Other lines, such as
+/// the commands `sftp` echoes, are skipped.
+fn parse_entry(line: &str) -> Option<Entry<'_>> {
+    let mut fields = Vec::with_capacity(8);
+    let mut rest = line;
+
+    for _ in 0..8 {
+        rest = rest.trim_start_matches(' ');
+        let end = rest.find(' ')?;
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    // names can start with spaces, so only the space separating it is removed
+    let name = rest.strip_prefix(' ')?;
+    let mode = fields[0];
+
+    if mode.len() != 10 || name.is_empty() || name == "." || name == ".." {
+        return None;
+    }
+
+    let folder = match mode.chars().next()? {
+        'd' => true,
+        '-' => false,
+        // links and devices are skipped, so that links can't send the listing round in circles
+        _ => return None,
+    };
+
+    Some(Entry {
+        name,
+        folder,
+        modified: parse_date(fields[5], fields[6], fields[7]).unwrap_or_default(),
+        size: fields[4].parse().unwrap_or_default(),
+    })
+}
+
+/// Lists the files in the folder and the folders inside it, all in one `sftp` session.
+pub fn list_files(root: &Url, username: Option<&str>) -> anyhow::Result<Vec<RemoteFile>> {
+    let mut files = Vec::new();
+    let mut folders = vec![root.clone()];
+    let mut session = Session::start(root, username)?;
+
+    while let Some(folder) = folders.pop() {
+        let path = remote_path(&folder);
+        let Some(output) = session.list(&path)? else {
+            session.close()?;
+            bail!("sftp stopped while listing {folder}");
+        };
+
+        // files in a folder that's named in the command are listed with the folder's path
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+
+        for entry in output.iter().filter_map(|line| parse_entry(line)) {
+            let name = entry.name.strip_prefix(&prefix).unwrap_or(entry.name);
+
+            let mut url = folder.clone();
+            url.path_segments_mut()
+                .map_err(|_| anyhow::anyhow!("{folder} can't hold files"))?
+                .pop_if_empty()
+                .push(name);
+
+            if entry.folder {
+                url.set_path(&format!("{}/", url.path()));
+                folders.push(url);
+            } else {
+                files.push(RemoteFile {
+                    location: PathBuf::from(url.as_str()),
+                    modified: entry.modified,
+                });
+            }
+        }
+    }
+
+    session.close()?;
+
+    Ok(files)
+}
+
+/// Downloads the file at the address to the path.
+pub fn download(url: &Url, username: Option<&str>, to: &Path) -> anyhow::Result<()> {
+    let commands = format!(
+        "get {} {}\n",
+        quote(&remote_path(url)),
+        quote(&to.to_string_lossy())
+    );
+    run(url, username, &commands)?;
+
+    Ok(())
+}
+
+/// Downloads the file at the address to the path, carrying on from the end of the file at the
+/// path if `resume` is set. `progress` is called with how much of the file has been written, and
+/// its size, while it downloads. The download stops early if it returns false.
+pub fn download_range(
+    url: &Url,
+    username: Option<&str>,
+    to: &Path,
+    resume: bool,
+    mut progress: impl FnMut(u64, Option<u64>) -> bool,
+) -> anyhow::Result<()> {
+    let path = quote(&remote_path(url));
+
+    // `get` doesn't say how big the file is, so it's listed first
+    let listing = run(url, username, &format!("ls -ln {path}\n"))?;
+    let total = listing
+        .lines()
+        .filter_map(parse_entry)
+        .next()
+        .map(|entry| entry.size);
+
+    let command = if resume { "reget" } else { "get" };
+    let commands = format!("{command} {path} {}\n", quote(&to.to_string_lossy()));
+    let mut child = spawn(url, username, &commands)?;
+
+    while child.try_wait()?.is_none() {
+        let written = fs::metadata(to).map(|metadata| metadata.len()).unwrap_or(0);
+
+        if !progress(written, total) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(());
+        }
+
+        sleep(Duration::from_millis(100));
+    }
+
+    finish(child)?;
+    progress(fs::metadata(to)?.len(), total);
+
+    Ok(())
+}
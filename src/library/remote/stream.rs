@@ -0,0 +1,339 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread::sleep,
+    time::Duration,
+};
+
+use tracing::{info, warn};
+use url::Url;
+
+use crate::media::traits::MediaStream;
+
+use super::{address, cached_path, sftp, trim_cache, webdav};
+
+/// How many times in a row the connection is made again after it drops, before the download is
+/// given up on.
+const MAX_RECONNECTS: u32 = 5;
+
+/// Tells apart the partial files of downloads that run at the same time, such as when a track is
+/// played again before its first download has finished.
+static NEXT_DOWNLOAD: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Default)]
+struct Progress {
+    /// How much of the file has been downloaded, in bytes.
+    downloaded: u64,
+    /// The size of the file, if the server gave it.
+    total: Option<u64>,
+    /// Whether the server has started sending the file.
+    started: bool,
+    finished: bool,
+    /// Why the download was given up on, if it was.
+    error: Option<String>,
+    /// How far into the file has been read.
+    position: u64,
+    /// Set when the file is no longer being read, to stop the download.
+    cancelled: bool,
+}
+
+/// A download of a remote file that's played while it's downloaded.
+#[derive(Debug, Default)]
+pub struct Download {
+    progress: Mutex<Progress>,
+    changed: Condvar,
+}
+
+impl Download {
+    fn progress(&self) -> MutexGuard<'_, Progress> {
+        self.progress.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn update(&self, update: impl FnOnce(&mut Progress)) {
+        update(&mut self.progress());
+        self.changed.notify_all();
+    }
+
+    /// Whether `ahead` bytes past where the file has been read have been downloaded. Also true
+    /// once the download has finished or been given up on, since waiting won't bring any more.
+    pub fn is_buffered(&self, ahead: u64) -> bool {
+        let progress = self.progress();
+        progress.finished
+            || progress.error.is_some()
+            || progress.downloaded >= progress.position.saturating_add(ahead)
+    }
+
+    /// Whether the whole file has been downloaded, or the download has been given up on.
+    pub fn is_finished(&self) -> bool {
+        let progress = self.progress();
+        progress.finished || progress.error.is_some()
+    }
+
+    /// The size of the file, if it's known.
+    pub fn total(&self) -> Option<u64> {
+        let progress = self.progress();
+        progress
+            .total
+            .or(progress.finished.then_some(progress.downloaded))
+    }
+}
+
+/// Reads a remote file while it's downloaded. Reading past what has been downloaded waits for
+/// the download to catch up. The download is stopped if this is dropped before it finishes.
+pub struct RemoteStream {
+    download: Arc<Download>,
+    file: File,
+    position: u64,
+}
+
+impl RemoteStream {
+    pub fn download(&self) -> Arc<Download> {
+        self.download.clone()
+    }
+}
+
+impl Read for RemoteStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut progress = self.download.progress();
+
+        loop {
+            if self.position < progress.downloaded {
+                let available = (progress.downloaded - self.position).min(buf.len() as u64);
+                drop(progress);
+
+                self.file.seek(SeekFrom::Start(self.position))?;
+                let read = self.file.read(&mut buf[..available as usize])?;
+                self.position += read as u64;
+                self.download
+                    .update(|progress| progress.position = self.position);
+
+                return Ok(read);
+            }
+
+            if progress.finished {
+                return Ok(0);
+            }
+
+            if let Some(err) = &progress.error {
+                return Err(io::Error::other(err.clone()));
+            }
+
+            progress = self
+                .download
+                .changed
+                .wait(progress)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+    }
+}
+
+impl Seek for RemoteStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self
+                .byte_len()
+                .and_then(|len| len.checked_add_signed(offset)),
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek position"))?;
+
+        self.position = position;
+        self.download
+            .update(|progress| progress.position = position);
+
+        Ok(position)
+    }
+}
+
+impl MediaStream for RemoteStream {
+    fn byte_len(&self) -> Option<u64> {
+        self.download.total()
+    }
+}
+
+impl Drop for RemoteStream {
+    fn drop(&mut self) {
+        self.download.update(|progress| progress.cancelled = true);
+    }
+}
+
+/// Starts downloading the remote file into the cache, and returns a stream that reads it as it
+/// downloads. Blocks until the server starts sending the file, so that a server that can't be
+/// reached is reported straight away. If the connection drops after that, it's made again and the
+/// download carries on from where it stopped. The cache is trimmed to `cache_size` bytes once
+/// the download finishes.
+pub fn stream(location: &Path, cache_size: u64) -> anyhow::Result<RemoteStream> {
+    let (url, username, password) = address(location)?;
+
+    let to = cached_path(location);
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let id = NEXT_DOWNLOAD.fetch_add(1, Ordering::Relaxed);
+    let partial = to.with_extension(format!("{}.{id}.part", std::process::id()));
+    let writer = File::create(&partial)?;
+    let file = File::open(&partial)?;
+
+    let download = Arc::new(Download::default());
+    let background = download.clone();
+
+    std::thread::Builder::new()
+        .name("remote-stream".to_string())
+        .spawn(move || {
+            let connection = Connection {
+                url,
+                username,
+                password,
+            };
+            download_in_background(&connection, writer, &partial, &to, &background);
+
+            if background.progress().finished {
+                trim_cache(cache_size, &to);
+            }
+        })?;
+
+    let mut progress = download.progress();
+    while !progress.started && progress.error.is_none() {
+        progress = download
+            .changed
+            .wait(progress)
+            .unwrap_or_else(|err| err.into_inner());
+    }
+
+    if let Some(err) = &progress.error {
+        anyhow::bail!("{err}");
+    }
+    drop(progress);
+
+    Ok(RemoteStream {
+        download,
+        file,
+        position: 0,
+    })
+}
+
+struct Connection {
+    url: Url,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl Connection {
+    /// Downloads the file into the partial file, carrying on from `from` bytes in.
+    fn download(
+        &self,
+        writer: &mut File,
+        partial: &Path,
+        from: u64,
+        download: &Download,
+    ) -> anyhow::Result<()> {
+        let progress = |written: u64, total: Option<u64>| {
+            download.update(|progress| {
+                progress.started = true;
+                progress.downloaded = written;
+                progress.total = total;
+            });
+
+            !download.progress().cancelled
+        };
+
+        if self.url.scheme() == "sftp" {
+            sftp::download_range(
+                &self.url,
+                self.username.as_deref(),
+                partial,
+                from > 0,
+                progress,
+            )
+        } else {
+            crate::RUNTIME.block_on(webdav::download_range(
+                &self.url,
+                self.username.as_deref(),
+                self.password.as_deref(),
+                writer,
+                from,
+                progress,
+            ))
+        }
+    }
+}
+
+/// Downloads the file, making the connection again whenever it drops. The partial file is moved
+/// into the cache once the whole file has been downloaded, and removed if the download is given
+/// up on or no longer needed.
+fn download_in_background(
+    connection: &Connection,
+    mut writer: File,
+    partial: &Path,
+    to: &Path,
+    download: &Download,
+) {
+    let url = &connection.url;
+    let mut reconnects = 0;
+
+    loop {
+        let from = download.progress().downloaded;
+        let result = connection.download(&mut writer, partial, from, download);
+
+        let progress = download.progress();
+        if progress.cancelled {
+            break;
+        }
+
+        let complete = progress
+            .total
+            .is_none_or(|total| progress.downloaded >= total);
+        let err = match result {
+            Ok(()) if complete => None,
+            Ok(()) => Some(anyhow::anyhow!("the connection was closed early")),
+            Err(err) => Some(err),
+        };
+
+        let Some(err) = err else {
+            drop(progress);
+
+            if let Err(err) = fs::rename(partial, to) {
+                warn!("Failed to move the download of {url} into the cache: {err}");
+            }
+
+            download.update(|progress| progress.finished = true);
+            return;
+        };
+
+        // the server couldn't be reached at all, which is reported rather than retried
+        if !progress.started {
+            drop(progress);
+            download.update(|progress| progress.error = Some(err.to_string()));
+            break;
+        }
+
+        // the count starts again once the download gets further
+        if progress.downloaded > from {
+            reconnects = 0;
+        }
+        drop(progress);
+
+        reconnects += 1;
+        if reconnects > MAX_RECONNECTS {
+            warn!("Gave up on downloading {url}: {err}");
+            download.update(|progress| progress.error = Some(err.to_string()));
+            break;
+        }
+
+        info!("Lost the connection while downloading {url}, reconnecting: {err}");
+        sleep(Duration::from_secs(1 << (reconnects - 1).min(4)));
+
+        if download.progress().cancelled {
+            break;
+        }
+    }
+
+    let _ = fs::remove_file(partial);
+}
@@ -0,0 +1,226 @@
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use chrono::DateTime;
+use rustc_hash::FxHashSet;
+use url::Url;
+use zed_reqwest::{
+    Client, Method, RequestBuilder, StatusCode,
+    header::{CONTENT_RANGE, CONTENT_TYPE, RANGE},
+};
+
+use crate::services::musicbrainz::USER_AGENT;
+
+use super::RemoteFile;
+
+const DAV_NAMESPACE: &str = "DAV:";
+
+/// Asks for only the properties that are needed to list the folder.
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<propfind xmlns="DAV:"><prop><resourcetype/><getlastmodified/></prop></propfind>"#;
+
+/// A file or folder in a folder listing.
+struct Entry {
+    url: Url,
+    folder: bool,
+    modified: u64,
+}
+
+fn client() -> anyhow::Result<Client> {
+    Ok(Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(Duration::from_secs(20))
+        // a connection that stops sending anything has dropped, even if it hasn't been closed
+        .read_timeout(Duration::from_secs(20))
+        .build()?)
+}
+
+fn request(
+    client: &Client,
+    method: Method,
+    url: &Url,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> RequestBuilder {
+    let request = client.request(method, url.clone());
+
+    match username {
+        Some(username) => request.basic_auth(username, password),
+        None => request,
+    }
+}
+
+/// The text of the first element with the name inside the node.
+fn dav_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.descendants()
+        .find(|child| child.has_tag_name((DAV_NAMESPACE, name)))
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+/// Reads the entries in a multi-status response to a listing of the folder. The folder itself is
+/// left out.
+fn parse_listing(folder: &Url, body: &str) -> anyhow::Result<Vec<Entry>> {
+    let document = roxmltree::Document::parse(body)?;
+    let mut entries = Vec::new();
+
+    for response in document
+        .descendants()
+        .filter(|node| node.has_tag_name((DAV_NAMESPACE, "response")))
+    {
+        let Some(href) = dav_text(response, "href").and_then(|href| folder.join(href).ok()) else {
+            continue;
+        };
+
+        // some servers give full addresses, which are made to match the folder's so that
+        // locations always start with the address in the settings
+        let mut url = folder.clone();
+        url.set_path(href.path());
+
+        if url.path().trim_end_matches('/') == folder.path().trim_end_matches('/') {
+            continue;
+        }
+
+        let is_folder = response
+            .descendants()
+            .any(|node| node.has_tag_name((DAV_NAMESPACE, "collection")));
+        let modified = dav_text(response, "getlastmodified")
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.timestamp().max(0) as u64)
+            .unwrap_or_default();
+
+        entries.push(Entry {
+            url,
+            folder: is_folder,
+            modified,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Lists the files in the folder and the folders inside it, one folder at a time, since many
+/// servers refuse to list a whole tree at once.
+pub async fn list_files(
+    root: &Url,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> anyhow::Result<Vec<RemoteFile>> {
+    let client = client()?;
+    let propfind = Method::from_bytes(b"PROPFIND")?;
+
+    let mut files = Vec::new();
+    let mut folders = vec![root.clone()];
+    let mut visited = FxHashSet::default();
+
+    while let Some(folder) = folders.pop() {
+        if !visited.insert(folder.path().trim_end_matches('/').to_string()) {
+            continue;
+        }
+
+        let body = request(&client, propfind.clone(), &folder, username, password)
+            .header("Depth", "1")
+            .header(CONTENT_TYPE, "application/xml")
+            .body(PROPFIND_BODY)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let entries = parse_listing(&folder, &body)
+            .with_context(|| format!("the listing of {folder} couldn't be read"))?;
+
+        for entry in entries {
+            if entry.folder {
+                let mut url = entry.url;
+                if !url.path().ends_with('/') {
+                    url.set_path(&format!("{}/", url.path()));
+                }
+
+                // folders elsewhere on the server aren't part of the library
+                if url.as_str().starts_with(root.as_str()) {
+                    folders.push(url);
+                }
+            } else {
+                files.push(RemoteFile {
+                    location: PathBuf::from(entry.url.as_str()),
+                    modified: entry.modified,
+                });
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Downloads the file at the address to the path.
+pub async fn download(
+    url: &Url,
+    username: Option<&str>,
+    password: Option<&str>,
+    to: &Path,
+) -> anyhow::Result<()> {
+    let mut file = File::create(to)?;
+    download_range(url, username, password, &mut file, 0, |_, _| true).await
+}
+
+/// Downloads the file at the address into `file`, starting `from` bytes in, where an earlier
+/// download that was cut off stopped. `progress` is called with how much of the file has been
+/// written, and its size if the server gave it, after every chunk. The download stops early if
+/// it returns false.
+pub async fn download_range(
+    url: &Url,
+    username: Option<&str>,
+    password: Option<&str>,
+    file: &mut File,
+    from: u64,
+    mut progress: impl FnMut(u64, Option<u64>) -> bool,
+) -> anyhow::Result<()> {
+    let mut request = request(&client()?, Method::GET, url, username, password);
+    if from > 0 {
+        request = request.header(RANGE, format!("bytes={from}-"));
+    }
+
+    let mut response = request.send().await?.error_for_status()?;
+
+    // servers that don't support ranges send the whole file, so the start is skipped
+    let (mut skip, total) = if response.status() == StatusCode::PARTIAL_CONTENT {
+        let total = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|range| range.to_str().ok())
+            .and_then(|range| range.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse().ok());
+        (0, total)
+    } else {
+        (from, response.content_length())
+    };
+
+    let mut written = from;
+    file.seek(SeekFrom::Start(from))?;
+
+    if !progress(written, total) {
+        return Ok(());
+    }
+
+    while let Some(chunk) = response.chunk().await? {
+        let start = (skip as usize).min(chunk.len());
+        skip -= start as u64;
+
+        file.write_all(&chunk[start..])?;
+        written += (chunk.len() - start) as u64;
+
+        if !progress(written, total) {
+            return Ok(());
+        }
+    }
+    file.flush()?;
+
+    Ok(())
+}
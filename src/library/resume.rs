@@ -0,0 +1,166 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// The name of the file kept in the synced folder.
+pub const RESUME_FILE_NAME: &str = "hummingbird-resume.json";
+
+/// Tracks at least this long (in seconds) are shared, along with every audiobook chapter.
+pub const LONG_TRACK: i64 = 20 * 60;
+
+/// How many tracks the file remembers. The tracks that were left the longest ago are forgotten
+/// first.
+const MAX_POINTS: usize = 500;
+
+/// Held while the file is being changed, so that two saves don't undo each other.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Tracks are matched between computers by their album and title, since the files are rarely in
+/// the same place on each.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackKey {
+    pub album: String,
+    pub title: String,
+}
+
+/// Where a track was left on one of the computers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResumePoint {
+    pub track: TrackKey,
+    /// In seconds.
+    pub position: u64,
+    /// The name of the computer that saved the point.
+    pub device: String,
+    /// When the point was saved, in seconds since the Unix epoch.
+    pub updated: u64,
+}
+
+impl ResumePoint {
+    pub fn new(track: TrackKey, position: u64, device: String) -> Self {
+        let updated = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+
+        ResumePoint {
+            track,
+            position,
+            device,
+            updated,
+        }
+    }
+}
+
+/// The contents of the file. Each track only has the point saved by the computer that played it
+/// last, so a point from another computer is always newer than anything saved here.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ResumeFile {
+    #[serde(default)]
+    pub points: Vec<ResumePoint>,
+    /// The track that was played last, on any computer.
+    #[serde(default)]
+    pub current: Option<ResumePoint>,
+    /// Whether the current track was playing (rather than paused) when it was saved.
+    #[serde(default)]
+    pub playing: bool,
+}
+
+impl ResumeFile {
+    /// Reads the file in the folder. A missing file is the same as an empty one.
+    pub fn read(folder: &Path) -> anyhow::Result<Self> {
+        match std::fs::read(folder.join(RESUME_FILE_NAME)) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(ResumeFile::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn point(&self, track: &TrackKey) -> Option<&ResumePoint> {
+        self.points.iter().find(|point| point.track == *track)
+    }
+
+    /// Replaces the track's point, and makes it the current track.
+    pub fn save(&mut self, point: ResumePoint, playing: bool) {
+        self.remove(&point.track);
+        self.current = Some(point.clone());
+        self.playing = playing;
+        self.points.push(point);
+
+        if self.points.len() > MAX_POINTS {
+            self.points
+                .sort_by_key(|point| std::cmp::Reverse(point.updated));
+            self.points.truncate(MAX_POINTS);
+        }
+    }
+
+    pub fn remove(&mut self, track: &TrackKey) {
+        self.points.retain(|point| point.track != *track);
+    }
+}
+
+/// Reads the file in the folder, changes it and writes it back. The new file is written next to
+/// the old one and moved over it, so that sync clients never pick up half of it.
+pub fn update_resume_file(
+    folder: &Path,
+    change: impl FnOnce(&mut ResumeFile),
+) -> anyhow::Result<()> {
+    let _lock = FILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+    let mut file = ResumeFile::read(folder)?;
+    change(&mut file);
+
+    let temp = folder.join(format!(".{RESUME_FILE_NAME}.tmp"));
+    std::fs::write(&temp, serde_json::to_vec_pretty(&file)?)?;
+    std::fs::rename(&temp, folder.join(RESUME_FILE_NAME))?;
+
+    Ok(())
+}
+
+/// The name this computer goes by when no name is set: its host name, if it can be found.
+pub fn default_device_name() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Hummingbird".to_string())
+}
+
+/// Looks up the track in the file, returning its key, its duration in seconds and whether it is
+/// an audiobook chapter. Returns `None` if the file isn't in the library or has no album.
+pub async fn find_resume_track(
+    pool: &SqlitePool,
+    path: &str,
+) -> Result<Option<(TrackKey, i64, bool)>, sqlx::Error> {
+    let track: Option<(String, String, i64, bool)> =
+        sqlx::query_as(include_str!("../../queries/resume/find_resume_track.sql"))
+            .bind(path)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(track.map(|(album, title, duration, audiobook)| {
+        (TrackKey { album, title }, duration, audiobook)
+    }))
+}
+
+/// Finds the track in this library that the key refers to, returning its ID, location and album.
+pub async fn find_track_by_key(
+    pool: &SqlitePool,
+    key: &TrackKey,
+) -> Result<Option<(i64, PathBuf, Option<i64>)>, sqlx::Error> {
+    let track: Option<(i64, String, Option<i64>)> =
+        sqlx::query_as(include_str!("../../queries/resume/find_track_by_key.sql"))
+            .bind(&key.album)
+            .bind(&key.title)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(track.map(|(id, location, album_id)| (id, PathBuf::from(location), album_id)))
+}
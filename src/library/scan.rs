@@ -1,32 +1,48 @@
 use std::{
     fs::{self, File},
-    io::{BufReader, Cursor, Write},
+    io::{BufReader, Write},
     path::{Path, PathBuf},
-    time::SystemTime,
+    time::{Duration, Instant, SystemTime},
 };
 
+use anyhow::Context;
 use globwalk::GlobWalkerBuilder;
 use gpui::{App, Global};
-use image::{DynamicImage, EncodableLayout, codecs::jpeg::JpegEncoder, imageops::thumbnail};
-use rustc_hash::FxHashMap;
-use sqlx::SqlitePool;
-use tokio::sync::mpsc::{
-    Receiver, Sender, UnboundedReceiver, UnboundedSender, channel, unbounded_channel,
-};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher, event::ModifyKind};
+use rustc_hash::{FxHashMap, FxHashSet};
+use sqlx::{Acquire, SqliteConnection, SqlitePool};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tracing::{debug, error, info, warn};
 
 /// The version of the scanning process. If this version number is incremented, a re-scan of all
 /// files will be forced (see [ScanCommand::ForceScan]).
-const SCAN_VERSION: u16 = 1;
+const SCAN_VERSION: u16 = 4;
+
+/// The number of files that are written to the database in a single transaction.
+const BATCH_SIZE: usize = 50;
+
+/// How long watched folders have to go without changing before they're scanned, so that copying
+/// an album in doesn't start a scan for every file.
+const WATCH_DELAY: Duration = Duration::from_secs(2);
 
 use crate::{
+    library::{
+        art::{remove_unused_art, store_album_art},
+        artists::{ArtistAliases, apply_artist_aliases},
+        audiobooks::mark_audiobooks,
+        genres::{GenreAliases, apply_genre_aliases},
+        guess::{fill_metadata, guess_tags, is_untagged},
+        remote::{self, is_remote},
+        sync::detect_devices,
+        types::TrackSource,
+    },
     media::{
         builtin::symphonia::SymphoniaProvider,
-        metadata::Metadata,
+        metadata::{CueTrack, Metadata, StreamInfo},
         traits::{MediaPlugin, MediaProvider},
     },
     settings::scan::ScanSettings,
-    ui::{app::get_dirs, models::Models},
+    ui::models::Models,
 };
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -38,7 +54,7 @@ pub enum ScanEvent {
     ScanCompleteIdle,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone)]
 enum ScanCommand {
     Scan,
     /// A force-scan is different to a regular scan in that it will ignore all previous data and
@@ -47,39 +63,48 @@ enum ScanCommand {
     /// and is usually triggered by the scan version changing (see [SCAN_VERSION]).
     ForceScan,
     Stop,
+    UpdateSettings(ScanSettings),
 }
 
+/// Sends commands to the scan thread. Commands are sent without blocking, and are handled in the
+/// order they're sent.
 pub struct ScanInterface {
     events_rx: Option<UnboundedReceiver<ScanEvent>>,
-    cmd_tx: Sender<ScanCommand>,
+    cmd_tx: UnboundedSender<ScanCommand>,
 }
 
 impl ScanInterface {
     pub(self) fn new(
         events_rx: Option<UnboundedReceiver<ScanEvent>>,
-        cmd_tx: Sender<ScanCommand>,
+        cmd_tx: UnboundedSender<ScanCommand>,
     ) -> Self {
         ScanInterface { events_rx, cmd_tx }
     }
 
     pub fn scan(&self) {
         self.cmd_tx
-            .blocking_send(ScanCommand::Scan)
+            .send(ScanCommand::Scan)
             .expect("could not send scan start command");
     }
 
     pub fn force_scan(&self) {
         self.cmd_tx
-            .blocking_send(ScanCommand::ForceScan)
+            .send(ScanCommand::ForceScan)
             .expect("could not send force re-scan start command");
     }
 
     pub fn stop(&self) {
         self.cmd_tx
-            .blocking_send(ScanCommand::Stop)
+            .send(ScanCommand::Stop)
             .expect("could not send scan stop command");
     }
 
+    pub fn update_settings(&self, settings: ScanSettings) {
+        self.cmd_tx
+            .send(ScanCommand::UpdateSettings(settings))
+            .expect("could not send scan settings");
+    }
+
     pub fn start_broadcast(&mut self, cx: &mut App) {
         let mut events_rx = None;
         std::mem::swap(&mut self.events_rx, &mut events_rx);
@@ -90,15 +115,13 @@ impl ScanInterface {
             return;
         };
         cx.spawn(async move |cx| {
-            loop {
-                while let Some(event) = events_rx.recv().await {
-                    state_model
-                        .update(cx, |m, cx| {
-                            *m = event;
-                            cx.notify()
-                        })
-                        .expect("failed to update scan state model");
-                }
+            while let Some(event) = events_rx.recv().await {
+                state_model
+                    .update(cx, |m, cx| {
+                        *m = event;
+                        cx.notify()
+                    })
+                    .expect("failed to update scan state model");
             }
         })
         .detach();
@@ -107,6 +130,15 @@ impl ScanInterface {
 
 impl Global for ScanInterface {}
 
+/// The time spent in each stage of a scan.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScanTimings {
+    /// Time spent opening files and reading their metadata and embedded art.
+    pub decode: Duration,
+    /// Time spent processing album art and writing to the database.
+    pub database: Duration,
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ScanState {
     Idle,
@@ -117,9 +149,11 @@ pub enum ScanState {
 
 pub struct ScanThread {
     event_tx: UnboundedSender<ScanEvent>,
-    command_rx: Receiver<ScanCommand>,
+    command_rx: UnboundedReceiver<ScanCommand>,
     pool: SqlitePool,
     scan_settings: ScanSettings,
+    genre_aliases: GenreAliases,
+    artist_aliases: ArtistAliases,
     visited: Vec<PathBuf>,
     discovered: Vec<PathBuf>,
     to_process: Vec<PathBuf>,
@@ -127,8 +161,19 @@ pub struct ScanThread {
     provider_table: Vec<(&'static [&'static str], Box<dyn MediaProvider>)>,
     scan_record: FxHashMap<PathBuf, u64>,
     scan_record_path: Option<PathBuf>,
+    /// The directory full-size album art is stored in.
+    art_dir: PathBuf,
+    /// Files that have been read but not yet written to the database (see [BATCH_SIZE]), along
+    /// with their stored album art. Files that couldn't be read are quarantined along with the
+    /// error.
+    batch: Vec<(PathBuf, Result<ReadFile, String>)>,
+    /// The last album art that was stored, by the hash of the image it was read as. The files of
+    /// an album are usually scanned one after another with the same art, so this saves decoding
+    /// it again for each of them.
+    last_art: Option<(md5::Digest, Option<StoredArt>)>,
     scanned: u64,
     discovered_total: u64,
+    timings: ScanTimings,
     /// Whether or not to force a rescan all files. This is set to true when a force-scan is
     /// requested, which results in all previous data being ignored.
     is_force: bool,
@@ -136,6 +181,24 @@ pub struct ScanThread {
     /// determine whether or not an album should be inserted, instead of checking the
     /// album_title_artist_id_idx index.
     force_encountered_albums: Vec<i64>,
+    /// Watches the scan paths that have watching turned on, along with the channel it sends
+    /// changes to. Only set up when scanning in the background.
+    watcher: Option<(
+        RecommendedWatcher,
+        std::sync::mpsc::Receiver<notify::Result<Event>>,
+    )>,
+    watched: Vec<PathBuf>,
+    /// The folders files are found in, along with how the files in each entered the library.
+    /// Worked out when a scan starts (see [ScanThread::find_roots]).
+    roots: Vec<(PathBuf, TrackSource)>,
+    /// When a watched folder last changed, if it hasn't been scanned since.
+    changed_at: Option<Instant>,
+    /// The album art found in remote folders, by the folder it's in. Files in remote folders
+    /// can't be looked for when they're scanned, so they're picked out of the folder listings.
+    remote_art: FxHashMap<PathBuf, PathBuf>,
+    /// When the current scan started. Art written after this may not be in the database yet, so
+    /// it's left alone when unused art is removed.
+    started_at: SystemTime,
 }
 
 fn build_provider_table() -> Vec<(&'static [&'static str], Box<dyn MediaProvider>)> {
@@ -158,20 +221,57 @@ fn file_is_scannable_with_provider(path: &Path, exts: &&[&str]) -> bool {
     false
 }
 
-type FileInformation = (Metadata, u64, Option<Box<[u8]>>);
+type FileInformation = (Metadata, u64, Option<Box<[u8]>>, StreamInfo);
+
+/// The hash of an album's art in the art directory, and its thumbnail.
+type StoredArt = (String, Vec<u8>);
 
+/// A file that has been read, with its album art already stored.
+type ReadFile = (FileInformation, Option<StoredArt>);
+
+/// Reads the file's metadata, returning a description of the error if it can't be read.
 fn scan_file_with_provider(
     path: &PathBuf,
     provider: &mut Box<dyn MediaProvider>,
-) -> Result<FileInformation, ()> {
-    let src = std::fs::File::open(path).map_err(|_| ())?;
-    provider.open(src, None).map_err(|_| ())?;
-    provider.start_playback().map_err(|_| ())?;
-    let metadata = provider.read_metadata().cloned().map_err(|_| ())?;
-    let image = provider.read_image().map_err(|_| ())?;
-    let len = provider.duration_secs().map_err(|_| ())?;
-    provider.close().map_err(|_| ())?;
-    Ok((metadata, len, image))
+) -> Result<FileInformation, String> {
+    let src = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    provider.open(src, None).map_err(|e| e.to_string())?;
+    provider.start_playback().map_err(|e| e.to_string())?;
+    let metadata = provider
+        .read_metadata()
+        .cloned()
+        .map_err(|e| e.to_string())?;
+    let image = provider.read_image().map_err(|e| e.to_string())?;
+    let len = provider.duration_secs().map_err(|e| e.to_string())?;
+    // files with a stream that can't be described are still added, without a format badge
+    let stream_info = provider.stream_info().unwrap_or_default();
+    provider.close().map_err(|e| e.to_string())?;
+    Ok((metadata, len, image, stream_info))
+}
+
+/// Whether the file is named like album art (see [scan_path_for_album_art]).
+fn is_album_art_name(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let name = name.to_lowercase();
+
+    ["folder", "cover", "front"].iter().any(|stem| {
+        ["jpg", "jpeg", "png"]
+            .iter()
+            .any(|ext| name == format!("{stem}.{ext}"))
+    })
+}
+
+/// Downloads the remote file to a temporary file, returning its path.
+fn download_remote(path: &Path) -> anyhow::Result<PathBuf> {
+    let mut temp = std::env::temp_dir().join(format!("hummingbird-remote-{}", std::process::id()));
+    if let Some(extension) = path.extension() {
+        temp.set_extension(extension);
+    }
+
+    remote::download(path, &temp)?;
+    Ok(temp)
 }
 
 // Returns the first image (cover/front/folder.jpeg/png/jpeg) in the track's containing folder
@@ -196,71 +296,192 @@ fn scan_path_for_album_art(path: &Path) -> Option<Box<[u8]>> {
 }
 
 impl ScanThread {
-    pub fn start(pool: SqlitePool, settings: ScanSettings) -> ScanInterface {
-        let (cmd_tx, commands_rx) = channel(10);
+    fn new(
+        pool: SqlitePool,
+        settings: ScanSettings,
+        art_dir: PathBuf,
+        event_tx: UnboundedSender<ScanEvent>,
+        command_rx: UnboundedReceiver<ScanCommand>,
+    ) -> Self {
+        remote::set_sources(&settings.remotes);
+
+        ScanThread {
+            event_tx,
+            command_rx,
+            pool,
+            visited: Vec::new(),
+            discovered: Vec::new(),
+            to_process: Vec::new(),
+            scan_state: ScanState::Idle,
+            provider_table: build_provider_table(),
+            genre_aliases: GenreAliases::new(&settings.genre_aliases),
+            artist_aliases: ArtistAliases::new(&settings.artist_aliases),
+            scan_settings: settings,
+            scan_record: FxHashMap::default(),
+            scan_record_path: None,
+            art_dir,
+            batch: Vec::new(),
+            scanned: 0,
+            discovered_total: 0,
+            timings: ScanTimings::default(),
+            is_force: false,
+            force_encountered_albums: Vec::new(),
+            watcher: None,
+            watched: Vec::new(),
+            roots: Vec::new(),
+            changed_at: None,
+            remote_art: FxHashMap::default(),
+            last_art: None,
+            started_at: SystemTime::now(),
+        }
+    }
+
+    pub fn start(
+        pool: SqlitePool,
+        settings: ScanSettings,
+        art_dir: PathBuf,
+        scan_record_path: PathBuf,
+    ) -> ScanInterface {
+        let (cmd_tx, commands_rx) = unbounded_channel();
         let (events_tx, events_rx) = unbounded_channel();
 
         std::thread::Builder::new()
             .name("scanner".to_string())
             .spawn(move || {
-                let mut thread = ScanThread {
-                    event_tx: events_tx,
-                    command_rx: commands_rx,
-                    pool,
-                    visited: Vec::new(),
-                    discovered: Vec::new(),
-                    to_process: Vec::new(),
-                    scan_state: ScanState::Idle,
-                    provider_table: build_provider_table(),
-                    scan_settings: settings,
-                    scan_record: FxHashMap::default(),
-                    scan_record_path: None,
-                    scanned: 0,
-                    discovered_total: 0,
-                    is_force: false,
-                    force_encountered_albums: Vec::new(),
-                };
+                let mut thread = ScanThread::new(pool, settings, art_dir, events_tx, commands_rx);
 
-                thread.run();
+                thread.run(scan_record_path);
             })
             .expect("could not start playback thread");
 
         ScanInterface::new(Some(events_rx), cmd_tx)
     }
 
-    fn run(&mut self) {
-        let dirs = get_dirs();
-        let directory = dirs.data_dir();
-        if !directory.exists() {
-            fs::create_dir(directory).expect("couldn't create data directory");
+    /// Scans the configured paths on the current thread, returning once the scan is complete.
+    /// The scan record at `scan_record_path` is used to skip unchanged files unless `force` is
+    /// set. Returns the number of files scanned and the time spent in each stage of the scan.
+    pub fn scan_blocking(
+        pool: SqlitePool,
+        settings: ScanSettings,
+        art_dir: PathBuf,
+        scan_record_path: PathBuf,
+        force: bool,
+    ) -> (u64, ScanTimings) {
+        let (_, commands_rx) = unbounded_channel();
+        let (events_tx, _events_rx) = unbounded_channel();
+
+        let mut thread = ScanThread::new(pool, settings, art_dir, events_tx, commands_rx);
+
+        if !force {
+            thread.read_scan_record(&scan_record_path);
         }
-        let file_path = directory.join("scan_record.json");
 
-        if file_path.exists() {
-            let file = File::open(&file_path);
+        thread.scan_record_path = Some(scan_record_path);
+        thread.discovered = thread.scan_settings.folders();
+        thread.find_roots(&[]);
+        thread.is_force = force;
+        thread.scan_state = ScanState::Cleanup;
+        thread.run_blocking();
 
-            let Ok(file) = file else {
-                return;
-            };
-            let reader = BufReader::new(file);
+        (thread.scanned, thread.timings)
+    }
 
-            match serde_json::from_reader(reader) {
-                Ok(scan_record) => {
-                    self.scan_record = scan_record;
-                }
-                Err(e) => {
-                    error!("could not read scan record: {:?}", e);
-                    error!("scanning will be slow until the scan record is rebuilt");
-                }
+    /// Adds files and folders to the library on the current thread, without adding them to the
+    /// scan paths. The tracks found are recorded as imported. Returns the number of files
+    /// scanned.
+    pub fn import_blocking(
+        pool: SqlitePool,
+        settings: ScanSettings,
+        art_dir: PathBuf,
+        scan_record_path: PathBuf,
+        paths: &[PathBuf],
+    ) -> anyhow::Result<u64> {
+        let (_, commands_rx) = unbounded_channel();
+        let (events_tx, _events_rx) = unbounded_channel();
+
+        let mut thread = ScanThread::new(pool, settings, art_dir, events_tx, commands_rx);
+        thread.read_scan_record(&scan_record_path);
+        thread.scan_record_path = Some(scan_record_path);
+
+        let mut imports = Vec::new();
+        for path in paths {
+            let path = path
+                .canonicalize()
+                .with_context(|| format!("could not find {}", path.display()))?;
+
+            if path.is_dir() {
+                thread.discovered.push(path.clone());
+            } else if thread.file_is_scannable(&path) {
+                thread.to_process.push(path.clone());
+                thread.discovered_total += 1;
+            }
+
+            imports.push(path);
+        }
+
+        thread.find_roots(&imports);
+        thread.scan_state = ScanState::Discovering;
+        thread.run_blocking();
+
+        Ok(thread.scanned)
+    }
+
+    /// Runs the scan that has been set up until it's complete.
+    fn run_blocking(&mut self) {
+        loop {
+            match self.scan_state {
+                ScanState::Idle => break,
+                ScanState::Cleanup => self.cleanup(),
+                ScanState::Discovering => self.discover(),
+                ScanState::Scanning => self.scan(),
             }
         }
+    }
+
+    /// Works out how the files in each folder that's about to be scanned entered the library.
+    /// Imported paths come first, so that they're matched before any scan path they're in.
+    fn find_roots(&mut self, imports: &[PathBuf]) {
+        let devices: Vec<PathBuf> = detect_devices()
+            .into_iter()
+            .map(|device| device.path)
+            .collect();
+
+        let folders = self.scan_settings.folders().into_iter().map(|folder| {
+            let folder = folder.canonicalize().unwrap_or(folder);
+            let source = if devices.iter().any(|device| folder.starts_with(device)) {
+                TrackSource::Device
+            } else {
+                TrackSource::Scan
+            };
+
+            (folder, source)
+        });
+
+        let remotes = self
+            .scan_settings
+            .remotes
+            .iter()
+            .filter_map(|source| remote::root(source).ok())
+            .map(|root| (PathBuf::from(root.as_str()), TrackSource::Remote));
+
+        self.roots = imports
+            .iter()
+            .map(|path| (path.clone(), TrackSource::Import))
+            .chain(folders)
+            .chain(remotes)
+            .collect();
+    }
 
-        self.scan_record_path = Some(file_path);
+    fn run(&mut self, scan_record_path: PathBuf) {
+        self.read_scan_record(&scan_record_path);
+
+        self.scan_record_path = Some(scan_record_path);
+        self.update_watches();
 
         loop {
             self.read_commands();
+            self.read_watch_events();
 
-            // TODO: start file watcher to update db automatically when files are added or removed
             match self.scan_state {
                 ScanState::Idle => {
                     std::thread::sleep(std::time::Duration::from_millis(100));
@@ -281,48 +502,53 @@ impl ScanThread {
     fn read_commands(&mut self) {
         while let Ok(command) = self.command_rx.try_recv() {
             match command {
-                ScanCommand::Scan => {
-                    if self.scan_state == ScanState::Idle {
-                        self.discovered = self.scan_settings.paths.clone();
-                        self.scan_state = ScanState::Cleanup;
-                        self.scanned = 0;
-                        self.discovered_total = 0;
-                        self.discovered = self.scan_settings.paths.clone();
-                        self.visited.clear();
-                        self.to_process.clear();
-                        self.is_force = false;
-
-                        self.event_tx
-                            .send(ScanEvent::Cleaning)
-                            .expect("could not send scan event");
-                    }
-                }
-                ScanCommand::ForceScan => {
-                    if self.scan_state == ScanState::Idle {
-                        self.discovered = self.scan_settings.paths.clone();
-                        self.scan_state = ScanState::Cleanup;
-                        self.scanned = 0;
-                        self.discovered_total = 0;
-                        self.discovered = self.scan_settings.paths.clone();
-                        self.visited.clear();
-                        self.to_process.clear();
-
-                        self.is_force = true;
-                        self.force_encountered_albums.clear();
-
-                        self.scan_record = FxHashMap::default();
-
-                        self.event_tx
-                            .send(ScanEvent::Cleaning)
-                            .expect("could not send scan event");
-                    }
-                }
+                ScanCommand::Scan => self.start_scan(false),
+                ScanCommand::ForceScan => self.start_scan(true),
                 ScanCommand::Stop => {
+                    self.flush_batch();
                     self.scan_state = ScanState::Idle;
                     self.visited.clear();
                     self.discovered.clear();
                     self.to_process.clear();
                 }
+                ScanCommand::UpdateSettings(settings) => {
+                    let aliases_changed =
+                        settings.genre_aliases != self.scan_settings.genre_aliases;
+                    let artist_aliases_changed =
+                        settings.artist_aliases != self.scan_settings.artist_aliases;
+                    let audiobooks_changed =
+                        settings.audiobook_folders() != self.scan_settings.audiobook_folders();
+                    let folders_added = settings
+                        .folders()
+                        .iter()
+                        .any(|folder| !self.scan_settings.folders().contains(folder))
+                        || settings
+                            .remotes
+                            .iter()
+                            .any(|source| !self.scan_settings.remotes.contains(source));
+                    remote::set_sources(&settings.remotes);
+                    self.scan_settings = settings;
+                    self.update_watches();
+
+                    if aliases_changed {
+                        self.genre_aliases = GenreAliases::new(&self.scan_settings.genre_aliases);
+                        self.apply_genre_aliases();
+                    }
+
+                    if artist_aliases_changed {
+                        self.artist_aliases =
+                            ArtistAliases::new(&self.scan_settings.artist_aliases);
+                        self.apply_artist_aliases();
+                    }
+
+                    if audiobooks_changed {
+                        self.mark_audiobooks();
+                    }
+
+                    if folders_added {
+                        self.start_scan(false);
+                    }
+                }
             }
         }
 
@@ -401,18 +627,32 @@ impl ScanThread {
         self.visited.push(path.clone());
     }
 
-    async fn insert_artist(&self, metadata: &Metadata) -> anyhow::Result<Option<i64>> {
-        let artist = metadata.album_artist.clone().or(metadata.artist.clone());
+    async fn insert_artist(
+        &self,
+        conn: &mut SqliteConnection,
+        metadata: &Metadata,
+    ) -> anyhow::Result<Option<i64>> {
+        let artist = metadata
+            .album_artist
+            .as_deref()
+            .or(metadata.artist.as_deref());
 
-        let Some(artist) = artist else {
+        let Some(artist) = artist.map(|artist| self.artist_aliases.apply(artist)) else {
             return Ok(None);
         };
 
+        let mbid = if metadata.album_artist.is_some() {
+            metadata.mbid_album_artist.as_ref()
+        } else {
+            metadata.mbid_artist.as_ref()
+        };
+
         let result: Result<(i64,), sqlx::Error> =
             sqlx::query_as(include_str!("../../queries/scan/create_artist.sql"))
-                .bind(&artist)
-                .bind(metadata.artist_sort.as_ref().unwrap_or(&artist))
-                .fetch_one(&self.pool)
+                .bind(artist)
+                .bind(metadata.artist_sort.as_deref().unwrap_or(artist))
+                .bind(mbid)
+                .fetch_one(&mut *conn)
                 .await;
 
         match result {
@@ -420,14 +660,22 @@ impl ScanThread {
             Err(sqlx::Error::RowNotFound) => {
                 let result: Result<(i64,), sqlx::Error> =
                     sqlx::query_as(include_str!("../../queries/scan/get_artist_id.sql"))
-                        .bind(&artist)
-                        .fetch_one(&self.pool)
+                        .bind(artist)
+                        .fetch_one(&mut *conn)
                         .await;
 
-                match result {
-                    Ok(v) => Ok(Some(v.0)),
-                    Err(e) => Err(e.into()),
+                let id = result?.0;
+
+                // artists scanned before their files were tagged with an ID pick it up later
+                if let Some(mbid) = mbid {
+                    sqlx::query(include_str!("../../queries/scan/set_artist_mbid.sql"))
+                        .bind(id)
+                        .bind(mbid)
+                        .execute(&mut *conn)
+                        .await?;
                 }
+
+                Ok(Some(id))
             }
             Err(e) => Err(e.into()),
         }
@@ -435,9 +683,10 @@ impl ScanThread {
 
     async fn insert_album(
         &mut self,
+        conn: &mut SqliteConnection,
         metadata: &Metadata,
         artist_id: Option<i64>,
-        image: &Option<Box<[u8]>>,
+        art: Option<&StoredArt>,
     ) -> anyhow::Result<Option<i64>> {
         let Some(album) = &metadata.album else {
             return Ok(None);
@@ -452,7 +701,7 @@ impl ScanThread {
             sqlx::query_as(include_str!("../../queries/scan/get_album_id.sql"))
                 .bind(album)
                 .bind(&mbid)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut *conn)
                 .await;
 
         let should_force = if let Ok((id,)) = &result
@@ -470,65 +719,16 @@ impl ScanThread {
         match (result, should_force) {
             (Ok(v), false) => Ok(Some(v.0)),
             (Err(sqlx::Error::RowNotFound), _) | (Ok(_), true) => {
-                let (resized_image, thumb) = match image {
-                    Some(image) => {
-                        // if there is a decode error, just ignore it and pretend there is no image
-                        let mut decoded = image::ImageReader::new(Cursor::new(&image))
-                            .with_guessed_format()?
-                            .decode()?
-                            .into_rgb8();
-
-                        // for some reason, thumbnails don't load properly when saved as rgb8
-                        // also, into_rgba8() causes the application to crash on certain images
-                        //
-                        // no, I don't no why, and no I can't fix it upstream
-                        // this will have to do for now
-                        let decoded_rgba = DynamicImage::ImageRgb8(decoded.clone()).into_rgba8();
-
-                        let thumb = thumbnail(&decoded_rgba, 70, 70);
-
-                        let mut buf: Cursor<Vec<u8>> = Cursor::new(Vec::new());
-
-                        thumb
-                            .write_to(&mut buf, image::ImageFormat::Bmp)
-                            .expect("i don't know how Cursor could fail");
-                        buf.flush().expect("could not flush buffer");
-
-                        let resized =
-                            if decoded.dimensions().0 <= 1024 || decoded.dimensions().1 <= 1024 {
-                                image.clone().to_vec()
-                            } else {
-                                decoded = image::imageops::resize(
-                                    &decoded,
-                                    1024,
-                                    1024,
-                                    image::imageops::FilterType::Lanczos3,
-                                );
-                                let mut buf: Cursor<Vec<u8>> = Cursor::new(Vec::new());
-                                let mut encoder = JpegEncoder::new_with_quality(&mut buf, 70);
-
-                                encoder.encode(
-                                    decoded.as_bytes(),
-                                    decoded.width(),
-                                    decoded.height(),
-                                    image::ExtendedColorType::Rgb8,
-                                )?;
-                                buf.flush()?;
-
-                                buf.get_mut().clone()
-                            };
-
-                        (Some(resized), Some(buf.get_mut().clone()))
-                    }
-                    None => (None, None),
-                };
+                let (image_hash, thumb) = art
+                    .map(|(hash, thumb)| (hash.as_str(), thumb.as_slice()))
+                    .unzip();
 
                 let result: (i64,) =
                     sqlx::query_as(include_str!("../../queries/scan/create_album.sql"))
                         .bind(album)
                         .bind(metadata.sort_album.as_ref().unwrap_or(album))
                         .bind(artist_id)
-                        .bind(resized_image)
+                        .bind(image_hash)
                         .bind(thumb)
                         .bind(metadata.date)
                         .bind(metadata.year)
@@ -536,7 +736,7 @@ impl ScanThread {
                         .bind(&metadata.catalog)
                         .bind(&metadata.isrc)
                         .bind(&mbid)
-                        .fetch_one(&self.pool)
+                        .fetch_one(&mut *conn)
                         .await?;
 
                 Ok(Some(result.0))
@@ -547,21 +747,26 @@ impl ScanThread {
 
     async fn insert_track(
         &self,
+        conn: &mut SqliteConnection,
         metadata: &Metadata,
         album_id: Option<i64>,
         path: &Path,
-        length: u64,
+        cue_track: Option<&CueTrack>,
+        file: &FileInformation,
     ) -> anyhow::Result<()> {
         if album_id.is_none() {
             return Ok(());
         }
 
+        let (_, length, _, stream_info) = file;
+        let length = cue_track.map_or(*length, |track| (track.end - track.start).max(0.0) as u64);
+
         let disc_num = metadata.disc_current.map(|v| v as i64).unwrap_or(-1);
         let find_path: Result<(String,), _> =
             sqlx::query_as(include_str!("../../queries/scan/get_album_path.sql"))
                 .bind(album_id)
                 .bind(disc_num)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut *conn)
                 .await;
 
         let parent = path.parent().unwrap();
@@ -577,12 +782,26 @@ impl ScanThread {
                     .bind(album_id)
                     .bind(parent.to_str())
                     .bind(disc_num)
-                    .execute(&self.pool)
+                    .execute(&mut *conn)
                     .await?;
             }
             Err(e) => return Err(e.into()),
         }
 
+        let genre = metadata
+            .genre
+            .as_deref()
+            .map(|genre| self.genre_aliases.apply(genre));
+        let artist = metadata
+            .artist
+            .as_deref()
+            .map(|artist| self.artist_aliases.apply(artist));
+        let disc_subtitle = metadata
+            .disc_subtitle
+            .as_deref()
+            .map(str::trim)
+            .filter(|subtitle| !subtitle.is_empty());
+
         let name = metadata
             .name
             .clone()
@@ -593,6 +812,10 @@ impl ScanThread {
             })
             .ok_or_else(|| anyhow::anyhow!("failed to retrieve filename"))?;
 
+        let root = self.roots.iter().find(|(root, _)| path.starts_with(root));
+        let modified = self.scan_record.get(path).map(|modified| *modified as i64);
+        let location = cue_track.map_or_else(|| path.to_path_buf(), |track| track.location(path));
+
         let result: Result<(i64,), sqlx::Error> =
             sqlx::query_as(include_str!("../../queries/scan/create_track.sql"))
                 .bind(&name)
@@ -601,54 +824,262 @@ impl ScanThread {
                 .bind(metadata.track_current.map(|x| x as i32))
                 .bind(metadata.disc_current.map(|x| x as i32))
                 .bind(length as i32)
-                .bind(path.to_str())
-                .bind(&metadata.genre)
-                .bind(&metadata.artist)
+                .bind(location.to_str())
+                .bind(genre)
+                .bind(artist)
                 .bind(parent.to_str())
-                .fetch_one(&self.pool)
+                .bind(metadata.replaygain_track_gain)
+                .bind(metadata.replaygain_track_peak)
+                .bind(&metadata.mbid_track)
+                .bind(&metadata.work)
+                .bind(&metadata.movement)
+                .bind(&stream_info.codec)
+                .bind(stream_info.sample_rate)
+                .bind(stream_info.bits_per_sample)
+                .bind(metadata.explicit())
+                .bind(root.map(|(_, source)| *source))
+                .bind(root.and_then(|(root, _)| root.to_str()))
+                .bind(modified)
+                .bind(disc_subtitle)
+                .bind(metadata.disc_max.map(|x| x as i32))
+                .bind(cue_track.and(path.to_str()))
+                .bind(cue_track.map(|track| track.start))
+                .bind(cue_track.map(|track| track.end))
+                .fetch_one(&mut *conn)
                 .await;
 
-        match result {
-            Ok(_) => Ok(()),
-            Err(sqlx::Error::RowNotFound) => Ok(()),
-            Err(e) => Err(e.into()),
+        let track_id = match result {
+            Ok((id,)) => id,
+            Err(sqlx::Error::RowNotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        sqlx::query(include_str!("../../queries/scan/clear_track_fields.sql"))
+            .bind(track_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for (name, value) in &metadata.custom {
+            sqlx::query(include_str!("../../queries/scan/add_track_field.sql"))
+                .bind(track_id)
+                .bind(name)
+                .bind(value)
+                .execute(&mut *conn)
+                .await?;
         }
+
+        Ok(())
     }
 
     async fn update_metadata(
         &mut self,
-        metadata: (Metadata, u64, Option<Box<[u8]>>),
+        conn: &mut SqliteConnection,
+        metadata: FileInformation,
+        art: Option<&StoredArt>,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        // files split by their cue sheet replace the track of the whole file, and the other way
+        // around, and tracks can be taken out of the cue sheet
+        let cue_tracks = &metadata.0.cue_tracks;
+        let locations: Vec<PathBuf> = if cue_tracks.is_empty() {
+            vec![path.to_path_buf()]
+        } else {
+            cue_tracks
+                .iter()
+                .map(|track| track.location(path))
+                .collect()
+        };
+        sqlx::query(include_str!(
+            "../../queries/scan/delete_stale_cue_tracks.sql"
+        ))
+        .bind(path.to_str())
+        .bind(serde_json::to_string(&locations)?)
+        .execute(&mut *conn)
+        .await?;
+
+        if cue_tracks.is_empty() {
+            return self.update_track(conn, &metadata, art, path, None).await;
+        }
+
+        for track in cue_tracks {
+            self.update_track(conn, &metadata, art, path, Some(track))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds or updates the track of a file, or one of the tracks of its cue sheet.
+    async fn update_track(
+        &mut self,
+        conn: &mut SqliteConnection,
+        file: &FileInformation,
+        art: Option<&StoredArt>,
         path: &Path,
+        cue_track: Option<&CueTrack>,
     ) -> anyhow::Result<()> {
+        let cue_metadata = cue_track.map(|track| file.0.cue_track_metadata(track));
+        let metadata = cue_metadata.as_ref().unwrap_or(&file.0);
+        let location = cue_track.map_or_else(|| path.to_path_buf(), |track| track.location(path));
+
+        // locked tracks keep the metadata they were given by hand until the file changes
+        let modified = self.scan_record.get(path).map(|modified| *modified as i64);
+        let lock: Option<(bool, Option<i64>)> =
+            sqlx::query_as(include_str!("../../queries/scan/get_track_lock.sql"))
+                .bind(location.to_str())
+                .fetch_optional(&mut *conn)
+                .await?;
+
+        if let Some((true, recorded)) = lock
+            && (recorded.is_none() || recorded == modified)
+        {
+            debug!("Keeping the locked metadata of {:?}", location);
+            sqlx::query(include_str!("../../queries/scan/set_track_modified.sql"))
+                .bind(location.to_str())
+                .bind(modified)
+                .execute(&mut *conn)
+                .await?;
+
+            return Ok(());
+        }
+
         debug!(
             "Adding/updating record for {:?} - {:?}",
-            metadata.0.artist, metadata.0.name
+            metadata.artist, metadata.name
         );
 
-        let artist_id = self.insert_artist(&metadata.0).await?;
-        let album_id = self
-            .insert_album(&metadata.0, artist_id, &metadata.2)
-            .await?;
-        self.insert_track(&metadata.0, album_id, path, metadata.1)
+        let artist_id = self.insert_artist(conn, metadata).await?;
+        let album_id = self.insert_album(conn, metadata, artist_id, art).await?;
+        self.insert_track(conn, metadata, album_id, path, cue_track, file)
             .await?;
 
         Ok(())
     }
 
-    fn read_metadata_for_path(&mut self, path: &PathBuf) -> Option<FileInformation> {
+    fn read_metadata_for_path(&mut self, path: &PathBuf) -> Result<FileInformation, String> {
+        if !is_remote(path) {
+            return self.read_metadata_for_file(path, path);
+        }
+
+        let file = match download_remote(path) {
+            Ok(file) => file,
+            Err(err) => {
+                // scanned again next time, rather than waiting for the file to change
+                self.scan_record.remove(path);
+                return Err(format!("Could not download the file: {err}"));
+            }
+        };
+
+        let result = self.read_metadata_for_file(path, &file);
+
+        // the file has changed, so the copy kept for offline listening is out of date
+        if remote::is_offline(path)
+            && let Err(err) = remote::replace_offline(path, &file)
+        {
+            warn!("Failed to update the offline copy of {:?}: {}", path, err);
+        }
+
+        let _ = fs::remove_file(&file);
+
+        result
+    }
+
+    /// Reads the metadata of the file at `path`, which is a copy of the file at `location` if the
+    /// file is in a remote folder.
+    fn read_metadata_for_file(
+        &mut self,
+        location: &PathBuf,
+        path: &PathBuf,
+    ) -> Result<FileInformation, String> {
+        let mut error = String::from("No provider supports this file");
+        let remote = is_remote(location);
+        let art = remote
+            .then(|| {
+                location
+                    .parent()
+                    .and_then(|folder| self.remote_art.get(folder))
+            })
+            .flatten()
+            .cloned();
+        // tags are guessed from the names of the file and its folder, which are escaped in
+        // addresses
+        let location = if remote {
+            &remote::decode_location(location)
+        } else {
+            location
+        };
+
         for (exts, provider) in &mut self.provider_table {
-            if file_is_scannable_with_provider(path, exts)
-                && let Ok(mut metadata) = scan_file_with_provider(path, provider)
-            {
-                if metadata.2.is_none() {
-                    metadata.2 = scan_path_for_album_art(path);
+            if !file_is_scannable_with_provider(location, exts) {
+                continue;
+            }
+
+            let mut metadata = match scan_file_with_provider(path, provider) {
+                Ok(metadata) => metadata,
+                Err(err) => {
+                    error = err;
+                    continue;
                 }
+            };
 
-                return Some(metadata);
+            if metadata.2.is_none() && remote {
+                metadata.2 = art
+                    .as_deref()
+                    .and_then(|art| download_remote(art).ok())
+                    .and_then(|file| {
+                        let bytes = fs::read(&file).ok();
+                        let _ = fs::remove_file(&file);
+                        bytes.map(Vec::into_boxed_slice)
+                    });
+            } else if metadata.2.is_none() {
+                metadata.2 = scan_path_for_album_art(path);
             }
+
+            if is_untagged(&metadata.0)
+                && let Some(guess) = guess_tags(location, &self.scan_settings.filename_patterns)
+            {
+                debug!("Guessed tags for untagged file {:?}: {:?}", path, guess);
+                fill_metadata(&mut metadata.0, guess);
+
+                // files without an album aren't added to the library, so untagged files are
+                // grouped by the folder they're in
+                if metadata.0.album.is_none() {
+                    metadata.0.album = location
+                        .parent()
+                        .and_then(|dir| dir.file_name())
+                        .map(|name| name.to_string_lossy().to_string());
+                }
+            }
+
+            return Ok(metadata);
+        }
+
+        Err(error)
+    }
+
+    fn read_scan_record(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
         }
 
-        None
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("could not open scan record: {:?}", e);
+                return;
+            }
+        };
+        let reader = BufReader::new(file);
+
+        match serde_json::from_reader(reader) {
+            Ok(scan_record) => {
+                self.scan_record = scan_record;
+            }
+            Err(e) => {
+                error!("could not read scan record: {:?}", e);
+                error!("scanning will be slow until the scan record is rebuilt");
+            }
+        }
     }
 
     fn write_scan_record(&self) {
@@ -666,10 +1097,122 @@ impl ScanThread {
         }
     }
 
+    /// Stores album art read from a file in the art directory, shrinking it first if it's very
+    /// large. Art that can't be decoded is ignored, as if the file had none.
+    fn store_art(&mut self, image: &[u8]) -> Option<StoredArt> {
+        let digest = md5::compute(image);
+        if let Some((last, art)) = &self.last_art
+            && *last == digest
+        {
+            return art.clone();
+        }
+
+        let art = match store_album_art(&self.art_dir, image) {
+            Ok(art) => Some(art),
+            Err(err) => {
+                warn!("Could not store album art: {err}");
+                None
+            }
+        };
+        self.last_art = Some((digest, art.clone()));
+
+        art
+    }
+
+    /// Writes all of the files in the current batch to the database in a single transaction.
+    fn flush_batch(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+
+        let batch = std::mem::take(&mut self.batch);
+        let pool = self.pool.clone();
+
+        debug!("Writing batch of {} files to the database", batch.len());
+
+        let start = Instant::now();
+
+        let result = crate::RUNTIME.block_on(async {
+            let mut tx = pool.begin().await?;
+
+            for (path, metadata) in batch {
+                let (metadata, art) = match metadata {
+                    Ok(metadata) => metadata,
+                    Err(err) => {
+                        sqlx::query(include_str!("../../queries/quarantine/add_file.sql"))
+                            .bind(path.to_str())
+                            .bind(err)
+                            .execute(&mut *tx)
+                            .await?;
+                        continue;
+                    }
+                };
+
+                // each file gets a savepoint, so that a file that fails partway through doesn't
+                // leave the artist or album it already wrote behind
+                let encountered = self.force_encountered_albums.len();
+                let mut savepoint = tx.begin().await?;
+
+                match self
+                    .update_metadata(&mut savepoint, metadata, art.as_ref(), &path)
+                    .await
+                {
+                    Ok(()) => {
+                        savepoint.commit().await?;
+
+                        // the file may have been fixed since it was quarantined
+                        sqlx::query(include_str!("../../queries/quarantine/remove_file.sql"))
+                            .bind(path.to_str())
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                    Err(err) => {
+                        savepoint.rollback().await?;
+                        self.force_encountered_albums.truncate(encountered);
+                        error!(
+                            "Failed to update metadata for file: {:?}, error: {}",
+                            path, err
+                        );
+
+                        sqlx::query(include_str!("../../queries/quarantine/add_file.sql"))
+                            .bind(path.to_str())
+                            .bind(err.to_string())
+                            .execute(&mut *tx)
+                            .await?;
+
+                        // scanned again next time, rather than waiting for the file to change
+                        self.scan_record.remove(&path);
+                    }
+                }
+            }
+
+            tx.commit().await?;
+
+            anyhow::Ok(())
+        });
+
+        self.timings.database += start.elapsed();
+
+        if let Err(err) = result {
+            error!("Failed to write scan batch to the database: {}", err);
+        }
+    }
+
     fn scan(&mut self) {
         if self.to_process.is_empty() {
+            self.flush_batch();
+
             info!("Scan complete, writing scan record and stopping");
             self.write_scan_record();
+
+            if let Err(err) = crate::RUNTIME.block_on(remove_unused_art(
+                &self.pool,
+                &self.art_dir,
+                self.started_at,
+            )) {
+                error!("Failed to remove unused album art: {}", err);
+            }
+
             self.scan_state = ScanState::Idle;
             self.event_tx
                 .send(ScanEvent::ScanCompleteIdle)
@@ -678,18 +1221,27 @@ impl ScanThread {
         }
 
         let path = self.to_process.pop().unwrap();
-        let metadata = self.read_metadata_for_path(&path);
 
-        if let Some(metadata) = metadata {
-            let result = crate::RUNTIME.block_on(self.update_metadata(metadata, &path));
+        let start = Instant::now();
+        // art is decoded and stored now, so the batch's transaction isn't kept waiting on it
+        let metadata = self.read_metadata_for_path(&path).map(|mut file| {
+            let art = file.2.take().and_then(|image| self.store_art(&image));
+            (file, art)
+        });
+        self.timings.decode += start.elapsed();
+
+        let read = metadata.is_ok();
+        if let Err(err) = &metadata {
+            warn!("Could not read metadata for file: {:?}: {}", path, err);
+        }
 
-            if let Err(err) = result {
-                error!(
-                    "Failed to update metadata for file: {:?}, error: {}",
-                    path, err
-                );
-            }
+        self.batch.push((path, metadata));
+
+        if self.batch.len() >= BATCH_SIZE {
+            self.flush_batch();
+        }
 
+        if read {
             self.scanned += 1;
 
             if self.scanned.is_multiple_of(5) {
@@ -700,37 +1252,247 @@ impl ScanThread {
                     })
                     .expect("could not send scan event");
             }
-        } else {
-            warn!("Could not read metadata for file: {:?}", path);
         }
     }
 
-    async fn delete_track(&mut self, path: &PathBuf) {
-        debug!("track deleted or moved: {:?}", path);
-        let result = sqlx::query(include_str!("../../queries/scan/delete_track.sql"))
-            .bind(path.to_str())
-            .execute(&self.pool)
-            .await;
+    /// Starts scanning the scan paths, unless a scan is already running.
+    fn start_scan(&mut self, force: bool) {
+        if self.scan_state != ScanState::Idle {
+            return;
+        }
 
-        if let Err(e) = result {
-            error!("Database error while deleting track: {:?}", e);
-        } else {
-            self.scan_record.remove(path);
+        self.scan_state = ScanState::Cleanup;
+        self.started_at = SystemTime::now();
+        self.scanned = 0;
+        self.discovered_total = 0;
+        // the folders are visited from the end of the list, and the files found last are
+        // scanned first, so the highest priority folder has to come first
+        self.discovered = self.scan_settings.folders();
+        self.find_roots(&[]);
+        self.visited.clear();
+        self.to_process.clear();
+        // unused art is removed after each scan, so what was stored last may be gone
+        self.last_art = None;
+        self.is_force = force;
+
+        if force {
+            self.force_encountered_albums.clear();
+            self.scan_record = FxHashMap::default();
+        }
+
+        self.event_tx
+            .send(ScanEvent::Cleaning)
+            .expect("could not send scan event");
+    }
+
+    /// Starts watching the scan paths that have watching turned on, if they've changed.
+    fn update_watches(&mut self) {
+        let folders = self.scan_settings.watched_folders();
+        if folders == self.watched {
+            return;
+        }
+
+        self.watcher = None;
+        self.watched = folders;
+
+        if self.watched.is_empty() {
+            return;
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                warn!("Failed to create folder watcher: {}", err);
+                return;
+            }
+        };
+
+        for folder in &self.watched {
+            if let Err(err) = watcher.watch(folder, RecursiveMode::Recursive) {
+                warn!("Failed to watch {}: {}", folder.display(), err);
+            }
+        }
+
+        self.watcher = Some((watcher, rx));
+    }
+
+    /// Starts a scan once the watched folders have stopped changing.
+    fn read_watch_events(&mut self) {
+        let Some((_, rx)) = &self.watcher else {
+            return;
+        };
+
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                // metadata changes include access times, which scanning itself changes
+                Ok(event)
+                    if matches!(
+                        event.kind,
+                        EventKind::Create(_)
+                            | EventKind::Remove(_)
+                            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_))
+                            | EventKind::Modify(ModifyKind::Any)
+                    ) =>
+                {
+                    self.changed_at = Some(Instant::now());
+                }
+                Ok(_) => (),
+                Err(err) => warn!("Folder watch error: {:?}", err),
+            }
+        }
+
+        if self.scan_state == ScanState::Idle
+            && self
+                .changed_at
+                .is_some_and(|changed_at| changed_at.elapsed() >= WATCH_DELAY)
+        {
+            info!("Watched folders changed, scanning");
+            self.changed_at = None;
+            self.start_scan(false);
+        }
+    }
+
+    fn apply_genre_aliases(&self) {
+        match crate::RUNTIME.block_on(apply_genre_aliases(&self.pool, &self.genre_aliases)) {
+            Ok(0) => (),
+            Ok(changed) => info!("Renamed the genres of {changed} tracks"),
+            Err(err) => error!("Failed to rename genres: {}", err),
+        }
+    }
+
+    fn apply_artist_aliases(&self) {
+        match crate::RUNTIME.block_on(apply_artist_aliases(&self.pool, &self.artist_aliases)) {
+            Ok(0) => (),
+            Ok(changed) => info!("Merged or renamed {changed} artists"),
+            Err(err) => error!("Failed to apply artist aliases: {}", err),
+        }
+    }
+
+    /// Marks the tracks in the audiobook folders as audiobook chapters. This is done after every
+    /// scan rather than as tracks are added, so that tracks are moved in and out of the audiobook
+    /// section as soon as the folders are changed.
+    fn mark_audiobooks(&self) {
+        let folders = self.scan_settings.audiobook_folders();
+        if let Err(err) = crate::RUNTIME.block_on(mark_audiobooks(&self.pool, &folders)) {
+            error!("Failed to mark audiobooks: {}", err);
         }
     }
 
     // This is done in one shot because it's required for data integrity
     // Cleanup cannot be cancelled
     fn cleanup(&mut self) {
-        self.scan_record
-            .clone()
-            .iter()
-            .filter(|v| !v.0.exists())
-            .map(|v| v.0)
-            .for_each(|v| {
-                crate::RUNTIME.block_on(self.delete_track(v));
+        // aliases could have been changed while Hummingbird wasn't running
+        self.apply_genre_aliases();
+        self.apply_artist_aliases();
+        self.mark_audiobooks();
+
+        let mut missing: Vec<PathBuf> = self
+            .scan_record
+            .keys()
+            .filter(|v| !is_remote(v) && !v.exists())
+            .cloned()
+            .collect();
+        missing.extend(self.discover_remotes());
+
+        if !missing.is_empty() {
+            let result = crate::RUNTIME.block_on(async {
+                let mut tx = self.pool.begin().await?;
+
+                for path in &missing {
+                    debug!("track deleted or moved: {:?}", path);
+                    sqlx::query(include_str!("../../queries/scan/delete_track.sql"))
+                        .bind(path.to_str())
+                        .execute(&mut *tx)
+                        .await?;
+                    sqlx::query(include_str!("../../queries/quarantine/remove_file.sql"))
+                        .bind(path.to_str())
+                        .execute(&mut *tx)
+                        .await?;
+                }
+
+                tx.commit().await
             });
 
+            match result {
+                Ok(()) => {
+                    for path in &missing {
+                        self.scan_record.remove(path);
+
+                        if is_remote(path) {
+                            remote::forget(path);
+                            remote::remove_offline(path);
+                        }
+                    }
+                }
+                Err(e) => error!("Database error while deleting tracks: {:?}", e),
+            }
+        }
+
         self.scan_state = ScanState::Discovering;
     }
+
+    /// Lists the files in the remote folders, queueing the ones that are new or have changed
+    /// since they were last scanned. Returns the files that have been removed from the folders.
+    /// Folders that can't be reached are skipped, and their tracks are left in the library.
+    fn discover_remotes(&mut self) -> Vec<PathBuf> {
+        let mut removed = Vec::new();
+        self.remote_art.clear();
+
+        for source in self.scan_settings.remotes.clone() {
+            let files = match remote::list_files(&source) {
+                Ok(files) => files,
+                Err(err) => {
+                    warn!("Failed to list the files in {}: {}", source.url, err);
+                    continue;
+                }
+            };
+
+            if let Ok(root) = remote::root(&source) {
+                let root = PathBuf::from(root.as_str());
+                let found: FxHashSet<&PathBuf> = files.iter().map(|file| &file.location).collect();
+
+                removed.extend(
+                    self.scan_record
+                        .keys()
+                        .filter(|path| path.starts_with(&root) && !found.contains(path))
+                        .cloned(),
+                );
+            }
+
+            for file in files {
+                if is_album_art_name(&file.location) {
+                    if let Some(folder) = file.location.parent() {
+                        self.remote_art
+                            .entry(folder.to_path_buf())
+                            .or_insert(file.location);
+                    }
+                    continue;
+                }
+
+                let scannable = self
+                    .provider_table
+                    .iter()
+                    .any(|(exts, _)| file_is_scannable_with_provider(&file.location, exts));
+                if !scannable {
+                    continue;
+                }
+
+                match self
+                    .scan_record
+                    .insert(file.location.clone(), file.modified)
+                {
+                    Some(modified) if modified == file.modified => continue,
+                    // the copy that's played is out of date
+                    Some(_) => remote::forget(&file.location),
+                    None => (),
+                }
+
+                self.to_process.push(file.location);
+                self.discovered_total += 1;
+            }
+        }
+
+        removed
+    }
 }
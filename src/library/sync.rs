@@ -0,0 +1,422 @@
+mod detect;
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+use crate::{
+    media::{errors::TranscodeError, metadata::TrackFile, transcode::Transcoder},
+    settings::transcode::TranscodeProfile,
+};
+
+use super::{
+    db::find_track_file,
+    organize::{OrganizeTrack, remove_empty_dirs, render_pattern, validate_pattern},
+};
+
+pub use self::detect::{SyncDevice, detect_devices};
+
+/// Where files are placed on the device when no pattern is given.
+pub const DEFAULT_PATTERN: &str = "Music/{albumartist}/{album}/{track} {title}";
+
+/// The file on the device recording what has been synced to it.
+const MANIFEST_NAME: &str = ".hummingbird-sync.json";
+
+/// A file that was synced to the device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestFile {
+    /// Where the file is on the device, relative to its root.
+    path: PathBuf,
+    /// Where the pattern placed the file. This is different from the path if a number had to be
+    /// added to the name to avoid overwriting another file.
+    pattern_path: PathBuf,
+    /// The modification time of the source file when it was copied, in seconds since the epoch.
+    modified: u64,
+    size: u64,
+    /// The profile the file was converted with, if it was converted.
+    #[serde(default)]
+    profile: Option<TranscodeProfile>,
+}
+
+/// What has been synced to a device, stored on the device itself so that it can be synced from
+/// more than one computer, or after the library is moved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Manifest {
+    /// The albums chosen the last time the device was synced.
+    #[serde(default)]
+    albums: Vec<i64>,
+    /// The playlists chosen the last time the device was synced.
+    #[serde(default)]
+    playlists: Vec<i64>,
+    /// The files that have been synced, by the location of the file they were copied from.
+    #[serde(default)]
+    files: BTreeMap<String, ManifestFile>,
+}
+
+impl Manifest {
+    fn load(device: &Path) -> anyhow::Result<Self> {
+        match fs::read(device.join(MANIFEST_NAME)) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&self, device: &Path) -> anyhow::Result<()> {
+        let path = device.join(MANIFEST_NAME);
+        let part = path.with_extension("json.part");
+
+        fs::write(&part, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(part, path)?;
+
+        Ok(())
+    }
+}
+
+/// A file that will be copied to the device.
+#[derive(Debug, Clone)]
+pub struct PlannedCopy {
+    /// The location of the track in the library.
+    pub from: PathBuf,
+    /// Where the track's audio is.
+    file: TrackFile,
+    /// Where the file will be placed, relative to the root of the device.
+    pub to: PathBuf,
+    /// Whether an older copy of the file is already on the device.
+    pub update: bool,
+    /// Whether the file is converted with the plan's profile, instead of being copied as is.
+    pub transcode: bool,
+    pattern_path: PathBuf,
+    modified: u64,
+    size: u64,
+}
+
+/// The changes needed to bring a device in line with the chosen albums and playlists.
+#[derive(Debug, Clone, Default)]
+pub struct SyncPlan {
+    pub copies: Vec<PlannedCopy>,
+    /// Files that were synced before but are no longer chosen, relative to the root of the
+    /// device. Files that weren't put on the device by a sync are never removed.
+    pub removals: Vec<PathBuf>,
+    /// The number of chosen files that are already up to date on the device.
+    pub unchanged: usize,
+    albums: Vec<i64>,
+    playlists: Vec<i64>,
+    profile: Option<TranscodeProfile>,
+}
+
+/// A file that's been chosen to be on the device.
+struct WantedFile {
+    source: String,
+    file: TrackFile,
+    pattern_path: PathBuf,
+    modified: u64,
+    size: u64,
+    /// The profile the file will be converted with, if it needs converting.
+    profile: Option<TranscodeProfile>,
+}
+
+impl WantedFile {
+    fn into_copy(self, to: PathBuf, update: bool) -> PlannedCopy {
+        PlannedCopy {
+            from: self.file.location.clone(),
+            file: self.file,
+            to,
+            update,
+            transcode: self.profile.is_some(),
+            pattern_path: self.pattern_path,
+            modified: self.modified,
+            size: self.size,
+        }
+    }
+}
+
+/// Lists the tracks in the albums and playlists, without repeating tracks that are in more than
+/// one of them.
+async fn selected_tracks(
+    pool: &SqlitePool,
+    albums: &[i64],
+    playlists: &[i64],
+) -> Result<Vec<OrganizeTrack>, sqlx::Error> {
+    let mut seen = FxHashSet::default();
+    let mut tracks = Vec::new();
+
+    let queries = albums
+        .iter()
+        .map(|id| (include_str!("../../queries/sync/list_album_tracks.sql"), id))
+        .chain(playlists.iter().map(|id| {
+            (
+                include_str!("../../queries/sync/list_playlist_tracks.sql"),
+                id,
+            )
+        }));
+
+    for (query, id) in queries {
+        let found: Vec<OrganizeTrack> = sqlx::query_as(query).bind(id).fetch_all(pool).await?;
+        tracks.extend(found.into_iter().filter(|track| seen.insert(track.id)));
+    }
+
+    Ok(tracks)
+}
+
+/// Adds " (2)", " (3)", and so on to the file name until it doesn't collide with anything.
+fn resolve_collision(path: &Path, is_taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|ext| ext.to_string_lossy());
+
+    (2..)
+        .map(|n| {
+            let name = match &extension {
+                Some(ext) => format!("{stem} ({n}).{ext}"),
+                None => format!("{stem} ({n})"),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !is_taken(candidate))
+        .unwrap()
+}
+
+/// Works out what has to be copied to and removed from the device. If no albums or playlists
+/// are given, the ones chosen the last time the device was synced are used. If a profile is
+/// given, files that aren't already in its format are converted. Nothing is changed on the
+/// device.
+pub async fn plan_sync(
+    pool: &SqlitePool,
+    device: &Path,
+    pattern: &str,
+    albums: &[i64],
+    playlists: &[i64],
+    profile: Option<&TranscodeProfile>,
+) -> anyhow::Result<SyncPlan> {
+    validate_pattern(pattern)?;
+
+    let manifest = Manifest::load(device)?;
+    let (albums, playlists) = if albums.is_empty() && playlists.is_empty() {
+        (manifest.albums.clone(), manifest.playlists.clone())
+    } else {
+        (albums.to_vec(), playlists.to_vec())
+    };
+
+    let mut plan = SyncPlan {
+        albums: albums.clone(),
+        playlists: playlists.clone(),
+        profile: profile.cloned(),
+        ..Default::default()
+    };
+
+    let mut wanted = Vec::new();
+    for track in selected_tracks(pool, &albums, &playlists).await? {
+        let file = find_track_file(pool, Path::new(&track.location)).await?;
+
+        // tracks split from a cue sheet are cut out of their file by converting them
+        if file.range.is_some() && profile.is_none() {
+            warn!(
+                "{:?} is split from a cue sheet and can only be synced by converting it, skipping",
+                file.location
+            );
+            continue;
+        }
+
+        let metadata = match fs::metadata(&file.path) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                warn!("Could not read {:?}, skipping: {err}", file.path);
+                continue;
+            }
+        };
+
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+
+        let transcode =
+            profile.filter(|profile| file.range.is_some() || !profile.format.matches(&file.path));
+
+        let mut pattern_path = render_pattern(pattern, &track);
+        match transcode {
+            Some(profile) => {
+                pattern_path.set_extension(profile.format.extension());
+            }
+            None => {
+                if let Some(ext) = file.path.extension() {
+                    pattern_path.set_extension(ext);
+                }
+            }
+        }
+
+        wanted.push(WantedFile {
+            source: track.location,
+            file,
+            pattern_path,
+            modified,
+            size: metadata.len(),
+            profile: transcode.cloned(),
+        });
+    }
+
+    let wanted_sources: FxHashSet<&str> = wanted.iter().map(|v| v.source.as_str()).collect();
+
+    // files that are staying where they are on the device
+    let mut taken: FxHashSet<PathBuf> = FxHashSet::default();
+    // files that were synced before, and whether they're going to be replaced or removed
+    let mut owned: FxHashMap<PathBuf, bool> = FxHashMap::default();
+
+    for (source, file) in &manifest.files {
+        let leaving = !wanted_sources.contains(source.as_str());
+        owned.insert(file.path.clone(), leaving);
+        if leaving {
+            plan.removals.push(file.path.clone());
+        }
+    }
+
+    let mut pending = Vec::new();
+    for wanted in wanted {
+        match manifest.files.get(&wanted.source) {
+            Some(file)
+                if file.pattern_path == wanted.pattern_path
+                    && file.modified == wanted.modified
+                    && file.size == wanted.size
+                    && file.profile == wanted.profile
+                    && device.join(&file.path).exists() =>
+            {
+                taken.insert(file.path.clone());
+                plan.unchanged += 1;
+            }
+            // the old copy is overwritten if it's still in the same place
+            Some(file) if file.pattern_path == wanted.pattern_path => {
+                taken.insert(file.path.clone());
+                plan.copies.push(wanted.into_copy(file.path.clone(), true));
+            }
+            file => {
+                if let Some(file) = file {
+                    plan.removals.push(file.path.clone());
+                    owned.insert(file.path.clone(), true);
+                }
+                pending.push((wanted, file.is_some()));
+            }
+        }
+    }
+
+    // a path is free if nothing is staying there, and anything already on the device there is
+    // a synced file that's being removed
+    for (wanted, update) in pending {
+        let is_taken = |path: &Path| {
+            taken.contains(path)
+                || (device.join(path).exists() && !owned.get(path).copied().unwrap_or(false))
+        };
+
+        let to = if is_taken(&wanted.pattern_path) {
+            resolve_collision(&wanted.pattern_path, is_taken)
+        } else {
+            wanted.pattern_path.clone()
+        };
+
+        taken.insert(to.clone());
+        plan.copies.push(wanted.into_copy(to, update));
+    }
+
+    Ok(plan)
+}
+
+/// Copies the file, writing it under a temporary name first so that a device unplugged part of
+/// the way through isn't left with a truncated file.
+fn copy_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut part = to.as_os_str().to_owned();
+    part.push(".part");
+
+    fs::copy(from, &part)?;
+    fs::rename(&part, to)
+}
+
+/// Removes and copies the files in the plan, and records what's on the device so that the next
+/// sync only copies what has changed. A transcoder is needed if any files are being converted.
+/// Returns the number of files that were copied; files that fail to copy are logged and skipped.
+pub fn apply_sync(
+    device: &Path,
+    plan: &SyncPlan,
+    transcoder: Option<&Transcoder>,
+) -> anyhow::Result<usize> {
+    let transcode = match (&plan.profile, transcoder) {
+        (Some(profile), Some(transcoder)) => Some((profile, transcoder)),
+        (Some(_), None) if plan.copies.iter().any(|copy| copy.transcode) => {
+            return Err(TranscodeError::FfmpegNotFound.into());
+        }
+        _ => None,
+    };
+
+    let mut manifest = Manifest::load(device)?;
+    let removed: FxHashSet<&PathBuf> = plan.removals.iter().collect();
+
+    for path in &plan.removals {
+        debug!("removing {:?} from device", path);
+        let full = device.join(path);
+
+        if let Err(err) = fs::remove_file(&full)
+            && err.kind() != io::ErrorKind::NotFound
+        {
+            warn!(?err, "Could not remove {:?} from device: {err}", full);
+        }
+
+        if let Some(parent) = full.parent() {
+            remove_empty_dirs(parent, device);
+        }
+    }
+
+    manifest
+        .files
+        .retain(|_, file| !removed.contains(&file.path));
+    manifest.albums = plan.albums.clone();
+    manifest.playlists = plan.playlists.clone();
+
+    let mut copied = 0;
+    for planned in &plan.copies {
+        debug!("copying {:?} to {:?}", planned.from, planned.to);
+        let to = device.join(&planned.to);
+
+        let result = match transcode {
+            Some((profile, transcoder)) if planned.transcode => to
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(TranscodeError::from)
+                .and_then(|_| transcoder.transcode(&planned.file, &to, profile))
+                .map_err(anyhow::Error::from),
+            _ => copy_file(&planned.file.path, &to).map_err(anyhow::Error::from),
+        };
+
+        if let Err(err) = result {
+            warn!(?err, "Could not copy {:?} to device: {err}", planned.from);
+            continue;
+        }
+
+        manifest.files.insert(
+            planned.from.to_string_lossy().to_string(),
+            ManifestFile {
+                path: planned.to.clone(),
+                pattern_path: planned.pattern_path.clone(),
+                modified: planned.modified,
+                size: planned.size,
+                profile: plan.profile.clone().filter(|_| planned.transcode),
+            },
+        );
+        copied += 1;
+    }
+
+    manifest.save(device)?;
+
+    Ok(copied)
+}
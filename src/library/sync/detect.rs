@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+
+/// How a device is connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    /// A USB drive, SD card, or player that shows up as a drive.
+    MassStorage,
+    /// A phone or player that uses the Media Transfer Protocol, mounted by the desktop (through
+    /// GVFS on Linux).
+    Mtp,
+}
+
+impl DeviceKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            DeviceKind::MassStorage => "storage",
+            DeviceKind::Mtp => "mtp",
+        }
+    }
+}
+
+/// A device that music can be synced to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncDevice {
+    pub name: String,
+    pub kind: DeviceKind,
+    /// Where the device is mounted. Files are synced relative to this path.
+    pub path: PathBuf,
+}
+
+/// Finds the removable drives and MTP devices that are currently mounted.
+pub fn detect_devices() -> Vec<SyncDevice> {
+    let mut devices = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    {
+        devices.extend(linux::mounted_drives());
+        devices.extend(linux::mtp_devices());
+    }
+
+    #[cfg(target_os = "macos")]
+    devices.extend(macos::volumes());
+
+    #[cfg(target_os = "windows")]
+    devices.extend(windows::drives());
+
+    devices
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        fs,
+        os::unix::fs::MetadataExt,
+        path::{Path, PathBuf},
+    };
+
+    use super::{DeviceKind, SyncDevice};
+
+    /// Removable drives are mounted under these directories by udisks and most desktops.
+    const REMOVABLE_ROOTS: &[&str] = &["/media/", "/run/media/", "/mnt/"];
+
+    /// Mount points in /proc/mounts escape spaces and a few other characters as octal.
+    fn unescape(path: &str) -> String {
+        let mut result = String::new();
+        let mut rest = path;
+
+        while let Some(start) = rest.find('\\') {
+            result.push_str(&rest[..start]);
+            let code = rest.get(start + 1..start + 4);
+
+            match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+                Some(byte) => {
+                    result.push(byte as char);
+                    rest = &rest[start + 4..];
+                }
+                None => {
+                    result.push('\\');
+                    rest = &rest[start + 1..];
+                }
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    pub fn mounted_drives() -> Vec<SyncDevice> {
+        let Ok(mounts) = fs::read_to_string("/proc/mounts") else {
+            return Vec::new();
+        };
+
+        mounts
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .map(unescape)
+            .filter(|mount| REMOVABLE_ROOTS.iter().any(|root| mount.starts_with(root)))
+            .map(|mount| {
+                let path = PathBuf::from(mount);
+                SyncDevice {
+                    name: file_name(&path),
+                    kind: DeviceKind::MassStorage,
+                    path,
+                }
+            })
+            .collect()
+    }
+
+    /// GVFS mounts MTP devices at /run/user/<uid>/gvfs/mtp:host=<device>, with one directory per
+    /// storage on the device.
+    pub fn mtp_devices() -> Vec<SyncDevice> {
+        let Ok(uid) = fs::metadata("/proc/self").map(|meta| meta.uid()) else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(format!("/run/user/{uid}/gvfs")) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("mtp:"))
+            .flat_map(|device| {
+                let device_name = file_name(&device.path())
+                    .trim_start_matches("mtp:host=")
+                    .to_string();
+
+                fs::read_dir(device.path())
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(move |storage| SyncDevice {
+                        name: format!("{device_name} ({})", file_name(&storage.path())),
+                        kind: DeviceKind::Mtp,
+                        path: storage.path(),
+                    })
+            })
+            .collect()
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::fs;
+
+    use super::{DeviceKind, SyncDevice};
+
+    /// Every mounted volume shows up in /Volumes, including the startup disk, which is a link to
+    /// the root of the file system.
+    pub fn volumes() -> Vec<SyncDevice> {
+        let Ok(entries) = fs::read_dir("/Volumes") else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter(|entry| {
+                fs::canonicalize(entry.path()).is_ok_and(|path| path != std::path::Path::new("/"))
+            })
+            .map(|entry| SyncDevice {
+                name: entry.file_name().to_string_lossy().to_string(),
+                kind: DeviceKind::MassStorage,
+                path: entry.path(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::path::PathBuf;
+
+    use super::{DeviceKind, SyncDevice};
+
+    /// Every drive letter other than the one Windows is installed on. MTP devices aren't given a
+    /// drive letter, so they can't be found this way.
+    pub fn drives() -> Vec<SyncDevice> {
+        let system = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+
+        ('A'..='Z')
+            .map(|letter| format!("{letter}:"))
+            .filter(|drive| !drive.eq_ignore_ascii_case(&system))
+            .map(|drive| PathBuf::from(format!("{drive}\\")))
+            .filter(|path| path.exists())
+            .map(|path| SyncDevice {
+                name: path.display().to_string(),
+                kind: DeviceKind::MassStorage,
+                path,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,376 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::library::playlist::LIKED_PLAYLIST_ID;
+
+/// The version written to exported files. Files from newer versions are refused, since they may
+/// hold data that this version would import wrongly.
+const DATA_VERSION: u32 = 1;
+
+/// A track in an exported file. Tracks are found by their path first, then by their path inside
+/// the scan folder holding them (for libraries kept in different places on each computer), and
+/// then by their MusicBrainz ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackRef {
+    path: String,
+    /// The path relative to the scan folder holding the track, with `/` between folders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relative: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mbid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayRecord {
+    /// The index of the track in [LibraryData::tracks].
+    track: usize,
+    /// When the track was played, in RFC 3339 format.
+    played_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlaylistRecord {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// Whether this is the liked tracks playlist, which is named differently in each language.
+    #[serde(default)]
+    liked: bool,
+    /// The indices of the playlist's tracks in [LibraryData::tracks], in order.
+    tracks: Vec<usize>,
+}
+
+/// The playlists, liked tracks and play history of a library, as written to an exported file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LibraryData {
+    version: u32,
+    tracks: Vec<TrackRef>,
+    plays: Vec<PlayRecord>,
+    playlists: Vec<PlaylistRecord>,
+}
+
+/// What was written by [export_library_data].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExportSummary {
+    pub plays: usize,
+    pub playlists: usize,
+}
+
+/// The changes made by [import_library_data].
+#[derive(Debug, Default, Clone)]
+pub struct ImportSummary {
+    pub plays: usize,
+    pub playlists_created: usize,
+    pub playlist_items: usize,
+    /// The number of tracks in the file that aren't in this library.
+    pub missing_tracks: usize,
+    /// The playlists that were created or added to.
+    pub changed_playlists: Vec<i64>,
+}
+
+/// Turns the location into a path relative to the scan folder holding it.
+fn relative_path(location: &Path, roots: &[PathBuf]) -> Option<String> {
+    let relative = roots
+        .iter()
+        .find_map(|root| location.strip_prefix(root).ok())?;
+
+    let parts: Vec<_> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect();
+
+    Some(parts.join("/"))
+}
+
+/// Builds the list of tracks in an exported file, adding each track the first time it's used.
+struct TrackRefs<'a> {
+    roots: &'a [PathBuf],
+    tracks: FxHashMap<i64, (String, Option<String>)>,
+    indices: FxHashMap<i64, usize>,
+    refs: Vec<TrackRef>,
+}
+
+impl TrackRefs<'_> {
+    fn index(&mut self, track_id: i64) -> Option<usize> {
+        if let Some(index) = self.indices.get(&track_id) {
+            return Some(*index);
+        }
+
+        let (location, mbid) = self.tracks.get(&track_id)?;
+        self.refs.push(TrackRef {
+            path: location.clone(),
+            relative: relative_path(Path::new(location), self.roots),
+            mbid: mbid.clone(),
+        });
+
+        let index = self.refs.len() - 1;
+        self.indices.insert(track_id, index);
+        Some(index)
+    }
+}
+
+/// Writes the playlists, liked tracks and play history of the library to a file, so that they
+/// can be imported into another library with [import_library_data]. `roots` are the library's
+/// scan folders.
+pub async fn export_library_data(
+    pool: &SqlitePool,
+    roots: &[PathBuf],
+    path: &Path,
+) -> anyhow::Result<ExportSummary> {
+    let tracks: Vec<(i64, String, Option<String>)> =
+        sqlx::query_as(include_str!("../../queries/transfer/list_tracks.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let mut refs = TrackRefs {
+        roots,
+        tracks: tracks
+            .into_iter()
+            .map(|(id, location, mbid)| (id, (location, mbid)))
+            .collect(),
+        indices: FxHashMap::default(),
+        refs: Vec::new(),
+    };
+
+    let plays: Vec<(i64, DateTime<Utc>)> =
+        sqlx::query_as(include_str!("../../queries/transfer/list_plays.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let plays: Vec<PlayRecord> = plays
+        .into_iter()
+        .filter_map(|(track_id, played_at)| {
+            Some(PlayRecord {
+                track: refs.index(track_id)?,
+                played_at: played_at.to_rfc3339(),
+            })
+        })
+        .collect();
+
+    let playlists: Vec<(i64, String, Option<String>)> =
+        sqlx::query_as(include_str!("../../queries/transfer/list_playlists.sql"))
+            .fetch_all(pool)
+            .await?;
+
+    let mut records = Vec::with_capacity(playlists.len());
+
+    for (id, name, description) in playlists {
+        let track_ids: Vec<i64> = sqlx::query_scalar(include_str!(
+            "../../queries/transfer/list_playlist_tracks.sql"
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        records.push(PlaylistRecord {
+            name,
+            description,
+            liked: id == LIKED_PLAYLIST_ID,
+            tracks: track_ids
+                .into_iter()
+                .filter_map(|track_id| refs.index(track_id))
+                .collect(),
+        });
+    }
+
+    let summary = ExportSummary {
+        plays: plays.len(),
+        playlists: records.len(),
+    };
+
+    let data = LibraryData {
+        version: DATA_VERSION,
+        tracks: refs.refs,
+        plays,
+        playlists: records,
+    };
+
+    std::fs::write(path, serde_json::to_vec_pretty(&data)?)?;
+
+    Ok(summary)
+}
+
+async fn find_track_by_location(
+    conn: &mut SqliteConnection,
+    location: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(include_str!(
+        "../../queries/transfer/find_track_by_location.sql"
+    ))
+    .bind(location)
+    .fetch_optional(conn)
+    .await
+}
+
+async fn find_track(
+    conn: &mut SqliteConnection,
+    track: &TrackRef,
+    roots: &[PathBuf],
+) -> Result<Option<i64>, sqlx::Error> {
+    if let Some(id) = find_track_by_location(conn, &track.path).await? {
+        return Ok(Some(id));
+    }
+
+    if let Some(relative) = &track.relative {
+        for root in roots {
+            let location = root.join(relative);
+            if let Some(id) = find_track_by_location(conn, &location.to_string_lossy()).await? {
+                return Ok(Some(id));
+            }
+        }
+    }
+
+    match &track.mbid {
+        Some(mbid) => {
+            sqlx::query_scalar(include_str!(
+                "../../queries/transfer/find_track_by_mbid.sql"
+            ))
+            .bind(mbid)
+            .fetch_optional(conn)
+            .await
+        }
+        None => Ok(None),
+    }
+}
+
+/// Finds the playlist the record is for, creating it if there isn't one with its name. Returns
+/// the playlist's ID and whether it was created.
+async fn find_or_create_playlist(
+    conn: &mut SqliteConnection,
+    record: &PlaylistRecord,
+) -> Result<(i64, bool), sqlx::Error> {
+    if record.liked {
+        return Ok((LIKED_PLAYLIST_ID, false));
+    }
+
+    let existing: Option<i64> =
+        sqlx::query_scalar(include_str!("../../queries/transfer/find_playlist.sql"))
+            .bind(&record.name)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+    if let Some(id) = existing {
+        return Ok((id, false));
+    }
+
+    sqlx::query(include_str!(
+        "../../queries/playlist/purge_deleted_named.sql"
+    ))
+    .bind(&record.name)
+    .execute(&mut *conn)
+    .await?;
+
+    let id = sqlx::query(include_str!("../../queries/playlist/create_playlist.sql"))
+        .bind(&record.name)
+        .execute(&mut *conn)
+        .await?
+        .last_insert_rowid();
+
+    if let Some(description) = &record.description {
+        sqlx::query(include_str!("../../queries/playlist/set_description.sql"))
+            .bind(id)
+            .bind(description)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok((id, true))
+}
+
+/// Merges the playlists, liked tracks and play history in a file written by
+/// [export_library_data] into the library. Nothing is removed: plays and playlist tracks that
+/// are already in the library are skipped, so importing the same file twice changes nothing.
+/// `roots` are the library's scan folders.
+pub async fn import_library_data(
+    pool: &SqlitePool,
+    roots: &[PathBuf],
+    path: &Path,
+) -> anyhow::Result<ImportSummary> {
+    let data: LibraryData = serde_json::from_slice(&std::fs::read(path)?)?;
+
+    if data.version > DATA_VERSION {
+        bail!("the file was exported by a newer version of Hummingbird");
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut summary = ImportSummary::default();
+
+    let mut track_ids = Vec::with_capacity(data.tracks.len());
+    for track in &data.tracks {
+        let id = find_track(&mut tx, track, roots).await?;
+        if id.is_none() {
+            summary.missing_tracks += 1;
+        }
+        track_ids.push(id);
+    }
+
+    let track_id = |index: usize| track_ids.get(index).copied().flatten();
+
+    for play in &data.plays {
+        let Some(id) = track_id(play.track) else {
+            continue;
+        };
+        let Ok(played_at) = DateTime::parse_from_rfc3339(&play.played_at) else {
+            continue;
+        };
+
+        let added = sqlx::query(include_str!("../../queries/transfer/add_play.sql"))
+            .bind(id)
+            .bind(played_at.with_timezone(&Utc))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        summary.plays += added as usize;
+    }
+
+    for record in &data.playlists {
+        let ids: Vec<i64> = record.tracks.iter().filter_map(|i| track_id(*i)).collect();
+        if ids.is_empty() && !record.liked {
+            continue;
+        }
+
+        let (playlist_id, created) = find_or_create_playlist(&mut tx, record).await?;
+        let mut changed = created;
+
+        if created {
+            summary.playlists_created += 1;
+        }
+
+        for id in ids {
+            let existing: Option<i64> = sqlx::query_scalar(include_str!(
+                "../../queries/playlist/playlist_has_track.sql"
+            ))
+            .bind(playlist_id)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if existing.is_some() {
+                continue;
+            }
+
+            sqlx::query(include_str!("../../queries/playlist/add_track.sql"))
+                .bind(playlist_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+
+            summary.playlist_items += 1;
+            changed = true;
+        }
+
+        if changed {
+            summary.changed_playlists.push(playlist_id);
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(summary)
+}
@@ -23,8 +23,15 @@ pub struct Artist {
     pub image: Option<Box<[u8]>>,
     #[sqlx(default)]
     pub image_mime: Option<DBString>,
+    #[sqlx(default)]
+    pub image_hash: Option<String>,
+    /// When the bio and image were last looked up online.
+    #[sqlx(default)]
+    pub info_fetched_at: Option<DateTime<Utc>>,
     #[sqlx(skip)]
     pub tags: Option<Vec<String>>,
+    #[sqlx(default)]
+    pub mbid: Option<String>,
 }
 
 #[derive(Clone)]
@@ -199,7 +206,7 @@ pub struct Album {
     pub release_year: Option<u16>,
     pub created_at: DateTime<Utc>,
     #[sqlx(default)]
-    pub image: Option<Box<[u8]>>,
+    pub image_hash: Option<String>,
     #[sqlx(default)]
     pub thumb: Option<Thumbnail>,
     #[sqlx(default)]
@@ -212,6 +219,22 @@ pub struct Album {
     pub catalog_number: Option<DBString>,
     #[sqlx(default)]
     pub isrc: Option<DBString>,
+    /// The MusicBrainz release ID from the album's tags, or `none` if it isn't tagged.
+    #[sqlx(default)]
+    pub mbid: Option<String>,
+    /// A MusicBrainz release ID that was linked by hand, which takes precedence over the tagged
+    /// one.
+    #[sqlx(default)]
+    pub mbid_linked: Option<String>,
+}
+
+impl Album {
+    /// The MusicBrainz release this album is linked to, if it is.
+    pub fn release_mbid(&self) -> Option<&str> {
+        self.mbid_linked
+            .as_deref()
+            .or(self.mbid.as_deref().filter(|mbid| *mbid != "none"))
+    }
 }
 
 #[derive(sqlx::FromRow, Clone, Debug)]
@@ -225,6 +248,12 @@ pub struct Track {
     pub track_number: Option<i32>,
     #[sqlx(default)]
     pub disc_number: Option<i32>,
+    /// The title of the disc the track is on, such as `Live in Tokyo`.
+    #[sqlx(default)]
+    pub disc_subtitle: Option<DBString>,
+    /// The number of discs in the release, according to the track's tags.
+    #[sqlx(default)]
+    pub disc_total: Option<i32>,
     pub duration: i64,
     pub created_at: DateTime<Utc>,
     #[sqlx(skip)]
@@ -234,6 +263,89 @@ pub struct Track {
     #[sqlx(try_from = "String")]
     pub location: PathBuf,
     pub artist_names: Option<DBString>,
+    #[sqlx(default)]
+    pub bpm: Option<f64>,
+    #[sqlx(default)]
+    pub musical_key: Option<DBString>,
+    /// The MusicBrainz recording ID.
+    #[sqlx(default)]
+    pub mbid: Option<String>,
+    /// The short name of the codec, such as `flac` or `mp3`. Tracks scanned before formats were
+    /// recorded don't have one until they're scanned again.
+    #[sqlx(default)]
+    pub codec: Option<String>,
+    #[sqlx(default)]
+    pub sample_rate: Option<i64>,
+    #[sqlx(default)]
+    pub bit_depth: Option<i64>,
+    /// Whether the track's tags mark it as explicit.
+    #[sqlx(default)]
+    pub explicit: bool,
+    /// How the track entered the library. Tracks added before this was recorded don't have one.
+    #[sqlx(default)]
+    pub source: Option<TrackSource>,
+    /// The scan path or imported folder the track was found in.
+    #[sqlx(default)]
+    pub source_path: Option<String>,
+    /// Whether rescans leave the track's metadata alone unless the file has changed.
+    #[sqlx(default)]
+    pub metadata_locked: bool,
+    /// How much louder or quieter the track is played, in decibels.
+    #[sqlx(default)]
+    pub volume_offset: Option<f64>,
+}
+
+/// How a track entered the library.
+#[derive(sqlx::Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[sqlx(rename_all = "lowercase")]
+pub enum TrackSource {
+    /// Found in one of the scan paths.
+    Scan,
+    /// Imported from outside the scan paths.
+    Import,
+    /// Found in a scan path on a removable drive or MTP device.
+    Device,
+    /// Found in a remote folder on a WebDAV or SFTP server.
+    Remote,
+}
+
+impl Track {
+    /// The format shown in the track's badge, such as `FLAC` or `Hi-Res`. Tracks without a
+    /// recorded codec fall back to their file extension.
+    pub fn format_label(&self) -> Option<String> {
+        match &self.codec {
+            Some(codec) => Some(format_label(
+                codec,
+                is_hi_res(self.sample_rate, self.bit_depth),
+            )),
+            None => self
+                .location
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_uppercase()),
+        }
+    }
+}
+
+/// Whether audio with this sample rate and bit depth is better than CD quality.
+pub fn is_hi_res(sample_rate: Option<i64>, bit_depth: Option<i64>) -> bool {
+    bit_depth.is_some_and(|bits| bits > 16) || sample_rate.is_some_and(|rate| rate > 48000)
+}
+
+fn is_lossless(codec: &str) -> bool {
+    matches!(codec, "flac" | "alac" | "wavpack") || codec.starts_with("pcm")
+}
+
+/// The name shown for a codec, or `Hi-Res` for lossless audio better than CD quality.
+pub fn format_label(codec: &str, hi_res: bool) -> String {
+    if hi_res && is_lossless(codec) {
+        return "Hi-Res".to_string();
+    }
+
+    match codec {
+        "vorbis" => "OGG".to_string(),
+        codec if codec.starts_with("pcm") => "PCM".to_string(),
+        codec => codec.to_uppercase(),
+    }
 }
 
 #[derive(sqlx::Type, Clone, Copy, Debug, PartialEq)]
@@ -250,6 +362,29 @@ pub struct Playlist {
     pub created_at: DateTime<Utc>,
     #[sqlx(rename = "type")]
     pub playlist_type: PlaylistType,
+    /// The hash of the playlist's cover in the art directory, if one has been set.
+    pub image_hash: Option<String>,
+    pub description: Option<String>,
+    /// When the playlist was deleted. Deleted playlists can be restored for 30 days.
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Playlist {
+    /// The path the playlist's cover can be loaded from, if it has one.
+    pub fn image_path(&self) -> Option<SharedString> {
+        playlist_image_path(self.id, self.image_hash.as_deref())
+    }
+}
+
+impl PlaylistWithCount {
+    /// The path the playlist's cover can be loaded from, if it has one.
+    pub fn image_path(&self) -> Option<SharedString> {
+        playlist_image_path(self.id, self.image_hash.as_deref())
+    }
+}
+
+fn playlist_image_path(id: i64, hash: Option<&str>) -> Option<SharedString> {
+    hash.map(|hash| format!("!db://playlist/{id}/{hash}").into())
 }
 
 #[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
@@ -259,6 +394,9 @@ pub struct PlaylistWithCount {
     pub created_at: DateTime<Utc>,
     #[sqlx(rename = "type")]
     pub playlist_type: PlaylistType,
+    pub image_hash: Option<String>,
+    pub description: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub track_count: i64,
 }
 
@@ -271,8 +409,152 @@ pub struct PlaylistItem {
     pub position: i64,
 }
 
+/// A track that was removed from a playlist, which can still be put back.
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct DeletedPlaylistItem {
+    pub id: i64,
+    pub playlist_id: i64,
+    pub playlist_name: DBString,
+    pub track_title: DBString,
+    pub deleted_at: DateTime<Utc>,
+}
+
 #[derive(sqlx::FromRow, Clone)]
 pub struct TrackStats {
     pub track_count: i64,
     pub total_duration: i64,
 }
+
+/// A file that couldn't be added to the library, and why.
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct QuarantinedFile {
+    pub location: String,
+    pub error: String,
+    pub quarantined_at: DateTime<Utc>,
+}
+
+/// A track with a custom tag, and the tag's value.
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TaggedTrack {
+    pub id: i64,
+    pub title: String,
+    pub value: String,
+    pub location: String,
+}
+
+#[derive(sqlx::FromRow, Clone, Debug, Default)]
+pub struct PlayStats {
+    pub play_count: i64,
+    pub last_played: Option<DateTime<Utc>>,
+}
+
+/// A play recorded in the play history.
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct Play {
+    pub id: i64,
+    pub track_id: i64,
+    pub played_at: DateTime<Utc>,
+    pub title: DBString,
+    pub artist_names: Option<DBString>,
+    /// The duration of the track, in seconds.
+    pub duration: i64,
+}
+
+/// What a track listing can be filtered and sorted by.
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct TrackSortKeys {
+    pub id: i64,
+    pub title: DBString,
+    pub artist_names: Option<DBString>,
+    pub duration: i64,
+    pub play_count: i64,
+}
+
+/// The badges shown for an album, worked out from its tracks.
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct AlbumFormat {
+    pub album_id: i64,
+    /// The codec of the album's tracks, if they all have the same one.
+    pub codec: Option<String>,
+    /// Whether every track is better than CD quality.
+    pub hi_res: bool,
+    /// Whether any track is marked as explicit.
+    pub explicit: bool,
+}
+
+impl AlbumFormat {
+    pub fn format_label(&self) -> Option<String> {
+        self.codec
+            .as_deref()
+            .map(|codec| format_label(codec, self.hi_res))
+    }
+}
+
+/// A mood or activity that tracks have been tagged with, such as Focus or Workout.
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub id: i64,
+    pub name: DBString,
+    pub track_count: i64,
+}
+
+/// An album along with the totals shown in the compact album list.
+#[derive(sqlx::FromRow, Clone, Debug)]
+pub struct AlbumSummary {
+    pub id: i64,
+    pub title: DBString,
+    pub title_sortable: DBString,
+    pub artist_name: Option<DBString>,
+    pub artist_name_sortable: Option<DBString>,
+    pub year: Option<i64>,
+    pub track_count: i64,
+    /// The total duration of the album's tracks, in seconds.
+    pub duration: i64,
+}
+
+/// A podcast feed that has been subscribed to.
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct Podcast {
+    pub id: i64,
+    pub url: String,
+    pub title: DBString,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub refreshed_at: Option<DateTime<Utc>>,
+    /// The number of episodes that haven't been seen since they were added.
+    pub new_count: i64,
+}
+
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct PodcastEpisode {
+    pub id: i64,
+    pub podcast_id: i64,
+    pub guid: String,
+    pub title: DBString,
+    pub description: Option<String>,
+    pub audio_url: String,
+    pub published_at: Option<DateTime<Utc>>,
+    /// The duration given by the feed, in seconds.
+    pub duration: Option<i64>,
+    /// Where the episode was downloaded to, if it was.
+    pub file_path: Option<String>,
+    /// Where playback stopped, in seconds.
+    pub position: i64,
+    pub listened: bool,
+    pub seen: bool,
+}
+
+/// An album of audiobook chapters.
+#[derive(sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct Audiobook {
+    pub id: i64,
+    pub title: DBString,
+    pub author: Option<DBString>,
+    pub series: Option<String>,
+    pub series_part: Option<String>,
+    pub chapter_count: i64,
+    pub duration: i64,
+    /// Whether any of the chapters were left part of the way through.
+    pub in_progress: bool,
+}
@@ -2,12 +2,16 @@ use std::sync::Arc;
 
 use gpui::{App, SharedString};
 use indexmap::IndexMap;
-use rustc_hash::FxBuildHasher;
+use rustc_hash::{FxBuildHasher, FxHashSet};
 
-use super::Album;
+use super::{Album, AlbumSummary};
 use crate::{
     library::db::{AlbumMethod, AlbumSortMethod, LibraryAccess},
-    ui::components::table::table_data::{Column, TableData, TableSort},
+    ui::{
+        components::table::table_data::{Column, TableData, TableSort},
+        locale::t,
+        models::Models,
+    },
 };
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -22,15 +26,35 @@ pub enum AlbumColumn {
 impl Column for AlbumColumn {
     fn get_column_name(&self) -> &'static str {
         match self {
-            AlbumColumn::Title => "Title",
-            AlbumColumn::Artist => "Artist",
-            AlbumColumn::Date => "Date",
-            AlbumColumn::Label => "Label",
-            AlbumColumn::CatalogNumber => "Catalog Number",
+            AlbumColumn::Title => "table.album.title",
+            AlbumColumn::Artist => "table.album.artist",
+            AlbumColumn::Date => "table.album.date",
+            AlbumColumn::Label => "table.album.label",
+            AlbumColumn::CatalogNumber => "table.album.catalog_number",
         }
     }
 }
 
+/// Returns the ids of the albums let through by the album filter and the filter text, or `None`
+/// if every album is.
+fn filtered_album_ids(cx: &mut App) -> anyhow::Result<Option<FxHashSet<i64>>> {
+    let filter = *cx.global::<Models>().album_filter.read(cx);
+    let text = cx.global::<Models>().album_filter_text.read(cx).clone();
+    let mut ids: Option<FxHashSet<i64>> = cx
+        .list_album_ids_by_filter(filter)?
+        .map(|ids| ids.into_iter().collect());
+
+    if !text.trim().is_empty() {
+        let matching = cx.list_album_ids_by_text(text.trim())?;
+        ids = Some(match ids {
+            Some(ids) => matching.into_iter().filter(|id| ids.contains(id)).collect(),
+            None => matching.into_iter().collect(),
+        });
+    }
+
+    Ok(ids)
+}
+
 impl TableData<AlbumColumn> for Album {
     type Identifier = (u32, String);
 
@@ -38,6 +62,10 @@ impl TableData<AlbumColumn> for Album {
         "Albums"
     }
 
+    fn get_table_title(cx: &App) -> SharedString {
+        t!(cx, "table.album.table_title")
+    }
+
     fn get_rows(
         cx: &mut gpui::App,
         sort: Option<TableSort<AlbumColumn>>,
@@ -86,11 +114,17 @@ impl TableData<AlbumColumn> for Album {
             _ => AlbumSortMethod::ArtistAsc,
         };
 
-        Ok(cx.list_albums(sort_method)?)
+        let mut albums = cx.list_albums(sort_method)?;
+
+        if let Some(ids) = filtered_album_ids(cx)? {
+            albums.retain(|(id, _)| ids.contains(&(*id as i64)));
+        }
+
+        Ok(albums)
     }
 
     fn get_row(cx: &mut gpui::App, id: Self::Identifier) -> anyhow::Result<Option<Arc<Self>>> {
-        Ok(cx.get_album_by_id(id.0 as i64, AlbumMethod::Thumbnail).ok())
+        Ok(cx.get_album_by_id(id.0 as i64, AlbumMethod::Metadata).ok())
     }
 
     fn get_column(&self, cx: &mut App, column: AlbumColumn) -> Option<SharedString> {
@@ -139,3 +173,152 @@ impl TableData<AlbumColumn> for Album {
         columns
     }
 }
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum AlbumSummaryColumn {
+    Artist,
+    Title,
+    Year,
+    Tracks,
+    Duration,
+}
+
+impl Column for AlbumSummaryColumn {
+    fn get_column_name(&self) -> &'static str {
+        match self {
+            AlbumSummaryColumn::Artist => "table.album.artist",
+            AlbumSummaryColumn::Title => "table.album.title",
+            AlbumSummaryColumn::Year => "table.album.year",
+            AlbumSummaryColumn::Tracks => "table.album.tracks",
+            AlbumSummaryColumn::Duration => "table.album.duration",
+        }
+    }
+}
+
+/// The compact album list. Every album is loaded up front so that the list can be sorted by the
+/// totals, which the album queries don't have.
+impl TableData<AlbumSummaryColumn> for AlbumSummary {
+    type Identifier = (u32, String);
+
+    fn get_table_name() -> &'static str {
+        "AlbumSummaries"
+    }
+
+    fn get_table_title(cx: &App) -> SharedString {
+        t!(cx, "table.album.table_title")
+    }
+
+    fn get_rows(
+        cx: &mut gpui::App,
+        sort: Option<TableSort<AlbumSummaryColumn>>,
+    ) -> anyhow::Result<Vec<Self::Identifier>> {
+        let mut albums = cx.list_album_summaries()?;
+
+        if let Some(ids) = filtered_album_ids(cx)? {
+            albums.retain(|album| ids.contains(&album.id));
+        }
+
+        let sort = sort.unwrap_or(TableSort {
+            column: AlbumSummaryColumn::Artist,
+            ascending: true,
+        });
+
+        albums.sort_by(|a, b| {
+            let by_artist = || {
+                let name = |album: &AlbumSummary| {
+                    album
+                        .artist_name_sortable
+                        .as_ref()
+                        .map(|name| name.0.to_lowercase())
+                };
+                name(a).cmp(&name(b)).then(a.year.cmp(&b.year))
+            };
+            let by_title = || {
+                a.title_sortable
+                    .0
+                    .to_lowercase()
+                    .cmp(&b.title_sortable.0.to_lowercase())
+            };
+
+            let ordering = match sort.column {
+                AlbumSummaryColumn::Artist => by_artist(),
+                AlbumSummaryColumn::Title => by_title(),
+                AlbumSummaryColumn::Year => a.year.cmp(&b.year).then_with(by_artist),
+                AlbumSummaryColumn::Tracks => a.track_count.cmp(&b.track_count),
+                AlbumSummaryColumn::Duration => a.duration.cmp(&b.duration),
+            };
+
+            if sort.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+
+        Ok(albums
+            .into_iter()
+            .map(|album| (album.id as u32, album.title.0.to_string()))
+            .collect())
+    }
+
+    fn get_row(cx: &mut gpui::App, id: Self::Identifier) -> anyhow::Result<Option<Arc<Self>>> {
+        Ok(cx.get_album_summary_by_id(id.0 as i64).ok())
+    }
+
+    fn get_column(&self, _: &mut App, column: AlbumSummaryColumn) -> Option<SharedString> {
+        match column {
+            AlbumSummaryColumn::Artist => self.artist_name.as_ref().map(|v| v.0.clone()),
+            AlbumSummaryColumn::Title => Some(self.title.0.clone()),
+            AlbumSummaryColumn::Year => self.year.map(|year| year.to_string().into()),
+            AlbumSummaryColumn::Tracks => Some(self.track_count.to_string().into()),
+            AlbumSummaryColumn::Duration => {
+                let (hours, minutes, seconds) = (
+                    self.duration / 3600,
+                    self.duration / 60 % 60,
+                    self.duration % 60,
+                );
+
+                Some(if hours > 0 {
+                    format!("{hours}:{minutes:02}:{seconds:02}").into()
+                } else {
+                    format!("{minutes}:{seconds:02}").into()
+                })
+            }
+        }
+    }
+
+    fn get_image_path(&self) -> Option<SharedString> {
+        None
+    }
+
+    fn has_images() -> bool {
+        false
+    }
+
+    fn column_monospace(column: AlbumSummaryColumn) -> bool {
+        matches!(
+            column,
+            AlbumSummaryColumn::Year | AlbumSummaryColumn::Tracks | AlbumSummaryColumn::Duration
+        )
+    }
+
+    fn get_element_id(&self) -> impl Into<gpui::ElementId> {
+        ("album-summary", self.id as u32)
+    }
+
+    fn get_table_id(&self) -> Self::Identifier {
+        (self.id as u32, self.title.0.to_string())
+    }
+
+    fn default_columns() -> IndexMap<AlbumSummaryColumn, f32, FxBuildHasher> {
+        let s = FxBuildHasher;
+        let mut columns: IndexMap<AlbumSummaryColumn, f32, FxBuildHasher> =
+            IndexMap::with_hasher(s);
+        columns.insert(AlbumSummaryColumn::Artist, 220.0);
+        columns.insert(AlbumSummaryColumn::Title, 320.0);
+        columns.insert(AlbumSummaryColumn::Year, 70.0);
+        columns.insert(AlbumSummaryColumn::Tracks, 70.0);
+        columns.insert(AlbumSummaryColumn::Duration, 90.0);
+        columns
+    }
+}
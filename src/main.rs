@@ -6,6 +6,7 @@
 
 use std::sync::LazyLock;
 
+use clap::Parser;
 use services::mmb::lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET};
 
 mod devices;
@@ -17,6 +18,11 @@ mod settings;
 mod ui;
 mod util;
 
+/// The runtime all async IO (the database, network requests and the media metadata broadcast
+/// services) runs on. GPUI tasks hand work to it with `RUNTIME.spawn` and await the result, and
+/// only block on it for short database reads. The playback, scan and analysis threads are plain
+/// threads that are sent commands through unbounded channels, which never block the sender and
+/// deliver commands in the order they were sent.
 static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -26,7 +32,34 @@ static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
 });
 
 fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    // logs go to stderr so that they don't get mixed in with the output of library commands
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = ui::arguments::Args::parse();
+    ui::app::set_dirs(args.data_dir.clone(), args.config_dir.clone());
+
+    // switching libraries restarts the app without its arguments, so the directories are passed
+    // on through the environment as well
+    for (var, dir) in [
+        ("HUMMINGBIRD_DATA_DIR", &args.data_dir),
+        ("HUMMINGBIRD_CONFIG_DIR", &args.config_dir),
+    ] {
+        if let Some(dir) = dir {
+            // SAFETY: no other threads have been started yet
+            unsafe { std::env::set_var(var, dir) };
+        }
+    }
+
+    if let Some(dir) = &args.bench_scan {
+        return library::bench::bench_scan(dir);
+    }
+
+    if let Some(ui::arguments::Command::Library(command)) = &args.command {
+        let dirs = ui::app::get_dirs();
+        return library::headless::run(dirs.data_dir(), dirs.config_dir(), command);
+    }
 
     tracing::info!("Starting application");
 
@@ -36,5 +69,5 @@ fn main() -> anyhow::Result<()> {
         );
     }
 
-    crate::ui::app::run()
+    crate::ui::app::run(args)
 }
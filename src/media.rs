@@ -2,4 +2,7 @@ pub mod builtin;
 pub mod errors;
 pub mod metadata;
 pub mod playback;
+pub mod properties;
+pub mod tags;
 pub mod traits;
+pub mod transcode;
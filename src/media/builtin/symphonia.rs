@@ -1,15 +1,20 @@
-use std::{ffi::OsStr, fs::File};
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
 
 use intx::{I24, U24};
 use regex::Regex;
+use rustc_hash::FxHashMap;
 use symphonia::{
     core::{
         audio::{AudioBufferRef, Channels, Signal},
         codecs::{CODEC_TYPE_NULL, Decoder, DecoderOptions},
         errors::Error,
         formats::{FormatOptions, FormatReader, SeekMode, SeekTo},
-        io::MediaSourceStream,
-        meta::{MetadataOptions, StandardTagKey, Tag, Value, Visual},
+        io::{MediaSource, MediaSourceStream},
+        meta::{MetadataOptions, StandardTagKey, StandardVisualKey, Tag, Value, Visual},
         probe::{Hint, ProbeResult},
         units::{Time, TimeBase},
     },
@@ -24,9 +29,12 @@ use crate::{
             PlaybackReadError, PlaybackStartError, PlaybackStopError, SeekError,
             TrackDurationError,
         },
-        metadata::Metadata,
+        metadata::{
+            Chapter, CueTrack, EmbeddedImage, ImageKind, Metadata, StreamInfo, custom_tag_name,
+            parse_mbid,
+        },
         playback::{PlaybackFrame, Samples},
-        traits::{MediaPlugin, MediaProvider},
+        traits::{MediaPlugin, MediaProvider, MediaStream},
     },
 };
 
@@ -37,14 +45,88 @@ pub struct SymphoniaProvider {
     current_track: u32,
     current_duration: u64,
     current_length: Option<u64>,
-    current_position: u64,
+    current_position: f64,
     current_timebase: Option<TimeBase>,
     decoder: Option<Box<dyn Decoder>>,
     pending_metadata_update: bool,
-    last_image: Option<Visual>,
+    segment_metadata: bool,
+    /// The images in the file's metadata, until they're read.
+    images: Vec<Visual>,
+    bitrate_window_bytes: u64,
+    bitrate_window_frames: u64,
+    current_bitrate: Option<u32>,
+    /// The text of the cue sheet stored in the file's CUESHEET tag, until it's read along with
+    /// the file's cue points.
+    cue_sheet: Option<String>,
+}
+
+/// Lets Symphonia read a [MediaStream].
+struct StreamSource(Box<dyn MediaStream>);
+
+impl Read for StreamSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Seek for StreamSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl MediaSource for StreamSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.0.byte_len()
+    }
 }
 
 impl SymphoniaProvider {
+    fn open_source(
+        &mut self,
+        source: Box<dyn MediaSource>,
+        ext: Option<&OsStr>,
+    ) -> Result<(), OpenError> {
+        let mss = MediaSourceStream::new(source, Default::default());
+        let meta_opts: MetadataOptions = Default::default();
+        let fmt_opts: FormatOptions = Default::default();
+
+        let ext_as_str = ext.and_then(|e| e.to_str());
+        let mut probed = if let Some(ext) = ext_as_str {
+            let mut hint = Hint::new();
+            hint.with_extension(ext);
+
+            symphonia::default::get_probe()
+                .format(&hint, mss, &fmt_opts, &meta_opts)
+                .map_err(|_| OpenError::UnsupportedFormat)?
+        } else {
+            let hint = Hint::new();
+
+            symphonia::default::get_probe()
+                .format(&hint, mss, &fmt_opts, &meta_opts)
+                .map_err(|_| OpenError::UnsupportedFormat)?
+        };
+
+        self.read_base_metadata(&mut probed);
+        self.current_position = 0.0;
+        self.current_length = None;
+        self.current_timebase = None;
+        self.reset_bitrate();
+        self.format = Some(probed.format);
+
+        Ok(())
+    }
+
+    fn reset_bitrate(&mut self) {
+        self.bitrate_window_bytes = 0;
+        self.bitrate_window_frames = 0;
+        self.current_bitrate = None;
+    }
+
     fn break_metadata(&mut self, tags: &[Tag]) {
         let id3_position_in_set_regex = Regex::new(r"(\d+)/(\d+)").unwrap();
 
@@ -145,6 +227,13 @@ impl SymphoniaProvider {
                         _ => None,
                     }
                 }
+                Some(StandardTagKey::DiscSubtitle) => {
+                    self.current_metadata.disc_subtitle = Some(tag.value.to_string())
+                }
+                // ID3's set subtitle frame isn't mapped to a standard key
+                None if custom_tag_name(&tag.key) == "TSST" => {
+                    self.current_metadata.disc_subtitle = Some(tag.value.to_string())
+                }
                 Some(StandardTagKey::Label) => {
                     self.current_metadata.label = Some(tag.value.to_string())
                 }
@@ -163,6 +252,55 @@ impl SymphoniaProvider {
                 Some(StandardTagKey::MusicBrainzAlbumId) => {
                     self.current_metadata.mbid_album = Some(tag.value.to_string())
                 }
+                // Picard writes the recording ID to the MUSICBRAINZ_TRACKID tag
+                Some(
+                    StandardTagKey::MusicBrainzRecordingId | StandardTagKey::MusicBrainzTrackId,
+                ) => self.current_metadata.mbid_track = parse_mbid(&tag.value.to_string()),
+                Some(StandardTagKey::MusicBrainzArtistId) => {
+                    self.current_metadata.mbid_artist = parse_mbid(&tag.value.to_string())
+                }
+                Some(StandardTagKey::MusicBrainzAlbumArtistId) => {
+                    self.current_metadata.mbid_album_artist = parse_mbid(&tag.value.to_string())
+                }
+                Some(StandardTagKey::ReplayGainTrackGain) => {
+                    self.current_metadata.replaygain_track_gain = parse_replaygain(&tag.value)
+                }
+                Some(StandardTagKey::ReplayGainTrackPeak) => {
+                    self.current_metadata.replaygain_track_peak = parse_replaygain(&tag.value)
+                }
+                Some(StandardTagKey::MovementName) => {
+                    self.current_metadata.movement = Some(tag.value.to_string())
+                }
+                Some(StandardTagKey::MovementNumber) => {
+                    self.current_metadata.movement_number = match &tag.value {
+                        Value::String(v) => v.split('/').next().and_then(|v| v.parse().ok()),
+                        Value::UnsignedInt(v) => Some(*v),
+                        _ => None,
+                    }
+                }
+                None if custom_tag_name(&tag.key) == "CUESHEET" => {
+                    self.cue_sheet = Some(tag.value.to_string())
+                }
+                // Vorbis comments and APE tags have no standard work field, but taggers write one
+                None if custom_tag_name(&tag.key) == "WORK" => {
+                    self.current_metadata.work = Some(tag.value.to_string())
+                }
+                Some(StandardTagKey::Mood) | None => {
+                    let name = custom_tag_name(&tag.key);
+                    let value = match &tag.value {
+                        Value::Binary(_) | Value::Flag => continue,
+                        value => value.to_string(),
+                    };
+
+                    // the same tags can be read from both the container and the stream
+                    let field = (name, value.trim().to_string());
+                    if !field.0.is_empty()
+                        && !field.1.is_empty()
+                        && !self.current_metadata.custom.contains(&field)
+                    {
+                        self.current_metadata.custom.push(field);
+                    }
+                }
                 _ => (),
             }
         }
@@ -170,55 +308,223 @@ impl SymphoniaProvider {
 
     fn read_base_metadata(&mut self, probed: &mut ProbeResult) {
         self.current_metadata = Metadata::default();
-        self.last_image = None;
+        self.images.clear();
+        self.cue_sheet = None;
 
         if let Some(metadata) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
             self.break_metadata(metadata.tags());
-            if !metadata.visuals().is_empty() {
-                self.last_image = Some(metadata.visuals()[0].clone());
-            }
+            self.add_images(metadata.visuals());
         }
 
         if let Some(metadata) = probed.format.metadata().current() {
             self.break_metadata(metadata.tags());
-            if !metadata.visuals().is_empty() {
-                self.last_image = Some(metadata.visuals()[0].clone());
+            self.add_images(metadata.visuals());
+        }
+
+        // iTunes stores the work in the grouping field of tracks that have a movement
+        if self.current_metadata.work.is_none() && self.current_metadata.movement.is_some() {
+            self.current_metadata.work = self.current_metadata.grouping.clone();
+        }
+
+        self.read_cue_tracks(probed.format.as_ref());
+
+        self.segment_metadata = false;
+        self.pending_metadata_update = true;
+    }
+
+    /// Keeps the images of a metadata revision, skipping any that were already read from the
+    /// file's other tags.
+    fn add_images(&mut self, visuals: &[Visual]) {
+        for visual in visuals {
+            if !self.images.iter().any(|image| image.data == visual.data) {
+                self.images.push(visual.clone());
             }
         }
+    }
+
+    /// Reads the tracks marked by the file's embedded cue sheet. FLAC's CUESHEET block only says
+    /// where each track starts, so titles and performers are taken from the cue sheet text that
+    /// rippers also store in the CUESHEET tag, if there is one.
+    fn read_cue_tracks(&mut self, format: &dyn FormatReader) {
+        let Some(tb) = cue_time_base(format) else {
+            return;
+        };
+
+        let mut named = self
+            .cue_sheet
+            .take()
+            .map(|text| parse_cue_sheet(&text))
+            .unwrap_or_default();
+        let cues = format.cues();
+
+        let tracks: Vec<CueTrack> = cues
+            .iter()
+            .zip(cues.iter().skip(1))
+            .filter(|(cue, _)| !is_lead_out(cue.index))
+            .map(|(cue, next)| {
+                let seconds = |ts| {
+                    let time = tb.calc_time(ts);
+                    time.seconds as f64 + time.frac
+                };
+                let number = cue.index as u64;
+                let named = named.remove(&number).unwrap_or_default();
+                let isrc = cue
+                    .tags
+                    .iter()
+                    .find(|tag| tag.std_key == Some(StandardTagKey::IdentIsrc))
+                    .map(|tag| tag.value.to_string().trim_matches('\0').trim().to_string())
+                    .filter(|isrc| !isrc.is_empty());
+
+                CueTrack {
+                    number,
+                    start: seconds(cue.start_ts),
+                    end: seconds(next.start_ts),
+                    isrc,
+                    ..named
+                }
+            })
+            .collect();
+
+        // a cue sheet with a single track describes the file as a whole
+        if tracks.len() > 1 {
+            self.current_metadata.cue_tracks = tracks;
+        }
+    }
+
+    /// Reads metadata that appears partway through the stream, such as the tags of the next song
+    /// in a chained Ogg stream. Updates at the very start of the stream are part of the file's own
+    /// metadata and have already been read.
+    fn read_stream_metadata(&mut self) {
+        let Some(format) = &mut self.format else {
+            return;
+        };
+
+        let mut metadata = format.metadata();
+        if metadata.is_latest() {
+            return;
+        }
+
+        let Some(tags) = metadata
+            .skip_to_latest()
+            .map(|revision| revision.tags().to_vec())
+        else {
+            return;
+        };
+        if self.current_position <= 0.0 {
+            return;
+        }
+
+        let has_title = tags
+            .iter()
+            .any(|tag| tag.std_key == Some(StandardTagKey::TrackTitle));
+        if !has_title {
+            return;
+        }
 
+        self.current_metadata.name = None;
+        self.current_metadata.artist = None;
+        self.break_metadata(&tags);
+        self.segment_metadata = true;
         self.pending_metadata_update = true;
     }
 }
 
-impl MediaProvider for SymphoniaProvider {
-    fn open(&mut self, file: File, ext: Option<&OsStr>) -> Result<(), OpenError> {
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
-        let meta_opts: MetadataOptions = Default::default();
-        let fmt_opts: FormatOptions = Default::default();
+/// Returns what an image shows, from the usage its tag marks it with.
+fn image_kind(usage: Option<StandardVisualKey>) -> ImageKind {
+    match usage {
+        Some(StandardVisualKey::FrontCover) => ImageKind::FrontCover,
+        Some(StandardVisualKey::BackCover) => ImageKind::BackCover,
+        Some(StandardVisualKey::Leaflet) => ImageKind::Leaflet,
+        Some(StandardVisualKey::Media) => ImageKind::Media,
+        Some(
+            StandardVisualKey::LeadArtistPerformerSoloist
+            | StandardVisualKey::ArtistPerformer
+            | StandardVisualKey::Conductor
+            | StandardVisualKey::BandOrchestra
+            | StandardVisualKey::Composer
+            | StandardVisualKey::Lyricist,
+        ) => ImageKind::Artist,
+        _ => ImageKind::Other,
+    }
+}
 
-        let ext_as_str = ext.and_then(|e| e.to_str());
-        let mut probed = if let Some(ext) = ext_as_str {
-            let mut hint = Hint::new();
-            hint.with_extension(ext);
+/// Returns the time base of the file's audio, which its cue points are measured in.
+fn cue_time_base(format: &dyn FormatReader) -> Option<TimeBase> {
+    format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .and_then(|t| t.codec_params.time_base)
+}
 
-            symphonia::default::get_probe()
-                .format(&hint, mss, &fmt_opts, &meta_opts)
-                .map_err(|_| OpenError::UnsupportedFormat)?
-        } else {
-            let hint = Hint::new();
+/// Whether the cue is the lead-out of a FLAC cue sheet, which marks where the last track ends
+/// rather than a track of its own. CD-DA cue sheets number it 170, and others number it 255.
+fn is_lead_out(index: u32) -> bool {
+    index == 170 || index == 255
+}
 
-            symphonia::default::get_probe()
-                .format(&hint, mss, &fmt_opts, &meta_opts)
-                .map_err(|_| OpenError::UnsupportedFormat)?
-        };
+/// Reads the titles and performers of the tracks in the text of a cue sheet, keyed by track
+/// number. Where the tracks start is read from the file's cue points instead.
+fn parse_cue_sheet(text: &str) -> FxHashMap<u64, CueTrack> {
+    let mut tracks: FxHashMap<u64, CueTrack> = FxHashMap::default();
+    let mut current = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let value = rest
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+            .unwrap_or(rest)
+            .trim();
+
+        // the title and performer before the first track are the disc's
+        match (command.to_uppercase().as_str(), current) {
+            ("TRACK", _) => {
+                current = rest
+                    .split_whitespace()
+                    .next()
+                    .and_then(|number| number.parse().ok());
+            }
+            ("TITLE", Some(number)) if !value.is_empty() => {
+                tracks.entry(number).or_default().title = Some(value.to_string());
+            }
+            ("PERFORMER", Some(number)) if !value.is_empty() => {
+                tracks.entry(number).or_default().artist = Some(value.to_string());
+            }
+            _ => (),
+        }
+    }
 
-        self.read_base_metadata(&mut probed);
-        self.current_position = 0;
-        self.current_length = None;
-        self.current_timebase = None;
-        self.format = Some(probed.format);
+    tracks
+}
 
-        Ok(())
+/// Reads a ReplayGain value, which is usually written with its unit, like `-6.50 dB`.
+fn parse_replaygain(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(v) => Some(*v),
+        value => value
+            .to_string()
+            .trim()
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .trim()
+            .parse()
+            .ok(),
+    }
+}
+
+impl MediaProvider for SymphoniaProvider {
+    fn open(&mut self, file: File, ext: Option<&OsStr>) -> Result<(), OpenError> {
+        self.open_source(Box::new(file), ext)
+    }
+
+    fn open_stream(
+        &mut self,
+        stream: Box<dyn MediaStream>,
+        ext: Option<&OsStr>,
+    ) -> Result<(), OpenError> {
+        self.open_source(Box::new(StreamSource(stream)), ext)
     }
 
     fn close(&mut self) -> Result<(), CloseError> {
@@ -265,6 +571,8 @@ impl MediaProvider for SymphoniaProvider {
     }
 
     fn read_samples(&mut self) -> Result<PlaybackFrame, PlaybackReadError> {
+        self.read_stream_metadata();
+
         let Some(format) = &mut self.format else {
             return Err(PlaybackReadError::NothingOpen);
         };
@@ -279,11 +587,6 @@ impl MediaProvider for SymphoniaProvider {
                 }
             };
 
-            while !format.metadata().is_latest() {
-                // TODO: handle metadata updates
-                format.metadata().pop();
-            }
-
             if packet.track_id() != self.current_track {
                 continue;
             }
@@ -298,8 +601,22 @@ impl MediaProvider for SymphoniaProvider {
                     let channel_count = decoded.spec().channels.count();
                     self.current_duration = decoded.capacity() as u64;
 
+                    // measure the bitrate over roughly a second of audio, so that it follows
+                    // variable bitrate streams without changing on every packet
+                    self.bitrate_window_bytes += packet.buf().len() as u64;
+                    self.bitrate_window_frames += decoded.frames() as u64;
+
+                    if rate > 0 && self.bitrate_window_frames >= rate as u64 {
+                        let bits = self.bitrate_window_bytes * 8 * rate as u64;
+                        self.current_bitrate =
+                            Some((bits / self.bitrate_window_frames / 1000) as u32);
+                        self.bitrate_window_bytes = 0;
+                        self.bitrate_window_frames = 0;
+                    }
+
                     if let Some(tb) = &self.current_timebase {
-                        self.current_position = tb.calc_time(packet.ts()).seconds;
+                        let time = tb.calc_time(packet.ts());
+                        self.current_position = time.seconds as f64 + time.frac;
                     }
 
                     match decoded {
@@ -501,22 +818,118 @@ impl MediaProvider for SymphoniaProvider {
         }
     }
 
+    fn stream_info(&self) -> Result<StreamInfo, MetadataError> {
+        let Some(format) = &self.format else {
+            return Err(MetadataError::NothingOpen);
+        };
+
+        let Some(track) = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        else {
+            return Ok(StreamInfo::default());
+        };
+
+        let params = &track.codec_params;
+        let duration = params.n_frames.zip(params.time_base).map(|(frames, tb)| {
+            let time = tb.calc_time(frames);
+            time.seconds as f64 + time.frac
+        });
+
+        Ok(StreamInfo {
+            codec: get_codecs()
+                .get_codec(params.codec)
+                .map(|codec| codec.short_name.to_string()),
+            sample_rate: params.sample_rate,
+            bits_per_sample: params.bits_per_sample,
+            channels: params.channels.map(|channels| channels.count() as u16),
+            duration,
+            bitrate: self.current_bitrate,
+        })
+    }
+
     fn metadata_updated(&self) -> bool {
         self.pending_metadata_update
     }
 
+    fn metadata_starts_segment(&self) -> bool {
+        self.segment_metadata
+    }
+
     fn read_image(&mut self) -> Result<Option<Box<[u8]>>, MetadataError> {
-        if self.format.is_some() {
-            if let Some(visual) = &self.last_image {
-                let data = Ok(Some(visual.data.clone()));
-                self.last_image = None;
-                data
-            } else {
-                Ok(None)
-            }
-        } else {
-            Err(MetadataError::NothingOpen)
+        if self.format.is_none() {
+            return Err(MetadataError::NothingOpen);
         }
+
+        // files often hold the back cover or the artist too, but the front cover is the album art
+        let images = std::mem::take(&mut self.images);
+        let cover = images
+            .iter()
+            .find(|visual| visual.usage == Some(StandardVisualKey::FrontCover))
+            .or(images.first());
+
+        Ok(cover.map(|visual| visual.data.clone()))
+    }
+
+    fn read_images(&mut self) -> Result<Vec<EmbeddedImage>, MetadataError> {
+        if self.format.is_none() {
+            return Err(MetadataError::NothingOpen);
+        }
+
+        Ok(std::mem::take(&mut self.images)
+            .into_iter()
+            .map(|visual| EmbeddedImage {
+                kind: image_kind(visual.usage),
+                data: visual.data,
+            })
+            .collect())
+    }
+
+    fn chapters(&self) -> Result<Vec<Chapter>, MetadataError> {
+        let Some(format) = &self.format else {
+            return Err(MetadataError::NothingOpen);
+        };
+
+        // the cue points of FLAC files are named by the cue sheet in their tags
+        if !self.current_metadata.cue_tracks.is_empty() {
+            return Ok(self
+                .current_metadata
+                .cue_tracks
+                .iter()
+                .map(|track| Chapter {
+                    start: track.start,
+                    title: track.title.clone(),
+                    artist: track.artist.clone(),
+                })
+                .collect());
+        }
+
+        let Some(tb) = cue_time_base(format.as_ref()) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(format
+            .cues()
+            .iter()
+            .filter(|cue| !is_lead_out(cue.index))
+            .map(|cue| {
+                let time = tb.calc_time(cue.start_ts);
+                let tag = |key| {
+                    cue.tags
+                        .iter()
+                        .find(|tag| tag.std_key == Some(key))
+                        .map(|tag| tag.value.to_string())
+                        .filter(|value| !value.trim().is_empty())
+                };
+
+                Chapter {
+                    start: time.seconds as f64 + time.frac,
+                    title: tag(StandardTagKey::TrackTitle),
+                    artist: tag(StandardTagKey::Artist),
+                }
+            })
+            .collect())
     }
 
     fn duration_secs(&self) -> Result<u64, TrackDurationError> {
@@ -530,6 +943,16 @@ impl MediaProvider for SymphoniaProvider {
     }
 
     fn position_secs(&self) -> Result<u64, TrackDurationError> {
+        if self.decoder.is_none() {
+            Err(TrackDurationError::NothingOpen)
+        } else if self.current_length.is_none() {
+            Err(TrackDurationError::NeverStarted)
+        } else {
+            Ok(self.current_position as u64)
+        }
+    }
+
+    fn position(&self) -> Result<f64, TrackDurationError> {
         if self.decoder.is_none() {
             Err(TrackDurationError::NothingOpen)
         } else if self.current_length.is_none() {
@@ -558,9 +981,13 @@ impl MediaProvider for SymphoniaProvider {
             .map_err(|e| SeekError::Unknown(e.to_string()))?;
 
         if let Some(timebase) = timebase {
-            self.current_position = timebase.calc_time(seek.actual_ts).seconds;
+            let time = timebase.calc_time(seek.actual_ts);
+            self.current_position = time.seconds as f64 + time.frac;
         }
 
+        self.bitrate_window_bytes = 0;
+        self.bitrate_window_frames = 0;
+
         Ok(())
     }
 
@@ -111,3 +111,27 @@ pub enum ChannelRetrievalError {
     #[error("Unknown media provider error: `{0}`")]
     Unknown(String),
 }
+
+#[derive(Debug, Error)]
+pub enum TagWriteError {
+    #[error("Writing tags to this format is not supported")]
+    UnsupportedFormat,
+    #[error("File is corrupt")]
+    FileCorrupt,
+    #[error("Tag data is too large to write")]
+    TooLarge,
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ID3 error: {0}")]
+    Id3(#[from] id3::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum TranscodeError {
+    #[error("ffmpeg could not be found, install it or set transcode.ffmpeg in the settings")]
+    FfmpegNotFound,
+    #[error("ffmpeg failed: {0}")]
+    Failed(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
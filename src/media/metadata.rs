@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Default, PartialEq, Clone)]
@@ -12,6 +14,10 @@ pub struct Metadata {
     pub sort_album: Option<String>,
     pub genre: Option<String>,
     pub grouping: Option<String>,
+    /// The classical work the track is a movement of.
+    pub work: Option<String>,
+    pub movement: Option<String>,
+    pub movement_number: Option<u64>,
     pub bpm: Option<u64>,
     pub compilation: bool,
     pub date: Option<DateTime<Utc>>,
@@ -24,10 +30,213 @@ pub struct Metadata {
     pub track_max: Option<u64>,
     pub disc_current: Option<u64>,
     pub disc_max: Option<u64>,
+    /// The title of the disc the track is on, such as `Live in Tokyo`.
+    pub disc_subtitle: Option<String>,
 
     pub label: Option<String>,
     pub catalog: Option<String>,
     pub isrc: Option<String>,
 
     pub mbid_album: Option<String>,
+    /// The MusicBrainz recording ID.
+    pub mbid_track: Option<String>,
+    pub mbid_artist: Option<String>,
+    pub mbid_album_artist: Option<String>,
+
+    /// The ReplayGain track gain in decibels.
+    pub replaygain_track_gain: Option<f64>,
+    /// The ReplayGain track peak, where 1.0 is full scale.
+    pub replaygain_track_peak: Option<f64>,
+
+    /// Tags that aren't otherwise read, such as `MOOD` or `OCCASION`, as pairs of their upper case
+    /// name and value.
+    pub custom: Vec<(String, String)>,
+
+    /// The tracks marked by the file's embedded cue sheet, if it marks more than one. Files like
+    /// this hold a whole disc, and each of these tracks is added to the library on its own.
+    pub cue_tracks: Vec<CueTrack>,
+}
+
+impl Metadata {
+    /// The title shown while the track is playing. Movements of a work are shown as
+    /// `Work — Movement`, since movement names like `Allegro` mean little on their own.
+    pub fn display_title(&self) -> Option<String> {
+        match (&self.work, &self.movement) {
+            (Some(work), Some(movement)) => Some(format!("{work} — {movement}")),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// Whether the tags mark the track as explicit, with iTunes' `ITUNESADVISORY` tag (where 1
+    /// and 4 mean explicit, and 2 means clean) or an `EXPLICIT` tag.
+    pub fn explicit(&self) -> bool {
+        self.custom.iter().any(|(name, value)| match name.as_str() {
+            "ITUNESADVISORY" => matches!(value.trim(), "1" | "4"),
+            "EXPLICIT" => matches!(
+                value.trim().to_lowercase().as_str(),
+                "1" | "yes" | "true" | "explicit"
+            ),
+            _ => false,
+        })
+    }
+
+    /// Returns the metadata of one of the file's cue tracks. The track's own title, artist and
+    /// number replace the file's, and the rest is shared by every track of the file.
+    pub fn cue_track_metadata(&self, track: &CueTrack) -> Metadata {
+        Metadata {
+            name: Some(
+                track
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| format!("Track {:02}", track.number)),
+            ),
+            artist: track.artist.clone().or_else(|| self.artist.clone()),
+            // tracks with their own performers still belong to the file's album
+            album_artist: self.album_artist.clone().or_else(|| self.artist.clone()),
+            track_current: Some(track.number),
+            track_max: Some(self.cue_tracks.len() as u64),
+            mbid_track: None,
+            isrc: track.isrc.clone(),
+            cue_tracks: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// A track of a file that holds a whole disc, as marked by the file's embedded cue sheet.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct CueTrack {
+    pub number: u64,
+    /// Where the track starts in the file, in seconds.
+    pub start: f64,
+    /// Where the track ends in the file, in seconds.
+    pub end: f64,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub isrc: Option<String>,
+}
+
+impl CueTrack {
+    /// Returns the location the track is stored under in the library. Cue tracks don't have a
+    /// file of their own, so they're placed inside the file that holds them, like
+    /// `Album.flac/03`.
+    pub fn location(&self, file: &Path) -> PathBuf {
+        file.join(format!("{:02}", self.number))
+    }
+}
+
+/// Where a track's audio is. Most tracks are a whole file, but tracks split from a cue sheet are
+/// a part of the file that holds the disc (see [CueTrack::location]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackFile {
+    /// The location the track is stored under in the library.
+    pub location: PathBuf,
+    /// The file the track's audio is in.
+    pub path: PathBuf,
+    /// Where the track starts and ends in the file, in seconds, if it's only a part of the file.
+    pub range: Option<(f64, f64)>,
+}
+
+impl TrackFile {
+    /// A track that is the whole of the file at its location.
+    pub fn whole(location: impl Into<PathBuf>) -> Self {
+        let location = location.into();
+
+        TrackFile {
+            path: location.clone(),
+            location,
+            range: None,
+        }
+    }
+
+    /// The name of the track's file, without its extension. Tracks split from a cue sheet don't
+    /// have a file of their own, so they're named after the file and their number, like
+    /// `Album 03`.
+    pub fn stem(&self) -> String {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+
+        match self.range {
+            Some(_) => format!(
+                "{stem} {}",
+                self.location
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            ),
+            None => stem.into_owned(),
+        }
+    }
+}
+
+/// Returns the file that holds a cue track, if the location is one of a cue track (see
+/// [CueTrack::location]).
+pub fn cue_track_file(location: &Path) -> Option<&Path> {
+    location.parent().filter(|file| file.is_file())
+}
+
+/// Returns the name a custom tag is stored under. Formats that keep user-defined tags in a
+/// container, like ID3's `TXXX:OCCASION` or iTunes' `com.apple.iTunes:OCCASION`, are unwrapped
+/// so that the same tag has the same name in every format.
+pub fn custom_tag_name(key: &str) -> String {
+    key.rsplit(':').next().unwrap_or(key).trim().to_uppercase()
+}
+
+/// Technical information about the audio stream of a file.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct StreamInfo {
+    /// The short name of the codec, such as `flac` or `mp3`.
+    pub codec: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub bits_per_sample: Option<u32>,
+    pub channels: Option<u16>,
+    /// The length of the stream in seconds.
+    pub duration: Option<f64>,
+    /// The bitrate of the most recently decoded second of audio in kilobits per second. This is
+    /// only known once playback has started, and changes over time for variable bitrate streams.
+    pub bitrate: Option<u32>,
+}
+
+/// What an image embedded in a file shows, as marked by its tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    FrontCover,
+    BackCover,
+    /// A page of the booklet.
+    Leaflet,
+    /// The disc itself.
+    Media,
+    /// The artist, a member of the band or anyone else who performed on the recording.
+    Artist,
+    Other,
+}
+
+/// An image embedded in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedImage {
+    pub kind: ImageKind,
+    pub data: Box<[u8]>,
+}
+
+/// A chapter of a file, as marked by one of its cue points.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Chapter {
+    /// Where the chapter starts, in seconds.
+    pub start: f64,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+}
+
+/// Finds the first MusicBrainz ID in a tag value or a link to a MusicBrainz page. Tags with more
+/// than one artist can hold several IDs, separated by slashes or semicolons.
+pub fn parse_mbid(value: &str) -> Option<String> {
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+    let bytes = value.as_bytes();
+
+    (0..bytes.len().saturating_sub(35)).find_map(|start| {
+        let candidate = value.get(start..start + 36)?;
+        let valid = candidate.split('-').map(str::len).eq(GROUPS)
+            && candidate.chars().all(|c| c == '-' || c.is_ascii_hexdigit());
+
+        valid.then(|| candidate.to_ascii_lowercase())
+    })
 }
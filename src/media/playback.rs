@@ -20,6 +20,23 @@ pub enum Samples {
 }
 
 impl Samples {
+    /// The format the samples were decoded in.
+    pub fn format(&self) -> SampleFormat {
+        match self {
+            Samples::Float64(_) => SampleFormat::Float64,
+            Samples::Float32(_) => SampleFormat::Float32,
+            Samples::Signed32(_) => SampleFormat::Signed32,
+            Samples::Unsigned32(_) => SampleFormat::Unsigned32,
+            Samples::Signed24(_) => SampleFormat::Signed24,
+            Samples::Unsigned24(_) => SampleFormat::Unsigned24,
+            Samples::Signed16(_) => SampleFormat::Signed16,
+            Samples::Unsigned16(_) => SampleFormat::Unsigned16,
+            Samples::Signed8(_) => SampleFormat::Signed8,
+            Samples::Unsigned8(_) => SampleFormat::Unsigned8,
+            Samples::Dsd(_) => SampleFormat::Dsd,
+        }
+    }
+
     pub fn is_format(&self, format: SampleFormat) -> bool {
         match self {
             Samples::Float64(_) => format == SampleFormat::Float64,
@@ -0,0 +1,186 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use super::{builtin::symphonia::SymphoniaProvider, metadata::StreamInfo, traits::MediaProvider};
+
+/// Technical information about a file on disk.
+#[derive(Debug, Clone)]
+pub struct FileProperties {
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    pub stream: StreamInfo,
+    /// The average bitrate of the file in kilobits per second, including any tags and artwork.
+    pub bitrate: Option<u32>,
+    /// The tag formats present in the file, such as `ID3v2.4` or `APEv2`.
+    pub tag_formats: Vec<String>,
+    /// Tags that aren't otherwise read, by name.
+    pub custom_tags: Vec<(String, String)>,
+}
+
+/// Reads the technical information for the file at the given path.
+pub fn read_file_properties(path: &Path) -> anyhow::Result<FileProperties> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let tag_formats = detect_tag_formats(&mut file, size)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    // TODO: Switch to a different media provider based on the file
+    let mut provider = SymphoniaProvider::default();
+    provider.open(file, path.extension())?;
+    let stream = provider.stream_info()?;
+    let custom_tags = provider.read_metadata()?.custom.clone();
+    provider.close()?;
+
+    let bitrate = stream
+        .duration
+        .filter(|duration| *duration > 0.0)
+        .map(|duration| (size as f64 * 8.0 / duration / 1000.0).round() as u32);
+
+    Ok(FileProperties {
+        path: path.to_path_buf(),
+        size,
+        stream,
+        bitrate,
+        tag_formats,
+        custom_tags,
+    })
+}
+
+fn read_at<const N: usize>(file: &mut File, offset: u64) -> std::io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Finds the tag formats in the file by looking at the places they are stored, without parsing
+/// the tags themselves.
+fn detect_tag_formats(file: &mut File, size: u64) -> std::io::Result<Vec<String>> {
+    let mut formats = Vec::new();
+    let mut start = 0;
+
+    if size >= 10 {
+        let header: [u8; 10] = read_at(file, 0)?;
+
+        if &header[..3] == b"ID3" {
+            formats.push(format!("ID3v2.{}", header[3]));
+
+            // the tag size is stored as a 28-bit "syncsafe" integer
+            let tag_size = header[6..10]
+                .iter()
+                .fold(0_u64, |acc, byte| (acc << 7) | (*byte as u64 & 0x7f));
+            start = tag_size + 10;
+        }
+    }
+
+    if start + 8 <= size {
+        let magic: [u8; 8] = read_at(file, start)?;
+
+        if &magic[..4] == b"fLaC" && flac_has_vorbis_comment(file, start + 4, size)? {
+            formats.push("Vorbis comments".to_string());
+        } else if &magic[..4] == b"OggS" {
+            // every ogg vorbis and opus stream is required to have a comment header
+            formats.push("Vorbis comments".to_string());
+        } else if &magic[4..] == b"ftyp" && mp4_has_item_list(file, size)? {
+            formats.push("MP4 metadata".to_string());
+        }
+    }
+
+    let mut end = size;
+
+    if size >= 128 {
+        let tag: [u8; 3] = read_at(file, size - 128)?;
+
+        if &tag == b"TAG" {
+            formats.push("ID3v1".to_string());
+            end -= 128;
+        }
+    }
+
+    if end >= 32 {
+        let footer: [u8; 12] = read_at(file, end - 32)?;
+
+        if &footer[..8] == b"APETAGEX" {
+            let version = u32::from_le_bytes(footer[8..12].try_into().unwrap());
+            formats.push(format!("APEv{}", version / 1000));
+        }
+    }
+
+    Ok(formats)
+}
+
+fn flac_has_vorbis_comment(file: &mut File, mut offset: u64, size: u64) -> std::io::Result<bool> {
+    const VORBIS_COMMENT: u8 = 4;
+
+    while offset + 4 <= size {
+        let header: [u8; 4] = read_at(file, offset)?;
+        let is_last = header[0] & 0x80 != 0;
+
+        if header[0] & 0x7f == VORBIS_COMMENT {
+            return Ok(true);
+        }
+
+        if is_last {
+            break;
+        }
+
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as u64;
+        offset += 4 + length;
+    }
+
+    Ok(false)
+}
+
+/// Returns the bounds of the contents of the first atom with the given name between start and end.
+fn find_atom(
+    file: &mut File,
+    mut start: u64,
+    end: u64,
+    name: &[u8; 4],
+) -> std::io::Result<Option<(u64, u64)>> {
+    while start + 8 <= end {
+        let header: [u8; 8] = read_at(file, start)?;
+        let mut atom_size = u32::from_be_bytes(header[..4].try_into().unwrap()) as u64;
+        let mut header_size = 8;
+
+        // a size of 1 means the real size follows the header as a 64-bit integer, which is
+        // common for large mdat atoms
+        if atom_size == 1 && start + 16 <= end {
+            atom_size = u64::from_be_bytes(read_at(file, start + 8)?);
+            header_size = 16;
+        }
+
+        // a size of 0 means the atom runs to the end of the file, anything else this small is
+        // corrupt
+        if atom_size < header_size {
+            break;
+        }
+
+        if &header[4..] == name {
+            return Ok(Some((start + header_size, (start + atom_size).min(end))));
+        }
+
+        start += atom_size;
+    }
+
+    Ok(None)
+}
+
+fn mp4_has_item_list(file: &mut File, size: u64) -> std::io::Result<bool> {
+    let Some((start, end)) = find_atom(file, 0, size, b"moov")? else {
+        return Ok(false);
+    };
+    let Some((start, end)) = find_atom(file, start, end, b"udta")? else {
+        return Ok(false);
+    };
+    let Some((start, end)) = find_atom(file, start, end, b"meta")? else {
+        return Ok(false);
+    };
+
+    // the meta atom has a 4 byte version and flags field before its children
+    Ok(find_atom(file, start + 4, end, b"ilst")?.is_some())
+}
@@ -0,0 +1,140 @@
+mod flac;
+
+use std::path::Path;
+
+use id3::{
+    TagLike, Version,
+    frame::{ExtendedText, Picture, PictureType},
+};
+
+use super::errors::TagWriteError;
+
+/// An image to embed in a file as its front cover.
+pub struct CoverArt {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A text field that can be written to a file's tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagField {
+    Title,
+    Artist,
+    Album,
+    Genre,
+    AlbumArtist,
+    Year,
+    TrackNumber,
+    DiscNumber,
+    /// The ReplayGain track gain, written like `-6.50 dB`.
+    ReplayGainTrackGain,
+    /// The ReplayGain track peak, where 1.0 is full scale.
+    ReplayGainTrackPeak,
+}
+
+impl TagField {
+    fn id3_frame(self) -> &'static str {
+        match self {
+            TagField::Title => "TIT2",
+            TagField::Artist => "TPE1",
+            TagField::Album => "TALB",
+            TagField::Genre => "TCON",
+            TagField::AlbumArtist => "TPE2",
+            TagField::Year => "TDRC",
+            TagField::TrackNumber => "TRCK",
+            TagField::DiscNumber => "TPOS",
+            TagField::ReplayGainTrackGain | TagField::ReplayGainTrackPeak => "TXXX",
+        }
+    }
+
+    /// The description of the user-defined text frame the field is stored in, for fields that
+    /// don't have a frame of their own in ID3.
+    fn id3_description(self) -> Option<&'static str> {
+        match self {
+            TagField::ReplayGainTrackGain => Some("REPLAYGAIN_TRACK_GAIN"),
+            TagField::ReplayGainTrackPeak => Some("REPLAYGAIN_TRACK_PEAK"),
+            _ => None,
+        }
+    }
+
+    fn vorbis_key(self) -> &'static str {
+        match self {
+            TagField::Title => "TITLE",
+            TagField::Artist => "ARTIST",
+            TagField::Album => "ALBUM",
+            TagField::Genre => "GENRE",
+            TagField::AlbumArtist => "ALBUMARTIST",
+            TagField::Year => "DATE",
+            TagField::TrackNumber => "TRACKNUMBER",
+            TagField::DiscNumber => "DISCNUMBER",
+            TagField::ReplayGainTrackGain => "REPLAYGAIN_TRACK_GAIN",
+            TagField::ReplayGainTrackPeak => "REPLAYGAIN_TRACK_PEAK",
+        }
+    }
+}
+
+/// Changes to make to a file's tags. Anything left as `None` is not changed.
+#[derive(Default)]
+pub struct TagChanges {
+    /// Replaces the file's front cover.
+    pub front_cover: Option<CoverArt>,
+    /// Replaces the values of text fields. An empty value removes the field.
+    pub text: Vec<(TagField, String)>,
+}
+
+/// Writes the changes to the file's tags. Only MP3 (ID3v2) and FLAC files are supported.
+pub fn write_tags(path: &Path, changes: &TagChanges) -> Result<(), TagWriteError> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("mp3") => write_id3(path, changes),
+        Some("flac") => flac::write_tags(path, changes),
+        _ => Err(TagWriteError::UnsupportedFormat),
+    }
+}
+
+fn write_id3(path: &Path, changes: &TagChanges) -> Result<(), TagWriteError> {
+    let mut tag = match id3::Tag::read_from_path(path) {
+        Ok(tag) => tag,
+        Err(id3::Error {
+            kind: id3::ErrorKind::NoTag,
+            ..
+        }) => id3::Tag::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    if let Some(cover) = &changes.front_cover {
+        tag.remove_picture_by_type(PictureType::CoverFront);
+        tag.add_frame(Picture {
+            mime_type: cover.mime_type.clone(),
+            picture_type: PictureType::CoverFront,
+            description: String::new(),
+            data: cover.data.clone(),
+        });
+    }
+
+    for (field, value) in &changes.text {
+        if let Some(description) = field.id3_description() {
+            tag.remove_extended_text(Some(description), None);
+            if !value.is_empty() {
+                tag.add_frame(ExtendedText {
+                    description: description.to_string(),
+                    value: value.clone(),
+                });
+            }
+        } else if value.is_empty() {
+            tag.remove(field.id3_frame());
+        } else {
+            tag.set_text(field.id3_frame(), value.clone());
+        }
+    }
+
+    tag.write_to_path(path, Version::Id3v24)?;
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::media::errors::TagWriteError;
+
+use super::{CoverArt, TagChanges};
+
+const FLAC_MAGIC: &[u8; 4] = b"fLaC";
+
+const BLOCK_PADDING: u8 = 1;
+const BLOCK_VORBIS_COMMENT: u8 = 4;
+const BLOCK_PICTURE: u8 = 6;
+
+/// The vendor string used when a file has no comment block to take one from.
+const VENDOR: &str = "Hummingbird";
+
+/// The picture type used for front covers, shared with ID3.
+const PICTURE_FRONT_COVER: u32 = 3;
+
+/// The amount of padding left after the metadata, so that later edits have room to grow.
+const PADDING_SIZE: usize = 4096;
+
+/// The largest block that can be written, as block lengths are stored in 24 bits.
+const MAX_BLOCK_SIZE: usize = (1 << 24) - 1;
+
+struct Block {
+    kind: u8,
+    data: Vec<u8>,
+}
+
+fn read_blocks(reader: &mut impl Read) -> Result<Vec<Block>, TagWriteError> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != FLAC_MAGIC {
+        return Err(TagWriteError::FileCorrupt);
+    }
+
+    let mut blocks = Vec::new();
+
+    loop {
+        let mut header = [0; 4];
+        reader.read_exact(&mut header)?;
+
+        let is_last = header[0] & 0x80 != 0;
+        let kind = header[0] & 0x7f;
+        let length = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+
+        let mut data = vec![0; length];
+        reader.read_exact(&mut data)?;
+        blocks.push(Block { kind, data });
+
+        if is_last {
+            return Ok(blocks);
+        }
+    }
+}
+
+/// The vendor string and the `KEY=value` comments of a VORBIS_COMMENT block. Unlike the rest of
+/// FLAC, the lengths in this block are little-endian.
+struct VorbisComments {
+    vendor: Vec<u8>,
+    comments: Vec<Vec<u8>>,
+}
+
+fn read_u32_le(data: &[u8], pos: &mut usize) -> Result<u32, TagWriteError> {
+    let bytes = data.get(*pos..*pos + 4).ok_or(TagWriteError::FileCorrupt)?;
+    *pos += 4;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_bytes(data: &[u8], pos: &mut usize) -> Result<Vec<u8>, TagWriteError> {
+    let length = read_u32_le(data, pos)? as usize;
+    let bytes = data
+        .get(*pos..*pos + length)
+        .ok_or(TagWriteError::FileCorrupt)?;
+    *pos += length;
+    Ok(bytes.to_vec())
+}
+
+impl VorbisComments {
+    fn parse(data: &[u8]) -> Result<Self, TagWriteError> {
+        let mut pos = 0;
+        let vendor = read_bytes(data, &mut pos)?;
+        let count = read_u32_le(data, &mut pos)?;
+        let comments = (0..count)
+            .map(|_| read_bytes(data, &mut pos))
+            .collect::<Result<_, _>>()?;
+
+        Ok(VorbisComments { vendor, comments })
+    }
+
+    /// Removes every comment with the key, which is compared case-insensitively, and then adds
+    /// the new value unless it is empty.
+    fn set(&mut self, key: &str, value: &str) {
+        self.comments.retain(|comment| {
+            let name = comment.split(|b| *b == b'=').next().unwrap_or_default();
+            !name.eq_ignore_ascii_case(key.as_bytes())
+        });
+
+        if !value.is_empty() {
+            self.comments.push(format!("{key}={value}").into_bytes());
+        }
+    }
+
+    fn to_block(&self) -> Block {
+        let mut data = Vec::new();
+
+        data.extend_from_slice(&(self.vendor.len() as u32).to_le_bytes());
+        data.extend_from_slice(&self.vendor);
+        data.extend_from_slice(&(self.comments.len() as u32).to_le_bytes());
+        for comment in &self.comments {
+            data.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            data.extend_from_slice(comment);
+        }
+
+        Block {
+            kind: BLOCK_VORBIS_COMMENT,
+            data,
+        }
+    }
+}
+
+fn picture_type(block: &Block) -> Option<u32> {
+    let bytes = block.data.get(0..4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn picture_block(cover: &CoverArt) -> Block {
+    let mut data = Vec::with_capacity(cover.data.len() + 64);
+
+    data.extend_from_slice(&PICTURE_FRONT_COVER.to_be_bytes());
+    data.extend_from_slice(&(cover.mime_type.len() as u32).to_be_bytes());
+    data.extend_from_slice(cover.mime_type.as_bytes());
+    // no description
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&cover.width.to_be_bytes());
+    data.extend_from_slice(&cover.height.to_be_bytes());
+    // color depth, and the number of colors for indexed images
+    data.extend_from_slice(&24u32.to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&(cover.data.len() as u32).to_be_bytes());
+    data.extend_from_slice(&cover.data);
+
+    Block {
+        kind: BLOCK_PICTURE,
+        data,
+    }
+}
+
+/// Rewrites the metadata blocks at the start of a FLAC file. The audio is copied over unchanged
+/// into a new file, which then replaces the original, so that a failure part way through can't
+/// leave a broken file behind.
+pub fn write_tags(path: &Path, changes: &TagChanges) -> Result<(), TagWriteError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut blocks = read_blocks(&mut reader)?;
+
+    if !changes.text.is_empty() {
+        let existing = blocks
+            .iter()
+            .position(|block| block.kind == BLOCK_VORBIS_COMMENT);
+
+        let mut comments = match existing {
+            Some(idx) => VorbisComments::parse(&blocks.remove(idx).data)?,
+            None => VorbisComments {
+                vendor: VENDOR.as_bytes().to_vec(),
+                comments: Vec::new(),
+            },
+        };
+
+        for (field, value) in &changes.text {
+            comments.set(field.vorbis_key(), value);
+        }
+
+        // STREAMINFO always has to come first
+        blocks.insert(existing.unwrap_or(1).min(blocks.len()), comments.to_block());
+    }
+
+    if let Some(cover) = &changes.front_cover {
+        blocks.retain(|block| {
+            block.kind != BLOCK_PICTURE || picture_type(block) != Some(PICTURE_FRONT_COVER)
+        });
+        blocks.push(picture_block(cover));
+    }
+
+    blocks.retain(|block| block.kind != BLOCK_PADDING);
+    blocks.push(Block {
+        kind: BLOCK_PADDING,
+        data: vec![0; PADDING_SIZE],
+    });
+
+    if blocks.iter().any(|block| block.data.len() > MAX_BLOCK_SIZE) {
+        return Err(TagWriteError::TooLarge);
+    }
+
+    let file_name = path.file_name().ok_or(TagWriteError::FileCorrupt)?;
+    let tmp_path = path.with_file_name(format!("{}.tmp", file_name.to_string_lossy()));
+
+    let result = (|| -> Result<(), TagWriteError> {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_all(FLAC_MAGIC)?;
+
+        let last = blocks.len() - 1;
+        for (idx, block) in blocks.iter().enumerate() {
+            let length = (block.data.len() as u32).to_be_bytes();
+            let flag = if idx == last { 0x80 } else { 0 };
+
+            writer.write_all(&[block.kind | flag, length[1], length[2], length[3]])?;
+            writer.write_all(&block.data)?;
+        }
+
+        io::copy(&mut reader, &mut writer)?;
+        writer.flush()?;
+        drop(writer);
+
+        fs::set_permissions(&tmp_path, fs::metadata(path)?.permissions())?;
+        fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+
+    result
+}
@@ -1,4 +1,8 @@
-use std::{ffi::OsStr, fs::File};
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{Read, Seek},
+};
 
 use crate::devices::format::ChannelSpec;
 
@@ -7,7 +11,7 @@ use super::{
         ChannelRetrievalError, CloseError, FrameDurationError, MetadataError, OpenError,
         PlaybackReadError, PlaybackStartError, PlaybackStopError, SeekError, TrackDurationError,
     },
-    metadata::Metadata,
+    metadata::{Chapter, EmbeddedImage, ImageKind, Metadata, StreamInfo},
     playback::PlaybackFrame,
 };
 
@@ -40,6 +44,13 @@ pub trait MediaPlugin: MediaProvider {
     const INDEXING_SUPPORTED: bool;
 }
 
+/// A file that's read while it's still arriving, such as a track in a remote folder that's
+/// played while it downloads. Reads past what has arrived wait for it to arrive.
+pub trait MediaStream: Read + Seek + Send + Sync {
+    /// The size of the whole file in bytes, if it's known yet.
+    fn byte_len(&self) -> Option<u64>;
+}
+
 /// The MediaProvider trait defines the methods used to interact with a media provider. A media
 /// provider is responsible for opening, closing, and reading samples and metadata from a media
 /// file, but not all Providers are required to support all (or, technically, any) of these
@@ -59,6 +70,17 @@ pub trait MediaProvider {
     /// Provider attempts to determine the extension based off of the file's contents.
     fn open(&mut self, file: File, ext: Option<&OsStr>) -> Result<(), OpenError>;
 
+    /// Requests the Provider open the specified stream, which works like [MediaProvider::open]
+    /// except that reading from the stream may block until more of it arrives. Providers that
+    /// can only read whole files don't need to implement this.
+    fn open_stream(
+        &mut self,
+        _stream: Box<dyn MediaStream>,
+        _ext: Option<&OsStr>,
+    ) -> Result<(), OpenError> {
+        Err(OpenError::UnsupportedFormat)
+    }
+
     /// Informs the Provider that the currently opened file is no longer needed. This function is
     /// not guaranteed to be called before open if a file is already opened.
     fn close(&mut self) -> Result<(), CloseError>;
@@ -86,6 +108,11 @@ pub trait MediaProvider {
     /// does not support metadata retrieval, this function should return an error.
     fn read_metadata(&mut self) -> Result<&Metadata, MetadataError>;
 
+    /// Returns technical information about the audio stream of the currently opened file. This
+    /// function should be available immediately after the file is opened. If no file is opened,
+    /// this function should return an error.
+    fn stream_info(&self) -> Result<StreamInfo, MetadataError>;
+
     /// Returns whether or not there has been a metadata update since the last call to
     /// read_metadata.
     fn metadata_updated(&self) -> bool;
@@ -95,6 +122,20 @@ pub trait MediaProvider {
     /// error.
     fn read_image(&mut self) -> Result<Option<Box<[u8]>>, MetadataError>;
 
+    /// Retrieves every image in the track's metadata, such as the front and back covers and
+    /// pictures of the artist, in the order they're stored. Providers that can only read a
+    /// single image don't need to implement this.
+    fn read_images(&mut self) -> Result<Vec<EmbeddedImage>, MetadataError> {
+        Ok(self
+            .read_image()?
+            .map(|data| EmbeddedImage {
+                kind: ImageKind::Other,
+                data,
+            })
+            .into_iter()
+            .collect())
+    }
+
     /// Returns the duration of the currently opened file in seconds. If no file is opened, or
     /// playback has not started, this function should return an error. This function should be
     /// available immediately after playback has started, and should not require reading any
@@ -106,6 +147,22 @@ pub trait MediaProvider {
     /// immediately after playback has started, and should not require reading any samples.
     fn position_secs(&self) -> Result<u64, TrackDurationError>;
 
+    /// Returns the current playback position in seconds, including the fraction of the current
+    /// second. The same rules as [MediaProvider::position_secs] apply.
+    fn position(&self) -> Result<f64, TrackDurationError>;
+
+    /// Returns the chapters of the currently opened file, as marked by the file's cue points, in
+    /// the order they start. If no file is opened, this function should return an error.
+    fn chapters(&self) -> Result<Vec<Chapter>, MetadataError>;
+
+    /// Returns whether the most recent metadata update came from within the stream (for example,
+    /// a chained Ogg stream moving on to its next song), rather than from the start of the file.
+    /// The playback thread treats such updates as the start of a new track. Providers that don't
+    /// read metadata from within the stream don't need to implement this.
+    fn metadata_starts_segment(&self) -> bool {
+        false
+    }
+
     /// Returns the chnanel specification used by the track being decoded. This function should be
     /// available immediately after playback has started, and should not require reading any
     /// samples.
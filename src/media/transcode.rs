@@ -0,0 +1,200 @@
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use tracing::debug;
+
+use crate::settings::transcode::{TranscodeFormat, TranscodeProfile, TranscodeSettings};
+
+use super::{errors::TranscodeError, metadata::TrackFile};
+
+impl TranscodeFormat {
+    /// The extension given to converted files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            TranscodeFormat::Opus => "opus",
+            TranscodeFormat::Vorbis => "ogg",
+            TranscodeFormat::Mp3 => "mp3",
+            TranscodeFormat::Aac => "m4a",
+            TranscodeFormat::Flac => "flac",
+        }
+    }
+
+    /// Whether the file already looks like it's in this format, judging by its extension.
+    /// Converting these files again would only lose quality, so they're copied instead.
+    pub fn matches(self, path: &Path) -> bool {
+        let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+            return false;
+        };
+        let extension = extension.to_ascii_lowercase();
+
+        match self {
+            TranscodeFormat::Vorbis => extension == "ogg" || extension == "oga",
+            TranscodeFormat::Aac => extension == "m4a" || extension == "aac",
+            format => extension == format.extension(),
+        }
+    }
+
+    /// The ffmpeg encoder and muxer used for the format.
+    fn ffmpeg_names(self) -> (&'static str, &'static str) {
+        match self {
+            TranscodeFormat::Opus => ("libopus", "opus"),
+            TranscodeFormat::Vorbis => ("libvorbis", "ogg"),
+            TranscodeFormat::Mp3 => ("libmp3lame", "mp3"),
+            TranscodeFormat::Aac => ("aac", "ipod"),
+            TranscodeFormat::Flac => ("flac", "flac"),
+        }
+    }
+
+    /// Whether the container can hold the front cover as an attached picture.
+    fn keeps_cover(self) -> bool {
+        matches!(
+            self,
+            TranscodeFormat::Mp3 | TranscodeFormat::Aac | TranscodeFormat::Flac
+        )
+    }
+}
+
+/// Converts files between formats by running ffmpeg.
+pub struct Transcoder {
+    ffmpeg: PathBuf,
+}
+
+impl Transcoder {
+    /// Finds ffmpeg, either at the path in the settings or on the `PATH`.
+    pub fn find(settings: &TranscodeSettings) -> Result<Self, TranscodeError> {
+        if let Some(ffmpeg) = &settings.ffmpeg {
+            return if ffmpeg.is_file() {
+                Ok(Transcoder {
+                    ffmpeg: ffmpeg.clone(),
+                })
+            } else {
+                Err(TranscodeError::FfmpegNotFound)
+            };
+        }
+
+        let name = if cfg!(windows) {
+            "ffmpeg.exe"
+        } else {
+            "ffmpeg"
+        };
+
+        std::env::var_os("PATH")
+            .iter()
+            .flat_map(std::env::split_paths)
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .map(|ffmpeg| Transcoder { ffmpeg })
+            .ok_or(TranscodeError::FfmpegNotFound)
+    }
+
+    /// Converts the track with the profile, replacing anything already at the destination. Tags
+    /// are carried over, along with the front cover where the format allows it. Tracks that are
+    /// only part of a file are cut out of it.
+    pub fn transcode(
+        &self,
+        from: &TrackFile,
+        to: &Path,
+        profile: &TranscodeProfile,
+    ) -> Result<(), TranscodeError> {
+        let (encoder, muxer) = profile.format.ffmpeg_names();
+
+        let mut part = to.as_os_str().to_owned();
+        part.push(".part");
+
+        let mut args: Vec<OsString> = ["-nostdin", "-hide_banner", "-loglevel", "error", "-y"]
+            .into_iter()
+            .map(OsString::from)
+            .collect();
+        if let Some((start, end)) = from.range {
+            args.extend(["-ss".into(), start.to_string().into()]);
+            args.extend(["-to".into(), end.to_string().into()]);
+        }
+        args.extend(["-i".into(), from.path.as_os_str().to_owned()]);
+        args.extend(["-map", "0:a:0", "-map_metadata", "0"].map(OsString::from));
+
+        if profile.format.keeps_cover() {
+            args.extend(
+                [
+                    "-map",
+                    "0:v?",
+                    "-c:v",
+                    "copy",
+                    "-disposition:v",
+                    "attached_pic",
+                ]
+                .map(OsString::from),
+            );
+        }
+
+        args.extend(["-c:a", encoder].map(OsString::from));
+
+        if let Some(bitrate) = profile.bitrate
+            && profile.format != TranscodeFormat::Flac
+        {
+            args.extend(["-b:a".into(), format!("{bitrate}k").into()]);
+        }
+        if let Some(rate) = profile.sample_rate {
+            args.extend(["-ar".into(), rate.to_string().into()]);
+        }
+
+        // the muxer is given explicitly since ffmpeg can't tell the format from the .part name
+        args.extend(["-f".into(), muxer.into(), part.clone()]);
+
+        debug!("Running {:?} {:?}", self.ffmpeg, args);
+        let output = Command::new(&self.ffmpeg).args(&args).output()?;
+
+        if !output.status.success() {
+            let _ = fs::remove_file(&part);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines().last().unwrap_or_default().trim().to_string();
+
+            return Err(TranscodeError::Failed(if message.is_empty() {
+                output.status.to_string()
+            } else {
+                message
+            }));
+        }
+
+        fs::rename(part, to)?;
+
+        Ok(())
+    }
+
+    /// Converts the track into the directory, keeping its name but changing its extension. A
+    /// number is added to the name if a file with it already exists. Files that are already in
+    /// the profile's format are copied, unless the track is only part of the file. Returns where
+    /// the file was written.
+    pub fn convert_into(
+        &self,
+        from: &TrackFile,
+        dir: &Path,
+        profile: &TranscodeProfile,
+    ) -> Result<PathBuf, TranscodeError> {
+        let stem = from.stem();
+        let copy = from.range.is_none() && profile.format.matches(&from.path);
+        let extension = if copy {
+            from.path.extension().unwrap_or_default().to_string_lossy()
+        } else {
+            profile.format.extension().into()
+        };
+
+        let to = std::iter::once(dir.join(format!("{stem}.{extension}")))
+            .chain((2..).map(|n| dir.join(format!("{stem} ({n}).{extension}"))))
+            .find(|path| !path.exists())
+            .unwrap();
+
+        fs::create_dir_all(dir)?;
+
+        if copy {
+            fs::copy(&from.path, &to)?;
+        } else {
+            self.transcode(from, &to, profile)?;
+        }
+
+        Ok(to)
+    }
+}
@@ -1,4 +1,5 @@
 pub mod events;
 pub mod interface;
 pub mod queue;
+pub mod renderer;
 pub mod thread;
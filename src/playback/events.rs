@@ -1,8 +1,14 @@
 #![allow(dead_code)]
 
-use crate::media::metadata::Metadata;
+use rustc_hash::FxHashMap;
 
-use super::{queue::QueueItemData, thread::PlaybackState};
+use crate::{
+    devices::format::SampleFormat,
+    media::metadata::{Metadata, StreamInfo},
+    settings::playback::PlaybackSettings,
+};
+
+use super::{queue::QueueItemData, renderer::RendererHandle, thread::PlaybackState};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq, Copy)]
@@ -39,6 +45,10 @@ pub enum PlaybackCommand {
     Previous,
     /// Requests that the playback thread clear the queue.
     ClearQueue,
+    /// Requests that the playback thread clear the queue, except for the current file.
+    ClearQueueExceptCurrent,
+    /// Requests that the playback thread remove the files before the current file from the queue.
+    RemovePlayed,
     /// Jumps to the specified position in the queue.
     Jump(usize),
     /// Jumps to the specified position in the queue. This will use the position of the track
@@ -58,6 +68,25 @@ pub enum PlaybackCommand {
     ToggleShuffle,
     /// Requests that the repeating setting should be set to the specified RepeatState.
     SetRepeat(RepeatState),
+    /// Replaces the playback settings, after they have been changed by the user.
+    UpdateSettings(PlaybackSettings),
+    /// Requests that audio be sent to the given network renderer, or back to the local device if
+    /// `None` is given.
+    SetRenderer(Option<RendererHandle>),
+    /// Requests that the playback thread list the output devices available to it.
+    ListOutputDevices,
+    /// Requests that audio be played on the output device with the given UID, or the system's
+    /// default device if `None` is given.
+    SetOutputDevice(Option<String>),
+    /// Replaces the volume remembered for each output, keyed by the output device's UID (or
+    /// `default`, or `renderer:` followed by a network device's name).
+    SetOutputVolumes(FxHashMap<String, f64>),
+    /// Changes how much louder or quieter the current track is played, in decibels, after its
+    /// volume offset has been changed in the library.
+    SetVolumeOffset(f64),
+    /// Requests that playback be turned down while another application plays audio (true), or
+    /// turned back up (false).
+    SetDucked(bool),
 }
 
 /// An event from the playback thread. This is used to communicate information from the playback
@@ -72,6 +101,13 @@ pub enum PlaybackEvent {
     /// Indicates that the duration of the current file has changed. The f64 is the new duration,
     /// in seconds.
     DurationChanged(u64),
+    /// Indicates that the chapters of the current file have changed. Each f64 is the start of a
+    /// chapter, in seconds.
+    ChaptersChanged(Vec<f64>),
+    /// Indicates that playback has moved into a new part of the current file that's treated as a
+    /// track of its own, such as the next titled chapter of a mix, or the next song of a chained
+    /// stream. The new part's metadata follows in a [PlaybackEvent::MetadataUpdate].
+    SegmentChanged(Segment),
     /// Indicates that the queue has been updated.
     QueueUpdated,
     /// Indicates that the position in the queue has changed. The usize is the new position.
@@ -91,4 +127,75 @@ pub enum PlaybackEvent {
     RepeatChanged(RepeatState),
     /// Indicates that the volume has changed. The f64 is the new volume, from 0.0 to 1.0.
     VolumeChanged(f64),
+    /// Indicates that the volume for an output has changed, so that it can be remembered. The
+    /// String is the output's key (see [PlaybackCommand::SetOutputVolumes]).
+    OutputVolumeChanged(String, f64),
+    /// Indicates that the technical information about the current stream has changed, either
+    /// because a new file was opened or because the bitrate of a VBR stream changed.
+    StreamInfoChanged(StreamInfo),
+    /// Indicates that the path the current track's audio takes to the output device has changed,
+    /// either because a new file was opened or because the volume was changed.
+    OutputPathChanged(OutputPath),
+    /// Indicates that audio is now being sent to the named network renderer, or to the local
+    /// device if `None`.
+    RendererChanged(Option<String>),
+    /// Lists the output devices available to the playback thread, in response to
+    /// [PlaybackCommand::ListOutputDevices].
+    OutputDevicesListed(Vec<OutputDevice>),
+    /// Indicates that audio is now played on the output device with the given UID, or the
+    /// default device if `None`.
+    OutputDeviceChanged(Option<String>),
+    /// Indicates that playback has stopped to wait for more of a track that's played while it
+    /// downloads (true), or has carried on (false).
+    Buffering(bool),
+}
+
+/// A local output device that audio can be played on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputDevice {
+    pub uid: String,
+    pub name: String,
+}
+
+/// How the current track's audio reaches the local output device: the format it was decoded in,
+/// and the format the device was opened with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputPath {
+    pub source_rate: u32,
+    pub source_format: SampleFormat,
+    pub source_channels: u16,
+    pub device_rate: u32,
+    pub device_format: SampleFormat,
+    pub device_channels: u16,
+    /// Whether the volume is turned down, in which case every sample is scaled.
+    pub volume_reduced: bool,
+}
+
+impl OutputPath {
+    pub fn resampled(&self) -> bool {
+        self.source_rate != self.device_rate
+    }
+
+    pub fn converted(&self) -> bool {
+        self.source_format != self.device_format
+    }
+
+    pub fn remixed(&self) -> bool {
+        self.source_channels != self.device_channels
+    }
+
+    /// Whether the decoded samples reach the device unchanged: no resampling, no conversion to
+    /// another sample format, no change in the number of channels, and no volume scaling.
+    pub fn is_bit_perfect(&self) -> bool {
+        !self.resampled() && !self.converted() && !self.remixed() && !self.volume_reduced
+    }
+}
+
+/// A part of the current file that's treated as a track of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Where the part starts in the file, in seconds.
+    pub start: u64,
+    /// How long the part is in seconds, if known. Songs in a chained stream have no known length.
+    pub duration: Option<u64>,
 }
@@ -1,21 +1,31 @@
 #![allow(dead_code)]
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use gpui::App;
+use gpui::{App, AsyncApp, SharedString};
+use rustc_hash::FxHashMap;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 use crate::{
     playback::events::RepeatState,
-    ui::models::{CurrentTrack, ImageEvent, MMBSEvent, Models, PlaybackInfo},
+    settings::{SettingsGlobal, playback::PlaybackSettings},
+    ui::models::{CurrentTrack, ImageEvent, Models, PlaybackInfo},
 };
 
 use super::{
     events::{PlaybackCommand, PlaybackEvent},
     queue::QueueItemData,
+    renderer::RendererHandle,
     thread::PlaybackState,
 };
 
+/// How often the playback position shown in the interface is updated while the computer is
+/// locked, if [PlaybackSettings::defer_position_when_locked] is turned on.
+const DEFERRED_POSITION_INTERVAL: Duration = Duration::from_secs(30);
+
 /// The playback interface struct that will be used to communicate between the playback thread and
 /// the main thread. This implementation takes advantage of the GPUI Global trait to allow any
 /// function (so long as it is running on the main thread) to send commands to the playback thread.
@@ -77,6 +87,16 @@ impl PlaybackInterface {
         self.cmd_tx.send(PlaybackCommand::ClearQueue).unwrap();
     }
 
+    pub fn clear_queue_except_current(&self) {
+        self.cmd_tx
+            .send(PlaybackCommand::ClearQueueExceptCurrent)
+            .unwrap();
+    }
+
+    pub fn remove_played(&self) {
+        self.cmd_tx.send(PlaybackCommand::RemovePlayed).unwrap();
+    }
+
     pub fn jump(&self, index: usize) {
         self.cmd_tx.send(PlaybackCommand::Jump(index)).unwrap();
     }
@@ -115,13 +135,57 @@ impl PlaybackInterface {
         self.cmd_tx.send(PlaybackCommand::SetRepeat(state)).unwrap();
     }
 
+    pub fn update_settings(&self, settings: PlaybackSettings) {
+        self.cmd_tx
+            .send(PlaybackCommand::UpdateSettings(settings))
+            .unwrap();
+    }
+
+    pub fn set_renderer(&self, renderer: Option<RendererHandle>) {
+        self.cmd_tx
+            .send(PlaybackCommand::SetRenderer(renderer))
+            .unwrap();
+    }
+
+    pub fn list_output_devices(&self) {
+        self.cmd_tx
+            .send(PlaybackCommand::ListOutputDevices)
+            .unwrap();
+    }
+
+    pub fn set_output_device(&self, uid: Option<String>) {
+        self.cmd_tx
+            .send(PlaybackCommand::SetOutputDevice(uid))
+            .unwrap();
+    }
+
+    pub fn set_output_volumes(&self, volumes: FxHashMap<String, f64>) {
+        self.cmd_tx
+            .send(PlaybackCommand::SetOutputVolumes(volumes))
+            .unwrap();
+    }
+
+    pub fn set_volume_offset(&self, offset: f64) {
+        self.cmd_tx
+            .send(PlaybackCommand::SetVolumeOffset(offset))
+            .unwrap();
+    }
+
+    pub fn set_ducked(&self, ducked: bool) {
+        self.cmd_tx
+            .send(PlaybackCommand::SetDucked(ducked))
+            .unwrap();
+    }
+
     pub fn get_sender(&self) -> UnboundedSender<PlaybackCommand> {
         self.cmd_tx.clone()
     }
 
-    /// Starts the broadcast loop that will read events from the playback thread and update data
-    /// models accordingly. This function should be called once, and will panic if called more than
-    /// once.
+    /// Starts the broadcast loop that will read events from the playback thread, update data models
+    /// accordingly, and then re-emit each event on the playback event bus (see [`PlaybackEvents`]).
+    /// This function should be called once, and will panic if called more than once.
+    ///
+    /// [`PlaybackEvents`]: crate::ui::models::PlaybackEvents
     pub fn start_broadcast(&mut self, app: &mut App) {
         // This function's sole responsibility is to read events from the playback thread and update
         // data models accordingly.
@@ -131,7 +195,9 @@ impl PlaybackInterface {
         let metadata_model = app.global::<Models>().metadata.clone();
         let albumart_model = app.global::<Models>().albumart.clone();
         let queue_model = app.global::<Models>().queue.clone();
-        let mmbs_model = app.global::<Models>().mmbs.clone();
+        let events_model = app.global::<Models>().playback_events.clone();
+        let session_locked = app.global::<Models>().session_locked.clone();
+        let settings = app.global::<SettingsGlobal>().model.clone();
 
         let playback_info = app.global::<PlaybackInfo>().clone();
 
@@ -140,156 +206,230 @@ impl PlaybackInterface {
         };
 
         app.spawn(async move |cx| {
-            loop {
-                while let Some(event) = events_rx.recv().await {
-                    match event {
-                        PlaybackEvent::MetadataUpdate(v) => {
-                            let metadata = Arc::new(*v.clone());
+            let set_position = |position: u64, cx: &mut AsyncApp| {
+                playback_info
+                    .position
+                    .update(cx, |m, cx| {
+                        *m = position;
+                        cx.notify()
+                    })
+                    .expect("failed to update position");
+            };
+            // the latest position that hasn't been shown yet because the computer is locked, and
+            // when the position was last shown
+            let mut deferred_position = None;
+            let mut position_shown_at = Instant::now();
+
+            while let Some(event) = events_rx.recv().await {
+                // the models are updated first, so that subscribers to the bus see the new state
+                let broadcast = event.clone();
+
+                // anything else happening shows the position that was held back, so that it's
+                // right when playback is paused or the track changes
+                if !matches!(event, PlaybackEvent::PositionChanged(_))
+                    && let Some(position) = deferred_position.take()
+                {
+                    set_position(position, cx);
+                    position_shown_at = Instant::now();
+                }
 
-                            metadata_model
-                                .update(cx, |m, cx| {
-                                    *m = *v;
+                match event {
+                    PlaybackEvent::MetadataUpdate(v) => {
+                        metadata_model
+                            .update(cx, |m, cx| {
+                                *m = *v;
+                                cx.notify()
+                            })
+                            .expect("failed to update metadata");
+                    }
+                    PlaybackEvent::AlbumArtUpdate(v) => {
+                        albumart_model
+                            .update(cx, |m, cx| {
+                                if let Some(v) = v {
+                                    cx.emit(ImageEvent(v))
+                                } else {
+                                    *m = None;
                                     cx.notify()
-                                })
-                                .expect("failed to update metadata");
+                                }
+                            })
+                            .expect("failed to update albumart");
+                    }
+                    PlaybackEvent::StateChanged(v) => {
+                        playback_info
+                            .playback_state
+                            .update(cx, |m, cx| {
+                                *m = v;
+                                cx.notify()
+                            })
+                            .expect("failed to update playback state");
 
-                            mmbs_model
-                                .update(cx, |_, cx| {
-                                    cx.emit(MMBSEvent::MetadataRecieved(metadata));
-                                })
-                                .expect("failed to broadcast MMBS event MetadataRecieved");
-                        }
-                        PlaybackEvent::AlbumArtUpdate(v) => {
-                            albumart_model
-                                .update(cx, |m, cx| {
-                                    if let Some(v) = v {
-                                        cx.emit(ImageEvent(v))
-                                    } else {
-                                        *m = None;
-                                        cx.notify()
-                                    }
-                                })
-                                .expect("failed to update albumart");
-                        }
-                        PlaybackEvent::StateChanged(v) => {
-                            playback_info
-                                .playback_state
-                                .update(cx, |m, cx| {
-                                    *m = v;
-                                    cx.notify()
-                                })
-                                .expect("failed to update playback state");
-
-                            if v == PlaybackState::Stopped {
-                                playback_info
-                                    .current_track
-                                    .update(cx, |m, cx| {
-                                        *m = None;
-                                        cx.notify()
-                                    })
-                                    .expect("failed to update current track");
-                            }
-
-                            mmbs_model
-                                .update(cx, |_, cx| {
-                                    cx.emit(MMBSEvent::StateChanged(v));
-                                })
-                                .expect("failed to broadcast MMBS event StateChanged");
-                        }
-                        PlaybackEvent::PositionChanged(v) => {
-                            playback_info
-                                .position
-                                .update(cx, |m, cx| {
-                                    *m = v;
-                                    cx.notify()
-                                })
-                                .expect("failed to update position");
-                            mmbs_model
-                                .update(cx, |_, cx| {
-                                    cx.emit(MMBSEvent::PositionChanged(v));
-                                })
-                                .expect("failed to broadcast MMBS event PositionChanged");
-                        }
-                        PlaybackEvent::DurationChanged(v) => {
-                            playback_info
-                                .duration
-                                .update(cx, |m, cx| {
-                                    *m = v;
-                                    cx.notify()
-                                })
-                                .expect("failed to update duration");
-                            mmbs_model
-                                .update(cx, |_, cx| {
-                                    cx.emit(MMBSEvent::DurationChanged(v));
-                                })
-                                .expect("failed to broadcast MMBS event DurationChanged");
-                        }
-                        PlaybackEvent::SongChanged(path) => {
+                        if v == PlaybackState::Stopped {
                             playback_info
                                 .current_track
                                 .update(cx, |m, cx| {
-                                    *m = Some(CurrentTrack::new(path.clone()));
+                                    *m = None;
                                     cx.notify()
                                 })
                                 .expect("failed to update current track");
-                            mmbs_model
-                                .update(cx, |_, cx| {
-                                    cx.emit(MMBSEvent::NewTrack(path));
-                                })
-                                .expect("failed to broadcast MMBS event NewTrack");
-                        }
-                        PlaybackEvent::QueueUpdated => {
-                            queue_model
-                                .update(cx, |_, cx| cx.notify())
-                                .expect("failed to update queue");
-                        }
-                        PlaybackEvent::ShuffleToggled(v, _) => {
+
                             playback_info
-                                .shuffling
+                                .stream_info
                                 .update(cx, |m, cx| {
-                                    *m = v;
+                                    *m = None;
                                     cx.notify()
                                 })
-                                .expect("failed to update shuffle state");
+                                .expect("failed to update stream info");
                         }
-                        PlaybackEvent::VolumeChanged(v) => {
+                    }
+                    PlaybackEvent::PositionChanged(v) => {
+                        let defer = settings
+                            .read_with(cx, |settings, _| {
+                                settings.playback.defer_position_when_locked
+                            })
+                            .unwrap_or_default()
+                            && session_locked
+                                .read_with(cx, |locked, _| *locked)
+                                .unwrap_or_default();
+
+                        if defer && position_shown_at.elapsed() < DEFERRED_POSITION_INTERVAL {
+                            deferred_position = Some(v);
+                        } else {
+                            deferred_position = None;
+                            set_position(v, cx);
+                            position_shown_at = Instant::now();
+                        }
+                    }
+                    PlaybackEvent::DurationChanged(v) => {
+                        playback_info
+                            .duration
+                            .update(cx, |m, cx| {
+                                *m = v;
+                                cx.notify()
+                            })
+                            .expect("failed to update duration");
+                    }
+                    PlaybackEvent::SongChanged(path) => {
+                        playback_info
+                            .current_track
+                            .update(cx, |m, cx| {
+                                *m = Some(CurrentTrack::new(path));
+                                cx.notify()
+                            })
+                            .expect("failed to update current track");
+                    }
+                    PlaybackEvent::QueueUpdated => {
+                        queue_model
+                            .update(cx, |_, cx| cx.notify())
+                            .expect("failed to update queue");
+                    }
+                    PlaybackEvent::ShuffleToggled(v, _) => {
+                        playback_info
+                            .shuffling
+                            .update(cx, |m, cx| {
+                                *m = v;
+                                cx.notify()
+                            })
+                            .expect("failed to update shuffle state");
+                    }
+                    PlaybackEvent::VolumeChanged(v) => {
+                        playback_info
+                            .volume
+                            .update(cx, |m, cx| {
+                                *m = v;
+                                cx.notify()
+                            })
+                            .expect("failed to update volume model");
+
+                        // Note: `prev_volume` should not be to small.
+                        // Its value needs to be visible in UI
+                        // while toggling volume `on` / `off` and even
+                        // an user used a slider to move volume to `0`
+                        if v > 0.05 {
                             playback_info
-                                .volume
+                                .prev_volume
                                 .update(cx, |m, cx| {
                                     *m = v;
                                     cx.notify()
                                 })
                                 .expect("failed to update volume model");
-
-                            // Note: `prev_volume` should not be to small.
-                            // Its value needs to be visible in UI
-                            // while toggling volume `on` / `off` and even
-                            // an user used a slider to move volume to `0`
-                            if v > 0.05 {
-                                playback_info
-                                    .prev_volume
-                                    .update(cx, |m, cx| {
-                                        *m = v;
-                                        cx.notify()
-                                    })
-                                    .expect("failed to update volume model");
-                            }
                         }
-                        PlaybackEvent::QueuePositionChanged(v) => queue_model
-                            .update(cx, |m, cx| {
-                                m.position = v;
-                                cx.notify();
-                            })
-                            .expect("failed to update queue position"),
-                        PlaybackEvent::RepeatChanged(v) => playback_info
-                            .repeating
-                            .update(cx, |m, cx| {
-                                *m = v;
-                                cx.notify();
-                            })
-                            .expect("failed to update repeat model"),
                     }
+                    PlaybackEvent::OutputVolumeChanged(output, v) => playback_info
+                        .output_volumes
+                        .update(cx, |m, _| {
+                            m.insert(output, v);
+                        })
+                        .expect("failed to update output volumes"),
+                    PlaybackEvent::QueuePositionChanged(v) => queue_model
+                        .update(cx, |m, cx| {
+                            m.position = v;
+                            cx.notify();
+                        })
+                        .expect("failed to update queue position"),
+                    PlaybackEvent::RepeatChanged(v) => playback_info
+                        .repeating
+                        .update(cx, |m, cx| {
+                            *m = v;
+                            cx.notify();
+                        })
+                        .expect("failed to update repeat model"),
+                    PlaybackEvent::ChaptersChanged(v) => playback_info
+                        .chapters
+                        .update(cx, |m, cx| {
+                            *m = v;
+                            cx.notify();
+                        })
+                        .expect("failed to update chapters"),
+                    // only media services need to know about segments; their metadata is sent
+                    // separately
+                    PlaybackEvent::SegmentChanged(_) => (),
+                    PlaybackEvent::StreamInfoChanged(v) => playback_info
+                        .stream_info
+                        .update(cx, |m, cx| {
+                            *m = Some(v);
+                            cx.notify();
+                        })
+                        .expect("failed to update stream info"),
+                    PlaybackEvent::OutputPathChanged(v) => playback_info
+                        .output_path
+                        .update(cx, |m, cx| {
+                            *m = Some(v);
+                            cx.notify();
+                        })
+                        .expect("failed to update output path"),
+                    PlaybackEvent::RendererChanged(v) => playback_info
+                        .renderer
+                        .update(cx, |m, cx| {
+                            *m = v.map(SharedString::from);
+                            cx.notify();
+                        })
+                        .expect("failed to update renderer"),
+                    PlaybackEvent::OutputDevicesListed(v) => playback_info
+                        .output_devices
+                        .update(cx, |m, cx| {
+                            *m = v;
+                            cx.notify();
+                        })
+                        .expect("failed to update output devices"),
+                    PlaybackEvent::OutputDeviceChanged(v) => playback_info
+                        .output_device
+                        .update(cx, |m, cx| {
+                            *m = v;
+                            cx.notify();
+                        })
+                        .expect("failed to update output device"),
+                    PlaybackEvent::Buffering(v) => playback_info
+                        .buffering
+                        .update(cx, |m, cx| {
+                            *m = v;
+                            cx.notify();
+                        })
+                        .expect("failed to update buffering"),
                 }
+
+                events_model
+                    .update(cx, |_, cx| cx.emit(broadcast))
+                    .expect("failed to broadcast playback event");
             }
         })
         .detach();
@@ -116,4 +116,14 @@ impl QueueItemData {
     pub fn get_path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// Returns the database ID of the track the queue item is from, if it is known.
+    pub fn get_db_id(&self) -> Option<i64> {
+        self.db_id
+    }
+
+    /// Returns the database ID of the album the queue item is from, if it is known.
+    pub fn get_db_album_id(&self) -> Option<i64> {
+        self.db_album_id
+    }
 }
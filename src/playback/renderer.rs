@@ -0,0 +1,85 @@
+pub mod server;
+
+use std::{
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// Information about a track, shown by the renderer while it plays.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RendererMedia {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// The duration of the track, in seconds.
+    pub duration: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererState {
+    /// Nothing is loaded.
+    Idle,
+    /// The track is loaded but hasn't started playing yet.
+    Buffering,
+    Playing,
+    Paused,
+    /// The track played to the end.
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RendererStatus {
+    pub state: RendererState,
+    /// The position in the current track, in seconds.
+    pub position: f64,
+}
+
+/// A device on the network that plays files itself, such as a Chromecast or a DLNA speaker.
+///
+/// While a renderer is attached, the playback thread still manages the queue and opens each track
+/// to read its metadata, but the audio is played by the renderer instead of the local output
+/// device. The thread polls the renderer for its status, so that changes made on the device (like
+/// pausing it from its own remote) are reflected in the UI.
+pub trait Renderer: Send {
+    /// The name of the device, as shown to the user.
+    fn name(&self) -> &str;
+    /// Starts playing the file from the given position, in seconds.
+    fn load(&mut self, path: &Path, media: &RendererMedia, position: f64) -> anyhow::Result<()>;
+    fn play(&mut self) -> anyhow::Result<()>;
+    fn pause(&mut self) -> anyhow::Result<()>;
+    fn seek(&mut self, position: f64) -> anyhow::Result<()>;
+    /// Sets the volume of the device, from 0.0 to 1.0.
+    fn set_volume(&mut self, volume: f64) -> anyhow::Result<()>;
+    /// Stops playback, and ends the session on the device.
+    fn stop(&mut self) -> anyhow::Result<()>;
+    fn status(&mut self) -> anyhow::Result<RendererStatus>;
+}
+
+/// A shared handle to a renderer, so that it can be sent to the playback thread.
+#[derive(Clone)]
+pub struct RendererHandle(Arc<Mutex<Box<dyn Renderer>>>);
+
+impl RendererHandle {
+    pub fn new(renderer: impl Renderer + 'static) -> Self {
+        RendererHandle(Arc::new(Mutex::new(Box::new(renderer))))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Box<dyn Renderer>> {
+        self.0.lock().expect("renderer lock poisoned")
+    }
+}
+
+impl fmt::Debug for RendererHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RendererHandle")
+            .field(&self.lock().name())
+            .finish()
+    }
+}
+
+impl PartialEq for RendererHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
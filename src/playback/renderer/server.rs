@@ -0,0 +1,316 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use tracing::{debug, warn};
+
+/// Only the most recently shared files can be fetched. Renderers only need the current track and
+/// sometimes the next one, so this is plenty.
+const SHARED_FILES: usize = 16;
+
+static SERVER: OnceLock<MediaServer> = OnceLock::new();
+
+/// A small HTTP server that lets renderers on the network fetch the files they've been asked to
+/// play. Each file is given a random URL when it's shared, and nothing else on disk can be read
+/// through the server.
+pub struct MediaServer {
+    port: u16,
+    files: Arc<Mutex<VecDeque<(String, PathBuf)>>>,
+}
+
+impl MediaServer {
+    /// Returns the server, starting it if it isn't running yet.
+    pub fn shared() -> io::Result<&'static MediaServer> {
+        if let Some(server) = SERVER.get() {
+            return Ok(server);
+        }
+
+        let server = MediaServer::start()?;
+        // if another thread got there first, this server's listener is simply left unused
+        let _ = SERVER.set(server);
+        Ok(SERVER.get().unwrap())
+    }
+
+    fn start() -> io::Result<MediaServer> {
+        let listener = TcpListener::bind(("0.0.0.0", 0))?;
+        let port = listener.local_addr()?.port();
+        let files: Arc<Mutex<VecDeque<(String, PathBuf)>>> = Arc::default();
+
+        let thread_files = files.clone();
+        std::thread::Builder::new()
+            .name("media_server".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let files = thread_files.clone();
+                    std::thread::spawn(move || {
+                        if let Err(err) = handle_connection(stream, &files) {
+                            debug!("Media server connection failed: {err}");
+                        }
+                    });
+                }
+            })?;
+
+        debug!("Media server listening on port {port}");
+
+        Ok(MediaServer { port, files })
+    }
+
+    /// Shares the file, and returns a URL that `peer` can fetch it from. The path has to be a
+    /// file on disk, so library locations that aren't (tracks split from a cue sheet, or files in
+    /// remote folders) have to be resolved to one first.
+    pub fn url_for(&self, path: &Path, peer: IpAddr) -> io::Result<String> {
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not a file that can be shared", path.display()),
+            ));
+        }
+
+        let local = SocketAddr::new(local_ip_for(peer)?, self.port);
+
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("bin")
+            .to_ascii_lowercase();
+        let token = format!("{:016x}.{extension}", rand::random::<u64>());
+
+        let mut files = self.files.lock().expect("media server lock poisoned");
+        files.push_back((token.clone(), path.to_path_buf()));
+        while files.len() > SHARED_FILES {
+            files.pop_front();
+        }
+
+        Ok(format!("http://{local}/media/{token}"))
+    }
+}
+
+/// Guesses the MIME type of an audio file from its extension.
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("mp3") => "audio/mpeg",
+        Some("flac") => "audio/flac",
+        Some("ogg" | "oga") => "audio/ogg",
+        Some("opus") => "audio/ogg; codecs=opus",
+        Some("m4a" | "mp4" | "aac") => "audio/mp4",
+        Some("wav") => "audio/wav",
+        Some("aif" | "aiff") => "audio/aiff",
+        Some("wv") => "audio/x-wavpack",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Parses a `Range: bytes=start-end` header into an inclusive byte range.
+fn parse_range(value: &str, length: u64) -> Option<(u64, u64)> {
+    let (start, end) = value.trim().strip_prefix("bytes=")?.split_once('-')?;
+    let last = length.checked_sub(1)?;
+
+    let range = match (start.trim(), end.trim()) {
+        ("", suffix) => (length.saturating_sub(suffix.parse().ok()?), last),
+        (start, "") => (start.parse().ok()?, last),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(last)),
+    };
+
+    (range.0 <= range.1).then_some(range)
+}
+
+/// The longest request or header line that's read. Anything past it is read as the next line.
+const MAX_LINE: u64 = 8 * 1024;
+
+/// The largest request body the servers will read, when they read bodies at all. SOAP actions
+/// are far smaller than this.
+pub const MAX_BODY: usize = 64 * 1024;
+
+/// An HTTP request, with the headers that the servers care about.
+pub struct Request {
+    pub method: String,
+    pub target: String,
+    pub range: Option<String>,
+    pub body: Vec<u8>,
+    /// Whether the body was longer than the limit given to [read_request], in which case it
+    /// wasn't read, and the connection can't be used for anything else.
+    pub too_large: bool,
+}
+
+/// Reads the next request from the connection. Returns `None` once the client closes it.
+///
+/// Bodies up to `max_body` bytes are read into the request. Without a limit, the body is read and
+/// thrown away instead, for servers that don't need it.
+pub fn read_request(
+    reader: &mut impl BufRead,
+    max_body: Option<usize>,
+) -> io::Result<Option<Request>> {
+    let mut request_line = String::new();
+    if reader.take(MAX_LINE).read_line(&mut request_line)? == 0 {
+        return Ok(None);
+    }
+
+    let mut range = None;
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.take(MAX_LINE).read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        if name.trim().eq_ignore_ascii_case("range") {
+            range = Some(value.trim().to_string());
+        } else if name.trim().eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut body = Vec::new();
+    let mut too_large = false;
+    match max_body {
+        Some(max_body) if length > max_body => too_large = true,
+        Some(_) => {
+            body = vec![0; length];
+            reader.read_exact(&mut body)?;
+        }
+        None => {
+            io::copy(&mut reader.take(length as u64), &mut io::sink())?;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+
+    Ok(Some(Request {
+        method: parts.next().unwrap_or_default().to_string(),
+        target: parts.next().unwrap_or_default().to_string(),
+        range,
+        body,
+        too_large,
+    }))
+}
+
+/// Returns the address of this machine on the interface that can reach `peer`.
+pub fn local_ip_for(peer: IpAddr) -> io::Result<IpAddr> {
+    // connecting a UDP socket doesn't send anything, but picks the interface that can reach the
+    // peer
+    let socket = UdpSocket::bind(SocketAddr::new(
+        if peer.is_ipv4() {
+            IpAddr::from([0, 0, 0, 0])
+        } else {
+            IpAddr::from([0u16; 8])
+        },
+        0,
+    ))?;
+    socket.connect((peer, 9))?;
+
+    Ok(socket.local_addr()?.ip())
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    files: &Mutex<VecDeque<(String, PathBuf)>>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    // renderers usually keep the connection open for more requests, especially for seeking
+    while let Some(request) = read_request(&mut reader, None)? {
+        let path = request.target.strip_prefix("/media/").and_then(|token| {
+            let files = files.lock().expect("media server lock poisoned");
+            files
+                .iter()
+                .find(|(shared, _)| shared == token)
+                .map(|(_, path)| path.clone())
+        });
+
+        match path {
+            Some(path) => serve_file(&mut writer, &request, &path)?,
+            None => respond(&mut writer, "404 Not Found", &[])?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Responds to a GET or HEAD request with the contents of the file, or the part of it that was
+/// asked for.
+pub fn serve_file(writer: &mut TcpStream, request: &Request, path: &Path) -> io::Result<()> {
+    if request.method != "GET" && request.method != "HEAD" {
+        return respond(writer, "405 Method Not Allowed", &[]);
+    }
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => {
+            warn!("Media server could not open {:?}: {err}", path);
+            return respond(writer, "404 Not Found", &[]);
+        }
+    };
+    let length = file.metadata()?.len();
+
+    let (status, start, end) = match &request.range {
+        Some(range) => match parse_range(range, length) {
+            Some((start, end)) => ("206 Partial Content", start, end),
+            None => {
+                return respond(
+                    writer,
+                    "416 Range Not Satisfiable",
+                    &[("Content-Range", format!("bytes */{length}"))],
+                );
+            }
+        },
+        None => ("200 OK", 0, length.saturating_sub(1)),
+    };
+    let body_length = if length == 0 { 0 } else { end - start + 1 };
+
+    let mut headers = vec![
+        ("Content-Type", content_type(path).to_string()),
+        ("Content-Length", body_length.to_string()),
+        ("Accept-Ranges", "bytes".to_string()),
+        // asked for by DLNA renderers, and ignored by everything else
+        ("transferMode.dlna.org", "Streaming".to_string()),
+    ];
+    if status.starts_with("206") {
+        headers.push(("Content-Range", format!("bytes {start}-{end}/{length}")));
+    }
+
+    write_head(writer, status, &headers)?;
+
+    if request.method == "GET" {
+        file.seek(SeekFrom::Start(start))?;
+        io::copy(&mut file.take(body_length), writer)?;
+    }
+
+    writer.flush()
+}
+
+pub fn write_head(
+    writer: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, String)],
+) -> io::Result<()> {
+    let mut head = format!("HTTP/1.1 {status}\r\nServer: Hummingbird\r\n");
+    for (name, value) in headers {
+        head += &format!("{name}: {value}\r\n");
+    }
+    head += "\r\n";
+
+    writer.write_all(head.as_bytes())
+}
+
+/// Sends a response without a body.
+pub fn respond(writer: &mut TcpStream, status: &str, headers: &[(&str, String)]) -> io::Result<()> {
+    let mut headers = headers.to_vec();
+    headers.push(("Content-Length", "0".to_string()));
+    write_head(writer, status, &headers)
+}
@@ -1,12 +1,15 @@
 use std::{
     env::consts::OS,
-    mem::swap,
-    path::PathBuf,
+    mem::take,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
     thread::sleep,
+    time::{Duration, Instant},
 };
 
 use rand::{rng, seq::SliceRandom};
+use rustc_hash::{FxHashMap, FxHashSet};
+use sqlx::SqlitePool;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use tracing::{debug, error, info, warn};
 
@@ -14,7 +17,13 @@ use crate::{
     devices::builtin::cpal::CpalProvider, media::errors::PlaybackStartError,
     playback::events::RepeatState,
 };
-use crate::{devices::builtin::dummy::DummyDeviceProvider, settings::playback::PlaybackSettings};
+use crate::{
+    devices::builtin::{
+        dummy::DummyDeviceProvider,
+        pcm::{PCM_UID_PREFIX, PcmDevice},
+    },
+    settings::playback::{PlaybackSettings, VolumeCurve},
+};
 // #[cfg(target_os = "linux")]
 // use crate::devices::builtin::pulse::PulseProvider;
 #[cfg(target_os = "windows")]
@@ -26,15 +35,23 @@ use crate::{
         resample::Resampler,
         traits::{Device, DeviceProvider, OutputStream},
     },
+    library::{
+        db::find_track_file,
+        remote::{self, Download, is_remote},
+    },
     media::{
-        builtin::symphonia::SymphoniaProvider, errors::PlaybackReadError, traits::MediaProvider,
+        builtin::symphonia::SymphoniaProvider,
+        errors::PlaybackReadError,
+        metadata::{Chapter, Metadata, StreamInfo, TrackFile},
+        traits::MediaProvider,
     },
 };
 
 use super::{
-    events::{PlaybackCommand, PlaybackEvent},
+    events::{OutputDevice, OutputPath, PlaybackCommand, PlaybackEvent, Segment},
     interface::PlaybackInterface,
     queue::QueueItemData,
+    renderer::{RendererHandle, RendererMedia, RendererState},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -66,6 +83,10 @@ pub struct PlaybackThread {
     /// The current device.
     device: Option<Box<dyn Device>>,
 
+    /// The UID of the device picked by the user. If `None`, or if the device can't be found, the
+    /// default device is used.
+    output_device: Option<String>,
+
     /// The current stream.
     ///
     /// Note: This stream may become invalid (depending on the device provider). It is the
@@ -89,8 +110,9 @@ pub struct PlaybackThread {
     /// UI thread.
     queue: Arc<RwLock<Vec<QueueItemData>>>,
 
-    /// If the queue is shuffled, this is a copy of the original (unshuffled) queue.
-    original_queue: Vec<QueueItemData>,
+    /// If the queue is shuffled, this is a copy of the original (unshuffled) queue. Like the
+    /// queue, this is read by the UI thread.
+    original_queue: Arc<RwLock<Vec<QueueItemData>>>,
 
     /// Whether or not the queue is shuffled.
     shuffle: bool,
@@ -108,8 +130,88 @@ pub struct PlaybackThread {
 
     /// Whether or not the queue should be repeated when the end of the queue is reached.
     repeat: RepeatState,
+
+    /// The last stream information sent to the UI. This is used to determine if the stream
+    /// information (such as the bitrate of a VBR file) has changed since the last update.
+    last_stream_info: Option<StreamInfo>,
+
+    /// A read-only connection to the library, used to find the silence to skip in each track.
+    pool: SqlitePool,
+
+    /// If silence is being skipped, the position (in seconds) where the audible part of the
+    /// current track ends, or where the track ends if it's one of the tracks of a cue sheet. The
+    /// track is treated as finished once playback passes it.
+    audio_end: Option<f64>,
+
+    /// If the current track is one of the tracks of a cue sheet, where it starts in the file, in
+    /// seconds. Positions are sent relative to it, so that the track plays like a file of its own.
+    cue_start: Option<f64>,
+
+    /// The file that's currently open.
+    current: Option<PathBuf>,
+
+    /// The network device that audio is being sent to instead of the local device, if any.
+    renderer: Option<RendererHandle>,
+
+    /// When the renderer's status was last checked.
+    last_renderer_poll: Instant,
+
+    /// The volume picked by the user, from 0.0 to 1.0, before the volume curve is applied.
+    volume: f64,
+
+    /// The volume last used on each output, keyed by [PlaybackThread::output_key].
+    output_volumes: FxHashMap<String, f64>,
+
+    /// The volume last applied to the stream, after the volume curve, volume offset and ducking.
+    scaled_volume: f64,
+
+    /// How much louder or quieter the current track is played than the volume picked by the
+    /// user, in decibels, as set on the track in the library.
+    volume_offset: f64,
+
+    /// Whether playback is turned down while another application plays audio.
+    ducked: bool,
+
+    /// The path the current track's audio takes to the local device, as last sent to the UI.
+    output_path: Option<OutputPath>,
+
+    /// The parts of the current file that are treated as tracks of their own, taken from its
+    /// titled chapters. Empty if the file has no titled chapters.
+    segments: Vec<Chapter>,
+
+    /// The index of the part of the current file that's playing, in `segments`.
+    segment: Option<usize>,
+
+    /// The metadata read from the current file, before the playing part's title is applied.
+    file_metadata: Option<Metadata>,
+
+    /// The download of the current track, if it's in a remote folder and is played while it
+    /// downloads.
+    download: Option<Arc<Download>>,
+
+    /// How many bytes of the track being downloaded make up a second of audio.
+    download_rate: u64,
+
+    /// Whether playback is waiting for more of the track to download.
+    buffering: bool,
 }
 
+/// Silence shorter than this at the start of a track isn't worth seeking past.
+const MIN_SKIPPED_SILENCE: f64 = 0.05;
+
+/// How often the renderer is asked for its status.
+const RENDERER_POLL: Duration = Duration::from_millis(250);
+
+/// How many bytes make up a second of a downloading track whose size or length isn't known: the
+/// rate of CD audio, which few compressed files go over.
+const FALLBACK_DOWNLOAD_RATE: u64 = 176_400;
+
+/// How much of the volume is kept while another application plays audio, about -14 dB.
+const DUCKED_VOLUME: f64 = 0.2;
+
+/// Streams leave samples untouched when the volume is above this, rather than scaling them.
+const UNSCALED_VOLUME: f64 = 0.98;
+
 pub const LN_50: f64 = 3.91202300543_f64;
 pub const LINEAR_SCALING_COEFFICIENT: f64 = 0.295751527165_f64;
 
@@ -117,7 +219,9 @@ impl PlaybackThread {
     /// Starts the playback thread and returns the created interface.
     pub fn start(
         queue: Arc<RwLock<Vec<QueueItemData>>>,
+        original_queue: Arc<RwLock<Vec<QueueItemData>>>,
         settings: PlaybackSettings,
+        pool: SqlitePool,
     ) -> PlaybackInterface {
         // TODO: use the refresh rate for the bounds
         let (cmd_tx, commands_rx) = unbounded_channel();
@@ -132,12 +236,13 @@ impl PlaybackThread {
                     media_provider: None,
                     device_provider: None,
                     device: None,
+                    output_device: None,
                     stream: None,
                     state: PlaybackState::Stopped,
                     resampler: None,
                     format: None,
                     queue,
-                    original_queue: Vec::new(),
+                    original_queue,
                     shuffle: false,
                     queue_next: 0,
                     last_timestamp: u64::MAX,
@@ -148,6 +253,25 @@ impl PlaybackThread {
                         RepeatState::NotRepeating
                     },
                     playback_settings: settings,
+                    last_stream_info: None,
+                    pool,
+                    audio_end: None,
+                    cue_start: None,
+                    current: None,
+                    renderer: None,
+                    last_renderer_poll: Instant::now(),
+                    volume: 1.0,
+                    output_volumes: FxHashMap::default(),
+                    scaled_volume: 1.0,
+                    volume_offset: 0.0,
+                    ducked: false,
+                    output_path: None,
+                    segments: Vec::new(),
+                    segment: None,
+                    file_metadata: None,
+                    download: None,
+                    download_rate: FALLBACK_DOWNLOAD_RATE,
+                    buffering: false,
                 };
 
                 thread.run();
@@ -241,8 +365,14 @@ impl PlaybackThread {
     /// Start command intake and audio playback loop.
     pub fn main_loop(&mut self) {
         self.command_intake();
+        self.follow_default_device();
 
-        if self.state == PlaybackState::Playing {
+        if self.renderer.is_some() {
+            self.poll_renderer();
+            sleep(std::time::Duration::from_millis(10));
+        } else if self.state == PlaybackState::Playing && self.wait_for_download() {
+            sleep(std::time::Duration::from_millis(10));
+        } else if self.state == PlaybackState::Playing {
             self.play_audio();
         } else {
             sleep(std::time::Duration::from_millis(10));
@@ -256,24 +386,126 @@ impl PlaybackThread {
         let Some(provider) = &mut self.media_provider else {
             return;
         };
+
+        if let Ok(stream_info) = provider.stream_info()
+            && self.last_stream_info.as_ref() != Some(&stream_info)
+        {
+            self.last_stream_info = Some(stream_info.clone());
+            self.events_tx
+                .send(PlaybackEvent::StreamInfoChanged(stream_info))
+                .expect("unable to send event");
+        }
+
         if !provider.metadata_updated() {
             return;
         }
         // TODO: proper error handling
-        let metadata = Box::new(
-            provider
-                .read_metadata()
-                .expect("failed to get metadata")
-                .clone(),
-        );
+        let mut metadata = provider
+            .read_metadata()
+            .expect("failed to get metadata")
+            .clone();
+
+        // cue tracks are shown with their own title, as they are in the library
+        if let Some(start) = self.cue_start
+            && let Some(track) = metadata
+                .cue_tracks
+                .iter()
+                .find(|track| (track.start - start).abs() < 0.001)
+        {
+            metadata = metadata.cue_track_metadata(track);
+        }
+
+        // the next song of a chained stream keeps the art of the stream
+        if provider.metadata_starts_segment() {
+            let start = provider.position_secs().unwrap_or_default();
+            debug!("The stream moved on to a new track at {start}s");
+            self.events_tx
+                .send(PlaybackEvent::SegmentChanged(Segment {
+                    start,
+                    duration: None,
+                }))
+                .expect("unable to send event");
+        } else {
+            let image = provider.read_image().expect("failed to decode image");
+            self.events_tx
+                .send(PlaybackEvent::AlbumArtUpdate(image))
+                .expect("unable to send event");
+        }
+
+        self.file_metadata = Some(metadata.clone());
         self.events_tx
-            .send(PlaybackEvent::MetadataUpdate(metadata))
+            .send(PlaybackEvent::MetadataUpdate(Box::new(
+                self.segment_metadata(metadata),
+            )))
             .expect("unable to send event");
+    }
 
-        let image = provider.read_image().expect("failed to decode image");
+    /// Applies the title and artist of the playing chapter, if it has any, to the metadata of the
+    /// current file.
+    fn segment_metadata(&self, mut metadata: Metadata) -> Metadata {
+        let Some(chapter) = self.segment.and_then(|index| self.segments.get(index)) else {
+            return metadata;
+        };
+
+        if let Some(title) = &chapter.title {
+            metadata.name = Some(title.clone());
+            metadata.work = None;
+            metadata.movement = None;
+            metadata.track_current = None;
+        }
+        if let Some(artist) = &chapter.artist {
+            metadata.artist = Some(artist.clone());
+        }
+
+        metadata
+    }
+
+    /// Checks whether playback has moved into another titled chapter of the current file, and if
+    /// so, sends the new chapter as a segment along with its metadata. This doesn't touch the
+    /// audio, so gapless playback through the chapters is unaffected.
+    fn update_segment(&mut self, position: f64) {
+        if self.segments.is_empty() {
+            return;
+        }
+
+        let index = self
+            .segments
+            .iter()
+            .rposition(|chapter| chapter.start <= position)
+            .unwrap_or(0);
+
+        if self.segment == Some(index) {
+            return;
+        }
+        self.segment = Some(index);
+
+        let start = self.segments[index].start;
+        let end = self
+            .segments
+            .get(index + 1)
+            .map(|next| next.start)
+            .or_else(|| {
+                self.media_provider
+                    .as_ref()
+                    .and_then(|provider| provider.duration_secs().ok())
+                    .map(|duration| duration as f64)
+            });
+
+        debug!("Playing chapter {} of the current file", index + 1);
         self.events_tx
-            .send(PlaybackEvent::AlbumArtUpdate(image))
+            .send(PlaybackEvent::SegmentChanged(Segment {
+                start: start as u64,
+                duration: end.map(|end| (end - start).max(0.0) as u64),
+            }))
             .expect("unable to send event");
+
+        if let Some(metadata) = self.file_metadata.clone() {
+            self.events_tx
+                .send(PlaybackEvent::MetadataUpdate(Box::new(
+                    self.segment_metadata(metadata),
+                )))
+                .expect("unable to send event");
+        }
     }
 
     /// Read incoming commands from the command channel, and process them.
@@ -294,6 +526,8 @@ impl PlaybackThread {
                 PlaybackCommand::Next => self.next(true),
                 PlaybackCommand::Previous => self.previous(),
                 PlaybackCommand::ClearQueue => self.clear_queue(),
+                PlaybackCommand::ClearQueueExceptCurrent => self.clear_queue_except_current(),
+                PlaybackCommand::RemovePlayed => self.remove_played(),
                 PlaybackCommand::Jump(v) => self.jump(v),
                 PlaybackCommand::JumpUnshuffled(v) => self.jump_unshuffled(v),
                 PlaybackCommand::Seek(v) => self.seek(v),
@@ -302,6 +536,13 @@ impl PlaybackThread {
                 PlaybackCommand::Stop => self.stop(),
                 PlaybackCommand::ToggleShuffle => self.toggle_shuffle(),
                 PlaybackCommand::SetRepeat(v) => self.set_repeat(v),
+                PlaybackCommand::UpdateSettings(v) => self.update_settings(v),
+                PlaybackCommand::SetRenderer(v) => self.set_renderer(v),
+                PlaybackCommand::ListOutputDevices => self.list_output_devices(),
+                PlaybackCommand::SetOutputDevice(v) => self.set_output_device(v),
+                PlaybackCommand::SetOutputVolumes(v) => self.set_output_volumes(v),
+                PlaybackCommand::SetVolumeOffset(v) => self.set_volume_offset(v),
+                PlaybackCommand::SetDucked(v) => self.set_ducked(v),
             }
         }
     }
@@ -313,7 +554,11 @@ impl PlaybackThread {
         }
 
         if self.state == PlaybackState::Playing {
-            if let Some(stream) = &mut self.stream {
+            if let Some(renderer) = &self.renderer {
+                if let Err(err) = renderer.lock().pause() {
+                    warn!("Failed to pause renderer: {err}");
+                }
+            } else if let Some(stream) = &mut self.stream {
                 // stream is being played right now which means it has to be valid
                 // this is fine
                 stream.pause().expect("unable to pause stream");
@@ -334,7 +579,11 @@ impl PlaybackThread {
         }
 
         if self.state == PlaybackState::Paused {
-            if self.stream.is_some() {
+            if let Some(renderer) = &self.renderer {
+                if let Err(err) = renderer.lock().play() {
+                    warn!("Failed to resume renderer: {err}");
+                }
+            } else if self.stream.is_some() {
                 if self.pending_reset {
                     // we have to do .as_mut.unwrap() because we need self later
                     let result = self.stream.as_mut().unwrap().reset();
@@ -400,9 +649,53 @@ impl PlaybackThread {
     fn open(&mut self, path: &PathBuf) -> Result<(), PlaybackStartError> {
         info!("Opening: {:?}", path);
 
+        let track_file = crate::RUNTIME
+            .block_on(find_track_file(&self.pool, path))
+            .unwrap_or_else(|err| {
+                warn!("Failed to look up the file of {:?}: {err}", path);
+                TrackFile::whole(path)
+            });
+        let cue_range = track_file.range;
+        let audible_range = if self.playback_settings.skip_silence && cue_range.is_none() {
+            find_audible_range(&self.pool, path)
+        } else {
+            None
+        };
+        let file = &track_file.path;
+        self.volume_offset = find_volume_offset(&self.pool, path).unwrap_or_default();
+
+        // while a renderer is attached, the local stream stays paused
+        let local = self.renderer.is_none();
+
+        // files in remote folders are played while they download the first time, and from the
+        // downloaded copy after that; renderers are sent the whole file
+        let cache_size = self.playback_settings.remote_cache_size * 1024 * 1024;
+        let mut stream = None;
+        let file = &if !is_remote(file) {
+            file.clone()
+        } else if let Some(copy) = remote::local_copy(file) {
+            copy
+        } else if local {
+            self.set_buffering(true);
+            stream = Some(remote::stream(file, cache_size).map_err(|e| {
+                self.set_buffering(false);
+                PlaybackStartError::MediaError(format!("Unable to download file: {}", e))
+            })?);
+            remote::cached_path(file)
+        } else {
+            remote::fetch(file, cache_size).map_err(|e| {
+                PlaybackStartError::MediaError(format!("Unable to download file: {}", e))
+            })?
+        };
+
+        if stream.is_none() {
+            self.set_buffering(false);
+        }
+
         let mut recreation_required = false;
 
-        if self.state == PlaybackState::Paused
+        if local
+            && self.state == PlaybackState::Paused
             && let Some(result) = self.stream.as_mut()
             && let Err(err) = result.reset()
         {
@@ -410,7 +703,8 @@ impl PlaybackThread {
             recreation_required = true;
         }
 
-        if let Some(stream) = self.stream.as_mut()
+        if local
+            && let Some(stream) = self.stream.as_mut()
             && let Err(err) = stream.play()
         {
             warn!("Failed to reset device, forcing recreation: {:?}", err);
@@ -425,17 +719,53 @@ impl PlaybackThread {
             ))?;
 
         self.resampler = None;
-        let src = std::fs::File::open(path)
-            .map_err(|e| PlaybackStartError::MediaError(format!("Unable to open file: {}", e)))?;
+        self.download = stream.as_ref().map(|stream| stream.download());
+
+        let opened = match stream {
+            Some(stream) => provider.open_stream(Box::new(stream), file.extension()),
+            None => {
+                let src = std::fs::File::open(file).map_err(|e| {
+                    PlaybackStartError::MediaError(format!("Unable to open file: {}", e))
+                })?;
+                provider.open(src, None)
+            }
+        };
 
-        provider
-            .open(src, None)
-            .map_err(|e| PlaybackStartError::MediaError(format!("Unable to open file: {}", e)))?;
+        if let Err(e) = opened {
+            self.download = None;
+            self.set_buffering(false);
+            return Err(PlaybackStartError::MediaError(format!(
+                "Unable to open file: {}",
+                e
+            )));
+        }
 
         provider.start_playback().map_err(|e| {
             PlaybackStartError::MediaError(format!("Unable to start playback: {}", e))
         })?;
 
+        if let Some((start, _)) = audible_range
+            && start >= MIN_SKIPPED_SILENCE
+        {
+            debug!("Skipping {start:.2}s of silence at the start of the track");
+            if let Err(err) = provider.seek(start) {
+                warn!(
+                    "Failed to skip silence at the start of the track: {:?}",
+                    err
+                );
+            }
+        }
+        self.audio_end = audible_range.map(|(_, end)| end);
+
+        if let Some((start, end)) = cue_range {
+            debug!("Playing {start:.2}s to {end:.2}s of {:?}", file);
+            if let Err(err) = provider.seek(start) {
+                warn!("Failed to seek to the start of the cue track: {:?}", err);
+            }
+            self.audio_end = Some(end);
+        }
+        self.cue_start = cue_range.map(|(start, _)| start);
+
         // TODO: handle multiple media providers
         let channels = provider.channels().map_err(|e| {
             PlaybackStartError::MediaError(format!("Unable to get channels: {}", e))
@@ -449,7 +779,7 @@ impl PlaybackThread {
             PlaybackStartError::StreamError(format!("Unable to get stream format: {}", e))
         })?;
 
-        if channels.count() != stream_channels.channels.count() {
+        if local && channels.count() != stream_channels.channels.count() {
             info!(
                 "Channel count mismatch, re-opening with the correct channel count (if supported)"
             );
@@ -461,12 +791,31 @@ impl PlaybackThread {
             recreation_required = true;
         }
 
-        let path = path.clone();
+        let start = provider.position().unwrap_or_default();
+        let media = renderer_media(provider.as_mut());
+
+        self.download_rate = self
+            .download
+            .as_ref()
+            .and_then(|download| download.total())
+            .zip(
+                provider
+                    .duration_secs()
+                    .ok()
+                    .filter(|duration| *duration > 0),
+            )
+            .map_or(FALLBACK_DOWNLOAD_RATE, |(total, duration)| total / duration);
+
+        self.current = Some(file.clone());
         self.events_tx
-            .send(PlaybackEvent::SongChanged(path))
+            .send(PlaybackEvent::SongChanged(path.clone()))
             .expect("unable to send event");
 
-        if let Ok(duration) = provider.duration_secs() {
+        if let Some((start, end)) = cue_range {
+            self.events_tx
+                .send(PlaybackEvent::DurationChanged((end - start).max(0.0) as u64))
+                .expect("unable to send event");
+        } else if let Ok(duration) = provider.duration_secs() {
             self.events_tx
                 .send(PlaybackEvent::DurationChanged(duration))
                 .expect("unable to send event");
@@ -476,6 +825,35 @@ impl PlaybackThread {
                 .expect("unable to send event");
         }
 
+        // the other tracks of a cue sheet aren't chapters of the one that's playing
+        let chapters = if self.cue_start.is_some() {
+            Vec::new()
+        } else {
+            provider.chapters().unwrap_or_default()
+        };
+        self.events_tx
+            .send(PlaybackEvent::ChaptersChanged(
+                chapters
+                    .iter()
+                    .map(|chapter| chapter.start)
+                    .filter(|start| *start > 0.0)
+                    .collect(),
+            ))
+            .expect("unable to send event");
+
+        // mixes with titled chapters are scrobbled and shown chapter by chapter
+        self.segments = if chapters.iter().any(|chapter| chapter.title.is_some()) {
+            let mut segments = chapters;
+            if segments.first().is_none_or(|chapter| chapter.start > 0.0) {
+                segments.insert(0, Chapter::default());
+            }
+            segments
+        } else {
+            Vec::new()
+        };
+        self.segment = None;
+        self.file_metadata = None;
+
         if recreation_required {
             self.recreate_stream(true, Some(channels));
             let play_result = self.stream.as_mut().unwrap().play();
@@ -484,11 +862,23 @@ impl PlaybackThread {
                 error!("Device was recreated and we still can't play");
                 panic!("couldn't play device")
             }
+        } else if local {
+            self.apply_volume();
+        }
+
+        if let Some(renderer) = self.renderer.clone() {
+            let result = renderer.lock().load(file, &media, start);
+
+            if let Err(err) = result {
+                error!("Failed to send the track to the renderer, playing locally: {err}");
+                self.detach_renderer();
+            }
         }
 
         self.state = PlaybackState::Playing;
 
         self.update_ts();
+        self.update_segment(start);
 
         self.events_tx
             .send(PlaybackEvent::StateChanged(PlaybackState::Playing))
@@ -511,6 +901,14 @@ impl PlaybackThread {
             return;
         }
 
+        if user_initiated
+            && self.playback_settings.skip_by_work
+            && self.queue_next > 0
+            && self.queue_next <= queue.len()
+        {
+            self.queue_next = self.work_end(&queue, self.queue_next - 1);
+        }
+
         if self.queue_next < queue.len() {
             info!("Opening next file in queue");
             let path = queue[self.queue_next].get_path().clone();
@@ -527,7 +925,7 @@ impl PlaybackThread {
                 info!("End of queue reached, repeating.");
 
                 if self.shuffle {
-                    queue.shuffle(&mut rng());
+                    shuffle_items(&self.pool, &mut queue);
 
                     self.events_tx
                         .send(PlaybackEvent::QueueUpdated)
@@ -556,6 +954,18 @@ impl PlaybackThread {
 
         let queue = self.queue.read().expect("couldn't get the queue");
 
+        if self.playback_settings.skip_by_work
+            && self.state != PlaybackState::Stopped
+            && self.queue_next > 0
+            && self.queue_next <= queue.len()
+            && let Some(start) = self.previous_work_start(&queue, self.queue_next - 1)
+        {
+            drop(queue);
+            info!("Opening the start of the previous work in queue");
+            self.jump(start);
+            return;
+        }
+
         if self.state == PlaybackState::Stopped && !queue.is_empty() {
             let path = queue.last().unwrap().get_path().clone();
             self.queue_next = queue.len();
@@ -585,6 +995,60 @@ impl PlaybackThread {
         }
     }
 
+    /// Returns the index after the last consecutive movement of the work the item at `index`
+    /// belongs to. Items that aren't part of a work are their own unit.
+    fn work_end(&self, queue: &[QueueItemData], index: usize) -> usize {
+        crate::RUNTIME.block_on(async {
+            let Some(work) = find_work(&self.pool, queue[index].get_path()).await else {
+                return index + 1;
+            };
+
+            let mut end = index + 1;
+            while end < queue.len()
+                && find_work(&self.pool, queue[end].get_path()).await.as_ref() == Some(&work)
+            {
+                end += 1;
+            }
+
+            end
+        })
+    }
+
+    /// Returns the index of the first consecutive movement of the work the item at `index`
+    /// belongs to.
+    async fn work_start(&self, queue: &[QueueItemData], index: usize, work: &Work) -> usize {
+        let mut start = index;
+        while start > 0
+            && find_work(&self.pool, queue[start - 1].get_path())
+                .await
+                .as_ref()
+                == Some(work)
+        {
+            start -= 1;
+        }
+
+        start
+    }
+
+    /// Finds where going back from the item at `index` should land when skipping by work: the
+    /// start of its own work if it isn't the first movement, otherwise the start of the work
+    /// before it. Returns `None` when neither item is part of a work.
+    fn previous_work_start(&self, queue: &[QueueItemData], index: usize) -> Option<usize> {
+        crate::RUNTIME.block_on(async {
+            if let Some(work) = find_work(&self.pool, queue[index].get_path()).await {
+                let start = self.work_start(queue, index, &work).await;
+                if start < index {
+                    return Some(start);
+                }
+            }
+
+            let previous = index.checked_sub(1)?;
+            let work = find_work(&self.pool, queue[previous].get_path()).await?;
+
+            Some(self.work_start(queue, previous, &work).await)
+        })
+    }
+
     /// Add a new QueueItemData to the queue. If nothing is playing, start playing it.
     fn queue(&mut self, item: QueueItemData) {
         info!("Adding file to queue: {}", item);
@@ -597,7 +1061,10 @@ impl PlaybackThread {
         drop(queue);
 
         if self.shuffle {
-            self.original_queue.push(item.clone());
+            self.original_queue
+                .write()
+                .expect("couldn't get the original queue")
+                .push(item.clone());
         }
 
         if self.state == PlaybackState::Stopped {
@@ -629,12 +1096,15 @@ impl PlaybackThread {
 
         if self.shuffle {
             let mut shuffled_paths = paths.clone();
-            shuffled_paths.shuffle(&mut rng());
+            shuffle_items(&self.pool, &mut shuffled_paths);
 
             queue.append(&mut shuffled_paths);
             drop(queue);
 
-            self.original_queue.append(&mut paths);
+            self.original_queue
+                .write()
+                .expect("couldn't get the original queue")
+                .append(&mut paths);
         } else {
             queue.append(&mut paths);
             drop(queue);
@@ -661,28 +1131,60 @@ impl PlaybackThread {
 
     /// Emit a PositionChanged event if the timestamp has changed.
     fn update_ts(&mut self) {
-        if let Some(provider) = &self.media_provider
-            && let Ok(timestamp) = provider.position_secs()
-        {
-            if timestamp == self.last_timestamp {
-                return;
-            }
+        let Some(provider) = &self.media_provider else {
+            return;
+        };
+        let Ok(timestamp) = provider.position_secs() else {
+            return;
+        };
+        let position = provider.position().unwrap_or(timestamp as f64);
+        let timestamp = self.relative_timestamp(position).unwrap_or(timestamp);
+        if timestamp == self.last_timestamp {
+            return;
+        }
 
-            self.events_tx
-                .send(PlaybackEvent::PositionChanged(timestamp))
-                .expect("unable to send event");
+        self.events_tx
+            .send(PlaybackEvent::PositionChanged(timestamp))
+            .expect("unable to send event");
 
-            self.last_timestamp = timestamp;
-        }
+        self.last_timestamp = timestamp;
+        self.update_segment(position);
+    }
+
+    /// Returns the timestamp (in whole seconds) of a position in the current file, relative to
+    /// the start of the cue track that's playing. Returns `None` if no cue track is playing.
+    fn relative_timestamp(&self, position: f64) -> Option<u64> {
+        self.cue_start
+            .map(|start| (position - start).max(0.0) as u64)
     }
 
     /// Seek to the specified timestamp (in seconds).
     fn seek(&mut self, timestamp: f64) {
+        let timestamp = timestamp + self.cue_start.unwrap_or_default();
+
+        // seeking past what has been downloaded waits for the download to get there
+        if self
+            .download
+            .as_ref()
+            .is_some_and(|download| !download.is_finished())
+        {
+            self.set_buffering(true);
+        }
+
         if let Some(provider) = &mut self.media_provider {
-            provider.seek(timestamp).expect("unable to seek");
+            if let Err(err) = provider.seek(timestamp) {
+                error!("Unable to seek: {:?}", err);
+                return;
+            }
             self.pending_reset = true;
             self.update_ts();
         }
+
+        if let Some(renderer) = &self.renderer
+            && let Err(err) = renderer.lock().seek(timestamp)
+        {
+            warn!("Failed to seek renderer: {err}");
+        }
     }
 
     /// Jump to the specified index in the queue.
@@ -712,8 +1214,14 @@ impl PlaybackThread {
         }
 
         let queue = self.queue.read().expect("couldn't get the queue");
-        let path = self.original_queue[index].get_path();
-        let pos = queue.iter().position(|a| a.get_path() == path);
+        let original_queue = self
+            .original_queue
+            .read()
+            .expect("couldn't get the original queue");
+        let pos = original_queue
+            .get(index)
+            .and_then(|item| queue.iter().position(|a| a.get_path() == item.get_path()));
+        drop(original_queue);
         drop(queue);
 
         if let Some(pos) = pos {
@@ -729,12 +1237,15 @@ impl PlaybackThread {
 
         if self.shuffle {
             let mut shuffled_paths = paths.clone();
-            shuffled_paths.shuffle(&mut rng());
+            shuffle_items(&self.pool, &mut shuffled_paths);
 
             *queue = shuffled_paths;
 
             drop(queue);
-            self.original_queue = paths;
+            *self
+                .original_queue
+                .write()
+                .expect("couldn't get the original queue") = paths;
         } else {
             *queue = paths;
             drop(queue);
@@ -752,7 +1263,10 @@ impl PlaybackThread {
     fn clear_queue(&mut self) {
         let mut queue = self.queue.write().expect("couldn't get the queue");
         *queue = Vec::new();
-        self.original_queue = Vec::new();
+        self.original_queue
+            .write()
+            .expect("couldn't get the original queue")
+            .clear();
         self.queue_next = 0;
 
         self.events_tx
@@ -761,13 +1275,124 @@ impl PlaybackThread {
         self.events_tx.send(PlaybackEvent::QueueUpdated).unwrap();
     }
 
+    /// Clear the current queue, keeping only the current track.
+    fn clear_queue_except_current(&mut self) {
+        if self.queue_next == 0 {
+            self.clear_queue();
+        } else {
+            self.keep_from_current(Some(1));
+        }
+    }
+
+    /// Remove the tracks before the current track from the queue.
+    fn remove_played(&mut self) {
+        if self.queue_next > 0 {
+            self.keep_from_current(None);
+        }
+    }
+
+    /// Remove everything before the current track from the queue, keeping at most `count` tracks
+    /// starting from the current track. There must be a current track.
+    fn keep_from_current(&mut self, count: Option<usize>) {
+        let mut queue = self.queue.write().expect("couldn't get the queue");
+        let current = self.queue_next - 1;
+
+        if current >= queue.len() {
+            return;
+        }
+
+        let end = count.map_or(queue.len(), |count| (current + count).min(queue.len()));
+        let removed: Vec<QueueItemData> = queue[..current]
+            .iter()
+            .chain(queue[end..].iter())
+            .cloned()
+            .collect();
+        *queue = queue[current..end].to_vec();
+        drop(queue);
+
+        if self.shuffle {
+            let mut original_queue = self
+                .original_queue
+                .write()
+                .expect("couldn't get the original queue");
+
+            // the same file can be in the queue more than once, so only one copy is removed for
+            // each removed item
+            for item in removed {
+                if let Some(index) = original_queue
+                    .iter()
+                    .position(|x| x.get_path() == item.get_path())
+                {
+                    original_queue.remove(index);
+                }
+            }
+        }
+
+        self.queue_next = 1;
+
+        self.events_tx
+            .send(PlaybackEvent::QueuePositionChanged(0))
+            .expect("unable to send event");
+        self.events_tx
+            .send(PlaybackEvent::QueueUpdated)
+            .expect("unable to send event");
+    }
+
+    /// Tells the UI whether playback is waiting for more of the track to download.
+    fn set_buffering(&mut self, buffering: bool) {
+        if self.buffering != buffering {
+            self.buffering = buffering;
+            self.events_tx
+                .send(PlaybackEvent::Buffering(buffering))
+                .expect("unable to send event");
+        }
+    }
+
+    /// Whether playback has to wait for more of the track that's downloading. Once the download
+    /// falls behind, playback waits until the read-ahead in the settings has been downloaded
+    /// again, so that it doesn't stop every few moments on a slow connection.
+    fn wait_for_download(&mut self) -> bool {
+        let Some(download) = &self.download else {
+            return false;
+        };
+
+        let ahead = if self.buffering {
+            self.playback_settings.stream_read_ahead * self.download_rate
+        } else {
+            self.download_rate
+        };
+        let buffering = !download.is_buffered(ahead);
+
+        if buffering && !self.buffering {
+            info!("Waiting for more of the track to download");
+        }
+        self.set_buffering(buffering);
+
+        buffering
+    }
+
     /// Stop the current playback.
     fn stop(&mut self) {
         if let Some(provider) = &mut self.media_provider {
             provider.stop_playback().expect("unable to stop playback");
             provider.close().expect("unable to close media");
         }
+        if let Some(renderer) = &self.renderer
+            && let Err(err) = renderer.lock().pause()
+        {
+            warn!("Failed to pause renderer: {err}");
+        }
+
         self.state = PlaybackState::Stopped;
+        self.last_stream_info = None;
+        self.audio_end = None;
+        self.cue_start = None;
+        self.current = None;
+        self.segments.clear();
+        self.segment = None;
+        self.file_metadata = None;
+        self.download = None;
+        self.set_buffering(false);
 
         self.events_tx
             .send(PlaybackEvent::StateChanged(PlaybackState::Stopped))
@@ -777,13 +1402,16 @@ impl PlaybackThread {
     /// Toggle shuffle mode. This will result in the queue being duplicated and shuffled.
     fn toggle_shuffle(&mut self) {
         let mut queue = self.queue.write().expect("couldn't get the queue");
+        let mut original_queue = self
+            .original_queue
+            .write()
+            .expect("couldn't get the original queue");
 
         if self.shuffle {
             // find the current track in the unshuffled queue
             let index = if self.queue_next > 0 {
                 let path = queue[self.queue_next - 1].get_path();
-                let index = self
-                    .original_queue
+                let index = original_queue
                     .iter()
                     .position(|x| x.get_path() == path)
                     .unwrap();
@@ -793,9 +1421,9 @@ impl PlaybackThread {
                 0
             };
 
-            swap(&mut self.original_queue, &mut queue);
-            self.original_queue = Vec::new();
+            *queue = take(&mut *original_queue);
             self.shuffle = false;
+            drop(original_queue);
             drop(queue);
 
             self.events_tx
@@ -810,11 +1438,12 @@ impl PlaybackThread {
                     .expect("unable to send event");
             }
         } else {
-            self.original_queue = queue.clone();
+            *original_queue = queue.clone();
             let length = queue.len();
-            queue[self.queue_next..length].shuffle(&mut rng());
+            shuffle_items(&self.pool, &mut queue[self.queue_next..length]);
             self.shuffle = true;
             let queue_next = self.queue_next;
+            drop(original_queue);
             drop(queue);
 
             self.events_tx
@@ -826,27 +1455,141 @@ impl PlaybackThread {
         }
     }
 
-    /// Sets the volume of the playback stream.
+    /// Sets the volume of the playback stream, and remembers it for the current output.
     fn set_volume(&mut self, volume: f64) {
-        if let Some(stream) = self.stream.as_mut() {
-            let volume_scaled = if volume >= 0.99_f64 {
-                1_f64
-            } else if volume > 0.1 {
-                f64::exp(LN_50 * volume) / 50_f64
-            } else {
-                volume * LINEAR_SCALING_COEFFICIENT
+        self.volume = volume;
+        let key = self.output_key();
+        self.output_volumes.insert(key.clone(), volume);
+
+        if let Some(renderer) = &self.renderer {
+            // renderers apply their own curve
+            if let Err(err) = renderer.lock().set_volume(volume) {
+                warn!("Failed to set renderer volume: {err}");
+            }
+        } else {
+            self.apply_volume();
+        }
+
+        self.events_tx
+            .send(PlaybackEvent::VolumeChanged(volume))
+            .expect("unable to send event");
+        self.events_tx
+            .send(PlaybackEvent::OutputVolumeChanged(key, volume))
+            .expect("unable to send event");
+    }
+
+    /// Applies the volume to the stream, after the volume curve, the current track's volume
+    /// offset and any ducking. Streams start at full volume, so this has to be done whenever the stream is
+    /// recreated. Streams can't play louder than full volume, so raising a track only has an
+    /// effect while the volume is turned down.
+    fn apply_volume(&mut self) {
+        let volume = self.volume;
+        let volume_scaled = match self.playback_settings.volume_curve {
+            VolumeCurve::Logarithmic if volume >= 0.99_f64 => 1_f64,
+            VolumeCurve::Logarithmic if volume > 0.1 => f64::exp(LN_50 * volume) / 50_f64,
+            VolumeCurve::Logarithmic => volume * LINEAR_SCALING_COEFFICIENT,
+            VolumeCurve::Linear => volume,
+        };
+        let volume_scaled = (volume_scaled * 10_f64.powf(self.volume_offset / 20.0)).min(1.0);
+        let volume_scaled = if self.ducked {
+            volume_scaled * DUCKED_VOLUME
+        } else {
+            volume_scaled
+        };
+
+        if let Some(stream) = self.stream.as_mut()
+            && let Err(err) = stream.set_volume(volume_scaled)
+        {
+            error!("Failed to set volume: {:?}", err);
+        }
+
+        self.scaled_volume = volume_scaled;
+
+        if let Some(path) = &self.output_path {
+            let path = OutputPath {
+                volume_reduced: volume_scaled <= UNSCALED_VOLUME,
+                ..path.clone()
             };
+            self.set_output_path(path);
+        }
+    }
 
-            stream
-                .set_volume(volume_scaled)
-                .expect("failed to set volume");
+    /// Tells the UI about the path audio takes to the device, if it has changed.
+    fn set_output_path(&mut self, path: OutputPath) {
+        if self.output_path.as_ref() == Some(&path) {
+            return;
+        }
 
-            self.events_tx
-                .send(PlaybackEvent::VolumeChanged(volume))
-                .expect("unable to send event");
+        info!(
+            "Output path: {} Hz {:?} ({} channels) to {} Hz {:?} ({} channels), volume reduced: \
+             {}, bit-perfect: {}",
+            path.source_rate,
+            path.source_format,
+            path.source_channels,
+            path.device_rate,
+            path.device_format,
+            path.device_channels,
+            path.volume_reduced,
+            path.is_bit_perfect()
+        );
+
+        self.output_path = Some(path.clone());
+        self.events_tx
+            .send(PlaybackEvent::OutputPathChanged(path))
+            .expect("unable to send event");
+    }
+
+    /// Changes the volume offset of the current track, in decibels, after it has been changed in
+    /// the library.
+    fn set_volume_offset(&mut self, offset: f64) {
+        self.volume_offset = offset;
+
+        if self.renderer.is_none() {
+            self.apply_volume();
+        }
+    }
+
+    /// Turns playback down while another application plays audio, or back up once it stops.
+    /// Renderers keep their own volume, so they aren't ducked.
+    fn set_ducked(&mut self, ducked: bool) {
+        if self.ducked == ducked {
+            return;
+        }
+
+        info!("{} playback", if ducked { "Ducking" } else { "Unducking" });
+        self.ducked = ducked;
+
+        if self.renderer.is_none() {
+            self.apply_volume();
+        }
+    }
+
+    /// Identifies the output audio is currently sent to, so that each output can keep its own
+    /// volume.
+    fn output_key(&self) -> String {
+        match (&self.renderer, &self.output_device) {
+            (Some(renderer), _) => format!("renderer:{}", renderer.lock().name()),
+            (None, Some(uid)) => uid.clone(),
+            (None, None) => "default".to_string(),
         }
     }
 
+    /// Restores the volume last used on the current output. Outputs that haven't been used before
+    /// carry on with the current volume, except for renderers, which keep their own.
+    fn restore_volume(&mut self) {
+        match self.output_volumes.get(&self.output_key()) {
+            Some(&volume) => self.set_volume(volume),
+            None if self.renderer.is_none() => self.set_volume(self.volume),
+            None => {}
+        }
+    }
+
+    /// Replaces the remembered volumes, such as with the ones saved when the app was last closed.
+    fn set_output_volumes(&mut self, volumes: FxHashMap<String, f64>) {
+        self.output_volumes = volumes;
+        self.restore_volume();
+    }
+
     /// Sets the repeat mode. The queue will loop infinitely when repeat mode is enabled. When repeat once mode is enabled If shuffle
     /// mode is also enabled, the queue will be reshuffled when looped.
     fn set_repeat(&mut self, state: RepeatState) {
@@ -862,6 +1605,31 @@ impl PlaybackThread {
             .expect("unable to send event");
     }
 
+    fn update_settings(&mut self, settings: PlaybackSettings) {
+        let outputs_changed = self.playback_settings.pcm_outputs != settings.pcm_outputs;
+        let curve_changed = self.playback_settings.volume_curve != settings.volume_curve;
+        self.playback_settings = settings;
+
+        if curve_changed {
+            self.apply_volume();
+        }
+
+        // the PCM output being played to might have been changed or removed
+        if outputs_changed
+            && self
+                .output_device
+                .as_ref()
+                .is_some_and(|uid| uid.starts_with(PCM_UID_PREFIX))
+        {
+            self.switch_output_device();
+        }
+
+        // turning on always repeat should take effect immediately
+        if self.repeat == RepeatState::NotRepeating && self.playback_settings.always_repeat {
+            self.set_repeat(RepeatState::Repeating);
+        }
+    }
+
     /// Toggles between play/pause.
     fn toggle_play_pause(&mut self) {
         match self.state {
@@ -871,6 +1639,250 @@ impl PlaybackThread {
         }
     }
 
+    /// Sends audio to the given renderer instead of the local device, or back to the local device
+    /// if `None` is given. Playback continues from the same position.
+    fn set_renderer(&mut self, renderer: Option<RendererHandle>) {
+        let Some(renderer) = renderer else {
+            self.detach_renderer();
+            return;
+        };
+
+        if let Some(previous) = self.renderer.take()
+            && let Err(err) = previous.lock().stop()
+        {
+            warn!("Failed to stop renderer: {err}");
+        }
+
+        let name = renderer.lock().name().to_string();
+
+        if let Some(path) = &self.current
+            && let Some(provider) = &mut self.media_provider
+        {
+            let position = provider.position().unwrap_or_default();
+            let media = renderer_media(provider.as_mut());
+
+            let mut locked = renderer.lock();
+            let mut result = locked.load(path, &media, position);
+            if result.is_ok() && self.state == PlaybackState::Paused {
+                result = locked.pause();
+            }
+
+            if let Err(err) = result {
+                error!("Failed to send the track to {name}: {err}");
+                return;
+            }
+        }
+
+        if self.state == PlaybackState::Playing
+            && let Some(stream) = &mut self.stream
+            && let Err(err) = stream.pause()
+        {
+            warn!("Failed to pause stream: {:?}", err);
+        }
+        self.pending_reset = true;
+
+        info!("Sending audio to {name}");
+        self.renderer = Some(renderer);
+        self.events_tx
+            .send(PlaybackEvent::RendererChanged(Some(name)))
+            .expect("unable to send event");
+        self.restore_volume();
+    }
+
+    /// Stops sending audio to the renderer, and carries on playing from the same position on the
+    /// local device.
+    fn detach_renderer(&mut self) {
+        let Some(renderer) = self.renderer.take() else {
+            return;
+        };
+
+        if let Err(err) = renderer.lock().stop() {
+            warn!("Failed to stop renderer: {err}");
+        }
+
+        info!("Sending audio to the local device");
+
+        if self.state != PlaybackState::Stopped
+            && let Some(provider) = &mut self.media_provider
+            && let Err(err) =
+                provider.seek(self.last_timestamp as f64 + self.cue_start.unwrap_or_default())
+        {
+            warn!("Failed to seek after leaving renderer: {:?}", err);
+        }
+
+        if self.state == PlaybackState::Playing
+            && let Some(stream) = &mut self.stream
+        {
+            let restarted = stream.reset().is_ok() && stream.play().is_ok();
+
+            if !restarted {
+                warn!("Failed to restart playback, recreating device");
+                let format = self.format.clone();
+                self.recreate_stream(true, format.map(|v| v.channels));
+
+                if let Some(stream) = &mut self.stream
+                    && let Err(err) = stream.play()
+                {
+                    error!("Failed to start playback after recreation: {:?}", err);
+                }
+            }
+
+            self.pending_reset = false;
+        }
+
+        self.events_tx
+            .send(PlaybackEvent::RendererChanged(None))
+            .expect("unable to send event");
+        self.restore_volume();
+    }
+
+    /// Checks the renderer's status, so that the position is updated and changes made on the
+    /// device itself are picked up. If the renderer can't be reached, playback moves back to the
+    /// local device.
+    fn poll_renderer(&mut self) {
+        if self.state == PlaybackState::Stopped || self.last_renderer_poll.elapsed() < RENDERER_POLL
+        {
+            return;
+        }
+        self.last_renderer_poll = Instant::now();
+
+        let Some(renderer) = self.renderer.clone() else {
+            return;
+        };
+        let status = renderer.lock().status();
+
+        let status = match status {
+            Ok(status) => status,
+            Err(err) => {
+                error!("Lost connection to the renderer, playing locally: {err}");
+                self.detach_renderer();
+                return;
+            }
+        };
+
+        let timestamp = self
+            .relative_timestamp(status.position)
+            .unwrap_or(status.position as u64);
+        if timestamp != self.last_timestamp {
+            self.last_timestamp = timestamp;
+            self.events_tx
+                .send(PlaybackEvent::PositionChanged(timestamp))
+                .expect("unable to send event");
+            self.update_segment(status.position);
+        }
+
+        match (status.state, self.state) {
+            (RendererState::Finished, PlaybackState::Playing) => self.next(false),
+            (RendererState::Playing, PlaybackState::Playing)
+                if self.audio_end.is_some_and(|end| status.position >= end) =>
+            {
+                debug!("Reached the end of the track's audio, moving on");
+                self.audio_end = None;
+                self.next(false);
+            }
+            (RendererState::Paused, PlaybackState::Playing) => {
+                self.state = PlaybackState::Paused;
+                self.events_tx
+                    .send(PlaybackEvent::StateChanged(PlaybackState::Paused))
+                    .expect("unable to send event");
+            }
+            (RendererState::Playing, PlaybackState::Paused) => {
+                self.state = PlaybackState::Playing;
+                self.events_tx
+                    .send(PlaybackEvent::StateChanged(PlaybackState::Playing))
+                    .expect("unable to send event");
+            }
+            (RendererState::Idle, PlaybackState::Playing) => {
+                warn!("The renderer stopped playing, pausing");
+                self.pause();
+            }
+            _ => (),
+        }
+    }
+
+    fn list_output_devices(&mut self) {
+        let Some(device_provider) = self.device_provider.as_mut() else {
+            return;
+        };
+
+        let devices = match device_provider.get_devices() {
+            Ok(devices) => devices,
+            Err(err) => {
+                error!("Failed to list output devices: {err}");
+                return;
+            }
+        };
+
+        let devices = devices
+            .iter()
+            .filter_map(|device| {
+                Some(OutputDevice {
+                    uid: device.get_uid().ok()?,
+                    name: device.get_name().ok()?,
+                })
+            })
+            .chain(
+                self.playback_settings
+                    .pcm_outputs
+                    .iter()
+                    .map(|output| OutputDevice {
+                        uid: format!("{PCM_UID_PREFIX}{}", output.name),
+                        name: output.name.clone(),
+                    }),
+            )
+            .collect();
+
+        self.events_tx
+            .send(PlaybackEvent::OutputDevicesListed(devices))
+            .expect("unable to send event");
+    }
+
+    /// Moves playback to the output device with the given UID, or the default device.
+    fn set_output_device(&mut self, uid: Option<String>) {
+        if self.output_device == uid && self.stream.is_some() {
+            return;
+        }
+
+        info!("Switching output device to {:?}", uid);
+        self.output_device = uid.clone();
+        self.switch_output_device();
+
+        self.events_tx
+            .send(PlaybackEvent::OutputDeviceChanged(uid))
+            .expect("unable to send event");
+        self.restore_volume();
+    }
+
+    /// Moves playback to the new default output device when the system's default changes, as long
+    /// as the default output is the one being played to.
+    fn follow_default_device(&mut self) {
+        let changed = self
+            .device_provider
+            .as_mut()
+            .is_some_and(|provider| provider.default_device_changed());
+
+        if changed && self.output_device.is_none() && self.renderer.is_none() {
+            info!("The default output device changed, switching to it");
+            self.switch_output_device();
+        }
+    }
+
+    /// Reopens the stream on the current output device, continuing playback if it was playing.
+    fn switch_output_device(&mut self) {
+        let format = self.format.clone();
+        self.recreate_stream(true, format.map(|v| v.channels));
+        // the new device might use a different sample rate
+        self.resampler = None;
+
+        if self.state == PlaybackState::Playing
+            && self.renderer.is_none()
+            && let Some(stream) = &mut self.stream
+            && let Err(err) = stream.play()
+        {
+            error!("Failed to start playback on the new device: {:?}", err);
+        }
+    }
+
     /// Recreates the playback stream with the given channels if any are provided, otherwise uses
     /// the device's default channel layout.
     fn recreate_stream(&mut self, force: bool, channels: Option<ChannelSpec>) {
@@ -882,7 +1894,31 @@ impl PlaybackThread {
             panic!("playback thread incorrectly initialized")
         };
 
-        let Ok(mut device) = device_provider.get_default_device() else {
+        let pcm_output = self.output_device.as_ref().and_then(|uid| {
+            let name = uid.strip_prefix(PCM_UID_PREFIX)?;
+            self.playback_settings
+                .pcm_outputs
+                .iter()
+                .find(|output| output.name == name)
+        });
+
+        let device = if let Some(output) = pcm_output {
+            Ok(Box::new(PcmDevice::new(
+                output.name.clone(),
+                output.target.clone(),
+                output.sample_rate,
+            )) as Box<dyn Device>)
+        } else {
+            match &self.output_device {
+                Some(uid) => device_provider.get_device_by_uid(uid).or_else(|err| {
+                    warn!("Output device {uid} is unavailable, using the default device: {err}");
+                    device_provider.get_default_device()
+                }),
+                None => device_provider.get_default_device(),
+            }
+        };
+
+        let Ok(mut device) = device else {
             error!("No playback device found, audio will not play");
             return;
         };
@@ -934,6 +1970,7 @@ impl PlaybackThread {
 
         self.device = Some(device);
         self.stream = Some(stream);
+        self.apply_volume();
 
         let format = self.stream.as_mut().unwrap().get_current_format().unwrap();
 
@@ -949,6 +1986,16 @@ impl PlaybackThread {
     /// Uses the current media provider to decode audio samples and sends them to the current
     /// playback stream.
     fn play_audio(&mut self) {
+        if let Some(end) = self.audio_end
+            && let Some(provider) = &self.media_provider
+            && provider.position().is_ok_and(|position| position >= end)
+        {
+            debug!("Skipping silence at the end of the track");
+            self.audio_end = None;
+            self.next(false);
+            return;
+        }
+
         let Some(stream) = &mut self.stream else {
             return;
         };
@@ -1000,6 +2047,20 @@ impl PlaybackThread {
             ));
             self.format = Some(device_format.clone());
 
+            let output_path = OutputPath {
+                source_rate: first_samples.rate,
+                source_format: first_samples.samples.format(),
+                source_channels: provider
+                    .stream_info()
+                    .ok()
+                    .and_then(|info| info.channels)
+                    .unwrap_or(device_format.channels.count()),
+                device_rate: device_format.sample_rate,
+                device_format: device_format.sample_type,
+                device_channels: device_format.channels.count(),
+                volume_reduced: self.scaled_volume <= UNSCALED_VOLUME,
+            };
+
             // Convert the first samples to the device format
             let converted = self
                 .resampler
@@ -1030,6 +2091,7 @@ impl PlaybackThread {
             }
 
             self.update_ts();
+            self.set_output_path(output_path);
         } else {
             // Ditto above but without creating the resampler
             let samples = match provider.read_samples() {
@@ -1092,3 +2154,111 @@ impl PlaybackThread {
         }
     }
 }
+
+/// Describes the open track for a renderer.
+fn renderer_media(provider: &mut dyn MediaProvider) -> RendererMedia {
+    let mut media = provider
+        .read_metadata()
+        .map(|metadata| RendererMedia {
+            title: metadata.name.clone(),
+            artist: metadata.artist.clone(),
+            album: metadata.album.clone(),
+            duration: None,
+        })
+        .unwrap_or_default();
+    media.duration = provider.duration_secs().ok().map(|v| v as f64);
+
+    media
+}
+
+/// Shuffles the items, leaving audiobook chapters where they are so that books are still heard in
+/// order.
+fn shuffle_items(pool: &SqlitePool, items: &mut [QueueItemData]) {
+    let result: Result<Vec<i64>, sqlx::Error> = crate::RUNTIME.block_on(
+        sqlx::query_scalar(include_str!(
+            "../../queries/audiobooks/list_audiobook_ids.sql"
+        ))
+        .fetch_all(pool),
+    );
+    let audiobooks: FxHashSet<i64> = result
+        .unwrap_or_else(|err| {
+            warn!("Failed to look up audiobook chapters: {err}");
+            Vec::new()
+        })
+        .into_iter()
+        .collect();
+
+    if audiobooks.is_empty() {
+        items.shuffle(&mut rng());
+        return;
+    }
+
+    let movable: Vec<usize> = (0..items.len())
+        .filter(|index| {
+            !items[*index]
+                .get_db_id()
+                .is_some_and(|id| audiobooks.contains(&id))
+        })
+        .collect();
+    let mut shuffled: Vec<QueueItemData> =
+        movable.iter().map(|index| items[*index].clone()).collect();
+    shuffled.shuffle(&mut rng());
+
+    for (index, item) in movable.into_iter().zip(shuffled) {
+        items[index] = item;
+    }
+}
+
+/// A classical work, along with the album the recording of it is on, so that two recordings of
+/// the same work next to each other in the queue aren't taken to be one.
+type Work = (Option<i64>, String);
+
+/// Looks up the classical work the file is a movement of, if the file is in the library.
+async fn find_work(pool: &SqlitePool, path: &Path) -> Option<Work> {
+    let result: Result<Option<Work>, sqlx::Error> =
+        sqlx::query_as(include_str!("../../queries/playback/find_work.sql"))
+            .bind(path.to_str())
+            .fetch_optional(pool)
+            .await;
+
+    result.unwrap_or_else(|err| {
+        warn!("Failed to look up the work of {:?}: {err}", path);
+        None
+    })
+}
+
+/// Looks up how much louder or quieter the file is played, in decibels, if the file is in the
+/// library and has been given a volume offset.
+fn find_volume_offset(pool: &SqlitePool, path: &Path) -> Option<f64> {
+    let result: Result<Option<(f64,)>, sqlx::Error> = crate::RUNTIME.block_on(
+        sqlx::query_as(include_str!(
+            "../../queries/playback/find_volume_offset.sql"
+        ))
+        .bind(path.to_str())
+        .fetch_optional(pool),
+    );
+
+    result
+        .unwrap_or_else(|err| {
+            warn!("Failed to look up the volume offset of {:?}: {err}", path);
+            None
+        })
+        .map(|(offset,)| offset)
+}
+
+/// Looks up where the audible part of the file starts and ends, if the file is in the library and
+/// has been analyzed.
+fn find_audible_range(pool: &SqlitePool, path: &Path) -> Option<(f64, f64)> {
+    let result: Result<Option<(f64, f64)>, sqlx::Error> = crate::RUNTIME.block_on(
+        sqlx::query_as(include_str!(
+            "../../queries/playback/find_audible_range.sql"
+        ))
+        .bind(path.to_str())
+        .fetch_optional(pool),
+    );
+
+    result.unwrap_or_else(|err| {
+        warn!("Failed to look up silence for {:?}: {err}", path);
+        None
+    })
+}
@@ -1,2 +1,12 @@
+pub mod artist_info;
+pub mod cast;
 pub mod controllers;
+pub mod dlna;
 pub mod mmb;
+pub mod musicbrainz;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub mod other_audio;
+pub mod podcasts;
+pub mod scripts;
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+pub mod session;
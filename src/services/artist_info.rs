@@ -0,0 +1,316 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use chrono::Utc;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+use crate::{
+    library::{
+        art::store_art,
+        db::{get_artist_by_id, list_artists_search},
+    },
+    services::{
+        mmb::lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET, client::LastFMClient},
+        musicbrainz::http_client,
+    },
+};
+
+/// How long fetched artist information is kept before it is looked up again.
+const REFRESH_AFTER: chrono::Duration = chrono::Duration::days(30);
+
+/// The width artist images are requested at from Wikimedia Commons.
+const IMAGE_WIDTH: u32 = 500;
+
+/// A short biography and image for an artist, found online.
+#[derive(Debug, Default)]
+pub struct ArtistInfo {
+    pub bio: Option<String>,
+    pub image: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct ArtistSearch {
+    artists: Vec<ArtistSearchResult>,
+}
+
+#[derive(Deserialize)]
+struct ArtistSearchResult {
+    id: String,
+    score: u32,
+}
+
+#[derive(Deserialize)]
+struct ArtistRelations {
+    #[serde(default)]
+    relations: Vec<Relation>,
+}
+
+#[derive(Deserialize)]
+struct Relation {
+    #[serde(rename = "type")]
+    relation_type: String,
+    url: Option<RelationUrl>,
+}
+
+#[derive(Deserialize)]
+struct RelationUrl {
+    resource: String,
+}
+
+#[derive(Deserialize)]
+struct PageSummary {
+    extract: Option<String>,
+}
+
+/// Finds the artist on MusicBrainz by name. A low score means MusicBrainz only found a partial
+/// match, which is likely a different artist.
+async fn search_artist(client: &zed_reqwest::Client, name: &str) -> anyhow::Result<String> {
+    let search: ArtistSearch = client
+        .get("https://musicbrainz.org/ws/2/artist/")
+        .query(&[
+            ("query", format!("artist:\"{}\"", name.replace('"', ""))),
+            ("fmt", "json".to_string()),
+            ("limit", "1".to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    search
+        .artists
+        .into_iter()
+        .find(|artist| artist.score >= 90)
+        .map(|artist| artist.id)
+        .ok_or_else(|| anyhow!("artist not found on MusicBrainz"))
+}
+
+/// Finds the Wikidata item for an artist through their MusicBrainz relationships. The artist is
+/// searched for by name unless their MusicBrainz ID is known.
+async fn find_wikidata_id(
+    client: &zed_reqwest::Client,
+    name: &str,
+    mbid: Option<&str>,
+) -> anyhow::Result<String> {
+    let mbid = match mbid {
+        Some(mbid) => mbid.to_string(),
+        None => search_artist(client, name).await?,
+    };
+
+    let relations: ArtistRelations = client
+        .get(format!("https://musicbrainz.org/ws/2/artist/{mbid}"))
+        .query(&[("inc", "url-rels"), ("fmt", "json")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    relations
+        .relations
+        .into_iter()
+        .filter(|relation| relation.relation_type == "wikidata")
+        .find_map(|relation| {
+            let url = relation.url?.resource;
+            url.rsplit('/').next().map(str::to_string)
+        })
+        .ok_or_else(|| anyhow!("artist has no Wikidata relationship"))
+}
+
+/// Returns the image file name and English Wikipedia page title of a Wikidata item.
+async fn read_wikidata(
+    client: &zed_reqwest::Client,
+    id: &str,
+) -> anyhow::Result<(Option<String>, Option<String>)> {
+    let data: Value = client
+        .get(format!(
+            "https://www.wikidata.org/wiki/Special:EntityData/{id}.json"
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let entity = &data["entities"][id];
+    let image = entity["claims"]["P18"][0]["mainsnak"]["datavalue"]["value"]
+        .as_str()
+        .map(str::to_string);
+    let page = entity["sitelinks"]["enwiki"]["title"]
+        .as_str()
+        .map(str::to_string);
+
+    Ok((image, page))
+}
+
+async fn fetch_image(client: &zed_reqwest::Client, file: &str) -> anyhow::Result<Vec<u8>> {
+    let mut url = url::Url::parse("https://commons.wikimedia.org/wiki/Special:FilePath/")?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("invalid url"))?
+        .pop_if_empty()
+        .push(file);
+    url.query_pairs_mut()
+        .append_pair("width", &IMAGE_WIDTH.to_string());
+
+    Ok(client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+async fn fetch_wikipedia_summary(
+    client: &zed_reqwest::Client,
+    page: &str,
+) -> anyhow::Result<Option<String>> {
+    let mut url = url::Url::parse("https://en.wikipedia.org/api/rest_v1/page/summary/")?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("invalid url"))?
+        .pop_if_empty()
+        .push(page);
+
+    let summary: PageSummary = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(summary.extract.filter(|extract| !extract.is_empty()))
+}
+
+async fn fetch_lastfm_bio(name: &str) -> anyhow::Result<Option<String>> {
+    let (Some(key), Some(secret)) = (LASTFM_API_KEY, LASTFM_API_SECRET) else {
+        return Ok(None);
+    };
+
+    let mut client = LastFMClient::new(key.to_string(), secret.to_string());
+    client.get_artist_bio(name).await
+}
+
+/// Looks up a short biography and an image for the artist with the given name. The bio comes
+/// from Last.fm when it is available, and Wikipedia otherwise. The image comes from Wikidata,
+/// which is found through the artist's MusicBrainz relationships.
+pub async fn fetch_artist_info(name: &str, mbid: Option<&str>) -> anyhow::Result<ArtistInfo> {
+    let client = http_client()?;
+    let mut info = ArtistInfo {
+        bio: fetch_lastfm_bio(name)
+            .await
+            .inspect_err(|err| debug!(?err, "Could not get bio for {name} from last.fm"))
+            .ok()
+            .flatten(),
+        image: None,
+    };
+
+    let (image, page) = match find_wikidata_id(&client, name, mbid).await {
+        Ok(id) => read_wikidata(&client, &id).await?,
+        Err(err) => {
+            debug!("Could not find {name} on Wikidata: {err}");
+            return Ok(info);
+        }
+    };
+
+    if let Some(file) = image {
+        info.image = fetch_image(&client, &file)
+            .await
+            .inspect_err(|err| warn!(?err, "Could not download image for {name}: {err}"))
+            .ok();
+    }
+
+    if info.bio.is_none()
+        && let Some(page) = page
+    {
+        info.bio = fetch_wikipedia_summary(&client, &page).await?;
+    }
+
+    Ok(info)
+}
+
+/// Fetches the bio and image for an artist and stores them in the library, unless they were
+/// fetched recently. Returns whether the artist was updated.
+pub async fn update_artist_info(
+    read_pool: &SqlitePool,
+    write_pool: &SqlitePool,
+    art_dir: &Path,
+    artist_id: i64,
+) -> anyhow::Result<bool> {
+    let artist = get_artist_by_id(read_pool, artist_id).await?;
+
+    if artist
+        .info_fetched_at
+        .is_some_and(|fetched_at| Utc::now() - fetched_at < REFRESH_AFTER)
+    {
+        return Ok(false);
+    }
+
+    let Some(name) = &artist.name else {
+        return Ok(false);
+    };
+
+    let info = fetch_artist_info(&name.0, artist.mbid.as_deref()).await?;
+    let image_hash = info
+        .image
+        .as_deref()
+        .map(|image| store_art(art_dir, image))
+        .transpose()?;
+
+    sqlx::query(include_str!("../../queries/library/update_artist_info.sql"))
+        .bind(artist_id)
+        .bind(info.bio)
+        .bind(image_hash)
+        .execute(write_pool)
+        .await?;
+
+    Ok(true)
+}
+
+/// An artist in the library that is similar to another artist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarArtist {
+    pub id: i64,
+    pub name: String,
+    pub image_hash: Option<String>,
+}
+
+/// Finds artists in the library that Last.fm considers similar to the given artist, most similar
+/// first. Returns nothing if Last.fm isn't available.
+pub async fn find_similar_artists(
+    pool: &SqlitePool,
+    name: &str,
+) -> anyhow::Result<Vec<SimilarArtist>> {
+    let (Some(key), Some(secret)) = (LASTFM_API_KEY, LASTFM_API_SECRET) else {
+        return Ok(Vec::new());
+    };
+
+    let mut client = LastFMClient::new(key.to_string(), secret.to_string());
+    let similar = client.get_similar_artists(name).await?;
+
+    let mut library: FxHashMap<String, SimilarArtist> = list_artists_search(pool)
+        .await?
+        .into_iter()
+        .map(|(id, name, image_hash)| {
+            (
+                name.to_lowercase(),
+                SimilarArtist {
+                    id: id as i64,
+                    name,
+                    image_hash,
+                },
+            )
+        })
+        .collect();
+
+    Ok(similar
+        .iter()
+        .filter_map(|name| library.remove(&name.to_lowercase()))
+        .collect())
+}
@@ -0,0 +1,415 @@
+mod channel;
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, bail};
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use serde_json::{Value, json};
+use tracing::{debug, info, warn};
+
+use crate::playback::renderer::{
+    Renderer, RendererMedia, RendererState, RendererStatus,
+    server::{MediaServer, content_type},
+};
+
+use self::channel::{CastChannel, CastMessage};
+
+const CAST_SERVICE: &str = "_googlecast._tcp.local.";
+
+/// The Default Media Receiver, which every Cast device can run without registering an app.
+const DEFAULT_RECEIVER: &str = "CC1AD845";
+
+const NS_CONNECTION: &str = "urn:x-cast:com.google.cast.tp.connection";
+const NS_HEARTBEAT: &str = "urn:x-cast:com.google.cast.tp.heartbeat";
+const NS_RECEIVER: &str = "urn:x-cast:com.google.cast.receiver";
+const NS_MEDIA: &str = "urn:x-cast:com.google.cast.media";
+
+const SENDER: &str = "sender-0";
+const RECEIVER: &str = "receiver-0";
+
+/// The device drops the connection if it doesn't hear from the sender for a while.
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+/// The position is extrapolated between status updates, and corrected this often.
+const STATUS_INTERVAL: Duration = Duration::from_secs(5);
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A Cast device found on the network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastDevice {
+    pub name: String,
+    pub address: SocketAddr,
+}
+
+/// Looks for Cast devices on the local network for the given amount of time.
+pub fn discover(timeout: Duration) -> anyhow::Result<Vec<CastDevice>> {
+    let daemon = ServiceDaemon::new()?;
+    let events = daemon.browse(CAST_SERVICE)?;
+    let deadline = Instant::now() + timeout;
+    let mut devices: Vec<CastDevice> = Vec::new();
+
+    while let Ok(event) = events.recv_deadline(deadline) {
+        let ServiceEvent::ServiceResolved(info) = event else {
+            continue;
+        };
+
+        // prefer IPv4, since not every device listens on IPv6
+        let Some(ip) = info
+            .get_addresses()
+            .iter()
+            .min_by_key(|ip| ip.is_ipv6())
+            .copied()
+        else {
+            continue;
+        };
+
+        let name = info
+            .get_property_val_str("fn")
+            .unwrap_or(info.get_fullname())
+            .to_string();
+        let address = SocketAddr::new(ip, info.get_port());
+
+        if !devices.iter().any(|device| device.address == address) {
+            debug!("Found Cast device {name} at {address}");
+            devices.push(CastDevice { name, address });
+        }
+    }
+
+    let _ = daemon.shutdown();
+
+    Ok(devices)
+}
+
+/// Plays tracks on a Cast device, using the Default Media Receiver. The device fetches each file
+/// from the [MediaServer].
+pub struct CastRenderer {
+    name: String,
+    ip: IpAddr,
+    channel: CastChannel,
+    request_id: u64,
+    /// The ID used to talk to the receiver app.
+    transport_id: String,
+    session_id: String,
+    media_session_id: Option<i64>,
+    status: RendererStatus,
+    /// When the status was last received, so the position can be extrapolated while playing.
+    status_time: Instant,
+    /// Whether a track has been loaded and the device hasn't confirmed it yet. The status of the
+    /// previous track is ignored until then.
+    loading: bool,
+    /// A media request made before the device reported the media session, sent once it does.
+    pending: Option<Value>,
+    last_ping: Instant,
+    last_status_request: Instant,
+}
+
+impl CastRenderer {
+    /// Connects to the device and starts the receiver app on it.
+    pub fn connect(device: &CastDevice) -> anyhow::Result<Self> {
+        info!("Connecting to Cast device {}", device.name);
+
+        let mut renderer = CastRenderer {
+            name: device.name.clone(),
+            ip: device.address.ip(),
+            channel: CastChannel::connect(device.address)?,
+            request_id: 0,
+            transport_id: String::new(),
+            session_id: String::new(),
+            media_session_id: None,
+            status: RendererStatus {
+                state: RendererState::Idle,
+                position: 0.0,
+            },
+            status_time: Instant::now(),
+            loading: false,
+            pending: None,
+            last_ping: Instant::now(),
+            last_status_request: Instant::now(),
+        };
+
+        renderer.send(RECEIVER, NS_CONNECTION, json!({ "type": "CONNECT" }))?;
+        let request_id = renderer.request(
+            RECEIVER,
+            NS_RECEIVER,
+            json!({ "type": "LAUNCH", "appId": DEFAULT_RECEIVER }),
+        )?;
+
+        let status = renderer.wait_for(|message, payload| {
+            message.namespace == NS_RECEIVER && payload["requestId"] == request_id
+        })?;
+        if status["type"] != "RECEIVER_STATUS" {
+            bail!(
+                "the device could not start the receiver: {}",
+                status["type"]
+            );
+        }
+
+        let app = find_app(&status).context("the receiver did not start")?;
+        renderer.transport_id = app["transportId"].as_str().unwrap_or_default().to_string();
+        renderer.session_id = app["sessionId"].as_str().unwrap_or_default().to_string();
+
+        let transport_id = renderer.transport_id.clone();
+        renderer.send(&transport_id, NS_CONNECTION, json!({ "type": "CONNECT" }))?;
+
+        Ok(renderer)
+    }
+
+    fn send(&mut self, destination: &str, namespace: &str, payload: Value) -> anyhow::Result<()> {
+        self.channel.send(&CastMessage {
+            source: SENDER.to_string(),
+            destination: destination.to_string(),
+            namespace: namespace.to_string(),
+            payload: payload.to_string(),
+        })?;
+
+        Ok(())
+    }
+
+    /// Sends a message with a new request ID, and returns the ID.
+    fn request(
+        &mut self,
+        destination: &str,
+        namespace: &str,
+        mut payload: Value,
+    ) -> anyhow::Result<u64> {
+        self.request_id += 1;
+        payload["requestId"] = self.request_id.into();
+        self.send(destination, namespace, payload)?;
+
+        Ok(self.request_id)
+    }
+
+    fn media_request(&mut self, mut payload: Value) -> anyhow::Result<()> {
+        let Some(media_session_id) = self.media_session_id.filter(|_| !self.loading) else {
+            self.pending = Some(payload);
+            return Ok(());
+        };
+
+        payload["mediaSessionId"] = media_session_id.into();
+        let transport_id = self.transport_id.clone();
+        self.request(&transport_id, NS_MEDIA, payload)?;
+
+        Ok(())
+    }
+
+    /// Handles messages until one matches, and returns its payload.
+    fn wait_for(
+        &mut self,
+        matches: impl Fn(&CastMessage, &Value) -> bool,
+    ) -> anyhow::Result<Value> {
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                bail!("the device did not respond");
+            }
+
+            if let Some(message) = self.channel.receive(remaining)? {
+                let payload = self.handle(&message)?;
+                if matches(&message, &payload) {
+                    return Ok(payload);
+                }
+            }
+        }
+    }
+
+    /// Handles a message from the device, keeping the connection alive and tracking the status.
+    fn handle(&mut self, message: &CastMessage) -> anyhow::Result<Value> {
+        let payload: Value = serde_json::from_str(&message.payload).unwrap_or_default();
+
+        match (message.namespace.as_str(), payload["type"].as_str()) {
+            (NS_HEARTBEAT, Some("PING")) => {
+                self.send(&message.source, NS_HEARTBEAT, json!({ "type": "PONG" }))?;
+            }
+            (NS_CONNECTION, Some("CLOSE")) => bail!("the device closed the session"),
+            // another sender can replace the receiver app with their own
+            (NS_RECEIVER, Some("RECEIVER_STATUS"))
+                if !self.session_id.is_empty()
+                    && find_app(&payload).and_then(|app| app["sessionId"].as_str())
+                        != Some(self.session_id.as_str()) =>
+            {
+                bail!("the receiver was closed on the device");
+            }
+            (NS_MEDIA, Some("MEDIA_STATUS")) => self.update_status(&payload),
+            (NS_MEDIA, Some("LOAD_FAILED" | "LOAD_CANCELLED")) => {
+                warn!("{} could not load the track", self.name);
+                self.loading = false;
+                self.set_status(RendererState::Idle, 0.0);
+            }
+            _ => (),
+        }
+
+        Ok(payload)
+    }
+
+    fn update_status(&mut self, payload: &Value) {
+        let Some(status) = payload["status"].get(0) else {
+            return;
+        };
+
+        let state = match status["playerState"].as_str() {
+            Some("PLAYING") => RendererState::Playing,
+            Some("PAUSED") => RendererState::Paused,
+            Some("BUFFERING") => RendererState::Buffering,
+            _ if status["idleReason"] == "FINISHED" => RendererState::Finished,
+            _ => RendererState::Idle,
+        };
+
+        if self.loading && matches!(state, RendererState::Idle | RendererState::Finished) {
+            return;
+        }
+
+        self.loading = false;
+        self.media_session_id = status["mediaSessionId"].as_i64().or(self.media_session_id);
+
+        let position = status["currentTime"]
+            .as_f64()
+            .unwrap_or_else(|| self.position());
+        self.set_status(state, position);
+
+        if let Some(pending) = self.pending.take()
+            && let Err(err) = self.media_request(pending)
+        {
+            warn!("Could not send request to {}: {err}", self.name);
+        }
+    }
+
+    fn set_status(&mut self, state: RendererState, position: f64) {
+        self.status = RendererStatus { state, position };
+        self.status_time = Instant::now();
+    }
+
+    fn position(&self) -> f64 {
+        if self.status.state == RendererState::Playing {
+            self.status.position + self.status_time.elapsed().as_secs_f64()
+        } else {
+            self.status.position
+        }
+    }
+}
+
+/// Finds the Default Media Receiver in a receiver status message.
+fn find_app(status: &Value) -> Option<&Value> {
+    status["status"]["applications"]
+        .as_array()?
+        .iter()
+        .find(|app| app["appId"] == DEFAULT_RECEIVER)
+}
+
+impl Renderer for CastRenderer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn load(&mut self, path: &Path, media: &RendererMedia, position: f64) -> anyhow::Result<()> {
+        self.pending = None;
+        let url = MediaServer::shared()?.url_for(path, self.ip)?;
+        debug!("Casting {:?} from {url}", path);
+
+        let mut metadata = json!({ "metadataType": 3 });
+        if let Some(title) = &media.title {
+            metadata["title"] = title.as_str().into();
+        }
+        if let Some(artist) = &media.artist {
+            metadata["artist"] = artist.as_str().into();
+        }
+        if let Some(album) = &media.album {
+            metadata["albumName"] = album.as_str().into();
+        }
+
+        let mut info = json!({
+            "contentId": url,
+            "streamType": "BUFFERED",
+            "contentType": content_type(path),
+            "metadata": metadata,
+        });
+        if let Some(duration) = media.duration {
+            info["duration"] = duration.into();
+        }
+
+        let transport_id = self.transport_id.clone();
+        let session_id = self.session_id.clone();
+        self.request(
+            &transport_id,
+            NS_MEDIA,
+            json!({
+                "type": "LOAD",
+                "sessionId": session_id,
+                "media": info,
+                "autoplay": true,
+                "currentTime": position,
+            }),
+        )?;
+
+        self.loading = true;
+        self.set_status(RendererState::Buffering, position);
+
+        Ok(())
+    }
+
+    // the status is changed straight away, so that it isn't mistaken for a change made on the
+    // device before the device confirms it
+    fn play(&mut self) -> anyhow::Result<()> {
+        self.set_status(RendererState::Playing, self.position());
+        self.media_request(json!({ "type": "PLAY" }))
+    }
+
+    fn pause(&mut self) -> anyhow::Result<()> {
+        self.set_status(RendererState::Paused, self.position());
+        self.media_request(json!({ "type": "PAUSE" }))
+    }
+
+    fn seek(&mut self, position: f64) -> anyhow::Result<()> {
+        let state = self.status.state;
+        self.set_status(state, position);
+        self.media_request(json!({ "type": "SEEK", "currentTime": position }))
+    }
+
+    fn set_volume(&mut self, volume: f64) -> anyhow::Result<()> {
+        self.request(
+            RECEIVER,
+            NS_RECEIVER,
+            json!({ "type": "SET_VOLUME", "volume": { "level": volume.clamp(0.0, 1.0) } }),
+        )?;
+
+        Ok(())
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        let session_id = std::mem::take(&mut self.session_id);
+        self.request(
+            RECEIVER,
+            NS_RECEIVER,
+            json!({ "type": "STOP", "sessionId": session_id }),
+        )?;
+        self.send(RECEIVER, NS_CONNECTION, json!({ "type": "CLOSE" }))
+    }
+
+    fn status(&mut self) -> anyhow::Result<RendererStatus> {
+        while let Some(message) = self.channel.receive(Duration::ZERO)? {
+            self.handle(&message)?;
+        }
+
+        if self.last_ping.elapsed() >= PING_INTERVAL {
+            self.send(RECEIVER, NS_HEARTBEAT, json!({ "type": "PING" }))?;
+            self.last_ping = Instant::now();
+        }
+
+        if self.last_status_request.elapsed() >= STATUS_INTERVAL
+            && self.media_session_id.is_some()
+            && !self.loading
+        {
+            self.media_request(json!({ "type": "GET_STATUS" }))?;
+            self.last_status_request = Instant::now();
+        }
+
+        Ok(RendererStatus {
+            state: self.status.state,
+            position: self.position(),
+        })
+    }
+}
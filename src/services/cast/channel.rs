@@ -0,0 +1,260 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use rustls::{
+    ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme, StreamOwned,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+};
+
+/// Cast devices use self-signed certificates, so the connection is encrypted but the device
+/// can't be verified. This is how every Cast sender outside of Chrome works.
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _: &CertificateDer<'_>,
+        _: &[CertificateDer<'_>],
+        _: &ServerName<'_>,
+        _: &[u8],
+        _: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// A message sent over a Cast channel. Only string payloads are supported, since binary payloads
+/// are only used for device authentication.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CastMessage {
+    pub source: String,
+    pub destination: String,
+    pub namespace: String,
+    pub payload: String,
+}
+
+// the fields of the CastMessage protobuf message
+const FIELD_PROTOCOL_VERSION: u64 = 1;
+const FIELD_SOURCE: u64 = 2;
+const FIELD_DESTINATION: u64 = 3;
+const FIELD_NAMESPACE: u64 = 4;
+const FIELD_PAYLOAD_TYPE: u64 = 5;
+const FIELD_PAYLOAD_UTF8: u64 = 6;
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_string(out: &mut Vec<u8>, field: u64, value: &str) {
+    put_varint(out, (field << 3) | 2);
+    put_varint(out, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> Option<u64> {
+    let mut value = 0;
+
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*position)?;
+        *position += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+impl CastMessage {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        // CASTV2_1_0
+        put_varint(&mut out, FIELD_PROTOCOL_VERSION << 3);
+        put_varint(&mut out, 0);
+        put_string(&mut out, FIELD_SOURCE, &self.source);
+        put_string(&mut out, FIELD_DESTINATION, &self.destination);
+        put_string(&mut out, FIELD_NAMESPACE, &self.namespace);
+        // STRING
+        put_varint(&mut out, FIELD_PAYLOAD_TYPE << 3);
+        put_varint(&mut out, 0);
+        put_string(&mut out, FIELD_PAYLOAD_UTF8, &self.payload);
+
+        out
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        let mut message = CastMessage::default();
+        let mut position = 0;
+
+        while position < data.len() {
+            let tag = read_varint(data, &mut position)?;
+
+            match tag & 7 {
+                0 => {
+                    read_varint(data, &mut position)?;
+                }
+                1 => position += 8,
+                2 => {
+                    let length = read_varint(data, &mut position)? as usize;
+                    let value = data.get(position..position.checked_add(length)?)?;
+                    position += length;
+
+                    let value = String::from_utf8_lossy(value).to_string();
+                    match tag >> 3 {
+                        FIELD_SOURCE => message.source = value,
+                        FIELD_DESTINATION => message.destination = value,
+                        FIELD_NAMESPACE => message.namespace = value,
+                        FIELD_PAYLOAD_UTF8 => message.payload = value,
+                        _ => (),
+                    }
+                }
+                5 => position += 4,
+                _ => return None,
+            }
+        }
+
+        Some(message)
+    }
+}
+
+/// A connection to a Cast device. Messages are protobufs, each preceded by their length.
+pub struct CastChannel {
+    stream: StreamOwned<ClientConnection, TcpStream>,
+    buffer: Vec<u8>,
+}
+
+impl CastChannel {
+    pub fn connect(address: SocketAddr) -> anyhow::Result<Self> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+            .with_no_client_auth();
+
+        let connection = ClientConnection::new(Arc::new(config), ServerName::from(address.ip()))?;
+        let socket = TcpStream::connect_timeout(&address, Duration::from_secs(5))?;
+        socket.set_nodelay(true)?;
+
+        Ok(CastChannel {
+            stream: StreamOwned::new(connection, socket),
+            buffer: Vec::new(),
+        })
+    }
+
+    pub fn send(&mut self, message: &CastMessage) -> io::Result<()> {
+        let data = message.encode();
+
+        let mut frame = (data.len() as u32).to_be_bytes().to_vec();
+        frame.extend(data);
+
+        self.stream.write_all(&frame)?;
+        self.stream.flush()
+    }
+
+    /// Takes the first message out of the buffer, if all of it has been received.
+    fn take_buffered(&mut self) -> io::Result<Option<CastMessage>> {
+        let Some(length) = self.buffer.get(..4) else {
+            return Ok(None);
+        };
+        let length = u32::from_be_bytes(length.try_into().unwrap()) as usize;
+
+        if self.buffer.len() < length + 4 {
+            return Ok(None);
+        }
+
+        let frame: Vec<u8> = self.buffer.drain(..length + 4).skip(4).collect();
+        CastMessage::decode(&frame)
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid cast message"))
+    }
+
+    /// Waits up to `timeout` for the next message. Returns `None` if nothing arrived in time.
+    pub fn receive(&mut self, timeout: Duration) -> io::Result<Option<CastMessage>> {
+        let deadline = Instant::now() + timeout;
+        let mut chunk = [0; 4096];
+        let mut read_once = false;
+
+        loop {
+            if let Some(message) = self.take_buffered()? {
+                return Ok(Some(message));
+            }
+
+            // the socket is always checked at least once, even without a timeout
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() && read_once {
+                return Ok(None);
+            }
+            read_once = true;
+
+            self.stream
+                .sock
+                .set_read_timeout(Some(remaining.max(Duration::from_millis(1))))?;
+
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "the device closed the connection",
+                    ));
+                }
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
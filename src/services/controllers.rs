@@ -8,8 +8,7 @@ mod windows;
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
-use futures::StreamExt;
-use gpui::{App, Global, Window};
+use gpui::{App, Global, SharedString, Window};
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 use rustc_hash::FxHashMap;
 use tokio::sync::mpsc::UnboundedSender;
@@ -18,11 +17,11 @@ use tracing::{error, warn};
 use crate::{
     media::metadata::Metadata,
     playback::{
-        events::{PlaybackCommand, RepeatState},
+        events::{PlaybackCommand, PlaybackEvent, RepeatState},
         interface::PlaybackInterface,
         thread::PlaybackState,
     },
-    ui::models::{ImageEvent, Models, PlaybackInfo},
+    ui::models::Models,
 };
 
 /// The InitPlaybackController trait allows you to initialize a new PlaybackController. All
@@ -104,67 +103,74 @@ impl ControllerBridge {
         Self { playback_thread }
     }
 
+    /// Sends the command to the playback thread. Controllers call these from OS callbacks, so a
+    /// closed channel (which only happens while the app is shutting down) is logged rather than
+    /// panicking on the OS's thread.
+    fn send(&self, command: PlaybackCommand) {
+        if let Err(err) = self.playback_thread.send(command) {
+            error!("playback thread closed, dropping controller command: {err}");
+        }
+    }
+
     pub fn play(&self) {
-        self.playback_thread.send(PlaybackCommand::Play).unwrap();
+        self.send(PlaybackCommand::Play);
     }
 
     pub fn pause(&self) {
-        self.playback_thread.send(PlaybackCommand::Pause).unwrap();
+        self.send(PlaybackCommand::Pause);
     }
 
     pub fn toggle_play_pause(&self) {
-        self.playback_thread
-            .send(PlaybackCommand::TogglePlayPause)
-            .unwrap();
+        self.send(PlaybackCommand::TogglePlayPause);
     }
 
     pub fn stop(&self) {
-        self.playback_thread.send(PlaybackCommand::Stop).unwrap();
+        self.send(PlaybackCommand::Stop);
     }
 
     pub fn next(&self) {
-        self.playback_thread.send(PlaybackCommand::Next).unwrap();
+        self.send(PlaybackCommand::Next);
     }
 
     pub fn previous(&self) {
-        self.playback_thread
-            .send(PlaybackCommand::Previous)
-            .unwrap();
+        self.send(PlaybackCommand::Previous);
     }
 
     pub fn jump(&self, index: usize) {
-        self.playback_thread
-            .send(PlaybackCommand::Jump(index))
-            .unwrap();
+        self.send(PlaybackCommand::Jump(index));
     }
 
     pub fn seek(&self, position: f64) {
-        self.playback_thread
-            .send(PlaybackCommand::Seek(position))
-            .unwrap();
+        self.send(PlaybackCommand::Seek(position));
     }
 
     pub fn set_volume(&self, volume: f64) {
-        self.playback_thread
-            .send(PlaybackCommand::SetVolume(volume))
-            .unwrap();
+        self.send(PlaybackCommand::SetVolume(volume));
     }
 
     pub fn toggle_shuffle(&self) {
-        self.playback_thread
-            .send(PlaybackCommand::ToggleShuffle)
-            .unwrap();
+        self.send(PlaybackCommand::ToggleShuffle);
     }
 
     pub fn set_repeat(&self, repeat: RepeatState) {
-        self.playback_thread
-            .send(PlaybackCommand::SetRepeat(repeat))
-            .unwrap();
+        self.send(PlaybackCommand::SetRepeat(repeat));
     }
 }
 
 type ControllerList = FxHashMap<String, Box<dyn PlaybackController>>;
 
+/// How a playback controller is doing, shown in the about dialog.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControllerHealth {
+    /// The controller handled the last event it was sent.
+    Healthy,
+    /// The controller failed to handle the last event it was sent. It's still sent events, and is
+    /// healthy again once it handles one.
+    Failing(SharedString),
+    /// The controller couldn't be started, so it isn't sent events.
+    Unavailable(SharedString),
+}
+
 // has to be held in memory
 #[allow(dead_code)]
 pub struct PbcHandle(UnboundedSender<PbcEvent>, tokio::task::JoinHandle<()>);
@@ -199,98 +205,28 @@ impl PbcEvent {
     }
 }
 
+/// Forwards the events on the playback event bus that the OS media controls care about to the
+/// controller task.
 pub fn register_pbc_event_handlers(cx: &mut App) {
-    let models = cx.global::<Models>();
-    let metadata = models.metadata.clone();
-    let albumart = models.albumart.clone();
-
-    cx.observe(&metadata, |e, cx| {
-        let meta = e.read(cx).clone();
-        let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::MetadataChanged(Box::new(meta))) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    cx.subscribe(&albumart, |_, ImageEvent(img), cx| {
-        let PbcHandle(tx, _) = cx.global();
-        // FIXME: this is really way too expensive
-        if let Err(err) = tx.send(PbcEvent::AlbumArtChanged(img.clone())) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    let playback_info = cx.global::<PlaybackInfo>();
-    let position = playback_info.position.clone();
-    let duration = playback_info.duration.clone();
-    let track = playback_info.current_track.clone();
-    let volume = playback_info.volume.clone();
-    let repeat = playback_info.repeating.clone();
-    let state = playback_info.playback_state.clone();
-    let shuffle = playback_info.shuffling.clone();
-
-    cx.observe(&position, |e, cx| {
-        let &pos = e.read(cx);
-        let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::PositionChanged(pos)) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    cx.observe(&duration, |e, cx| {
-        let &dur = e.read(cx);
-        let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::DurationChanged(dur)) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    cx.observe(&track, |e, cx| {
-        if let Some(track) = e.read(cx)
-            && let path = track.get_path().clone()
-            && let PbcHandle(tx, _) = cx.global()
-            && let Err(err) = tx.send(PbcEvent::NewFile(path))
-        {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    cx.observe(&volume, |e, cx| {
-        let &vol = e.read(cx);
-        let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::VolumeChanged(vol)) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    cx.observe(&repeat, |e, cx| {
-        let &repeat = e.read(cx);
-        let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::RepeatStateChanged(repeat)) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
-
-    cx.observe(&state, |e, cx| {
-        let &state = e.read(cx);
-        let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::PlaybackStateChanged(state)) {
-            error!("playback controller channel closed: {err}");
-        }
-    })
-    .detach();
+    let playback_events = cx.global::<Models>().playback_events.clone();
+
+    cx.subscribe(&playback_events, |_, ev, cx| {
+        let ev = match ev {
+            PlaybackEvent::MetadataUpdate(meta) => PbcEvent::MetadataChanged(meta.clone()),
+            // FIXME: this is really way too expensive
+            PlaybackEvent::AlbumArtUpdate(Some(img)) => PbcEvent::AlbumArtChanged(img.clone()),
+            PlaybackEvent::PositionChanged(pos) => PbcEvent::PositionChanged(*pos),
+            PlaybackEvent::DurationChanged(dur) => PbcEvent::DurationChanged(*dur),
+            PlaybackEvent::SongChanged(path) => PbcEvent::NewFile(path.clone()),
+            PlaybackEvent::VolumeChanged(vol) => PbcEvent::VolumeChanged(*vol),
+            PlaybackEvent::RepeatChanged(repeat) => PbcEvent::RepeatStateChanged(*repeat),
+            PlaybackEvent::StateChanged(state) => PbcEvent::PlaybackStateChanged(*state),
+            PlaybackEvent::ShuffleToggled(shuffle, _) => PbcEvent::ShuffleStateChanged(*shuffle),
+            _ => return,
+        };
 
-    cx.observe(&shuffle, |e, cx| {
-        let &shuffle = e.read(cx);
         let PbcHandle(tx, _) = cx.global();
-        if let Err(err) = tx.send(PbcEvent::ShuffleStateChanged(shuffle)) {
+        if let Err(err) = tx.send(ev) {
             error!("playback controller channel closed: {err}");
         }
     })
@@ -299,6 +235,7 @@ pub fn register_pbc_event_handlers(cx: &mut App) {
 
 pub fn init_pbc_task(cx: &mut App, window: &mut Window) {
     let mut list = ControllerList::default();
+    let mut health = FxHashMap::default();
 
     let sender = cx.global::<PlaybackInterface>().get_sender();
     let bridge = ControllerBridge::new(sender);
@@ -312,52 +249,90 @@ pub fn init_pbc_task(cx: &mut App, window: &mut Window) {
             .map(|v| v.as_raw())
     };
 
+    let mut add =
+        |name: &str, controller: anyhow::Result<Box<dyn PlaybackController>>| match controller {
+            Ok(controller) => {
+                list.insert(name.to_string(), controller);
+                health.insert(name.to_string(), ControllerHealth::Healthy);
+            }
+            Err(err) => {
+                error!(
+                    ?err,
+                    "Failed to initialize playback controller '{name}': {err}"
+                );
+                warn!("Desktop integration will be unavailable.");
+                health.insert(
+                    name.to_string(),
+                    ControllerHealth::Unavailable(err.to_string().into()),
+                );
+            }
+        };
+
     #[cfg(target_os = "macos")]
-    {
-        if let Ok(macos_pc) = macos::MacMediaPlayerController::init(bridge, rwh) {
-            list.insert("macos".to_string(), macos_pc);
-        } else {
-            error!("Failed to initialize MacMediaPlayerController!");
-            warn!("Desktop integration will be unavailable.");
-        }
-    }
+    add(
+        "Now Playing",
+        macos::MacMediaPlayerController::init(bridge, rwh),
+    );
 
     #[cfg(target_os = "linux")]
-    {
-        if let Ok(mpris_pc) = mpris::MprisController::init(bridge, rwh) {
-            list.insert("mpris".to_string(), mpris_pc);
-        } else {
-            error!("Failed to initialize MprisController!");
-            warn!("Desktop integration will be unavailable.");
-        };
-    }
+    add("MPRIS", mpris::MprisController::init(bridge, rwh));
 
     #[cfg(target_os = "windows")]
-    {
-        if let Ok(windows_pc) = windows::WindowsController::init(bridge, rwh) {
-            list.insert("windows".to_string(), windows_pc);
-        } else {
-            error!("Failed to initialize WindowsController!");
-            warn!("Desktop integration will be unavailable.");
-        };
-    }
+    add("SMTC", windows::WindowsController::init(bridge, rwh));
 
+    let health_model = cx.global::<Models>().controller_health.clone();
+    health_model.write(cx, health);
+
+    let (health_tx, mut health_rx) = tokio::sync::mpsc::unbounded_channel();
     let (pbc_tx, mut pbc_rx) = tokio::sync::mpsc::unbounded_channel::<PbcEvent>();
     let task = crate::RUNTIME.spawn(async move {
         tracing::debug_span!("playback_controller_task");
 
+        let mut failing = FxHashMap::default();
+
         while let Some(event) = pbc_rx.recv().await {
-            futures::stream::iter(&mut list)
-                .for_each_concurrent(None, async |(name, pbc)| {
-                    if let Err(err) = event.handle_event(pbc.as_mut()).await {
+            let results = futures::future::join_all(list.iter_mut().map(|(name, pbc)| {
+                let event = &event;
+                async move {
+                    let result = event.handle_event(pbc.as_mut()).await;
+                    if let Err(err) = &result {
                         error!(?err, "playback controller '{name}': {err}");
                     }
-                })
-                .await;
+                    (name.clone(), result.err().map(|err| err.to_string()))
+                }
+            }))
+            .await;
+
+            // only changes are sent, since most events are position updates
+            for (name, err) in results {
+                if failing.get(&name) != Some(&err) {
+                    failing.insert(name.clone(), err.clone());
+                    let _ = health_tx.send((name, err));
+                }
+            }
         }
 
         tracing::info!("channel closed, ending task");
     });
 
+    cx.spawn(async move |cx| {
+        while let Some((name, err)) = health_rx.recv().await {
+            let health = match err {
+                Some(err) => ControllerHealth::Failing(err.into()),
+                None => ControllerHealth::Healthy,
+            };
+
+            let result = health_model.update(cx, |m, cx| {
+                m.insert(name, health);
+                cx.notify();
+            });
+
+            if result.is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+
     cx.set_global(PbcHandle(pbc_tx, task));
 }
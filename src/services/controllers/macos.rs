@@ -34,15 +34,14 @@ impl MacMediaPlayerController {
 
             let file_name = path
                 .file_name()
-                .expect("files should have file names")
-                .to_str()
-                .expect("files should have UTF-8 names");
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy();
 
             let media_center = MPNowPlayingInfoCenter::defaultCenter();
             let now_playing: Retained<NSMutableDictionary<NSString>> =
                 NSMutableDictionary::dictionary();
 
-            let ns_name = NSString::from_str(file_name);
+            let ns_name = NSString::from_str(&file_name);
             now_playing
                 .setObject_forKey(&ns_name, ProtocolObject::from_ref(MPMediaItemPropertyTitle));
 
@@ -142,7 +141,10 @@ impl MacMediaPlayerController {
             // the only way that it wouldn't is if, once it disappears in to macOS, the OS drops it
             // there's an even better chance that if it does, there's no way to fix it
             // TODO: figure out this mess
-            let image = NonNull::new(Retained::into_raw(image)).unwrap();
+            let Some(image) = NonNull::new(Retained::into_raw(image)) else {
+                error!("NSImage for album art was null");
+                return;
+            };
 
             let request_handler = RcBlock::new(move |_cg: CGSize| image);
             let bounds_size = CGSize::new(size.width as f64, size.height as f64);
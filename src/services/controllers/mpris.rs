@@ -7,7 +7,7 @@ use async_trait::async_trait;
 use base64::{Engine, prelude::BASE64_STANDARD};
 use mpris_server::{
     LoopStatus, PlaybackRate, PlaybackStatus, PlayerInterface, Property, RootInterface, Server,
-    Signal, Time, Volume,
+    Signal, Time, TrackId, Volume,
 };
 use raw_window_handle::RawWindowHandle;
 use tokio::sync::RwLock;
@@ -31,6 +31,22 @@ pub struct MprisControllerData {
     last_duration: Option<u64>,
     last_volume: Option<f64>,
     last_shuffle: bool,
+    /// Counts the files that have been played, so that each one gets a different track ID even
+    /// if the same file is played twice in a row.
+    track_count: u64,
+}
+
+impl MprisControllerData {
+    /// The track ID of the current file. Bluetooth devices (via BlueZ's `mpris-proxy`) use changes
+    /// in the track ID to notice that the track has changed, so it has to be set.
+    fn track_id(&self) -> TrackId {
+        if self.last_file.is_none() {
+            return TrackId::NO_TRACK;
+        }
+
+        TrackId::try_from(format!("/org/mailliw/hummingbird/track/{}", self.track_count).as_str())
+            .unwrap_or(TrackId::NO_TRACK)
+    }
 }
 
 pub struct MprisControllerServer {
@@ -57,10 +73,18 @@ impl MprisControllerServer {
     async fn metadata_int(&self) -> fdo::Result<mpris_server::Metadata> {
         let data = self.data.read().await;
 
-        if let Some(metadata) = &data.last_mdata {
-            let mut mpris_data = mpris_server::Metadata::new();
+        let mut mpris_data = mpris_server::Metadata::new();
+        mpris_data.set_trackid(Some(data.track_id()));
+
+        // car head units and Bluetooth speakers show nothing at all without a title
+        let file_name = data
+            .last_file
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|name| name.to_string_lossy().to_string());
 
-            mpris_data.set_title(metadata.name.clone());
+        if let Some(metadata) = &data.last_mdata {
+            mpris_data.set_title(metadata.name.clone().or(file_name));
             mpris_data.set_album(metadata.album.clone());
             mpris_data.set_artist(metadata.artist.clone().map(|v| [v]));
             mpris_data.set_album_artist(metadata.album_artist.clone().map(|v| [v]));
@@ -70,11 +94,12 @@ impl MprisControllerServer {
             mpris_data.set_disc_number(metadata.disc_current.map(|v| v as i32));
             mpris_data.set_length(data.last_duration.map(|v| Time::from_secs(v as i64)));
             mpris_data.set_art_url(data.last_album_art.clone());
-
-            Ok(mpris_data)
         } else {
-            Ok(mpris_server::Metadata::new())
+            mpris_data.set_title(file_name);
+            mpris_data.set_length(data.last_duration.map(|v| Time::from_secs(v as i64)));
         }
+
+        Ok(mpris_data)
     }
 
     async fn playback_status_int(&self) -> fdo::Result<PlaybackStatus> {
@@ -243,11 +268,12 @@ impl PlayerInterface for MprisControllerServer {
         Ok(())
     }
 
-    async fn set_position(
-        &self,
-        _track_id: mpris_server::TrackId, // TODO: handle this?
-        position: Time,
-    ) -> fdo::Result<()> {
+    async fn set_position(&self, track_id: TrackId, position: Time) -> fdo::Result<()> {
+        // the spec asks for requests meant for a previous track to be ignored
+        if track_id != self.data.read().await.track_id() {
+            return Ok(());
+        }
+
         let position = position.as_secs();
         self.bridge.seek(position as f64);
 
@@ -338,6 +364,7 @@ impl InitPlaybackController for MprisController {
             last_volume: None,
             last_shuffle: false,
             last_album_art: None,
+            track_count: 0,
         }));
 
         let server_data = data.clone();
@@ -356,19 +383,18 @@ impl InitPlaybackController for MprisController {
 impl PlaybackController for MprisController {
     async fn position_changed(&mut self, new_position: u64) -> anyhow::Result<()> {
         let mut data = self.data.write().await;
-        let original_position = data.last_position;
+        // a new file starts from zero, unless it was resumed from somewhere else
+        let original_position = data.last_position.unwrap_or_default();
 
         data.last_position = Some(new_position);
 
-        if let Some(original_position) = original_position {
-            let position_diff = new_position as i64 - original_position as i64;
-            if position_diff != 1 {
-                self.server
-                    .emit(Signal::Seeked {
-                        position: Time::from_secs(new_position as i64),
-                    })
-                    .await?;
-            }
+        let position_diff = new_position as i64 - original_position as i64;
+        if !(0..=1).contains(&position_diff) {
+            self.server
+                .emit(Signal::Seeked {
+                    position: Time::from_secs(new_position as i64),
+                })
+                .await?;
         }
 
         Ok(())
@@ -380,9 +406,7 @@ impl PlaybackController for MprisController {
         drop(data);
 
         self.server
-            .properties_changed([Property::Metadata(
-                self.server.imp().metadata_int().await.unwrap(),
-            )])
+            .properties_changed([Property::Metadata(self.server.imp().metadata_int().await?)])
             .await?;
 
         Ok(())
@@ -405,9 +429,7 @@ impl PlaybackController for MprisController {
         drop(data);
 
         self.server
-            .properties_changed([Property::Metadata(
-                self.server.imp().metadata_int().await.unwrap(),
-            )])
+            .properties_changed([Property::Metadata(self.server.imp().metadata_int().await?)])
             .await?;
 
         Ok(())
@@ -425,9 +447,7 @@ impl PlaybackController for MprisController {
         drop(data);
 
         self.server
-            .properties_changed([Property::Metadata(
-                self.server.imp().metadata_int().await.unwrap(),
-            )])
+            .properties_changed([Property::Metadata(self.server.imp().metadata_int().await?)])
             .await?;
 
         Ok(())
@@ -440,7 +460,7 @@ impl PlaybackController for MprisController {
 
         self.server
             .properties_changed([Property::LoopStatus(
-                self.server.imp().loop_status_int().await.unwrap(),
+                self.server.imp().loop_status_int().await?,
             )])
             .await?;
 
@@ -457,10 +477,10 @@ impl PlaybackController for MprisController {
 
         self.server
             .properties_changed([
-                Property::PlaybackStatus(self.server.imp().playback_status_int().await.unwrap()),
-                Property::CanPause(self.server.imp().can_pause_int().await.unwrap()),
-                Property::CanPlay(self.server.imp().can_play_int().await.unwrap()),
-                Property::CanSeek(self.server.imp().can_seek_int().await.unwrap()),
+                Property::PlaybackStatus(self.server.imp().playback_status_int().await?),
+                Property::CanPause(self.server.imp().can_pause_int().await?),
+                Property::CanPlay(self.server.imp().can_play_int().await?),
+                Property::CanSeek(self.server.imp().can_seek_int().await?),
             ])
             .await?;
 
@@ -485,18 +505,28 @@ impl PlaybackController for MprisController {
         data.last_duration = None;
         data.last_mdata = None;
         data.last_album_art = None;
+        data.track_count += 1;
         drop(data);
 
         self.server
             .properties_changed([
-                Property::PlaybackStatus(self.server.imp().playback_status_int().await.unwrap()),
-                Property::CanPause(self.server.imp().can_pause_int().await.unwrap()),
-                Property::CanPlay(self.server.imp().can_play_int().await.unwrap()),
-                Property::CanSeek(self.server.imp().can_seek_int().await.unwrap()),
-                Property::Metadata(self.server.imp().metadata_int().await.unwrap()),
+                Property::PlaybackStatus(self.server.imp().playback_status_int().await?),
+                Property::CanPause(self.server.imp().can_pause_int().await?),
+                Property::CanPlay(self.server.imp().can_play_int().await?),
+                Property::CanSeek(self.server.imp().can_seek_int().await?),
+                Property::Metadata(self.server.imp().metadata_int().await?),
             ])
             .await?;
 
+        // the position isn't sent again until it changes, and clients that track the position
+        // themselves (like BlueZ, for Bluetooth devices) would otherwise carry on from where the
+        // last track was
+        self.server
+            .emit(Signal::Seeked {
+                position: Time::ZERO,
+            })
+            .await?;
+
         Ok(())
     }
 }
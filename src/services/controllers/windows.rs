@@ -43,7 +43,10 @@ impl WindowsController {
             SystemMediaTransportControls,
             SystemMediaTransportControlsButtonPressedEventArgs,
         >::new(move |_, args| {
-            let event = args.as_ref().unwrap().Button().unwrap();
+            let Some(args) = args.as_ref() else {
+                return Ok(());
+            };
+            let event = args.Button()?;
 
             match event {
                 SystemMediaTransportControlsButton::Play => bridge.play(),
@@ -72,12 +75,14 @@ impl InitPlaybackController for WindowsController {
 
         let hwnd = match handle {
             Some(RawWindowHandle::Win32(handle)) => handle,
-            _ => panic!("non-Win32 window handle/invalid window handle during creation of SMTC"),
+            _ => anyhow::bail!(
+                "non-Win32 window handle/invalid window handle during creation of SMTC"
+            ),
         };
 
         let controls: SystemMediaTransportControls = unsafe {
             let pointer = hwnd.hwnd.get() as *mut c_void;
-            interop.GetForWindow(HWND(pointer)).unwrap()
+            interop.GetForWindow(HWND(pointer))?
         };
 
         let display = controls.DisplayUpdater()?;
@@ -124,36 +129,29 @@ impl PlaybackController for WindowsController {
     }
 
     async fn metadata_changed(&mut self, metadata: &Metadata) -> anyhow::Result<()> {
+        let properties = self.display.MusicProperties()?;
+
         if let Some(title) = metadata.name.clone() {
             let string = HSTRING::from(title);
-            self.display.MusicProperties().unwrap().SetTitle(&string)?;
+            properties.SetTitle(&string)?;
         }
 
         if let Some(artist) = metadata.artist.clone() {
             let string = HSTRING::from(artist);
-            self.display.MusicProperties().unwrap().SetArtist(&string)?;
+            properties.SetArtist(&string)?;
         }
 
         if let Some(album) = metadata.album.clone() {
             let string = HSTRING::from(album);
-            self.display
-                .MusicProperties()
-                .unwrap()
-                .SetAlbumTitle(&string)?;
+            properties.SetAlbumTitle(&string)?;
         }
 
         if let Some(track_number) = metadata.track_current {
-            self.display
-                .MusicProperties()
-                .unwrap()
-                .SetTrackNumber(track_number as u32)?;
+            properties.SetTrackNumber(track_number as u32)?;
         }
 
         if let Some(track_max) = metadata.track_max {
-            self.display
-                .MusicProperties()
-                .unwrap()
-                .SetAlbumTrackCount(track_max as u32)?;
+            properties.SetAlbumTrackCount(track_max as u32)?;
         }
 
         self.display.Update()?;
@@ -162,15 +160,12 @@ impl PlaybackController for WindowsController {
     }
 
     async fn album_art_changed(&mut self, album_art: &[u8]) -> anyhow::Result<()> {
-        let stream = InMemoryRandomAccessStream::new().expect("could not create RAS");
-        let writer = DataWriter::CreateDataWriter(&stream).unwrap();
+        let stream = InMemoryRandomAccessStream::new()?;
+        let writer = DataWriter::CreateDataWriter(&stream)?;
 
         writer.WriteBytes(album_art)?;
 
-        writer
-            .StoreAsync()
-            .expect("could not start store operation")
-            .await?;
+        writer.StoreAsync()?.await?;
 
         writer.DetachStream()?;
         let reference = RandomAccessStreamReference::CreateFromStream(&stream)?;
@@ -213,11 +208,9 @@ impl PlaybackController for WindowsController {
     async fn new_file(&mut self, path: &Path) -> anyhow::Result<()> {
         self.display.ClearAll()?;
         self.display.SetType(MediaPlaybackType::Music)?;
-        let title_string = HSTRING::from(path.file_name().unwrap().to_str().unwrap());
-        self.display
-            .MusicProperties()
-            .unwrap()
-            .SetTitle(&title_string)?;
+        let file_name = path.file_name().unwrap_or(path.as_os_str());
+        let title_string = HSTRING::from(file_name.to_string_lossy().as_ref());
+        self.display.MusicProperties()?.SetTitle(&title_string)?;
         self.display.Update()?;
 
         Ok(())
@@ -0,0 +1,440 @@
+mod http;
+pub mod server;
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::playback::renderer::{
+    Renderer, RendererMedia, RendererState, RendererStatus,
+    server::{MediaServer, content_type},
+};
+
+use self::http::{escape, soap};
+
+const SSDP_ADDRESS: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(239, 255, 255, 250)), 1900);
+
+const MEDIA_RENDERER: &str = "urn:schemas-upnp-org:device:MediaRenderer:1";
+const AV_TRANSPORT: &str = "urn:schemas-upnp-org:service:AVTransport:1";
+const RENDERING_CONTROL: &str = "urn:schemas-upnp-org:service:RenderingControl:1";
+
+/// Each status update takes two requests, so the device is asked less often than the playback
+/// thread polls, and the position is extrapolated in between.
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a track can take to start playing after it's loaded before the device is considered
+/// to have given up on it.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(15);
+/// Requests are occasionally dropped by devices on busy networks, so the connection is only
+/// considered lost after this many status updates fail in a row.
+const MAX_FAILURES: u32 = 3;
+
+/// A DLNA renderer found on the network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DlnaDevice {
+    pub name: String,
+    /// The control URL of the device's AVTransport service.
+    pub av_transport: Url,
+    /// The control URL of the device's RenderingControl service, if it has one.
+    pub rendering_control: Option<Url>,
+}
+
+/// Looks for DLNA renderers on the local network for the given amount of time.
+pub fn discover(timeout: Duration) -> anyhow::Result<Vec<DlnaDevice>> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {SSDP_ADDRESS}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\n\
+        ST: {MEDIA_RENDERER}\r\n\r\n"
+    );
+
+    // searches are sent over UDP, so they're repeated in case one is lost
+    for _ in 0..2 {
+        socket.send_to(search.as_bytes(), SSDP_ADDRESS)?;
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut buffer = [0; 2048];
+    let mut locations: Vec<Url> = Vec::new();
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let read = match socket.recv_from(&mut buffer) {
+            Ok((read, _)) => read,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                break;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let response = String::from_utf8_lossy(&buffer[..read]);
+        let location = response.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("location")
+                .then(|| Url::parse(value.trim()).ok())?
+        });
+
+        if let Some(location) = location
+            && !locations.contains(&location)
+        {
+            locations.push(location);
+        }
+    }
+
+    let devices = locations
+        .iter()
+        .filter_map(|location| {
+            describe(location)
+                .inspect_err(|err| debug!("Could not describe DLNA device at {location}: {err}"))
+                .ok()
+        })
+        .collect();
+
+    Ok(devices)
+}
+
+/// Reads the device description, to find its name and the services needed to control it.
+fn describe(location: &Url) -> anyhow::Result<DlnaDevice> {
+    let response = http::request("GET", location, &[], "")?;
+    let document = roxmltree::Document::parse(&response.body)?;
+
+    let text = |name: &str| {
+        document
+            .descendants()
+            .find(|node| node.has_tag_name(name))
+            .and_then(|node| node.text())
+            .map(|text| text.trim().to_string())
+    };
+
+    let base = text("URLBase")
+        .and_then(|base| Url::parse(&base).ok())
+        .unwrap_or_else(|| location.clone());
+    let name = text("friendlyName").unwrap_or_else(|| base.to_string());
+
+    let control_url = |service_type: &str| {
+        document
+            .descendants()
+            .filter(|node| node.has_tag_name("service"))
+            .find(|service| {
+                service.children().any(|child| {
+                    child.has_tag_name("serviceType") && child.text() == Some(service_type)
+                })
+            })
+            .and_then(|service| {
+                service
+                    .children()
+                    .find(|child| child.has_tag_name("controlURL"))
+            })
+            .and_then(|node| node.text())
+            .and_then(|url| base.join(url.trim()).ok())
+    };
+
+    let av_transport = control_url(AV_TRANSPORT).context("the device can't play media")?;
+    let rendering_control = control_url(RENDERING_CONTROL);
+
+    debug!("Found DLNA device {name} at {location}");
+
+    Ok(DlnaDevice {
+        name,
+        av_transport,
+        rendering_control,
+    })
+}
+
+/// Formats a position in seconds as `H:MM:SS`, which is how UPnP describes times.
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+/// Parses a `H:MM:SS` or `H:MM:SS.F` time into seconds.
+fn parse_time(time: &str) -> Option<f64> {
+    let mut parts = time.trim().split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Describes a track as a DIDL-Lite item, which is how UPnP devices exchange metadata.
+fn didl_item(id: &str, parent: &str, media: &RendererMedia, url: &str, path: &Path) -> String {
+    let mut item = format!(
+        "<item id=\"{}\" parentID=\"{}\" restricted=\"1\"><dc:title>{}</dc:title>",
+        escape(id),
+        escape(parent),
+        escape(media.title.as_deref().unwrap_or_default())
+    );
+
+    if let Some(artist) = &media.artist {
+        item += &format!(
+            "<upnp:artist>{0}</upnp:artist><dc:creator>{0}</dc:creator>",
+            escape(artist)
+        );
+    }
+    if let Some(album) = &media.album {
+        item += &format!("<upnp:album>{}</upnp:album>", escape(album));
+    }
+
+    item += "<upnp:class>object.item.audioItem.musicTrack</upnp:class>";
+
+    let duration = media
+        .duration
+        .map(|duration| format!(" duration=\"{}.000\"", format_time(duration)))
+        .unwrap_or_default();
+    item += &format!(
+        "<res protocolInfo=\"http-get:*:{}:*\"{duration}>{}</res></item>",
+        content_type(path),
+        escape(url)
+    );
+
+    item
+}
+
+/// Wraps DIDL-Lite items or containers in a document.
+fn didl(objects: &str) -> String {
+    format!(
+        "<DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" \
+        xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+        xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\">{objects}</DIDL-Lite>"
+    )
+}
+
+/// Plays tracks on a DLNA renderer. Like Cast devices, the renderer fetches each file from the
+/// [MediaServer].
+pub struct DlnaRenderer {
+    device: DlnaDevice,
+    ip: IpAddr,
+    status: RendererStatus,
+    /// When the status was last received, so the position can be extrapolated while playing.
+    status_time: Instant,
+    /// When the current track was loaded, if it hasn't started playing yet. Renderers report that
+    /// they're stopped until then.
+    loading: Option<Instant>,
+    failures: u32,
+}
+
+impl DlnaRenderer {
+    /// Connects to the device, making sure that it responds.
+    pub fn connect(device: &DlnaDevice) -> anyhow::Result<Self> {
+        info!("Connecting to DLNA device {}", device.name);
+
+        let ip = device
+            .av_transport
+            .socket_addrs(|| Some(80))?
+            .first()
+            .context("could not resolve the device's address")?
+            .ip();
+
+        let renderer = DlnaRenderer {
+            device: device.clone(),
+            ip,
+            status: RendererStatus {
+                state: RendererState::Idle,
+                position: 0.0,
+            },
+            status_time: Instant::now(),
+            loading: None,
+            failures: 0,
+        };
+        renderer.transport("GetTransportInfo", &[])?;
+
+        Ok(renderer)
+    }
+
+    fn transport(
+        &self,
+        action: &str,
+        arguments: &[(&str, &str)],
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let mut all = vec![("InstanceID", "0")];
+        all.extend_from_slice(arguments);
+        soap(&self.device.av_transport, AV_TRANSPORT, action, &all)
+    }
+
+    fn set_status(&mut self, state: RendererState, position: f64) {
+        self.status = RendererStatus { state, position };
+        self.status_time = Instant::now();
+    }
+
+    fn position(&self) -> f64 {
+        if self.status.state == RendererState::Playing {
+            self.status.position + self.status_time.elapsed().as_secs_f64()
+        } else {
+            self.status.position
+        }
+    }
+
+    fn update_status(&mut self) -> anyhow::Result<()> {
+        let info = self.transport("GetTransportInfo", &[])?;
+        let position = self.transport("GetPositionInfo", &[])?;
+
+        let reported = match info
+            .get("CurrentTransportState")
+            .map(String::as_str)
+            .unwrap_or_default()
+        {
+            "PLAYING" => RendererState::Playing,
+            "PAUSED_PLAYBACK" | "PAUSED_RECORDING" => RendererState::Paused,
+            "TRANSITIONING" => RendererState::Buffering,
+            _ => RendererState::Idle,
+        };
+
+        let state = match (reported, self.loading) {
+            (RendererState::Playing | RendererState::Paused, _) => {
+                self.loading = None;
+                reported
+            }
+            (_, Some(loaded)) if loaded.elapsed() < LOAD_TIMEOUT => RendererState::Buffering,
+            // renderers stop once they reach the end of the track, so stopping while playing is
+            // taken as the track finishing
+            (RendererState::Idle, _)
+                if matches!(
+                    self.status.state,
+                    RendererState::Playing | RendererState::Finished
+                ) =>
+            {
+                RendererState::Finished
+            }
+            _ => reported,
+        };
+
+        let position = position
+            .get("RelTime")
+            .and_then(|time| parse_time(time))
+            .unwrap_or_else(|| self.position());
+        self.set_status(state, position);
+
+        Ok(())
+    }
+}
+
+impl Renderer for DlnaRenderer {
+    fn name(&self) -> &str {
+        &self.device.name
+    }
+
+    fn load(&mut self, path: &Path, media: &RendererMedia, position: f64) -> anyhow::Result<()> {
+        let url = MediaServer::shared()?.url_for(path, self.ip)?;
+        debug!("Sending {:?} to {} from {url}", path, self.device.name);
+
+        let metadata = didl(&didl_item("0", "-1", media, &url, path));
+        self.transport(
+            "SetAVTransportURI",
+            &[("CurrentURI", &url), ("CurrentURIMetaData", &metadata)],
+        )?;
+        self.transport("Play", &[("Speed", "1")])?;
+
+        if position >= 1.0 {
+            let target = format_time(position);
+            if let Err(err) = self.transport("Seek", &[("Unit", "REL_TIME"), ("Target", &target)]) {
+                warn!("{} could not seek to {target}: {err}", self.device.name);
+            }
+        }
+
+        self.loading = Some(Instant::now());
+        self.set_status(RendererState::Buffering, position);
+
+        Ok(())
+    }
+
+    fn play(&mut self) -> anyhow::Result<()> {
+        self.set_status(RendererState::Playing, self.position());
+        self.transport("Play", &[("Speed", "1")])?;
+
+        Ok(())
+    }
+
+    fn pause(&mut self) -> anyhow::Result<()> {
+        self.set_status(RendererState::Paused, self.position());
+        self.transport("Pause", &[])?;
+
+        Ok(())
+    }
+
+    fn seek(&mut self, position: f64) -> anyhow::Result<()> {
+        let state = self.status.state;
+        self.set_status(state, position);
+        self.transport(
+            "Seek",
+            &[("Unit", "REL_TIME"), ("Target", &format_time(position))],
+        )?;
+
+        Ok(())
+    }
+
+    fn set_volume(&mut self, volume: f64) -> anyhow::Result<()> {
+        let Some(control) = &self.device.rendering_control else {
+            return Ok(());
+        };
+
+        let volume = ((volume.clamp(0.0, 1.0) * 100.0).round() as u32).to_string();
+        soap(
+            control,
+            RENDERING_CONTROL,
+            "SetVolume",
+            &[
+                ("InstanceID", "0"),
+                ("Channel", "Master"),
+                ("DesiredVolume", &volume),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        self.loading = None;
+        self.set_status(RendererState::Idle, 0.0);
+        self.transport("Stop", &[])?;
+
+        Ok(())
+    }
+
+    fn status(&mut self) -> anyhow::Result<RendererStatus> {
+        if self.status_time.elapsed() >= STATUS_INTERVAL {
+            match self.update_status() {
+                Ok(()) => self.failures = 0,
+                Err(err) => {
+                    self.failures += 1;
+                    if self.failures >= MAX_FAILURES {
+                        return Err(err);
+                    }
+
+                    debug!("Could not get the status of {}: {err}", self.device.name);
+                    // try again after the usual interval
+                    let state = self.status.state;
+                    self.set_status(state, self.position());
+                }
+            }
+        }
+
+        Ok(RendererStatus {
+            state: self.status.state,
+            position: self.position(),
+        })
+    }
+}
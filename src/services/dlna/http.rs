@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+use url::{Position, Url};
+
+/// UPnP devices are on the local network, so anything slower than this has gone away.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Makes a blocking HTTP request. The connection is closed afterwards, since requests to UPnP
+/// devices are few and far between.
+pub fn request(
+    method: &str,
+    url: &Url,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> anyhow::Result<Response> {
+    let address = *url
+        .socket_addrs(|| Some(80))?
+        .first()
+        .context("could not resolve the device's address")?;
+
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut head = format!(
+        "{method} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        &url[Position::BeforePath..Position::AfterQuery],
+        &url[Position::BeforeHost..Position::AfterPort],
+        body.len()
+    );
+    for (name, value) in headers {
+        head += &format!("{name}: {value}\r\n");
+    }
+    head += "\r\n";
+
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .context("the device sent an invalid response")?;
+
+    let mut length = None;
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse::<usize>().ok();
+        } else if name.trim().eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.trim().eq_ignore_ascii_case("chunked");
+        }
+    }
+
+    let mut body = Vec::new();
+    if chunked {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size)?;
+            let size = size.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size, 16).context("invalid chunk in response")?;
+            if size == 0 {
+                break;
+            }
+
+            let start = body.len();
+            body.resize(start + size, 0);
+            reader.read_exact(&mut body[start..])?;
+            // the line break after the chunk
+            reader.read_line(&mut String::new())?;
+        }
+    } else if let Some(length) = length {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    } else {
+        reader.read_to_end(&mut body)?;
+    }
+
+    Ok(Response {
+        status,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/// Escapes text so that it can be placed in an XML document.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+/// Calls an action on a UPnP service, and returns its output arguments.
+pub fn soap(
+    control: &Url,
+    service: &str,
+    action: &str,
+    arguments: &[(&str, &str)],
+) -> anyhow::Result<HashMap<String, String>> {
+    let arguments: String = arguments
+        .iter()
+        .map(|(name, value)| format!("<{name}>{}</{name}>", escape(value)))
+        .collect();
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+        <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+        s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+        <s:Body><u:{action} xmlns:u=\"{service}\">{arguments}</u:{action}></s:Body>\
+        </s:Envelope>"
+    );
+    let soap_action = format!("\"{service}#{action}\"");
+
+    let response = request(
+        "POST",
+        control,
+        &[
+            ("Content-Type", "text/xml; charset=\"utf-8\""),
+            ("SOAPAction", &soap_action),
+        ],
+        &body,
+    )?;
+
+    if response.status != 200 {
+        let document = roxmltree::Document::parse(&response.body);
+        let description = document
+            .as_ref()
+            .ok()
+            .and_then(|document| {
+                document
+                    .descendants()
+                    .find(|node| node.has_tag_name("errorDescription"))
+            })
+            .and_then(|node| node.text())
+            .unwrap_or("unknown error");
+        bail!("{action} failed: {description} ({})", response.status);
+    }
+
+    let document = roxmltree::Document::parse(&response.body)
+        .with_context(|| format!("invalid response to {action}"))?;
+
+    let response_name = format!("{action}Response");
+    let outputs = document
+        .descendants()
+        .find(|node| node.tag_name().name() == response_name)
+        .map(|node| {
+            node.children()
+                .filter(|child| child.is_element())
+                .map(|child| {
+                    (
+                        child.tag_name().name().to_string(),
+                        child.text().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(outputs)
+}
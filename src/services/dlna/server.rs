@@ -0,0 +1,824 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufReader},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use socket2::{Domain, Protocol, Socket, Type};
+use sqlx::SqlitePool;
+use tracing::{debug, info, warn};
+
+use crate::{
+    library::remote::{self, is_remote},
+    playback::renderer::{
+        RendererMedia,
+        server::{MAX_BODY, Request, local_ip_for, read_request, respond, serve_file, write_head},
+    },
+    settings::sharing::SharingSettings,
+};
+
+use super::{SSDP_ADDRESS, didl, didl_item, http::escape};
+
+const MEDIA_SERVER: &str = "urn:schemas-upnp-org:device:MediaServer:1";
+const CONTENT_DIRECTORY: &str = "urn:schemas-upnp-org:service:ContentDirectory:1";
+const CONNECTION_MANAGER: &str = "urn:schemas-upnp-org:service:ConnectionManager:1";
+
+/// Advertisements expire after 30 minutes, so they're repeated well before then.
+const NOTIFY_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// The formats clients are told the server can send.
+const PROTOCOLS: &str = "http-get:*:audio/mpeg:*,http-get:*:audio/flac:*,http-get:*:audio/ogg:*,\
+    http-get:*:audio/mp4:*,http-get:*:audio/wav:*,http-get:*:audio/aiff:*,\
+    http-get:*:audio/x-wavpack:*";
+
+const CONTENT_DIRECTORY_SCPD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<scpd xmlns="urn:schemas-upnp-org:service-1-0">
+<specVersion><major>1</major><minor>0</minor></specVersion>
+<actionList>
+<action><name>Browse</name><argumentList>
+<argument><name>ObjectID</name><direction>in</direction><relatedStateVariable>A_ARG_TYPE_ObjectID</relatedStateVariable></argument>
+<argument><name>BrowseFlag</name><direction>in</direction><relatedStateVariable>A_ARG_TYPE_BrowseFlag</relatedStateVariable></argument>
+<argument><name>Filter</name><direction>in</direction><relatedStateVariable>A_ARG_TYPE_Filter</relatedStateVariable></argument>
+<argument><name>StartingIndex</name><direction>in</direction><relatedStateVariable>A_ARG_TYPE_Index</relatedStateVariable></argument>
+<argument><name>RequestedCount</name><direction>in</direction><relatedStateVariable>A_ARG_TYPE_Count</relatedStateVariable></argument>
+<argument><name>SortCriteria</name><direction>in</direction><relatedStateVariable>A_ARG_TYPE_SortCriteria</relatedStateVariable></argument>
+<argument><name>Result</name><direction>out</direction><relatedStateVariable>A_ARG_TYPE_Result</relatedStateVariable></argument>
+<argument><name>NumberReturned</name><direction>out</direction><relatedStateVariable>A_ARG_TYPE_Count</relatedStateVariable></argument>
+<argument><name>TotalMatches</name><direction>out</direction><relatedStateVariable>A_ARG_TYPE_Count</relatedStateVariable></argument>
+<argument><name>UpdateID</name><direction>out</direction><relatedStateVariable>A_ARG_TYPE_UpdateID</relatedStateVariable></argument>
+</argumentList></action>
+<action><name>GetSearchCapabilities</name><argumentList>
+<argument><name>SearchCaps</name><direction>out</direction><relatedStateVariable>SearchCapabilities</relatedStateVariable></argument>
+</argumentList></action>
+<action><name>GetSortCapabilities</name><argumentList>
+<argument><name>SortCaps</name><direction>out</direction><relatedStateVariable>SortCapabilities</relatedStateVariable></argument>
+</argumentList></action>
+<action><name>GetSystemUpdateID</name><argumentList>
+<argument><name>Id</name><direction>out</direction><relatedStateVariable>SystemUpdateID</relatedStateVariable></argument>
+</argumentList></action>
+</actionList>
+<serviceStateTable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_ObjectID</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_BrowseFlag</name><dataType>string</dataType><allowedValueList><allowedValue>BrowseMetadata</allowedValue><allowedValue>BrowseDirectChildren</allowedValue></allowedValueList></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_Filter</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_Index</name><dataType>ui4</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_Count</name><dataType>ui4</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_SortCriteria</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_Result</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>A_ARG_TYPE_UpdateID</name><dataType>ui4</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>SearchCapabilities</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="no"><name>SortCapabilities</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="yes"><name>SystemUpdateID</name><dataType>ui4</dataType></stateVariable>
+</serviceStateTable>
+</scpd>"#;
+
+const CONNECTION_MANAGER_SCPD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<scpd xmlns="urn:schemas-upnp-org:service-1-0">
+<specVersion><major>1</major><minor>0</minor></specVersion>
+<actionList>
+<action><name>GetProtocolInfo</name><argumentList>
+<argument><name>Source</name><direction>out</direction><relatedStateVariable>SourceProtocolInfo</relatedStateVariable></argument>
+<argument><name>Sink</name><direction>out</direction><relatedStateVariable>SinkProtocolInfo</relatedStateVariable></argument>
+</argumentList></action>
+<action><name>GetCurrentConnectionIDs</name><argumentList>
+<argument><name>ConnectionIDs</name><direction>out</direction><relatedStateVariable>CurrentConnectionIDs</relatedStateVariable></argument>
+</argumentList></action>
+</actionList>
+<serviceStateTable>
+<stateVariable sendEvents="yes"><name>SourceProtocolInfo</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="yes"><name>SinkProtocolInfo</name><dataType>string</dataType></stateVariable>
+<stateVariable sendEvents="yes"><name>CurrentConnectionIDs</name><dataType>string</dataType></stateVariable>
+</serviceStateTable>
+</scpd>"#;
+
+#[derive(sqlx::FromRow)]
+struct SharedAlbum {
+    id: i64,
+    title: String,
+    artist: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SharedPlaylist {
+    id: i64,
+    name: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct SharedTrack {
+    id: i64,
+    title: String,
+    artist_names: Option<String>,
+    duration: i64,
+    location: String,
+    album_id: Option<i64>,
+    album_title: Option<String>,
+    album_artist: Option<String>,
+}
+
+/// An object in the content directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Object {
+    Root,
+    Albums,
+    Playlists,
+    Album(i64),
+    Playlist(i64),
+    Track(i64),
+}
+
+impl Object {
+    fn parse(id: &str) -> Option<Self> {
+        match id {
+            "0" => Some(Object::Root),
+            "albums" => Some(Object::Albums),
+            "playlists" => Some(Object::Playlists),
+            _ => {
+                let (kind, id) = id.split_once('-')?;
+                let id = id.parse().ok()?;
+
+                match kind {
+                    "album" => Some(Object::Album(id)),
+                    "playlist" => Some(Object::Playlist(id)),
+                    "track" => Some(Object::Track(id)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn id(&self) -> String {
+        match self {
+            Object::Root => "0".to_string(),
+            Object::Albums => "albums".to_string(),
+            Object::Playlists => "playlists".to_string(),
+            Object::Album(id) => format!("album-{id}"),
+            Object::Playlist(id) => format!("playlist-{id}"),
+            Object::Track(id) => format!("track-{id}"),
+        }
+    }
+}
+
+fn container(
+    object: Object,
+    parent: &str,
+    title: &str,
+    class: &str,
+    artist: Option<&str>,
+) -> String {
+    let artist = artist
+        .map(|artist| format!("<upnp:artist>{}</upnp:artist>", escape(artist)))
+        .unwrap_or_default();
+
+    format!(
+        "<container id=\"{}\" parentID=\"{}\" restricted=\"1\" searchable=\"0\">\
+        <dc:title>{}</dc:title>{artist}<upnp:class>{class}</upnp:class></container>",
+        escape(&object.id()),
+        escape(parent),
+        escape(title)
+    )
+}
+
+/// The library, as shared with clients.
+struct Library {
+    pool: SqlitePool,
+    name: String,
+    uuid: String,
+    port: u16,
+    /// How much of the cache files in remote folders can take up when they're downloaded to be
+    /// served, in bytes.
+    cache_size: u64,
+}
+
+impl Library {
+    fn description(&self) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0" xmlns:dlna="urn:schemas-dlna-org:device-1-0">
+<specVersion><major>1</major><minor>0</minor></specVersion>
+<device>
+<deviceType>{MEDIA_SERVER}</deviceType>
+<friendlyName>{}</friendlyName>
+<manufacturer>Hummingbird</manufacturer>
+<modelName>Hummingbird</modelName>
+<modelNumber>{}</modelNumber>
+<UDN>uuid:{}</UDN>
+<dlna:X_DLNADOC>DMS-1.50</dlna:X_DLNADOC>
+<serviceList>
+<service>
+<serviceType>{CONTENT_DIRECTORY}</serviceType>
+<serviceId>urn:upnp-org:serviceId:ContentDirectory</serviceId>
+<SCPDURL>/ContentDirectory.xml</SCPDURL>
+<controlURL>/control/ContentDirectory</controlURL>
+<eventSubURL>/events/ContentDirectory</eventSubURL>
+</service>
+<service>
+<serviceType>{CONNECTION_MANAGER}</serviceType>
+<serviceId>urn:upnp-org:serviceId:ConnectionManager</serviceId>
+<SCPDURL>/ConnectionManager.xml</SCPDURL>
+<controlURL>/control/ConnectionManager</controlURL>
+<eventSubURL>/events/ConnectionManager</eventSubURL>
+</service>
+</serviceList>
+</device>
+</root>"#,
+            escape(&self.name),
+            env!("CARGO_PKG_VERSION"),
+            self.uuid
+        )
+    }
+
+    /// The notification types the server is advertised under, with their unique service names.
+    fn targets(&self) -> Vec<(String, String)> {
+        let uuid = format!("uuid:{}", self.uuid);
+
+        let mut targets = vec![
+            (
+                "upnp:rootdevice".to_string(),
+                format!("{uuid}::upnp:rootdevice"),
+            ),
+            (uuid.clone(), uuid.clone()),
+        ];
+        for kind in [MEDIA_SERVER, CONTENT_DIRECTORY, CONNECTION_MANAGER] {
+            targets.push((kind.to_string(), format!("{uuid}::{kind}")));
+        }
+
+        targets
+    }
+
+    fn track_item(&self, track: &SharedTrack, parent: &str, host: SocketAddr) -> String {
+        let path = PathBuf::from(&track.location);
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("bin")
+            .to_ascii_lowercase();
+        let url = format!("http://{host}/track/{}.{extension}", track.id);
+
+        let media = RendererMedia {
+            title: Some(track.title.clone()),
+            artist: track.artist_names.clone().or(track.album_artist.clone()),
+            album: track.album_title.clone(),
+            duration: Some(track.duration as f64),
+        };
+
+        didl_item(&Object::Track(track.id).id(), parent, &media, &url, &path)
+    }
+
+    async fn tracks(&self, query: &str, id: i64) -> Result<Vec<SharedTrack>, sqlx::Error> {
+        sqlx::query_as(query).bind(id).fetch_all(&self.pool).await
+    }
+
+    /// Returns the DIDL-Lite objects inside the object.
+    async fn children(&self, object: Object, host: SocketAddr) -> anyhow::Result<Vec<String>> {
+        let parent = object.id();
+
+        let children = match object {
+            Object::Root => vec![
+                container(
+                    Object::Albums,
+                    &parent,
+                    "Albums",
+                    "object.container.storageFolder",
+                    None,
+                ),
+                container(
+                    Object::Playlists,
+                    &parent,
+                    "Playlists",
+                    "object.container.storageFolder",
+                    None,
+                ),
+            ],
+            Object::Albums => {
+                let albums: Vec<SharedAlbum> =
+                    sqlx::query_as(include_str!("../../../queries/dlna/list_albums.sql"))
+                        .fetch_all(&self.pool)
+                        .await?;
+
+                albums
+                    .iter()
+                    .map(|album| {
+                        container(
+                            Object::Album(album.id),
+                            &parent,
+                            &album.title,
+                            "object.container.album.musicAlbum",
+                            album.artist.as_deref(),
+                        )
+                    })
+                    .collect()
+            }
+            Object::Playlists => {
+                let playlists: Vec<SharedPlaylist> =
+                    sqlx::query_as(include_str!("../../../queries/dlna/list_playlists.sql"))
+                        .fetch_all(&self.pool)
+                        .await?;
+
+                playlists
+                    .iter()
+                    .map(|playlist| {
+                        container(
+                            Object::Playlist(playlist.id),
+                            &parent,
+                            &playlist.name,
+                            "object.container.playlistContainer",
+                            None,
+                        )
+                    })
+                    .collect()
+            }
+            Object::Album(id) => self
+                .tracks(
+                    include_str!("../../../queries/dlna/list_album_tracks.sql"),
+                    id,
+                )
+                .await?
+                .iter()
+                .map(|track| self.track_item(track, &parent, host))
+                .collect(),
+            Object::Playlist(id) => self
+                .tracks(
+                    include_str!("../../../queries/dlna/list_playlist_tracks.sql"),
+                    id,
+                )
+                .await?
+                .iter()
+                .map(|track| self.track_item(track, &parent, host))
+                .collect(),
+            Object::Track(_) => Vec::new(),
+        };
+
+        Ok(children)
+    }
+
+    /// Returns the DIDL-Lite object describing the object itself.
+    async fn metadata(&self, object: Object, host: SocketAddr) -> anyhow::Result<String> {
+        let folder = "object.container.storageFolder";
+
+        let metadata = match object {
+            Object::Root => container(object, "-1", &self.name, folder, None),
+            Object::Albums => container(object, "0", "Albums", folder, None),
+            Object::Playlists => container(object, "0", "Playlists", folder, None),
+            Object::Album(id) => {
+                let album: SharedAlbum =
+                    sqlx::query_as(include_str!("../../../queries/dlna/find_album.sql"))
+                        .bind(id)
+                        .fetch_one(&self.pool)
+                        .await?;
+
+                container(
+                    object,
+                    "albums",
+                    &album.title,
+                    "object.container.album.musicAlbum",
+                    album.artist.as_deref(),
+                )
+            }
+            Object::Playlist(id) => {
+                let playlist: SharedPlaylist =
+                    sqlx::query_as(include_str!("../../../queries/dlna/find_playlist.sql"))
+                        .bind(id)
+                        .fetch_one(&self.pool)
+                        .await?;
+
+                container(
+                    object,
+                    "playlists",
+                    &playlist.name,
+                    "object.container.playlistContainer",
+                    None,
+                )
+            }
+            Object::Track(id) => {
+                let track = self.track(id).await?;
+                let parent = track
+                    .album_id
+                    .map(|album| Object::Album(album).id())
+                    .unwrap_or_else(|| Object::Albums.id());
+
+                self.track_item(&track, &parent, host)
+            }
+        };
+
+        Ok(metadata)
+    }
+
+    async fn track(&self, id: i64) -> Result<SharedTrack, sqlx::Error> {
+        sqlx::query_as(include_str!("../../../queries/dlna/find_track.sql"))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    fn browse(
+        &self,
+        arguments: &HashMap<String, String>,
+        host: SocketAddr,
+    ) -> anyhow::Result<Vec<(&'static str, String)>> {
+        let argument = |name: &str| arguments.get(name).map(String::as_str).unwrap_or_default();
+
+        let object = Object::parse(argument("ObjectID")).context("no such object")?;
+        let start: usize = argument("StartingIndex").parse().unwrap_or(0);
+        // zero means that every object is wanted
+        let count = match argument("RequestedCount").parse().unwrap_or(0) {
+            0 => usize::MAX,
+            count => count,
+        };
+
+        let (objects, total) = if argument("BrowseFlag") == "BrowseMetadata" {
+            let metadata = crate::RUNTIME.block_on(self.metadata(object, host))?;
+            (metadata, 1)
+        } else {
+            let children = crate::RUNTIME.block_on(self.children(object, host))?;
+            let total = children.len();
+            let page: String = children.into_iter().skip(start).take(count).collect();
+            (page, total)
+        };
+
+        let returned = if argument("BrowseFlag") == "BrowseMetadata" {
+            1
+        } else {
+            total.saturating_sub(start).min(count)
+        };
+
+        Ok(vec![
+            ("Result", didl(&objects)),
+            ("NumberReturned", returned.to_string()),
+            ("TotalMatches", total.to_string()),
+            ("UpdateID", "0".to_string()),
+        ])
+    }
+
+    /// Handles a SOAP request to one of the services, returning the output arguments.
+    fn control(
+        &self,
+        service: &str,
+        action: &str,
+        arguments: &HashMap<String, String>,
+        host: SocketAddr,
+    ) -> anyhow::Result<Vec<(&'static str, String)>> {
+        let outputs = match (service, action) {
+            (CONTENT_DIRECTORY, "Browse") => self.browse(arguments, host)?,
+            (CONTENT_DIRECTORY, "GetSearchCapabilities") => vec![("SearchCaps", String::new())],
+            (CONTENT_DIRECTORY, "GetSortCapabilities") => vec![("SortCaps", String::new())],
+            (CONTENT_DIRECTORY, "GetSystemUpdateID") => vec![("Id", "0".to_string())],
+            (CONNECTION_MANAGER, "GetProtocolInfo") => {
+                vec![("Source", PROTOCOLS.to_string()), ("Sink", String::new())]
+            }
+            (CONNECTION_MANAGER, "GetCurrentConnectionIDs") => {
+                vec![("ConnectionIDs", "0".to_string())]
+            }
+            _ => anyhow::bail!("unsupported action {action}"),
+        };
+
+        Ok(outputs)
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+        let host = stream.local_addr()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+
+        while let Some(request) = read_request(&mut reader, Some(MAX_BODY))? {
+            if request.too_large {
+                // the body is still waiting to be read, so the connection can't be reused
+                return respond(
+                    &mut writer,
+                    "413 Content Too Large",
+                    &[("Connection", "close".to_string())],
+                );
+            }
+
+            self.handle_request(&mut writer, &request, host)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(
+        &self,
+        writer: &mut TcpStream,
+        request: &Request,
+        host: SocketAddr,
+    ) -> io::Result<()> {
+        let target = request.target.as_str();
+
+        match (request.method.as_str(), target) {
+            ("GET" | "HEAD", "/description.xml") => respond_xml(writer, &self.description()),
+            ("GET" | "HEAD", "/ContentDirectory.xml") => {
+                respond_xml(writer, CONTENT_DIRECTORY_SCPD)
+            }
+            ("GET" | "HEAD", "/ConnectionManager.xml") => {
+                respond_xml(writer, CONNECTION_MANAGER_SCPD)
+            }
+            ("POST", "/control/ContentDirectory" | "/control/ConnectionManager") => {
+                let service = if target.ends_with("ContentDirectory") {
+                    CONTENT_DIRECTORY
+                } else {
+                    CONNECTION_MANAGER
+                };
+                let body = String::from_utf8_lossy(&request.body);
+
+                let result = parse_action(&body).and_then(|(action, arguments)| {
+                    let outputs = self.control(service, &action, &arguments, host)?;
+                    Ok((action, outputs))
+                });
+
+                match result {
+                    Ok((action, outputs)) => {
+                        respond_xml(writer, &soap_response(service, &action, &outputs))
+                    }
+                    Err(err) => {
+                        debug!("UPnP request failed: {err}");
+                        respond_soap_error(writer)
+                    }
+                }
+            }
+            // events aren't sent, since the library is only read when it's browsed, but some
+            // clients won't continue without subscribing
+            ("SUBSCRIBE", _) => respond(
+                writer,
+                "200 OK",
+                &[
+                    ("SID", format!("uuid:{:032x}", rand::random::<u128>())),
+                    ("TIMEOUT", "Second-1800".to_string()),
+                ],
+            ),
+            ("UNSUBSCRIBE", _) => respond(writer, "200 OK", &[]),
+            (_, target) if target.starts_with("/track/") => {
+                let id = target
+                    .trim_start_matches("/track/")
+                    .split('.')
+                    .next()
+                    .and_then(|id| id.parse().ok());
+                let track = id.and_then(|id| crate::RUNTIME.block_on(self.track(id)).ok());
+
+                // files in remote folders are served from their copy on this computer
+                let location = track.map(|track| PathBuf::from(track.location));
+                let path = match location {
+                    Some(location) if is_remote(&location) => {
+                        remote::fetch(&location, self.cache_size)
+                            .inspect_err(|err| {
+                                warn!("Could not download {:?} to share it: {err}", location)
+                            })
+                            .ok()
+                    }
+                    location => location,
+                };
+
+                match path {
+                    Some(path) => serve_file(writer, request, &path),
+                    None => respond(writer, "404 Not Found", &[]),
+                }
+            }
+            _ => respond(writer, "404 Not Found", &[]),
+        }
+    }
+
+    /// Answers searches for the server, and announces it when it starts and periodically after.
+    fn advertise(&self, socket: UdpSocket) -> io::Result<()> {
+        let mut buffer = [0; 2048];
+        let mut last_notify: Option<Instant> = None;
+
+        loop {
+            if last_notify.is_none_or(|last| last.elapsed() >= NOTIFY_INTERVAL) {
+                self.notify(&socket);
+                last_notify = Some(Instant::now());
+            }
+
+            let remaining = NOTIFY_INTERVAL.saturating_sub(last_notify.unwrap().elapsed());
+            socket.set_read_timeout(Some(remaining.max(Duration::from_secs(1))))?;
+
+            let (read, peer) = match socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            let message = String::from_utf8_lossy(&buffer[..read]);
+            if !message.starts_with("M-SEARCH") {
+                continue;
+            }
+
+            let Some(search) = message.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.trim()
+                    .eq_ignore_ascii_case("st")
+                    .then(|| value.trim().to_string())
+            }) else {
+                continue;
+            };
+
+            let Ok(ip) = local_ip_for(peer.ip()) else {
+                continue;
+            };
+
+            for (target, usn) in self.targets() {
+                if search != "ssdp:all" && search != target {
+                    continue;
+                }
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=1800\r\nEXT:\r\n\
+                    LOCATION: http://{}/description.xml\r\nSERVER: {}\r\nST: {target}\r\n\
+                    USN: {usn}\r\n\r\n",
+                    SocketAddr::new(ip, self.port),
+                    server_header()
+                );
+                if let Err(err) = socket.send_to(response.as_bytes(), peer) {
+                    debug!("Could not answer search from {peer}: {err}");
+                }
+            }
+        }
+    }
+
+    fn notify(&self, socket: &UdpSocket) {
+        let Ok(ip) = local_ip_for(SSDP_ADDRESS.ip()) else {
+            return;
+        };
+
+        for (target, usn) in self.targets() {
+            let message = format!(
+                "NOTIFY * HTTP/1.1\r\nHOST: {SSDP_ADDRESS}\r\nCACHE-CONTROL: max-age=1800\r\n\
+                LOCATION: http://{}/description.xml\r\nNT: {target}\r\nNTS: ssdp:alive\r\n\
+                SERVER: {}\r\nUSN: {usn}\r\n\r\n",
+                SocketAddr::new(ip, self.port),
+                server_header()
+            );
+            if let Err(err) = socket.send_to(message.as_bytes(), SSDP_ADDRESS) {
+                debug!("Could not announce the library server: {err}");
+            }
+        }
+    }
+}
+
+fn server_header() -> String {
+    format!(
+        "{}/1.0 UPnP/1.0 Hummingbird/{}",
+        std::env::consts::OS,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Reads the action and its arguments from a SOAP request.
+fn parse_action(body: &str) -> anyhow::Result<(String, HashMap<String, String>)> {
+    let document = roxmltree::Document::parse(body)?;
+    let action = document
+        .descendants()
+        .find(|node| node.has_tag_name("Body"))
+        .and_then(|body| body.children().find(|child| child.is_element()))
+        .context("the request has no action")?;
+
+    let arguments = action
+        .children()
+        .filter(|child| child.is_element())
+        .map(|child| {
+            (
+                child.tag_name().name().to_string(),
+                child.text().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+
+    Ok((action.tag_name().name().to_string(), arguments))
+}
+
+fn soap_response(service: &str, action: &str, outputs: &[(&str, String)]) -> String {
+    let outputs: String = outputs
+        .iter()
+        .map(|(name, value)| format!("<{name}>{}</{name}>", escape(value)))
+        .collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+        <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+        s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+        <s:Body><u:{action}Response xmlns:u=\"{service}\">{outputs}</u:{action}Response>\
+        </s:Body></s:Envelope>"
+    )
+}
+
+fn respond_xml(writer: &mut TcpStream, body: &str) -> io::Result<()> {
+    write_head(
+        writer,
+        "200 OK",
+        &[
+            ("Content-Type", "text/xml; charset=\"utf-8\"".to_string()),
+            ("Content-Length", body.len().to_string()),
+        ],
+    )?;
+    io::Write::write_all(writer, body.as_bytes())
+}
+
+fn respond_soap_error(writer: &mut TcpStream) -> io::Result<()> {
+    let body = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+        <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+        s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body><s:Fault>\
+        <faultcode>s:Client</faultcode><faultstring>UPnPError</faultstring><detail>\
+        <UPnPError xmlns=\"urn:schemas-upnp-org:control-1-0\"><errorCode>701</errorCode>\
+        <errorDescription>No such object</errorDescription></UPnPError></detail></s:Fault>\
+        </s:Body></s:Envelope>";
+
+    write_head(
+        writer,
+        "500 Internal Server Error",
+        &[
+            ("Content-Type", "text/xml; charset=\"utf-8\"".to_string()),
+            ("Content-Length", body.len().to_string()),
+        ],
+    )?;
+    io::Write::write_all(writer, body.as_bytes())
+}
+
+/// Joins the SSDP multicast group, sharing the port with any other UPnP software on the machine.
+fn ssdp_socket() -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, SSDP_ADDRESS.port())).into())?;
+
+    let SocketAddr::V4(group) = SSDP_ADDRESS else {
+        unreachable!()
+    };
+    socket.join_multicast_v4(group.ip(), &Ipv4Addr::UNSPECIFIED)?;
+
+    Ok(socket.into())
+}
+
+/// Shares the library on the network as a UPnP media server, so that DLNA clients can browse
+/// the albums and playlists in it and play them. Tracks are served straight from disk, and
+/// tracks in remote folders are downloaded into the cache of up to `cache_size` bytes first.
+pub fn share_library(
+    pool: SqlitePool,
+    settings: &SharingSettings,
+    data_dir: &Path,
+    cache_size: u64,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", 0))?;
+    let socket = ssdp_socket()?;
+
+    // the ID has to stay the same between runs, or clients will see a new server every time
+    let hash = format!(
+        "{:x}",
+        md5::compute(format!("{}\0{}", data_dir.display(), settings.name))
+    );
+    let uuid = format!(
+        "{}-{}-{}-{}-{}",
+        &hash[..8],
+        &hash[8..12],
+        &hash[12..16],
+        &hash[16..20],
+        &hash[20..]
+    );
+
+    let library = Arc::new(Library {
+        pool,
+        name: settings.name.clone(),
+        uuid,
+        port: listener.local_addr()?.port(),
+        cache_size,
+    });
+
+    let http_library = library.clone();
+    std::thread::Builder::new()
+        .name("library_server".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let library = http_library.clone();
+                std::thread::spawn(move || {
+                    if let Err(err) = library.handle_connection(stream) {
+                        debug!("Library server connection failed: {err}");
+                    }
+                });
+            }
+        })?;
+
+    info!(
+        "Sharing the library as {} on port {}",
+        library.name, library.port
+    );
+
+    std::thread::Builder::new()
+        .name("library_ssdp".to_string())
+        .spawn(move || {
+            if let Err(err) = library.advertise(socket) {
+                warn!("The library server stopped advertising itself: {err}");
+            }
+        })?;
+
+    Ok(())
+}
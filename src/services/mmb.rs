@@ -1,4 +1,10 @@
+pub mod history;
+pub mod hooks;
 pub mod lastfm;
+pub mod librefm;
+pub mod listenbrainz;
+pub mod now_playing;
+pub mod scrobbler;
 
 use std::{path::PathBuf, sync::Arc};
 
@@ -15,9 +21,10 @@ use async_trait::async_trait;
 /// that provides scrobbling functionality might want to wait some time before recording the
 /// scrobble.
 ///
-/// Note that MMBS operations can be performed on the UI thread, and thus services should not
-/// perform substantial blocking operations in their MMBS implementations. If, for example, a
-/// network request is needed, use an async function to perform the request.
+/// Each service handles one event at a time on the async runtime, in the order the events were
+/// sent (see [crate::ui::models::MMBSHandle]). Services should not perform substantial blocking
+/// operations, since that holds up the runtime. If, for example, a network request is needed, use
+/// an async function to perform the request.
 #[async_trait]
 pub trait MediaMetadataBroadcastService {
     /// Called when a new track is played.
@@ -32,4 +39,80 @@ pub trait MediaMetadataBroadcastService {
     /// Called when the duration of the currently playing track changes, or when a new track is
     /// played. Time is in seconds.
     async fn duration_changed(&mut self, duration: u64);
+    /// Called when playback moves into a part of the current file that should be treated as a
+    /// track of its own, like the next titled chapter of a mix. The part's metadata follows. Time
+    /// is in seconds, and the duration is unknown for songs in a chained stream.
+    async fn segment_changed(&mut self, _start: u64, _duration: Option<u64>) {}
+    /// Whether the service keeps a record of what's listened to, or shows it to other people.
+    /// These services aren't told about playback while private listening is on.
+    fn shares_listening(&self) -> bool {
+        false
+    }
+
+    /// Whether the service sends plays to a scrobbling service. Audiobook chapters aren't sent to
+    /// these services.
+    fn scrobbles(&self) -> bool {
+        false
+    }
+
+    /// Called when the plays that couldn't be sent earlier should be sent again right away,
+    /// instead of with the next play.
+    async fn retry_scrobbles(&mut self) {}
+    /// Called when a play that hasn't been sent yet should be forgotten, because it was recorded
+    /// by mistake. The play is identified by when it started, as a Unix timestamp.
+    async fn discard_scrobble(&mut self, _timestamp: i64) {}
+}
+
+/// Keeps track of how much of the current track has actually been listened to, so that services
+/// can decide whether it counts as played. Seeking doesn't add to the listened time.
+#[derive(Default)]
+pub struct ListenTimer {
+    accumulated_time: u64,
+    last_position: u64,
+    duration: Option<u64>,
+}
+
+impl ListenTimer {
+    /// Starts timing a new track.
+    pub fn reset(&mut self) {
+        self.accumulated_time = 0;
+        self.last_position = 0;
+    }
+
+    /// Starts timing a part of the current file as a track of its own (see
+    /// [MediaMetadataBroadcastService::segment_changed]).
+    pub fn start_segment(&mut self, duration: Option<u64>) {
+        self.accumulated_time = 0;
+        self.duration = duration;
+    }
+
+    pub fn position_changed(&mut self, position: u64) {
+        if position < self.last_position + 2 && position > self.last_position {
+            self.accumulated_time += position - self.last_position;
+        }
+
+        self.last_position = position;
+    }
+
+    pub fn duration_changed(&mut self, duration: u64) {
+        self.duration = Some(duration);
+    }
+
+    /// The duration of the current track, in seconds, if known.
+    pub fn duration(&self) -> Option<u64> {
+        self.duration
+    }
+
+    /// Whether the track has been played for half of its duration or four minutes, whichever
+    /// comes first. Tracks shorter than 30 seconds are never counted, and tracks of unknown
+    /// length need the full four minutes.
+    pub fn counts_as_played(&self) -> bool {
+        match self.duration {
+            Some(duration) => {
+                duration >= 30
+                    && (self.accumulated_time > duration / 2 || self.accumulated_time > 240)
+            }
+            None => self.accumulated_time > 240,
+        }
+    }
 }
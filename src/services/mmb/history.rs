@@ -0,0 +1,104 @@
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+use crate::{
+    library::db::{get_track_by_location, record_play},
+    media::metadata::Metadata,
+    playback::thread::PlaybackState,
+};
+
+use super::{ListenTimer, MediaMetadataBroadcastService};
+
+/// Records plays of tracks in the library to the play history, using the same rules as
+/// scrobbling. Tracks that aren't in the library are ignored.
+pub struct PlayHistory {
+    read_pool: SqlitePool,
+    write_pool: SqlitePool,
+    path: Option<PathBuf>,
+    start_timestamp: Option<DateTime<Utc>>,
+    timer: ListenTimer,
+    should_record: bool,
+}
+
+impl PlayHistory {
+    pub fn new(read_pool: SqlitePool, write_pool: SqlitePool) -> Self {
+        PlayHistory {
+            read_pool,
+            write_pool,
+            path: None,
+            start_timestamp: None,
+            timer: ListenTimer::default(),
+            should_record: false,
+        }
+    }
+
+    async fn record(&mut self) {
+        self.should_record = false;
+
+        let (Some(path), Some(played_at)) = (&self.path, self.start_timestamp) else {
+            return;
+        };
+
+        let result = match get_track_by_location(&self.read_pool, path).await {
+            Ok(Some(track)) => record_play(&self.write_pool, track.id, played_at).await,
+            Ok(None) => return,
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = result {
+            warn!(?err, "Could not record play: {err}");
+        }
+    }
+}
+
+#[async_trait]
+impl MediaMetadataBroadcastService for PlayHistory {
+    async fn new_track(&mut self, file_path: PathBuf) {
+        if self.should_record {
+            debug!("recording play");
+            self.record().await;
+        }
+
+        self.path = Some(file_path);
+        self.start_timestamp = Some(Utc::now());
+        self.timer.reset();
+    }
+
+    async fn metadata_recieved(&mut self, _: Arc<Metadata>) {}
+
+    async fn state_changed(&mut self, state: PlaybackState) {
+        if self.should_record && state != PlaybackState::Playing {
+            debug!("recording play");
+            self.record().await;
+        }
+    }
+
+    async fn position_changed(&mut self, position: u64) {
+        self.timer.position_changed(position);
+
+        if self.timer.counts_as_played() && !self.should_record && self.path.is_some() {
+            self.should_record = true;
+        }
+    }
+
+    async fn duration_changed(&mut self, duration: u64) {
+        self.timer.duration_changed(duration);
+    }
+
+    fn shares_listening(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for PlayHistory {
+    fn drop(&mut self) {
+        if self.should_record {
+            debug!("recording play before dropping PlayHistory, this will block");
+            crate::RUNTIME.block_on(self.record());
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{
+    media::metadata::Metadata,
+    playback::thread::PlaybackState,
+    services::scripts::run_external_command,
+    settings::scripts::{ExternalCommand, ScriptHooks},
+};
+
+use super::{ListenTimer, MediaMetadataBroadcastService};
+
+/// Runs the user's script hooks when tracks change and when they have been listened to.
+pub struct HookRunner {
+    hooks: ScriptHooks,
+    path: Option<PathBuf>,
+    metadata: Option<Arc<Metadata>>,
+    timer: ListenTimer,
+    scrobbled: bool,
+}
+
+impl HookRunner {
+    pub fn new(hooks: ScriptHooks) -> Self {
+        HookRunner {
+            hooks,
+            path: None,
+            metadata: None,
+            timer: ListenTimer::default(),
+            scrobbled: false,
+        }
+    }
+
+    fn run(&self, commands: &[ExternalCommand]) {
+        for command in commands {
+            run_external_command(command, self.path.as_deref(), self.metadata.as_deref());
+        }
+    }
+}
+
+#[async_trait]
+impl MediaMetadataBroadcastService for HookRunner {
+    async fn new_track(&mut self, file_path: PathBuf) {
+        self.path = Some(file_path);
+        self.metadata = None;
+        self.timer.reset();
+        self.scrobbled = false;
+    }
+
+    async fn segment_changed(&mut self, _: u64, duration: Option<u64>) {
+        self.metadata = None;
+        self.timer.start_segment(duration);
+        self.scrobbled = false;
+    }
+
+    async fn metadata_recieved(&mut self, info: Arc<Metadata>) {
+        // metadata can be sent more than once for the same track
+        let first = self.metadata.is_none();
+        self.metadata = Some(info);
+
+        if first && self.path.is_some() {
+            self.run(&self.hooks.track_changed);
+        }
+    }
+
+    async fn state_changed(&mut self, _: PlaybackState) {}
+
+    async fn position_changed(&mut self, position: u64) {
+        self.timer.position_changed(position);
+
+        if self.timer.counts_as_played() && !self.scrobbled && self.path.is_some() {
+            self.scrobbled = true;
+            self.run(&self.hooks.scrobbled);
+        }
+    }
+
+    async fn duration_changed(&mut self, duration: u64) {
+        self.timer.duration_changed(duration);
+    }
+}
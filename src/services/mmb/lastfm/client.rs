@@ -1,8 +1,11 @@
 use std::collections::BTreeMap;
 
-use chrono::{DateTime, Utc};
+use crate::services::mmb::scrobbler::{Rejected, Scrobble};
 
-use super::types::{GetSession, GetToken, Session};
+use super::types::{
+    GetArtistInfo, GetLovedTracks, GetMobileSession, GetSession, GetSimilarArtists, GetToken,
+    LovedTrack, MobileSession, Session,
+};
 
 pub struct LastFMClient {
     client: zed_reqwest::Client,
@@ -26,7 +29,7 @@ impl LastFMClient {
         }
     }
 
-    pub fn _set_endpoint<U: TryInto<url::Url>>(
+    pub fn set_endpoint<U: TryInto<url::Url>>(
         &mut self,
         endpoint: U,
     ) -> Result<&mut Self, U::Error> {
@@ -94,31 +97,65 @@ impl LastFMClient {
         Ok(session)
     }
 
-    pub async fn scrobble(
+    /// Signs in with a username and password instead of through the browser. Last.fm only allows
+    /// this for some API keys, but other services using the same API (like Libre.fm) don't mind.
+    pub async fn get_mobile_session(
         &mut self,
-        artist: &str,
-        track: &str,
-        timestamp: DateTime<Utc>,
-        album: Option<&str>,
-        duration: Option<u64>,
-    ) -> anyhow::Result<()> {
+        username: &str,
+        password: &str,
+    ) -> anyhow::Result<MobileSession> {
+        let password_hash = format!("{:x}", md5::compute(password));
+        let auth_token = format!("{:x}", md5::compute(format!("{username}{password_hash}")));
+        let req = self.post([
+            ("method", "auth.getMobileSession"),
+            ("username", username),
+            ("authToken", &auth_token),
+        ]);
+        let GetMobileSession { session } = req.send().await?.error_for_status()?.json().await?;
+        Ok(session)
+    }
+
+    /// Scrobbles up to 50 plays at once.
+    pub async fn scrobble(&mut self, scrobbles: &[Scrobble]) -> anyhow::Result<()> {
         let Some(session) = self.auth_session.as_deref() else {
             return Err(anyhow::Error::msg("not logged in"));
         };
+
+        let mut params = Vec::new();
+        for (index, scrobble) in scrobbles.iter().enumerate() {
+            let mut push = |name: &str, value: String| {
+                params.push((format!("{name}[{index}]"), value));
+            };
+
+            push("artist", scrobble.artist.clone());
+            push("track", scrobble.track.clone());
+            push("timestamp", scrobble.timestamp.to_string());
+            if let Some(album) = &scrobble.album {
+                push("album", album.clone());
+            }
+            if let Some(album_artist) = &scrobble.album_artist {
+                push("albumArtist", album_artist.clone());
+            }
+            if let Some(track_number) = scrobble.track_number {
+                push("trackNumber", track_number.to_string());
+            }
+            if let Some(duration) = scrobble.duration {
+                push("duration", duration.to_string());
+            }
+        }
+
         let req = self.post(
-            [
-                ("method", "track.scrobble"),
-                ("artist[0]", artist),
-                ("track[0]", track),
-                ("timestamp[0]", &timestamp.timestamp().to_string()),
-            ]
-            .into_iter()
-            .chain(Some("album[0]").zip(album))
-            .chain(Some("duration[0]").zip(duration.map(|d| d.to_string()).as_deref()))
-            .chain(Some(("sk", session))),
+            [("method", "track.scrobble"), ("sk", session)]
+                .into_iter()
+                .chain(params.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
         );
 
-        req.send().await?.error_for_status()?;
+        let response = req.send().await?;
+        if response.status() == zed_reqwest::StatusCode::BAD_REQUEST {
+            return Err(Rejected(response.text().await.unwrap_or_default()).into());
+        }
+
+        response.error_for_status()?;
         Ok(())
     }
 
@@ -147,4 +184,97 @@ impl LastFMClient {
         req.send().await?.error_for_status()?;
         Ok(())
     }
+
+    /// Returns the summary of the artist's biography, without the link to Last.fm that ends it.
+    pub async fn get_artist_bio(&mut self, artist: &str) -> anyhow::Result<Option<String>> {
+        let req = self.get([
+            ("method", "artist.getinfo"),
+            ("artist", artist),
+            ("autocorrect", "1"),
+        ]);
+        let GetArtistInfo { artist } = req.send().await?.error_for_status()?.json().await?;
+
+        Ok(artist
+            .bio
+            .map(|bio| {
+                let end = bio.summary.find("<a ").unwrap_or(bio.summary.len());
+                bio.summary[..end].trim().to_string()
+            })
+            .filter(|summary| !summary.is_empty()))
+    }
+
+    /// Returns the names of artists similar to the given artist, most similar first.
+    pub async fn get_similar_artists(&mut self, artist: &str) -> anyhow::Result<Vec<String>> {
+        let req = self.get([
+            ("method", "artist.getsimilar"),
+            ("artist", artist),
+            ("autocorrect", "1"),
+            ("limit", "100"),
+        ]);
+        let GetSimilarArtists { similar_artists } =
+            req.send().await?.error_for_status()?.json().await?;
+
+        Ok(similar_artists
+            .artist
+            .into_iter()
+            .map(|artist| artist.name)
+            .collect())
+    }
+
+    /// Returns every track the given user has loved, as tuples of artist and title.
+    pub async fn get_loved_tracks(&mut self, user: &str) -> anyhow::Result<Vec<(String, String)>> {
+        let mut tracks = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let page_str = page.to_string();
+            let req = self.get([
+                ("method", "user.getlovedtracks"),
+                ("user", user),
+                ("limit", "200"),
+                ("page", &page_str),
+            ]);
+            let GetLovedTracks { loved_tracks } =
+                req.send().await?.error_for_status()?.json().await?;
+
+            tracks.extend(
+                loved_tracks
+                    .track
+                    .into_iter()
+                    .map(|LovedTrack { name, artist }| (artist.name, name)),
+            );
+
+            let total_pages: u32 = loved_tracks.attr.total_pages.parse()?;
+            if page >= total_pages {
+                break;
+            }
+
+            page += 1;
+        }
+
+        Ok(tracks)
+    }
+
+    pub async fn love(&mut self, artist: &str, track: &str) -> anyhow::Result<()> {
+        self.set_loved("track.love", artist, track).await
+    }
+
+    pub async fn unlove(&mut self, artist: &str, track: &str) -> anyhow::Result<()> {
+        self.set_loved("track.unlove", artist, track).await
+    }
+
+    async fn set_loved(&mut self, method: &str, artist: &str, track: &str) -> anyhow::Result<()> {
+        let Some(session) = self.auth_session.as_deref() else {
+            return Err(anyhow::Error::msg("not logged in"));
+        };
+        let req = self.post([
+            ("method", method),
+            ("artist", artist),
+            ("track", track),
+            ("sk", session),
+        ]);
+
+        req.send().await?.error_for_status()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+use crate::library::{
+    db::{add_playlist_item, remove_playlist_item},
+    playlist::LIKED_PLAYLIST_ID,
+};
+
+use super::client::LastFMClient;
+
+/// Tracks are matched between the library and Last.fm by their artist and title, ignoring case.
+type TrackKey = (String, String);
+
+fn track_key(artist: &str, title: &str) -> TrackKey {
+    (artist.to_lowercase(), title.to_lowercase())
+}
+
+#[derive(sqlx::FromRow)]
+struct LikedEntry {
+    item_id: i64,
+    artist: String,
+    title: String,
+}
+
+/// The changes made by a sync.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SyncSummary {
+    pub liked: usize,
+    pub unliked: usize,
+    pub loved: usize,
+    pub unloved: usize,
+}
+
+fn read_record(path: &Path) -> FxHashSet<TrackKey> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<Vec<TrackKey>>(&data).ok())
+        .map(|keys| keys.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Syncs the liked tracks playlist with the user's loved tracks on Last.fm, in both directions.
+///
+/// The tracks that were in both places after the last sync are stored in the record file. This is
+/// how conflicts are resolved: a track that is only in one place is removed from it if it was in
+/// both places at the last sync (meaning it was removed from the other), and added to the other
+/// place otherwise. Loved tracks that aren't in the library are ignored, so a track is only
+/// unloved when it's still in the library but no longer liked.
+///
+/// Liked tracks belong to the library, so each library has its own record file.
+pub async fn sync_loved_tracks(
+    client: &mut LastFMClient,
+    user: &str,
+    read_pool: &SqlitePool,
+    write_pool: &SqlitePool,
+    record_path: &Path,
+) -> anyhow::Result<SyncSummary> {
+    let record = read_record(record_path);
+    let mut summary = SyncSummary::default();
+
+    let remote: FxHashMap<TrackKey, (String, String)> = client
+        .get_loved_tracks(user)
+        .await?
+        .into_iter()
+        .map(|(artist, title)| (track_key(&artist, &title), (artist, title)))
+        .collect();
+
+    let query = include_str!("../../../../queries/playlist/list_tracks_for_lastfm_sync.sql");
+    let local: Vec<LikedEntry> = sqlx::query_as(query)
+        .bind(LIKED_PLAYLIST_ID)
+        .fetch_all(read_pool)
+        .await?;
+    let local: FxHashMap<TrackKey, LikedEntry> = local
+        .into_iter()
+        .map(|entry| (track_key(&entry.artist, &entry.title), entry))
+        .collect();
+
+    let mut synced = FxHashSet::default();
+
+    for (key, entry) in &local {
+        if remote.contains_key(key) {
+            synced.insert(key.clone());
+        } else if record.contains(key) {
+            // unloved on last.fm since the last sync
+            remove_playlist_item(write_pool, entry.item_id).await?;
+            summary.unliked += 1;
+        } else if let Err(err) = client.love(&entry.artist, &entry.title).await {
+            warn!(?err, "Could not love track on last.fm: {err}");
+        } else {
+            synced.insert(key.clone());
+            summary.loved += 1;
+        }
+    }
+
+    for (key, (artist, title)) in &remote {
+        if local.contains_key(key) {
+            continue;
+        }
+
+        let query = include_str!("../../../../queries/library/find_track_by_artist_title.sql");
+        let track_id: Option<i64> = sqlx::query_scalar(query)
+            .bind(title)
+            .bind(artist)
+            .fetch_optional(read_pool)
+            .await?;
+
+        let Some(track_id) = track_id else {
+            // this also covers liked tracks that have since left the library, which are left
+            // loved and dropped from the record
+            debug!("loved track {artist} - {title} is not in the library");
+            continue;
+        };
+
+        if record.contains(key) {
+            // unliked in the library since the last sync
+            if let Err(err) = client.unlove(artist, title).await {
+                warn!(?err, "Could not unlove track on last.fm: {err}");
+                synced.insert(key.clone());
+            } else {
+                summary.unloved += 1;
+            }
+        } else {
+            add_playlist_item(write_pool, LIKED_PLAYLIST_ID, track_id).await?;
+            synced.insert(key.clone());
+            summary.liked += 1;
+        }
+    }
+
+    let record: Vec<&TrackKey> = synced.iter().collect();
+    std::fs::write(record_path, serde_json::to_vec(&record)?)?;
+
+    Ok(summary)
+}
@@ -10,9 +10,86 @@ pub struct GetSession {
     pub session: Session,
 }
 
+#[derive(Deserialize)]
+pub struct GetMobileSession {
+    pub session: MobileSession,
+}
+
+/// A session from `auth.getMobileSession`. Services other than Last.fm don't always send the
+/// subscriber field, so it's left out.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MobileSession {
+    pub name: String,
+    pub key: String,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Session {
     pub name: String,
     pub key: String,
     pub subscriber: i8,
 }
+
+#[derive(Deserialize)]
+pub struct GetLovedTracks {
+    #[serde(rename = "lovedtracks")]
+    pub loved_tracks: LovedTracks,
+}
+
+#[derive(Deserialize)]
+pub struct LovedTracks {
+    #[serde(default)]
+    pub track: Vec<LovedTrack>,
+    #[serde(rename = "@attr")]
+    pub attr: PageAttributes,
+}
+
+#[derive(Deserialize)]
+pub struct LovedTrack {
+    pub name: String,
+    pub artist: LovedTrackArtist,
+}
+
+#[derive(Deserialize)]
+pub struct LovedTrackArtist {
+    pub name: String,
+}
+
+/// Last.fm sends these numbers as strings.
+#[derive(Deserialize)]
+pub struct PageAttributes {
+    #[serde(rename = "totalPages")]
+    pub total_pages: String,
+}
+
+#[derive(Deserialize)]
+pub struct GetArtistInfo {
+    pub artist: ArtistInfo,
+}
+
+#[derive(Deserialize)]
+pub struct ArtistInfo {
+    pub bio: Option<ArtistBio>,
+}
+
+#[derive(Deserialize)]
+pub struct ArtistBio {
+    pub summary: String,
+}
+
+#[derive(Deserialize)]
+pub struct GetSimilarArtists {
+    #[serde(rename = "similarartists")]
+    pub similar_artists: SimilarArtists,
+}
+
+#[derive(Deserialize)]
+pub struct SimilarArtists {
+    #[serde(default)]
+    pub artist: Vec<SimilarArtist>,
+}
+
+#[derive(Deserialize)]
+pub struct SimilarArtist {
+    pub name: String,
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tracing::{info, warn};
+
+use super::{
+    lastfm::{client::LastFMClient, types::MobileSession},
+    scrobbler::{Scrobble, Scrobbler},
+};
+
+const LIBREFM_API_URL: &str = "https://libre.fm/2.0/";
+
+/// Libre.fm accepts any API key and secret, so these only identify the app.
+const LIBREFM_API_KEY: &str = "hummingbirdhummingbirdhummingbir";
+const LIBREFM_API_SECRET: &str = "hummingbirdhummingbirdhummingbir";
+
+/// Sends plays to Libre.fm, which uses the same API as Last.fm. Signing in is done with a
+/// username and password, and the session is saved so that the password isn't needed again.
+pub struct LibreFm {
+    client: LastFMClient,
+    username: String,
+    password: Option<String>,
+    session_path: PathBuf,
+    signed_in: bool,
+}
+
+impl LibreFm {
+    pub fn new(username: String, password: Option<String>, session_path: PathBuf) -> Self {
+        let mut client =
+            LastFMClient::new(LIBREFM_API_KEY.to_string(), LIBREFM_API_SECRET.to_string());
+        client
+            .set_endpoint(LIBREFM_API_URL)
+            .expect("the Libre.fm API URL is valid");
+
+        let saved: Option<MobileSession> = std::fs::read(&session_path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok());
+
+        // a session for a different account is left alone until the new one is signed in to
+        let signed_in = match saved {
+            Some(session) if session.name.eq_ignore_ascii_case(&username) => {
+                client.set_session(session.key);
+                true
+            }
+            _ => false,
+        };
+
+        LibreFm {
+            client,
+            username,
+            password,
+            session_path,
+            signed_in,
+        }
+    }
+
+    async fn sign_in(&mut self) -> anyhow::Result<()> {
+        if self.signed_in {
+            return Ok(());
+        }
+
+        let Some(password) = &self.password else {
+            anyhow::bail!("not signed in to Libre.fm, add the password to the settings");
+        };
+
+        let session = self
+            .client
+            .get_mobile_session(&self.username, password)
+            .await?;
+        info!("Signed in to Libre.fm as {}", session.name);
+
+        if let Err(err) = serde_json::to_vec(&session)
+            .map_err(std::io::Error::other)
+            .and_then(|data| std::fs::write(&self.session_path, data))
+        {
+            warn!(?err, "Could not save the Libre.fm session: {err}");
+        }
+
+        self.client.set_session(session.key);
+        self.signed_in = true;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Scrobbler for LibreFm {
+    async fn now_playing(&mut self, track: &Scrobble) -> anyhow::Result<()> {
+        self.sign_in().await?;
+        self.client
+            .now_playing(
+                &track.artist,
+                &track.track,
+                track.album.as_deref(),
+                track.duration,
+            )
+            .await
+    }
+
+    async fn scrobble(&mut self, scrobbles: &[Scrobble]) -> anyhow::Result<()> {
+        self.sign_in().await?;
+        self.client.scrobble(scrobbles).await
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::scrobbler::{Rejected, Scrobble, Scrobbler};
+
+/// Sends plays ("listens") to ListenBrainz, or another server with the same API.
+pub struct ListenBrainz {
+    client: zed_reqwest::Client,
+    submit_url: String,
+    token: String,
+}
+
+impl ListenBrainz {
+    pub fn new(api_url: &str, token: String) -> anyhow::Result<Self> {
+        Ok(ListenBrainz {
+            client: zed_reqwest::Client::builder()
+                .user_agent(concat!("Hummingbird/", env!("CARGO_PKG_VERSION")))
+                .timeout(Duration::from_secs(20))
+                .build()?,
+            submit_url: format!("{}/1/submit-listens", api_url.trim_end_matches('/')),
+            token,
+        })
+    }
+
+    async fn submit(&self, listen_type: &str, payload: Vec<Value>) -> anyhow::Result<()> {
+        let response = self
+            .client
+            .post(&self.submit_url)
+            .header("Authorization", format!("Token {}", self.token))
+            .json(&json!({ "listen_type": listen_type, "payload": payload }))
+            .send()
+            .await?;
+
+        if response.status() == zed_reqwest::StatusCode::BAD_REQUEST {
+            return Err(Rejected(response.text().await.unwrap_or_default()).into());
+        }
+
+        response.error_for_status()?;
+        Ok(())
+    }
+}
+
+fn track_metadata(track: &Scrobble) -> Value {
+    let mut additional_info = json!({
+        "media_player": "Hummingbird",
+        "submission_client": "Hummingbird",
+        "submission_client_version": env!("CARGO_PKG_VERSION"),
+    });
+
+    if let Some(duration) = track.duration {
+        additional_info["duration_ms"] = json!(duration * 1000);
+    }
+    if let Some(track_number) = track.track_number {
+        additional_info["tracknumber"] = json!(track_number);
+    }
+    if let Some(album_artist) = &track.album_artist {
+        additional_info["release_artist_name"] = json!(album_artist);
+    }
+
+    let mut metadata = json!({
+        "artist_name": track.artist,
+        "track_name": track.track,
+        "additional_info": additional_info,
+    });
+
+    if let Some(album) = &track.album {
+        metadata["release_name"] = json!(album);
+    }
+
+    metadata
+}
+
+#[async_trait]
+impl Scrobbler for ListenBrainz {
+    async fn now_playing(&mut self, track: &Scrobble) -> anyhow::Result<()> {
+        let payload = vec![json!({ "track_metadata": track_metadata(track) })];
+        self.submit("playing_now", payload).await
+    }
+
+    async fn scrobble(&mut self, scrobbles: &[Scrobble]) -> anyhow::Result<()> {
+        let payload = scrobbles
+            .iter()
+            .map(|scrobble| {
+                json!({
+                    "listened_at": scrobble.timestamp,
+                    "track_metadata": track_metadata(scrobble),
+                })
+            })
+            .collect();
+
+        // "single" is for plays that just finished, and "import" for anything older
+        let listen_type = if scrobbles.len() == 1 {
+            "single"
+        } else {
+            "import"
+        };
+
+        self.submit(listen_type, payload).await
+    }
+}
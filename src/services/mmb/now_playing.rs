@@ -0,0 +1,189 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc},
+};
+
+use async_trait::async_trait;
+use serde_json::json;
+use tracing::warn;
+
+use crate::{
+    media::metadata::Metadata,
+    playback::thread::PlaybackState,
+    services::scripts::expand_placeholders,
+    settings::now_playing::{NowPlayingFormat, NowPlayingSettings},
+};
+
+use super::MediaMetadataBroadcastService;
+
+/// Keeps a file up to date with the current track, for OBS overlays and other stream tools.
+///
+/// Writing happens on its own thread: opening a named pipe blocks until something reads from it,
+/// which shouldn't hold up the other services.
+pub struct NowPlayingWriter {
+    settings: NowPlayingSettings,
+    sender: mpsc::Sender<String>,
+    path: Option<PathBuf>,
+    metadata: Option<Arc<Metadata>>,
+    state: PlaybackState,
+    position: u64,
+    duration: u64,
+    last_written: Option<String>,
+}
+
+impl NowPlayingWriter {
+    pub fn new(settings: NowPlayingSettings, file: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let result = std::thread::Builder::new()
+            .name("now_playing".to_string())
+            .spawn(move || write_thread(file, receiver));
+
+        if let Err(err) = result {
+            warn!(?err, "Could not start the now playing thread: {err}");
+        }
+
+        NowPlayingWriter {
+            settings,
+            sender,
+            path: None,
+            metadata: None,
+            state: PlaybackState::Stopped,
+            position: 0,
+            duration: 0,
+            last_written: None,
+        }
+    }
+
+    fn render(&self) -> String {
+        if self.state == PlaybackState::Stopped || self.metadata.is_none() {
+            return match self.settings.format {
+                NowPlayingFormat::Text => String::new(),
+                NowPlayingFormat::Json => json!({ "state": "stopped" }).to_string(),
+            };
+        }
+
+        let state = match self.state {
+            PlaybackState::Stopped => "stopped",
+            PlaybackState::Playing => "playing",
+            PlaybackState::Paused => "paused",
+        };
+
+        match self.settings.format {
+            NowPlayingFormat::Text => expand_placeholders(
+                &self.settings.template,
+                self.path.as_deref(),
+                self.metadata.as_deref(),
+            )
+            .replace("%position%", &format_time(self.position))
+            .replace("%duration%", &format_time(self.duration))
+            .replace("%state%", state),
+            NowPlayingFormat::Json => {
+                let metadata = self.metadata.as_deref();
+                json!({
+                    "state": state,
+                    "title": metadata.and_then(|m| m.name.as_deref()),
+                    "artist": metadata.and_then(|m| m.artist.as_deref()),
+                    "album": metadata.and_then(|m| m.album.as_deref()),
+                    "album_artist": metadata.and_then(|m| m.album_artist.as_deref()),
+                    "genre": metadata.and_then(|m| m.genre.as_deref()),
+                    "year": metadata.and_then(|m| m.year),
+                    "track_number": metadata.and_then(|m| m.track_current),
+                    "path": self.path.as_deref().map(Path::to_string_lossy),
+                    "position": self.position,
+                    "duration": self.duration,
+                })
+                .to_string()
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let contents = self.render();
+
+        // the position is only in the output for some templates, so most updates change nothing
+        if self.last_written.as_ref() != Some(&contents) {
+            self.last_written = Some(contents.clone());
+            self.sender.send(contents).ok();
+        }
+    }
+}
+
+fn format_time(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn write_thread(file: PathBuf, receiver: mpsc::Receiver<String>) {
+    while let Ok(mut contents) = receiver.recv() {
+        // only the newest contents matter if the reader has fallen behind
+        while let Ok(newer) = receiver.try_recv() {
+            contents = newer;
+        }
+
+        if let Err(err) = write_contents(&file, &contents) {
+            warn!(
+                ?err,
+                "Could not write now playing info to {}: {err}",
+                file.display()
+            );
+        }
+    }
+}
+
+fn write_contents(file: &Path, contents: &str) -> std::io::Result<()> {
+    let is_pipe = std::fs::metadata(file).is_ok_and(|metadata| !metadata.is_file());
+
+    if is_pipe {
+        let mut pipe = std::fs::OpenOptions::new().write(true).open(file)?;
+        writeln!(pipe, "{contents}")
+    } else {
+        // written to a temporary file first, so that readers never see a half-written file
+        let mut temp = file.as_os_str().to_owned();
+        temp.push(".tmp");
+        std::fs::write(&temp, contents)?;
+        std::fs::rename(&temp, file)
+    }
+}
+
+#[async_trait]
+impl MediaMetadataBroadcastService for NowPlayingWriter {
+    async fn new_track(&mut self, file_path: PathBuf) {
+        self.path = Some(file_path);
+        self.metadata = None;
+        self.position = 0;
+        self.duration = 0;
+    }
+
+    async fn metadata_recieved(&mut self, info: Arc<Metadata>) {
+        self.metadata = Some(info);
+        self.update();
+    }
+
+    async fn state_changed(&mut self, state: PlaybackState) {
+        self.state = state;
+        self.update();
+    }
+
+    async fn position_changed(&mut self, position: u64) {
+        self.position = position;
+        self.update();
+    }
+
+    async fn duration_changed(&mut self, duration: u64) {
+        self.duration = duration;
+        self.update();
+    }
+
+    fn shares_listening(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for NowPlayingWriter {
+    fn drop(&mut self) {
+        // so that overlays don't keep showing a track after the app is closed
+        self.state = PlaybackState::Stopped;
+        self.update();
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{media::metadata::Metadata, playback::thread::PlaybackState};
+
+use super::{ListenTimer, MediaMetadataBroadcastService};
+
+/// The most plays sent to a service in one request. Last.fm doesn't accept more than this.
+pub const MAX_BATCH: usize = 50;
+
+/// A play of a track, as sent to scrobbling services.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scrobble {
+    pub artist: String,
+    pub track: String,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub track_number: Option<u64>,
+    /// The duration of the track, in seconds.
+    pub duration: Option<u64>,
+    /// When the track started playing, as a Unix timestamp.
+    pub timestamp: i64,
+}
+
+impl Scrobble {
+    /// Returns `None` if the track doesn't have an artist and a title, which every service needs.
+    fn new(metadata: &Metadata, timestamp: i64) -> Option<Self> {
+        Some(Scrobble {
+            artist: metadata.artist.clone()?,
+            track: metadata.name.clone()?,
+            album: metadata.album.clone(),
+            album_artist: metadata.album_artist.clone(),
+            track_number: metadata.track_current,
+            duration: None,
+            timestamp,
+        })
+    }
+}
+
+/// A service that plays can be sent to, such as Last.fm or ListenBrainz. Scrobblers are wrapped
+/// in a [ScrobbleQueue], which decides when tracks count as played and holds on to plays that
+/// couldn't be sent.
+#[async_trait]
+pub trait Scrobbler: Send {
+    /// Tells the service that a track has started playing.
+    async fn now_playing(&mut self, track: &Scrobble) -> anyhow::Result<()>;
+
+    /// Sends plays to the service, oldest first. No more than [MAX_BATCH] are sent at once.
+    ///
+    /// Errors are treated as temporary, and the plays are sent again later, unless the error is
+    /// a [Rejected] error.
+    async fn scrobble(&mut self, scrobbles: &[Scrobble]) -> anyhow::Result<()>;
+}
+
+/// Where the plays waiting to be sent to the named service are saved.
+pub fn queue_path(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join(format!("{name}_queue.json"))
+}
+
+/// Reads the plays waiting to be sent from a queue file. A missing or broken file counts as an
+/// empty queue.
+pub fn read_queue(queue_path: &Path) -> Vec<Scrobble> {
+    std::fs::read(queue_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Returned by scrobblers when the service refused the plays it was sent, so sending them again
+/// won't help.
+#[derive(Debug, thiserror::Error)]
+#[error("the service rejected the plays: {0}")]
+pub struct Rejected(pub String);
+
+/// Sends plays of tracks to a [Scrobbler]. Plays that can't be sent (because the computer is
+/// offline, or the service is down) are saved to a file and sent with the next play, including
+/// after the app is restarted.
+pub struct ScrobbleQueue {
+    name: &'static str,
+    scrobbler: Box<dyn Scrobbler>,
+    queue_path: PathBuf,
+    queue: Vec<Scrobble>,
+    current: Option<Scrobble>,
+    start_timestamp: i64,
+    timer: ListenTimer,
+    should_scrobble: bool,
+    scrobbled: bool,
+}
+
+impl ScrobbleQueue {
+    pub fn new(name: &'static str, scrobbler: Box<dyn Scrobbler>, queue_path: PathBuf) -> Self {
+        let queue = read_queue(&queue_path);
+
+        if !queue.is_empty() {
+            info!("{} plays are waiting to be sent to {name}", queue.len());
+        }
+
+        ScrobbleQueue {
+            name,
+            scrobbler,
+            queue_path,
+            queue,
+            current: None,
+            start_timestamp: Utc::now().timestamp(),
+            timer: ListenTimer::default(),
+            should_scrobble: false,
+            scrobbled: false,
+        }
+    }
+
+    fn save_queue(&self) {
+        let result = if self.queue.is_empty() {
+            match std::fs::remove_file(&self.queue_path) {
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            }
+        } else {
+            serde_json::to_vec(&self.queue)
+                .map_err(std::io::Error::other)
+                .and_then(|data| std::fs::write(&self.queue_path, data))
+        };
+
+        if let Err(err) = result {
+            warn!(
+                ?err,
+                "Could not save the {} scrobble queue: {err}", self.name
+            );
+        }
+    }
+
+    /// Adds the current track to the queue.
+    fn push_current(&mut self) {
+        self.should_scrobble = false;
+        self.scrobbled = true;
+
+        if let Some(mut scrobble) = self.current.clone() {
+            scrobble.duration = self.timer.duration();
+            self.queue.push(scrobble);
+            self.save_queue();
+        }
+    }
+
+    /// Queues the current track if it was listened to for long enough, sends the queue, and gets
+    /// ready for the next track.
+    async fn finish_current(&mut self) {
+        if self.should_scrobble {
+            self.push_current();
+        }
+
+        if !self.queue.is_empty() {
+            debug!("attempting scrobble to {}", self.name);
+            self.flush().await;
+        }
+
+        self.current = None;
+        self.start_timestamp = Utc::now().timestamp();
+        self.should_scrobble = false;
+        self.scrobbled = false;
+    }
+
+    /// Sends the queued plays, stopping at the first batch that can't be sent.
+    async fn flush(&mut self) {
+        while !self.queue.is_empty() {
+            let count = self.queue.len().min(MAX_BATCH);
+
+            match self.scrobbler.scrobble(&self.queue[..count]).await {
+                Ok(()) => debug!("sent {count} plays to {}", self.name),
+                Err(err) if err.is::<Rejected>() => {
+                    warn!(
+                        ?err,
+                        "{} rejected {count} plays, dropping them: {err}", self.name
+                    );
+                }
+                Err(err) => {
+                    warn!(
+                        ?err,
+                        "Could not scrobble to {}, will retry: {err}", self.name
+                    );
+                    break;
+                }
+            }
+
+            self.queue.drain(..count);
+            self.save_queue();
+        }
+    }
+}
+
+#[async_trait]
+impl MediaMetadataBroadcastService for ScrobbleQueue {
+    async fn new_track(&mut self, _: PathBuf) {
+        self.finish_current().await;
+        self.timer.reset();
+    }
+
+    async fn segment_changed(&mut self, _: u64, duration: Option<u64>) {
+        self.finish_current().await;
+        self.timer.start_segment(duration);
+    }
+
+    async fn metadata_recieved(&mut self, info: Arc<Metadata>) {
+        // metadata can be sent more than once for the same track
+        let first = self.current.is_none();
+        self.current = Scrobble::new(&info, self.start_timestamp);
+
+        if first
+            && let Some(current) = &self.current
+            && let Err(err) = self.scrobbler.now_playing(current).await
+        {
+            warn!("Could not set now playing on {}: {err}", self.name);
+        }
+    }
+
+    async fn state_changed(&mut self, state: PlaybackState) {
+        if self.should_scrobble && state != PlaybackState::Playing {
+            debug!("attempting scrobble to {}", self.name);
+            self.push_current();
+            self.flush().await;
+        }
+    }
+
+    async fn position_changed(&mut self, position: u64) {
+        self.timer.position_changed(position);
+
+        if self.timer.counts_as_played() && !self.scrobbled && self.current.is_some() {
+            self.should_scrobble = true;
+        }
+    }
+
+    async fn duration_changed(&mut self, duration: u64) {
+        self.timer.duration_changed(duration);
+    }
+
+    fn shares_listening(&self) -> bool {
+        true
+    }
+
+    fn scrobbles(&self) -> bool {
+        true
+    }
+
+    async fn retry_scrobbles(&mut self) {
+        if !self.queue.is_empty() {
+            debug!("retrying scrobbles to {}", self.name);
+            self.flush().await;
+        }
+    }
+
+    async fn discard_scrobble(&mut self, timestamp: i64) {
+        let count = self.queue.len();
+        self.queue
+            .retain(|scrobble| scrobble.timestamp != timestamp);
+
+        if self.queue.len() != count {
+            info!(
+                "discarded a play that was waiting to be sent to {}",
+                self.name
+            );
+            self.save_queue();
+        }
+    }
+}
+
+impl Drop for ScrobbleQueue {
+    fn drop(&mut self) {
+        // sent the next time the app is opened
+        if self.should_scrobble {
+            self.push_current();
+        }
+    }
+}
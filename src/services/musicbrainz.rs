@@ -0,0 +1,145 @@
+use std::{path::Path, time::Duration};
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::library::{
+    art::store_album_art,
+    db::{AlbumMethod, get_album_by_id},
+};
+
+/// The size album art is requested at from the Cover Art Archive.
+const COVER_SIZE: u32 = 1200;
+
+#[derive(Deserialize)]
+struct ReleaseLookup {
+    title: String,
+    #[serde(rename = "artist-credit", default)]
+    artist_credit: Vec<ArtistCredit>,
+}
+
+#[derive(Deserialize)]
+struct ArtistCredit {
+    artist: CreditedArtist,
+}
+
+#[derive(Deserialize)]
+struct CreditedArtist {
+    id: String,
+}
+
+/// A release on MusicBrainz.
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub title: String,
+    /// The ID of the first artist the release is credited to.
+    pub artist_mbid: Option<String>,
+}
+
+/// MusicBrainz and Wikimedia both ask for a user agent that identifies the application.
+pub const USER_AGENT: &str = concat!(
+    "Hummingbird/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/143mailliw/hummingbird )"
+);
+
+pub fn http_client() -> anyhow::Result<zed_reqwest::Client> {
+    Ok(zed_reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(20))
+        .build()?)
+}
+
+pub async fn lookup_release(mbid: &str) -> anyhow::Result<Release> {
+    let release: ReleaseLookup = http_client()?
+        .get(format!("https://musicbrainz.org/ws/2/release/{mbid}"))
+        .query(&[("inc", "artist-credits"), ("fmt", "json")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(Release {
+        title: release.title,
+        artist_mbid: release
+            .artist_credit
+            .into_iter()
+            .next()
+            .map(|credit| credit.artist.id),
+    })
+}
+
+/// Links the album to a MusicBrainz release, after checking that the release exists. The album's
+/// artist is linked to the release's artist too, unless they already have an ID.
+pub async fn link_album_release(
+    write_pool: &SqlitePool,
+    album_id: i64,
+    artist_id: i64,
+    mbid: &str,
+) -> anyhow::Result<Release> {
+    let release = lookup_release(mbid).await?;
+
+    sqlx::query(include_str!("../../queries/library/link_album_release.sql"))
+        .bind(album_id)
+        .bind(mbid)
+        .execute(write_pool)
+        .await?;
+
+    if let Some(artist_mbid) = &release.artist_mbid {
+        sqlx::query(include_str!("../../queries/scan/set_artist_mbid.sql"))
+            .bind(artist_id)
+            .bind(artist_mbid)
+            .execute(write_pool)
+            .await?;
+    }
+
+    Ok(release)
+}
+
+/// Removes the release the album was linked to by hand, so that the tagged one is used again.
+pub async fn unlink_album_release(write_pool: &SqlitePool, album_id: i64) -> anyhow::Result<()> {
+    sqlx::query(include_str!("../../queries/library/link_album_release.sql"))
+        .bind(album_id)
+        .bind(None::<String>)
+        .execute(write_pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Downloads the front cover of the album's release from the Cover Art Archive and uses it as the
+/// album's art.
+pub async fn fetch_album_cover(
+    read_pool: &SqlitePool,
+    write_pool: &SqlitePool,
+    art_dir: &Path,
+    album_id: i64,
+) -> anyhow::Result<()> {
+    let album = get_album_by_id(read_pool, album_id, AlbumMethod::Metadata).await?;
+    let mbid = album
+        .release_mbid()
+        .ok_or_else(|| anyhow!("album isn't linked to a release"))?;
+
+    let image = http_client()?
+        .get(format!(
+            "https://coverartarchive.org/release/{mbid}/front-{COVER_SIZE}"
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let (image_hash, thumb) = store_album_art(art_dir, &image)?;
+
+    sqlx::query(include_str!("../../queries/library/set_album_art.sql"))
+        .bind(album_id)
+        .bind(image_hash)
+        .bind(thumb)
+        .execute(write_pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use std::time::{Duration, Instant};
+
+use gpui::App;
+use tracing::{info, warn};
+
+#[cfg(target_os = "macos")]
+use macos::is_playing;
+#[cfg(target_os = "windows")]
+use windows::is_playing;
+
+use crate::{
+    playback::{interface::PlaybackInterface, thread::PlaybackState},
+    settings::{SettingsGlobal, playback::OtherAudio},
+    ui::models::PlaybackInfo,
+};
+
+/// How often other applications are checked for audio.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long other applications have to stay quiet before playback is turned back up or resumed,
+/// so that a moment of silence in a call doesn't bring the music back.
+const RESUME_DELAY: Duration = Duration::from_secs(3);
+
+/// What has been done about other applications playing audio.
+#[derive(Default)]
+struct OtherAudioState {
+    /// Whether another application was playing audio when last checked, after the resume delay.
+    playing: bool,
+    /// When other applications were first found quiet again, while waiting out the resume delay.
+    quiet_since: Option<Instant>,
+    /// Whether playback was paused because another application started playing.
+    paused: bool,
+    /// Whether playback was turned down because another application started playing.
+    ducked: bool,
+}
+
+impl OtherAudioState {
+    fn update(&mut self, cx: &mut App, others_playing: bool) {
+        let policy = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .playback
+            .other_audio;
+        let state = *cx.global::<PlaybackInfo>().playback_state.read(cx);
+        let interface = cx.global::<PlaybackInterface>();
+
+        // the setting was changed, or the user took over
+        if self.ducked && policy != OtherAudio::Duck {
+            interface.set_ducked(false);
+            self.ducked = false;
+        }
+        if policy != OtherAudio::Pause || state != PlaybackState::Paused {
+            self.paused = false;
+        }
+
+        if others_playing {
+            self.quiet_since = None;
+
+            if self.playing {
+                return;
+            }
+            self.playing = true;
+
+            match policy {
+                OtherAudio::Pause if state == PlaybackState::Playing => {
+                    info!("Another application started playing audio, pausing");
+                    interface.pause();
+                    self.paused = true;
+                }
+                OtherAudio::Duck => {
+                    interface.set_ducked(true);
+                    self.ducked = true;
+                }
+                _ => (),
+            }
+        } else if self.playing {
+            let quiet_since = *self.quiet_since.get_or_insert_with(Instant::now);
+            if quiet_since.elapsed() < RESUME_DELAY {
+                return;
+            }
+
+            self.playing = false;
+            self.quiet_since = None;
+
+            if self.paused {
+                info!("Other applications stopped playing audio, resuming");
+                interface.play();
+                self.paused = false;
+            }
+            if self.ducked {
+                interface.set_ducked(false);
+                self.ducked = false;
+            }
+        }
+    }
+}
+
+/// Pauses or turns down playback while another application plays audio, following
+/// [crate::settings::playback::PlaybackSettings::other_audio].
+pub fn watch_other_audio(cx: &mut App) {
+    cx.spawn(async move |cx| {
+        let mut state = OtherAudioState::default();
+
+        loop {
+            cx.background_executor().timer(POLL_INTERVAL).await;
+
+            let Ok(policy) = cx.update(|cx| {
+                cx.global::<SettingsGlobal>()
+                    .model
+                    .read(cx)
+                    .playback
+                    .other_audio
+            }) else {
+                break;
+            };
+
+            let others_playing = if policy == OtherAudio::Ignore {
+                false
+            } else {
+                match crate::RUNTIME.spawn_blocking(is_playing).await {
+                    Ok(Ok(playing)) => playing,
+                    Ok(Err(err)) => {
+                        warn!("Can't tell whether other applications are playing audio: {err}");
+                        break;
+                    }
+                    Err(err) => {
+                        warn!("Can't tell whether other applications are playing audio: {err}");
+                        break;
+                    }
+                }
+            };
+
+            if cx.update(|cx| state.update(cx, others_playing)).is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+}
@@ -0,0 +1,116 @@
+use std::{ffi::c_void, mem::size_of, ptr::null};
+
+use anyhow::bail;
+
+#[repr(C)]
+struct AudioObjectPropertyAddress {
+    selector: u32,
+    scope: u32,
+    element: u32,
+}
+
+#[link(name = "CoreAudio", kind = "framework")]
+unsafe extern "C" {
+    fn AudioObjectGetPropertyDataSize(
+        object: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        size: *mut u32,
+    ) -> i32;
+
+    fn AudioObjectGetPropertyData(
+        object: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        size: *mut u32,
+        data: *mut c_void,
+    ) -> i32;
+}
+
+const SYSTEM_OBJECT: u32 = 1;
+const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+const ELEMENT_MAIN: u32 = 0;
+/// The audio objects of the processes using Core Audio. Added in macOS 14.4.
+const PROCESS_OBJECT_LIST: u32 = u32::from_be_bytes(*b"prs#");
+const PROCESS_PID: u32 = u32::from_be_bytes(*b"ppid");
+const PROCESS_IS_RUNNING_OUTPUT: u32 = u32::from_be_bytes(*b"piro");
+
+fn address(selector: u32) -> AudioObjectPropertyAddress {
+    AudioObjectPropertyAddress {
+        selector,
+        scope: SCOPE_GLOBAL,
+        element: ELEMENT_MAIN,
+    }
+}
+
+/// Reads a property that holds a single value.
+fn property<T: Default>(object: u32, selector: u32) -> anyhow::Result<T> {
+    let mut value = T::default();
+    let mut size = size_of::<T>() as u32;
+
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            object,
+            &address(selector),
+            0,
+            null(),
+            &mut size,
+            (&raw mut value).cast(),
+        )
+    };
+    if status != 0 {
+        bail!("Core Audio error {status}");
+    }
+
+    Ok(value)
+}
+
+/// Lists the audio objects of the processes using Core Audio.
+fn processes() -> anyhow::Result<Vec<u32>> {
+    let address = address(PROCESS_OBJECT_LIST);
+    let mut size = 0;
+
+    let status =
+        unsafe { AudioObjectGetPropertyDataSize(SYSTEM_OBJECT, &address, 0, null(), &mut size) };
+    if status != 0 {
+        bail!("Core Audio error {status} (macOS 14.4 or later is needed)");
+    }
+
+    let mut processes = vec![0_u32; size as usize / size_of::<u32>()];
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            SYSTEM_OBJECT,
+            &address,
+            0,
+            null(),
+            &mut size,
+            processes.as_mut_ptr().cast(),
+        )
+    };
+    if status != 0 {
+        bail!("Core Audio error {status}");
+    }
+
+    processes.truncate(size as usize / size_of::<u32>());
+    Ok(processes)
+}
+
+/// Whether another process is playing audio through Core Audio.
+pub fn is_playing() -> anyhow::Result<bool> {
+    let own_process = std::process::id() as i32;
+
+    for process in processes()? {
+        // processes can go away while they're being looked at
+        let Ok(pid) = property::<i32>(process, PROCESS_PID) else {
+            continue;
+        };
+
+        if pid != own_process && property::<u32>(process, PROCESS_IS_RUNNING_OUTPUT)? != 0 {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
@@ -0,0 +1,44 @@
+use windows::{
+    Win32::{
+        Foundation::S_OK,
+        Media::Audio::{
+            AudioSessionStateActive, IAudioSessionControl2, IAudioSessionManager2,
+            IMMDeviceEnumerator, MMDeviceEnumerator, eConsole, eRender,
+        },
+        System::Com::{CLSCTX_ALL, COINIT_MULTITHREADED, CoCreateInstance, CoInitializeEx},
+    },
+    core::Interface,
+};
+
+/// Whether another application has an active audio session on the default output device.
+/// System sounds (notifications and the like) don't count.
+pub fn is_playing() -> anyhow::Result<bool> {
+    let own_process = std::process::id();
+
+    unsafe {
+        // the thread may already be set up for COM, which is fine
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+        let sessions = manager.GetSessionEnumerator()?;
+
+        for index in 0..sessions.GetCount()? {
+            let session: IAudioSessionControl2 = sessions.GetSession(index)?.cast()?;
+
+            if session.GetState()? != AudioSessionStateActive
+                || session.IsSystemSoundsSession() == S_OK
+            {
+                continue;
+            }
+
+            if session.GetProcessId()? != own_process {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
@@ -0,0 +1,388 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::{
+    library::{
+        db::list_podcasts,
+        types::{Podcast, PodcastEpisode},
+    },
+    services::musicbrainz::{USER_AGENT, http_client},
+};
+
+const ITUNES_NAMESPACE: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+
+/// A podcast feed, as read from its RSS.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub episodes: Vec<FeedEpisode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEpisode {
+    pub guid: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub audio_url: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub duration: Option<i64>,
+}
+
+/// Finds the child in the iTunes namespace with the name, which podcast feeds use for their artwork
+/// and durations.
+fn itunes<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.has_tag_name((ITUNES_NAMESPACE, name)))
+}
+
+/// Reads a duration given as seconds, `MM:SS` or `HH:MM:SS`.
+fn parse_duration(duration: &str) -> Option<i64> {
+    duration.trim().split(':').try_fold(0, |total: i64, part| {
+        Some(total * 60 + part.parse::<i64>().ok()?)
+    })
+}
+
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date.trim())
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| dateparser::parse(date).ok())
+}
+
+/// Removes the HTML that episode descriptions are usually written in, leaving the text.
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn parse_feed(xml: &str) -> anyhow::Result<Feed> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = roxmltree::Document::parse_with_options(xml, options)?;
+    let channel = document
+        .descendants()
+        .find(|node| node.has_tag_name("channel"))
+        .context("the feed isn't an RSS feed")?;
+
+    let child_text = |node: roxmltree::Node, name: &str| {
+        node.children()
+            .find(|child| child.has_tag_name(name) && child.tag_name().namespace().is_none())
+            .and_then(|child| child.text())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let image_url = itunes(channel, "image")
+        .and_then(|image| image.attribute("href"))
+        .map(str::to_string)
+        .or_else(|| {
+            channel
+                .children()
+                .find(|child| child.has_tag_name("image"))
+                .and_then(|image| child_text(image, "url"))
+        });
+
+    let episodes = channel
+        .children()
+        .filter(|child| child.has_tag_name("item"))
+        .filter_map(|item| {
+            // episodes without audio can't be played, so they're left out
+            let audio_url = item
+                .children()
+                .find(|child| child.has_tag_name("enclosure"))
+                .and_then(|enclosure| enclosure.attribute("url"))?
+                .trim()
+                .to_string();
+
+            Some(FeedEpisode {
+                guid: child_text(item, "guid").unwrap_or_else(|| audio_url.clone()),
+                title: child_text(item, "title").unwrap_or_else(|| audio_url.clone()),
+                description: child_text(item, "description")
+                    .or_else(|| {
+                        itunes(item, "summary")
+                            .and_then(|summary| summary.text())
+                            .map(str::to_string)
+                    })
+                    .map(|description| plain_text(&description)),
+                published_at: child_text(item, "pubDate").and_then(|date| parse_date(&date)),
+                duration: itunes(item, "duration")
+                    .and_then(|duration| duration.text())
+                    .and_then(parse_duration),
+                audio_url,
+            })
+        })
+        .collect();
+
+    Ok(Feed {
+        title: child_text(channel, "title").context("the feed has no title")?,
+        description: child_text(channel, "description").map(|text| plain_text(&text)),
+        image_url,
+        episodes,
+    })
+}
+
+pub async fn fetch_feed(url: &str) -> anyhow::Result<Feed> {
+    let body = http_client()?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    parse_feed(&body)
+}
+
+/// Saves the podcast and its episodes. When `seen` is true, the episodes aren't counted as new,
+/// which is used when first subscribing so that the whole back catalogue isn't marked as new.
+async fn store_feed(
+    write_pool: &SqlitePool,
+    url: &str,
+    feed: &Feed,
+    seen: bool,
+) -> anyhow::Result<i64> {
+    let mut tx = write_pool.begin().await?;
+
+    let (podcast_id,): (i64,) =
+        sqlx::query_as(include_str!("../../queries/podcast/create_podcast.sql"))
+            .bind(url)
+            .bind(&feed.title)
+            .bind(&feed.description)
+            .bind(&feed.image_url)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    for episode in &feed.episodes {
+        sqlx::query(include_str!("../../queries/podcast/add_episode.sql"))
+            .bind(podcast_id)
+            .bind(&episode.guid)
+            .bind(&episode.title)
+            .bind(&episode.description)
+            .bind(&episode.audio_url)
+            .bind(episode.published_at)
+            .bind(episode.duration)
+            .bind(seen)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query(include_str!("../../queries/podcast/set_refreshed.sql"))
+        .bind(podcast_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(podcast_id)
+}
+
+/// Subscribes to the feed at the URL, returning the podcast's ID.
+pub async fn subscribe(write_pool: &SqlitePool, url: &str) -> anyhow::Result<i64> {
+    let url = Url::parse(url.trim()).context("the feed URL isn't valid")?;
+    let feed = fetch_feed(url.as_str()).await?;
+
+    debug!(
+        "Subscribed to {} with {} episodes",
+        feed.title,
+        feed.episodes.len()
+    );
+
+    store_feed(write_pool, url.as_str(), &feed, true).await
+}
+
+/// Removes the podcast, along with any episodes that were downloaded.
+pub async fn unsubscribe(
+    write_pool: &SqlitePool,
+    download_dir: &Path,
+    podcast_id: i64,
+) -> anyhow::Result<()> {
+    sqlx::query(include_str!("../../queries/podcast/delete_podcast.sql"))
+        .bind(podcast_id)
+        .execute(write_pool)
+        .await?;
+
+    let dir = download_dir.join(podcast_id.to_string());
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+
+    Ok(())
+}
+
+pub async fn refresh_podcast(write_pool: &SqlitePool, podcast: &Podcast) -> anyhow::Result<()> {
+    let feed = fetch_feed(&podcast.url).await?;
+    store_feed(write_pool, &podcast.url, &feed, false).await?;
+
+    Ok(())
+}
+
+/// Checks every feed for new episodes. A feed that can't be fetched doesn't stop the others from
+/// being checked.
+pub async fn refresh_podcasts(
+    read_pool: &SqlitePool,
+    write_pool: &SqlitePool,
+) -> anyhow::Result<()> {
+    for podcast in list_podcasts(read_pool).await? {
+        if let Err(err) = refresh_podcast(write_pool, &podcast).await {
+            warn!(?err, "Could not refresh podcast {}: {err}", podcast.url);
+        }
+    }
+
+    Ok(())
+}
+
+/// The extension of the episode's audio file, going by its URL.
+fn episode_extension(episode: &PodcastEpisode) -> String {
+    Url::parse(&episode.audio_url)
+        .ok()
+        .and_then(|url| {
+            Path::new(url.path())
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+        })
+        .filter(|ext| ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or_else(|| "mp3".to_string())
+}
+
+/// Downloads the URL to the path. The file is written under a temporary name first, so that an
+/// interrupted download doesn't leave a partial file behind.
+async fn download_to(url: &str, path: &Path) -> anyhow::Result<()> {
+    let client = zed_reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(Duration::from_secs(20))
+        .build()?;
+
+    let mut response = client.get(url).send().await?.error_for_status()?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let partial = path.with_extension("part");
+    let mut file = File::create(&partial)?;
+
+    let result = async {
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk)?;
+        }
+        file.flush()?;
+
+        anyhow::Ok(())
+    }
+    .await;
+
+    if let Err(err) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(err);
+    }
+
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+/// Downloads the episode so that it can be played without a connection, returning where it was
+/// saved.
+pub async fn download_episode(
+    write_pool: &SqlitePool,
+    download_dir: &Path,
+    episode: &PodcastEpisode,
+) -> anyhow::Result<PathBuf> {
+    let path = download_dir
+        .join(episode.podcast_id.to_string())
+        .join(format!("{}.{}", episode.id, episode_extension(episode)));
+
+    download_to(&episode.audio_url, &path).await?;
+
+    sqlx::query(include_str!("../../queries/podcast/set_episode_file.sql"))
+        .bind(episode.id)
+        .bind(path.to_string_lossy().as_ref())
+        .execute(write_pool)
+        .await?;
+
+    Ok(path)
+}
+
+/// Deletes the downloaded copy of the episode.
+pub async fn delete_download(
+    write_pool: &SqlitePool,
+    episode: &PodcastEpisode,
+) -> anyhow::Result<()> {
+    let path = episode
+        .file_path
+        .as_ref()
+        .ok_or_else(|| anyhow!("the episode hasn't been downloaded"))?;
+
+    if let Err(err) = fs::remove_file(path)
+        && err.kind() != std::io::ErrorKind::NotFound
+    {
+        return Err(err.into());
+    }
+
+    sqlx::query(include_str!("../../queries/podcast/set_episode_file.sql"))
+        .bind(episode.id)
+        .bind(None::<String>)
+        .execute(write_pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Fetches an episode that hasn't been downloaded so that it can be played, returning where it
+/// was saved. The decoders read from files, so the episode is kept in a cache that only ever holds
+/// the episode that was streamed last.
+pub async fn stream_episode(cache_dir: &Path, episode: &PodcastEpisode) -> anyhow::Result<PathBuf> {
+    let path = cache_dir.join(format!("{}.{}", episode.id, episode_extension(episode)));
+
+    if path.exists() {
+        return Ok(path);
+    }
+
+    if let Ok(entries) = fs::read_dir(cache_dir) {
+        for entry in entries.flatten() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    download_to(&episode.audio_url, &path).await?;
+
+    Ok(path)
+}
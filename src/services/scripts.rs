@@ -0,0 +1,49 @@
+use std::{path::Path, process::Stdio};
+
+use tracing::{debug, warn};
+
+use crate::{media::metadata::Metadata, settings::scripts::ExternalCommand};
+
+/// Replaces the placeholders in an argument with information about the track.
+pub fn expand_placeholders(arg: &str, path: Option<&Path>, metadata: Option<&Metadata>) -> String {
+    let field = |get: fn(&Metadata) -> &Option<String>| {
+        metadata.and_then(|m| get(m).as_deref()).unwrap_or_default()
+    };
+
+    arg.replace(
+        "%path%",
+        &path.map(|p| p.to_string_lossy()).unwrap_or_default(),
+    )
+    .replace("%title%", field(|m| &m.name))
+    .replace("%artist%", field(|m| &m.artist))
+    .replace("%album_artist%", field(|m| &m.album_artist))
+    .replace("%album%", field(|m| &m.album))
+    .replace("%genre%", field(|m| &m.genre))
+}
+
+/// Starts the external program without waiting for it to finish. Each argument is passed to the
+/// program as-is after its placeholders are replaced, so no shell quoting is needed.
+pub fn run_external_command(
+    command: &ExternalCommand,
+    path: Option<&Path>,
+    metadata: Option<&Metadata>,
+) {
+    let args: Vec<String> = command
+        .args
+        .iter()
+        .map(|arg| expand_placeholders(arg, path, metadata))
+        .collect();
+
+    debug!("running {} {:?}", command.program, args);
+
+    let result = std::process::Command::new(&command.program)
+        .args(args)
+        .stdin(Stdio::null())
+        .spawn();
+
+    match result {
+        // the child is waited on so that it doesn't stay around as a zombie process
+        Ok(mut child) => drop(crate::RUNTIME.spawn_blocking(move || child.wait())),
+        Err(err) => warn!(?err, "Could not run {}: {err}", command.program),
+    }
+}
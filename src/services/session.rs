@@ -0,0 +1,157 @@
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+use std::time::Duration;
+
+use anyhow::Context;
+use gpui::App;
+use tracing::{info, warn};
+
+#[cfg(target_os = "linux")]
+use linux::{idle_time, is_locked};
+#[cfg(target_os = "macos")]
+use macos::{idle_time, is_locked};
+#[cfg(target_os = "windows")]
+use windows::{idle_time, is_locked};
+
+use crate::{
+    playback::{interface::PlaybackInterface, thread::PlaybackState},
+    settings::SettingsGlobal,
+    ui::models::{Models, PlaybackInfo},
+};
+
+/// How often the computer is checked for being locked or idle.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The longest the watcher waits before trying again when the session can't be checked. The wait
+/// doubles after each failure, starting from [POLL_INTERVAL].
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Whether the computer is locked and how long it has gone without input, as last checked.
+#[derive(Clone, Copy, Default)]
+struct SessionState {
+    locked: bool,
+    idle: Duration,
+}
+
+impl SessionState {
+    fn check() -> anyhow::Result<Self> {
+        Ok(Self {
+            locked: is_locked().context("can't tell whether the computer is locked")?,
+            idle: idle_time().context("can't tell how long the computer has been idle")?,
+        })
+    }
+}
+
+/// Keeps [Models::session_locked] up to date, and pauses playback when the computer is locked or
+/// has been idle for a while, following
+/// [crate::settings::playback::PlaybackSettings::pause_when_locked] and
+/// [crate::settings::playback::PlaybackSettings::pause_when_idle]. The session is only checked
+/// while a setting needs it (see [crate::settings::playback::PlaybackSettings::watches_session]).
+pub fn watch_session(cx: &mut App) {
+    cx.spawn(async move |cx| {
+        let mut last = SessionState::default();
+        // whether playback was already paused for the current idle stretch, so that playing again
+        // without touching the computer (such as from a phone) isn't paused straight away
+        let mut paused_for_idle = false;
+        let mut interval = POLL_INTERVAL;
+        // failures are only logged once, until checking works again
+        let mut failing = false;
+
+        loop {
+            cx.background_executor().timer(interval).await;
+
+            let Ok(watching) = cx.update(|cx| {
+                cx.global::<SettingsGlobal>()
+                    .model
+                    .read(cx)
+                    .playback
+                    .watches_session()
+            }) else {
+                break;
+            };
+
+            let state = if watching {
+                crate::RUNTIME
+                    .spawn_blocking(SessionState::check)
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+            } else {
+                // nothing is checked, so the computer is treated as unlocked and in use
+                Ok(SessionState::default())
+            };
+
+            let state = match state {
+                Ok(state) => {
+                    if failing {
+                        info!("The session can be checked again");
+                        failing = false;
+                    }
+
+                    interval = POLL_INTERVAL;
+                    state
+                }
+                Err(err) => {
+                    if !failing {
+                        warn!("Can't check the session, trying again less often: {err:#}");
+                        failing = true;
+                    }
+
+                    interval = (interval * 2).min(MAX_RETRY_INTERVAL);
+                    continue;
+                }
+            };
+
+            let result = cx.update(|cx| {
+                let settings = &cx.global::<SettingsGlobal>().model.read(cx).playback;
+                let pause_when_locked = settings.pause_when_locked;
+                let idle_limit = settings
+                    .pause_when_idle
+                    .map(|minutes| Duration::from_secs(minutes * 60));
+
+                let playing =
+                    *cx.global::<PlaybackInfo>().playback_state.read(cx) == PlaybackState::Playing;
+
+                if state.locked != last.locked {
+                    info!(
+                        "The computer was {}",
+                        if state.locked { "locked" } else { "unlocked" }
+                    );
+                    let session_locked = cx.global::<Models>().session_locked.clone();
+                    session_locked.update(cx, |locked, cx| {
+                        *locked = state.locked;
+                        cx.notify();
+                    });
+
+                    if state.locked && pause_when_locked && playing {
+                        cx.global::<PlaybackInterface>().pause();
+                    }
+                }
+
+                let idle = idle_limit.is_some_and(|limit| state.idle >= limit);
+                if !idle {
+                    paused_for_idle = false;
+                } else if !paused_for_idle {
+                    paused_for_idle = true;
+
+                    if playing {
+                        info!("The computer has been idle for a while, pausing");
+                        cx.global::<PlaybackInterface>().pause();
+                    }
+                }
+            });
+
+            if result.is_err() {
+                break;
+            }
+
+            last = state;
+        }
+    })
+    .detach();
+}
@@ -0,0 +1,45 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use zbus::blocking::{Connection, Proxy};
+
+/// The session the app is running in, as seen by systemd-logind. Desktop environments keep its
+/// locked and idle hints up to date.
+fn session() -> anyhow::Result<Proxy<'static>> {
+    // the connection is kept, rather than made again every time the session is checked
+    static CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+
+    let mut cached = CONNECTION.lock().unwrap_or_else(|err| err.into_inner());
+    let connection = match cached.as_ref() {
+        Some(connection) => connection.clone(),
+        None => cached.insert(Connection::system()?).clone(),
+    };
+
+    Ok(Proxy::new_owned(
+        connection,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1/session/auto",
+        "org.freedesktop.login1.Session",
+    )?)
+}
+
+/// Whether the computer is locked.
+pub fn is_locked() -> anyhow::Result<bool> {
+    Ok(session()?.get_property("LockedHint")?)
+}
+
+/// How long it has been since the session went idle, or nothing if it isn't idle.
+pub fn idle_time() -> anyhow::Result<Duration> {
+    let session = session()?;
+    if !session.get_property::<bool>("IdleHint")? {
+        return Ok(Duration::ZERO);
+    }
+
+    // in microseconds since the Unix epoch
+    let since: u64 = session.get_property("IdleSinceHint")?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    Ok(now.saturating_sub(Duration::from_micros(since)))
+}
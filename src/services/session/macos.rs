@@ -0,0 +1,64 @@
+use std::{
+    ffi::{c_char, c_void},
+    time::Duration,
+};
+
+use anyhow::bail;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGSessionCopyCurrentDictionary() -> *const c_void;
+    fn CGEventSourceSecondsSinceLastEventType(state: i32, event_type: u32) -> f64;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C" {
+    fn CFStringCreateWithCString(
+        allocator: *const c_void,
+        string: *const c_char,
+        encoding: u32,
+    ) -> *const c_void;
+    fn CFDictionaryGetValue(dictionary: *const c_void, key: *const c_void) -> *const c_void;
+    fn CFBooleanGetValue(boolean: *const c_void) -> u8;
+    fn CFRelease(object: *const c_void);
+}
+
+const STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const EVENT_SOURCE_STATE_COMBINED_SESSION: i32 = 0;
+const ANY_INPUT_EVENT_TYPE: u32 = !0;
+
+/// Whether the computer is locked. The session's dictionary only has the locked key while the
+/// lock screen is up.
+pub fn is_locked() -> anyhow::Result<bool> {
+    unsafe {
+        let session = CGSessionCopyCurrentDictionary();
+        if session.is_null() {
+            bail!("there's no window server session");
+        }
+
+        let key = CFStringCreateWithCString(
+            std::ptr::null(),
+            c"CGSSessionScreenIsLocked".as_ptr(),
+            STRING_ENCODING_UTF8,
+        );
+        let value = CFDictionaryGetValue(session, key);
+        let locked = !value.is_null() && CFBooleanGetValue(value) != 0;
+
+        CFRelease(key);
+        CFRelease(session);
+
+        Ok(locked)
+    }
+}
+
+/// How long it has been since the last keyboard or mouse input.
+pub fn idle_time() -> anyhow::Result<Duration> {
+    let seconds = unsafe {
+        CGEventSourceSecondsSinceLastEventType(
+            EVENT_SOURCE_STATE_COMBINED_SESSION,
+            ANY_INPUT_EVENT_TYPE,
+        )
+    };
+
+    Ok(Duration::from_secs_f64(seconds.max(0.0)))
+}
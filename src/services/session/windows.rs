@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use windows::Win32::{
+    System::{
+        StationsAndDesktops::{
+            CloseDesktop, DESKTOP_CONTROL_FLAGS, DESKTOP_SWITCHDESKTOP, OpenInputDesktop,
+        },
+        SystemInformation::GetTickCount,
+    },
+    UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+};
+
+/// Whether the computer is locked. The desktop that takes input can't be opened while the lock
+/// screen is up.
+pub fn is_locked() -> anyhow::Result<bool> {
+    let desktop = unsafe {
+        OpenInputDesktop(
+            DESKTOP_CONTROL_FLAGS::default(),
+            false,
+            DESKTOP_SWITCHDESKTOP,
+        )
+    };
+
+    match desktop {
+        Ok(desktop) => {
+            unsafe { CloseDesktop(desktop)? };
+            Ok(false)
+        }
+        Err(_) => Ok(true),
+    }
+}
+
+/// How long it has been since the last keyboard or mouse input.
+pub fn idle_time() -> anyhow::Result<Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        anyhow::bail!("GetLastInputInfo failed");
+    }
+
+    // both are in milliseconds since startup, and wrap around after 49 days
+    let now = unsafe { GetTickCount() };
+    Ok(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+}
@@ -1,6 +1,16 @@
+pub mod analysis;
+pub mod interface;
+pub mod libraries;
+pub mod now_playing;
 pub mod playback;
+pub mod resume_sync;
 pub mod scan;
+pub mod scripts;
+pub mod scrobbling;
+pub mod sharing;
 pub mod storage;
+pub mod tags;
+pub mod transcode;
 
 use std::{fs::File, path::PathBuf, sync::mpsc::channel, time::Duration};
 
@@ -15,6 +25,26 @@ pub struct Settings {
     pub scanning: scan::ScanSettings,
     #[serde(default)]
     pub playback: playback::PlaybackSettings,
+    #[serde(default)]
+    pub interface: interface::InterfaceSettings,
+    #[serde(default)]
+    pub scripts: scripts::ScriptSettings,
+    #[serde(default)]
+    pub tags: tags::TagSettings,
+    #[serde(default)]
+    pub analysis: analysis::AnalysisSettings,
+    #[serde(default)]
+    pub transcode: transcode::TranscodeSettings,
+    #[serde(default)]
+    pub sharing: sharing::SharingSettings,
+    #[serde(default)]
+    pub scrobbling: scrobbling::ScrobblingSettings,
+    #[serde(default)]
+    pub now_playing: now_playing::NowPlayingSettings,
+    #[serde(default)]
+    pub resume_sync: resume_sync::ResumeSyncSettings,
+    #[serde(default)]
+    pub libraries: Vec<libraries::LibraryProfile>,
 }
 
 pub fn create_settings(path: &PathBuf) -> Settings {
@@ -33,6 +63,8 @@ pub fn create_settings(path: &PathBuf) -> Settings {
 
 pub struct SettingsGlobal {
     pub model: Entity<Settings>,
+    /// The file the settings were loaded from, and are written back to by [save_settings].
+    pub path: PathBuf,
     #[allow(dead_code)]
     pub watcher: Option<Box<dyn Watcher>>,
 }
@@ -53,6 +85,7 @@ pub fn setup_settings(cx: &mut App, path: PathBuf) {
 
         let global = SettingsGlobal {
             model: settings,
+            path,
             watcher: None,
         };
 
@@ -63,6 +96,8 @@ pub fn setup_settings(cx: &mut App, path: PathBuf) {
         warn!("failed to watch settings file: {:?}", e);
     }
 
+    let watched_path = path.clone();
+
     cx.spawn(async move |app: &mut AsyncApp| {
         loop {
             while let Ok(event) = rx.try_recv() {
@@ -74,7 +109,7 @@ pub fn setup_settings(cx: &mut App, path: PathBuf) {
                         match v.kind {
                             notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
                                 info!("Settings changed, updating...");
-                                let settings = create_settings(&path);
+                                let settings = create_settings(&watched_path);
                                 settings_model
                                     .update(app, |v, _| {
                                         *v = settings;
@@ -105,8 +140,23 @@ pub fn setup_settings(cx: &mut App, path: PathBuf) {
 
     let global = SettingsGlobal {
         model: settings,
+        path,
         watcher: Some(Box::new(watcher)),
     };
 
     cx.set_global(global);
 }
+
+/// Writes the current settings to the settings file.
+pub fn save_settings(cx: &App) {
+    let global = cx.global::<SettingsGlobal>();
+    let settings = global.model.read(cx);
+
+    let result = File::create(&global.path)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| Ok(serde_json::to_writer_pretty(file, settings)?));
+
+    if let Err(e) = result {
+        warn!("Failed to write settings file: {:?}", e);
+    }
+}
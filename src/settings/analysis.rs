@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for analyzing the audio of tracks in the library.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisSettings {
+    /// Whether tracks are analyzed in the background to find their tempo and key. Each track is
+    /// decoded in full, so the first run over a large library can take a long time.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Whether tracks without ReplayGain tags have their loudness and true peak measured in the
+    /// background, following EBU R 128.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub loudness: bool,
+    /// Whether measured loudness is written back to files as ReplayGain tags. Only MP3 and FLAC
+    /// files can be written to.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub write_replaygain: bool,
+    /// Samples quieter than this level (in dBFS) count as silence when finding the silence at the
+    /// start and end of each track, which happens alongside tempo and key analysis. Changing this
+    /// only affects tracks analyzed afterwards.
+    ///
+    /// Defaults to -60.
+    #[serde(default = "default_silence_threshold")]
+    pub silence_threshold: f64,
+}
+
+fn default_silence_threshold() -> f64 {
+    -60.0
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            loudness: false,
+            write_replaygain: false,
+            silence_threshold: default_silence_threshold(),
+        }
+    }
+}
+
+impl AnalysisSettings {
+    /// Whether any kind of analysis is turned on.
+    pub fn any_enabled(&self) -> bool {
+        self.enabled || self.loudness
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The smallest interface scale that can be selected.
+pub const MIN_SCALE: f32 = 0.5;
+/// The largest interface scale that can be selected.
+pub const MAX_SCALE: f32 = 2.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceSettings {
+    /// The scale of the interface. All paddings, text, and album art are multiplied by this value,
+    /// which is clamped between [MIN_SCALE] and [MAX_SCALE].
+    ///
+    /// Defaults to 1.0.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+
+    /// The language used for the interface, as a language tag such as `en` or `pt-BR`. Messages
+    /// missing from the selected language are shown in English.
+    ///
+    /// Defaults to the operating system's language.
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Whether track listings show how many times each track has been played. Only used for
+    /// listings that don't have their columns set in `track_columns`.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub show_play_count: bool,
+
+    /// Whether track listings show the date each track was last played. Only used for listings
+    /// that don't have their columns set in `track_columns`.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub show_last_played: bool,
+
+    /// Whether track listings show the tempo of each track, once it has been analyzed. Only used
+    /// for listings that don't have their columns set in `track_columns`.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub show_bpm: bool,
+
+    /// Whether track listings show the key of each track, once it has been analyzed. Only used
+    /// for listings that don't have their columns set in `track_columns`.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub show_key: bool,
+
+    /// Whether album rows and track listings show badges for the audio format (such as FLAC or
+    /// Hi-Res) and for tracks marked as explicit.
+    ///
+    /// Defaults to true.
+    #[serde(default = "default_true")]
+    pub show_badges: bool,
+
+    /// The columns shown in each kind of track listing, in order.
+    ///
+    /// Defaults to the title, artist and duration, plus the track number in albums and the
+    /// columns turned on with the `show_` options above.
+    #[serde(default)]
+    pub track_columns: TrackColumnSettings,
+
+    /// Whether the album list is shown as a dense table without album art, with the artist, title,
+    /// year, number of tracks and total duration of each album.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub compact_album_list: bool,
+
+    /// Extra key bindings, from keystrokes (such as `ctrl-alt-right`) to the name of the action
+    /// they run (such as `player::SeekForward`). These take priority over the built-in bindings,
+    /// and are applied when Hummingbird starts.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub keybindings: BTreeMap<String, String>,
+}
+
+impl Default for InterfaceSettings {
+    fn default() -> Self {
+        Self {
+            scale: default_scale(),
+            language: None,
+            show_play_count: false,
+            show_last_played: false,
+            show_bpm: false,
+            show_key: false,
+            show_badges: true,
+            track_columns: TrackColumnSettings::default(),
+            compact_album_list: false,
+            keybindings: BTreeMap::new(),
+        }
+    }
+}
+
+impl InterfaceSettings {
+    /// Returns the interface scale, clamped to the supported range.
+    pub fn scale(&self) -> f32 {
+        self.scale.clamp(MIN_SCALE, MAX_SCALE)
+    }
+
+    /// Returns the columns shown in the kind of track listing, in order.
+    pub fn track_columns(&self, kind: TrackListingKind) -> Vec<TrackColumnSetting> {
+        let columns = match kind {
+            TrackListingKind::Album => &self.track_columns.album,
+            TrackListingKind::Playlist => &self.track_columns.playlist,
+        };
+
+        if let Some(columns) = columns {
+            return columns.clone();
+        }
+
+        let shown = [
+            (TrackColumn::TrackNumber, kind == TrackListingKind::Album),
+            (TrackColumn::Title, true),
+            (TrackColumn::Artist, true),
+            (TrackColumn::PlayCount, self.show_play_count),
+            (TrackColumn::LastPlayed, self.show_last_played),
+            (TrackColumn::Bpm, self.show_bpm),
+            (TrackColumn::Key, self.show_key),
+            (TrackColumn::Duration, true),
+        ];
+
+        shown
+            .into_iter()
+            .filter(|(_, shown)| *shown)
+            .map(|(column, _)| column.into())
+            .collect()
+    }
+
+    /// Shows the column in the kind of track listing if it's hidden, or hides it if it's shown.
+    /// Columns that are shown are put in the same place as in [TrackColumn::ALL].
+    pub fn toggle_track_column(&mut self, kind: TrackListingKind, column: TrackColumn) {
+        let mut columns = self.track_columns(kind);
+
+        if let Some(idx) = columns.iter().position(|setting| setting.column == column) {
+            columns.remove(idx);
+        } else {
+            let order = |column: TrackColumn| TrackColumn::ALL.iter().position(|c| *c == column);
+            let idx = columns
+                .iter()
+                .position(|setting| order(setting.column) > order(column))
+                .unwrap_or(columns.len());
+            columns.insert(idx, column.into());
+        }
+
+        match kind {
+            TrackListingKind::Album => self.track_columns.album = Some(columns),
+            TrackListingKind::Playlist => self.track_columns.playlist = Some(columns),
+        }
+    }
+}
+
+/// The kinds of view with a track listing. Each kind has its own columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackListingKind {
+    Album,
+    Playlist,
+}
+
+/// The columns shown in each kind of track listing. Listings without columns set use the
+/// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackColumnSettings {
+    #[serde(default)]
+    pub album: Option<Vec<TrackColumnSetting>>,
+    #[serde(default)]
+    pub playlist: Option<Vec<TrackColumnSetting>>,
+}
+
+/// A column that can be shown in track listings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackColumn {
+    TrackNumber,
+    Title,
+    Artist,
+    PlayCount,
+    LastPlayed,
+    Bpm,
+    Key,
+    /// The file's format, from its extension.
+    Format,
+    /// The file's average bitrate, worked out from its size and duration.
+    Bitrate,
+    Duration,
+}
+
+impl TrackColumn {
+    pub const ALL: [TrackColumn; 10] = [
+        TrackColumn::TrackNumber,
+        TrackColumn::Title,
+        TrackColumn::Artist,
+        TrackColumn::PlayCount,
+        TrackColumn::LastPlayed,
+        TrackColumn::Bpm,
+        TrackColumn::Key,
+        TrackColumn::Format,
+        TrackColumn::Bitrate,
+        TrackColumn::Duration,
+    ];
+
+    /// The width the column is given when none is set, or `None` if it's sized to fit the space
+    /// that's left over (or its contents, for the duration).
+    pub fn default_width(&self) -> Option<f32> {
+        match self {
+            TrackColumn::TrackNumber => Some(62.0),
+            TrackColumn::PlayCount => Some(36.0),
+            TrackColumn::LastPlayed => Some(84.0),
+            TrackColumn::Bpm => Some(48.0),
+            TrackColumn::Key => Some(32.0),
+            TrackColumn::Format => Some(48.0),
+            TrackColumn::Bitrate => Some(72.0),
+            TrackColumn::Title | TrackColumn::Artist | TrackColumn::Duration => None,
+        }
+    }
+}
+
+/// A column shown in a track listing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TrackColumnEntry", into = "TrackColumnEntry")]
+pub struct TrackColumnSetting {
+    pub column: TrackColumn,
+    /// The width of the column, before scaling.
+    ///
+    /// Defaults to [TrackColumn::default_width].
+    pub width: Option<f32>,
+}
+
+impl TrackColumnSetting {
+    /// Returns the width of the column, or `None` if it's sized to fit.
+    pub fn width(&self) -> Option<f32> {
+        self.width.or_else(|| self.column.default_width())
+    }
+}
+
+impl From<TrackColumn> for TrackColumnSetting {
+    fn from(column: TrackColumn) -> Self {
+        TrackColumnSetting {
+            column,
+            width: None,
+        }
+    }
+}
+
+/// Columns can be written as just the column's name, or as an object with its width.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum TrackColumnEntry {
+    Column(TrackColumn),
+    Options {
+        column: TrackColumn,
+        #[serde(default)]
+        width: Option<f32>,
+    },
+}
+
+impl From<TrackColumnEntry> for TrackColumnSetting {
+    fn from(entry: TrackColumnEntry) -> Self {
+        match entry {
+            TrackColumnEntry::Column(column) => column.into(),
+            TrackColumnEntry::Options { column, width } => TrackColumnSetting { column, width },
+        }
+    }
+}
+
+impl From<TrackColumnSetting> for TrackColumnEntry {
+    fn from(setting: TrackColumnSetting) -> Self {
+        match setting.width {
+            None => TrackColumnEntry::Column(setting.column),
+            Some(width) => TrackColumnEntry::Options {
+                column: setting.column,
+                width: Some(width),
+            },
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
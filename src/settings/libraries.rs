@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Settings,
+    scan::{ScanPath, ScanSettings},
+};
+
+/// An additional library with its own scan paths, such as one for DJ sets or children's music,
+/// that can be switched to from the sidebar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryProfile {
+    /// The name shown in the sidebar. Names must be unique.
+    pub name: String,
+    /// The folders scanned into this library, in place of the paths in the scan settings. The
+    /// rest of the scan settings are shared between all libraries.
+    #[serde(default)]
+    pub paths: Vec<ScanPath>,
+    /// Where the library's database, album art and scan record are kept. Libraries given the
+    /// same directory share a database, and are scanned with their paths combined.
+    ///
+    /// Defaults to a folder named after the library in the `libraries` folder of the data
+    /// directory.
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+}
+
+impl LibraryProfile {
+    /// Returns the directory the library's database is kept in.
+    pub fn directory(&self, data_dir: &Path) -> PathBuf {
+        self.directory.clone().unwrap_or_else(|| {
+            let name: String = self
+                .name
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' || c == '_' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+
+            data_dir.join("libraries").join(name)
+        })
+    }
+}
+
+/// Returns the profile with the given name. `None`, or a name that's no longer in the settings,
+/// refers to the default library.
+pub fn find_profile<'a>(settings: &'a Settings, name: Option<&str>) -> Option<&'a LibraryProfile> {
+    let name = name?;
+    settings
+        .libraries
+        .iter()
+        .find(|profile| profile.name == name)
+}
+
+/// The scan settings for the library kept in `directory`: the paths of every library kept there,
+/// plus the scan settings' own paths and remote folders if it's the default library's directory.
+pub fn scan_settings(settings: &Settings, directory: &Path, data_dir: &Path) -> ScanSettings {
+    let mut scanning = settings.scanning.clone();
+
+    if directory != data_dir {
+        scanning.paths.clear();
+        scanning.remotes.clear();
+    }
+
+    for profile in &settings.libraries {
+        if profile.directory(data_dir) != directory {
+            continue;
+        }
+
+        for path in &profile.paths {
+            if !scanning
+                .paths
+                .iter()
+                .any(|scan_path| scan_path.path == path.path)
+            {
+                scanning.paths.push(path.clone());
+            }
+        }
+    }
+
+    scanning
+}
+
+/// The lists of scan paths that make up the library kept in `directory`.
+fn path_lists_mut<'a>(
+    settings: &'a mut Settings,
+    directory: &'a Path,
+    data_dir: &'a Path,
+) -> impl Iterator<Item = &'a mut Vec<ScanPath>> {
+    let default_paths = (directory == data_dir).then_some(&mut settings.scanning.paths);
+    let profile_paths = settings
+        .libraries
+        .iter_mut()
+        .filter(move |profile| profile.directory(data_dir) == directory)
+        .map(|profile| &mut profile.paths);
+
+    default_paths.into_iter().chain(profile_paths)
+}
+
+/// Returns the scan path for the folder in the library kept in `directory`.
+pub fn find_scan_path_mut<'a>(
+    settings: &'a mut Settings,
+    directory: &'a Path,
+    data_dir: &'a Path,
+    folder: &Path,
+) -> Option<&'a mut ScanPath> {
+    path_lists_mut(settings, directory, data_dir)
+        .flat_map(|paths| paths.iter_mut())
+        .find(|path| path.path == folder)
+}
+
+/// Adds the folder to the scan paths of the library with the given name.
+pub fn add_scan_path(settings: &mut Settings, name: Option<&str>, folder: PathBuf) {
+    let paths = match name {
+        Some(name) => settings
+            .libraries
+            .iter_mut()
+            .find(|profile| profile.name == name)
+            .map(|profile| &mut profile.paths),
+        None => None,
+    }
+    .unwrap_or(&mut settings.scanning.paths);
+
+    if !paths.iter().any(|path| path.path == folder) {
+        paths.push(folder.into());
+    }
+}
+
+/// Removes the folder from the scan paths of the library kept in `directory`.
+pub fn remove_scan_path(settings: &mut Settings, directory: &Path, data_dir: &Path, folder: &Path) {
+    for paths in path_lists_mut(settings, directory, data_dir) {
+        paths.retain(|path| path.path != folder);
+    }
+}
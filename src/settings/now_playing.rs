@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Writes information about the current track to a file, for stream overlays and other tools.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NowPlayingSettings {
+    /// The file (or named pipe) to write to. Nothing is written unless this is set.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Whether the file contains the [template](Self::template) or a JSON object.
+    ///
+    /// Defaults to text.
+    #[serde(default)]
+    pub format: NowPlayingFormat,
+    /// The text written to the file when the format is text.
+    ///
+    /// Supports the same placeholders as script arguments, as well as `%position%`, `%duration%`
+    /// and `%state%`. Defaults to `%artist% - %title%`.
+    #[serde(default = "default_template")]
+    pub template: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NowPlayingFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+fn default_template() -> String {
+    "%artist% - %title%".to_string()
+}
+
+impl Default for NowPlayingSettings {
+    fn default() -> Self {
+        Self {
+            path: None,
+            format: NowPlayingFormat::default(),
+            template: default_template(),
+        }
+    }
+}
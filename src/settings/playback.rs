@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use crate::devices::builtin::pcm::DEFAULT_SAMPLE_RATE;
+
 /// User-set playback settings, to be passed to the playback thread.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlaybackSettings {
     /// Whether or not the playback thread should allow for repeating to be disabled.
     ///
@@ -28,14 +30,199 @@ pub struct PlaybackSettings {
     /// prefer this behavior)
     #[serde(default)]
     pub prev_track_jump_first: bool,
+
+    /// Whether silence at the start and end of tracks is skipped, which tightens the gaps between
+    /// tracks ripped from CDs with padding. Silence is found during analysis (see
+    /// [crate::settings::analysis::AnalysisSettings::enabled]), so tracks that haven't been
+    /// analyzed are played in full.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub skip_silence: bool,
+
+    /// Whether the next and previous track buttons skip over whole classical works instead of
+    /// single movements. Tracks without a work are skipped one at a time as usual.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub skip_by_work: bool,
+
+    /// Outputs that send raw PCM to a file, named pipe, or TCP socket, such as the input of a
+    /// Snapcast server. These are listed alongside the computer's own output devices.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub pcm_outputs: Vec<PcmOutput>,
+
+    /// How the volume slider maps to the level audio is played at. See [VolumeCurve].
+    ///
+    /// Defaults to logarithmic.
+    #[serde(default)]
+    pub volume_curve: VolumeCurve,
+
+    /// How far the short seek shortcuts move through the track, in seconds.
+    ///
+    /// Defaults to 5.
+    #[serde(default = "default_seek_step")]
+    pub seek_step: u64,
+
+    /// How far the long seek shortcuts move through the track, in seconds.
+    ///
+    /// Defaults to 30.
+    #[serde(default = "default_long_seek_step")]
+    pub long_seek_step: u64,
+
+    /// How much the volume shortcuts change the volume by, from 0.0 to 1.0.
+    ///
+    /// Defaults to 0.05.
+    #[serde(default = "default_volume_step")]
+    pub volume_step: f64,
+
+    /// How much space the copies of tracks in remote folders may take up in the cache, in
+    /// megabytes. The copies that were played the longest ago are removed first. Tracks kept for
+    /// offline listening aren't counted.
+    ///
+    /// Defaults to 2048.
+    #[serde(default = "default_remote_cache_size")]
+    pub remote_cache_size: u64,
+
+    /// How much of a track in a remote folder is downloaded ahead of what's playing before
+    /// playback starts, or carries on after the download falls behind, in seconds.
+    ///
+    /// Defaults to 10.
+    #[serde(default = "default_stream_read_ahead")]
+    pub stream_read_ahead: u64,
+
+    /// What happens to playback while another application plays audio, such as a call or a
+    /// video. See [OtherAudio]. Only Windows and macOS (14.4 and later) can tell when other
+    /// applications play audio.
+    ///
+    /// Defaults to ignoring other applications.
+    #[serde(default)]
+    pub other_audio: OtherAudio,
+
+    /// Whether playback is paused when the computer is locked.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub pause_when_locked: bool,
+
+    /// How long the computer has to go without keyboard or mouse input before playback is
+    /// paused, in minutes. Playback isn't paused for being idle if this isn't set.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub pause_when_idle: Option<u64>,
+
+    /// Whether tracks played while the computer is locked are treated like private listening,
+    /// so that they aren't scrobbled, added to the history or shown to others.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub private_when_locked: bool,
+
+    /// Whether the playback position shown in the interface is only updated every so often while
+    /// the computer is locked, to save redrawing what nobody can see.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub defer_position_when_locked: bool,
+}
+
+/// What happens to playback while another application plays audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OtherAudio {
+    /// Playback carries on as usual.
+    #[default]
+    Ignore,
+    /// Playback is paused, and resumed once the other application stops, unless it was
+    /// started or paused again in the meantime.
+    Pause,
+    /// Playback is turned down, and turned back up once the other application stops.
+    Duck,
+}
+
+/// How the volume picked by the user is turned into the level audio is played at. Network devices
+/// always use their own curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeCurve {
+    /// Each step on the slider changes the loudness by about the same amount, which is how
+    /// loudness is heard. The bottom tenth of the slider fades linearly down to silence.
+    #[default]
+    Logarithmic,
+    /// The slider is the amplitude the audio is scaled by, so most of the change in loudness
+    /// happens in the bottom part of the slider.
+    Linear,
+}
+
+/// An output that writes signed 16-bit little-endian stereo PCM to a target. See
+/// [crate::devices::builtin::pcm::PcmDevice] for the targets that are supported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PcmOutput {
+    /// The name shown in the devices dialog. Each output must have a different name.
+    pub name: String,
+    pub target: String,
+    /// Defaults to 48000, to match Snapcast's default sample format.
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+}
+
+fn default_sample_rate() -> u32 {
+    DEFAULT_SAMPLE_RATE
+}
+
+fn default_seek_step() -> u64 {
+    5
+}
+
+fn default_long_seek_step() -> u64 {
+    30
+}
+
+fn default_remote_cache_size() -> u64 {
+    2048
+}
+
+fn default_stream_read_ahead() -> u64 {
+    10
+}
+
+fn default_volume_step() -> f64 {
+    0.05
 }
 
-#[allow(clippy::derivable_impls)]
 impl Default for PlaybackSettings {
     fn default() -> Self {
         Self {
             always_repeat: false,
             prev_track_jump_first: false,
+            skip_silence: false,
+            skip_by_work: false,
+            pcm_outputs: Vec::new(),
+            volume_curve: VolumeCurve::Logarithmic,
+            seek_step: default_seek_step(),
+            long_seek_step: default_long_seek_step(),
+            volume_step: default_volume_step(),
+            remote_cache_size: default_remote_cache_size(),
+            stream_read_ahead: default_stream_read_ahead(),
+            other_audio: OtherAudio::default(),
+            pause_when_locked: false,
+            pause_when_idle: None,
+            private_when_locked: false,
+            defer_position_when_locked: false,
         }
     }
 }
+
+impl PlaybackSettings {
+    /// Whether any setting depends on the computer being locked or idle, so that the session
+    /// needs to be watched.
+    pub fn watches_session(&self) -> bool {
+        self.pause_when_locked
+            || self.pause_when_idle.is_some()
+            || self.private_when_locked
+            || self.defer_position_when_locked
+    }
+}
@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Shares where long tracks were left with other computers running Hummingbird, through a file
+/// in a folder that's kept in sync between them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ResumeSyncSettings {
+    /// The synced folder (such as a Dropbox or Syncthing folder) to keep the file in. Nothing is
+    /// shared unless this is set.
+    #[serde(default)]
+    pub folder: Option<PathBuf>,
+    /// The name this computer goes by in the file.
+    ///
+    /// Defaults to the computer's host name.
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
@@ -1,23 +1,212 @@
-use std::{fs::exists, path::PathBuf};
+use std::{cmp::Reverse, collections::BTreeMap, fs::exists, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 use tracing::{error, warn};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::library::guess::DEFAULT_PATTERNS;
+
+/// What a scan path holds, which decides the section of the library its tracks are listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    #[default]
+    Music,
+    /// Treated the same as folders in `audiobook_paths`.
+    Audiobooks,
+}
+
+/// A folder that's scanned into the library.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ScanPathEntry", into = "ScanPathEntry")]
+pub struct ScanPath {
+    pub path: PathBuf,
+    /// Whether the folder is watched for changes while Hummingbird is running, so that files
+    /// that are added, changed or removed are scanned straight away.
+    ///
+    /// Defaults to false.
+    pub watch: bool,
+    /// Folders with a higher priority are scanned first.
+    ///
+    /// Defaults to 0.
+    pub priority: i32,
+    /// What the folder holds.
+    ///
+    /// Defaults to music.
+    pub content: ContentType,
+}
+
+impl From<PathBuf> for ScanPath {
+    fn from(path: PathBuf) -> Self {
+        ScanPath {
+            path,
+            watch: false,
+            priority: 0,
+            content: ContentType::Music,
+        }
+    }
+}
+
+/// Scan paths can be written as just the path, or as an object with their options.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ScanPathEntry {
+    Path(PathBuf),
+    Options {
+        path: PathBuf,
+        #[serde(default)]
+        watch: bool,
+        #[serde(default)]
+        priority: i32,
+        #[serde(default)]
+        content: ContentType,
+    },
+}
+
+impl From<ScanPathEntry> for ScanPath {
+    fn from(entry: ScanPathEntry) -> Self {
+        match entry {
+            ScanPathEntry::Path(path) => path.into(),
+            ScanPathEntry::Options {
+                path,
+                watch,
+                priority,
+                content,
+            } => ScanPath {
+                path,
+                watch,
+                priority,
+                content,
+            },
+        }
+    }
+}
+
+impl From<ScanPath> for ScanPathEntry {
+    fn from(path: ScanPath) -> Self {
+        if path == ScanPath::from(path.path.clone()) {
+            return ScanPathEntry::Path(path.path);
+        }
+
+        ScanPathEntry::Options {
+            path: path.path,
+            watch: path.watch,
+            priority: path.priority,
+            content: path.content,
+        }
+    }
+}
+
+/// A folder on a WebDAV or SFTP server that's scanned into the library. Its files are downloaded
+/// to be scanned and played.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSource {
+    /// The address of the folder, such as `https://cloud.example.com/dav/Music` or
+    /// `sftp://me@nas.local/srv/music`.
+    pub url: String,
+    /// The user name to sign in to a WebDAV server with. SFTP servers are signed in to with the
+    /// user name in the address and your SSH keys.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// The password to sign in to a WebDAV server with.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScanSettings {
     #[serde(default = "retrieve_default_paths")]
-    pub paths: Vec<PathBuf>,
+    pub paths: Vec<ScanPath>,
+    /// Patterns used to guess the tags of files that have none, tried in order. An empty list
+    /// turns guessing off.
+    #[serde(default = "default_filename_patterns")]
+    pub filename_patterns: Vec<String>,
+    /// Genres that are renamed when tracks are scanned, from the name in the tags (such as
+    /// `hip hop`) to the name used in the library (such as `Hip-Hop`). Names are matched ignoring
+    /// case, spaces, hyphens and other punctuation, and changes are applied to tracks that were
+    /// already scanned too.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub genre_aliases: BTreeMap<String, String>,
+    /// Artists that are renamed when tracks are scanned, from a name in the tags (such as
+    /// `Beyonce`) to the name of the artist in the library (such as `Beyoncé`). Names are matched
+    /// ignoring case, spaces and punctuation. Artists already in the library under an alias are
+    /// merged into the artist it's for. Merging artists in the library adds to these.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub artist_aliases: BTreeMap<String, String>,
+    /// Folders holding audiobooks rather than music. They need to be in one of the scanned
+    /// folders. Audiobook chapters always resume from where they were left, aren't shuffled or
+    /// scrobbled, and are listed in their own section.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub audiobook_paths: Vec<PathBuf>,
+    /// Folders on WebDAV and SFTP servers that are scanned along with the scan paths.
+    ///
+    /// Defaults to none.
+    #[serde(default)]
+    pub remotes: Vec<RemoteSource>,
 }
 
 impl Default for ScanSettings {
     fn default() -> Self {
         Self {
             paths: retrieve_default_paths(),
+            filename_patterns: default_filename_patterns(),
+            genre_aliases: BTreeMap::new(),
+            artist_aliases: BTreeMap::new(),
+            audiobook_paths: Vec::new(),
+            remotes: Vec::new(),
         }
     }
 }
 
-fn retrieve_default_paths() -> Vec<PathBuf> {
+impl ScanSettings {
+    /// The scan paths, highest priority first. Paths with the same priority are kept in the
+    /// order they're listed in.
+    pub fn folders(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<&ScanPath> = self.paths.iter().collect();
+        paths.sort_by_key(|path| Reverse(path.priority));
+        paths.into_iter().map(|path| path.path.clone()).collect()
+    }
+
+    /// The scan paths that are watched for changes.
+    pub fn watched_folders(&self) -> Vec<PathBuf> {
+        self.paths
+            .iter()
+            .filter(|path| path.watch)
+            .map(|path| path.path.clone())
+            .collect()
+    }
+
+    /// The folders holding audiobooks: those in `audiobook_paths`, and scan paths holding
+    /// audiobooks.
+    pub fn audiobook_folders(&self) -> Vec<PathBuf> {
+        let scan_paths = self
+            .paths
+            .iter()
+            .filter(|path| path.content == ContentType::Audiobooks)
+            .map(|path| path.path.clone());
+
+        self.audiobook_paths
+            .iter()
+            .cloned()
+            .chain(scan_paths)
+            .collect()
+    }
+}
+
+fn default_filename_patterns() -> Vec<String> {
+    DEFAULT_PATTERNS.iter().map(|v| v.to_string()).collect()
+}
+
+fn retrieve_default_paths() -> Vec<ScanPath> {
     #[cfg(target_os = "windows")]
     {
         use windows::Storage::{KnownLibraryId, StorageLibrary};
@@ -29,7 +218,7 @@ fn retrieve_default_paths() -> Vec<PathBuf> {
             .Folders()
             .unwrap()
             .into_iter()
-            .map(|v| v.Path().unwrap().to_string().into())
+            .map(|v| PathBuf::from(v.Path().unwrap().to_string()).into())
             .collect()
     }
 
@@ -38,7 +227,7 @@ fn retrieve_default_paths() -> Vec<PathBuf> {
         if let Some(user_directories) = directories::UserDirs::new() {
             if let Some(dir) = user_directories.audio_dir() {
                 if exists(dir).unwrap_or(false) {
-                    return vec![dir.into()];
+                    return vec![dir.to_path_buf().into()];
                 } else {
                     warn!("Music directory doesn't exist: nothing will be scanned by default.");
                 }
@@ -46,7 +235,7 @@ fn retrieve_default_paths() -> Vec<PathBuf> {
                 let dir = user_directories.home_dir().join("Music");
                 warn!("Music directory couldn't be discovered normally, using $HOME/Music.");
                 if exists(&dir).unwrap_or(false) {
-                    return vec![dir];
+                    return vec![dir.into()];
                 } else {
                     warn!("$HOME/Music doesn't exist: nothing will be scanned by default.");
                 }
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// External programs that can be run from the command palette, or when something happens during
+/// playback.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptSettings {
+    /// Commands added to the command palette.
+    #[serde(default)]
+    pub commands: Vec<CustomCommand>,
+
+    /// Programs run in response to playback events.
+    #[serde(default)]
+    pub hooks: ScriptHooks,
+}
+
+/// A program to run, and the arguments to pass to it.
+///
+/// Arguments can contain placeholders, which are replaced with information about the currently
+/// playing track: `%path%`, `%title%`, `%artist%`, `%album%`, `%album_artist%` and `%genre%`.
+/// Placeholders without a value are replaced with an empty string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    /// The name shown in the command palette.
+    pub name: String,
+    #[serde(flatten)]
+    pub command: ExternalCommand,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptHooks {
+    /// Run when a new track starts playing, once its metadata has been read.
+    #[serde(default)]
+    pub track_changed: Vec<ExternalCommand>,
+
+    /// Run when the current track has been listened to for long enough to count as played. This
+    /// follows the same rules as Last.fm scrobbling.
+    #[serde(default)]
+    pub scrobbled: Vec<ExternalCommand>,
+}
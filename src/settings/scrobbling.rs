@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for the services plays are sent to. Each service has its own queue, so any number of
+/// them can be used at once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ScrobblingSettings {
+    #[serde(default)]
+    pub lastfm: LastFmSettings,
+    #[serde(default)]
+    pub listenbrainz: ListenBrainzSettings,
+    #[serde(default)]
+    pub librefm: LibreFmSettings,
+}
+
+/// Last.fm is signed in to from the button in the header, so the session isn't kept here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastFmSettings {
+    /// Whether plays are sent to Last.fm once signed in.
+    ///
+    /// Defaults to true.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl Default for LastFmSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListenBrainzSettings {
+    /// The user token from ListenBrainz's settings page. Plays are only sent when a token is set.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// The server to send plays to. Other servers that implement the ListenBrainz API (like
+    /// Maloja) can be used by changing this.
+    ///
+    /// Defaults to `https://api.listenbrainz.org`.
+    #[serde(default = "default_listenbrainz_url")]
+    pub api_url: String,
+}
+
+impl Default for ListenBrainzSettings {
+    fn default() -> Self {
+        Self {
+            token: None,
+            api_url: default_listenbrainz_url(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct LibreFmSettings {
+    /// Plays are only sent when a username is set.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Only needed to sign in: the session is saved afterwards, and the password can be removed.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_listenbrainz_url() -> String {
+    "https://api.listenbrainz.org".to_string()
+}
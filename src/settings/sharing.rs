@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for sharing the library with other devices on the network.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharingSettings {
+    /// Whether the library is shared as a UPnP media server, so that TVs, receivers and other
+    /// DLNA clients on the network can browse and play it. Only read on startup.
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub upnp_server: bool,
+    /// The name the library is shown with on other devices.
+    ///
+    /// Defaults to "Hummingbird".
+    #[serde(default = "default_name")]
+    pub name: String,
+}
+
+fn default_name() -> String {
+    "Hummingbird".to_string()
+}
+
+impl Default for SharingSettings {
+    fn default() -> Self {
+        Self {
+            upnp_server: false,
+            name: default_name(),
+        }
+    }
+}
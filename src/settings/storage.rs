@@ -1,13 +1,66 @@
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
-use crate::ui::models::CurrentTrack;
+use crate::{
+    media::metadata::cue_track_file,
+    ui::{library::ViewSwitchMessage, models::CurrentTrack},
+};
 
 use std::{fs, path::PathBuf};
 
 /// Data to store while quitting the app
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageData {
     pub current_track: Option<CurrentTrack>,
+    #[serde(default)]
+    pub window: Option<WindowState>,
+    #[serde(default = "default_true")]
+    pub show_queue: bool,
+    #[serde(default = "default_true")]
+    pub show_sidebar: bool,
+    /// The library view that was open, so that it can be reopened on the next start.
+    #[serde(default)]
+    pub view: Option<ViewSwitchMessage>,
+    /// The UID of the output device that was picked, if it isn't the default device.
+    #[serde(default)]
+    pub output_device: Option<String>,
+    /// The volume last used on each output, so that switching between outputs doesn't leave
+    /// headphones at speaker volume.
+    #[serde(default)]
+    pub output_volumes: FxHashMap<String, f64>,
+    /// The name of the library that was open, if it isn't the default library.
+    #[serde(default)]
+    pub library: Option<String>,
+}
+
+impl Default for StorageData {
+    fn default() -> Self {
+        Self {
+            current_track: None,
+            window: None,
+            show_queue: true,
+            show_sidebar: true,
+            view: None,
+            output_device: None,
+            output_volumes: FxHashMap::default(),
+            library: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The position and size of the main window. When the window is maximized, the bounds are the
+/// bounds the window will return to when it is restored.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub maximized: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -39,10 +92,16 @@ impl Storage {
                 serde_json::from_reader(file)
                     .map_err(|e| e.into())
                     .map(|data: StorageData| match &data.current_track {
-                        // validate whether path still exists
-                        Some(current_track) if !current_track.get_path().exists() => StorageData {
-                            current_track: None,
-                        },
+                        // validate whether path (or the file holding the cue track) still exists
+                        Some(current_track)
+                            if !current_track.get_path().exists()
+                                && cue_track_file(current_track.get_path()).is_none() =>
+                        {
+                            StorageData {
+                                current_track: None,
+                                ..data
+                            }
+                        }
                         _ => data,
                     })
             })
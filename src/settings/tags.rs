@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings for writing to the tags of files in the library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSettings {
+    /// The largest width or height, in pixels, of album art embedded into files. Larger images
+    /// are scaled down before being embedded.
+    ///
+    /// Defaults to 1000.
+    #[serde(default = "default_embed_art_max_size")]
+    pub embed_art_max_size: u32,
+}
+
+impl Default for TagSettings {
+    fn default() -> Self {
+        Self {
+            embed_art_max_size: default_embed_art_max_size(),
+        }
+    }
+}
+
+fn default_embed_art_max_size() -> u32 {
+    1000
+}
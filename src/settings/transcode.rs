@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// A format files can be converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscodeFormat {
+    Opus,
+    Vorbis,
+    Mp3,
+    Aac,
+    Flac,
+}
+
+/// A named set of options for converting files, used when syncing to devices and converting
+/// tracks from the library.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscodeProfile {
+    pub name: String,
+    pub format: TranscodeFormat,
+    /// The bitrate in kilobits per second. Ignored for FLAC, which is lossless. If not set, the
+    /// encoder's default is used.
+    #[serde(default)]
+    pub bitrate: Option<u32>,
+    /// The sample rate of the converted files in hertz. If not set, the source's rate is kept
+    /// where the format allows it.
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+}
+
+/// Settings for converting files to other formats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscodeSettings {
+    /// The ffmpeg executable used to convert files. If not set, ffmpeg is looked for on the
+    /// `PATH`.
+    #[serde(default)]
+    pub ffmpeg: Option<PathBuf>,
+    /// The profiles files can be converted with.
+    ///
+    /// Defaults to Opus at 128 kbps, MP3 at 320 kbps, and AAC at 256 kbps.
+    #[serde(default = "default_profiles")]
+    pub profiles: Vec<TranscodeProfile>,
+}
+
+impl TranscodeSettings {
+    pub fn profile(&self, name: &str) -> Option<&TranscodeProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+}
+
+impl Default for TranscodeSettings {
+    fn default() -> Self {
+        Self {
+            ffmpeg: None,
+            profiles: default_profiles(),
+        }
+    }
+}
+
+fn default_profiles() -> Vec<TranscodeProfile> {
+    [
+        ("opus-128", TranscodeFormat::Opus, 128),
+        ("mp3-320", TranscodeFormat::Mp3, 320),
+        ("aac-256", TranscodeFormat::Aac, 256),
+    ]
+    .into_iter()
+    .map(|(name, format, bitrate)| TranscodeProfile {
+        name: name.to_string(),
+        format,
+        bitrate: Some(bitrate),
+        sample_rate: None,
+    })
+    .collect()
+}
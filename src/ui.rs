@@ -1,8 +1,9 @@
 mod about;
 pub mod app;
-mod arguments;
+pub mod arguments;
 mod assets;
 mod caching;
+mod cast;
 mod command_palette;
 pub mod components;
 mod constants;
@@ -10,8 +11,10 @@ mod controls;
 pub mod data;
 mod global_actions;
 mod header;
-mod library;
+pub mod library;
+pub mod locale;
 pub mod models;
+mod onboarding;
 mod queue;
 mod search;
 mod theme;
@@ -1,11 +1,16 @@
 use gpui::{
-    FontWeight, InteractiveElement, IntoElement, ParentElement, RenderOnce,
-    StatefulInteractiveElement, Styled, div, img, px,
+    FontWeight, InteractiveElement, IntoElement, ParentElement, RenderOnce, SharedString,
+    StatefulInteractiveElement, Styled, div, img, prelude::FluentBuilder,
 };
 
+use crate::services::controllers::ControllerHealth;
+
 use super::{
     components::modal::{OnExitHandler, modal},
+    locale::t,
+    models::Models,
     theme::Theme,
+    util::spx,
 };
 
 const ISSUES_URL: &str = "https://github.com/143mailliw/hummingbird/issues";
@@ -23,47 +28,70 @@ impl RenderOnce for AboutDialog {
         let version = env!("CARGO_PKG_VERSION");
         let hash = env!("VERGEN_GIT_SHA");
 
+        let mut controllers: Vec<SharedString> = cx
+            .global::<Models>()
+            .controller_health
+            .read(cx)
+            .iter()
+            .map(|(name, health)| match health {
+                ControllerHealth::Healthy => t!(cx, "about.controller_healthy", name = name),
+                ControllerHealth::Failing(error) => {
+                    t!(cx, "about.controller_failing", name = name, error = error)
+                }
+                ControllerHealth::Unavailable(error) => {
+                    t!(
+                        cx,
+                        "about.controller_unavailable",
+                        name = name,
+                        error = error
+                    )
+                }
+            })
+            .collect();
+        controllers.sort();
+
         modal().on_exit(self.on_exit).child(
             div()
-                .p(px(20.0))
-                .pb(px(18.0))
+                .p(spx(20.0))
+                .pb(spx(18.0))
                 .flex()
-                .child(img("!bundled:images/logo.png").w(px(66.0)).mr(px(20.0)))
+                .child(img("!bundled:images/logo.png").w(spx(66.0)).mr(spx(20.0)))
                 .child(
                     div()
                         .flex()
                         .flex_col()
                         .child(
-                            div().flex().mr(px(200.0)).child(
+                            div().flex().mr(spx(200.0)).child(
                                 div()
                                     .child(
                                         div()
                                             .font_weight(FontWeight::BOLD)
                                             .font_family("Lexend")
-                                            .text_size(px(36.0))
-                                            .line_height(px(36.0))
-                                            .ml(px(-2.0))
+                                            .text_size(spx(36.0))
+                                            .line_height(spx(36.0))
+                                            .ml(spx(-2.0))
                                             .child("Hummingbird"),
                                     )
                                     .child(
                                         div()
-                                            .text_size(px(13.0))
-                                            .line_height(px(13.0))
+                                            .text_size(spx(13.0))
+                                            .line_height(spx(13.0))
                                             .text_color(theme.text_secondary)
-                                            .mt(px(1.0))
+                                            .mt(spx(1.0))
                                             .child(format!("{version} ({hash})")),
                                     ),
                             ),
                         )
                         .child(
-                            div().mt(px(15.0)).flex().child(
+                            div().mt(spx(15.0)).flex().child(
                                 div()
                                     .text_sm()
-                                    .text_size(px(13.0))
+                                    .text_size(spx(13.0))
                                     .text_color(theme.text_secondary)
                                     .child(
                                         div()
                                             .flex()
+                                            .flex_wrap()
                                             .child(
                                                 div()
                                                     .id("about-bug-link")
@@ -76,9 +104,9 @@ impl RenderOnce for AboutDialog {
                                                     .on_click(|_, _, cx| {
                                                         cx.open_url(ISSUES_URL);
                                                     })
-                                                    .child("Report a bug"),
+                                                    .child(t!(cx, "about.report_bug")),
                                             )
-                                            .child(" or ")
+                                            .child(t!(cx, "about.or"))
                                             .child(
                                                 div()
                                                     .id("about-source-link")
@@ -91,20 +119,16 @@ impl RenderOnce for AboutDialog {
                                                     .on_click(|_, _, cx| {
                                                         cx.open_url(SOURCE_URL);
                                                     })
-                                                    .child("view the source code"),
+                                                    .child(t!(cx, "about.view_source")),
                                             )
-                                            .child(" on GitHub."),
+                                            .child(t!(cx, "about.on_github")),
                                     )
-                                    .child(div().child(
-                                        "Copyright © 2024 - 2025 William Whittaker and \
-                                        contributors.",
-                                    ))
+                                    .child(div().child(t!(cx, "about.copyright")))
                                     .child(
                                         div()
                                             .flex()
-                                            .child(
-                                                "Licensed under the Apache License, version 2.0. ",
-                                            )
+                                            .flex_wrap()
+                                            .child(t!(cx, "about.license"))
                                             .child(
                                                 div()
                                                     .id("about-rights-link")
@@ -117,11 +141,25 @@ impl RenderOnce for AboutDialog {
                                                     .on_click(|_, _, cx| {
                                                         cx.open_url(LICENSE_URL);
                                                     })
-                                                    .child("Learn more about your rights."),
+                                                    .child(t!(cx, "about.rights")),
                                             ),
                                     ),
                             ),
-                        ),
+                        )
+                        .when(!controllers.is_empty(), |this| {
+                            this.child(
+                                div()
+                                    .mt(spx(15.0))
+                                    .text_size(spx(13.0))
+                                    .text_color(theme.text_secondary)
+                                    .child(
+                                        div()
+                                            .font_weight(FontWeight::BOLD)
+                                            .child(t!(cx, "about.controllers")),
+                                    )
+                                    .children(controllers),
+                            )
+                        }),
                 ),
         )
     }
@@ -1,6 +1,7 @@
 use std::{
     fs,
-    sync::{Arc, RwLock},
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, RwLock},
 };
 
 use directories::ProjectDirs;
@@ -11,17 +12,24 @@ use tracing::debug;
 
 use crate::{
     library::{
-        db::create_pool,
+        analysis::{AnalysisInterface, AnalysisThread},
+        art::migrate_album_art,
+        db::{create_pool, create_read_pool, purge_deleted_playlists},
         scan::{ScanInterface, ScanThread},
     },
     playback::{interface::PlaybackInterface, queue::QueueItemData, thread::PlaybackThread},
-    services::controllers::{init_pbc_task, register_pbc_event_handlers},
+    services::{
+        controllers::{init_pbc_task, register_pbc_event_handlers},
+        dlna::server::share_library,
+    },
     settings::{
-        SettingsGlobal, setup_settings,
-        storage::{Storage, StorageData},
+        Settings, SettingsGlobal, create_settings, libraries,
+        scan::ScanSettings,
+        setup_settings,
+        storage::{Storage, StorageData, WindowState},
     },
     ui::{
-        assets::HummingbirdAssetSource,
+        assets::{HummingbirdAssetSource, db::DbAssetLoader},
         command_palette::{CommandPalette, CommandPaletteHolder},
         constants::APP_SHADOW_SIZE,
         library,
@@ -30,18 +38,20 @@ use crate::{
 
 use super::{
     about::about_dialog,
-    arguments::parse_args_and_prepare,
+    arguments::{Args, parse_args_and_prepare},
     components::{input, modal},
     constants::APP_ROUNDING,
     controls::Controls,
-    global_actions::register_actions,
+    global_actions::{bind_custom_keys, register_actions, register_custom_commands},
     header::Header,
     library::Library,
+    locale::setup_locale,
     models::{self, Models, PlaybackInfo, build_models},
-    queue::Queue,
+    onboarding::Onboarding,
+    queue::{self, Queue},
     search::SearchView,
     theme::{Theme, setup_theme},
-    util::drop_image_from_app,
+    util::{drop_image_from_app, scaled_rem_size, spx},
 };
 
 struct WindowShadow {
@@ -51,8 +61,12 @@ struct WindowShadow {
     pub header: Entity<Header>,
     pub search: Entity<SearchView>,
     pub show_queue: Entity<bool>,
+    pub show_sidebar: Entity<bool>,
     pub show_about: Entity<bool>,
     pub palette: Entity<CommandPalette>,
+    pub onboarding: Entity<Onboarding>,
+    /// The most recent position and size of the window, saved when the app quits.
+    pub window_state: WindowState,
 }
 
 impl Render for WindowShadow {
@@ -62,11 +76,13 @@ impl Render for WindowShadow {
         let decorations = window.window_decorations();
         let rounding = APP_ROUNDING;
         let shadow_size = APP_SHADOW_SIZE;
-        let border_size = px(1.0);
+        let border_size = spx(1.0);
         window.set_client_inset(shadow_size);
+        window.set_rem_size(scaled_rem_size(cx));
 
         let queue = self.queue.clone();
         let show_about = *self.show_about.clone().read(cx);
+        let queue_detached = cx.global::<Models>().queue_window.read(cx).is_some();
 
         let mut element = div()
             .id("window-backdrop")
@@ -209,11 +225,14 @@ impl Render for WindowShadow {
                             .max_h_full()
                             .overflow_hidden()
                             .child(self.library.clone())
-                            .when(*self.show_queue.read(cx), |this| this.child(queue)),
+                            .when(*self.show_queue.read(cx) && !queue_detached, |this| {
+                                this.child(queue)
+                            }),
                     )
                     .child(self.controls.clone())
                     .child(self.search.clone())
                     .child(self.palette.clone())
+                    .child(self.onboarding.clone())
                     .when(show_about, |this| {
                         this.child(about_dialog(&|_, cx| {
                             let show_about = cx.global::<Models>().show_about.clone();
@@ -273,6 +292,57 @@ fn resize_edge(
     Some(edge)
 }
 
+fn window_state(window: &Window) -> WindowState {
+    let (bounds, maximized) = match window.window_bounds() {
+        WindowBounds::Windowed(bounds) => (bounds, false),
+        WindowBounds::Maximized(bounds) | WindowBounds::Fullscreen(bounds) => (bounds, true),
+    };
+
+    WindowState {
+        x: bounds.origin.x.into(),
+        y: bounds.origin.y.into(),
+        width: bounds.size.width.into(),
+        height: bounds.size.height.into(),
+        maximized,
+    }
+}
+
+/// Returns the bounds the main window should be opened with. The saved window state is ignored if
+/// the window would be smaller than `min_size`, or wouldn't be visible on any connected display
+/// (e.g. because the monitor it was on has since been disconnected).
+fn initial_window_bounds(
+    cx: &App,
+    saved: Option<WindowState>,
+    min_size: Size<Pixels>,
+) -> WindowBounds {
+    let default = WindowBounds::Windowed(Bounds::centered(None, size(px(1024.0), px(700.0)), cx));
+
+    let Some(saved) = saved else {
+        return default;
+    };
+
+    let bounds = Bounds::new(
+        point(px(saved.x), px(saved.y)),
+        size(px(saved.width), px(saved.height)),
+    );
+
+    let too_small = bounds.size.width < min_size.width || bounds.size.height < min_size.height;
+    let visible = cx
+        .displays()
+        .iter()
+        .any(|display| display.bounds().intersects(&bounds));
+
+    if too_small || !visible {
+        return default;
+    }
+
+    if saved.maximized {
+        WindowBounds::Maximized(bounds)
+    } else {
+        WindowBounds::Windowed(bounds)
+    }
+}
+
 pub fn find_fonts(cx: &mut App) -> gpui::Result<()> {
     let paths = cx.asset_source().list("!bundled:fonts")?;
     let mut fonts = vec![];
@@ -289,61 +359,248 @@ pub fn find_fonts(cx: &mut App) -> gpui::Result<()> {
     results
 }
 
-pub struct Pool(pub SqlitePool);
+pub struct Pool {
+    /// A read-only pool, used for anything that doesn't modify the database.
+    pub read: SqlitePool,
+    /// The single-connection pool all writes go through.
+    pub write: SqlitePool,
+}
 
 impl Global for Pool {}
 
-pub fn get_dirs() -> ProjectDirs {
-    let secondary_dirs = directories::ProjectDirs::from("me", "william341", "muzak")
+/// The library that was opened on startup. Switching libraries restarts the app.
+pub struct ActiveLibrary {
+    /// The name of the library profile, or `None` for the default library.
+    pub name: Option<String>,
+    /// The directory the library's database, album art and scan record are kept in.
+    pub directory: PathBuf,
+    /// The data directory, which holds the default library and everything that's shared between
+    /// libraries.
+    pub data_dir: PathBuf,
+}
+
+impl Global for ActiveLibrary {}
+
+impl ActiveLibrary {
+    pub fn art_dir(&self) -> PathBuf {
+        self.directory.join("art")
+    }
+
+    /// Returns the scan settings for this library from the given settings.
+    pub fn scan_settings(&self, settings: &Settings) -> ScanSettings {
+        libraries::scan_settings(settings, &self.directory, &self.data_dir)
+    }
+}
+
+/// Switches to the library with the given name, or the default library if `None`, by restarting
+/// the app.
+pub fn switch_library(cx: &mut App, name: Option<String>) {
+    if cx.global::<ActiveLibrary>().name == name {
+        return;
+    }
+
+    // the library is saved with the rest of the storage data when quitting
+    cx.global_mut::<ActiveLibrary>().name = name;
+    cx.restart();
+    cx.quit();
+}
+
+/// The directories Hummingbird keeps its files in.
+pub struct Dirs {
+    data_dir: PathBuf,
+    config_dir: PathBuf,
+    cache_dir: PathBuf,
+}
+
+impl Dirs {
+    /// Where the library and everything else that's stored is kept.
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// Where the settings, theme and translations are read from.
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+}
+
+static DIRS: OnceLock<Dirs> = OnceLock::new();
+
+/// Copies the contents of `from` into `to`, which is created if it doesn't exist.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies the data directory from before Muzak was renamed to Hummingbird into the new location,
+/// the first time Hummingbird is opened. The database is upgraded by its migrations when it's
+/// next opened. The old directory is left in place, so nothing is lost if the copy fails.
+fn migrate_muzak_dir(muzak_dir: &Path, data_dir: &Path) -> std::io::Result<()> {
+    // copied next to the new directory first, so that a partial copy is never mistaken for a
+    // finished one
+    let partial = data_dir.with_extension("migrating");
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+
+    copy_dir(muzak_dir, &partial)?;
+    fs::rename(&partial, data_dir)
+}
+
+fn project_dirs() -> ProjectDirs {
+    let muzak_dirs = directories::ProjectDirs::from("me", "william341", "muzak")
         .expect("couldn't generate project dirs (secondary)");
+    let dirs = directories::ProjectDirs::from("org", "mailliw", "hummingbird")
+        .expect("couldn't generate project dirs");
+
+    if muzak_dirs.data_dir().exists() && !dirs.data_dir().exists() {
+        tracing::info!(
+            "moving data from '{}' to '{}'",
+            muzak_dirs.data_dir().display(),
+            dirs.data_dir().display()
+        );
+
+        if let Err(error) = migrate_muzak_dir(muzak_dirs.data_dir(), dirs.data_dir()) {
+            tracing::error!(?error, "couldn't move data from the Muzak data directory");
+            return muzak_dirs;
+        }
+    }
 
-    if secondary_dirs.data_dir().exists() {
-        return secondary_dirs;
+    dirs
+}
+
+/// Overrides the directories returned by [get_dirs]. The config directory defaults to the data
+/// directory, and when the data directory is overridden the cache is kept inside it, so that
+/// separate data directories don't share anything. Must be called before [get_dirs] is first
+/// used.
+pub fn set_dirs(data_dir: Option<PathBuf>, config_dir: Option<PathBuf>) {
+    let project_dirs = project_dirs();
+    let cache_dir = match &data_dir {
+        Some(data_dir) => data_dir.join("cache"),
+        None => project_dirs.cache_dir().to_path_buf(),
+    };
+    let data_dir = data_dir.unwrap_or_else(|| project_dirs.data_dir().to_path_buf());
+    let config_dir = config_dir.unwrap_or_else(|| data_dir.clone());
+
+    let dirs = Dirs {
+        data_dir,
+        config_dir,
+        cache_dir,
+    };
+
+    if DIRS.set(dirs).is_err() {
+        tracing::warn!("directories were overridden after they were first used");
     }
+}
 
-    directories::ProjectDirs::from("org", "mailliw", "hummingbird")
-        .expect("couldn't generate project dirs")
+pub fn get_dirs() -> &'static Dirs {
+    DIRS.get_or_init(|| {
+        let project_dirs = project_dirs();
+
+        Dirs {
+            data_dir: project_dirs.data_dir().to_path_buf(),
+            config_dir: project_dirs.data_dir().to_path_buf(),
+            cache_dir: project_dirs.cache_dir().to_path_buf(),
+        }
+    })
 }
 
 pub struct DropImageDummyModel;
 
 impl EventEmitter<Vec<Arc<RenderImage>>> for DropImageDummyModel {}
 
-pub fn run() -> anyhow::Result<()> {
+pub fn run(args: Args) -> anyhow::Result<()> {
     let dirs = get_dirs();
     let data_dir = dirs.data_dir().to_path_buf();
-    fs::create_dir_all(&data_dir).inspect_err(|error| {
+    let config_dir = dirs.config_dir().to_path_buf();
+    for dir in [&data_dir, &config_dir] {
+        fs::create_dir_all(dir).inspect_err(|error| {
+            tracing::error!(?error, "couldn't create directory '{}'", dir.display())
+        })?;
+    }
+
+    // the storage file is written when quitting, so it's only missing the first time the app runs
+    let first_run = !data_dir.join("app_data.json").exists();
+    let storage = Storage::new(data_dir.join("app_data.json"));
+    let storage_data = storage.load_or_default();
+
+    let startup_settings = create_settings(&config_dir.join("settings.json"));
+    let library = libraries::find_profile(&startup_settings, storage_data.library.as_deref());
+    let library_dir = library
+        .map(|profile| profile.directory(&data_dir))
+        .unwrap_or_else(|| data_dir.clone());
+    let library_name = library.map(|profile| profile.name.clone());
+
+    fs::create_dir_all(&library_dir).inspect_err(|error| {
         tracing::error!(
             ?error,
-            "couldn't create data directory '{}'",
-            data_dir.display(),
+            "couldn't create library directory '{}'",
+            library_dir.display(),
         )
     })?;
 
     let pool = crate::RUNTIME
-        .block_on(create_pool(data_dir.join("library.db")))
+        .block_on(create_pool(library_dir.join("library.db")))
         .inspect_err(|error| {
             tracing::error!(?error, "fatal: unable to create database pool");
         })?;
 
+    let art_dir = library_dir.join("art");
+
+    // art that couldn't be migrated is still loaded from the database, so this isn't fatal
+    if let Err(error) = crate::RUNTIME.block_on(migrate_album_art(&pool, &art_dir)) {
+        tracing::error!(?error, "unable to move album art out of the database");
+    }
+
+    if let Err(error) = crate::RUNTIME.block_on(purge_deleted_playlists(&pool)) {
+        tracing::error!(?error, "unable to clear out old deleted playlists");
+    }
+
+    let read_pool = crate::RUNTIME
+        .block_on(create_read_pool(library_dir.join("library.db")))
+        .inspect_err(|error| {
+            tracing::error!(?error, "fatal: unable to create read-only database pool");
+        })?;
+
     Application::new()
-        .with_assets(HummingbirdAssetSource::new(pool.clone()))
+        .with_assets(HummingbirdAssetSource::new(
+            read_pool.clone(),
+            art_dir.clone(),
+        ))
         .run(move |cx: &mut App| {
-            let bounds = Bounds::centered(None, size(px(1024.0), px(700.0)), cx);
             find_fonts(cx).expect("unable to load fonts");
             register_actions(cx);
 
             let queue: Arc<RwLock<Vec<QueueItemData>>> = Arc::new(RwLock::new(Vec::new()));
-            let storage = Storage::new(data_dir.join("app_data.json"));
-            let storage_data = storage.load_or_default();
+            let original_queue: Arc<RwLock<Vec<QueueItemData>>> = Arc::new(RwLock::new(Vec::new()));
+            let min_size = size(px(800.0), px(600.0));
+            let window_bounds = initial_window_bounds(cx, storage_data.window, min_size);
 
-            setup_theme(cx, data_dir.join("theme.json"));
-            setup_settings(cx, data_dir.join("settings.json"));
+            setup_theme(cx, config_dir.join("theme.json"));
+            setup_settings(cx, config_dir.join("settings.json"));
+            setup_locale(cx, config_dir.join("locales"));
 
             build_models(
                 cx,
                 models::Queue {
                     data: queue.clone(),
+                    original: original_queue.clone(),
                     position: 0,
                 },
                 &storage_data,
@@ -351,17 +608,57 @@ pub fn run() -> anyhow::Result<()> {
 
             input::bind_actions(cx);
             modal::bind_actions(cx);
+            queue::bind_actions(cx);
             library::bind_actions(cx);
+            bind_custom_keys(cx);
+
+            cx.set_global(ActiveLibrary {
+                name: library_name,
+                directory: library_dir.clone(),
+                data_dir: data_dir.clone(),
+            });
 
             let settings = cx.global::<SettingsGlobal>().model.read(cx);
             let playback_settings = settings.playback.clone();
-            let mut scan_interface: ScanInterface =
-                ScanThread::start(pool.clone(), settings.scanning.clone());
+            let analysis_settings = settings.analysis.clone();
+            let sharing_settings = settings.sharing.clone();
+            let mut scan_interface: ScanInterface = ScanThread::start(
+                pool.clone(),
+                cx.global::<ActiveLibrary>().scan_settings(settings),
+                art_dir.clone(),
+                library_dir.join("scan_record.json"),
+            );
             scan_interface.scan();
             scan_interface.start_broadcast(cx);
 
             cx.set_global(scan_interface);
-            cx.set_global(Pool(pool));
+            cx.set_global(DbAssetLoader::start(read_pool.clone(), art_dir.clone()));
+
+            cx.set_global(AnalysisThread::start(pool.clone(), analysis_settings));
+
+            if sharing_settings.upnp_server
+                && let Err(error) = share_library(
+                    read_pool.clone(),
+                    &sharing_settings,
+                    &data_dir,
+                    playback_settings.remote_cache_size * 1024 * 1024,
+                )
+            {
+                tracing::error!(?error, "unable to share the library on the network");
+            }
+
+            cx.set_global(Pool {
+                read: read_pool,
+                write: pool,
+            });
+            models::create_history_mmbs(cx);
+            models::create_hooks_mmbs(cx);
+            models::create_scrobbler_mmbs(cx);
+            models::create_now_playing_mmbs(cx);
+            library::track_podcasts(cx);
+            library::track_audiobooks(cx);
+            library::track_resume_sync(cx);
+            library::track_tags(cx);
 
             let drop_model = cx.new(|_| DropImageDummyModel);
 
@@ -372,11 +669,20 @@ pub fn run() -> anyhow::Result<()> {
             })
             .detach();
 
-            let mut playback_interface: PlaybackInterface =
-                PlaybackThread::start(queue, playback_settings);
+            let mut playback_interface: PlaybackInterface = PlaybackThread::start(
+                queue,
+                original_queue,
+                playback_settings,
+                cx.global::<Pool>().read.clone(),
+            );
             playback_interface.start_broadcast(cx);
 
-            if !parse_args_and_prepare(cx, &playback_interface)
+            playback_interface.set_output_volumes(storage_data.output_volumes.clone());
+            if let Some(device) = storage_data.output_device.clone() {
+                playback_interface.set_output_device(Some(device));
+            }
+
+            if !parse_args_and_prepare(cx, &playback_interface, &args)
                 && let Some(track) = storage_data.current_track
             {
                 // open current track,
@@ -386,14 +692,53 @@ pub fn run() -> anyhow::Result<()> {
             }
             cx.set_global(playback_interface);
 
+            #[cfg(any(target_os = "windows", target_os = "macos"))]
+            crate::services::other_audio::watch_other_audio(cx);
+            #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+            crate::services::session::watch_session(cx);
+
+            // the playback, analysis and scan threads only read their settings on startup, so
+            // changes are sent to them
+            let settings = cx.global::<SettingsGlobal>().model.clone();
+            let mut playback_settings = settings.read(cx).playback.clone();
+            let mut analysis_settings = settings.read(cx).analysis.clone();
+            let mut scan_settings = cx
+                .global::<ActiveLibrary>()
+                .scan_settings(settings.read(cx));
+            cx.observe(&settings, move |settings, cx| {
+                let new_settings = settings.read(cx).playback.clone();
+                if new_settings != playback_settings {
+                    cx.global::<PlaybackInterface>()
+                        .update_settings(new_settings.clone());
+                    playback_settings = new_settings;
+                }
+
+                let new_settings = settings.read(cx).analysis.clone();
+                if new_settings != analysis_settings {
+                    cx.global::<AnalysisInterface>()
+                        .update_settings(new_settings.clone());
+                    analysis_settings = new_settings;
+                }
+
+                let new_settings = cx
+                    .global::<ActiveLibrary>()
+                    .scan_settings(settings.read(cx));
+                if new_settings != scan_settings {
+                    cx.global::<ScanInterface>()
+                        .update_settings(new_settings.clone());
+                    scan_settings = new_settings;
+                }
+            })
+            .detach();
+
             cx.activate(true);
 
             cx.open_window(
                 WindowOptions {
-                    window_bounds: Some(WindowBounds::Windowed(bounds)),
+                    window_bounds: Some(window_bounds),
                     window_background: WindowBackgroundAppearance::Opaque,
                     window_decorations: Some(WindowDecorations::Client),
-                    window_min_size: Some(size(px(800.0), px(600.0))),
+                    window_min_size: Some(min_size),
                     titlebar: Some(TitlebarOptions {
                         title: Some(SharedString::from("Hummingbird")),
                         appears_transparent: true,
@@ -415,6 +760,7 @@ pub fn run() -> anyhow::Result<()> {
                     let palette = CommandPalette::new(cx, window);
 
                     cx.set_global(CommandPaletteHolder::new(palette.clone()));
+                    register_custom_commands(cx);
 
                     cx.new(|cx| {
                         cx.observe_window_appearance(window, |_, _, cx| {
@@ -425,33 +771,69 @@ pub fn run() -> anyhow::Result<()> {
                         // Update `StorageData` and save it to file system while quitting the app
                         cx.on_app_quit({
                             let current_track = cx.global::<PlaybackInfo>().current_track.clone();
-                            move |_, cx| {
-                                let current_track = current_track.read(cx).clone();
+                            let switcher_model = cx.global::<Models>().switcher_model.clone();
+                            let output_device = cx.global::<PlaybackInfo>().output_device.clone();
+                            let output_volumes = cx.global::<PlaybackInfo>().output_volumes.clone();
+                            move |this: &mut WindowShadow, cx| {
+                                let data = StorageData {
+                                    current_track: current_track.read(cx).clone(),
+                                    window: Some(this.window_state),
+                                    show_queue: *this.show_queue.read(cx),
+                                    show_sidebar: *this.show_sidebar.read(cx),
+                                    view: switcher_model.read(cx).back().copied(),
+                                    output_device: output_device.read(cx).clone(),
+                                    output_volumes: output_volumes.read(cx).clone(),
+                                    library: cx.global::<ActiveLibrary>().name.clone(),
+                                };
                                 let storage = storage.clone();
                                 cx.background_executor().spawn(async move {
-                                    storage.save(&StorageData { current_track });
+                                    storage.save(&data);
                                 })
                             }
                         })
                         .detach();
 
-                        let show_queue = cx.new(|_| true);
+                        cx.observe_window_bounds(window, |this: &mut WindowShadow, window, _| {
+                            this.window_state = window_state(window);
+                        })
+                        .detach();
+
+                        let show_queue = cx.new(|_| storage_data.show_queue);
+                        let show_sidebar = cx.new(|_| storage_data.show_sidebar);
                         let show_about = cx.global::<Models>().show_about.clone();
+                        let show_onboarding = cx.new(|_| first_run);
 
                         cx.observe(&show_about, |_, _, cx| {
                             cx.notify();
                         })
                         .detach();
 
+                        let queue_window = cx.global::<Models>().queue_window.clone();
+
+                        cx.observe(&queue_window, |_, _, cx| {
+                            cx.notify();
+                        })
+                        .detach();
+
+                        let settings = cx.global::<SettingsGlobal>().model.clone();
+
+                        cx.observe(&settings, |_, _, cx| {
+                            cx.notify();
+                        })
+                        .detach();
+
                         WindowShadow {
                             controls: Controls::new(cx, show_queue.clone()),
-                            queue: Queue::new(cx, show_queue.clone()),
-                            library: Library::new(cx),
+                            queue: Queue::new(cx, Some(show_queue.clone())),
+                            library: Library::new(cx, show_sidebar.clone()),
                             header: Header::new(cx),
                             search: SearchView::new(cx),
                             show_queue,
+                            show_sidebar,
                             show_about,
                             palette,
+                            onboarding: Onboarding::new(cx, show_onboarding),
+                            window_state: window_state(window),
                         }
                     })
                 },
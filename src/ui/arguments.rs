@@ -1,23 +1,48 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use gpui::App;
 use tracing::info;
 
-use crate::playback::{interface::PlaybackInterface, queue::QueueItemData};
+use crate::{
+    library::headless::LibraryCommand,
+    playback::{interface::PlaybackInterface, queue::QueueItemData},
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
-struct Args {
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     #[arg()]
-    files: Option<Vec<PathBuf>>,
+    pub files: Option<Vec<PathBuf>>,
+    /// Scan the given directory into a temporary database and report how long it took
+    #[arg(long, value_name = "DIR")]
+    pub bench_scan: Option<PathBuf>,
+    /// Keep the library and everything else Hummingbird stores in this directory
+    #[arg(long, value_name = "DIR", env = "HUMMINGBIRD_DATA_DIR", global = true)]
+    pub data_dir: Option<PathBuf>,
+    /// Read the settings, theme and translations from this directory. Defaults to the data
+    /// directory
+    #[arg(
+        long,
+        value_name = "DIR",
+        env = "HUMMINGBIRD_CONFIG_DIR",
+        global = true
+    )]
+    pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Manage the library without opening a window
+    #[command(subcommand)]
+    Library(LibraryCommand),
 }
 
 /// Parses the arguments provided by the user and handles them. Returns true if files were provided
 /// for playback as command line arguments.
-pub fn parse_args_and_prepare(cx: &mut App, interface: &PlaybackInterface) -> bool {
-    let args = Args::parse();
-
+pub fn parse_args_and_prepare(cx: &mut App, interface: &PlaybackInterface, args: &Args) -> bool {
     if let Some(files) = &args.files {
         info!("Queueing files found in arguments: {:?}", files);
 
@@ -1,7 +1,7 @@
 pub mod bundled;
 pub mod db;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, path::PathBuf};
 
 use gpui::AssetSource;
 use sqlx::SqlitePool;
@@ -11,11 +11,12 @@ use crate::ui::assets::bundled::BundledAssets;
 
 pub struct HummingbirdAssetSource {
     pool: SqlitePool,
+    art_dir: PathBuf,
 }
 
 impl HummingbirdAssetSource {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+    pub fn new(pool: SqlitePool, art_dir: PathBuf) -> Self {
+        Self { pool, art_dir }
     }
 }
 
@@ -24,7 +25,7 @@ impl AssetSource for HummingbirdAssetSource {
         let url = Url::parse(&path[1..])?;
 
         match url.scheme() {
-            "db" => db::load(&self.pool, url),
+            "db" => db::load(&self.pool, &self.art_dir, url),
             "bundled" => BundledAssets::load(url),
             _ => panic!("invalid url scheme for resource"),
         }
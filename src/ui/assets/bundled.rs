@@ -7,6 +7,8 @@ use url::Url;
 #[include = "fonts/*"]
 #[include = "icons/*"]
 #[include = "images/*"]
+#[include = "locales/*"]
+#[include = "themes/*"]
 #[exclude = "*.DS_Store"]
 #[exclude = "icons/LICENSE"]
 pub struct BundledAssets;
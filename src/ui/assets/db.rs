@@ -1,10 +1,80 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
 
 use anyhow::anyhow;
+use gpui::Global;
 use sqlx::SqlitePool;
+use tokio::sync::{mpsc, oneshot};
+use tracing::debug;
 use url::Url;
 
-pub fn load(pool: &SqlitePool, url: Url) -> gpui::Result<Option<Cow<'static, [u8]>>> {
+use crate::library::art::read_art;
+
+type DbAssetRequest = (Url, oneshot::Sender<anyhow::Result<Option<Vec<u8>>>>);
+
+/// The maximum number of asset requests that can be waiting to be processed at once. Requests
+/// made while the queue is full will wait for space to become available.
+const REQUEST_QUEUE_SIZE: usize = 32;
+
+/// Loads database-backed assets on the tokio runtime, so that waiting on the database never
+/// blocks any of GPUI's threads.
+pub struct DbAssetLoader {
+    requests: mpsc::Sender<DbAssetRequest>,
+}
+
+impl Global for DbAssetLoader {}
+
+impl DbAssetLoader {
+    pub fn start(pool: SqlitePool, art_dir: PathBuf) -> Self {
+        let (requests, mut requests_rx) = mpsc::channel::<DbAssetRequest>(REQUEST_QUEUE_SIZE);
+
+        crate::RUNTIME.spawn(async move {
+            while let Some((url, reply)) = requests_rx.recv().await {
+                let result = fetch(&pool, &art_dir, &url).await;
+
+                if reply.send(result).is_err() {
+                    debug!("asset request for {url} was dropped before it completed");
+                }
+            }
+        });
+
+        Self { requests }
+    }
+
+    /// Requests the asset at the given url. The returned future can be awaited from any executor.
+    pub fn request(
+        &self,
+        url: Url,
+    ) -> impl Future<Output = anyhow::Result<Option<Vec<u8>>>> + Send + 'static {
+        let requests = self.requests.clone();
+
+        async move {
+            let (reply, reply_rx) = oneshot::channel();
+
+            requests
+                .send((url, reply))
+                .await
+                .map_err(|_| anyhow!("asset loader has stopped"))?;
+
+            reply_rx.await?
+        }
+    }
+}
+
+/// Synchronously loads the asset at the given url. Prefer [DbAssetLoader] where possible.
+pub fn load(
+    pool: &SqlitePool,
+    art_dir: &Path,
+    url: Url,
+) -> gpui::Result<Option<Cow<'static, [u8]>>> {
+    Ok(crate::RUNTIME
+        .block_on(fetch(pool, art_dir, &url))?
+        .map(Cow::Owned))
+}
+
+async fn fetch(pool: &SqlitePool, art_dir: &Path, url: &Url) -> anyhow::Result<Option<Vec<u8>>> {
     match url
         .host_str()
         .ok_or_else(|| anyhow!("missing table name"))?
@@ -19,15 +89,58 @@ pub fn load(pool: &SqlitePool, url: Url) -> gpui::Result<Option<Cow<'static, [u8
                 .next()
                 .ok_or_else(|| anyhow!("missing image type"))?;
 
-            let query = match image_type {
-                "thumb" => include_str!("../../../queries/assets/find_album_thumb.sql"),
-                "full" => include_str!("../../../queries/assets/find_album_art.sql"),
-                _ => unimplemented!("invalid image type '{image_type}'"),
-            };
+            match image_type {
+                "thumb" => {
+                    let query = include_str!("../../../queries/assets/find_album_thumb.sql");
+                    let (image,) = sqlx::query_as(query).bind(id).fetch_one(pool).await?;
+                    Ok(Some(image))
+                }
+                "full" => {
+                    let query = include_str!("../../../queries/assets/find_album_art.sql");
+                    let (hash, image): (Option<String>, Option<Vec<u8>>) =
+                        sqlx::query_as(query).bind(id).fetch_one(pool).await?;
+
+                    // albums that haven't been migrated yet still have their art in the database
+                    match (hash, image) {
+                        (Some(hash), _) => Ok(Some(read_art(art_dir, &hash)?)),
+                        (None, Some(image)) => Ok(Some(image)),
+                        (None, None) => Err(anyhow!("album {id} has no art")),
+                    }
+                }
+                _ => Err(anyhow!("invalid image type '{image_type}'")),
+            }
+        }
+        "artist" => {
+            let mut segments = url.path_segments().ok_or_else(|| anyhow!("missing path"))?;
+            let id: i64 = segments
+                .next()
+                .ok_or_else(|| anyhow!("missing id"))?
+                .parse()?;
+
+            let query = include_str!("../../../queries/assets/find_artist_image.sql");
+            let hash: Option<String> = sqlx::query_scalar(query).bind(id).fetch_one(pool).await?;
+
+            match hash {
+                Some(hash) => Ok(Some(read_art(art_dir, &hash)?)),
+                None => Err(anyhow!("artist {id} has no image")),
+            }
+        }
+        "playlist" => {
+            // the second segment is the image's hash, which is only there so that changing the
+            // cover also changes the url, and the old cover isn't shown from the cache
+            let mut segments = url.path_segments().ok_or_else(|| anyhow!("missing path"))?;
+            let id: i64 = segments
+                .next()
+                .ok_or_else(|| anyhow!("missing id"))?
+                .parse()?;
+
+            let query = include_str!("../../../queries/assets/find_playlist_image.sql");
+            let hash: Option<String> = sqlx::query_scalar(query).bind(id).fetch_one(pool).await?;
 
-            let (image,) =
-                crate::RUNTIME.block_on(sqlx::query_as(query).bind(id).fetch_one(pool))?;
-            Ok(Some(Cow::Owned(image)))
+            match hash {
+                Some(hash) => Ok(Some(read_art(art_dir, &hash)?)),
+                None => Err(anyhow!("playlist {id} has no cover")),
+            }
         }
         _ => Ok(None),
     }
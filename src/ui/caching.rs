@@ -1,12 +1,18 @@
-use std::{collections::VecDeque, mem::take};
+use std::{collections::VecDeque, mem::take, sync::Arc};
 
+use anyhow::anyhow;
 use futures::FutureExt;
 use gpui::{
     App, AppContext, Asset, AssetLogger, ElementId, Entity, ImageAssetLoader, ImageCache,
-    ImageCacheItem, ImageCacheProvider, ImageSource, Resource, hash,
+    ImageCacheError, ImageCacheItem, ImageCacheProvider, ImageSource, RenderImage, Resource, hash,
 };
+use image::Frame;
 use rustc_hash::{FxBuildHasher, FxHashMap};
+use smallvec::SmallVec;
 use tracing::{debug, error};
+use url::Url;
+
+use crate::{ui::assets::db::DbAssetLoader, util::rgb_to_bgr};
 
 pub fn hummingbird_cache(
     id: impl Into<ElementId>,
@@ -89,8 +95,16 @@ impl ImageCache for HummingbirdImageCache {
             return item.0.get();
         }
 
-        let load_future = AssetLogger::<ImageAssetLoader>::load(resource.clone(), cx);
-        let task = cx.background_executor().spawn(load_future).shared();
+        let task = match resource {
+            Resource::Embedded(path) if path.starts_with("!db:") => {
+                let load_future = DbImageLoader::load(path.clone(), cx);
+                cx.background_executor().spawn(load_future.boxed()).shared()
+            }
+            _ => {
+                let load_future = AssetLogger::<ImageAssetLoader>::load(resource.clone(), cx);
+                cx.background_executor().spawn(load_future.boxed()).shared()
+            }
+        };
 
         if self.usage_list.len() >= self.max_items {
             debug!("Image cache is full, evicting oldest item");
@@ -137,3 +151,38 @@ impl ImageCache for HummingbirdImageCache {
         None
     }
 }
+
+/// Loads images stored in the library database through the [DbAssetLoader], instead of the
+/// (blocking) asset source.
+pub enum DbImageLoader {}
+
+impl Asset for DbImageLoader {
+    type Source = gpui::SharedString;
+    type Output = Result<Arc<RenderImage>, ImageCacheError>;
+
+    fn load(
+        source: Self::Source,
+        cx: &mut App,
+    ) -> impl Future<Output = Self::Output> + Send + 'static {
+        let url = Url::parse(&source[1..]);
+        let request = url.map(|url| cx.global::<DbAssetLoader>().request(url));
+
+        async move {
+            let bytes = request
+                .map_err(|e| anyhow!(e))?
+                .await?
+                .ok_or_else(|| anyhow!("asset not found: {source}"))?;
+
+            let mut image = image::load_from_memory(&bytes)
+                .map_err(|e| ImageCacheError::Image(Arc::new(e)))?
+                .into_rgba8();
+
+            rgb_to_bgr(&mut image);
+
+            Ok(Arc::new(RenderImage::new(SmallVec::from_elem(
+                Frame::new(image),
+                1,
+            ))))
+        }
+    }
+}
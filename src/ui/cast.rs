@@ -0,0 +1,359 @@
+#[cfg(target_os = "macos")]
+mod airplay;
+
+use std::time::Duration;
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    Styled, Window, div, prelude::FluentBuilder,
+};
+use tracing::{error, info};
+
+use crate::{
+    playback::{interface::PlaybackInterface, renderer::RendererHandle},
+    services::{
+        cast::{self, CastDevice, CastRenderer},
+        dlna::{self, DlnaDevice, DlnaRenderer},
+    },
+    ui::{
+        components::{
+            button::{ButtonIntent, button},
+            modal::modal,
+        },
+        locale::t,
+        models::PlaybackInfo,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// How long to look for devices each time the dialog is opened.
+const DISCOVERY_TIME: Duration = Duration::from_secs(3);
+
+/// A device on the network that playback can be sent to.
+#[derive(Debug, Clone, PartialEq)]
+enum Device {
+    Cast(CastDevice),
+    Dlna(DlnaDevice),
+}
+
+impl Device {
+    fn name(&self) -> &str {
+        match self {
+            Device::Cast(device) => &device.name,
+            Device::Dlna(device) => &device.name,
+        }
+    }
+
+    fn connect(&self) -> anyhow::Result<RendererHandle> {
+        match self {
+            Device::Cast(device) => Ok(RendererHandle::new(CastRenderer::connect(device)?)),
+            Device::Dlna(device) => Ok(RendererHandle::new(DlnaRenderer::connect(device)?)),
+        }
+    }
+}
+
+/// A dialog for picking where audio is played: one of this computer's output devices, an AirPlay
+/// speaker on macOS, or a Cast or DLNA device found on the network.
+pub struct CastPicker {
+    show: Entity<bool>,
+    devices: Vec<Device>,
+    searching: bool,
+    /// The device that's being connected to.
+    connecting: Option<SharedString>,
+    error: Option<SharedString>,
+    /// The AirPlay menu, which is kept until the dialog is closed.
+    #[cfg(target_os = "macos")]
+    route_picker: Option<airplay::RoutePicker>,
+}
+
+impl CastPicker {
+    pub fn new(cx: &mut App, show: Entity<bool>) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                #[cfg(target_os = "macos")]
+                {
+                    this.route_picker = None;
+                }
+
+                if *show.read(cx) && !this.searching {
+                    cx.global::<PlaybackInterface>().list_output_devices();
+                    this.discover(cx);
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let info = cx.global::<PlaybackInfo>();
+            let renderer = info.renderer.clone();
+            let output_devices = info.output_devices.clone();
+            let output_device = info.output_device.clone();
+            cx.observe(&renderer, |_, _, cx| cx.notify()).detach();
+            cx.observe(&output_devices, |_, _, cx| cx.notify()).detach();
+            cx.observe(&output_device, |_, _, cx| cx.notify()).detach();
+
+            Self {
+                show,
+                devices: Vec::new(),
+                searching: false,
+                connecting: None,
+                error: None,
+                #[cfg(target_os = "macos")]
+                route_picker: None,
+            }
+        })
+    }
+
+    fn discover(&mut self, cx: &mut Context<Self>) {
+        self.searching = true;
+        self.error = None;
+
+        cx.spawn(async move |this, cx| {
+            let (cast, dlna) = futures::future::join(
+                crate::RUNTIME.spawn_blocking(|| cast::discover(DISCOVERY_TIME)),
+                crate::RUNTIME.spawn_blocking(|| dlna::discover(DISCOVERY_TIME)),
+            )
+            .await;
+
+            let mut devices = Vec::new();
+            let mut errors = Vec::new();
+
+            match cast {
+                Ok(Ok(found)) => devices.extend(found.into_iter().map(Device::Cast)),
+                Ok(Err(err)) => errors.push(err),
+                Err(err) => error!("Failed to look for Cast devices: {err}"),
+            }
+            match dlna {
+                Ok(Ok(found)) => devices.extend(found.into_iter().map(Device::Dlna)),
+                Ok(Err(err)) => errors.push(err),
+                Err(err) => error!("Failed to look for DLNA devices: {err}"),
+            }
+
+            for err in &errors {
+                error!("Failed to look for devices: {err}");
+            }
+
+            this.update(cx, |this, cx| {
+                this.searching = false;
+                this.devices = devices;
+
+                // one kind of device failing to be found isn't worth mentioning if the other was
+                if this.devices.is_empty()
+                    && let Some(err) = errors.first()
+                {
+                    this.error = Some(err.to_string().into());
+                }
+
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn connect(&mut self, device: Device, cx: &mut Context<Self>) {
+        self.connecting = Some(device.name().to_string().into());
+        self.error = None;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn_blocking(move || device.connect())
+                .await;
+
+            this.update(cx, |this, cx| {
+                this.connecting = None;
+
+                match result {
+                    Ok(Ok(renderer)) => {
+                        info!("Connected to {}", renderer.lock().name());
+                        cx.global::<PlaybackInterface>()
+                            .set_renderer(Some(renderer));
+                        this.show.write(cx, false);
+                    }
+                    Ok(Err(err)) => {
+                        error!("Failed to connect to device: {err}");
+                        this.error = Some(err.to_string().into());
+                    }
+                    Err(err) => error!("Failed to connect to device: {err}"),
+                }
+
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Opens the system's AirPlay menu. Picking a speaker there changes the system's output
+    /// device, so playback is moved to the default output to follow it.
+    #[cfg(target_os = "macos")]
+    fn show_airplay(
+        &mut self,
+        position: gpui::Point<gpui::Pixels>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.error = None;
+        self.route_picker = None;
+
+        match airplay::RoutePicker::show(window, position) {
+            Ok(picker) => {
+                let playback = cx.global::<PlaybackInterface>();
+                playback.set_renderer(None);
+                playback.set_output_device(None);
+                self.route_picker = Some(picker);
+            }
+            Err(err) => {
+                error!("Failed to open the AirPlay menu: {err}");
+                self.error = Some(err.to_string().into());
+            }
+        }
+
+        cx.notify();
+    }
+}
+
+impl Render for CastPicker {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let theme = cx.global::<Theme>();
+        let show = self.show.clone();
+        let show_close = self.show.clone();
+        let info = cx.global::<PlaybackInfo>();
+        let current = info.renderer.read(cx).clone();
+        let output_device = info.output_device.read(cx).clone();
+        let output_devices = info.output_devices.read(cx).clone();
+
+        let status = if let Some(err) = &self.error {
+            Some(err.clone())
+        } else if let Some(name) = &self.connecting {
+            Some(t!(cx, "cast.connecting", name = name))
+        } else if self.searching {
+            Some(t!(cx, "cast.searching"))
+        } else if self.devices.is_empty() {
+            Some(t!(cx, "cast.none_found"))
+        } else {
+            None
+        };
+
+        let devices = self.devices.iter().enumerate().map(|(index, device)| {
+            let active = current
+                .as_ref()
+                .is_some_and(|current| current == device.name());
+            let name = SharedString::from(device.name().to_string());
+            let kind = match device {
+                Device::Cast(_) => t!(cx, "cast.kind_cast"),
+                Device::Dlna(_) => t!(cx, "cast.kind_dlna"),
+            };
+            let device = device.clone();
+
+            button()
+                .id(("cast-device", index))
+                .justify_between()
+                .when(active, |this| this.intent(ButtonIntent::Primary))
+                .on_click(cx.listener(move |this, _, _, cx| this.connect(device.clone(), cx)))
+                .child(div().child(name))
+                .child(div().text_color(theme.text_secondary).child(kind))
+        });
+
+        let outputs = std::iter::once((None, t!(cx, "cast.default_output")))
+            .chain(
+                output_devices
+                    .into_iter()
+                    .map(|device| (Some(device.uid), SharedString::from(device.name))),
+            )
+            .enumerate()
+            .map(|(index, (uid, name))| {
+                let active = current.is_none() && output_device == uid;
+                let show = self.show.clone();
+
+                button()
+                    .id(("output-device", index))
+                    .when(active, |this| this.intent(ButtonIntent::Primary))
+                    .on_click(move |_, _, cx| {
+                        let playback = cx.global::<PlaybackInterface>();
+                        playback.set_renderer(None);
+                        playback.set_output_device(uid.clone());
+                        show.write(cx, false);
+                    })
+                    .child(name)
+            });
+
+        let outputs = div().flex().flex_col().gap(spx(6.0)).children(outputs);
+
+        // AirPlay speakers are picked from the system's menu, and then become the default output
+        #[cfg(target_os = "macos")]
+        let outputs = outputs.child(
+            button()
+                .id("output-airplay")
+                .on_click(cx.listener(|this, event: &gpui::ClickEvent, window, cx| {
+                    this.show_airplay(event.position(), window, cx)
+                }))
+                .child(t!(cx, "cast.airplay")),
+        );
+
+        let heading = |label: SharedString| {
+            div()
+                .font_weight(FontWeight::SEMIBOLD)
+                .pt(spx(6.0))
+                .child(label)
+        };
+
+        modal()
+            .child(
+                div()
+                    .w(spx(400.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .pb(spx(6.0))
+                            .child(t!(cx, "cast.title")),
+                    )
+                    .child(heading(t!(cx, "cast.this_computer")))
+                    .child(outputs)
+                    .child(heading(t!(cx, "cast.network")))
+                    .when_some(status, |this, status| {
+                        this.child(div().text_color(theme.text_secondary).child(status))
+                    })
+                    .child(div().flex().flex_col().gap(spx(6.0)).children(devices))
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap(spx(8.0))
+                            .pt(spx(10.0))
+                            .when(current.is_some(), |this| {
+                                this.child(
+                                    button()
+                                        .id("cast-stop")
+                                        .on_click(|_, _, cx| {
+                                            cx.global::<PlaybackInterface>().set_renderer(None);
+                                        })
+                                        .child(t!(cx, "cast.stop")),
+                                )
+                            })
+                            .child(
+                                button()
+                                    .id("cast-close")
+                                    .on_click(move |_, _, cx| show_close.write(cx, false))
+                                    .child(t!(cx, "cast.close")),
+                            ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::{Context, bail};
+use gpui::{Pixels, Point, Window};
+use objc2::{
+    msg_send,
+    rc::{Allocated, Retained},
+    runtime::{AnyClass, AnyObject},
+};
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+// AVRoutePickerView is looked up by name, so nothing else makes sure AVKit is loaded
+#[link(name = "AVKit", kind = "framework")]
+unsafe extern "C" {}
+
+/// The size of the invisible view that the AirPlay menu is anchored to.
+const ANCHOR_SIZE: f64 = 24.0;
+
+/// The system's AirPlay menu, opened from an invisible AVRoutePickerView placed in the window.
+///
+/// Without a player attached, the route picker changes the system's output device. Playback on
+/// the default output follows that change, and the receiver shows what's playing through the
+/// Now Playing info the media controller already publishes.
+pub struct RoutePicker {
+    view: Retained<AnyObject>,
+}
+
+impl RoutePicker {
+    /// Opens the AirPlay menu at the given position in the window. The menu stays open as long as
+    /// the returned picker is kept.
+    pub fn show(window: &Window, position: Point<Pixels>) -> anyhow::Result<Self> {
+        let RawWindowHandle::AppKit(handle) = HasWindowHandle::window_handle(window)?.as_raw()
+        else {
+            bail!("The window has no AppKit view");
+        };
+        let picker_class = AnyClass::get(c"AVRoutePickerView").context("AVKit is unavailable")?;
+        let button_class = AnyClass::get(c"NSButton").context("AppKit is unavailable")?;
+
+        unsafe {
+            let parent: &AnyObject = handle.ns_view.cast().as_ref();
+            let bounds: CGRect = msg_send![parent, bounds];
+
+            // AppKit measures from the bottom left of the view, gpui from the top left
+            let origin = CGPoint::new(
+                f64::from(position.x) - ANCHOR_SIZE / 2.0,
+                bounds.size.height - f64::from(position.y) - ANCHOR_SIZE / 2.0,
+            );
+            let frame = CGRect::new(origin, CGSize::new(ANCHOR_SIZE, ANCHOR_SIZE));
+
+            let view: Allocated<AnyObject> = msg_send![picker_class, alloc];
+            let view: Retained<AnyObject> = msg_send![view, initWithFrame: frame];
+            let _: () = msg_send![&*view, setAlphaValue: 0.0f64];
+            let _: () = msg_send![parent, addSubview: &*view];
+            let _: () = msg_send![&*view, layoutSubtreeIfNeeded];
+
+            // removes the view again if the menu can't be opened
+            let picker = Self { view };
+
+            let button = find_subview(&picker.view, button_class)
+                .context("The route picker has no button")?;
+            let _: () = msg_send![&*button, performClick: None::<&AnyObject>];
+
+            Ok(picker)
+        }
+    }
+}
+
+impl Drop for RoutePicker {
+    fn drop(&mut self) {
+        unsafe {
+            let _: () = msg_send![&*self.view, removeFromSuperview];
+        }
+    }
+}
+
+/// Looks through the subviews of a view, depth first, for one of the given class.
+unsafe fn find_subview(view: &AnyObject, class: &AnyClass) -> Option<Retained<AnyObject>> {
+    unsafe {
+        let subviews: Retained<AnyObject> = msg_send![view, subviews];
+        let count: usize = msg_send![&*subviews, count];
+
+        for index in 0..count {
+            let subview: Retained<AnyObject> = msg_send![&*subviews, objectAtIndex: index];
+            let matches: bool = msg_send![&*subview, isKindOfClass: class];
+
+            if matches {
+                return Some(subview);
+            }
+
+            if let Some(found) = find_subview(&subview, class) {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+}
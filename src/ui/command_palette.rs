@@ -1,28 +1,54 @@
-use std::sync::Arc;
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
 
 use gpui::{
     Action, App, AppContext, Context, Entity, EventEmitter, FocusHandle, Global, IntoElement,
-    ParentElement, Render, SharedString, Styled, Window, actions, div, px,
+    ParentElement, Render, SharedString, Styled, WeakEntity, Window, actions, div,
 };
 use nucleo::Utf32String;
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use std::hash::Hash;
-use tracing::error;
-
-use crate::ui::{
-    components::{
-        modal::modal,
-        palette::{FinderItemLeft, Palette, PaletteItem},
+use tracing::{error, warn};
+
+use crate::{
+    library::db::LibraryAccess,
+    ui::{
+        app::get_dirs,
+        components::{
+            modal::modal,
+            palette::{ExtraItem, FinderItemLeft, Palette, PaletteItem},
+        },
+        global_actions::{
+            About, AddPlayingToPlaylist, ExportLibraryData, ForceScan, GoToAlbum, GoToView,
+            ImportLibraryData, Next, PlayPause, Previous, Quit, ResetZoom, ResumeFromOtherDevice,
+            Search, SeekBackward, SeekBackwardLong, SeekForward, SeekForwardLong, SettingToggle,
+            TogglePrivateListening, ToggleSetting, VolumeDown, VolumeUp, ZoomIn, ZoomOut,
+        },
+        library::{ViewSwitchMessage, decades},
+        locale::t,
+        util::spx,
     },
-    global_actions::{About, ForceScan, Next, PlayPause, Previous, Quit, Search},
 };
 
+/// The number of commands shown in the recently used section of the palette.
+const RECENT_COMMANDS: usize = 3;
+
 actions!(hummingbird, [OpenPalette]);
 
+/// Creates the commands for the next stage of a multi-step command, such as the list of
+/// playlists for "Add to Playlist…".
+pub type StageProvider = Arc<dyn Fn(&mut App) -> Vec<Arc<Command>> + Send + Sync>;
+
 pub struct Command {
     category: Option<SharedString>,
     name: SharedString,
-    action: Box<dyn Action + Sync>,
+    /// The action to run, or the next stage of the command if there is no action.
+    action: Option<Box<dyn Action + Sync>>,
+    stage: Option<StageProvider>,
     focus_handle: Option<FocusHandle>,
 }
 
@@ -36,22 +62,44 @@ impl Command {
         Arc::new(Command {
             category: category.map(Into::into),
             name: name.into(),
-            action: Box::new(action),
+            action: Some(Box::new(action)),
+            stage: None,
             focus_handle,
         })
     }
+
+    /// Creates a command that shows another list of commands in the palette when it is chosen,
+    /// instead of running an action.
+    pub fn new_staged(
+        category: Option<impl Into<SharedString>>,
+        name: impl Into<SharedString>,
+        stage: StageProvider,
+    ) -> Arc<Self> {
+        Arc::new(Command {
+            category: category.map(Into::into),
+            name: name.into(),
+            action: None,
+            stage: Some(stage),
+            focus_handle: None,
+        })
+    }
 }
 
 impl PartialEq for Command {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.action.partial_eq(&(*other.action))
+        self.name == other.name
+            && match (&self.action, &other.action) {
+                (Some(a), Some(b)) => a.partial_eq(&(**b)),
+                (None, None) => true,
+                _ => false,
+            }
     }
 }
 
 impl Hash for Command {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.name.hash(state);
-        self.action.name().hash(state);
+        self.action.as_ref().map(|action| action.name()).hash(state);
     }
 }
 
@@ -68,9 +116,11 @@ impl PaletteItem for Command {
     }
 
     fn right_content(&self, cx: &mut gpui::App) -> Option<SharedString> {
+        let action = self.action.as_ref()?;
+
         cx.key_bindings()
             .borrow()
-            .bindings_for_action(&(*self.action))
+            .bindings_for_action(&(**action))
             .last()
             .map(|binding| {
                 binding
@@ -87,10 +137,74 @@ impl PaletteItem for Command {
 type MatcherFunc = Box<dyn Fn(&Arc<Command>, &mut App) -> Utf32String + 'static>;
 type OnAccept = Box<dyn Fn(&Arc<Command>, &mut App) + 'static>;
 
+/// How often and how recently a command has been run from the palette.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct CommandUsage {
+    count: u32,
+    /// When the command was last run, as a Unix timestamp.
+    last_used: i64,
+}
+
+fn usage_path() -> PathBuf {
+    get_dirs().data_dir().join("command_usage.json")
+}
+
+fn load_usage() -> FxHashMap<String, CommandUsage> {
+    File::open(usage_path())
+        .ok()
+        .and_then(|file| serde_json::from_reader(std::io::BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+fn usage_key(id: &(&'static str, i64)) -> String {
+    format!("{}:{}", id.0, id.1)
+}
+
 pub struct CommandPalette {
     show: bool,
     palette: Entity<Palette<Command, MatcherFunc, OnAccept>>,
     items: FxHashMap<(&'static str, i64), Arc<Command>>,
+    usage: FxHashMap<String, CommandUsage>,
+    /// The items shown in the recently used section, read by the palette's extra item provider.
+    recent: Arc<RwLock<Vec<ExtraItem>>>,
+}
+
+/// Runs the command and closes the palette, or moves on to the command's next stage.
+fn accept_command(weak_self: &WeakEntity<CommandPalette>, item: &Arc<Command>, cx: &mut App) {
+    if let Some(stage) = &item.stage {
+        let items = stage(cx);
+
+        weak_self
+            .update(cx, |this: &mut CommandPalette, cx| {
+                this.record_use(item);
+                this.enter_stage(items, cx);
+            })
+            .ok();
+
+        return;
+    }
+
+    let Some(action) = &item.action else {
+        return;
+    };
+
+    if let Some(focus_handle) = &item.focus_handle
+        && let Err(err) = cx.update_window(cx.active_window().unwrap(), |_, window, _| {
+            focus_handle.focus(window);
+        })
+    {
+        error!("Failed to focus window, action may not trigger: {}", err);
+    }
+
+    cx.dispatch_action(&(**action));
+
+    weak_self
+        .update(cx, |this: &mut CommandPalette, cx| {
+            this.show = false;
+            this.record_use(item);
+            cx.notify();
+        })
+        .ok();
 }
 
 impl CommandPalette {
@@ -100,23 +214,7 @@ impl CommandPalette {
 
             let weak_self = cx.weak_entity();
             let on_accept: OnAccept = Box::new(move |item, cx| {
-                if let Some(focus_handle) = &item.focus_handle
-                    && let Err(err) =
-                        cx.update_window(cx.active_window().unwrap(), |_, window, _| {
-                            focus_handle.focus(window);
-                        })
-                {
-                    error!("Failed to focus window, action may not trigger: {}", err);
-                }
-
-                cx.dispatch_action(&(*item.action));
-
-                weak_self
-                    .update(cx, |this: &mut Self, cx| {
-                        this.show = false;
-                        cx.notify();
-                    })
-                    .ok();
+                accept_command(&weak_self, item, cx);
             });
 
             let mut items = FxHashMap::default();
@@ -129,11 +227,7 @@ impl CommandPalette {
                     CommandEvent::RemoveCommand(id) => this.items.remove(id),
                 };
 
-                let vec: Vec<_> = this.items.values().cloned().collect();
-
-                this.palette.update(cx, |_, cx| {
-                    cx.emit(vec);
-                });
+                this.update_items(cx);
 
                 cx.notify();
             })
@@ -142,47 +236,454 @@ impl CommandPalette {
             // add basic items
             items.insert(
                 ("hummingbird::quit", 0),
-                Command::new(Some("Hummingbird"), "Quit", Quit, None),
+                Command::new(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.quit"),
+                    Quit,
+                    None,
+                ),
             );
             items.insert(
                 ("hummingbird::about", 0),
-                Command::new(Some("Hummingbird"), "About", About, None),
+                Command::new(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.about"),
+                    About,
+                    None,
+                ),
             );
             items.insert(
                 ("hummingbird::search", 0),
-                Command::new(Some("Hummingbird"), "Search", Search, None),
+                Command::new(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.search"),
+                    Search,
+                    None,
+                ),
+            );
+
+            items.insert(
+                ("hummingbird::browseyears", 0),
+                Command::new_staged(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.browse_years"),
+                    Arc::new(|cx| {
+                        let category = t!(cx, "sidebar.years");
+                        let all_years = Command::new(
+                            Some(category.clone()),
+                            t!(cx, "years.all"),
+                            GoToView {
+                                view: ViewSwitchMessage::Years,
+                            },
+                            None,
+                        );
+
+                        let mut commands = vec![all_years];
+                        for decade in decades(cx) {
+                            let decade_name = t!(cx, "years.decade", decade = decade.start);
+
+                            commands.push(Command::new(
+                                Some(category.clone()),
+                                decade_name.clone(),
+                                GoToView {
+                                    view: ViewSwitchMessage::Decade(decade.start),
+                                },
+                                None,
+                            ));
+                            commands.extend(decade.years.into_iter().map(|(year, _)| {
+                                Command::new(
+                                    Some(decade_name.clone()),
+                                    year.to_string(),
+                                    GoToView {
+                                        view: ViewSwitchMessage::Year(year),
+                                    },
+                                    None,
+                                )
+                            }));
+                        }
+
+                        commands
+                    }),
+                ),
+            );
+
+            items.insert(
+                ("hummingbird::browselabels", 0),
+                Command::new_staged(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.browse_labels"),
+                    Arc::new(|cx| {
+                        let category = t!(cx, "sidebar.labels");
+
+                        cx.list_labels()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(name, album_id, _)| {
+                                Command::new(
+                                    Some(category.clone()),
+                                    name,
+                                    GoToView {
+                                        view: ViewSwitchMessage::Label(album_id),
+                                    },
+                                    None,
+                                )
+                            })
+                            .collect()
+                    }),
+                ),
+            );
+
+            items.insert(
+                ("hummingbird::gotoalbum", 0),
+                Command::new_staged(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.go_to_album"),
+                    Arc::new(|cx| {
+                        cx.list_albums_search()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(id, title, artist)| {
+                                Command::new(
+                                    Some(artist),
+                                    title,
+                                    GoToAlbum {
+                                        album_id: id as i64,
+                                    },
+                                    None,
+                                )
+                            })
+                            .collect()
+                    }),
+                ),
+            );
+
+            items.insert(
+                ("library::exportdata", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.export_library_data"),
+                    ExportLibraryData,
+                    None,
+                ),
+            );
+            items.insert(
+                ("library::importdata", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.hummingbird")),
+                    t!(cx, "command.import_library_data"),
+                    ImportLibraryData,
+                    None,
+                ),
             );
 
             items.insert(
                 ("player::playpause", 0),
                 Command::new(
-                    Some("Playback"),
-                    "Pause/Resume Current Track",
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.play_pause"),
                     PlayPause,
                     None,
                 ),
             );
             items.insert(
                 ("player::next", 0),
-                Command::new(Some("Playback"), "Next Track", Next, None),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.next"),
+                    Next,
+                    None,
+                ),
             );
             items.insert(
                 ("player::previous", 0),
-                Command::new(Some("Playback"), "Previous Track", Previous, None),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.previous"),
+                    Previous,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::seekforward", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.seek_forward"),
+                    SeekForward,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::seekbackward", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.seek_backward"),
+                    SeekBackward,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::seekforwardlong", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.seek_forward_long"),
+                    SeekForwardLong,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::seekbackwardlong", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.seek_backward_long"),
+                    SeekBackwardLong,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::volumeup", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.volume_up"),
+                    VolumeUp,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::volumedown", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.volume_down"),
+                    VolumeDown,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::toggleprivatelistening", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.toggle_private_listening"),
+                    TogglePrivateListening,
+                    None,
+                ),
+            );
+            items.insert(
+                ("player::resumefromotherdevice", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.playback")),
+                    t!(cx, "command.resume_from_other_device"),
+                    ResumeFromOtherDevice,
+                    None,
+                ),
+            );
+
+            items.insert(
+                ("player::addtoplaylist", 0),
+                Command::new_staged(
+                    Some(t!(cx, "command.category.playlist")),
+                    t!(cx, "command.add_to_playlist"),
+                    Arc::new(|cx| {
+                        let category = t!(cx, "command.category.playlist");
+
+                        cx.get_all_playlists()
+                            .map(|playlists| {
+                                playlists
+                                    .iter()
+                                    .map(|playlist| {
+                                        Command::new(
+                                            Some(category.clone()),
+                                            playlist.name.to_string(),
+                                            AddPlayingToPlaylist {
+                                                playlist_id: playlist.id,
+                                            },
+                                            None,
+                                        )
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_default()
+                    }),
+                ),
             );
 
             items.insert(
                 ("scan::forcescan", 0),
-                Command::new(Some("Scan"), "Rescan Entire Library", ForceScan, None),
+                Command::new(
+                    Some(t!(cx, "command.category.scan")),
+                    t!(cx, "command.force_scan"),
+                    ForceScan,
+                    None,
+                ),
             );
 
-            let palette = Palette::new(cx, items.values().cloned().collect(), matcher, on_accept);
+            items.insert(
+                ("interface::zoomin", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.interface")),
+                    t!(cx, "command.zoom_in"),
+                    ZoomIn,
+                    None,
+                ),
+            );
+            items.insert(
+                ("interface::zoomout", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.interface")),
+                    t!(cx, "command.zoom_out"),
+                    ZoomOut,
+                    None,
+                ),
+            );
+            items.insert(
+                ("interface::resetzoom", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.interface")),
+                    t!(cx, "command.reset_zoom"),
+                    ResetZoom,
+                    None,
+                ),
+            );
+
+            items.insert(
+                ("settings::alwaysrepeat", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.always_repeat"),
+                    ToggleSetting {
+                        setting: SettingToggle::AlwaysRepeat,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::prevtrackjumpfirst", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.prev_track_jump_first"),
+                    ToggleSetting {
+                        setting: SettingToggle::PrevTrackJumpFirst,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::showplaycount", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.show_play_count"),
+                    ToggleSetting {
+                        setting: SettingToggle::ShowPlayCount,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::showlastplayed", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.show_last_played"),
+                    ToggleSetting {
+                        setting: SettingToggle::ShowLastPlayed,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::skipsilence", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.skip_silence"),
+                    ToggleSetting {
+                        setting: SettingToggle::SkipSilence,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::skipbywork", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.skip_by_work"),
+                    ToggleSetting {
+                        setting: SettingToggle::SkipByWork,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::showbpm", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.show_bpm"),
+                    ToggleSetting {
+                        setting: SettingToggle::ShowBpm,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::showkey", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.show_key"),
+                    ToggleSetting {
+                        setting: SettingToggle::ShowKey,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::showbadges", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.show_badges"),
+                    ToggleSetting {
+                        setting: SettingToggle::ShowBadges,
+                    },
+                    None,
+                ),
+            );
+            items.insert(
+                ("settings::analyzetracks", 0),
+                Command::new(
+                    Some(t!(cx, "command.category.settings")),
+                    t!(cx, "command.toggle.analyze_tracks"),
+                    ToggleSetting {
+                        setting: SettingToggle::AnalyzeTracks,
+                    },
+                    None,
+                ),
+            );
+
+            let usage = load_usage();
+            let palette = Palette::new(cx, sorted_by_usage(&items, &usage), matcher, on_accept);
+
+            // recently used commands are only shown before anything has been typed, everything
+            // else is found by searching
+            let recent: Arc<RwLock<Vec<ExtraItem>>> = Arc::new(RwLock::new(Vec::new()));
+            let recent_provider = recent.clone();
+
+            palette.update(cx, |palette, cx| {
+                palette.register_extra_provider(
+                    Arc::new(move |query: &str| {
+                        if query.is_empty() {
+                            recent_provider.read().unwrap().clone()
+                        } else {
+                            Vec::new()
+                        }
+                    }),
+                    cx,
+                );
+            });
 
             let weak_self = cx.weak_entity();
             App::on_action(cx, move |_: &OpenPalette, cx: &mut App| {
                 weak_self
                     .update(cx, |this: &mut Self, cx| {
                         this.show = true;
+                        // the palette could have been closed partway through a multi-step
+                        // command, so it always starts again from the first stage
+                        this.update_items(cx);
+                        this.update_recent(cx);
                         this.palette.update(cx, |palette, cx| {
                             palette.reset(cx);
                         });
@@ -196,9 +697,111 @@ impl CommandPalette {
                 show: false,
                 items,
                 palette,
+                usage,
+                recent,
             }
         })
     }
+
+    fn record_use(&mut self, item: &Arc<Command>) {
+        let Some(id) = self
+            .items
+            .iter()
+            .find(|(_, command)| Arc::ptr_eq(command, item))
+            .map(|(id, _)| *id)
+        else {
+            return;
+        };
+
+        let usage = self.usage.entry(usage_key(&id)).or_default();
+        usage.count += 1;
+        usage.last_used = chrono::Utc::now().timestamp();
+
+        let result = File::create(usage_path())
+            .map_err(anyhow::Error::from)
+            .and_then(|file| Ok(serde_json::to_writer(file, &self.usage)?));
+
+        if let Err(err) = result {
+            warn!("Failed to write command usage: {:?}", err);
+        }
+    }
+
+    /// Replaces the commands in the palette with the next stage of a command.
+    fn enter_stage(&mut self, items: Vec<Arc<Command>>, cx: &mut Context<Self>) {
+        self.recent.write().unwrap().clear();
+
+        self.palette.update(cx, |palette, cx| {
+            cx.emit(items);
+            palette.reset(cx);
+        });
+    }
+
+    /// Sends the commands to the palette, most used first.
+    fn update_items(&mut self, cx: &mut Context<Self>) {
+        let items = sorted_by_usage(&self.items, &self.usage);
+
+        self.palette.update(cx, |_, cx| {
+            cx.emit(items);
+        });
+    }
+
+    fn update_recent(&mut self, cx: &mut Context<Self>) {
+        let mut used: Vec<_> = self
+            .items
+            .iter()
+            .filter_map(|(id, command)| {
+                let usage = self.usage.get(&usage_key(id))?;
+                Some((usage.last_used, command.clone()))
+            })
+            .collect();
+        used.sort_by_key(|(last_used, _)| std::cmp::Reverse(*last_used));
+
+        let category = t!(cx, "command.recent");
+        let weak_self = cx.weak_entity();
+
+        *self.recent.write().unwrap() = used
+            .into_iter()
+            .take(RECENT_COMMANDS)
+            .map(|(_, command)| {
+                let weak_self = weak_self.clone();
+                let accepted = command.clone();
+
+                ExtraItem {
+                    left: Some(FinderItemLeft::Text(category.clone())),
+                    middle: command.middle_content(cx),
+                    right: command.right_content(cx),
+                    on_accept: Arc::new(move |cx| accept_command(&weak_self, &accepted, cx)),
+                }
+            })
+            .collect();
+    }
+}
+
+/// Orders the commands so that the most used ones come first when nothing has been searched for.
+/// Commands that have never been used are sorted by name.
+fn sorted_by_usage(
+    items: &FxHashMap<(&'static str, i64), Arc<Command>>,
+    usage: &FxHashMap<String, CommandUsage>,
+) -> Vec<Arc<Command>> {
+    let mut items: Vec<_> = items
+        .iter()
+        .map(|(id, command)| {
+            (
+                usage.get(&usage_key(id)).copied().unwrap_or_default(),
+                command.clone(),
+            )
+        })
+        .collect();
+
+    items.sort_by(|(a_usage, a), (b_usage, b)| {
+        b_usage
+            .count
+            .cmp(&a_usage.count)
+            .then(b_usage.last_used.cmp(&a_usage.last_used))
+            .then(a.name.cmp(&b.name))
+    });
+
+    items.into_iter().map(|(_, command)| command).collect()
 }
 
 impl Render for CommandPalette {
@@ -212,7 +815,7 @@ impl Render for CommandPalette {
             });
 
             modal()
-                .child(div().w(px(550.0)).h(px(300.0)).child(palette.clone()))
+                .child(div().w(spx(550.0)).h(spx(300.0)).child(palette.clone()))
                 .on_exit(move |_, cx| {
                     weak_self
                         .update(cx, |this, cx| {
@@ -1,3 +1,4 @@
+pub mod badge;
 pub mod button;
 pub mod context;
 pub mod icons;
@@ -10,3 +11,4 @@ pub mod sidebar;
 pub mod slider;
 pub mod styling;
 pub mod table;
+pub mod tooltip;
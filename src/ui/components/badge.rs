@@ -0,0 +1,58 @@
+use gpui::{
+    App, Div, FontWeight, InteractiveElement, ParentElement, SharedString,
+    StatefulInteractiveElement, Styled, div, prelude::FluentBuilder,
+};
+
+use crate::{
+    settings::SettingsGlobal,
+    ui::{components::tooltip::tooltip, locale::t, theme::Theme, util::spx},
+};
+
+/// A small outlined label, such as the format of a track.
+pub fn badge(label: impl Into<SharedString>, theme: &Theme) -> Div {
+    div()
+        .flex_shrink_0()
+        .px(spx(4.0))
+        .rounded(spx(3.0))
+        .border_1()
+        .border_color(theme.border_color)
+        .text_color(theme.text_secondary)
+        .text_size(spx(10.0))
+        .line_height(spx(14.0))
+        .font_weight(FontWeight::BOLD)
+        .child(label.into())
+}
+
+/// The badges shown next to the title of a track or album: its format (such as FLAC or Hi-Res),
+/// and whether it's explicit. Returns nothing if there are no badges to show, or they've been
+/// turned off with `interface.show_badges`.
+pub fn badges(cx: &App, format: Option<String>, explicit: bool) -> Option<Div> {
+    let show = cx
+        .global::<SettingsGlobal>()
+        .model
+        .read(cx)
+        .interface
+        .show_badges;
+
+    if !show || (format.is_none() && !explicit) {
+        return None;
+    }
+
+    let theme = cx.global::<Theme>();
+
+    Some(
+        div()
+            .flex()
+            .items_center()
+            .gap(spx(4.0))
+            .flex_shrink_0()
+            .when(explicit, |this| {
+                this.child(
+                    badge(t!(cx, "badge.explicit"), theme)
+                        .id("explicit-badge")
+                        .tooltip(tooltip(t!(cx, "badge.explicit_tooltip"))),
+                )
+            })
+            .when_some(format, |this, format| this.child(badge(format, theme))),
+    )
+}
@@ -1,8 +1,8 @@
 use gpui::*;
 
-use crate::ui::theme::Theme;
+use crate::ui::{theme::Theme, util::spx};
 
-use super::styling::AdditionalStyleUtil;
+use super::{styling::AdditionalStyleUtil, tooltip::tooltip};
 
 #[derive(Clone, Copy)]
 pub enum ButtonSize {
@@ -30,11 +30,13 @@ impl ButtonStyle {
     where
         T: Styled,
     {
-        let div = dest.cursor_pointer().flex();
+        // labels are truncated rather than overflowing, since translated labels can be much
+        // longer than the english ones
+        let div = dest.cursor_pointer().flex().truncate();
 
         match self {
-            ButtonStyle::Regular => div.shadow_md().rounded(px(4.0)),
-            ButtonStyle::Minimal => div.background_opacity(0.0).rounded(px(4.0)),
+            ButtonStyle::Regular => div.shadow_md().rounded(spx(4.0)),
+            ButtonStyle::Minimal => div.background_opacity(0.0).rounded(spx(4.0)),
             ButtonStyle::MinimalNoRounding => div.background_opacity(0.0),
         }
     }
@@ -66,13 +68,13 @@ impl ButtonSize {
         T: Styled,
     {
         match self {
-            ButtonSize::Regular => dest.px(px(10.0)).py(px(3.0)).text_sm().gap(px(8.0)),
+            ButtonSize::Regular => dest.px(spx(10.0)).py(spx(3.0)).text_sm().gap(spx(8.0)),
             ButtonSize::Large => dest
-                .px(px(12.0))
-                .pt(px(4.0))
-                .pb(px(3.0))
+                .px(spx(12.0))
+                .pt(spx(4.0))
+                .pb(spx(3.0))
                 .text_sm()
-                .gap(px(8.0)),
+                .gap(spx(8.0)),
         }
     }
 
@@ -223,6 +225,13 @@ impl InteractiveButton {
         self.div = self.div.on_click(fun);
         self
     }
+
+    /// Sets the label shown when hovering over the button. Buttons that only contain an icon
+    /// should always have one.
+    pub fn tooltip(mut self, label: impl Into<SharedString>) -> Self {
+        self.div = self.div.tooltip(tooltip(label.into()));
+        self
+    }
 }
 
 impl Styled for InteractiveButton {
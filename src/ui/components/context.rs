@@ -6,7 +6,7 @@ use std::{
     sync::atomic::{AtomicBool, Ordering},
 };
 
-use crate::ui::theme::Theme;
+use crate::ui::{theme::Theme, util::spx};
 
 pub struct ContextMenu {
     pub(self) id: ElementId,
@@ -102,7 +102,7 @@ impl Element for ContextMenu {
                         menu.occlude()
                             .border_1()
                             .shadow_sm()
-                            .rounded(px(6.0))
+                            .rounded(spx(6.0))
                             .border_color(theme.elevated_border_color)
                             .bg(theme.elevated_background)
                             .id("menu")
@@ -2,14 +2,27 @@
 // See assets/icons/LICENSE
 
 pub const ARROW_LEFT: &str = "!bundled:icons/arrow-left.svg";
+pub const ARROW_RIGHT: &str = "!bundled:icons/arrow-right.svg";
 pub const SHUFFLE: &str = "!bundled:icons/arrows-shuffle.svg";
 pub const LAST_FM: &str = "!bundled:icons/brand-lastfm.svg";
+pub const EXTERNAL_LINK: &str = "!bundled:icons/external-link.svg";
+pub const BOOK: &str = "!bundled:icons/book.svg";
+pub const CALENDAR: &str = "!bundled:icons/calendar.svg";
+pub const CAST: &str = "!bundled:icons/cast.svg";
+pub const CLEAR_ALL: &str = "!bundled:icons/clear-all.svg";
 pub const CIRCLE_PLUS: &str = "!bundled:icons/circle-plus.svg";
+pub const FOLDER: &str = "!bundled:icons/folder.svg";
+pub const HISTORY: &str = "!bundled:icons/history.svg";
 pub const FOLDER_CHECK: &str = "!bundled:icons/folder-check.svg";
 pub const FOLDER_SEARCH: &str = "!bundled:icons/folder-search.svg";
+pub const INFO: &str = "!bundled:icons/info-circle.svg";
+pub const INCOGNITO: &str = "!bundled:icons/incognito.svg";
+pub const LAYOUT_GRID: &str = "!bundled:icons/layout-grid.svg";
 pub const MAXIMIZE: &str = "!bundled:icons/maximize.svg";
 pub const MINIMIZE: &str = "!bundled:icons/minimize.svg";
 pub const MINUS: &str = "!bundled:icons/minus.svg";
+pub const PHOTO: &str = "!bundled:icons/photo.svg";
+pub const PHOTO_OFF: &str = "!bundled:icons/photo-off.svg";
 pub const PAUSE: &str = "!bundled:icons/player-pause.svg";
 pub const PLAY: &str = "!bundled:icons/player-play.svg";
 pub const NEXT_TRACK: &str = "!bundled:icons/player-track-next.svg";
@@ -22,7 +35,9 @@ pub const TRASH: &str = "!bundled:icons/trash.svg";
 pub const CROSS: &str = "!bundled:icons/x.svg";
 pub const VOLUME: &str = "!bundled:icons/volume.svg";
 pub const VOLUME_OFF: &str = "!bundled:icons/volume-off.svg";
+pub const VINYL: &str = "!bundled:icons/vinyl.svg";
 pub const MENU: &str = "!bundled:icons/menu-2.svg";
+pub const MICROPHONE: &str = "!bundled:icons/microphone.svg";
 pub const CHEVRON_UP: &str = "!bundled:icons/chevron-up.svg";
 pub const CHEVRON_DOWN: &str = "!bundled:icons/chevron-down.svg";
 pub const DISC: &str = "!bundled:icons/disc.svg";
@@ -34,3 +49,5 @@ pub const STAR_FILLED: &str = "!bundled:icons/star-filled.svg";
 pub const SIDEBAR: &str = "!bundled:icons/layout-sidebar.svg";
 pub const SIDEBAR_INACTIVE: &str = "!bundled:icons/layout-sidebar-inactive.svg";
 pub const SEARCH: &str = "!bundled:icons/search.svg";
+pub const STACK: &str = "!bundled:icons/stack.svg";
+pub const USER: &str = "!bundled:icons/user.svg";
@@ -6,7 +6,7 @@ use gpui::*;
 use tracing::debug;
 use unicode_segmentation::*;
 
-use crate::ui::{global_actions::PlayPause, theme::Theme};
+use crate::ui::{global_actions::PlayPause, theme::Theme, util::spx};
 
 actions!(
     text_input,
@@ -26,7 +26,8 @@ actions!(
         Copy,
         Next,
         Previous,
-        Accept
+        Accept,
+        SecondaryAccept
     ]
 );
 
@@ -35,6 +36,7 @@ pub enum EnrichedInputAction {
     Next,
     Previous,
     Accept,
+    SecondaryAccept,
 }
 
 pub fn bind_actions(cx: &mut App) {
@@ -48,6 +50,7 @@ pub fn bind_actions(cx: &mut App) {
         KeyBinding::new("home", Home, None),
         KeyBinding::new("end", End, None),
         KeyBinding::new("enter", Accept, None),
+        KeyBinding::new("shift-enter", SecondaryAccept, None),
         KeyBinding::new("down", Next, None),
         KeyBinding::new("up", Previous, None),
     ]);
@@ -99,8 +102,15 @@ impl TextInput {
         }
     }
 
-    fn right(&mut self, _: &Right, _: &mut Window, cx: &mut Context<Self>) {
-        if self.selected_range.is_empty() {
+    fn right(&mut self, _: &Right, window: &mut Window, cx: &mut Context<Self>) {
+        // there's nowhere further right to go, so the right arrow acts on the selected item
+        // instead, like Shift+Enter
+        if self.selected_range.is_empty()
+            && self.selected_range.end == self.content.len()
+            && let Some(handler) = self.enriched_input_handler.as_mut()
+        {
+            handler(EnrichedInputAction::SecondaryAccept, window, cx);
+        } else if self.selected_range.is_empty() {
             self.move_to(self.next_boundary(self.selected_range.end), cx);
         } else {
             self.move_to(self.selected_range.end, cx)
@@ -329,6 +339,18 @@ impl TextInput {
         };
         handler(EnrichedInputAction::Accept, window, cx);
     }
+
+    pub fn secondary_accept(
+        &mut self,
+        _: &SecondaryAccept,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(handler) = self.enriched_input_handler.as_mut() else {
+            return;
+        };
+        handler(EnrichedInputAction::SecondaryAccept, window, cx);
+    }
 }
 
 impl EntityInputHandler for TextInput {
@@ -705,6 +727,7 @@ impl Render for TextInput {
             .on_action(cx.listener(Self::next))
             .on_action(cx.listener(Self::previous))
             .on_action(cx.listener(Self::accept))
+            .on_action(cx.listener(Self::secondary_accept))
             .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
             .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
             .on_mouse_up_out(MouseButton::Left, cx.listener(Self::on_mouse_up))
@@ -715,8 +738,8 @@ impl Render for TextInput {
                     .overflow_x_scroll()
                     .track_scroll(&self.scroll_handle)
                     .w_full()
-                    .pr(px(2.0))
-                    .pb(px(2.0))
+                    .pr(spx(2.0))
+                    .pb(spx(2.0))
                     .child(TextElement {
                         input: cx.entity().clone(),
                     }),
@@ -1,6 +1,6 @@
 use gpui::{prelude::FluentBuilder, *};
 
-use crate::ui::{components::icons::icon, theme::Theme};
+use crate::ui::{components::icons::icon, theme::Theme, util::spx};
 
 type ClickEvHandler = dyn Fn(&ClickEvent, &mut Window, &mut App);
 
@@ -25,11 +25,11 @@ impl RenderOnce for CMenuItem {
             CMenuItem::Item(id, icon_path, name, func) => div()
                 .id(id)
                 .on_click(func)
-                .rounded(px(4.0))
+                .rounded(spx(4.0))
                 .flex()
-                .px(px(7.0))
-                .pt(px(6.0))
-                .pb(px(6.0))
+                .px(spx(7.0))
+                .pt(spx(6.0))
+                .pb(spx(6.0))
                 .line_height(rems(1.25))
                 .min_w_full()
                 .bg(theme.menu_item)
@@ -39,10 +39,10 @@ impl RenderOnce for CMenuItem {
                 .font_weight(FontWeight::MEDIUM)
                 .child(
                     div()
-                        .w(px(18.0))
-                        .h(px(18.0))
-                        .mr(px(7.0))
-                        .pt(px(0.5))
+                        .w(spx(18.0))
+                        .h(spx(18.0))
+                        .mr(spx(7.0))
+                        .pt(spx(0.5))
                         .my_auto()
                         .flex()
                         .items_center()
@@ -50,7 +50,7 @@ impl RenderOnce for CMenuItem {
                         .when_some(icon_path, |this, icon_path| {
                             this.child(
                                 icon(icon_path)
-                                    .size(px(18.0))
+                                    .size(spx(18.0))
                                     .text_color(theme.text_secondary),
                             )
                         }),
@@ -59,11 +59,11 @@ impl RenderOnce for CMenuItem {
                 .into_any_element(),
             CMenuItem::Seperator => div()
                 .min_w_full()
-                .h(px(1.0))
+                .h(spx(1.0))
                 .flex_shrink_0()
                 .bg(theme.elevated_border_color)
-                .mx(px(4.0))
-                .my(px(2.0))
+                .mx(spx(4.0))
+                .my(spx(2.0))
                 .into_any_element(),
             CMenuItem::Header(_) => div().into_any_element(), // TODO: implement this
         }
@@ -107,6 +107,6 @@ impl RenderOnce for Menu {
 pub fn menu() -> Menu {
     Menu {
         items: vec![],
-        div: div().min_w(px(200.0)).px(px(2.0)).py(px(2.0)),
+        div: div().min_w(spx(200.0)).px(spx(2.0)).py(spx(2.0)),
     }
 }
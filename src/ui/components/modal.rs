@@ -6,6 +6,7 @@ use prelude::FluentBuilder;
 use crate::ui::{
     constants::{APP_ROUNDING, APP_SHADOW_SIZE},
     theme::Theme,
+    util::spx,
 };
 
 pub type OnExitHandler = dyn Fn(&mut Window, &mut App);
@@ -111,7 +112,7 @@ impl RenderOnce for Modal {
                         .border_color(theme.border_color)
                         .border_1()
                         .bg(theme.background_secondary)
-                        .rounded(px(8.0))
+                        .rounded(spx(8.0))
                         .flex_col()
                         .on_any_mouse_down(|_, _, cx| {
                             cx.stop_propagation();
@@ -1,9 +1,9 @@
 use gpui::{
     Div, ElementId, InteractiveElement, IntoElement, ParentElement, RenderOnce, Stateful,
-    StatefulInteractiveElement, StyleRefinement, Styled, div, px,
+    StatefulInteractiveElement, StyleRefinement, Styled, div,
 };
 
-use crate::ui::{components::icons::icon, theme::Theme};
+use crate::ui::{components::icons::icon, theme::Theme, util::spx};
 
 #[derive(IntoElement)]
 pub struct NavButton {
@@ -31,7 +31,7 @@ impl RenderOnce for NavButton {
 
         self.div
             .flex()
-            .size(px(28.0))
+            .size(spx(28.0))
             .flex()
             .justify_center()
             .items_center()
@@ -40,7 +40,7 @@ impl RenderOnce for NavButton {
             .hover(|this| this.bg(theme.nav_button_hover))
             .active(|this| this.bg(theme.nav_button_active))
             .cursor_pointer()
-            .child(icon(self.icon).size(px(16.0)))
+            .child(icon(self.icon).size(spx(16.0)))
     }
 }
 
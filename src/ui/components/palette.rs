@@ -6,7 +6,7 @@ use std::sync::Arc;
 
 use gpui::{
     App, AppContext, Context, Entity, EventEmitter, FocusHandle, IntoElement, ParentElement,
-    Render, Styled, Window, div, px,
+    Render, Styled, Window, div,
 };
 use nucleo::Utf32String;
 
@@ -14,7 +14,7 @@ use crate::ui::components::{
     input::{EnrichedInputAction, TextInput},
     palette::finder::Finder,
 };
-use crate::ui::theme::Theme;
+use crate::ui::{theme::Theme, util::spx};
 
 pub struct Palette<T, MatcherFunc, OnAccept>
 where
@@ -120,11 +120,11 @@ where
             .child(
                 div()
                     .w_full()
-                    .p(px(12.0))
-                    .line_height(px(14.0))
-                    .h(px(40.0))
+                    .p(spx(12.0))
+                    .line_height(spx(14.0))
+                    .h(spx(40.0))
                     .text_sm()
-                    .border_b(px(1.0))
+                    .border_b(spx(1.0))
                     .border_color(theme.border_color)
                     .child(self.input.clone()),
             )
@@ -135,7 +135,7 @@ where
                     .h_full()
                     // FIXME: weird layout issue, this is a hack
                     // eventually this should be removed
-                    .pb(px(40.0))
+                    .pb(spx(40.0))
                     .child(self.finder.clone()),
             )
     }
@@ -1,8 +1,8 @@
 use std::{marker::PhantomData, sync::Arc, time::Duration};
 
 use gpui::{
-    App, AppContext, Context, ElementId, Entity, EventEmitter, FontWeight, InteractiveElement,
-    IntoElement, ListAlignment, ListState, ParentElement, Render, SharedString,
+    App, AppContext, ClickEvent, Context, ElementId, Entity, EventEmitter, FontWeight,
+    InteractiveElement, IntoElement, ListAlignment, ListState, ParentElement, Render, SharedString,
     StatefulInteractiveElement, Styled, WeakEntity, Window, div, img, list, prelude::FluentBuilder,
     px,
 };
@@ -14,12 +14,19 @@ use rustc_hash::FxHashMap;
 use tokio::sync::mpsc::channel;
 use tracing::debug;
 
-use crate::ui::{components::input::EnrichedInputAction, theme::Theme};
+use crate::ui::{components::input::EnrichedInputAction, theme::Theme, util::spx};
 
 pub trait PaletteItem {
     fn left_content(&self, cx: &mut App) -> Option<FinderItemLeft>;
     fn middle_content(&self, cx: &mut App) -> SharedString;
     fn right_content(&self, cx: &mut App) -> Option<SharedString>;
+
+    /// Runs the item's secondary action, chosen with Shift+Enter, the right arrow key, or
+    /// Shift+click. Returns false if the item doesn't have one, in which case it's accepted
+    /// normally instead.
+    fn secondary_accept(&self, _cx: &mut App) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
@@ -180,6 +187,20 @@ where
                             }
                         }
                     }
+                    EnrichedInputAction::SecondaryAccept => {
+                        let idx = *this.current_selection.read(cx);
+                        let match_idx = idx.saturating_sub(this.extra_items.len());
+
+                        // extra items don't have secondary actions
+                        if idx >= this.extra_items.len()
+                            && let Some(item) = this.last_match.get(match_idx)
+                            && !item.secondary_accept(cx)
+                        {
+                            on_accept_clone(item, cx);
+                        } else if let Some(extra) = this.extra_items.get(idx) {
+                            (extra.on_accept)(cx);
+                        }
+                    }
                 },
             )
             .detach();
@@ -349,7 +370,7 @@ where
             .image_cache(hummingbird_cache("finder-cache", 50))
             .id("finder")
             .flex()
-            .p(px(4.0))
+            .p(spx(4.0))
             .child(
                 list(self.list_state.clone(), move |idx, _, cx| {
                     let extras_len = extra_items.len();
@@ -418,7 +439,7 @@ where
                 })
                 .flex()
                 .flex_col()
-                .gap(px(2.0))
+                .gap(spx(2.0))
                 .w_full()
                 .h_full(),
             )
@@ -541,8 +562,8 @@ where
         let on_accept_override = self.on_accept_override.clone();
 
         div()
-            .px(px(10.0))
-            .py(px(6.0))
+            .px(spx(10.0))
+            .py(spx(6.0))
             .flex()
             .flex_row()
             .items_center()
@@ -553,13 +574,17 @@ where
             .when(self.current_selection == self.idx, |this| {
                 this.bg(theme.palette_item_hover)
             })
-            .rounded(px(4.0))
-            .on_click(cx.listener(move |_, _, _, cx| {
+            .rounded(spx(4.0))
+            .on_click(cx.listener(move |_, event: &ClickEvent, _, cx| {
                 if let Some(override_fn) = on_accept_override.clone() {
                     override_fn(cx);
                 } else if let Some(parent) = weak_parent.upgrade()
                     && let Some(item) = item_data.clone()
                 {
+                    if event.modifiers().shift && item.secondary_accept(cx) {
+                        return;
+                    }
+
                     parent.update(cx, |finder, cx| {
                         (finder.on_accept)(&item, cx);
                     });
@@ -572,22 +597,22 @@ where
                         .text_ellipsis()
                         .text_sm()
                         .text_color(theme.text_secondary)
-                        .mr(px(4.0)),
+                        .mr(spx(4.0)),
                     FinderItemLeft::Icon(icon_name) => {
                         use crate::ui::components::icons::icon;
                         div()
-                            .child(icon(icon_name).w(px(16.0)).h(px(16.0)))
-                            .mr(px(8.0))
+                            .child(icon(icon_name).w(spx(16.0)).h(spx(16.0)))
+                            .mr(spx(8.0))
                     }
                     FinderItemLeft::Image(image_path) => div()
-                        .rounded(px(2.0))
+                        .rounded(spx(2.0))
                         .bg(theme.album_art_background)
                         .shadow_sm()
-                        .w(px(16.0))
-                        .h(px(16.0))
+                        .w(spx(16.0))
+                        .h(spx(16.0))
                         .flex_shrink_0()
-                        .mr(px(8.0))
-                        .child(img(image_path).w(px(16.0)).h(px(16.0)).rounded(px(2.0))),
+                        .mr(spx(8.0))
+                        .child(img(image_path).w(spx(16.0)).h(spx(16.0)).rounded(spx(2.0))),
                 })
             })
             .child(
@@ -603,7 +628,7 @@ where
                 div_outer.child(
                     div()
                         .ml_auto()
-                        .pl(px(8.0))
+                        .pl(spx(8.0))
                         .flex_shrink()
                         .overflow_hidden()
                         .text_ellipsis()
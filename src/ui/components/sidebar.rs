@@ -1,10 +1,14 @@
 use gpui::{
     App, Div, ElementId, FontWeight, InteractiveElement, IntoElement, ParentElement, RenderOnce,
-    Stateful, StatefulInteractiveElement, StyleRefinement, Styled, Window, div,
-    prelude::FluentBuilder, px,
+    SharedString, Stateful, StatefulInteractiveElement, StyleRefinement, Styled, Window, div, img,
+    prelude::FluentBuilder,
 };
 
-use crate::ui::{components::icons::icon, theme::Theme, util::MaybeStateful};
+use crate::ui::{
+    components::icons::icon,
+    theme::Theme,
+    util::{MaybeStateful, spx},
+};
 
 #[derive(IntoElement)]
 pub struct Sidebar {
@@ -36,7 +40,7 @@ impl ParentElement for Sidebar {
 
 impl RenderOnce for Sidebar {
     fn render(self, _: &mut Window, _: &mut App) -> impl IntoElement {
-        self.div.w(px(225.0)).flex().flex_col()
+        self.div.w(spx(225.0)).flex().flex_col()
     }
 }
 
@@ -51,6 +55,7 @@ pub struct SidebarItem {
     parent_div: Stateful<Div>,
     children_div: Div,
     icon: Option<&'static str>,
+    image: Option<SharedString>,
     active: bool,
 }
 
@@ -60,6 +65,12 @@ impl SidebarItem {
         self
     }
 
+    /// Shows the image in place of the icon, such as a playlist's cover.
+    pub fn image(mut self, image: Option<SharedString>) -> Self {
+        self.image = image;
+        self
+    }
+
     pub fn active(mut self) -> Self {
         self.active = true;
         self
@@ -94,19 +105,24 @@ impl RenderOnce for SidebarItem {
             .bg(theme.background_primary)
             .text_sm()
             .when(self.active, |div| div.bg(theme.background_tertiary))
-            .rounded(px(4.0))
-            .px(px(9.0))
-            .py(px(7.0))
-            .line_height(px(18.0))
-            .gap(px(6.0))
+            .rounded(spx(4.0))
+            .px(spx(9.0))
+            .py(spx(7.0))
+            .line_height(spx(18.0))
+            .gap(spx(6.0))
             .font_weight(FontWeight::SEMIBOLD)
             .hover(|this| this.bg(theme.nav_button_hover))
             .active(|this| this.bg(theme.nav_button_active))
-            .when_none(&self.icon, |this| this.child(div().size(px(18.0))))
-            .when_some(self.icon, |this, used_icon| {
-                this.child(icon(used_icon).size(px(18.0)))
+            .when_some(self.image.clone(), |this, image| {
+                this.child(img(image).size(spx(18.0)).flex_shrink_0().rounded(spx(3.0)))
+            })
+            .when(self.image.is_none(), |this| {
+                this.when_none(&self.icon, |this| this.child(div().size(spx(18.0))))
+                    .when_some(self.icon, |this, used_icon| {
+                        this.child(icon(used_icon).size(spx(18.0)))
+                    })
             })
-            .child(self.children_div)
+            .child(self.children_div.truncate())
     }
 }
 
@@ -115,6 +131,7 @@ pub fn sidebar_item(id: impl Into<ElementId>) -> SidebarItem {
         parent_div: div().id(id),
         children_div: div(),
         icon: None,
+        image: None,
         active: false,
     }
 }
@@ -128,7 +145,7 @@ impl RenderOnce for SidebarSeparator {
 
         div()
             .w_full()
-            .my(px(6.0))
+            .my(spx(6.0))
             .border_b_1()
             .border_color(theme.border_color)
     }
@@ -5,12 +5,32 @@ use gpui::*;
 use crate::ui::theme::Theme;
 
 type ClickHandler = dyn FnMut(f32, &mut Window, &mut App);
+type HoverHandler = dyn FnMut(Option<f32>, &mut Window, &mut App);
+
+/// How fast the value follows the mouse while Shift is held, for fine adjustments.
+const FINE_DRAG_SPEED: f32 = 0.2;
+
+/// Where the mouse was when the value was last changed by dragging.
+#[derive(Clone, Copy)]
+struct Drag {
+    x: Pixels,
+    value: f32,
+}
+
+#[derive(Default)]
+struct SliderState {
+    drag: Option<Drag>,
+    hovering: bool,
+}
 
 pub struct Slider {
     pub(self) id: Option<ElementId>,
     pub(self) style: StyleRefinement,
     pub(self) value: f32,
     pub(self) on_change: Option<Rc<RefCell<ClickHandler>>>,
+    pub(self) on_release: Option<Rc<RefCell<ClickHandler>>>,
+    pub(self) on_hover: Option<Rc<RefCell<HoverHandler>>>,
+    pub(self) marks: Vec<f32>,
     pub(self) hitbox: Option<Hitbox>,
 }
 
@@ -29,6 +49,28 @@ impl Slider {
         self.on_change = Some(Rc::new(RefCell::new(func)));
         self
     }
+
+    /// Called with the final value when the mouse is released after clicking or dragging.
+    pub fn on_release(mut self, func: impl FnMut(f32, &mut Window, &mut App) + 'static) -> Self {
+        self.on_release = Some(Rc::new(RefCell::new(func)));
+        self
+    }
+
+    /// Called with the value under the mouse as it moves over the slider, and with `None` once it
+    /// leaves.
+    pub fn on_hover(
+        mut self,
+        func: impl FnMut(Option<f32>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_hover = Some(Rc::new(RefCell::new(func)));
+        self
+    }
+
+    /// Draws a tick at each of the values, such as the start of each chapter.
+    pub fn marks(mut self, marks: Vec<f32>) -> Self {
+        self.marks = marks;
+        self
+    }
 }
 
 impl Styled for Slider {
@@ -133,15 +175,35 @@ impl Element for Slider {
             BorderStyle::Solid,
         ));
 
+        for mark in &self.marks {
+            let x = bounds.origin.x + bounds.size.width * mark.clamp(0.0, 1.0);
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(x - px(1.0), bounds.origin.y),
+                    size(px(2.0), bounds.size.height),
+                ),
+                theme.background_primary,
+            ));
+        }
+
         if let Some(func) = self.on_change.as_ref() {
+            let on_release = self.on_release.clone();
+            let on_hover = self.on_hover.clone();
+
             window.with_optional_element_state(
                 id,
-                move |v: Option<Option<Rc<RefCell<bool>>>>, cx| {
-                    let mouse_in = v.flatten().unwrap_or_else(|| Rc::new(RefCell::new(false)));
+                move |v: Option<Option<Rc<RefCell<SliderState>>>>, cx| {
+                    let state = v.flatten().unwrap_or_default();
                     let func = func.clone();
                     let func_copy = func.clone();
 
-                    let mouse_in_1 = mouse_in.clone();
+                    let width: f32 = bounds.size.width.into();
+                    let value_at = move |position: Point<Pixels>| {
+                        let relative_x: f32 = (position - bounds.origin).x.into();
+                        (relative_x / width).clamp(0.0, 1.0)
+                    };
+
+                    let state_1 = state.clone();
 
                     cx.on_mouse_event(move |ev: &MouseDownEvent, _, window, cx| {
                         if !bounds.contains(&ev.position) {
@@ -151,35 +213,61 @@ impl Element for Slider {
                         window.prevent_default();
                         cx.stop_propagation();
 
-                        let relative = ev.position - bounds.origin;
-                        let relative_x: f32 = relative.x.into();
-                        let width: f32 = bounds.size.width.into();
-                        let value = (relative_x / width).clamp(0.0, 1.0);
+                        let value = value_at(ev.position);
 
                         (func.borrow_mut())(value, window, cx);
-                        (*mouse_in_1.borrow_mut()) = true;
+                        state_1.borrow_mut().drag = Some(Drag {
+                            x: ev.position.x,
+                            value,
+                        });
                     });
 
-                    let mouse_in_2 = mouse_in.clone();
+                    let state_2 = state.clone();
 
                     cx.on_mouse_event(move |ev: &MouseMoveEvent, _, window, cx| {
-                        if *mouse_in_2.borrow() {
-                            let relative = ev.position - bounds.origin;
-                            let relative_x: f32 = relative.x.into();
-                            let width: f32 = bounds.size.width.into();
-                            let value = (relative_x / width).clamp(0.0, 1.0);
+                        if let Some(on_hover) = on_hover.as_ref() {
+                            let hovering = bounds.contains(&ev.position);
 
-                            (func_copy.borrow_mut())(value, window, cx);
+                            if hovering {
+                                (on_hover.borrow_mut())(Some(value_at(ev.position)), window, cx);
+                            } else if state_2.borrow().hovering {
+                                (on_hover.borrow_mut())(None, window, cx);
+                            }
+
+                            state_2.borrow_mut().hovering = hovering;
                         }
+
+                        let Some(last) = state_2.borrow().drag else {
+                            return;
+                        };
+
+                        let value = if ev.modifiers.shift {
+                            let moved: f32 = (ev.position.x - last.x).into();
+                            (last.value + moved / width * FINE_DRAG_SPEED).clamp(0.0, 1.0)
+                        } else {
+                            value_at(ev.position)
+                        };
+
+                        (func_copy.borrow_mut())(value, window, cx);
+                        state_2.borrow_mut().drag = Some(Drag {
+                            x: ev.position.x,
+                            value,
+                        });
                     });
 
-                    let mouse_in_3 = mouse_in.clone();
+                    let state_3 = state.clone();
 
-                    cx.on_mouse_event(move |_: &MouseUpEvent, _, _, _| {
-                        (*mouse_in_3.borrow_mut()) = false;
+                    cx.on_mouse_event(move |_: &MouseUpEvent, _, window, cx| {
+                        let drag = state_3.borrow_mut().drag.take();
+
+                        if let Some(drag) = drag
+                            && let Some(on_release) = on_release.as_ref()
+                        {
+                            (on_release.borrow_mut())(drag.value, window, cx);
+                        }
                     });
 
-                    ((), Some(mouse_in))
+                    ((), Some(state))
                 },
             )
         }
@@ -192,6 +280,9 @@ pub fn slider() -> Slider {
         style: StyleRefinement::default(),
         value: 0.0,
         on_change: None,
+        on_release: None,
+        on_hover: None,
+        marks: Vec::new(),
         hitbox: None,
     }
 }
@@ -12,8 +12,9 @@ use table_item::TableItem;
 use crate::ui::{
     caching::hummingbird_cache,
     components::icons::{CHEVRON_DOWN, CHEVRON_UP, icon},
+    locale::t,
     theme::Theme,
-    util::{create_or_retrieve_view, prune_views},
+    util::{create_or_retrieve_view, prune_views, spx},
 };
 
 type RowMap<T, C> = FxHashMap<usize, Entity<TableItem<T, C>>>;
@@ -38,6 +39,10 @@ where
     items: Option<Arc<Vec<T::Identifier>>>,
     sort_method: Entity<Option<TableSort<C>>>,
     on_select: Option<OnSelectHandler<T, C>>,
+    /// Kept across row reloads so that the scroll position isn't lost when new rows arrive.
+    scroll_handle: UniformListScrollHandle,
+    /// Shown next to the title, for controls that change which rows are listed.
+    toolbar: Option<AnyView>,
 }
 
 pub enum TableEvent {
@@ -81,6 +86,7 @@ where
                 this.views = cx.new(|_| FxHashMap::default());
                 this.render_counter = cx.new(|_| 0);
                 this.items = items;
+                this.scroll_handle.scroll_to_item(0, ScrollStrategy::Top);
 
                 cx.notify();
             })
@@ -108,10 +114,16 @@ where
                 items,
                 sort_method,
                 on_select,
+                scroll_handle: UniformListScrollHandle::new(),
+                toolbar: None,
             }
         })
     }
 
+    pub fn set_toolbar(&mut self, toolbar: impl Into<AnyView>) {
+        self.toolbar = Some(toolbar.into());
+    }
+
     // fn make_list_state(
     //     cx: &mut Context<'_, Self>,
     //     views: Entity<RowMap<T, C>>,
@@ -170,11 +182,11 @@ where
         if T::has_images() {
             header = header.child(
                 div()
-                    .w(px(47.0))
-                    .h(px(36.0))
-                    .pl(px(21.0))
-                    .pr(px(10.0))
-                    .py(px(2.0))
+                    .w(spx(47.0))
+                    .h(spx(36.0))
+                    .pl(spx(21.0))
+                    .pr(spx(10.0))
+                    .py(spx(2.0))
                     .text_sm()
                     .flex_shrink_0()
                     .text_ellipsis()
@@ -191,15 +203,15 @@ where
             header = header.child(
                 div()
                     .flex()
-                    .w(px(width))
+                    .w(spx(width))
                     .when(T::has_images(), |div| {
-                        div.h(px(36.0)).px(px(12.0)).py(px(6.0))
+                        div.h(spx(36.0)).px(spx(12.0)).py(spx(6.0))
                     })
                     .when(!T::has_images(), |div| {
-                        div.h(px(30.0))
-                            .px(px(10.0))
-                            .py(px(2.0))
-                            .when(i == 0, |div| div.pl(px(21.0)))
+                        div.h(spx(30.0))
+                            .px(spx(10.0))
+                            .py(spx(2.0))
+                            .when(i == 0, |div| div.pl(spx(21.0)))
                     })
                     .text_sm()
                     .flex_shrink_0()
@@ -209,7 +221,7 @@ where
                     .border_b_1()
                     .border_color(theme.border_color)
                     .font_weight(FontWeight::BOLD)
-                    .child(SharedString::new_static(column_id.get_column_name()))
+                    .child(t!(cx, column_id.get_column_name()))
                     .when_some(sort_method.as_ref(), |this, method| {
                         this.when(method.column == column_id, |this| {
                             this.child(
@@ -218,8 +230,8 @@ where
                                 } else {
                                     CHEVRON_DOWN
                                 })
-                                .size(px(14.0))
-                                .ml(px(4.0))
+                                .size(spx(14.0))
+                                .ml(spx(4.0))
                                 .my_auto(),
                             )
                         })
@@ -260,12 +272,20 @@ where
             .child(
                 div()
                     .w_full()
-                    .pb(px(11.0))
-                    .px(px(16.0))
-                    .line_height(px(26.0))
-                    .font_weight(FontWeight::BOLD)
-                    .text_size(px(26.0))
-                    .child(T::get_table_name()),
+                    .flex()
+                    .items_center()
+                    .pb(spx(11.0))
+                    .px(spx(16.0))
+                    .child(
+                        div()
+                            .line_height(spx(26.0))
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(26.0))
+                            .child(T::get_table_title(cx)),
+                    )
+                    .when_some(self.toolbar.clone(), |this, toolbar| {
+                        this.child(div().ml_auto().child(toolbar))
+                    }),
             )
             .child(header)
             .when_some(items, |this, items| {
@@ -303,6 +323,7 @@ where
                             })
                             .collect()
                     })
+                    .track_scroll(self.scroll_handle.clone())
                     .w_full()
                     .h_full(),
                 )
@@ -5,7 +5,8 @@ use indexmap::IndexMap;
 use rustc_hash::FxBuildHasher;
 
 pub trait Column: Clone + Copy + Debug + Hash + PartialEq + Eq {
-    /// Retrieves the friendly name text of the column.
+    /// Retrieves the message id of the friendly name of the column. The name is translated when
+    /// the table is rendered.
     fn get_column_name(&self) -> &'static str;
 }
 
@@ -26,9 +27,12 @@ where
 {
     type Identifier: Clone + Debug;
 
-    /// Retrieves the name of the table.
+    /// Retrieves the name of the table. This is used to identify the table, and is not shown.
     fn get_table_name() -> &'static str;
 
+    /// Retrieves the title shown above the table.
+    fn get_table_title(cx: &App) -> SharedString;
+
     /// Retrieves the rows of the table. The rows are returned as a vector of identifiers, which
     /// can be used to retrieve the full row data. The sort parameter can be used to specify the
     /// sorting order of the rows.
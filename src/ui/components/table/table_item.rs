@@ -4,7 +4,7 @@ use gpui::{prelude::FluentBuilder, *};
 use indexmap::IndexMap;
 use rustc_hash::FxBuildHasher;
 
-use crate::ui::theme::Theme;
+use crate::ui::{theme::Theme, util::spx};
 
 use super::{
     OnSelectHandler,
@@ -101,10 +101,10 @@ where
         if T::has_images() {
             row = row.child(
                 div()
-                    .w(px(47.0))
-                    .h(px(36.0))
+                    .w(spx(47.0))
+                    .h(spx(36.0))
                     .text_sm()
-                    .pl(px(11.0))
+                    .pl(spx(11.0))
                     .flex_shrink_0()
                     .text_ellipsis()
                     //.border_r_1()
@@ -115,12 +115,12 @@ where
                     .child(
                         div()
                             .m_auto()
-                            .w(px(22.0))
-                            .h(px(22.0))
-                            .rounded(px(3.0))
+                            .w(spx(22.0))
+                            .h(spx(22.0))
+                            .rounded(spx(3.0))
                             .bg(theme.album_art_background)
                             .when_some(self.image_path.clone(), |div, image| {
-                                div.child(img(image).w(px(22.0)).h(px(22.0)).rounded(px(3.0)))
+                                div.child(img(image).w(spx(22.0)).h(spx(22.0)).rounded(spx(3.0)))
                             }),
                     ),
             );
@@ -135,15 +135,15 @@ where
                 let width = *col.1;
                 let monospace = T::column_monospace(*col.0);
                 let column = div()
-                    .w(px(width))
+                    .w(spx(width))
                     .when(T::has_images(), |div| {
-                        div.h(px(36.0)).px(px(12.0)).py(px(6.0))
+                        div.h(spx(36.0)).px(spx(12.0)).py(spx(6.0))
                     })
                     .when(!T::has_images(), |div| {
-                        div.h(px(30.0))
-                            .px(px(10.0))
-                            .py(px(2.0))
-                            .when(i == 0, |div| div.pl(px(21.0)))
+                        div.h(spx(30.0))
+                            .px(spx(10.0))
+                            .py(spx(2.0))
+                            .when(i == 0, |div| div.pl(spx(21.0)))
                     })
                     .when(monospace, |div| div.font_family("Roboto Mono"))
                     .text_sm()
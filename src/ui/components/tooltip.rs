@@ -0,0 +1,72 @@
+use std::rc::Rc;
+
+use gpui::{
+    AnyView, App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render,
+    SharedString, Styled, Window, div,
+};
+
+use crate::ui::{theme::Theme, util::spx};
+
+/// A short label describing an element, shown when it is hovered. Every control that only shows
+/// an icon should have one, so that what it does can be discovered without clicking it.
+///
+/// Screen readers never see tooltips.
+// TODO: give controls accessible names and roles once gpui has an accessibility API
+pub struct Tooltip {
+    label: SharedString,
+}
+
+impl Render for Tooltip {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        div()
+            .font_family("Inter")
+            .text_color(theme.text)
+            .text_xs()
+            .font_weight(FontWeight::SEMIBOLD)
+            .bg(theme.elevated_background)
+            .border_1()
+            .border_color(theme.elevated_border_color)
+            .rounded(spx(4.0))
+            .shadow_md()
+            .px(spx(6.0))
+            .py(spx(3.0))
+            .child(self.label.clone())
+    }
+}
+
+/// Returns a tooltip builder for use with [gpui::StatefulInteractiveElement::tooltip].
+pub fn tooltip(label: SharedString) -> impl Fn(&mut Window, &mut App) -> AnyView {
+    move |_, cx| {
+        cx.new(|_| Tooltip {
+            label: label.clone(),
+        })
+        .into()
+    }
+}
+
+/// Returns a tooltip builder whose label is created from the given entity, and is kept up to date
+/// with it for as long as the tooltip is shown.
+pub fn live_tooltip<T: 'static>(
+    entity: Entity<T>,
+    label: impl Fn(&T, &App) -> SharedString + 'static,
+) -> impl Fn(&mut Window, &mut App) -> AnyView {
+    let label = Rc::new(label);
+
+    move |_, cx| {
+        let initial = label(entity.read(cx), cx);
+        let label = label.clone();
+
+        cx.new(|cx| {
+            cx.observe(&entity, move |this: &mut Tooltip, entity, cx| {
+                this.label = label(entity.read(cx), cx);
+                cx.notify();
+            })
+            .detach();
+
+            Tooltip { label: initial }
+        })
+        .into()
+    }
+}
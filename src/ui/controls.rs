@@ -1,24 +1,43 @@
+use std::sync::Arc;
+
 use crate::{
-    playback::{events::RepeatState, interface::PlaybackInterface, thread::PlaybackState},
+    devices::format::SampleFormat,
+    library::{
+        db::LibraryAccess,
+        playlist::{LIKED_PLAYLIST_ID, set_track_liked},
+        types::Track,
+    },
+    media::metadata::StreamInfo,
+    playback::{
+        events::{OutputPath, RepeatState},
+        interface::PlaybackInterface,
+        thread::PlaybackState,
+    },
     settings::SettingsGlobal,
     ui::components::{
+        badge::badge,
         context::context,
         icons::{
-            MENU, NEXT_TRACK, PAUSE, PLAY, PREV_TRACK, REPEAT, REPEAT_OFF, REPEAT_ONCE, SHUFFLE,
-            VOLUME, VOLUME_OFF, icon,
+            CAST, INCOGNITO, MENU, NEXT_TRACK, PAUSE, PLAY, PREV_TRACK, REPEAT, REPEAT_OFF,
+            REPEAT_ONCE, SHUFFLE, STAR, STAR_FILLED, VOLUME, VOLUME_OFF, icon,
         },
         menu::{menu, menu_item},
+        tooltip::{live_tooltip, tooltip},
     },
 };
 use gpui::*;
 use prelude::FluentBuilder;
 
 use super::{
+    cast::CastPicker,
     components::slider::slider,
     constants::APP_ROUNDING,
-    global_actions::{Next, PlayPause, Previous},
-    models::{Models, PlaybackInfo},
+    global_actions::{Next, PlayPause, Previous, TogglePrivateListening},
+    locale::{Locale, t},
+    models::{Models, PlaybackInfo, PlaylistEvent},
+    queue::open_queue_window,
     theme::Theme,
+    util::spx,
 };
 
 pub struct Controls {
@@ -72,6 +91,8 @@ pub struct InfoSection {
     artist_name: Option<SharedString>,
     albumart_actual: Option<ImageSource>,
     playback_info: PlaybackInfo,
+    /// The playing track, if it is in the library, and its liked playlist item if it is liked.
+    library_track: Option<(Arc<Track>, Option<i64>)>,
 }
 
 impl InfoSection {
@@ -86,10 +107,20 @@ impl InfoSection {
             })
             .detach();
 
+            cx.observe(&playback_info.output_path, |_, _, cx| {
+                cx.notify();
+            })
+            .detach();
+
+            cx.observe(&playback_info.renderer, |_, _, cx| {
+                cx.notify();
+            })
+            .detach();
+
             cx.observe(&metadata_model, |this: &mut Self, m, cx| {
                 let metadata = m.read(cx);
 
-                this.track_name = metadata.name.clone().map(SharedString::from);
+                this.track_name = metadata.display_title().map(SharedString::from);
                 this.artist_name = metadata.artist.clone().map(SharedString::from);
 
                 cx.notify();
@@ -104,52 +135,210 @@ impl InfoSection {
             })
             .detach();
 
-            Self {
+            cx.observe(&playback_info.current_track, |this: &mut Self, _, cx| {
+                this.update_library_track(cx);
+                cx.notify();
+            })
+            .detach();
+
+            let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+
+            cx.subscribe(&playlist_tracker, |this: &mut Self, _, ev, cx| {
+                if *ev == PlaylistEvent::PlaylistUpdated(LIKED_PLAYLIST_ID) {
+                    this.update_library_track(cx);
+                    cx.notify();
+                }
+            })
+            .detach();
+
+            let mut this = Self {
                 artist_name: None,
                 track_name: None,
                 albumart_actual: None,
                 playback_info,
-            }
+                library_track: None,
+            };
+
+            this.update_library_track(cx);
+            this
         })
     }
 }
 
+impl InfoSection {
+    fn update_library_track(&mut self, cx: &mut Context<Self>) {
+        let current_track = self.playback_info.current_track.read(cx).clone();
+
+        self.library_track = current_track
+            .and_then(|current| cx.get_track_by_location(current.get_path()).ok().flatten())
+            .map(|track| {
+                let liked = cx
+                    .playlist_has_track(LIKED_PLAYLIST_ID, track.id)
+                    .unwrap_or_default();
+
+                (track, liked)
+            });
+    }
+}
+
+/// Summarizes the technical details of the playing stream, e.g. "FLAC · 44.1 kHz · 16-bit".
+fn stream_summary(info: &Option<StreamInfo>, cx: &App) -> SharedString {
+    let Some(info) = info else {
+        return t!(cx, "properties.unknown");
+    };
+
+    let locale = cx.global::<Locale>();
+    let mut parts: Vec<SharedString> = Vec::new();
+
+    if let Some(codec) = &info.codec {
+        parts.push(codec.to_uppercase().into());
+    }
+    if let Some(rate) = info.sample_rate {
+        parts.push(locale.format(
+            "properties.sample_rate_value",
+            &[("khz", &(rate as f64 / 1000.0))],
+        ));
+    }
+    if let Some(bits) = info.bits_per_sample {
+        parts.push(locale.format("properties.bit_depth_value", &[("bits", &bits)]));
+    }
+    if let Some(kbps) = info.bitrate {
+        parts.push(locale.format("properties.bitrate_value", &[("kbps", &kbps)]));
+    }
+
+    if parts.is_empty() {
+        t!(cx, "properties.unknown")
+    } else {
+        parts.join(" · ").into()
+    }
+}
+
+/// Describes a format audio is decoded in or played in, e.g. "96 kHz 24-bit".
+fn describe_format(locale: &Locale, rate: u32, format: SampleFormat) -> String {
+    let rate = locale.format(
+        "properties.sample_rate_value",
+        &[("khz", &(rate as f64 / 1000.0))],
+    );
+
+    match format.bits() {
+        Some(bits) if format.is_float() => {
+            format!(
+                "{rate} {}",
+                locale.format("output.float_value", &[("bits", &bits)])
+            )
+        }
+        Some(bits) => format!(
+            "{rate} {}",
+            locale.format("properties.bit_depth_value", &[("bits", &bits)])
+        ),
+        None => rate.to_string(),
+    }
+}
+
+/// Shows whether the audio reaches the output device bit-perfect, or the first thing that stops
+/// it from being bit-perfect. The tooltip shows the format of the file and of the device, e.g.
+/// "96 kHz 24-bit → 48 kHz 32-bit float · Resampled · Converted".
+fn output_path_badge(path: &OutputPath, cx: &App) -> Stateful<Div> {
+    let theme = cx.global::<Theme>();
+    let locale = cx.global::<Locale>();
+
+    let mut changes = Vec::new();
+    if path.resampled() {
+        changes.push(t!(cx, "output.resampled"));
+    }
+    if path.converted() {
+        changes.push(t!(cx, "output.converted"));
+    }
+    if path.remixed() {
+        changes.push(t!(cx, "output.remixed"));
+    }
+    if path.volume_reduced {
+        changes.push(t!(cx, "output.volume_reduced"));
+    }
+
+    let mut description = locale
+        .format(
+            "output.path",
+            &[
+                (
+                    "source",
+                    &describe_format(locale, path.source_rate, path.source_format),
+                ),
+                (
+                    "device",
+                    &describe_format(locale, path.device_rate, path.device_format),
+                ),
+            ],
+        )
+        .to_string();
+    for change in &changes {
+        description = format!("{description} · {change}");
+    }
+
+    let label = changes
+        .first()
+        .cloned()
+        .unwrap_or_else(|| t!(cx, "output.bit_perfect"));
+
+    badge(label, theme)
+        .when(path.is_bit_perfect(), |this| {
+            this.text_color(theme.text)
+                .border_color(theme.text_secondary)
+        })
+        .id("output-path")
+        .tooltip(tooltip(description.into()))
+}
+
 impl Render for InfoSection {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = cx.global::<Theme>();
         let state = self.playback_info.playback_state.read(cx);
+        // renderers play the file themselves, so only the path to the local device is known
+        let output_path = self
+            .playback_info
+            .output_path
+            .read(cx)
+            .clone()
+            .filter(|_| self.playback_info.renderer.read(cx).is_none());
 
         div()
             .id("info-section")
             .flex()
-            .w(px(275.0))
-            .min_w(px(275.0))
-            .max_w(px(275.0))
+            .w(spx(275.0))
+            .min_w(spx(275.0))
+            .max_w(spx(275.0))
             .overflow_x_hidden()
             .flex_shrink_0()
+            .when(*state != PlaybackState::Stopped, |this| {
+                this.tooltip(live_tooltip(
+                    self.playback_info.stream_info.clone(),
+                    stream_summary,
+                ))
+            })
             .child(
                 div()
-                    .mx(px(12.0))
-                    .mt(px(12.0))
-                    .mb(px(6.0))
-                    .gap(px(10.0))
+                    .mx(spx(12.0))
+                    .mt(spx(12.0))
+                    .mb(spx(6.0))
+                    .gap(spx(10.0))
                     .flex()
+                    .flex_grow()
                     .overflow_x_hidden()
                     .child(
                         div()
                             .id("album-art")
-                            .rounded(px(4.0))
+                            .rounded(spx(4.0))
                             .bg(theme.album_art_background)
                             .shadow_sm()
-                            .w(px(36.0))
-                            .h(px(36.0))
-                            .mb(px(6.0))
+                            .w(spx(36.0))
+                            .h(spx(36.0))
+                            .mb(spx(6.0))
                             .when(self.albumart_actual.is_some(), |div| {
                                 div.child(
                                     img(self.albumart_actual.clone().unwrap())
-                                        .w(px(36.0))
-                                        .h(px(36.0))
-                                        .rounded(px(4.0)),
+                                        .w(spx(36.0))
+                                        .h(spx(36.0))
+                                        .rounded(spx(4.0)),
                                 )
                             }),
                     )
@@ -158,11 +347,11 @@ impl Render for InfoSection {
                             div()
                                 .line_height(rems(1.0))
                                 .font_weight(FontWeight::EXTRA_BOLD)
-                                .text_size(px(15.0))
+                                .text_size(spx(15.0))
                                 .flex()
                                 .h_full()
                                 .items_center()
-                                .pb(px(6.0))
+                                .pb(spx(6.0))
                                 .child("Hummingbird"),
                         )
                     })
@@ -172,7 +361,7 @@ impl Render for InfoSection {
                                 .flex()
                                 .flex_col()
                                 .line_height(rems(1.0))
-                                .text_size(px(15.0))
+                                .text_size(spx(15.0))
                                 .gap_1()
                                 .overflow_x_hidden()
                                 .child(
@@ -183,23 +372,63 @@ impl Render for InfoSection {
                                         .child(
                                             self.track_name
                                                 .clone()
-                                                .unwrap_or("Unknown Track".into()),
+                                                .unwrap_or_else(|| t!(cx, "track.unknown_title")),
                                         ),
                                 )
                                 .child(
                                     div()
-                                        .overflow_x_hidden()
-                                        .pb(px(6.0))
-                                        .text_ellipsis()
+                                        .flex()
+                                        .items_center()
+                                        .gap(spx(6.0))
+                                        .pb(spx(6.0))
                                         .overflow_x_hidden()
                                         .child(
-                                            self.artist_name
-                                                .clone()
-                                                .unwrap_or("Unknown Artist".into()),
-                                        ),
+                                            div().overflow_x_hidden().text_ellipsis().child(
+                                                self.artist_name.clone().unwrap_or_else(|| {
+                                                    t!(cx, "track.unknown_artist")
+                                                }),
+                                            ),
+                                        )
+                                        .children(output_path.map(|path| {
+                                            output_path_badge(&path, cx).flex_shrink_0()
+                                        })),
                                 ),
                         )
-                    }),
+                    })
+                    .when_some(
+                        self.library_track
+                            .clone()
+                            .filter(|_| *state != PlaybackState::Stopped),
+                        |this, (track, liked)| {
+                            this.child(
+                                div()
+                                    .id("now-playing-like")
+                                    .tooltip(tooltip(if liked.is_some() {
+                                        t!(cx, "track.unlike")
+                                    } else {
+                                        t!(cx, "track.like")
+                                    }))
+                                    .ml_auto()
+                                    .my_auto()
+                                    .flex_shrink_0()
+                                    .rounded(spx(4.0))
+                                    .p(spx(6.0))
+                                    .hover(|this| this.bg(theme.button_secondary_hover))
+                                    .active(|this| this.bg(theme.button_secondary_active))
+                                    .child(
+                                        icon(if liked.is_some() { STAR_FILLED } else { STAR })
+                                            .size(spx(16.0))
+                                            .text_color(theme.text_secondary),
+                                    )
+                                    .on_click(move |_, _, cx| {
+                                        cx.stop_propagation();
+
+                                        set_track_liked(cx, &track, liked.is_none())
+                                            .expect("could not like song");
+                                    }),
+                            )
+                        },
+                    ),
             )
     }
 }
@@ -246,17 +475,17 @@ impl Render for PlaybackSection {
         div()
             .mr(auto())
             .ml(auto())
-            .mt(px(5.0))
+            .mt(spx(5.0))
             .flex()
             .w_full()
             .absolute()
             .child(
                 div()
-                    .rounded(px(3.0))
-                    .w(px(28.0))
-                    .h(px(25.0))
-                    .mt(px(3.0))
-                    .mr(px(6.0))
+                    .rounded(spx(3.0))
+                    .w(spx(28.0))
+                    .h(spx(25.0))
+                    .mt(spx(3.0))
+                    .mr(spx(6.0))
                     .ml_auto()
                     .border_color(theme.playback_button_border)
                     .flex()
@@ -264,6 +493,11 @@ impl Render for PlaybackSection {
                     .justify_center()
                     .hover(|style| style.bg(theme.playback_button_hover).cursor_pointer())
                     .id("header-shuffle-button")
+                    .tooltip(tooltip(if *shuffling {
+                        t!(cx, "controls.shuffle_off")
+                    } else {
+                        t!(cx, "controls.shuffle_on")
+                    }))
                     .active(|style| style.bg(theme.playback_button_active))
                     .on_mouse_down(MouseButton::Left, |_, window, cx| {
                         cx.stop_propagation();
@@ -272,27 +506,28 @@ impl Render for PlaybackSection {
                     .on_click(|_, _, cx| {
                         cx.global::<PlaybackInterface>().toggle_shuffle();
                     })
-                    .child(icon(SHUFFLE).size(px(14.0)).when(*shuffling, |this| {
+                    .child(icon(SHUFFLE).size(spx(14.0)).when(*shuffling, |this| {
                         this.text_color(theme.playback_button_toggled)
                     })),
             )
             .child(
                 div()
-                    .rounded(px(4.0))
+                    .rounded(spx(4.0))
                     .border_color(theme.playback_button_border)
                     .border_1()
                     .flex()
                     .child(
                         div()
-                            .w(px(30.0))
-                            .h(px(28.0))
-                            .rounded_l(px(3.0))
+                            .w(spx(30.0))
+                            .h(spx(28.0))
+                            .rounded_l(spx(3.0))
                             .bg(theme.playback_button)
                             .flex()
                             .items_center()
                             .justify_center()
                             .hover(|style| style.bg(theme.playback_button_hover).cursor_pointer())
                             .id("header-prev-button")
+                            .tooltip(tooltip(t!(cx, "controls.previous")))
                             .active(|style| style.bg(theme.playback_button_active))
                             .on_mouse_down(MouseButton::Left, |_, window, cx| {
                                 cx.stop_propagation();
@@ -301,21 +536,26 @@ impl Render for PlaybackSection {
                             .on_click(|_, window, cx| {
                                 window.dispatch_action(Box::new(Previous), cx);
                             })
-                            .child(icon(PREV_TRACK).size(px(16.0))),
+                            .child(icon(PREV_TRACK).size(spx(16.0))),
                     )
                     .child(
                         div()
-                            .w(px(32.0))
-                            .h(px(28.0))
+                            .w(spx(32.0))
+                            .h(spx(28.0))
                             .bg(theme.playback_button)
-                            .border_l(px(1.0))
-                            .border_r(px(1.0))
+                            .border_l(spx(1.0))
+                            .border_r(spx(1.0))
                             .border_color(theme.playback_button_border)
                             .flex()
                             .items_center()
                             .justify_center()
                             .hover(|style| style.bg(theme.playback_button_hover).cursor_pointer())
                             .id("header-play-button")
+                            .tooltip(tooltip(if *state == PlaybackState::Playing {
+                                t!(cx, "controls.pause")
+                            } else {
+                                t!(cx, "controls.play")
+                            }))
                             .active(|style| style.bg(theme.playback_button_active))
                             .on_mouse_down(MouseButton::Left, |_, window, cx| {
                                 cx.stop_propagation();
@@ -325,23 +565,24 @@ impl Render for PlaybackSection {
                                 window.dispatch_action(Box::new(PlayPause), cx);
                             })
                             .when(*state == PlaybackState::Playing, |div| {
-                                div.child(icon(PAUSE).size(px(16.0)))
+                                div.child(icon(PAUSE).size(spx(16.0)))
                             })
                             .when(*state != PlaybackState::Playing, |div| {
-                                div.child(icon(PLAY).size(px(16.0)))
+                                div.child(icon(PLAY).size(spx(16.0)))
                             }),
                     )
                     .child(
                         div()
-                            .w(px(30.0))
-                            .h(px(28.0))
-                            .rounded_r(px(3.0))
+                            .w(spx(30.0))
+                            .h(spx(28.0))
+                            .rounded_r(spx(3.0))
                             .bg(theme.playback_button)
                             .flex()
                             .items_center()
                             .justify_center()
                             .hover(|style| style.bg(theme.playback_button_hover).cursor_pointer())
                             .id("header-next-button")
+                            .tooltip(tooltip(t!(cx, "controls.next")))
                             .active(|style| style.bg(theme.playback_button_active))
                             .on_mouse_down(MouseButton::Left, |_, window, cx| {
                                 cx.stop_propagation();
@@ -350,7 +591,7 @@ impl Render for PlaybackSection {
                             .on_click(|_, window, cx| {
                                 window.dispatch_action(Box::new(Next), cx);
                             })
-                            .child(icon(NEXT_TRACK).size(px(16.0))),
+                            .child(icon(NEXT_TRACK).size(spx(16.0))),
                     ),
             )
             .child(
@@ -358,11 +599,11 @@ impl Render for PlaybackSection {
                     context("repeat-context")
                         .with(
                             div()
-                                .rounded(px(3.0))
-                                .w(px(28.0))
-                                .h(px(25.0))
-                                .mt(px(3.0))
-                                .ml(px(6.0))
+                                .rounded(spx(3.0))
+                                .w(spx(28.0))
+                                .h(spx(25.0))
+                                .mt(spx(3.0))
+                                .ml(spx(6.0))
                                 .border_color(theme.playback_button_border)
                                 .flex()
                                 .items_center()
@@ -371,6 +612,11 @@ impl Render for PlaybackSection {
                                     style.bg(theme.playback_button_hover).cursor_pointer()
                                 })
                                 .id("header-repeat-button")
+                                .tooltip(tooltip(match repeating {
+                                    RepeatState::NotRepeating => t!(cx, "controls.repeat_off"),
+                                    RepeatState::Repeating => t!(cx, "controls.repeat"),
+                                    RepeatState::RepeatingOne => t!(cx, "controls.repeat_one"),
+                                }))
                                 .active(|style| style.bg(theme.playback_button_active))
                                 .on_mouse_down(MouseButton::Left, |_, window, cx| {
                                     cx.stop_propagation();
@@ -394,7 +640,7 @@ impl Render for PlaybackSection {
                                         }
                                         RepeatState::RepeatingOne => REPEAT_ONCE,
                                     })
-                                    .size(px(14.0))
+                                    .size(spx(14.0))
                                     .when(
                                         repeating == RepeatState::Repeating
                                             || repeating == RepeatState::RepeatingOne,
@@ -409,7 +655,7 @@ impl Render for PlaybackSection {
                                         menu.item(menu_item(
                                             "repeat-not-repeat",
                                             Some(REPEAT_OFF),
-                                            "Off",
+                                            t!(cx, "controls.repeat_off"),
                                             move |_, _, cx| {
                                                 cx.global::<PlaybackInterface>()
                                                     .set_repeat(RepeatState::NotRepeating);
@@ -419,7 +665,7 @@ impl Render for PlaybackSection {
                                     .item(menu_item(
                                         "repeat-repeat",
                                         Some(REPEAT),
-                                        "Repeat",
+                                        t!(cx, "controls.repeat"),
                                         move |_, _, cx| {
                                             cx.global::<PlaybackInterface>()
                                                 .set_repeat(RepeatState::Repeating);
@@ -428,7 +674,7 @@ impl Render for PlaybackSection {
                                     .item(menu_item(
                                         "repeat-repeat-one",
                                         Some(REPEAT_ONCE),
-                                        "Repeat One",
+                                        t!(cx, "controls.repeat_one"),
                                         move |_, _, cx| {
                                             cx.global::<PlaybackInterface>()
                                                 .set_repeat(RepeatState::RepeatingOne);
@@ -444,7 +690,17 @@ impl Render for PlaybackSection {
 pub struct Scrubber {
     position: Entity<u64>,
     duration: Entity<u64>,
+    chapters: Entity<Vec<f64>>,
+    buffering: Entity<bool>,
     playback_section: Entity<PlaybackSection>,
+    /// Where on the bar the mouse is, from 0.0 to 1.0.
+    hover: Option<f32>,
+    /// Where the bar is being dragged to. The seek happens once the mouse is released.
+    scrub: Option<f32>,
+}
+
+fn format_time(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
 }
 
 impl Scrubber {
@@ -463,10 +719,28 @@ impl Scrubber {
             })
             .detach();
 
+            let chapters = cx.global::<PlaybackInfo>().chapters.clone();
+
+            cx.observe(&chapters, |_, _, cx| {
+                cx.notify();
+            })
+            .detach();
+
+            let buffering = cx.global::<PlaybackInfo>().buffering.clone();
+
+            cx.observe(&buffering, |_, _, cx| {
+                cx.notify();
+            })
+            .detach();
+
             Self {
                 position: position_model,
                 duration: duration_model,
+                chapters,
+                buffering,
                 playback_section: PlaybackSection::new(cx),
+                hover: None,
+                scrub: None,
             }
         })
     }
@@ -475,21 +749,38 @@ impl Scrubber {
 impl Render for Scrubber {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = cx.global::<Theme>();
-        let position = *self.position.read(cx);
         let duration = *self.duration.read(cx);
-        let remaining = duration - position;
+        // while scrubbing, the times show where the seek will go instead of where playback is
+        let position = match self.scrub {
+            Some(scrub) => (scrub as f64 * duration as f64) as u64,
+            None => *self.position.read(cx),
+        };
+        let remaining = duration.saturating_sub(position);
+
+        let marks = if duration > 0 {
+            self.chapters
+                .read(cx)
+                .iter()
+                .map(|start| (*start / duration as f64) as f32)
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         let window_width = window.viewport_size().width;
+        let this = cx.entity().downgrade();
+        let this_release = this.clone();
+        let this_hover = this.clone();
 
         div()
-            .pl(px(13.0))
-            .pr(px(13.0))
-            .border_x(px(1.0))
+            .pl(spx(13.0))
+            .pr(spx(13.0))
+            .border_x(spx(1.0))
             .border_color(theme.border_color)
             .flex_grow()
             .flex()
             .flex_col()
-            .text_size(px(15.0))
+            .text_size(spx(15.0))
             .font_weight(FontWeight::SEMIBOLD)
             .relative()
             .child(
@@ -498,54 +789,138 @@ impl Render for Scrubber {
                     .flex()
                     .relative()
                     .items_end()
-                    .mt(px(6.0))
-                    .mb(px(6.0))
-                    .child(div().mr(px(6.0)).line_height(rems(1.0)).child(format!(
-                        "{:02}:{:02}",
-                        position / 60,
-                        position % 60
-                    )))
+                    .mt(spx(6.0))
+                    .mb(spx(6.0))
+                    .child(
+                        div()
+                            .mr(spx(6.0))
+                            .line_height(rems(1.0))
+                            .child(format_time(position)),
+                    )
                     .when(window_width > px(900.0), |this| {
                         this.child(
                             div()
                                 .line_height(rems(1.0))
                                 .border_color(rgb(0x4b5563))
-                                .border_l(px(2.0))
-                                .pl(px(6.0))
+                                .border_l(spx(2.0))
+                                .pl(spx(6.0))
                                 .text_color(rgb(0xcbd5e1))
-                                .child(format!("{:02}:{:02}", duration / 60, duration % 60)),
+                                .child(format_time(duration)),
+                        )
+                    })
+                    // shown instead of leaving the time to stand still while the track downloads
+                    .when(*self.buffering.read(cx), |this| {
+                        this.child(
+                            div()
+                                .ml(spx(6.0))
+                                .line_height(rems(1.0))
+                                .text_color(rgb(0xcbd5e1))
+                                .child(t!(cx, "controls.buffering")),
                         )
                     })
                     .child(self.playback_section.clone())
-                    .child(div().h(px(30.0)))
-                    .child(div().ml(auto()).line_height(rems(1.0)).child(format!(
-                        "-{:02}:{:02}",
-                        remaining / 60,
-                        remaining % 60
-                    ))),
+                    .child(div().h(spx(30.0)))
+                    .child(
+                        div()
+                            .ml(auto())
+                            .line_height(rems(1.0))
+                            .child(format!("-{}", format_time(remaining))),
+                    ),
             )
             .child(
-                slider()
+                div()
+                    .relative()
                     .w_full()
-                    .h(px(6.0))
-                    .rounded(px(3.0))
-                    .id("scrubber-back")
-                    .value(position as f32 / duration as f32)
-                    .on_change(move |v, _, cx| {
-                        let info = cx.global::<PlaybackInfo>().clone();
-
-                        if duration > 0 && *info.playback_state.read(cx) != PlaybackState::Stopped {
-                            cx.global::<PlaybackInterface>()
-                                .seek(v as f64 * duration as f64);
-                        }
-                    }),
+                    .child(
+                        slider()
+                            .w_full()
+                            .h(spx(6.0))
+                            .rounded(spx(3.0))
+                            .id("scrubber-back")
+                            .value(position as f32 / duration as f32)
+                            .marks(marks)
+                            .on_change(move |v, _, cx| {
+                                let info = cx.global::<PlaybackInfo>().clone();
+
+                                if duration > 0
+                                    && *info.playback_state.read(cx) != PlaybackState::Stopped
+                                {
+                                    this.update(cx, |this, cx| {
+                                        this.scrub = Some(v);
+                                        cx.notify();
+                                    })
+                                    .ok();
+                                }
+                            })
+                            .on_release(move |v, _, cx| {
+                                let scrubbing = this_release
+                                    .update(cx, |this, cx| {
+                                        cx.notify();
+                                        this.scrub.take().is_some()
+                                    })
+                                    .unwrap_or(false);
+
+                                if scrubbing {
+                                    cx.global::<PlaybackInterface>()
+                                        .seek(v as f64 * duration as f64);
+                                }
+                            })
+                            .on_hover(move |v, _, cx| {
+                                this_hover
+                                    .update(cx, |this, cx| {
+                                        this.hover = v;
+                                        cx.notify();
+                                    })
+                                    .ok();
+                            }),
+                    )
+                    .when_some(
+                        self.hover.filter(|_| duration > 0 && self.scrub.is_none()),
+                        |this, hover| {
+                            this.child(
+                                div()
+                                    .absolute()
+                                    .bottom(spx(10.0))
+                                    .left(relative(hover))
+                                    .ml(spx(-22.0))
+                                    .w(spx(44.0))
+                                    .flex()
+                                    .justify_center()
+                                    .text_xs()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .bg(theme.elevated_background)
+                                    .border_1()
+                                    .border_color(theme.elevated_border_color)
+                                    .rounded(spx(4.0))
+                                    .shadow_md()
+                                    .py(spx(3.0))
+                                    .child(format_time((hover as f64 * duration as f64) as u64)),
+                            )
+                        },
+                    ),
             )
     }
 }
 
+/// How much a notch of the scroll wheel changes the volume.
+const VOLUME_STEP: f64 = 0.05;
+
+/// How much the volume changes with Shift held, when scrolling or dragging the slider.
+const FINE_VOLUME_STEP: f64 = 0.01;
+
+/// How far a trackpad has to scroll to change the volume by one step.
+const PIXELS_PER_VOLUME_STEP: f64 = 3.0;
+
+/// Rounds the volume to the nearest multiple of the step.
+fn round_volume(volume: f64, step: f64) -> f64 {
+    ((volume / step).round() * step).clamp(0.0, 1.0)
+}
+
 pub struct SecondaryControls {
     info: PlaybackInfo,
     show_queue: Entity<bool>,
+    cast_picker: Entity<CastPicker>,
+    show_cast_picker: Entity<bool>,
 }
 
 impl SecondaryControls {
@@ -559,7 +934,26 @@ impl SecondaryControls {
             })
             .detach();
 
-            Self { info, show_queue }
+            cx.observe(&info.renderer, |_, _, cx| {
+                cx.notify();
+            })
+            .detach();
+
+            let private_listening = cx.global::<Models>().private_listening.clone();
+            cx.observe(&private_listening, |_, _, cx| {
+                cx.notify();
+            })
+            .detach();
+
+            let show_cast_picker = cx.new(|_| false);
+            let cast_picker = CastPicker::new(cx, show_cast_picker.clone());
+
+            Self {
+                info,
+                show_queue,
+                cast_picker,
+                show_cast_picker,
+            }
         })
     }
 }
@@ -570,87 +964,193 @@ impl Render for SecondaryControls {
         let volume = *self.info.volume.read(cx);
         let prev_volume = *self.info.prev_volume.read(cx);
         let show_queue = self.show_queue.clone();
+        let show_cast_picker = self.show_cast_picker.clone();
+        let renderer = self.info.renderer.read(cx).clone();
+        let private_listening = *cx.global::<Models>().private_listening.read(cx);
 
-        div().px(px(18.0)).flex().child(
-            div()
-                .flex()
-                .my_auto()
-                .pb(px(2.0))
-                .gap(px(8.0))
-                .child(
-                    div()
-                        .rounded(px(3.0))
-                        .w(px(28.0))
-                        .h(px(25.0))
-                        .mt(px(2.0))
-                        .flex()
-                        .items_center()
-                        .justify_center()
-                        .border_color(theme.playback_button_border)
-                        .id("volume-button")
-                        .cursor_pointer()
-                        .bg(theme.playback_button)
-                        .hover(|this| this.bg(theme.playback_button_hover))
-                        .active(|this| this.bg(theme.playback_button_active))
-                        .when(volume <= 0.0, |div| {
-                            div.child(icon(VOLUME_OFF).size(px(14.0)))
-                                .on_click(move |_, _, cx| {
-                                    cx.global::<PlaybackInterface>().set_volume(prev_volume);
-                                })
-                        })
-                        .when(volume > 0.0, |div| {
-                            div.child(icon(VOLUME).size(px(14.0)))
-                                .on_click(move |_, _, cx| {
-                                    cx.global::<PlaybackInterface>().set_volume(0 as f64);
-                                })
-                        }),
-                )
-                .child(
-                    div()
-                        .child(
-                            slider()
-                                .w(px(80.0))
-                                .h(px(6.0))
-                                .mt(px(11.0))
-                                .rounded(px(3.0))
-                                .id("volume")
-                                .value((volume) as f32)
-                                .on_change(move |v, _, cx| {
-                                    cx.global::<PlaybackInterface>().set_volume(v as f64);
-                                }),
-                        )
-                        .on_scroll_wheel(move |ev, _, cx| {
-                            let delta: f64 = ev.delta.pixel_delta(px(0.01666666)).y.into();
-                            cx.global::<PlaybackInterface>().set_volume(f64::clamp(
-                                volume + delta,
-                                0_f64,
-                                1_f64,
-                            ));
-                        }),
-                )
-                .child(
-                    div()
-                        .rounded(px(3.0))
-                        .w(px(28.0))
-                        .h(px(25.0))
-                        .mt(px(2.0))
-                        .flex()
-                        .items_center()
-                        .justify_center()
-                        .border_color(theme.playback_button_border)
-                        .id("queue-button")
-                        .cursor_pointer()
-                        .bg(theme.playback_button)
-                        .hover(|this| this.bg(theme.playback_button_hover))
-                        .active(|this| this.bg(theme.playback_button_active))
-                        .child(icon(MENU).size(px(14.0)))
-                        .on_click(move |_, _, cx| {
-                            show_queue.update(cx, |m, cx| {
-                                *m = !*m;
-                                cx.notify();
+        div()
+            .px(spx(18.0))
+            .flex()
+            .child(self.cast_picker.clone())
+            .child(
+                div()
+                    .flex()
+                    .my_auto()
+                    .pb(spx(2.0))
+                    .gap(spx(8.0))
+                    .child(
+                        div()
+                            .rounded(spx(3.0))
+                            .w(spx(28.0))
+                            .h(spx(25.0))
+                            .mt(spx(2.0))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .border_color(theme.playback_button_border)
+                            .id("volume-button")
+                            .tooltip(tooltip(if volume <= 0.0 {
+                                t!(cx, "controls.unmute")
+                            } else {
+                                t!(cx, "controls.mute")
+                            }))
+                            .cursor_pointer()
+                            .bg(theme.playback_button)
+                            .hover(|this| this.bg(theme.playback_button_hover))
+                            .active(|this| this.bg(theme.playback_button_active))
+                            .when(volume <= 0.0, |div| {
+                                div.child(icon(VOLUME_OFF).size(spx(14.0))).on_click(
+                                    move |_, _, cx| {
+                                        cx.global::<PlaybackInterface>().set_volume(prev_volume);
+                                    },
+                                )
                             })
-                        }),
-                ),
-        )
+                            .when(volume > 0.0, |div| {
+                                div.child(icon(VOLUME).size(spx(14.0)))
+                                    .on_click(move |_, _, cx| {
+                                        cx.global::<PlaybackInterface>().set_volume(0 as f64);
+                                    })
+                            }),
+                    )
+                    .child(
+                        div()
+                            .id("volume-container")
+                            .tooltip(tooltip(t!(
+                                cx,
+                                "controls.volume",
+                                percent = (volume * 100.0).round()
+                            )))
+                            .child(
+                                slider()
+                                    .w(spx(80.0))
+                                    .h(spx(6.0))
+                                    .mt(spx(11.0))
+                                    .rounded(spx(3.0))
+                                    .id("volume")
+                                    .value((volume) as f32)
+                                    .on_change(move |v, _, cx| {
+                                        // the slider moves slower with shift held, so single
+                                        // percents can be picked
+                                        cx.global::<PlaybackInterface>()
+                                            .set_volume(round_volume(v as f64, FINE_VOLUME_STEP));
+                                    }),
+                            )
+                            .on_scroll_wheel(move |ev, _, cx| {
+                                let step = if ev.modifiers.shift {
+                                    FINE_VOLUME_STEP
+                                } else {
+                                    VOLUME_STEP
+                                };
+
+                                // some platforms turn shift + scroll into horizontal scrolling
+                                let volume = match ev.delta {
+                                    ScrollDelta::Lines(lines) => {
+                                        let lines = if lines.y != 0.0 { lines.y } else { lines.x };
+                                        round_volume(volume + lines as f64 * step, step)
+                                    }
+                                    ScrollDelta::Pixels(pixels) => {
+                                        let pixels = if pixels.y != px(0.0) {
+                                            pixels.y
+                                        } else {
+                                            pixels.x
+                                        };
+                                        let pixels: f64 = pixels.into();
+                                        volume + pixels / PIXELS_PER_VOLUME_STEP * step
+                                    }
+                                };
+
+                                cx.global::<PlaybackInterface>()
+                                    .set_volume(volume.clamp(0_f64, 1_f64));
+                            }),
+                    )
+                    .child(
+                        div()
+                            .rounded(spx(3.0))
+                            .w(spx(28.0))
+                            .h(spx(25.0))
+                            .mt(spx(2.0))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .border_color(theme.playback_button_border)
+                            .id("private-button")
+                            .tooltip(tooltip(if private_listening {
+                                t!(cx, "controls.private_listening_on")
+                            } else {
+                                t!(cx, "controls.private_listening")
+                            }))
+                            .cursor_pointer()
+                            .bg(theme.playback_button)
+                            .hover(|this| this.bg(theme.playback_button_hover))
+                            .active(|this| this.bg(theme.playback_button_active))
+                            .when(private_listening, |this| {
+                                this.bg(theme.playback_button_active)
+                            })
+                            .child(
+                                icon(INCOGNITO)
+                                    .size(spx(14.0))
+                                    .when(private_listening, |this| {
+                                        this.text_color(theme.playback_button_toggled)
+                                    }),
+                            )
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(Box::new(TogglePrivateListening), cx)
+                            }),
+                    )
+                    .child(
+                        div()
+                            .rounded(spx(3.0))
+                            .w(spx(28.0))
+                            .h(spx(25.0))
+                            .mt(spx(2.0))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .border_color(theme.playback_button_border)
+                            .id("cast-button")
+                            .tooltip(tooltip(match &renderer {
+                                Some(name) => t!(cx, "controls.casting_to", name = name),
+                                None => t!(cx, "controls.cast"),
+                            }))
+                            .cursor_pointer()
+                            .bg(theme.playback_button)
+                            .hover(|this| this.bg(theme.playback_button_hover))
+                            .active(|this| this.bg(theme.playback_button_active))
+                            .child(icon(CAST).size(spx(14.0)).when(renderer.is_some(), |this| {
+                                this.text_color(theme.playback_button_toggled)
+                            }))
+                            .on_click(move |_, _, cx| show_cast_picker.write(cx, true)),
+                    )
+                    .child(
+                        div()
+                            .rounded(spx(3.0))
+                            .w(spx(28.0))
+                            .h(spx(25.0))
+                            .mt(spx(2.0))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .border_color(theme.playback_button_border)
+                            .id("queue-button")
+                            .tooltip(tooltip(t!(cx, "controls.queue")))
+                            .cursor_pointer()
+                            .bg(theme.playback_button)
+                            .hover(|this| this.bg(theme.playback_button_hover))
+                            .active(|this| this.bg(theme.playback_button_active))
+                            .child(icon(MENU).size(spx(14.0)))
+                            .on_click(move |_, _, cx| {
+                                // bring the queue window forward instead if the queue is popped out
+                                if cx.global::<Models>().queue_window.read(cx).is_some() {
+                                    open_queue_window(cx);
+                                    return;
+                                }
+
+                                show_queue.update(cx, |m, cx| {
+                                    *m = !*m;
+                                    cx.notify();
+                                })
+                            }),
+                    ),
+            )
     }
 }
@@ -1,18 +1,100 @@
-use gpui::{App, KeyBinding, Menu, MenuItem, SharedString, actions};
-use tracing::{debug, info};
+use gpui::{Action, App, Entity, KeyBinding, Menu, MenuItem, SharedString, actions};
+use tracing::{debug, info, warn};
 
 use crate::{
-    library::scan::ScanInterface,
+    library::{
+        db::LibraryAccess,
+        playlist::{LIKED_PLAYLIST_ID, set_track_liked},
+        scan::ScanInterface,
+    },
     playback::{interface::PlaybackInterface, thread::PlaybackState},
-    ui::command_palette::OpenPalette,
+    services::scripts::run_external_command,
+    settings::{
+        Settings, SettingsGlobal,
+        interface::{MAX_SCALE, MIN_SCALE},
+        playback::PlaybackSettings,
+        save_settings,
+    },
+    ui::command_palette::{Command, CommandManager, OpenPalette},
 };
 
-use super::models::{Models, PlaybackInfo};
+use super::{
+    library::{self, ViewSwitchMessage},
+    locale::t,
+    models::{Models, PlaybackInfo, PlaylistEvent},
+};
 
 actions!(hummingbird, [Quit, About, Search]);
-actions!(player, [PlayPause, Next, Previous]);
+actions!(player, [PlayPause, Next, Previous, TogglePrivateListening]);
+actions!(player, [ResumeFromOtherDevice]);
+actions!(
+    player,
+    [
+        SeekForward,
+        SeekBackward,
+        SeekForwardLong,
+        SeekBackwardLong,
+        VolumeUp,
+        VolumeDown
+    ]
+);
 actions!(scan, [ForceScan]);
+actions!(library, [ExportLibraryData, ImportLibraryData]);
 actions!(hummingbird, [HideSelf, HideOthers, ShowAll]);
+actions!(interface, [ZoomIn, ZoomOut, ResetZoom]);
+
+/// Opens the release view for the album.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = hummingbird, no_json)]
+pub struct GoToAlbum {
+    pub album_id: i64,
+}
+
+/// Switches the library to the view.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = hummingbird, no_json)]
+pub struct GoToView {
+    pub view: ViewSwitchMessage,
+}
+
+/// Adds the currently playing track to the playlist.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = player, no_json)]
+pub struct AddPlayingToPlaylist {
+    pub playlist_id: i64,
+}
+
+/// Runs one of the custom commands from the settings, by its position in the list.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = hummingbird, no_json)]
+pub struct RunCustomCommand {
+    pub index: usize,
+}
+
+/// A setting that can be turned on and off from the command palette.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SettingToggle {
+    AlwaysRepeat,
+    PrevTrackJumpFirst,
+    SkipSilence,
+    SkipByWork,
+    ShowPlayCount,
+    ShowLastPlayed,
+    ShowBpm,
+    ShowKey,
+    ShowBadges,
+    AnalyzeTracks,
+}
+
+/// Turns the setting on if it's off, or off if it's on.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = settings, no_json)]
+pub struct ToggleSetting {
+    pub setting: SettingToggle,
+}
+
+/// The amount the interface scale changes by with each zoom in or zoom out.
+const ZOOM_STEP: f32 = 0.1;
 
 pub fn register_actions(cx: &mut App) {
     debug!("registering actions");
@@ -20,11 +102,31 @@ pub fn register_actions(cx: &mut App) {
     cx.on_action(play_pause);
     cx.on_action(next);
     cx.on_action(previous);
+    cx.on_action(toggle_private_listening);
+    cx.on_action(|_: &ResumeFromOtherDevice, cx| library::resume_from_other_device(cx));
+    cx.on_action(|_: &SeekForward, cx| seek_by(cx, |playback| playback.seek_step as i64));
+    cx.on_action(|_: &SeekBackward, cx| seek_by(cx, |playback| -(playback.seek_step as i64)));
+    cx.on_action(|_: &SeekForwardLong, cx| seek_by(cx, |playback| playback.long_seek_step as i64));
+    cx.on_action(|_: &SeekBackwardLong, cx| {
+        seek_by(cx, |playback| -(playback.long_seek_step as i64))
+    });
+    cx.on_action(|_: &VolumeUp, cx| change_volume(cx, 1.0));
+    cx.on_action(|_: &VolumeDown, cx| change_volume(cx, -1.0));
     cx.on_action(hide_self);
     cx.on_action(hide_others);
     cx.on_action(show_all);
     cx.on_action(about);
     cx.on_action(force_scan);
+    cx.on_action(|_: &ExportLibraryData, cx| library::export_data(cx));
+    cx.on_action(|_: &ImportLibraryData, cx| library::import_data(cx));
+    cx.on_action(zoom_in);
+    cx.on_action(zoom_out);
+    cx.on_action(reset_zoom);
+    cx.on_action(go_to_album);
+    cx.on_action(go_to_view);
+    cx.on_action(add_playing_to_playlist);
+    cx.on_action(run_custom_command);
+    cx.on_action(toggle_setting);
     debug!("actions: {:?}", cx.all_action_names());
     debug!("action available: {:?}", cx.is_action_available(&Quit));
     if cfg!(target_os = "macos") {
@@ -44,31 +146,123 @@ pub fn register_actions(cx: &mut App) {
     cx.bind_keys([KeyBinding::new("secondary-shift-p", OpenPalette, None)]);
 
     cx.bind_keys([KeyBinding::new("alt-shift-s", ForceScan, None)]);
+    cx.bind_keys([KeyBinding::new("secondary-=", ZoomIn, None)]);
+    cx.bind_keys([KeyBinding::new("secondary-+", ZoomIn, None)]);
+    cx.bind_keys([KeyBinding::new("secondary--", ZoomOut, None)]);
+    cx.bind_keys([KeyBinding::new("secondary-0", ResetZoom, None)]);
     cx.bind_keys([KeyBinding::new("space", PlayPause, None)]);
+    cx.bind_keys([KeyBinding::new("alt-right", SeekForward, None)]);
+    cx.bind_keys([KeyBinding::new("alt-left", SeekBackward, None)]);
+    cx.bind_keys([KeyBinding::new("alt-shift-right", SeekForwardLong, None)]);
+    cx.bind_keys([KeyBinding::new("alt-shift-left", SeekBackwardLong, None)]);
+    cx.bind_keys([KeyBinding::new("alt-up", VolumeUp, None)]);
+    cx.bind_keys([KeyBinding::new("alt-down", VolumeDown, None)]);
+}
+
+/// Binds the keys from [crate::settings::interface::InterfaceSettings::keybindings]. Bindings
+/// added later take priority, so this should be called after everything else has bound its keys.
+pub fn bind_custom_keys(cx: &mut App) {
+    let keybindings = cx
+        .global::<SettingsGlobal>()
+        .model
+        .read(cx)
+        .interface
+        .keybindings
+        .clone();
+
+    for (keystrokes, name) in keybindings {
+        let action = match cx.build_action(&name, None) {
+            Ok(action) => action,
+            Err(err) => {
+                warn!("Couldn't bind {keystrokes} to {name}: {err}");
+                continue;
+            }
+        };
+
+        match KeyBinding::load(
+            &keystrokes,
+            action,
+            None,
+            false,
+            None,
+            cx.keyboard_mapper().as_ref(),
+        ) {
+            Ok(binding) => cx.bind_keys([binding]),
+            Err(err) => warn!("Couldn't bind {keystrokes} to {name}: {err}"),
+        }
+    }
+}
+
+/// Adds the custom commands from the settings to the command palette, and keeps them up to date
+/// when the settings change. The command palette must already be set up.
+pub fn register_custom_commands(cx: &mut App) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+    let mut names: Vec<String> = Vec::new();
+
+    let mut update = move |settings: Entity<Settings>, cx: &mut App| {
+        let new_names: Vec<String> = settings
+            .read(cx)
+            .scripts
+            .commands
+            .iter()
+            .map(|command| command.name.clone())
+            .collect();
+
+        if new_names == names {
+            return;
+        }
+
+        for index in 0..names.len() {
+            cx.unregister_command(("custom::command", index as i64));
+        }
+
+        let category = t!(cx, "command.category.custom");
+
+        for (index, name) in new_names.iter().enumerate() {
+            cx.register_command(
+                ("custom::command", index as i64),
+                Command::new(
+                    Some(category.clone()),
+                    name.clone(),
+                    RunCustomCommand { index },
+                    None,
+                ),
+            );
+        }
+
+        names = new_names;
+    };
+
+    update(settings.clone(), cx);
+    cx.observe(&settings, update).detach();
+}
+
+/// Sets the application menus (only shown on macOS) in the current language.
+pub fn set_menus(cx: &mut App) {
     cx.set_menus(vec![
         Menu {
             name: SharedString::from("Hummingbird"),
             items: vec![
-                MenuItem::action("About Hummingbird", About),
+                MenuItem::action(t!(cx, "menu.about"), About),
                 MenuItem::separator(),
                 MenuItem::submenu(Menu {
-                    name: SharedString::from("Services"),
+                    name: t!(cx, "menu.services"),
                     items: vec![],
                 }),
                 MenuItem::separator(),
-                MenuItem::action("Hide Hummingbird", HideSelf),
-                MenuItem::action("Hide Others", HideOthers),
-                MenuItem::action("Show All", ShowAll),
+                MenuItem::action(t!(cx, "menu.hide"), HideSelf),
+                MenuItem::action(t!(cx, "menu.hide_others"), HideOthers),
+                MenuItem::action(t!(cx, "menu.show_all"), ShowAll),
                 MenuItem::separator(),
-                MenuItem::action("Quit Hummingbird", Quit),
+                MenuItem::action(t!(cx, "menu.quit"), Quit),
             ],
         },
         Menu {
-            name: SharedString::from("View"),
+            name: t!(cx, "menu.view"),
             items: vec![],
         },
         Menu {
-            name: SharedString::from("Window"),
+            name: t!(cx, "menu.window"),
             items: vec![],
         },
     ]);
@@ -105,6 +299,152 @@ fn previous(_: &Previous, cx: &mut App) {
     interface.previous();
 }
 
+fn toggle_private_listening(_: &TogglePrivateListening, cx: &mut App) {
+    let private_listening = cx.global::<Models>().private_listening.clone();
+    private_listening.update(cx, |private, cx| {
+        *private = !*private;
+        info!(
+            "Private listening turned {}",
+            if *private { "on" } else { "off" }
+        );
+        cx.notify();
+    });
+}
+
+/// Moves the playback position by the number of seconds the closure picks from the settings.
+fn seek_by(cx: &mut App, step: impl Fn(&PlaybackSettings) -> i64) {
+    let info = cx.global::<PlaybackInfo>();
+    if *info.playback_state.read(cx) == PlaybackState::Stopped {
+        return;
+    }
+
+    let position = *info.position.read(cx) as i64;
+    let duration = *info.duration.read(cx) as i64;
+    let step = step(&cx.global::<SettingsGlobal>().model.read(cx).playback);
+
+    let target = (position + step).clamp(0, duration.max(0));
+    cx.global::<PlaybackInterface>().seek(target as f64);
+}
+
+/// Changes the volume by the step from the settings, in the given direction.
+fn change_volume(cx: &mut App, direction: f64) {
+    let volume = *cx.global::<PlaybackInfo>().volume.read(cx);
+    let step = cx
+        .global::<SettingsGlobal>()
+        .model
+        .read(cx)
+        .playback
+        .volume_step;
+
+    cx.global::<PlaybackInterface>()
+        .set_volume((volume + step * direction).clamp(0.0, 1.0));
+}
+
+fn go_to_album(action: &GoToAlbum, cx: &mut App) {
+    let switcher_model = cx.global::<Models>().switcher_model.clone();
+    switcher_model.update(cx, |_, cx| {
+        cx.emit(ViewSwitchMessage::Release(action.album_id));
+    });
+}
+
+fn go_to_view(action: &GoToView, cx: &mut App) {
+    let switcher_model = cx.global::<Models>().switcher_model.clone();
+    switcher_model.update(cx, |_, cx| {
+        cx.emit(action.view);
+    });
+}
+
+fn add_playing_to_playlist(action: &AddPlayingToPlaylist, cx: &mut App) {
+    let Some(current_track) = cx.global::<PlaybackInfo>().current_track.read(cx).clone() else {
+        return;
+    };
+    let track = match cx.get_track_by_location(current_track.get_path()) {
+        Ok(Some(track)) => track,
+        Ok(None) => return,
+        Err(err) => {
+            warn!("Failed to look up the playing track: {err}");
+            return;
+        }
+    };
+
+    // liking a track also loves it on Last.fm
+    if action.playlist_id == LIKED_PLAYLIST_ID {
+        if let Err(err) = set_track_liked(cx, &track, true) {
+            warn!("Failed to like the playing track: {err}");
+        }
+        return;
+    }
+
+    match cx.playlist_has_track(action.playlist_id, track.id) {
+        Ok(Some(_)) => (),
+        Ok(None) => {
+            if let Err(err) = cx.add_playlist_item(action.playlist_id, track.id) {
+                warn!("Failed to add the playing track to the playlist: {err}");
+                return;
+            }
+
+            let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+            playlist_tracker.update(cx, |_, cx| {
+                cx.emit(PlaylistEvent::PlaylistUpdated(action.playlist_id));
+            });
+        }
+        Err(err) => warn!("Failed to add the playing track to the playlist: {err}"),
+    }
+}
+
+fn run_custom_command(action: &RunCustomCommand, cx: &mut App) {
+    let settings = cx.global::<SettingsGlobal>().model.read(cx);
+    let Some(custom) = settings.scripts.commands.get(action.index).cloned() else {
+        return;
+    };
+
+    let path = cx.global::<PlaybackInfo>().current_track.read(cx).clone();
+    let metadata = cx.global::<Models>().metadata.read(cx);
+
+    run_external_command(
+        &custom.command,
+        path.as_ref().map(|track| track.get_path().as_path()),
+        path.is_some().then_some(metadata),
+    );
+}
+
+fn toggle_setting(action: &ToggleSetting, cx: &mut App) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+
+    settings.update(cx, |settings, cx| {
+        match action.setting {
+            SettingToggle::AlwaysRepeat => {
+                settings.playback.always_repeat = !settings.playback.always_repeat
+            }
+            SettingToggle::PrevTrackJumpFirst => {
+                settings.playback.prev_track_jump_first = !settings.playback.prev_track_jump_first
+            }
+            SettingToggle::SkipSilence => {
+                settings.playback.skip_silence = !settings.playback.skip_silence
+            }
+            SettingToggle::SkipByWork => {
+                settings.playback.skip_by_work = !settings.playback.skip_by_work
+            }
+            SettingToggle::ShowPlayCount => {
+                settings.interface.show_play_count = !settings.interface.show_play_count
+            }
+            SettingToggle::ShowLastPlayed => {
+                settings.interface.show_last_played = !settings.interface.show_last_played
+            }
+            SettingToggle::ShowBpm => settings.interface.show_bpm = !settings.interface.show_bpm,
+            SettingToggle::ShowKey => settings.interface.show_key = !settings.interface.show_key,
+            SettingToggle::ShowBadges => {
+                settings.interface.show_badges = !settings.interface.show_badges
+            }
+            SettingToggle::AnalyzeTracks => settings.analysis.enabled = !settings.analysis.enabled,
+        }
+        cx.notify();
+    });
+
+    save_settings(cx);
+    cx.refresh_windows();
+}
+
 fn hide_self(_: &HideSelf, cx: &mut App) {
     cx.hide();
 }
@@ -126,3 +466,29 @@ fn force_scan(_: &ForceScan, cx: &mut App) {
     let scanner = cx.global::<ScanInterface>();
     scanner.force_scan();
 }
+
+fn set_scale(cx: &mut App, scale: impl FnOnce(f32) -> f32) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+
+    settings.update(cx, |settings, cx| {
+        let new_scale = scale(settings.interface.scale());
+        // round to avoid floating point error building up after repeated zooming
+        settings.interface.scale = ((new_scale * 10.0).round() / 10.0).clamp(MIN_SCALE, MAX_SCALE);
+        cx.notify();
+    });
+
+    save_settings(cx);
+    cx.refresh_windows();
+}
+
+fn zoom_in(_: &ZoomIn, cx: &mut App) {
+    set_scale(cx, |scale| scale + ZOOM_STEP);
+}
+
+fn zoom_out(_: &ZoomOut, cx: &mut App) {
+    set_scale(cx, |scale| scale - ZOOM_STEP);
+}
+
+fn reset_zoom(_: &ResetZoom, cx: &mut App) {
+    set_scale(cx, |_| 1.0);
+}
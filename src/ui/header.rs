@@ -1,4 +1,4 @@
-mod lastfm;
+pub mod lastfm;
 
 use gpui::*;
 use prelude::FluentBuilder;
@@ -6,10 +6,13 @@ use prelude::FluentBuilder;
 use crate::{
     library::scan::ScanEvent,
     services::mmb::lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET},
-    ui::components::icons::{CROSS, FOLDER_CHECK, FOLDER_SEARCH, MAXIMIZE, MINUS, icon},
+    ui::components::{
+        icons::{CROSS, FOLDER_CHECK, FOLDER_SEARCH, MAXIMIZE, MINUS, icon},
+        tooltip::tooltip,
+    },
 };
 
-use super::{constants::APP_ROUNDING, models::Models, theme::Theme};
+use super::{constants::APP_ROUNDING, locale::t, models::Models, theme::Theme, util::spx};
 
 pub struct Header {
     scan_status: Entity<ScanStatus>,
@@ -40,8 +43,8 @@ impl Render for Header {
             .flex()
             .w_full()
             .text_sm()
-            .min_h(px(37.0))
-            .max_h(px(37.0))
+            .min_h(spx(37.0))
+            .max_h(spx(37.0))
             .bg(theme.background_secondary)
             .text_sm()
             .border_b_1()
@@ -71,13 +74,13 @@ impl Render for Header {
                     }),
             })
             .when(cfg!(target_os = "macos"), |this| {
-                this.child(div().w(px(72.0)))
+                this.child(div().w(spx(72.0)))
             })
             .child(
                 div()
-                    .pl(px(12.0))
-                    .pb(px(8.0))
-                    .pt(px(7.0))
+                    .pl(spx(12.0))
+                    .pb(spx(8.0))
+                    .pt(spx(7.0))
                     .flex()
                     .when(cfg!(not(target_os = "macos")), |this| {
                         this.child(
@@ -93,7 +96,7 @@ impl Render for Header {
                                     show_about.write(cx, true);
                                 })
                                 .child("Hummingbird")
-                                .mr(px(8.0)),
+                                .mr(spx(8.0)),
                         )
                     })
                     .child(self.scan_status.clone()),
@@ -140,30 +143,29 @@ impl Render for ScanStatus {
             .flex()
             .text_sm()
             .child(
-                div().mr(px(8.0)).pt(px(4.5)).h_full().child(
+                div().mr(spx(8.0)).pt(spx(4.5)).h_full().child(
                     icon(match status {
                         ScanEvent::ScanCompleteIdle | ScanEvent::ScanCompleteWatching => {
                             FOLDER_CHECK
                         }
                         _ => FOLDER_SEARCH,
                     })
-                    .size(px(14.0)),
+                    .size(spx(14.0)),
                 ),
             )
             .text_color(theme.text_secondary)
             .child(match status {
-                ScanEvent::ScanCompleteIdle => "".to_string(),
-                ScanEvent::ScanProgress { current, total } => {
-                    format!(
-                        "Scanning ({}%)",
-                        (*current as f64 / *total as f64 * 100.0).round()
-                    )
-                }
+                ScanEvent::ScanCompleteIdle => SharedString::default(),
+                ScanEvent::ScanProgress { current, total } => t!(
+                    cx,
+                    "scan.scanning",
+                    percent = (*current as f64 / *total as f64 * 100.0).round()
+                ),
                 ScanEvent::DiscoverProgress(progress) => {
-                    format!("Discovering files ({progress})")
+                    t!(cx, "scan.discovering", count = progress)
                 }
-                ScanEvent::Cleaning => "".to_string(),
-                ScanEvent::ScanCompleteWatching => "Watching for updates".to_string(),
+                ScanEvent::Cleaning => SharedString::default(),
+                ScanEvent::ScanCompleteWatching => t!(cx, "scan.watching"),
             })
     }
 }
@@ -195,9 +197,9 @@ impl RenderOnce for WindowButton {
 
         div()
             .flex()
-            .w(px(36.0))
-            .h(px(37.0))
-            .pb(px(1.0))
+            .w(spx(36.0))
+            .h(spx(37.0))
+            .pb(spx(1.0))
             .items_center()
             .justify_center()
             .cursor_pointer()
@@ -206,6 +208,11 @@ impl RenderOnce for WindowButton {
                 WindowButton::Minimize => "minimize",
                 WindowButton::Maximize => "maximize",
             })
+            .tooltip(tooltip(match self {
+                WindowButton::Close => t!(cx, "window.close"),
+                WindowButton::Minimize => t!(cx, "window.minimize"),
+                WindowButton::Maximize => t!(cx, "window.maximize"),
+            }))
             .bg(bg)
             .hover(|this| this.bg(hover))
             .active(|this| this.bg(active))
@@ -214,7 +221,7 @@ impl RenderOnce for WindowButton {
                 WindowButton::Minimize => WindowControlArea::Min,
                 WindowButton::Maximize => WindowControlArea::Max,
             })
-            .text_size(px(11.0))
+            .text_size(spx(11.0))
             .on_mouse_down(MouseButton::Left, |_, window, cx| {
                 cx.stop_propagation();
                 window.prevent_default();
@@ -225,9 +232,11 @@ impl RenderOnce for WindowButton {
                     WindowButton::Minimize => MINUS,
                     WindowButton::Maximize => MAXIMIZE,
                 })
-                .size(px(14.0)),
+                .size(spx(14.0)),
             )
-            .when(self == WindowButton::Close, |this| this.rounded_tr(px(4.0)))
+            .when(self == WindowButton::Close, |this| {
+                this.rounded_tr(spx(4.0))
+            })
             .on_click(move |_, window, cx| match self {
                 WindowButton::Close => cx.quit(),
                 WindowButton::Minimize => window.minimize_window(),
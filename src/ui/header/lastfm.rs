@@ -1,18 +1,29 @@
-use gpui::*;
-use tracing::error;
+use gpui::{prelude::FluentBuilder, *};
+use tracing::{error, info};
 
 use crate::{
-    services::mmb::lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET, client::LastFMClient},
+    library::playlist::LIKED_PLAYLIST_ID,
+    services::mmb::lastfm::{
+        LASTFM_API_KEY, LASTFM_API_SECRET, client::LastFMClient, sync::sync_loved_tracks,
+        types::Session,
+    },
     ui::{
-        components::icons::{LAST_FM, icon},
-        models::{LastFMState, Models},
+        app::{ActiveLibrary, Pool},
+        components::{
+            icons::{LAST_FM, icon},
+            tooltip::tooltip,
+        },
+        locale::t,
+        models::{LastFMState, Models, PlaylistEvent},
         theme::Theme,
+        util::spx,
     },
 };
 
 pub struct LastFM {
     state: Entity<LastFMState>,
     name: Option<SharedString>,
+    syncing: bool,
 }
 
 impl LastFM {
@@ -35,6 +46,7 @@ impl LastFM {
                     _ => None,
                 },
                 state,
+                syncing: false,
             }
         })
     }
@@ -44,17 +56,21 @@ impl Render for LastFM {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = cx.global::<Theme>();
         let state = self.state.clone();
+        let connected = matches!(self.state.read(cx), LastFMState::Connected(_));
 
         div()
             .flex()
             .text_sm()
-            .px(px(12.0))
-            .pb(px(8.0))
-            .pt(px(7.0))
+            .px(spx(12.0))
+            .pb(spx(8.0))
+            .pt(spx(7.0))
             .cursor_pointer()
             .text_color(theme.text_secondary)
             .bg(theme.window_button)
             .id("lastfm-button")
+            .when(connected, |this| {
+                this.tooltip(tooltip(t!(cx, "lastfm.sync_loved")))
+            })
             .hover(|this| this.bg(theme.window_button_hover))
             .active(|this| this.bg(theme.window_button_active))
             .on_mouse_down(MouseButton::Left, |_, window, cx| {
@@ -63,43 +79,101 @@ impl Render for LastFM {
             })
             .child(
                 div()
-                    .mr(px(8.0))
-                    .pt(px(5.5))
-                    .text_size(px(11.0))
+                    .mr(spx(8.0))
+                    .pt(spx(5.5))
+                    .text_size(spx(11.0))
                     .h_full()
                     .child(
                         icon(LAST_FM)
-                            .size(px(14.0))
+                            .size(spx(14.0))
                             .text_color(theme.text_secondary),
                     ),
             )
             .child(
                 div().child(match self.state.read(cx) {
-                    LastFMState::Disconnected => "Sign in".into_any_element(),
-                    LastFMState::AwaitingFinalization(_) => {
-                        "Click to confirm sign in".into_any_element()
-                    }
+                    LastFMState::Disconnected => t!(cx, "lastfm.sign_in"),
+                    LastFMState::AwaitingFinalization(_) => t!(cx, "lastfm.confirm_sign_in"),
+                    LastFMState::Connected(_) if self.syncing => t!(cx, "lastfm.syncing"),
                     LastFMState::Connected(_) => self
                         .name
                         .clone()
-                        .unwrap_or(SharedString::new_static("Connected"))
-                        .into_any_element(),
+                        .unwrap_or_else(|| t!(cx, "lastfm.connected")),
                 }),
             )
-            .on_click(move |_, _, cx| {
+            .on_click(cx.listener(move |this, _, _, cx| {
                 let state = state.clone();
                 let read = state.read(cx).clone();
 
                 match read {
                     LastFMState::Disconnected => get_token(cx, state),
                     LastFMState::AwaitingFinalization(token) => confirm(cx, state, token),
+                    LastFMState::Connected(session) if !this.syncing => {
+                        this.sync_loved(cx, session)
+                    }
                     LastFMState::Connected(_) => (),
                 }
+            }))
+    }
+}
+
+impl LastFM {
+    /// Syncs the liked tracks playlist with the loved tracks of the connected account.
+    fn sync_loved(&mut self, cx: &mut Context<Self>, session: Session) {
+        let (Some(key), Some(secret)) = (LASTFM_API_KEY, LASTFM_API_SECRET) else {
+            return;
+        };
+
+        let read_pool = cx.global::<Pool>().read.clone();
+        let write_pool = cx.global::<Pool>().write.clone();
+        let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+        let record_path = cx
+            .global::<ActiveLibrary>()
+            .directory
+            .join("lastfm_loved.json");
+
+        self.syncing = true;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move {
+                    let mut client = LastFMClient::new(key.to_string(), secret.to_string());
+                    client.set_session(session.key);
+
+                    sync_loved_tracks(
+                        &mut client,
+                        &session.name,
+                        &read_pool,
+                        &write_pool,
+                        &record_path,
+                    )
+                    .await
+                })
+                .await;
+
+            match result {
+                Ok(Ok(summary)) => info!(?summary, "Synced loved tracks with last.fm"),
+                Ok(Err(err)) => error!(?err, "Could not sync loved tracks: {err}"),
+                Err(err) => error!(?err, "Could not sync loved tracks: {err}"),
+            }
+
+            playlist_tracker
+                .update(cx, |_, cx| {
+                    cx.emit(PlaylistEvent::PlaylistUpdated(LIKED_PLAYLIST_ID))
+                })
+                .ok();
+
+            this.update(cx, |this, cx| {
+                this.syncing = false;
+                cx.notify();
             })
+            .ok();
+        })
+        .detach();
     }
 }
 
-fn get_token(cx: &mut App, state: Entity<LastFMState>) {
+pub fn get_token(cx: &mut App, state: Entity<LastFMState>) {
     cx.spawn(async move |cx| {
         let mut client = LastFMClient::new(
             LASTFM_API_KEY.unwrap().to_string(),
@@ -131,7 +205,7 @@ fn get_token(cx: &mut App, state: Entity<LastFMState>) {
     .detach();
 }
 
-fn confirm(cx: &mut App, state: Entity<LastFMState>, token: String) {
+pub fn confirm(cx: &mut App, state: Entity<LastFMState>, token: String) {
     cx.spawn(async move |cx| {
         let mut client = LastFMClient::new(
             LASTFM_API_KEY.unwrap().to_string(),
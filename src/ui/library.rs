@@ -1,81 +1,254 @@
 use std::collections::VecDeque;
 
 use album_view::AlbumView;
-use gpui::*;
+use artist_view::ArtistView;
+use folder_view::FolderView;
+use gpui::{prelude::FluentBuilder, *};
 use navigation::NavigationView;
 use release_view::ReleaseView;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
-use crate::ui::{
-    command_palette::{Command, CommandManager},
-    library::{
-        playlist_view::{Import, PlaylistView},
-        sidebar::Sidebar,
-        update_playlist::UpdatePlaylist,
+use crate::{
+    library::db::{AlbumMethod, LibraryAccess},
+    ui::{
+        command_palette::{Command, CommandManager},
+        library::{
+            audiobooks::AudiobooksView,
+            label_view::{LabelView, LabelsView},
+            play_history::PlayHistoryView,
+            playlist_view::{Import, PlaylistView},
+            podcasts::{PodcastView, PodcastsView},
+            quarantine::{Quarantine, ShowQuarantine},
+            recap_view::RecapView,
+            recently_deleted::RecentlyDeletedView,
+            sidebar::Sidebar,
+            tag_view::TagView,
+            update_playlist::UpdatePlaylist,
+            year_view::{ReleasedView, YearsView},
+        },
+        locale::t,
+        models::PlaylistEvent,
+        util::spx,
     },
 };
 
 use super::models::Models;
 
 mod add_to_playlist;
+mod album_art;
 mod album_view;
+mod artist_view;
+mod audiobooks;
+mod convert;
+mod folder_view;
+mod guess_tags;
+mod label_view;
+mod merge_albums;
+mod merge_artists;
+mod musicbrainz_info;
 mod navigation;
+mod offline;
+mod play_history;
+mod playlist_bundle;
 mod playlist_view;
+mod podcasts;
+mod quarantine;
+mod recap_view;
+mod recently_deleted;
 mod release_view;
+mod resume_sync;
 mod sidebar;
+mod similar_artists;
+mod split_album;
+mod tag_view;
 mod track_listing;
+mod track_properties;
+mod transfer;
 mod update_playlist;
+mod year_view;
+
+pub(crate) use audiobooks::track_audiobooks;
+pub(crate) use podcasts::track_podcasts;
+pub(crate) use release_view::album_queue_items;
+pub(crate) use resume_sync::{resume_from_other_device, track_resume_sync};
+pub(crate) use tag_view::{tags_changed, track_tags};
+pub(crate) use transfer::{export_data, import_data};
+pub(crate) use year_view::decades;
 
 pub fn bind_actions(cx: &mut App) {
+    offline::bind_actions(cx);
     playlist_view::bind_actions(cx);
+    release_view::bind_actions(cx);
 }
 
 #[derive(Clone)]
 enum LibraryView {
     Album(Entity<AlbumView>),
     Release(Entity<ReleaseView>),
+    Artist(Entity<ArtistView>),
     Playlist(Entity<PlaylistView>),
+    Folders(Entity<FolderView>),
+    Years(Entity<YearsView>),
+    Released(Entity<ReleasedView>),
+    Labels(Entity<LabelsView>),
+    Label(Entity<LabelView>),
+    RecentlyDeleted(Entity<RecentlyDeletedView>),
+    Podcasts(Entity<PodcastsView>),
+    Podcast(Entity<PodcastView>),
+    Audiobooks(Entity<AudiobooksView>),
+    Tag(Entity<TagView>),
+    History(Entity<PlayHistoryView>),
+    Recap(Entity<RecapView>),
 }
 
 pub struct Library {
     view: LibraryView,
+    /// The album view is kept around for the lifetime of the library so that its scroll position
+    /// is preserved when navigating back to it.
+    album_view: Entity<AlbumView>,
+    /// Kept around for the same reason, so that going back to the folder view returns to the
+    /// folder that was open.
+    folder_view: Entity<FolderView>,
+    /// The views that were navigated back from, most recent last. This is cleared when navigating
+    /// anywhere other than forward.
+    forward: Vec<ViewSwitchMessage>,
     navigation_view: Entity<NavigationView>,
     sidebar: Entity<Sidebar>,
+    show_sidebar: Entity<bool>,
     show_update_playlist: Entity<bool>,
     update_playlist: Entity<UpdatePlaylist>,
+    show_quarantine: Entity<bool>,
+    quarantine: Entity<Quarantine>,
     focus_handle: FocusHandle,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ViewSwitchMessage {
     Albums,
     Release(i64),
+    Artist(i64),
     Playlist(i64),
+    Folders,
+    Years,
+    /// The albums released in the decade starting with the year.
+    Decade(i64),
+    Year(i64),
+    Labels,
+    /// The albums released on the same label as the album.
+    Label(i64),
+    RecentlyDeleted,
+    Podcasts,
+    Podcast(i64),
+    Audiobooks,
+    /// The tracks tagged with a mood or activity.
+    Tag(i64),
+    History,
+    /// A summary of the listening done during the year.
+    Recap(i64),
     Back,
+    Forward,
     Refresh,
 }
 
 impl EventEmitter<ViewSwitchMessage> for VecDeque<ViewSwitchMessage> {}
 
+/// Whether the item shown by the view still exists. The last visited view is restored when the app
+/// is started, and what it showed could have been removed since then.
+fn view_exists(message: &ViewSwitchMessage, cx: &mut App) -> bool {
+    match message {
+        ViewSwitchMessage::Release(id) => cx.get_album_by_id(*id, AlbumMethod::Metadata).is_ok(),
+        ViewSwitchMessage::Artist(id) => cx.get_artist_by_id(*id).is_ok(),
+        ViewSwitchMessage::Playlist(id) => cx.get_playlist(*id).is_ok(),
+        ViewSwitchMessage::Label(id) => cx.get_album_by_id(*id, AlbumMethod::Metadata).is_ok(),
+        ViewSwitchMessage::Podcast(id) => cx.get_podcast(*id).is_ok(),
+        ViewSwitchMessage::Tag(id) => cx.get_tag(*id).is_ok(),
+        _ => true,
+    }
+}
+
+/// Shows a view after what the library holds was changed from it, such as by merging albums. The
+/// view of anything that no longer exists is removed from the navigation history, and views that
+/// list albums are reloaded.
+pub(super) fn library_changed(
+    cx: &mut App,
+    removed: Option<ViewSwitchMessage>,
+    shown: ViewSwitchMessage,
+) {
+    let switcher_model = cx.global::<Models>().switcher_model.clone();
+
+    switcher_model.update(cx, |v, cx| {
+        v.retain(|message| Some(*message) != removed);
+
+        // the view is shown again with what changed, rather than going back to it
+        if v.back() == Some(&shown) {
+            v.pop_back();
+        }
+
+        cx.emit(shown);
+        cx.notify();
+    });
+
+    let scan_state = cx.global::<Models>().scan_state.clone();
+    scan_state.update(cx, |_, cx| cx.notify());
+}
+
 fn make_view(
     message: &ViewSwitchMessage,
     cx: &mut App,
-    model: Entity<VecDeque<ViewSwitchMessage>>,
+    album_view: &Entity<AlbumView>,
+    folder_view: &Entity<FolderView>,
 ) -> LibraryView {
     match message {
-        ViewSwitchMessage::Albums => LibraryView::Album(AlbumView::new(cx, model.clone())),
+        ViewSwitchMessage::Albums => LibraryView::Album(album_view.clone()),
+        ViewSwitchMessage::Folders => LibraryView::Folders(folder_view.clone()),
+        ViewSwitchMessage::Years => LibraryView::Years(YearsView::new(cx)),
+        ViewSwitchMessage::Decade(decade) => {
+            let title = t!(cx, "years.decade", decade = decade);
+            LibraryView::Released(ReleasedView::new(cx, title, *decade, decade + 9))
+        }
+        ViewSwitchMessage::Labels => LibraryView::Labels(LabelsView::new(cx)),
+        ViewSwitchMessage::Label(id) => LibraryView::Label(LabelView::new(cx, *id)),
+        ViewSwitchMessage::RecentlyDeleted => {
+            LibraryView::RecentlyDeleted(RecentlyDeletedView::new(cx))
+        }
+        ViewSwitchMessage::Podcasts => LibraryView::Podcasts(PodcastsView::new(cx)),
+        ViewSwitchMessage::Podcast(id) => LibraryView::Podcast(PodcastView::new(cx, *id)),
+        ViewSwitchMessage::Audiobooks => LibraryView::Audiobooks(AudiobooksView::new(cx)),
+        ViewSwitchMessage::Tag(id) => LibraryView::Tag(TagView::new(cx, *id)),
+        ViewSwitchMessage::History => LibraryView::History(PlayHistoryView::new(cx)),
+        ViewSwitchMessage::Recap(year) => LibraryView::Recap(RecapView::new(cx, *year)),
+        ViewSwitchMessage::Year(year) => {
+            let title = SharedString::from(year.to_string());
+            LibraryView::Released(ReleasedView::new(cx, title, *year, *year))
+        }
         ViewSwitchMessage::Release(id) => LibraryView::Release(ReleaseView::new(cx, *id)),
+        ViewSwitchMessage::Artist(id) => LibraryView::Artist(ArtistView::new(cx, *id)),
         ViewSwitchMessage::Playlist(id) => LibraryView::Playlist(PlaylistView::new(cx, *id)),
         ViewSwitchMessage::Back => panic!("improper use of make_view (cannot make Back)"),
+        ViewSwitchMessage::Forward => panic!("improper use of make_view (cannot make Forward)"),
         ViewSwitchMessage::Refresh => panic!("improper use of make_view (cannot make Refresh)"),
     }
 }
 
 impl Library {
-    pub fn new(cx: &mut App) -> Entity<Self> {
+    pub fn new(cx: &mut App, show_sidebar: Entity<bool>) -> Entity<Self> {
         cx.new(|cx| {
+            cx.observe(&show_sidebar, |_, _, cx| cx.notify()).detach();
+
             let switcher_model = cx.global::<Models>().switcher_model.clone();
-            let view = LibraryView::Album(AlbumView::new(cx, switcher_model.clone()));
+            let album_view = AlbumView::new(cx, switcher_model.clone());
+            let folder_view = FolderView::new(cx);
+
+            let current = *switcher_model.read(cx).back().unwrap();
+            let view = if view_exists(&current, cx) {
+                make_view(&current, cx, &album_view, &folder_view)
+            } else {
+                switcher_model.update(cx, |v, _| {
+                    v.retain(|message| *message == ViewSwitchMessage::Albums);
+                });
+                LibraryView::Album(album_view.clone())
+            };
 
             cx.subscribe(
                 &switcher_model,
@@ -84,7 +257,7 @@ impl Library {
                         ViewSwitchMessage::Back => {
                             let last = m.update(cx, |v: &mut VecDeque<ViewSwitchMessage>, cx| {
                                 if v.len() > 1 {
-                                    v.pop_back();
+                                    this.forward.extend(v.pop_back());
                                     cx.notify();
 
                                     v.back().cloned()
@@ -95,7 +268,19 @@ impl Library {
 
                             if let Some(message) = last {
                                 debug!("{:?}", message);
-                                make_view(&message, cx, m)
+                                make_view(&message, cx, &this.album_view, &this.folder_view)
+                            } else {
+                                this.view.clone()
+                            }
+                        }
+                        ViewSwitchMessage::Forward => {
+                            if let Some(message) = this.forward.pop() {
+                                m.update(cx, |v, cx| {
+                                    v.push_back(message);
+                                    cx.notify();
+                                });
+
+                                make_view(&message, cx, &this.album_view, &this.folder_view)
                             } else {
                                 this.view.clone()
                             }
@@ -103,9 +288,11 @@ impl Library {
                         ViewSwitchMessage::Refresh => {
                             let last = *m.read(cx).iter().last().unwrap();
 
-                            make_view(&last, cx, m)
+                            make_view(&last, cx, &this.album_view, &this.folder_view)
                         }
                         _ => {
+                            this.forward.clear();
+
                             m.update(cx, |v, cx| {
                                 if v.len() > 99 {
                                     v.pop_front();
@@ -115,7 +302,7 @@ impl Library {
                                 cx.notify();
                             });
 
-                            make_view(message, cx, m)
+                            make_view(message, cx, &this.album_view, &this.folder_view)
                         }
                     };
 
@@ -124,31 +311,64 @@ impl Library {
             )
             .detach();
 
+            let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+
+            cx.subscribe(&playlist_tracker, |this: &mut Library, _, ev, _| {
+                if let PlaylistEvent::PlaylistDeleted(id) = ev {
+                    this.forward
+                        .retain(|v| *v != ViewSwitchMessage::Playlist(*id));
+                }
+            })
+            .detach();
+
             let focus_handle = cx.focus_handle();
+            let category = t!(cx, "command.category.playlist");
+            let name = t!(cx, "command.import_playlist");
 
             cx.register_command(
                 ("playlist::import", 0),
+                Command::new(Some(category), name, Import, Some(focus_handle.clone())),
+            );
+
+            let category = t!(cx, "command.category.scan");
+            let name = t!(cx, "command.show_quarantine");
+
+            cx.register_command(
+                ("library::quarantine", 0),
                 Command::new(
-                    Some("Playlist"),
-                    "Import M3U Playlist",
-                    Import,
+                    Some(category),
+                    name,
+                    ShowQuarantine,
                     Some(focus_handle.clone()),
                 ),
             );
 
             cx.on_release(move |_, cx| {
                 cx.unregister_command(("playlist::import", 0));
+                cx.unregister_command(("library::quarantine", 0));
             })
             .detach();
 
+            let show_quarantine = cx.new(|_| false);
+
             let show_update_playlist = cx.new(|_| false);
 
             Library {
-                navigation_view: NavigationView::new(cx, switcher_model.clone()),
-                sidebar: Sidebar::new(cx, switcher_model.clone()),
+                navigation_view: NavigationView::new(
+                    cx,
+                    switcher_model.clone(),
+                    show_sidebar.clone(),
+                ),
+                sidebar: Sidebar::new(cx, switcher_model.clone(), show_sidebar.clone()),
+                show_sidebar,
                 view,
+                forward: Vec::new(),
+                album_view,
+                folder_view,
                 update_playlist: UpdatePlaylist::new(cx, show_update_playlist.clone()),
                 show_update_playlist,
+                quarantine: Quarantine::new(cx, show_quarantine.clone()),
+                show_quarantine,
                 focus_handle,
             }
         })
@@ -156,8 +376,12 @@ impl Library {
 }
 
 impl Render for Library {
-    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let show_update_playlist = self.show_update_playlist.clone();
+        let show_quarantine = self.show_quarantine.clone();
+        let show_sidebar = *self.show_sidebar.read(cx);
+        let switcher_model = cx.global::<Models>().switcher_model.clone();
+        let switcher_model_2 = switcher_model.clone();
 
         div()
             .id("library")
@@ -168,6 +392,22 @@ impl Render for Library {
                     cx.notify();
                 })
             })
+            .on_action(move |_: &ShowQuarantine, _, cx| {
+                show_quarantine.write(cx, true);
+            })
+            // the back and forward buttons found on some mice
+            .on_mouse_down(
+                MouseButton::Navigate(NavigationDirection::Back),
+                move |_, _, cx| {
+                    switcher_model.update(cx, |_, cx| cx.emit(ViewSwitchMessage::Back));
+                },
+            )
+            .on_mouse_down(
+                MouseButton::Navigate(NavigationDirection::Forward),
+                move |_, _, cx| {
+                    switcher_model_2.update(cx, |_, cx| cx.emit(ViewSwitchMessage::Forward));
+                },
+            )
             .w_full()
             .h_full()
             .flex()
@@ -175,17 +415,19 @@ impl Render for Library {
             .max_w_full()
             .max_h_full()
             .overflow_hidden()
-            .child(
-                div()
-                    .mr_auto()
-                    .flex()
-                    .flex_shrink_0()
-                    .child(self.sidebar.clone()),
-            )
+            .when(show_sidebar, |this| {
+                this.child(
+                    div()
+                        .mr_auto()
+                        .flex()
+                        .flex_shrink_0()
+                        .child(self.sidebar.clone()),
+                )
+            })
             .child(
                 div()
                     .w_full()
-                    .max_w(px(1000.0))
+                    .max_w(spx(1000.0))
                     .h_full()
                     .flex()
                     .flex_col()
@@ -198,11 +440,27 @@ impl Render for Library {
                         LibraryView::Release(release_view) => {
                             release_view.clone().into_any_element()
                         }
+                        LibraryView::Artist(artist_view) => artist_view.clone().into_any_element(),
                         LibraryView::Playlist(playlist_view) => {
                             playlist_view.clone().into_any_element()
                         }
+                        LibraryView::Folders(folder_view) => folder_view.clone().into_any_element(),
+                        LibraryView::Years(years_view) => years_view.clone().into_any_element(),
+                        LibraryView::Released(released_view) => {
+                            released_view.clone().into_any_element()
+                        }
+                        LibraryView::Labels(labels_view) => labels_view.clone().into_any_element(),
+                        LibraryView::Label(label_view) => label_view.clone().into_any_element(),
+                        LibraryView::RecentlyDeleted(view) => view.clone().into_any_element(),
+                        LibraryView::Podcasts(view) => view.clone().into_any_element(),
+                        LibraryView::Podcast(view) => view.clone().into_any_element(),
+                        LibraryView::Audiobooks(view) => view.clone().into_any_element(),
+                        LibraryView::Tag(view) => view.clone().into_any_element(),
+                        LibraryView::History(view) => view.clone().into_any_element(),
+                        LibraryView::Recap(view) => view.clone().into_any_element(),
                     }),
             )
             .child(self.update_playlist.clone())
+            .child(self.quarantine.clone())
     }
 }
@@ -1,37 +1,38 @@
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
 use gpui::{
-    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
-    Window, div, px,
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    Styled, Window, div, prelude::FluentBuilder,
 };
 use nucleo::Utf32String;
+use tracing::error;
 
 use crate::{
     library::{db::LibraryAccess, types::PlaylistWithCount},
     ui::{
         components::{
+            button::{ButtonIntent, button},
             icons::PLAYLIST_ADD,
             modal::modal,
             palette::{ExtraItem, ExtraItemProvider, FinderItemLeft, Palette, PaletteItem},
         },
+        locale::{Locale, fill, t},
         models::{Models, PlaylistEvent},
+        theme::Theme,
+        util::spx,
     },
 };
 
-// i64 here is the track ID
-impl PaletteItem for (i64, PlaylistWithCount) {
+/// A playlist, paired with the IDs of the tracks being added to it.
+type PlaylistTarget = (Arc<Vec<i64>>, PlaylistWithCount);
+
+impl PaletteItem for PlaylistTarget {
     fn left_content(&self, cx: &mut App) -> Option<FinderItemLeft> {
         self.1.left_content(cx)
     }
 
     fn middle_content(&self, cx: &mut App) -> SharedString {
-        let has_track = cx.playlist_has_track(self.1.id, self.0).ok().flatten();
-
-        if has_track.is_none() {
-            format!("Add to {}", self.1.name).into()
-        } else {
-            format!("Remove from {}", self.1.name).into()
-        }
+        t!(cx, "playlist.add_to", name = self.1.name)
     }
 
     fn right_content(&self, cx: &mut App) -> Option<SharedString> {
@@ -39,23 +40,84 @@ impl PaletteItem for (i64, PlaylistWithCount) {
     }
 }
 
-type MatcherFunc = Box<dyn Fn(&Arc<(i64, PlaylistWithCount)>, &mut App) -> Utf32String + 'static>;
-type OnAccept = Box<dyn Fn(&Arc<(i64, PlaylistWithCount)>, &mut App) + 'static>;
+type MatcherFunc = Box<dyn Fn(&Arc<PlaylistTarget>, &mut App) -> Utf32String + 'static>;
+type OnAccept = Box<dyn Fn(&Arc<PlaylistTarget>, &mut App) + 'static>;
+
+/// A track that is already in the playlist it's being added to, waiting on the user to decide
+/// whether it should be added again.
+struct Duplicate {
+    playlist_id: i64,
+    playlist_name: SharedString,
+    track_title: SharedString,
+    /// The duplicate track, followed by the tracks that haven't been added yet.
+    tracks: VecDeque<i64>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DuplicateChoice {
+    AddAnyway,
+    Skip,
+    SkipAll,
+}
+
+/// Adds the tracks to the playlist in order. Unless `skip_duplicates` is set, this stops at the
+/// first track that is already in the playlist and returns it.
+fn add_tracks(
+    cx: &mut App,
+    playlist_id: i64,
+    playlist_name: SharedString,
+    mut tracks: VecDeque<i64>,
+    skip_duplicates: bool,
+) -> Option<Duplicate> {
+    while let Some(&track_id) = tracks.front() {
+        match cx.playlist_has_track(playlist_id, track_id) {
+            Ok(Some(_)) if skip_duplicates => (),
+            Ok(Some(_)) => {
+                let track_title = cx
+                    .get_track_by_id(track_id)
+                    .map(|track| track.title.clone().into())
+                    .unwrap_or_default();
+
+                return Some(Duplicate {
+                    playlist_id,
+                    playlist_name,
+                    track_title,
+                    tracks,
+                });
+            }
+            Ok(None) => {
+                if let Err(err) = cx.add_playlist_item(playlist_id, track_id) {
+                    error!("Failed to add track to playlist: {err}");
+                }
+            }
+            Err(err) => error!("Failed to check the playlist for the track: {err}"),
+        }
+
+        tracks.pop_front();
+    }
+
+    None
+}
 
 pub struct AddToPlaylist {
     show: Entity<bool>,
-    palette: Entity<Palette<(i64, PlaylistWithCount), MatcherFunc, OnAccept>>,
+    palette: Entity<Palette<PlaylistTarget, MatcherFunc, OnAccept>>,
+    duplicate: Option<Duplicate>,
 }
 
 impl AddToPlaylist {
-    pub fn new(cx: &mut App, show: Entity<bool>, track_id: i64) -> Entity<Self> {
+    pub fn new(cx: &mut App, show: Entity<bool>, track_ids: Vec<i64>) -> Entity<Self> {
+        let track_ids = Arc::new(track_ids);
+
         cx.new(|cx| {
+            let track_ids_clone = track_ids.clone();
+
             cx.observe(&show, move |this: &mut Self, _, cx| {
                 this.palette.update(cx, |this, cx| {
                     let new_playlists = (*cx.get_all_playlists().unwrap())
                         .clone()
                         .into_iter()
-                        .map(|playlist| (track_id, playlist))
+                        .map(|playlist| (track_ids_clone.clone(), playlist))
                         .map(Arc::new)
                         .collect::<Vec<_>>();
 
@@ -70,39 +132,29 @@ impl AddToPlaylist {
 
             let matcher: MatcherFunc = Box::new(|playlist, _| playlist.1.name.0.to_string().into());
 
-            let show_clone = show.clone();
+            let this = cx.weak_entity();
 
             let on_accept: OnAccept = Box::new(move |playlist, cx| {
-                let has_track = cx
-                    .playlist_has_track(playlist.1.id, track_id)
-                    .ok()
-                    .flatten();
-
-                if let Some(id) = has_track {
-                    cx.remove_playlist_item(id).unwrap();
-                } else {
-                    cx.add_playlist_item(playlist.1.id, track_id).unwrap();
-                }
+                let tracks = playlist.0.iter().copied().collect();
+                let name = playlist.1.name.0.clone();
 
-                let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
-
-                playlist_tracker.update(cx, |_, cx| {
-                    cx.emit(PlaylistEvent::PlaylistUpdated(playlist.1.id));
-                });
-
-                show_clone.write(cx, false);
+                this.update(cx, |this, cx| {
+                    this.add(playlist.1.id, name, tracks, false, cx)
+                })
+                .ok();
             });
 
             let items = (*cx.get_all_playlists().unwrap())
                 .clone()
                 .into_iter()
-                .map(|playlist| (track_id, playlist))
+                .map(|playlist| (track_ids.clone(), playlist))
                 .map(Arc::new)
                 .collect();
 
             let palette = Palette::new(cx, items, matcher, on_accept);
 
-            let show_for_create = show.clone();
+            let this_for_create = cx.weak_entity();
+            let create_message = cx.global::<Locale>().message("playlist.create").to_string();
             let provider: ExtraItemProvider = Arc::new(move |query: &str| {
                 let name = query.trim();
                 if name.is_empty() {
@@ -110,24 +162,24 @@ impl AddToPlaylist {
                 }
 
                 let name_string = name.to_string();
-                let display = format!("Create new playlist '{}'", name_string);
+                let display = fill(&create_message, &[("name", &name_string)]);
 
-                let show_clone2 = show_for_create.clone();
+                let this = this_for_create.clone();
+                let track_ids = track_ids.clone();
 
                 vec![ExtraItem {
                     left: Some(FinderItemLeft::Icon(PLAYLIST_ADD.into())),
-                    middle: display.into(),
+                    middle: display,
                     right: None,
                     on_accept: Arc::new(move |cx| {
                         let playlist_id = cx.create_playlist(&name_string).unwrap();
-                        cx.add_playlist_item(playlist_id, track_id).unwrap();
+                        let tracks = track_ids.iter().copied().collect();
+                        let name = SharedString::from(name_string.clone());
 
-                        let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
-                        playlist_tracker.update(cx, |_, cx| {
-                            cx.emit(PlaylistEvent::PlaylistUpdated(playlist_id));
-                        });
-
-                        show_clone2.write(cx, false);
+                        this.update(cx, |this, cx| {
+                            this.add(playlist_id, name, tracks, false, cx)
+                        })
+                        .ok();
                     }),
                 }]
             });
@@ -136,13 +188,133 @@ impl AddToPlaylist {
                 palette.register_extra_provider(provider.clone(), cx);
             });
 
-            Self { show, palette }
+            Self {
+                show,
+                palette,
+                duplicate: None,
+            }
         })
     }
+
+    fn add(
+        &mut self,
+        playlist_id: i64,
+        playlist_name: SharedString,
+        tracks: VecDeque<i64>,
+        skip_duplicates: bool,
+        cx: &mut Context<Self>,
+    ) {
+        self.duplicate = add_tracks(cx, playlist_id, playlist_name, tracks, skip_duplicates);
+        self.show.write(cx, false);
+
+        let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+        playlist_tracker.update(cx, |_, cx| {
+            cx.emit(PlaylistEvent::PlaylistUpdated(playlist_id));
+        });
+
+        cx.notify();
+    }
+
+    fn resolve_duplicate(&mut self, choice: DuplicateChoice, cx: &mut Context<Self>) {
+        let Some(mut duplicate) = self.duplicate.take() else {
+            return;
+        };
+
+        if let Some(track_id) = duplicate.tracks.pop_front()
+            && choice == DuplicateChoice::AddAnyway
+            && let Err(err) = cx.add_playlist_item(duplicate.playlist_id, track_id)
+        {
+            error!("Failed to add track to playlist: {err}");
+        }
+
+        let playlist_id = duplicate.playlist_id;
+        self.add(
+            playlist_id,
+            duplicate.playlist_name,
+            duplicate.tracks,
+            choice == DuplicateChoice::SkipAll,
+            cx,
+        );
+    }
 }
 
 impl Render for AddToPlaylist {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if let Some(duplicate) = &self.duplicate {
+            let theme = cx.global::<Theme>();
+            let title = t!(
+                cx,
+                "playlist.duplicate.title",
+                name = duplicate.playlist_name
+            );
+            let message = t!(
+                cx,
+                "playlist.duplicate.message",
+                title = duplicate.track_title
+            );
+            let more_remaining = duplicate.tracks.len() > 1;
+            let this = cx.weak_entity();
+
+            return modal()
+                .child(
+                    div()
+                        .w(spx(420.0))
+                        .p(spx(16.0))
+                        .flex()
+                        .flex_col()
+                        .gap(spx(6.0))
+                        .text_sm()
+                        .child(
+                            div()
+                                .font_weight(FontWeight::BOLD)
+                                .text_size(spx(18.0))
+                                .child(title),
+                        )
+                        .child(div().text_color(theme.text_secondary).child(message))
+                        .child(
+                            div()
+                                .flex()
+                                .justify_end()
+                                .gap(spx(6.0))
+                                .pt(spx(10.0))
+                                .when(more_remaining, |this| {
+                                    this.child(
+                                        button()
+                                            .id("duplicate-skip-all")
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.resolve_duplicate(DuplicateChoice::SkipAll, cx)
+                                            }))
+                                            .child(t!(cx, "playlist.duplicate.skip_all")),
+                                    )
+                                })
+                                .child(
+                                    button()
+                                        .id("duplicate-skip")
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.resolve_duplicate(DuplicateChoice::Skip, cx)
+                                        }))
+                                        .child(t!(cx, "playlist.duplicate.skip")),
+                                )
+                                .child(
+                                    button()
+                                        .id("duplicate-add-anyway")
+                                        .intent(ButtonIntent::Primary)
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.resolve_duplicate(DuplicateChoice::AddAnyway, cx)
+                                        }))
+                                        .child(t!(cx, "playlist.duplicate.add_anyway")),
+                                ),
+                        ),
+                )
+                .on_exit(move |_, cx| {
+                    this.update(cx, |this, cx| {
+                        this.resolve_duplicate(DuplicateChoice::Skip, cx)
+                    })
+                    .ok();
+                })
+                .into_any_element();
+        }
+
         let show = self.show.clone();
         let palette = self.palette.clone();
         let show_read = *self.show.read(cx);
@@ -153,7 +325,7 @@ impl Render for AddToPlaylist {
             });
 
             modal()
-                .child(div().w(px(550.0)).h(px(300.0)).child(palette.clone()))
+                .child(div().w(spx(550.0)).h(spx(300.0)).child(palette.clone()))
                 .on_exit(move |_, cx| {
                     show.update(cx, |show, cx| {
                         *show = false;
@@ -0,0 +1,321 @@
+use std::{fs::File, io::Cursor, path::PathBuf, sync::Arc};
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, ImageSource, InteractiveElement, IntoElement,
+    ObjectFit, ParentElement, Render, RenderImage, SharedString, StatefulInteractiveElement,
+    Styled, StyledImage, Window, div, img, prelude::FluentBuilder,
+};
+use image::{Frame, ImageReader, imageops::thumbnail};
+use smallvec::smallvec;
+use tracing::{error, warn};
+
+use crate::{
+    library::{art::set_album_art, db::LibraryAccess},
+    media::{
+        builtin::symphonia::SymphoniaProvider,
+        metadata::{EmbeddedImage, ImageKind, cue_track_file},
+        traits::MediaProvider,
+    },
+    ui::{
+        app::{ActiveLibrary, Pool},
+        components::{button::button, modal::modal},
+        library::{ViewSwitchMessage, library_changed},
+        locale::t,
+        theme::Theme,
+        util::{drop_image_from_app, spx},
+    },
+    util::rgb_to_bgr,
+};
+
+/// An image embedded in the album's files, which can be made the album's art.
+struct ArtChoice {
+    kind: ImageKind,
+    data: Arc<[u8]>,
+    preview: Arc<RenderImage>,
+}
+
+enum ChoicesState {
+    Loading,
+    Loaded(Vec<ArtChoice>),
+}
+
+fn kind_label(cx: &App, kind: ImageKind) -> SharedString {
+    match kind {
+        ImageKind::FrontCover => t!(cx, "art.front_cover"),
+        ImageKind::BackCover => t!(cx, "art.back_cover"),
+        ImageKind::Leaflet => t!(cx, "art.leaflet"),
+        ImageKind::Media => t!(cx, "art.media"),
+        ImageKind::Artist => t!(cx, "art.artist"),
+        ImageKind::Other => t!(cx, "art.other"),
+    }
+}
+
+fn read_images(
+    provider: &mut SymphoniaProvider,
+    file: &PathBuf,
+) -> anyhow::Result<Vec<EmbeddedImage>> {
+    provider.open(File::open(file)?, None)?;
+    let images = provider.read_images()?;
+    provider.close()?;
+
+    Ok(images)
+}
+
+fn preview(data: &[u8]) -> anyhow::Result<Arc<RenderImage>> {
+    let image = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .decode()?
+        .into_rgba8();
+    let mut image = thumbnail(&image, 120, 120);
+    rgb_to_bgr(&mut image);
+
+    Ok(Arc::new(RenderImage::new(smallvec![Frame::new(image)])))
+}
+
+/// Reads every image embedded in the files, leaving out images that several files hold and
+/// images that can't be decoded. Front covers come first.
+fn read_album_images(files: &[PathBuf]) -> Vec<ArtChoice> {
+    let mut provider = SymphoniaProvider::default();
+    let mut choices: Vec<ArtChoice> = Vec::new();
+
+    for file in files {
+        let images = read_images(&mut provider, file).unwrap_or_else(|err| {
+            warn!("Could not read the images in {:?}: {err}", file);
+            Vec::new()
+        });
+
+        for image in images {
+            if choices.iter().any(|choice| *choice.data == *image.data) {
+                continue;
+            }
+
+            match preview(&image.data) {
+                Ok(preview) => choices.push(ArtChoice {
+                    kind: image.kind,
+                    data: image.data.into(),
+                    preview,
+                }),
+                Err(err) => warn!("Could not decode an image in {:?}: {err}", file),
+            }
+        }
+    }
+
+    choices.sort_by_key(|choice| choice.kind != ImageKind::FrontCover);
+    choices
+}
+
+/// A dialog listing the images embedded in an album's files, such as the front and back covers
+/// and pictures of the artist, where any of them can be picked as the album's art.
+pub struct AlbumArtPicker {
+    show: Entity<bool>,
+    album_id: i64,
+    state: ChoicesState,
+    error: Option<SharedString>,
+}
+
+impl AlbumArtPicker {
+    pub fn new(cx: &mut App, show: Entity<bool>, album_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                if *show.read(cx) {
+                    this.load(cx);
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            // the previews are drawn from memory, so they have to be let go of by hand
+            cx.on_release(|this: &mut Self, cx| this.drop_previews(cx))
+                .detach();
+
+            Self {
+                show,
+                album_id,
+                state: ChoicesState::Loading,
+                error: None,
+            }
+        })
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        // tracks split from a file by its cue sheet all hold the file's images
+        let files: Vec<PathBuf> = cx
+            .list_tracks_in_album(self.album_id)
+            .unwrap_or_else(|err| {
+                error!(
+                    "Failed to list the tracks in album {}: {err}",
+                    self.album_id
+                );
+                Default::default()
+            })
+            .iter()
+            .map(|track| {
+                cue_track_file(&track.location)
+                    .unwrap_or(&track.location)
+                    .to_path_buf()
+            })
+            .collect();
+
+        self.drop_previews(cx);
+        self.error = None;
+
+        cx.spawn(async move |this, cx| {
+            let choices = crate::RUNTIME
+                .spawn_blocking(move || read_album_images(&files))
+                .await
+                .unwrap_or_else(|err| {
+                    error!("Failed to read the album's images: {err}");
+                    Vec::new()
+                });
+
+            this.update(cx, |this, cx| {
+                this.state = ChoicesState::Loaded(choices);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn drop_previews(&mut self, cx: &mut App) {
+        if let ChoicesState::Loaded(choices) =
+            std::mem::replace(&mut self.state, ChoicesState::Loading)
+        {
+            for choice in choices {
+                drop_image_from_app(cx, choice.preview);
+            }
+        }
+    }
+
+    fn choose(&mut self, index: usize, cx: &mut Context<Self>) {
+        let ChoicesState::Loaded(choices) = &self.state else {
+            return;
+        };
+        let Some(choice) = choices.get(index) else {
+            return;
+        };
+
+        let pool = cx.global::<Pool>().write.clone();
+        let art_dir = cx.global::<ActiveLibrary>().art_dir();
+        let album_id = self.album_id;
+        let data = choice.data.clone();
+
+        let result = crate::RUNTIME.block_on(set_album_art(&pool, &art_dir, album_id, &data));
+
+        match result {
+            Ok(()) => {
+                self.show.write(cx, false);
+                library_changed(cx, None, ViewSwitchMessage::Release(album_id));
+            }
+            Err(err) => {
+                error!("Failed to set the art of album {album_id}: {err}");
+                self.error = Some(t!(cx, "art.failed", error = err.to_string()));
+                cx.notify();
+            }
+        }
+    }
+}
+
+impl Render for AlbumArtPicker {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let show_cancel = self.show.clone();
+        let theme = cx.global::<Theme>();
+
+        let body = match &self.state {
+            ChoicesState::Loading => div()
+                .text_color(theme.text_secondary)
+                .child(t!(cx, "art.loading"))
+                .into_any_element(),
+            ChoicesState::Loaded(choices) if choices.is_empty() => div()
+                .text_color(theme.text_secondary)
+                .child(t!(cx, "art.none"))
+                .into_any_element(),
+            ChoicesState::Loaded(choices) => div()
+                .id("album-art-choices")
+                .flex()
+                .flex_wrap()
+                .gap(spx(10.0))
+                .max_h(spx(320.0))
+                .overflow_y_scroll()
+                .children(choices.iter().enumerate().map(|(index, choice)| {
+                    div()
+                        .id(("album-art-choice", index))
+                        .w(spx(128.0))
+                        .p(spx(4.0))
+                        .flex()
+                        .flex_col()
+                        .gap(spx(4.0))
+                        .rounded(spx(4.0))
+                        .cursor_pointer()
+                        .hover(|this| this.bg(theme.queue_item_hover))
+                        .on_click(cx.listener(move |this, _, _, cx| this.choose(index, cx)))
+                        .child(
+                            div()
+                                .w(spx(120.0))
+                                .h(spx(120.0))
+                                .rounded(spx(4.0))
+                                .overflow_hidden()
+                                .bg(theme.album_art_background)
+                                .child(
+                                    img(ImageSource::Render(choice.preview.clone()))
+                                        .w(spx(120.0))
+                                        .h(spx(120.0))
+                                        .object_fit(ObjectFit::Contain),
+                                ),
+                        )
+                        .child(
+                            div()
+                                .text_color(theme.text_secondary)
+                                .truncate()
+                                .child(kind_label(cx, choice.kind)),
+                        )
+                }))
+                .into_any_element(),
+        };
+
+        modal()
+            .child(
+                div()
+                    .w(spx(560.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .child(t!(cx, "art.title")),
+                    )
+                    .child(
+                        div()
+                            .pb(spx(6.0))
+                            .text_color(theme.text_secondary)
+                            .child(t!(cx, "art.message")),
+                    )
+                    .child(body)
+                    .when_some(self.error.clone(), |this, error| {
+                        this.child(div().text_color(theme.text_secondary).child(error))
+                    })
+                    .child(
+                        div().flex().justify_end().pt(spx(10.0)).child(
+                            button()
+                                .id("album-art-cancel")
+                                .on_click(move |_, _, cx| show_cancel.write(cx, false))
+                                .child(t!(cx, "art.cancel")),
+                        ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
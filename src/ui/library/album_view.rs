@@ -1,23 +1,43 @@
-use std::{collections::VecDeque, rc::Rc};
+use std::{collections::VecDeque, rc::Rc, time::Duration};
 
 use gpui::*;
+use prelude::FluentBuilder;
 
 use crate::{
     library::{
+        db::AlbumFilter,
         scan::ScanEvent,
-        types::{Album, table::AlbumColumn},
+        types::{
+            Album, AlbumSummary,
+            table::{AlbumColumn, AlbumSummaryColumn},
+        },
     },
+    settings::{SettingsGlobal, save_settings},
     ui::{
-        components::table::{Table, TableEvent},
+        components::{
+            button::{ButtonIntent, ButtonStyle, button},
+            input::TextInput,
+            table::{OnSelectHandler, Table, TableEvent},
+        },
+        locale::t,
         models::Models,
+        theme::Theme,
+        util::spx,
     },
 };
 
 use super::ViewSwitchMessage;
 
+/// How long typing has to stop for before the album list is filtered.
+const FILTER_DELAY: Duration = Duration::from_millis(250);
+
 #[derive(Clone)]
 pub struct AlbumView {
     table: Entity<Table<Album, AlbumColumn>>,
+    /// The compact album list, created the first time it's shown.
+    compact_table: Option<Entity<Table<AlbumSummary, AlbumSummaryColumn>>>,
+    filter_bar: Entity<AlbumFilterBar>,
+    on_select: OnSelectHandler<Album, AlbumColumn>,
 }
 
 impl AlbumView {
@@ -28,46 +48,231 @@ impl AlbumView {
         cx.new(|cx| {
             let state = cx.global::<Models>().scan_state.clone();
 
-            let handler = Rc::new(move |cx: &mut App, id: &(u32, String)| {
+            let on_select = Rc::new(move |cx: &mut App, id: &(u32, String)| {
                 view_switch_model
                     .update(cx, |_, cx| cx.emit(ViewSwitchMessage::Release(id.0 as i64)))
             });
 
-            let table = Table::new(cx, Some(handler));
+            let table = Table::new(cx, Some(on_select.clone()));
+
+            let filter = cx.global::<Models>().album_filter.clone();
+            let filter_bar = AlbumFilterBar::new(cx);
+            table.update(cx, |table, _| table.set_toolbar(filter_bar.clone()));
+
+            cx.observe(&filter, |this: &mut AlbumView, _, cx| this.reload(cx))
+                .detach();
 
-            let table_clone = table.clone();
+            let filter_text = cx.global::<Models>().album_filter_text.clone();
+            cx.observe(&filter_text, |this: &mut AlbumView, _, cx| this.reload(cx))
+                .detach();
 
-            cx.observe(&state, move |_: &mut AlbumView, e, cx| {
+            cx.observe(&state, |this: &mut AlbumView, e, cx| {
                 let value = e.read(cx);
                 match value {
-                    ScanEvent::ScanCompleteIdle => {
-                        table_clone.update(cx, |_, cx| cx.emit(TableEvent::NewRows));
-                    }
-                    ScanEvent::ScanProgress { current, .. } => {
-                        if current % 100 == 0 {
-                            table_clone.update(cx, |_, cx| cx.emit(TableEvent::NewRows));
-                        }
+                    ScanEvent::ScanCompleteIdle => this.reload(cx),
+                    ScanEvent::ScanProgress { current, .. } if current % 100 == 0 => {
+                        this.reload(cx)
                     }
                     _ => {}
                 }
             })
             .detach();
 
-            AlbumView { table }
+            let settings = cx.global::<SettingsGlobal>().model.clone();
+            cx.observe(&settings, |_, _, cx| cx.notify()).detach();
+
+            AlbumView {
+                table,
+                compact_table: None,
+                filter_bar,
+                on_select,
+            }
         })
     }
+
+    /// Reloads the rows of the album lists, such as after the filter has changed.
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        self.table.update(cx, |_, cx| cx.emit(TableEvent::NewRows));
+
+        if let Some(compact_table) = &self.compact_table {
+            compact_table.update(cx, |_, cx| cx.emit(TableEvent::NewRows));
+        }
+    }
 }
 
 impl Render for AlbumView {
-    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let compact = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .interface
+            .compact_album_list;
+
+        let table: AnyView = if compact {
+            self.compact_table
+                .get_or_insert_with(|| {
+                    let table = Table::new(cx, Some(self.on_select.clone()));
+                    let filter_bar = self.filter_bar.clone();
+                    table.update(cx, |table, _| table.set_toolbar(filter_bar));
+                    table
+                })
+                .clone()
+                .into()
+        } else {
+            self.table.clone().into()
+        };
+
         div()
             .flex()
             .flex_col()
             .w_full()
             .h_full()
-            .max_w(px(1000.0))
-            .pt(px(10.0))
-            .pb(px(0.0))
-            .child(self.table.clone())
+            .max_w(spx(1000.0))
+            .pt(spx(10.0))
+            .pb(spx(0.0))
+            .child(table)
+    }
+}
+
+/// Switches the album list between all albums and the ones that haven't been played in a while,
+/// narrows it down to the albums matching the text typed in, and switches between the album list
+/// and the compact album list.
+struct AlbumFilterBar {
+    input: Entity<TextInput>,
+    focus_handle: FocusHandle,
+    /// Waits for typing to stop before updating the filter text. Replaced on every keystroke,
+    /// which cancels the previous wait.
+    pending: Option<Task<()>>,
+}
+
+impl AlbumFilterBar {
+    fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let filter = cx.global::<Models>().album_filter.clone();
+            cx.observe(&filter, |_, _, cx| cx.notify()).detach();
+
+            let settings = cx.global::<SettingsGlobal>().model.clone();
+            cx.observe(&settings, |_, _, cx| cx.notify()).detach();
+
+            let filter_text = cx.global::<Models>().album_filter_text.clone();
+            let focus_handle = cx.focus_handle();
+            let content = filter_text.read(cx).clone();
+            let placeholder = t!(cx, "albums.filter.placeholder");
+            let input = TextInput::new(
+                cx,
+                focus_handle.clone(),
+                Some(content),
+                Some(placeholder),
+                None,
+            );
+
+            cx.subscribe(&input, move |this: &mut Self, _, ev: &String, cx| {
+                let text = SharedString::from(ev.clone());
+                let filter_text = filter_text.clone();
+
+                this.pending = Some(cx.spawn(async move |_, cx| {
+                    cx.background_executor().timer(FILTER_DELAY).await;
+
+                    filter_text
+                        .update(cx, |m, cx| {
+                            if *m != text {
+                                *m = text;
+                                cx.notify();
+                            }
+                        })
+                        .ok();
+                }));
+            })
+            .detach();
+
+            AlbumFilterBar {
+                input,
+                focus_handle,
+                pending: None,
+            }
+        })
+    }
+}
+
+impl Render for AlbumFilterBar {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let filter = cx.global::<Models>().album_filter.clone();
+        let current = *filter.read(cx);
+        let focus_handle = self.focus_handle.clone();
+        let compact = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .interface
+            .compact_album_list;
+
+        let options = [
+            (AlbumFilter::All, t!(cx, "albums.filter.all")),
+            (
+                AlbumFilter::NeverPlayed,
+                t!(cx, "albums.filter.never_played"),
+            ),
+            (AlbumFilter::Forgotten, t!(cx, "albums.filter.forgotten")),
+        ];
+
+        div()
+            .flex()
+            .items_center()
+            .gap(spx(4.0))
+            .child(
+                div()
+                    .w(spx(180.0))
+                    .mr(spx(4.0))
+                    .px(spx(8.0))
+                    .py(spx(3.0))
+                    .line_height(spx(16.0))
+                    .text_sm()
+                    .border_1()
+                    .border_color(theme.border_color)
+                    .rounded(spx(4.0))
+                    .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                        focus_handle.focus(window)
+                    })
+                    .child(self.input.clone()),
+            )
+            .children(options.into_iter().enumerate().map(|(i, (option, label))| {
+                let filter = filter.clone();
+
+                button()
+                    .id(("album-filter", i))
+                    .text_sm()
+                    .when(option == current, |this| this.intent(ButtonIntent::Primary))
+                    .when(option != current, |this| this.style(ButtonStyle::Minimal))
+                    .on_click(move |_, _, cx| {
+                        filter.update(cx, |filter, cx| {
+                            if *filter != option {
+                                *filter = option;
+                                cx.notify();
+                            }
+                        })
+                    })
+                    .child(label)
+            }))
+            .child(
+                button()
+                    .id("album-compact")
+                    .text_sm()
+                    .ml(spx(8.0))
+                    .when(compact, |this| this.intent(ButtonIntent::Primary))
+                    .when(!compact, |this| this.style(ButtonStyle::Minimal))
+                    .child(t!(cx, "albums.compact"))
+                    .on_click(|_, _, cx| {
+                        let settings = cx.global::<SettingsGlobal>().model.clone();
+                        settings.update(cx, |settings, cx| {
+                            settings.interface.compact_album_list =
+                                !settings.interface.compact_album_list;
+                            cx.notify();
+                        });
+
+                        save_settings(cx);
+                    }),
+            )
     }
 }
@@ -0,0 +1,348 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use gpui::*;
+use prelude::FluentBuilder;
+use rustc_hash::FxHashMap;
+use tracing::{error, warn};
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        types::{Album, AlbumFormat, Artist},
+    },
+    services::artist_info::update_artist_info,
+    ui::{
+        app::{ActiveLibrary, Pool},
+        caching::HummingbirdImageCache,
+        command_palette::{Command, CommandManager},
+        components::{
+            badge::badges,
+            button::button,
+            icons::{STACK, USER, icon},
+        },
+        locale::t,
+        models::Models,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+use super::{ViewSwitchMessage, merge_artists::MergeArtists, similar_artists::SimilarArtists};
+
+actions!(artist, [Merge]);
+
+pub struct ArtistView {
+    artist: Arc<Artist>,
+    albums: Arc<Vec<Album>>,
+    formats: FxHashMap<i64, AlbumFormat>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+    image_cache: Entity<HummingbirdImageCache>,
+    similar_artists: Option<Entity<SimilarArtists>>,
+    merge_artists: Entity<MergeArtists>,
+    show_merge_artists: Entity<bool>,
+    focus_handle: FocusHandle,
+    first_render: bool,
+}
+
+impl ArtistView {
+    pub(super) fn new(cx: &mut App, artist_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let image_cache = HummingbirdImageCache::new(50, cx);
+            // TODO: error handling
+            let artist = cx
+                .get_artist_by_id(artist_id)
+                .expect("Failed to retrieve artist");
+            let albums = cx
+                .list_albums_by_artist(artist_id)
+                .expect("Failed to retrieve albums");
+            let formats = album_formats(cx, &albums);
+            let view_switcher = cx.global::<Models>().switcher_model.clone();
+            let similar_artists = artist
+                .name
+                .clone()
+                .map(|name| SimilarArtists::new(cx, name.to_string(), artist_id));
+
+            let show_merge_artists = cx.new(|_| false);
+            let merge_artists = MergeArtists::new(
+                cx,
+                show_merge_artists.clone(),
+                artist_id,
+                artist.name.clone().map(|name| name.0).unwrap_or_default(),
+            );
+
+            let focus_handle = cx.focus_handle();
+            let category = t!(cx, "command.category.artist");
+            let name = t!(cx, "command.merge_artists");
+
+            cx.register_command(
+                ("artist::merge", artist_id),
+                Command::new(Some(category), name, Merge, Some(focus_handle.clone())),
+            );
+
+            cx.on_release(move |_, cx| {
+                cx.unregister_command(("artist::merge", artist_id));
+            })
+            .detach();
+
+            let mut view = ArtistView {
+                artist,
+                albums,
+                formats,
+                view_switcher,
+                image_cache,
+                similar_artists,
+                merge_artists,
+                show_merge_artists,
+                focus_handle,
+                first_render: true,
+            };
+
+            view.fetch_info(cx);
+            view
+        })
+    }
+
+    /// Looks up the artist's bio and image online if they haven't been fetched recently, and
+    /// shows them once they've been stored.
+    fn fetch_info(&mut self, cx: &mut Context<Self>) {
+        let artist_id = self.artist.id;
+        let read_pool = cx.global::<Pool>().read.clone();
+        let write_pool = cx.global::<Pool>().write.clone();
+        let art_dir = cx.global::<ActiveLibrary>().art_dir();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move {
+                    update_artist_info(&read_pool, &write_pool, &art_dir, artist_id).await
+                })
+                .await;
+
+            match result {
+                Ok(Ok(true)) => {
+                    this.update(cx, |this, cx| {
+                        if let Ok(artist) = cx.get_artist_by_id(artist_id) {
+                            this.artist = artist;
+                            cx.notify();
+                        }
+                    })
+                    .ok();
+                }
+                Ok(Ok(false)) => (),
+                Ok(Err(err)) => warn!(?err, "Could not fetch artist info: {err}"),
+                Err(err) => warn!(?err, "Could not fetch artist info: {err}"),
+            }
+        })
+        .detach();
+    }
+}
+
+impl Render for ArtistView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let artist_id = self.artist.id;
+        let show_merge_artists = self.show_merge_artists.clone();
+        let show_merge_artists_action = self.show_merge_artists.clone();
+
+        if self.first_render {
+            self.first_render = false;
+            self.focus_handle.focus(window);
+        }
+
+        div()
+            .id("artist-view")
+            .track_focus(&self.focus_handle)
+            .on_action(move |_: &Merge, _, cx| show_merge_artists_action.write(cx, true))
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .flex()
+                    .px(spx(18.0))
+                    .w_full()
+                    .overflow_x_hidden()
+                    .child(
+                        div()
+                            .rounded_full()
+                            .bg(theme.album_art_background)
+                            .shadow_sm()
+                            .w(spx(160.0))
+                            .h(spx(160.0))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .flex_shrink_0()
+                            .overflow_hidden()
+                            .map(|this| match &self.artist.image_hash {
+                                // the hash makes sure a newly fetched image replaces a cached one
+                                Some(hash) => this.child(
+                                    img(SharedString::from(format!(
+                                        "!db://artist/{artist_id}/image?{hash}"
+                                    )))
+                                    .image_cache(&self.image_cache)
+                                    .w(spx(160.0))
+                                    .h(spx(160.0))
+                                    .object_fit(ObjectFit::Cover)
+                                    .rounded_full(),
+                                ),
+                                None => this.child(
+                                    icon(USER).size(spx(64.0)).text_color(theme.text_secondary),
+                                ),
+                            }),
+                    )
+                    .child(
+                        div()
+                            .ml(spx(18.0))
+                            .my_auto()
+                            .flex()
+                            .flex_col()
+                            .flex_shrink()
+                            .w_full()
+                            .overflow_x_hidden()
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::EXTRA_BOLD)
+                                    .text_size(rems(2.5))
+                                    .line_height(rems(2.75))
+                                    .pb(spx(6.0))
+                                    .truncate()
+                                    .child(
+                                        self.artist
+                                            .name
+                                            .clone()
+                                            .map(SharedString::from)
+                                            .unwrap_or_else(|| t!(cx, "track.unknown_artist")),
+                                    ),
+                            )
+                            .when_some(self.artist.bio.clone(), |this, bio| {
+                                this.child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(theme.text_secondary)
+                                        .line_clamp(5)
+                                        .child(SharedString::from(bio)),
+                                )
+                            })
+                            .child(
+                                div().flex().pt(spx(10.0)).child(
+                                    button()
+                                        .id("artist-merge-button")
+                                        .text_sm()
+                                        .on_click(move |_, _, cx| {
+                                            show_merge_artists.write(cx, true);
+                                        })
+                                        .child(icon(STACK).size(spx(14.0)).my_auto())
+                                        .child(t!(cx, "artist.merge")),
+                                ),
+                            ),
+                    ),
+            )
+            .child(
+                div()
+                    .text_color(theme.text_secondary)
+                    .text_sm()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .px(spx(18.0))
+                    .border_b_1()
+                    .w_full()
+                    .border_color(theme.border_color)
+                    .mt(spx(24.0))
+                    .pb(spx(6.0))
+                    .child(t!(cx, "artist.albums")),
+            )
+            .children(self.albums.iter().map(|album| {
+                let format = self.formats.get(&album.id);
+                album_row(album, format, self.view_switcher.clone(), false, cx)
+            }))
+            .when_some(self.similar_artists.clone(), |this, similar_artists| {
+                this.child(similar_artists)
+            })
+            .child(div().h(spx(24.0)))
+            .child(self.merge_artists.clone())
+    }
+}
+
+/// Works out the badges shown in the rows of a list of albums.
+pub(super) fn album_formats(cx: &mut App, albums: &[Album]) -> FxHashMap<i64, AlbumFormat> {
+    let album_ids: Vec<i64> = albums.iter().map(|album| album.id).collect();
+
+    cx.list_album_formats(&album_ids).unwrap_or_else(|err| {
+        error!("Failed to work out the formats of albums: {err}");
+        FxHashMap::default()
+    })
+}
+
+/// A row in a list of albums, which opens the album when clicked. The catalog number is shown
+/// before the year if `show_catalog` is set.
+pub(super) fn album_row(
+    album: &Album,
+    format: Option<&AlbumFormat>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+    show_catalog: bool,
+    cx: &App,
+) -> Stateful<Div> {
+    let theme = cx.global::<Theme>();
+    let album_id = album.id;
+    let year = album
+        .release_date
+        .map(|date| date.format("%Y").to_string())
+        .or_else(|| album.release_year.map(|year| year.to_string()));
+
+    div()
+        .id(("album-row", album_id as u64))
+        .flex()
+        .items_center()
+        .px(spx(18.0))
+        .py(spx(6.0))
+        .gap(spx(12.0))
+        .w_full()
+        .border_b_1()
+        .border_color(theme.border_color)
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.nav_button_hover))
+        .active(|this| this.bg(theme.nav_button_active))
+        .on_click(move |_, _, cx| {
+            view_switcher.update(cx, |_, cx| cx.emit(ViewSwitchMessage::Release(album_id)))
+        })
+        .child(
+            div()
+                .w(spx(36.0))
+                .h(spx(36.0))
+                .flex_shrink_0()
+                .rounded(spx(3.0))
+                .bg(theme.album_art_background)
+                .child(
+                    img(SharedString::from(format!("!db://album/{album_id}/thumb")))
+                        .w(spx(36.0))
+                        .h(spx(36.0))
+                        .rounded(spx(3.0)),
+                ),
+        )
+        .child(
+            div()
+                .font_weight(FontWeight::SEMIBOLD)
+                .flex_shrink()
+                .truncate()
+                .child(album.title.clone()),
+        )
+        .children(format.and_then(|format| badges(cx, format.format_label(), format.explicit)))
+        .child(
+            div()
+                .ml_auto()
+                .flex()
+                .gap(spx(12.0))
+                .flex_shrink_0()
+                .text_sm()
+                .text_color(theme.text_secondary)
+                .when(show_catalog, |this| {
+                    this.when_some(album.catalog_number.clone(), |this, catalog_number| {
+                        this.child(catalog_number)
+                    })
+                })
+                .when_some(year, |this, year| this.child(year)),
+        )
+}
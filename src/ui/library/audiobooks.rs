@@ -0,0 +1,276 @@
+use std::collections::VecDeque;
+
+use gpui::*;
+use prelude::FluentBuilder;
+use tracing::error;
+
+use crate::{
+    library::{
+        audiobooks::{clear_track_position, find_audiobook_track, set_track_position},
+        db::LibraryAccess,
+        types::Audiobook,
+    },
+    playback::interface::PlaybackInterface,
+    ui::{
+        app::Pool,
+        locale::{Locale, t},
+        models::{Models, PlaybackInfo},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+use super::{
+    ViewSwitchMessage,
+    podcasts::{LISTENED_WITHIN, SAVE_INTERVAL, format_length, new_badge, page_title},
+};
+
+/// The audiobook chapter that's playing, so that where it's up to can be saved.
+struct PlayingChapter {
+    id: i64,
+    /// The position that was saved last, in seconds.
+    saved: u64,
+    finished: bool,
+}
+
+#[derive(Default)]
+struct ChapterPlayback(Option<PlayingChapter>);
+
+impl Global for ChapterPlayback {}
+
+/// Resumes audiobook chapters from where they were left, and saves where they're up to as they
+/// play.
+pub fn track_audiobooks(cx: &mut App) {
+    cx.set_global(ChapterPlayback::default());
+
+    let current_track = cx.global::<PlaybackInfo>().current_track.clone();
+    let position = cx.global::<PlaybackInfo>().position.clone();
+
+    cx.observe(&current_track, |current_track, cx| {
+        let Some(track) = current_track.read(cx).clone() else {
+            cx.global_mut::<ChapterPlayback>().0 = None;
+            return;
+        };
+
+        let pool = cx.global::<Pool>().read.clone();
+        let chapter = crate::RUNTIME
+            .block_on(find_audiobook_track(
+                &pool,
+                &track.get_path().to_string_lossy(),
+            ))
+            .unwrap_or_else(|err| {
+                error!("Failed to look up audiobook chapter: {err}");
+                None
+            });
+
+        if let Some((_, position)) = chapter
+            && position > 0
+        {
+            cx.global::<PlaybackInterface>().seek(position as f64);
+        }
+
+        cx.global_mut::<ChapterPlayback>().0 = chapter.map(|(id, position)| PlayingChapter {
+            id,
+            saved: position as u64,
+            finished: false,
+        });
+    })
+    .detach();
+
+    cx.observe(&position, |position, cx| {
+        let position = *position.read(cx);
+        let duration = *cx.global::<PlaybackInfo>().duration.read(cx);
+        let write_pool = cx.global::<Pool>().write.clone();
+
+        let Some(playing) = cx.global_mut::<ChapterPlayback>().0.as_mut() else {
+            return;
+        };
+
+        // the position is reset to zero when the chapter is opened, before it's moved to where
+        // it was left
+        if playing.finished || position == 0 {
+            return;
+        }
+
+        let id = playing.id;
+
+        if duration > LISTENED_WITHIN && position + LISTENED_WITHIN >= duration {
+            // the next time the chapter is played, it starts from the beginning
+            playing.finished = true;
+            crate::RUNTIME.spawn(async move {
+                if let Err(err) = clear_track_position(&write_pool, id).await {
+                    error!("Failed to clear chapter position: {err}");
+                }
+            });
+        } else if position.abs_diff(playing.saved) >= SAVE_INTERVAL {
+            playing.saved = position;
+            crate::RUNTIME.spawn(async move {
+                if let Err(err) = set_track_position(&write_pool, id, position as i64).await {
+                    error!("Failed to save chapter position: {err}");
+                }
+            });
+        }
+    })
+    .detach();
+}
+
+/// Lists the books in the audiobook folders, grouped by series.
+pub struct AudiobooksView {
+    books: Vec<Audiobook>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+}
+
+impl AudiobooksView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            // books are added to the list as they're scanned
+            let scan_state = cx.global::<Models>().scan_state.clone();
+            cx.observe(&scan_state, |this: &mut Self, _, cx| {
+                this.load(cx);
+                cx.notify();
+            })
+            .detach();
+
+            let mut view = AudiobooksView {
+                books: Vec::new(),
+                view_switcher: cx.global::<Models>().switcher_model.clone(),
+            };
+            view.load(cx);
+            view
+        })
+    }
+
+    fn load(&mut self, cx: &mut App) {
+        self.books = cx.list_audiobooks().unwrap_or_else(|err| {
+            error!("Failed to list audiobooks: {err}");
+            Vec::new()
+        });
+    }
+}
+
+impl Render for AudiobooksView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let locale = cx.global::<Locale>();
+
+        let mut rows: Vec<AnyElement> = Vec::new();
+        let mut last_series: Option<String> = None;
+
+        for book in &self.books {
+            let series = book
+                .series
+                .clone()
+                .or_else(|| book.author.as_ref().map(|author| author.to_string()));
+
+            if series != last_series
+                && let Some(series) = &series
+            {
+                rows.push(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(14.0))
+                        .pb(spx(6.0))
+                        .text_sm()
+                        .font_weight(FontWeight::BOLD)
+                        .text_color(theme.text_secondary)
+                        .border_b_1()
+                        .border_color(theme.border_color)
+                        .child(SharedString::from(series.clone()))
+                        .into_any_element(),
+                );
+            }
+            last_series = series;
+
+            let mut details: Vec<SharedString> = Vec::new();
+            if let Some(part) = &book.series_part {
+                details.push(t!(cx, "audiobooks.book", part = part));
+            }
+            if book.series.is_some()
+                && let Some(author) = &book.author
+            {
+                details.push(author.0.clone());
+            }
+            details.push(locale.plural("audiobooks.chapter_count", book.chapter_count));
+            details.push(format_length(cx, book.duration.max(0) as u64));
+
+            let album_id = book.id;
+            let view_switcher = self.view_switcher.clone();
+
+            rows.push(
+                div()
+                    .id(("audiobook", album_id as u64))
+                    .flex()
+                    .items_center()
+                    .px(spx(18.0))
+                    .py(spx(8.0))
+                    .gap(spx(12.0))
+                    .w_full()
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.nav_button_hover))
+                    .active(|this| this.bg(theme.nav_button_active))
+                    .on_click(move |_, _, cx| {
+                        view_switcher
+                            .update(cx, |_, cx| cx.emit(ViewSwitchMessage::Release(album_id)))
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .flex_shrink()
+                            .overflow_x_hidden()
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .truncate()
+                                    .child(book.title.0.clone()),
+                            )
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(theme.text_secondary)
+                                    .truncate()
+                                    .child(details.join(" · ")),
+                            ),
+                    )
+                    .when(book.in_progress, |this| {
+                        this.child(
+                            div()
+                                .ml_auto()
+                                .child(new_badge(t!(cx, "audiobooks.in_progress"), theme)),
+                        )
+                    })
+                    .into_any_element(),
+            );
+        }
+
+        div()
+            .id("audiobooks-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                page_title(t!(cx, "sidebar.audiobooks"))
+                    .pb(spx(10.0))
+                    .border_b_1()
+                    .border_color(theme.border_color),
+            )
+            .when(self.books.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "audiobooks.empty")),
+                )
+            })
+            .children(rows)
+            .child(div().h(spx(24.0)))
+    }
+}
@@ -0,0 +1,250 @@
+use std::{path::PathBuf, sync::Arc};
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, PathPromptOptions,
+    Render, SharedString, Styled, Window, div,
+};
+use tracing::{info, warn};
+
+use crate::{
+    library::db::LibraryAccess,
+    media::{metadata::TrackFile, transcode::Transcoder},
+    settings::{SettingsGlobal, transcode::TranscodeProfile},
+    ui::{
+        components::{button::button, modal::modal},
+        locale::{Locale, t},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+enum ConvertState {
+    /// Waiting for a profile to be picked.
+    Choosing,
+    Converting {
+        done: usize,
+        failed: usize,
+    },
+    Finished {
+        converted: usize,
+        failed: usize,
+    },
+    Error(SharedString),
+}
+
+/// A dialog that converts files with one of the transcode profiles, writing them to a folder
+/// picked by the user.
+pub struct ConvertFiles {
+    show: Entity<bool>,
+    files: Vec<PathBuf>,
+    state: ConvertState,
+}
+
+impl ConvertFiles {
+    pub fn new(cx: &mut App, show: Entity<bool>, files: Vec<PathBuf>) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                if *show.read(cx) && !matches!(this.state, ConvertState::Converting { .. }) {
+                    this.state = ConvertState::Choosing;
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            Self {
+                show,
+                files,
+                state: ConvertState::Choosing,
+            }
+        })
+    }
+
+    fn convert(&mut self, profile: TranscodeProfile, cx: &mut Context<Self>) {
+        let settings = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .transcode
+            .clone();
+        let transcoder = match Transcoder::find(&settings) {
+            Ok(transcoder) => transcoder,
+            Err(err) => {
+                self.state = ConvertState::Error(err.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        let path_future = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some(t!(cx, "convert.prompt")),
+        });
+        let files: Vec<TrackFile> = self
+            .files
+            .iter()
+            .map(|location| {
+                cx.find_track_file(location).unwrap_or_else(|err| {
+                    warn!("Failed to look up the file of {:?}: {err}", location);
+                    TrackFile::whole(location)
+                })
+            })
+            .collect();
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = path_future.await else {
+                return;
+            };
+            let Some(dir) = paths.into_iter().next() else {
+                return;
+            };
+
+            this.update(cx, |this, cx| {
+                this.state = ConvertState::Converting { done: 0, failed: 0 };
+                cx.notify();
+            })
+            .ok();
+
+            let transcoder = Arc::new(transcoder);
+            let mut failed = 0;
+
+            for (index, file) in files.into_iter().enumerate() {
+                let transcoder = transcoder.clone();
+                let dir = dir.clone();
+                let profile = profile.clone();
+
+                let result = crate::RUNTIME
+                    .spawn_blocking(move || {
+                        let result = transcoder.convert_into(&file, &dir, &profile);
+                        (file, result)
+                    })
+                    .await;
+
+                match result {
+                    Ok((file, Ok(to))) => info!("Converted {:?} to {:?}", file.location, to),
+                    Ok((file, Err(err))) => {
+                        warn!("Could not convert {:?}: {err}", file.location);
+                        failed += 1;
+                    }
+                    Err(err) => {
+                        warn!("Could not convert file: {err}");
+                        failed += 1;
+                    }
+                }
+
+                this.update(cx, |this, cx| {
+                    this.state = ConvertState::Converting {
+                        done: index + 1,
+                        failed,
+                    };
+                    cx.notify();
+                })
+                .ok();
+            }
+
+            this.update(cx, |this, cx| {
+                let ConvertState::Converting { done, failed } = this.state else {
+                    return;
+                };
+
+                this.state = ConvertState::Finished {
+                    converted: done - failed,
+                    failed,
+                };
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl Render for ConvertFiles {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let theme = cx.global::<Theme>();
+        let show = self.show.clone();
+        let show_close = self.show.clone();
+        let total = self.files.len();
+
+        let locale = cx.global::<Locale>();
+        let status = match &self.state {
+            ConvertState::Choosing => locale.plural("convert.count", total as i64),
+            ConvertState::Converting { done, .. } => {
+                locale.format("convert.progress", &[("done", done), ("total", &total)])
+            }
+            ConvertState::Finished {
+                converted,
+                failed: 0,
+            } => locale.plural("convert.finished", *converted as i64),
+            ConvertState::Finished { converted, failed } => locale.format(
+                "convert.finished_with_failures",
+                &[("count", converted), ("failed", failed)],
+            ),
+            ConvertState::Error(err) => err.clone(),
+        };
+
+        let profiles = match self.state {
+            ConvertState::Choosing => cx
+                .global::<SettingsGlobal>()
+                .model
+                .read(cx)
+                .transcode
+                .profiles
+                .clone(),
+            _ => Vec::new(),
+        };
+
+        let profile_buttons = profiles.into_iter().enumerate().map(|(index, profile)| {
+            let label = profile.name.clone();
+            button()
+                .id(("convert-profile", index))
+                .on_click(cx.listener(move |this, _, _, cx| this.convert(profile.clone(), cx)))
+                .child(label)
+        });
+
+        modal()
+            .child(
+                div()
+                    .w(spx(400.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .pb(spx(6.0))
+                            .child(t!(cx, "convert.title")),
+                    )
+                    .child(div().text_color(theme.text_secondary).child(status))
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .gap(spx(8.0))
+                            .pt(spx(4.0))
+                            .children(profile_buttons),
+                    )
+                    .child(
+                        div().flex().justify_end().pt(spx(10.0)).child(
+                            button()
+                                .id("convert-close")
+                                .on_click(move |_, _, cx| show_close.write(cx, false))
+                                .child(t!(cx, "convert.close")),
+                        ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
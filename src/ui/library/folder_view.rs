@@ -0,0 +1,779 @@
+use std::{
+    cmp::Ordering,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, InteractiveElement, IntoElement, ParentElement,
+    PathPromptOptions, Render, SharedString, StatefulInteractiveElement, Styled, WeakEntity,
+    Window, div, prelude::FluentBuilder, rems, uniform_list,
+};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::{
+    library::{db::get_track_by_location, types::Track},
+    media::{builtin::symphonia::SymphoniaProvider, traits::MediaPlugin},
+    playback::{
+        interface::{PlaybackInterface, replace_queue},
+        queue::QueueItemData,
+    },
+    settings::{
+        SettingsGlobal, libraries, save_settings,
+        scan::{ContentType, ScanPath},
+    },
+    ui::{
+        app::{ActiveLibrary, Pool},
+        components::{
+            button::{ButtonIntent, ButtonSize, button},
+            icons::{CIRCLE_PLUS, FOLDER, MINUS, PLAY, PLUS, TRASH, icon},
+        },
+        locale::t,
+        models::PlaybackInfo,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+#[derive(Clone)]
+enum EntryKind {
+    Folder,
+    /// An audio file, and its track in the library if it has been scanned.
+    File(Option<Arc<Track>>),
+}
+
+#[derive(Clone)]
+struct Entry {
+    path: PathBuf,
+    name: SharedString,
+    kind: EntryKind,
+}
+
+impl Entry {
+    fn queue_item(&self, cx: &mut App) -> QueueItemData {
+        let track = match &self.kind {
+            EntryKind::File(track) => track.as_ref(),
+            EntryKind::Folder => None,
+        };
+
+        QueueItemData::new(
+            cx,
+            self.path.clone(),
+            track.map(|track| track.id),
+            track.and_then(|track| track.album_id),
+        )
+    }
+}
+
+/// The folder being browsed: the scan path it's in, and the folder itself.
+#[derive(Clone, PartialEq)]
+struct Location {
+    root: PathBuf,
+    folder: PathBuf,
+}
+
+/// Browses the scan paths as folders, for libraries that are organized on disk rather than by
+/// their tags. Files that are in the library are shown with their track's details.
+pub struct FolderView {
+    /// `None` when showing the list of scan paths.
+    location: Option<Location>,
+    entries: Arc<Vec<Entry>>,
+    /// The scan paths, in the same order as the entries when they're being shown.
+    roots: Arc<Vec<ScanPath>>,
+    loading: bool,
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        SymphoniaProvider::SUPPORTED_EXTENSIONS
+            .iter()
+            .any(|supported| ext.eq_ignore_ascii_case(supported))
+    })
+}
+
+fn file_name(path: &Path) -> SharedString {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+        .into()
+}
+
+/// Lists the folders and audio files in the folder, folders first. Hidden files are skipped.
+async fn read_folder(pool: &SqlitePool, folder: &Path) -> std::io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for dir_entry in std::fs::read_dir(folder)? {
+        let path = dir_entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+
+        let kind = if path.is_dir() {
+            EntryKind::Folder
+        } else if is_audio_file(&path) {
+            let track = get_track_by_location(pool, &path)
+                .await
+                .unwrap_or_else(|err| {
+                    warn!("Failed to look up {}: {err}", path.display());
+                    None
+                });
+            EntryKind::File(track)
+        } else {
+            continue;
+        };
+
+        entries.push(Entry {
+            name: file_name(&path),
+            path,
+            kind,
+        });
+    }
+
+    entries.sort_by(|a, b| {
+        match (&a.kind, &b.kind) {
+            (EntryKind::Folder, EntryKind::File(_)) => Ordering::Less,
+            (EntryKind::File(_), EntryKind::Folder) => Ordering::Greater,
+            _ => Ordering::Equal,
+        }
+        .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    Ok(entries)
+}
+
+/// Lists every audio file in the folder and its subfolders, in the order they're shown in.
+async fn read_folder_recursive(pool: &SqlitePool, folder: &Path) -> Vec<Entry> {
+    let mut files = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+
+    while let Some(folder) = pending.pop() {
+        let entries = match read_folder(pool, &folder).await {
+            Ok(entries) => entries,
+            Err(err) => {
+                warn!("Failed to read {}: {err}", folder.display());
+                continue;
+            }
+        };
+
+        // the folder's own files come before its subfolders, which are visited in order
+        let mut subfolders = Vec::new();
+        for entry in entries {
+            match entry.kind {
+                EntryKind::Folder => subfolders.push(entry.path),
+                EntryKind::File(_) => files.push(entry),
+            }
+        }
+        pending.extend(subfolders.into_iter().rev());
+    }
+
+    files
+}
+
+impl FolderView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let settings = cx.global::<SettingsGlobal>().model.clone();
+            cx.observe(&settings, |this: &mut Self, _, cx| {
+                if this.location.is_none() {
+                    this.show_roots(cx);
+                }
+            })
+            .detach();
+
+            let current_track = cx.global::<PlaybackInfo>().current_track.clone();
+            cx.observe(&current_track, |_, _, cx| cx.notify()).detach();
+
+            let mut view = Self {
+                location: None,
+                entries: Arc::new(Vec::new()),
+                roots: Arc::new(Vec::new()),
+                loading: false,
+            };
+            view.show_roots(cx);
+            view
+        })
+    }
+
+    fn show_roots(&mut self, cx: &mut Context<Self>) {
+        let settings = cx.global::<SettingsGlobal>().model.read(cx);
+        let roots = cx.global::<ActiveLibrary>().scan_settings(settings).paths;
+
+        self.location = None;
+        self.entries = Arc::new(
+            roots
+                .iter()
+                .map(|root| Entry {
+                    name: root.path.to_string_lossy().to_string().into(),
+                    path: root.path.clone(),
+                    kind: EntryKind::Folder,
+                })
+                .collect(),
+        );
+        self.roots = Arc::new(roots);
+        cx.notify();
+    }
+
+    /// Changes the options of the scan path and saves the settings. The list of scan paths is
+    /// reloaded once the settings have been changed.
+    fn update_root(
+        &mut self,
+        folder: &Path,
+        update: impl FnOnce(&mut ScanPath),
+        cx: &mut Context<Self>,
+    ) {
+        let library = cx.global::<ActiveLibrary>();
+        let directory = library.directory.clone();
+        let data_dir = library.data_dir.clone();
+        let settings = cx.global::<SettingsGlobal>().model.clone();
+
+        settings.update(cx, |settings, cx| {
+            if let Some(root) =
+                libraries::find_scan_path_mut(settings, &directory, &data_dir, folder)
+            {
+                update(root);
+                cx.notify();
+            }
+        });
+
+        save_settings(cx);
+    }
+
+    fn remove_root(&mut self, folder: &Path, cx: &mut Context<Self>) {
+        let library = cx.global::<ActiveLibrary>();
+        let directory = library.directory.clone();
+        let data_dir = library.data_dir.clone();
+        let settings = cx.global::<SettingsGlobal>().model.clone();
+
+        settings.update(cx, |settings, cx| {
+            libraries::remove_scan_path(settings, &directory, &data_dir, folder);
+            cx.notify();
+        });
+
+        save_settings(cx);
+    }
+
+    /// Asks for a folder to add to the scan paths. The scanner scans new paths as soon as it's
+    /// told about them.
+    fn add_root(&mut self, cx: &mut Context<Self>) {
+        let path_future = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some(t!(cx, "folders.add_prompt")),
+        });
+
+        cx.spawn(async move |_, cx| {
+            let Ok(Ok(Some(paths))) = path_future.await else {
+                return;
+            };
+            let Some(folder) = paths.into_iter().next() else {
+                return;
+            };
+
+            cx.update(|cx| {
+                let name = cx.global::<ActiveLibrary>().name.clone();
+                let settings = cx.global::<SettingsGlobal>().model.clone();
+
+                settings.update(cx, |settings, cx| {
+                    libraries::add_scan_path(settings, name.as_deref(), folder);
+                    cx.notify();
+                });
+
+                save_settings(cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn open(&mut self, location: Location, cx: &mut Context<Self>) {
+        let pool = cx.global::<Pool>().read.clone();
+        let folder = location.folder.clone();
+
+        self.location = Some(location.clone());
+        self.loading = true;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move { read_folder(&pool, &folder).await })
+                .await;
+
+            this.update(cx, |this, cx| {
+                // another folder could have been opened while this one was being read
+                if this.location.as_ref() != Some(&location) {
+                    return;
+                }
+
+                this.loading = false;
+                this.entries = Arc::new(match result {
+                    Ok(Ok(entries)) => entries,
+                    Ok(Err(err)) => {
+                        warn!("Failed to read {}: {err}", location.folder.display());
+                        Vec::new()
+                    }
+                    Err(err) => {
+                        warn!("Failed to read {}: {err}", location.folder.display());
+                        Vec::new()
+                    }
+                });
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn open_entry(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(index).cloned() else {
+            return;
+        };
+
+        match entry.kind {
+            EntryKind::Folder => {
+                let root = match &self.location {
+                    Some(location) => location.root.clone(),
+                    None => entry.path.clone(),
+                };
+
+                self.open(
+                    Location {
+                        root,
+                        folder: entry.path,
+                    },
+                    cx,
+                );
+            }
+            EntryKind::File(_) => {
+                let files: Vec<&Entry> = self
+                    .entries
+                    .iter()
+                    .filter(|entry| matches!(entry.kind, EntryKind::File(_)))
+                    .collect();
+                let position = files
+                    .iter()
+                    .position(|file| file.path == entry.path)
+                    .unwrap_or_default();
+                let items = files.iter().map(|file| file.queue_item(cx)).collect();
+
+                replace_queue(items, cx);
+                cx.global::<PlaybackInterface>().jump_unshuffled(position);
+            }
+        }
+    }
+
+    /// Plays (or adds to the queue) every file in the current folder and its subfolders.
+    fn queue_folder(&mut self, replace: bool, cx: &mut Context<Self>) {
+        let Some(location) = self.location.clone() else {
+            return;
+        };
+        let pool = cx.global::<Pool>().read.clone();
+
+        cx.spawn(async move |_, cx| {
+            let files = crate::RUNTIME
+                .spawn(async move { read_folder_recursive(&pool, &location.folder).await })
+                .await
+                .unwrap_or_default();
+
+            cx.update(|cx| {
+                let items: Vec<QueueItemData> =
+                    files.iter().map(|file| file.queue_item(cx)).collect();
+
+                if replace {
+                    replace_queue(items, cx);
+                } else {
+                    cx.global::<PlaybackInterface>().queue_list(items);
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// The folders from the scan path down to the current folder.
+    fn breadcrumbs(&self) -> Vec<Location> {
+        let Some(location) = &self.location else {
+            return Vec::new();
+        };
+
+        let mut crumbs: Vec<Location> = location
+            .folder
+            .ancestors()
+            .take_while(|folder| folder.starts_with(&location.root))
+            .map(|folder| Location {
+                root: location.root.clone(),
+                folder: folder.to_path_buf(),
+            })
+            .collect();
+        crumbs.reverse();
+        crumbs
+    }
+}
+
+/// The buttons for changing a scan path's options, shown next to it in the list of scan paths.
+fn root_options(
+    index: usize,
+    root: &ScanPath,
+    view: &WeakEntity<FolderView>,
+    cx: &App,
+) -> impl IntoElement {
+    let folder = root.path.clone();
+    let watch = root.watch;
+    let audiobooks = root.content == ContentType::Audiobooks;
+    let priority = root.priority;
+
+    let update = {
+        let view = view.clone();
+        move |folder: PathBuf, update: Box<dyn FnOnce(&mut ScanPath)>, cx: &mut App| {
+            cx.stop_propagation();
+            view.update(cx, |this, cx| this.update_root(&folder, update, cx))
+                .ok();
+        }
+    };
+
+    div()
+        .ml_auto()
+        .flex()
+        .flex_shrink_0()
+        .items_center()
+        .gap(spx(6.0))
+        .text_sm()
+        .child(
+            button()
+                .id(("folder-root-watch", index))
+                .when(watch, |this| this.intent(ButtonIntent::Primary))
+                .tooltip(t!(cx, "folders.watch_tooltip"))
+                .child(if watch {
+                    t!(cx, "folders.watching")
+                } else {
+                    t!(cx, "folders.not_watching")
+                })
+                .on_click({
+                    let folder = folder.clone();
+                    let update = update.clone();
+                    move |_, _, cx| {
+                        update(
+                            folder.clone(),
+                            Box::new(|root| root.watch = !root.watch),
+                            cx,
+                        )
+                    }
+                }),
+        )
+        .child(
+            button()
+                .id(("folder-root-content", index))
+                .tooltip(t!(cx, "folders.content_tooltip"))
+                .child(if audiobooks {
+                    t!(cx, "folders.audiobooks")
+                } else {
+                    t!(cx, "folders.music")
+                })
+                .on_click({
+                    let folder = folder.clone();
+                    let update = update.clone();
+                    move |_, _, cx| {
+                        update(
+                            folder.clone(),
+                            Box::new(move |root| {
+                                root.content = if audiobooks {
+                                    ContentType::Music
+                                } else {
+                                    ContentType::Audiobooks
+                                }
+                            }),
+                            cx,
+                        )
+                    }
+                }),
+        )
+        .child(
+            button()
+                .id(("folder-root-lower", index))
+                .tooltip(t!(cx, "folders.lower_priority"))
+                .child(icon(MINUS).size(spx(14.0)).my_auto())
+                .on_click({
+                    let folder = folder.clone();
+                    let update = update.clone();
+                    move |_, _, cx| update(folder.clone(), Box::new(|root| root.priority -= 1), cx)
+                }),
+        )
+        .child(
+            div()
+                .min_w(spx(24.0))
+                .flex()
+                .justify_center()
+                .child(SharedString::from(priority.to_string())),
+        )
+        .child(
+            button()
+                .id(("folder-root-raise", index))
+                .tooltip(t!(cx, "folders.raise_priority"))
+                .child(icon(PLUS).size(spx(14.0)).my_auto())
+                .on_click({
+                    let folder = folder.clone();
+                    move |_, _, cx| update(folder.clone(), Box::new(|root| root.priority += 1), cx)
+                }),
+        )
+        .child(
+            button()
+                .id(("folder-root-remove", index))
+                .intent(ButtonIntent::Danger)
+                .tooltip(t!(cx, "folders.remove_folder"))
+                .child(icon(TRASH).size(spx(14.0)).my_auto())
+                .on_click({
+                    let view = view.clone();
+                    move |_, _, cx| {
+                        cx.stop_propagation();
+                        view.update(cx, |this, cx| this.remove_root(&folder, cx))
+                            .ok();
+                    }
+                }),
+        )
+}
+
+impl Render for FolderView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let entries = self.entries.clone();
+        let roots = self.location.is_none().then(|| self.roots.clone());
+        let current_track = cx.global::<PlaybackInfo>().current_track.read(cx).clone();
+        let view = cx.entity().downgrade();
+
+        let title = match &self.location {
+            Some(location) if location.folder == location.root => {
+                SharedString::from(location.root.to_string_lossy().to_string())
+            }
+            Some(location) => file_name(&location.folder),
+            None => t!(cx, "sidebar.folders"),
+        };
+
+        let status = if self.loading {
+            Some(t!(cx, "folders.loading"))
+        } else if self.entries.is_empty() {
+            Some(if self.location.is_some() {
+                t!(cx, "folders.empty")
+            } else {
+                t!(cx, "folders.no_scan_paths")
+            })
+        } else {
+            None
+        };
+
+        let crumbs = self
+            .breadcrumbs()
+            .into_iter()
+            .enumerate()
+            .map(|(i, crumb)| {
+                let label = if crumb.folder == crumb.root {
+                    SharedString::from(crumb.root.to_string_lossy().to_string())
+                } else {
+                    file_name(&crumb.folder)
+                };
+
+                div()
+                    .id(("folder-crumb", i))
+                    .flex()
+                    .child(div().mx(spx(6.0)).child("/"))
+                    .child(
+                        div()
+                            .cursor_pointer()
+                            .hover(|this| this.text_color(theme.text))
+                            .child(label),
+                    )
+                    .on_click(cx.listener(move |this, _, _, cx| this.open(crumb.clone(), cx)))
+            });
+
+        div()
+            .id("folder-view")
+            .pt(spx(10.0))
+            .flex()
+            .flex_col()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .max_w(spx(1000.0))
+            .h_full()
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .flex()
+                    .flex_col()
+                    .w_full()
+                    .overflow_x_hidden()
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .text_sm()
+                            .text_color(theme.text_secondary)
+                            .child(
+                                div()
+                                    .id("folder-crumb-root")
+                                    .cursor_pointer()
+                                    .hover(|this| this.text_color(theme.text))
+                                    .child(t!(cx, "sidebar.folders"))
+                                    .on_click(cx.listener(|this, _, _, cx| this.show_roots(cx))),
+                            )
+                            .children(crumbs),
+                    )
+                    .child(
+                        div()
+                            .font_weight(FontWeight::EXTRA_BOLD)
+                            .text_size(rems(2.5))
+                            .line_height(rems(2.75))
+                            .overflow_x_hidden()
+                            .py(spx(10.0))
+                            .w_full()
+                            .text_ellipsis()
+                            .child(title),
+                    )
+                    .when(self.location.is_some(), |this| {
+                        this.child(
+                            div()
+                                .gap(spx(10.0))
+                                .flex()
+                                .child(
+                                    button()
+                                        .id("folder-play-button")
+                                        .size(ButtonSize::Large)
+                                        .font_weight(FontWeight::SEMIBOLD)
+                                        .intent(ButtonIntent::Primary)
+                                        .child(icon(PLAY).size(spx(16.0)).my_auto())
+                                        .child(t!(cx, "folders.play"))
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.queue_folder(true, cx)
+                                        })),
+                                )
+                                .child(
+                                    button()
+                                        .id("folder-add-button")
+                                        .tooltip(t!(cx, "folders.add_to_queue"))
+                                        .size(ButtonSize::Large)
+                                        .flex_none()
+                                        .child(icon(CIRCLE_PLUS).size(spx(16.0)).my_auto())
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.queue_folder(false, cx)
+                                        })),
+                                ),
+                        )
+                    })
+                    .when(self.location.is_none(), |this| {
+                        this.child(
+                            div().flex().child(
+                                button()
+                                    .id("folder-add-root-button")
+                                    .size(ButtonSize::Large)
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .child(icon(PLUS).size(spx(16.0)).my_auto())
+                                    .child(t!(cx, "folders.add_folder"))
+                                    .on_click(cx.listener(|this, _, _, cx| this.add_root(cx))),
+                            ),
+                        )
+                    }),
+            )
+            .when_some(status, |this, status| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(18.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(status),
+                )
+            })
+            .child(
+                uniform_list("folder-list", entries.len(), move |range, _, cx| {
+                    let theme = cx.global::<Theme>();
+
+                    range
+                        .map(|index| {
+                            let entry = &entries[index];
+                            let is_current = current_track
+                                .as_ref()
+                                .is_some_and(|track| *track == entry.path);
+                            let view = view.clone();
+
+                            let (name, detail, duration) = match &entry.kind {
+                                EntryKind::Folder => (entry.name.clone(), None, None),
+                                EntryKind::File(Some(track)) => (
+                                    SharedString::from(track.title.to_string()),
+                                    track
+                                        .artist_names
+                                        .as_ref()
+                                        .map(|artists| SharedString::from(artists.to_string())),
+                                    Some(format!(
+                                        "{}:{:02}",
+                                        track.duration / 60,
+                                        track.duration % 60
+                                    )),
+                                ),
+                                EntryKind::File(None) => (
+                                    entry.name.clone(),
+                                    Some(t!(cx, "folders.not_in_library")),
+                                    None,
+                                ),
+                            };
+
+                            div()
+                                .id(("folder-entry", index))
+                                .flex()
+                                .items_center()
+                                .gap(spx(12.0))
+                                .px(spx(18.0))
+                                .py(spx(6.0))
+                                .w_full()
+                                .border_b_1()
+                                .border_color(theme.border_color)
+                                .cursor_pointer()
+                                .when(is_current, |this| this.bg(theme.queue_item_current))
+                                .hover(|this| this.bg(theme.nav_button_hover))
+                                .active(|this| this.bg(theme.nav_button_active))
+                                .when(matches!(entry.kind, EntryKind::Folder), |this| {
+                                    this.child(icon(FOLDER).size(spx(16.0)).flex_shrink_0())
+                                })
+                                .child(
+                                    div()
+                                        .flex_shrink()
+                                        .overflow_x_hidden()
+                                        .text_ellipsis()
+                                        .whitespace_nowrap()
+                                        .child(name),
+                                )
+                                .when_some(detail, |this, detail| {
+                                    this.child(
+                                        div()
+                                            .flex_shrink()
+                                            .overflow_x_hidden()
+                                            .text_ellipsis()
+                                            .whitespace_nowrap()
+                                            .text_color(theme.text_secondary)
+                                            .child(detail),
+                                    )
+                                })
+                                .when_some(duration, |this, duration| {
+                                    this.child(div().ml_auto().flex_shrink_0().child(duration))
+                                })
+                                .when_some(
+                                    roots.as_ref().and_then(|roots| roots.get(index)),
+                                    |this, root| this.child(root_options(index, root, &view, cx)),
+                                )
+                                .on_click(move |_, _, cx| {
+                                    view.update(cx, |this, cx| this.open_entry(index, cx)).ok();
+                                })
+                        })
+                        .collect()
+                })
+                .w_full()
+                .h_full()
+                .flex()
+                .flex_col()
+                .border_color(theme.border_color)
+                .border_t_1()
+                .mt(spx(18.0)),
+            )
+    }
+}
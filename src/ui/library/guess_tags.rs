@@ -0,0 +1,254 @@
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    Styled, Window, div, prelude::FluentBuilder,
+};
+use tracing::{error, info};
+
+use crate::{
+    library::{
+        db::{AlbumMethod, LibraryAccess},
+        guess::{GuessedTags, guess_tags},
+        scan::ScanInterface,
+        types::Track,
+    },
+    media::tags::{TagChanges, TagField, write_tags},
+    settings::SettingsGlobal,
+    ui::{
+        components::{
+            button::{ButtonIntent, button},
+            modal::modal,
+        },
+        locale::t,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// A field that differs between the track and the guess.
+struct GuessRow {
+    field: TagField,
+    label: SharedString,
+    current: Option<String>,
+    guessed: String,
+}
+
+/// A dialog that guesses a track's tags from its file name, and writes them once confirmed.
+pub struct GuessTags {
+    show: Entity<bool>,
+    track: Track,
+    rows: Vec<GuessRow>,
+}
+
+impl GuessTags {
+    pub fn new(cx: &mut App, show: Entity<bool>, track: Track) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                // the patterns could have changed since the dialog was last opened
+                if *show.read(cx) {
+                    this.rows = this.guess(cx);
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            Self {
+                show,
+                track,
+                rows: Vec::new(),
+            }
+        })
+    }
+
+    fn guess(&self, cx: &mut App) -> Vec<GuessRow> {
+        let patterns = &cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .scanning
+            .filename_patterns;
+
+        let Some(guess) = guess_tags(&self.track.location, patterns) else {
+            return Vec::new();
+        };
+        let GuessedTags {
+            title,
+            artist,
+            album,
+            track,
+            disc,
+        } = guess;
+
+        let current_album = self
+            .track
+            .album_id
+            .and_then(|id| cx.get_album_by_id(id, AlbumMethod::Metadata).ok())
+            .map(|album| album.title.to_string());
+
+        let fields = [
+            (
+                TagField::Title,
+                t!(cx, "guess.field.title"),
+                Some(self.track.title.to_string()),
+                title,
+            ),
+            (
+                TagField::Artist,
+                t!(cx, "guess.field.artist"),
+                self.track.artist_names.as_ref().map(|v| v.to_string()),
+                artist,
+            ),
+            (
+                TagField::Album,
+                t!(cx, "guess.field.album"),
+                current_album,
+                album,
+            ),
+            (
+                TagField::TrackNumber,
+                t!(cx, "guess.field.track"),
+                self.track.track_number.map(|v| v.to_string()),
+                track.map(|v| v.to_string()),
+            ),
+            (
+                TagField::DiscNumber,
+                t!(cx, "guess.field.disc"),
+                self.track.disc_number.map(|v| v.to_string()),
+                disc.map(|v| v.to_string()),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(field, label, current, guessed)| {
+                let guessed = guessed?;
+                (current.as_ref() != Some(&guessed)).then_some(GuessRow {
+                    field,
+                    label,
+                    current,
+                    guessed,
+                })
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, cx: &mut Context<Self>) {
+        let path = self.track.location.clone();
+        let changes = TagChanges {
+            text: self
+                .rows
+                .iter()
+                .map(|row| (row.field, row.guessed.clone()))
+                .collect(),
+            ..Default::default()
+        };
+
+        cx.spawn(async move |_, cx| {
+            let result = crate::RUNTIME
+                .spawn_blocking(move || write_tags(&path, &changes))
+                .await;
+
+            match result {
+                Ok(Ok(())) => {
+                    info!("Wrote guessed tags, rescanning");
+                    // the scan picks up the new tags, since the file has changed
+                    cx.update(|cx| cx.global::<ScanInterface>().scan()).ok();
+                }
+                Ok(Err(err)) => error!("Failed to write guessed tags: {err}"),
+                Err(err) => error!("Failed to write guessed tags: {err}"),
+            }
+        })
+        .detach();
+
+        self.show.write(cx, false);
+    }
+}
+
+fn guess_row(cx: &App, row: &GuessRow) -> impl IntoElement + use<> {
+    let theme = cx.global::<Theme>();
+
+    div()
+        .flex()
+        .gap(spx(12.0))
+        .child(
+            div()
+                .w(spx(110.0))
+                .flex_shrink_0()
+                .text_color(theme.text_secondary)
+                .child(row.label.clone()),
+        )
+        .child(
+            div()
+                .flex_1()
+                .min_w(spx(0.0))
+                .text_color(theme.text_secondary)
+                .line_through()
+                .child(row.current.clone().unwrap_or_default()),
+        )
+        .child(div().flex_1().min_w(spx(0.0)).child(row.guessed.clone()))
+}
+
+impl Render for GuessTags {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let show_cancel = self.show.clone();
+
+        let body = if self.rows.is_empty() {
+            vec![div().child(t!(cx, "guess.no_match")).into_any_element()]
+        } else {
+            self.rows
+                .iter()
+                .map(|row| guess_row(cx, row).into_any_element())
+                .collect()
+        };
+
+        modal()
+            .child(
+                div()
+                    .w(spx(500.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .pb(spx(6.0))
+                            .child(t!(cx, "guess.title")),
+                    )
+                    .children(body)
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap(spx(8.0))
+                            .pt(spx(10.0))
+                            .child(
+                                button()
+                                    .id("guess-cancel")
+                                    .on_click(move |_, _, cx| show_cancel.write(cx, false))
+                                    .child(t!(cx, "guess.cancel")),
+                            )
+                            .when(!self.rows.is_empty(), |this| {
+                                this.child(
+                                    button()
+                                        .id("guess-apply")
+                                        .intent(ButtonIntent::Primary)
+                                        .on_click(cx.listener(|this, _, _, cx| this.apply(cx)))
+                                        .child(t!(cx, "guess.apply")),
+                                )
+                            }),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,196 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use gpui::*;
+use prelude::FluentBuilder;
+use rustc_hash::FxHashMap;
+use tracing::error;
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        types::{Album, AlbumFormat},
+    },
+    ui::{
+        locale::{Locale, t},
+        models::Models,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+use super::{
+    ViewSwitchMessage,
+    artist_view::{album_formats, album_row},
+};
+
+/// Lists the record labels albums in the library were released on.
+pub struct LabelsView {
+    /// The name of each label, the id of one of its albums, and how many albums it has.
+    labels: Vec<(String, i64, i64)>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+}
+
+impl LabelsView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| LabelsView {
+            labels: cx.list_labels().unwrap_or_else(|err| {
+                error!("Failed to list labels: {err}");
+                Vec::new()
+            }),
+            view_switcher: cx.global::<Models>().switcher_model.clone(),
+        })
+    }
+}
+
+impl Render for LabelsView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let locale = cx.global::<Locale>();
+
+        div()
+            .id("labels-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .pb(spx(10.0))
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .child(t!(cx, "sidebar.labels")),
+            )
+            .when(self.labels.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "labels.empty")),
+                )
+            })
+            .children(self.labels.iter().map(|(name, album_id, count)| {
+                let album_id = *album_id;
+                let view_switcher = self.view_switcher.clone();
+
+                div()
+                    .id(("label", album_id as u64))
+                    .flex()
+                    .items_center()
+                    .px(spx(18.0))
+                    .py(spx(8.0))
+                    .gap(spx(12.0))
+                    .w_full()
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.nav_button_hover))
+                    .active(|this| this.bg(theme.nav_button_active))
+                    .on_click(move |_, _, cx| {
+                        view_switcher
+                            .update(cx, |_, cx| cx.emit(ViewSwitchMessage::Label(album_id)))
+                    })
+                    .child(
+                        div()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .flex_shrink()
+                            .truncate()
+                            .child(SharedString::from(name.clone())),
+                    )
+                    .child(
+                        div()
+                            .ml_auto()
+                            .flex_shrink_0()
+                            .text_sm()
+                            .text_color(theme.text_secondary)
+                            .child(locale.plural("library.album_count", *count)),
+                    )
+            }))
+            .child(div().h(spx(24.0)))
+    }
+}
+
+/// Lists the albums released on a record label, by catalog number.
+pub struct LabelView {
+    name: SharedString,
+    albums: Arc<Vec<Album>>,
+    formats: FxHashMap<i64, AlbumFormat>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+}
+
+impl LabelView {
+    /// Shows the label the album was released on.
+    pub(super) fn new(cx: &mut App, album_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let albums = cx.list_albums_by_label(album_id).unwrap_or_else(|err| {
+                error!("Failed to list albums by label: {err}");
+                Arc::new(Vec::new())
+            });
+            let name = albums
+                .iter()
+                .find(|album| album.id == album_id)
+                .or(albums.first())
+                .and_then(|album| album.label.clone())
+                .map(SharedString::from)
+                .unwrap_or_default();
+
+            LabelView {
+                name,
+                formats: album_formats(cx, &albums),
+                albums,
+                view_switcher: cx.global::<Models>().switcher_model.clone(),
+            }
+        })
+    }
+}
+
+impl Render for LabelView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        div()
+            .id("label-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .truncate()
+                    .child(self.name.clone()),
+            )
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .pb(spx(6.0))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .child(
+                        cx.global::<Locale>()
+                            .plural("library.album_count", self.albums.len() as i64),
+                    ),
+            )
+            .children(self.albums.iter().map(|album| {
+                let format = self.formats.get(&album.id);
+                album_row(album, format, self.view_switcher.clone(), true, cx)
+            }))
+            .child(div().h(spx(24.0)))
+    }
+}
@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    Styled, Window, div, prelude::FluentBuilder,
+};
+use nucleo::Utf32String;
+use tracing::error;
+
+use crate::{
+    library::db::LibraryAccess,
+    ui::{
+        components::{
+            button::{ButtonIntent, button},
+            modal::modal,
+            palette::{FinderItemLeft, Palette, PaletteItem},
+        },
+        library::{ViewSwitchMessage, library_changed},
+        locale::t,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// An album that the album being viewed can be merged with.
+#[derive(PartialEq)]
+struct MergeCandidate {
+    id: i64,
+    title: SharedString,
+    artist: SharedString,
+}
+
+impl PaletteItem for MergeCandidate {
+    fn left_content(&self, _: &mut App) -> Option<FinderItemLeft> {
+        Some(FinderItemLeft::Image(
+            format!("!db://album/{}/thumb", self.id).into(),
+        ))
+    }
+
+    fn middle_content(&self, cx: &mut App) -> SharedString {
+        t!(cx, "merge.merge_with", title = self.title)
+    }
+
+    fn right_content(&self, _: &mut App) -> Option<SharedString> {
+        Some(self.artist.clone())
+    }
+}
+
+type MatcherFunc = Box<dyn Fn(&Arc<MergeCandidate>, &mut App) -> Utf32String + 'static>;
+type OnAccept = Box<dyn Fn(&Arc<MergeCandidate>, &mut App) + 'static>;
+
+/// Which of the two albums being merged something is kept from.
+#[derive(Clone, Copy, PartialEq)]
+enum Side {
+    This,
+    Other,
+}
+
+fn load_candidates(cx: &mut App, album_id: i64) -> Vec<Arc<MergeCandidate>> {
+    cx.list_albums_search()
+        .unwrap_or_else(|err| {
+            error!("Failed to list albums: {err}");
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|(id, _, _)| *id as i64 != album_id)
+        .map(|(id, title, artist)| {
+            Arc::new(MergeCandidate {
+                id: id as i64,
+                title: title.into(),
+                artist: artist.into(),
+            })
+        })
+        .collect()
+}
+
+/// A dialog that picks another album and merges it with the album being viewed, keeping the
+/// details and art of either one.
+pub struct MergeAlbums {
+    show: Entity<bool>,
+    album_id: i64,
+    album_title: SharedString,
+    palette: Entity<Palette<MergeCandidate, MatcherFunc, OnAccept>>,
+    /// The album picked to be merged, waiting on the user to confirm.
+    other: Option<Arc<MergeCandidate>>,
+    details_from: Side,
+    art_from: Side,
+    error: Option<SharedString>,
+}
+
+impl MergeAlbums {
+    pub fn new(
+        cx: &mut App,
+        show: Entity<bool>,
+        album_id: i64,
+        album_title: SharedString,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, move |this: &mut Self, show, cx| {
+                // albums could have been added or merged since the dialog was last opened
+                if *show.read(cx) {
+                    let candidates = load_candidates(cx, album_id);
+
+                    this.palette.update(cx, |palette, cx| {
+                        cx.emit(candidates);
+                        palette.reset(cx);
+                    });
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let matcher: MatcherFunc =
+                Box::new(|album, _| format!("{} {}", album.title, album.artist).into());
+
+            let this = cx.weak_entity();
+            let on_accept: OnAccept = Box::new(move |album, cx| {
+                this.update(cx, |this, cx| {
+                    this.other = Some(album.clone());
+                    this.details_from = Side::This;
+                    this.art_from = Side::This;
+                    this.error = None;
+                    this.show.write(cx, false);
+                })
+                .ok();
+            });
+
+            let items = load_candidates(cx, album_id);
+            let palette = Palette::new(cx, items, matcher, on_accept);
+
+            Self {
+                show,
+                album_id,
+                album_title,
+                palette,
+                other: None,
+                details_from: Side::This,
+                art_from: Side::This,
+                error: None,
+            }
+        })
+    }
+
+    fn merge(&mut self, cx: &mut Context<Self>) {
+        let Some(other) = self.other.clone() else {
+            return;
+        };
+
+        let (kept, removed) = match self.details_from {
+            Side::This => (self.album_id, other.id),
+            Side::Other => (other.id, self.album_id),
+        };
+
+        match cx.merge_albums(kept, removed, self.art_from != self.details_from) {
+            Ok(()) => {
+                self.other = None;
+                cx.notify();
+                library_changed(
+                    cx,
+                    Some(ViewSwitchMessage::Release(removed)),
+                    ViewSwitchMessage::Release(kept),
+                );
+            }
+            Err(err) => {
+                error!("Failed to merge albums: {err}");
+                self.error = Some(t!(cx, "merge.failed", error = err.to_string()));
+                cx.notify();
+            }
+        }
+    }
+
+    fn choice(
+        &self,
+        id: &'static str,
+        label: SharedString,
+        selected: Side,
+        set: fn(&mut Self, Side),
+        other_title: SharedString,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement + use<> {
+        let label_color = cx.global::<Theme>().text_secondary;
+        let option = |side: Side, title: SharedString, cx: &mut Context<Self>| {
+            button()
+                .id((id, side as usize))
+                .flex_1()
+                .min_w(spx(0.0))
+                .when(selected == side, |this| this.intent(ButtonIntent::Primary))
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    set(this, side);
+                    cx.notify();
+                }))
+                .child(div().truncate().child(title))
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(spx(4.0))
+            .pt(spx(6.0))
+            .child(div().text_color(label_color).child(label))
+            .child(
+                div()
+                    .flex()
+                    .gap(spx(6.0))
+                    .child(option(Side::This, self.album_title.clone(), cx))
+                    .child(option(Side::Other, other_title, cx)),
+            )
+    }
+}
+
+impl Render for MergeAlbums {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if let Some(other) = self.other.clone() {
+            let message = t!(
+                cx,
+                "merge.message",
+                this = self.album_title,
+                other = other.title
+            );
+            let error = self.error.clone();
+            let details = self.choice(
+                "merge-details",
+                t!(cx, "merge.keep_details"),
+                self.details_from,
+                |this, side| this.details_from = side,
+                other.title.clone(),
+                cx,
+            );
+            let art = self.choice(
+                "merge-art",
+                t!(cx, "merge.keep_art"),
+                self.art_from,
+                |this, side| this.art_from = side,
+                other.title.clone(),
+                cx,
+            );
+            let this = cx.weak_entity();
+            let theme = cx.global::<Theme>();
+
+            return modal()
+                .child(
+                    div()
+                        .w(spx(460.0))
+                        .p(spx(16.0))
+                        .flex()
+                        .flex_col()
+                        .gap(spx(6.0))
+                        .text_sm()
+                        .child(
+                            div()
+                                .font_weight(FontWeight::BOLD)
+                                .text_size(spx(18.0))
+                                .child(t!(cx, "merge.title")),
+                        )
+                        .child(div().text_color(theme.text_secondary).child(message))
+                        .child(details)
+                        .child(art)
+                        .when_some(error, |this, error| {
+                            this.child(
+                                div()
+                                    .pt(spx(6.0))
+                                    .text_color(theme.text_secondary)
+                                    .child(error),
+                            )
+                        })
+                        .child(
+                            div()
+                                .flex()
+                                .justify_end()
+                                .gap(spx(6.0))
+                                .pt(spx(10.0))
+                                .child(
+                                    button()
+                                        .id("merge-cancel")
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.other = None;
+                                            cx.notify();
+                                        }))
+                                        .child(t!(cx, "merge.cancel")),
+                                )
+                                .child(
+                                    button()
+                                        .id("merge-confirm")
+                                        .intent(ButtonIntent::Primary)
+                                        .on_click(cx.listener(|this, _, _, cx| this.merge(cx)))
+                                        .child(t!(cx, "merge.confirm")),
+                                ),
+                        ),
+                )
+                .on_exit(move |_, cx| {
+                    this.update(cx, |this, cx| {
+                        this.other = None;
+                        cx.notify();
+                    })
+                    .ok();
+                })
+                .into_any_element();
+        }
+
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let palette = self.palette.clone();
+
+        cx.update_entity(&palette, |palette, _| {
+            palette.focus(window);
+        });
+
+        modal()
+            .child(div().w(spx(550.0)).h(spx(300.0)).child(palette.clone()))
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
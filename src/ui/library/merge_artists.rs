@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    Styled, Window, div, prelude::FluentBuilder,
+};
+use nucleo::Utf32String;
+use tracing::error;
+
+use crate::{
+    library::db::LibraryAccess,
+    settings::{SettingsGlobal, save_settings},
+    ui::{
+        components::{
+            button::{ButtonIntent, button},
+            icons::USER,
+            modal::modal,
+            palette::{FinderItemLeft, Palette, PaletteItem},
+        },
+        library::{ViewSwitchMessage, library_changed},
+        locale::t,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// An artist that the artist being viewed can be merged with.
+#[derive(PartialEq)]
+struct MergeCandidate {
+    id: i64,
+    name: SharedString,
+    image_hash: Option<String>,
+}
+
+impl PaletteItem for MergeCandidate {
+    fn left_content(&self, _: &mut App) -> Option<FinderItemLeft> {
+        Some(match &self.image_hash {
+            Some(hash) => {
+                FinderItemLeft::Image(format!("!db://artist/{}/image?{hash}", self.id).into())
+            }
+            None => FinderItemLeft::Icon(USER.into()),
+        })
+    }
+
+    fn middle_content(&self, cx: &mut App) -> SharedString {
+        t!(cx, "merge.merge_with", title = self.name)
+    }
+
+    fn right_content(&self, _: &mut App) -> Option<SharedString> {
+        None
+    }
+}
+
+type MatcherFunc = Box<dyn Fn(&Arc<MergeCandidate>, &mut App) -> Utf32String + 'static>;
+type OnAccept = Box<dyn Fn(&Arc<MergeCandidate>, &mut App) + 'static>;
+
+fn load_candidates(cx: &mut App, artist_id: i64) -> Vec<Arc<MergeCandidate>> {
+    cx.list_artists_search()
+        .unwrap_or_else(|err| {
+            error!("Failed to list artists: {err}");
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|(id, _, _)| *id as i64 != artist_id)
+        .map(|(id, name, image_hash)| {
+            Arc::new(MergeCandidate {
+                id: id as i64,
+                name: name.into(),
+                image_hash,
+            })
+        })
+        .collect()
+}
+
+/// Makes `removed` an alias of `kept`, so that tracks tagged with it are given to `kept` when
+/// they're scanned. Aliases of `removed` become aliases of `kept` too.
+fn add_alias(cx: &mut App, removed: &str, kept: &str) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+
+    settings.update(cx, |settings, cx| {
+        let aliases = &mut settings.scanning.artist_aliases;
+
+        for name in aliases.values_mut().filter(|name| name.as_str() == removed) {
+            *name = kept.to_string();
+        }
+
+        aliases.remove(kept);
+        aliases.insert(removed.to_string(), kept.to_string());
+        cx.notify();
+    });
+
+    save_settings(cx);
+}
+
+/// A dialog that picks another artist and merges it with the artist being viewed, keeping
+/// either one's name. The other name becomes an alias, so that scanning doesn't bring it back.
+pub struct MergeArtists {
+    show: Entity<bool>,
+    artist_id: i64,
+    artist_name: SharedString,
+    palette: Entity<Palette<MergeCandidate, MatcherFunc, OnAccept>>,
+    /// The artist picked to be merged, waiting on the user to confirm.
+    other: Option<Arc<MergeCandidate>>,
+    /// Whether the name of the artist being viewed is kept, rather than the other artist's.
+    keep_this: bool,
+    error: Option<SharedString>,
+}
+
+impl MergeArtists {
+    pub fn new(
+        cx: &mut App,
+        show: Entity<bool>,
+        artist_id: i64,
+        artist_name: SharedString,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, move |this: &mut Self, show, cx| {
+                // artists could have been added or merged since the dialog was last opened
+                if *show.read(cx) {
+                    let candidates = load_candidates(cx, artist_id);
+
+                    this.palette.update(cx, |palette, cx| {
+                        cx.emit(candidates);
+                        palette.reset(cx);
+                    });
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let matcher: MatcherFunc = Box::new(|artist, _| artist.name.to_string().into());
+
+            let this = cx.weak_entity();
+            let on_accept: OnAccept = Box::new(move |artist, cx| {
+                this.update(cx, |this, cx| {
+                    this.other = Some(artist.clone());
+                    this.keep_this = true;
+                    this.error = None;
+                    this.show.write(cx, false);
+                })
+                .ok();
+            });
+
+            let items = load_candidates(cx, artist_id);
+            let palette = Palette::new(cx, items, matcher, on_accept);
+
+            Self {
+                show,
+                artist_id,
+                artist_name,
+                palette,
+                other: None,
+                keep_this: true,
+                error: None,
+            }
+        })
+    }
+
+    fn merge(&mut self, cx: &mut Context<Self>) {
+        let Some(other) = self.other.clone() else {
+            return;
+        };
+
+        let this = (self.artist_id, self.artist_name.clone());
+        let that = (other.id, other.name.clone());
+        let (kept, removed) = if self.keep_this {
+            (this, that)
+        } else {
+            (that, this)
+        };
+
+        match cx.merge_artists(kept.0, removed.0) {
+            Ok(()) => {
+                self.other = None;
+                cx.notify();
+                add_alias(cx, &removed.1, &kept.1);
+                library_changed(
+                    cx,
+                    Some(ViewSwitchMessage::Artist(removed.0)),
+                    ViewSwitchMessage::Artist(kept.0),
+                );
+            }
+            Err(err) => {
+                error!("Failed to merge artists: {err}");
+                self.error = Some(t!(cx, "merge.artists_failed", error = err.to_string()));
+                cx.notify();
+            }
+        }
+    }
+}
+
+impl Render for MergeArtists {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if let Some(other) = self.other.clone() {
+            let message = t!(
+                cx,
+                "merge.artists_message",
+                this = self.artist_name,
+                other = other.name
+            );
+            let option = |keep_this: bool, name: SharedString, cx: &mut Context<Self>| {
+                button()
+                    .id(("merge-artist-name", keep_this as usize))
+                    .flex_1()
+                    .min_w(spx(0.0))
+                    .when(self.keep_this == keep_this, |this| {
+                        this.intent(ButtonIntent::Primary)
+                    })
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.keep_this = keep_this;
+                        cx.notify();
+                    }))
+                    .child(div().truncate().child(name))
+            };
+            let keep_this = option(true, self.artist_name.clone(), cx);
+            let keep_other = option(false, other.name.clone(), cx);
+            let error = self.error.clone();
+            let this = cx.weak_entity();
+            let theme = cx.global::<Theme>();
+
+            return modal()
+                .child(
+                    div()
+                        .w(spx(460.0))
+                        .p(spx(16.0))
+                        .flex()
+                        .flex_col()
+                        .gap(spx(6.0))
+                        .text_sm()
+                        .child(
+                            div()
+                                .font_weight(FontWeight::BOLD)
+                                .text_size(spx(18.0))
+                                .child(t!(cx, "merge.artists_title")),
+                        )
+                        .child(div().text_color(theme.text_secondary).child(message))
+                        .child(
+                            div()
+                                .pt(spx(6.0))
+                                .text_color(theme.text_secondary)
+                                .child(t!(cx, "merge.keep_name")),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .gap(spx(6.0))
+                                .child(keep_this)
+                                .child(keep_other),
+                        )
+                        .when_some(error, |this, error| {
+                            this.child(
+                                div()
+                                    .pt(spx(6.0))
+                                    .text_color(theme.text_secondary)
+                                    .child(error),
+                            )
+                        })
+                        .child(
+                            div()
+                                .flex()
+                                .justify_end()
+                                .gap(spx(6.0))
+                                .pt(spx(10.0))
+                                .child(
+                                    button()
+                                        .id("merge-artists-cancel")
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.other = None;
+                                            cx.notify();
+                                        }))
+                                        .child(t!(cx, "merge.cancel")),
+                                )
+                                .child(
+                                    button()
+                                        .id("merge-artists-confirm")
+                                        .intent(ButtonIntent::Primary)
+                                        .on_click(cx.listener(|this, _, _, cx| this.merge(cx)))
+                                        .child(t!(cx, "merge.confirm")),
+                                ),
+                        ),
+                )
+                .on_exit(move |_, cx| {
+                    this.update(cx, |this, cx| {
+                        this.other = None;
+                        cx.notify();
+                    })
+                    .ok();
+                })
+                .into_any_element();
+        }
+
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let palette = self.palette.clone();
+
+        cx.update_entity(&palette, |palette, _| {
+            palette.focus(window);
+        });
+
+        modal()
+            .child(div().w(spx(550.0)).h(spx(300.0)).child(palette.clone()))
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,408 @@
+use std::sync::Arc;
+
+use gpui::*;
+use prelude::FluentBuilder;
+use tracing::{error, warn};
+
+use crate::{
+    library::{
+        db::{AlbumMethod, LibraryAccess},
+        types::{Album, Artist, Track},
+    },
+    media::metadata::parse_mbid,
+    services::musicbrainz::{fetch_album_cover, link_album_release, unlink_album_release},
+    ui::{
+        app::{ActiveLibrary, Pool},
+        components::{
+            button::{ButtonIntent, button},
+            input::{EnrichedInputAction, TextInput},
+            modal::modal,
+        },
+        locale::t,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+enum LinkState {
+    Idle,
+    Working,
+    Done(SharedString),
+    Failed(SharedString),
+}
+
+/// A dialog showing the MusicBrainz IDs of an album, its artist and its tracks, which can link the
+/// album to a different release.
+pub struct MusicBrainzInfo {
+    show: Entity<bool>,
+    album: Arc<Album>,
+    artist: Option<Arc<Artist>>,
+    tracks: Arc<Vec<Track>>,
+    input: Entity<TextInput>,
+    focus_handle: FocusHandle,
+    focused: bool,
+    draft: String,
+    state: LinkState,
+}
+
+impl MusicBrainzInfo {
+    pub fn new(cx: &mut App, show: Entity<bool>, album: Arc<Album>) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                if *show.read(cx) {
+                    this.reload(cx);
+                    this.focused = false;
+                    this.state = LinkState::Idle;
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let focus_handle = cx.focus_handle();
+            let this = cx.weak_entity();
+            let handler = move |action, _: &mut Window, cx: &mut App| {
+                if let EnrichedInputAction::Accept = action {
+                    this.update(cx, |this, cx| this.link(cx)).ok();
+                }
+            };
+
+            let placeholder = t!(cx, "musicbrainz.link_placeholder");
+            let input = TextInput::new(
+                cx,
+                focus_handle.clone(),
+                None,
+                Some(placeholder),
+                Some(Box::new(handler)),
+            );
+
+            cx.subscribe(&input, |this: &mut Self, _, ev: &String, _| {
+                this.draft = ev.clone();
+            })
+            .detach();
+
+            let artist = cx.get_artist_by_id(album.artist_id).ok();
+            let tracks = cx.list_tracks_in_album(album.id).unwrap_or_else(|err| {
+                error!("Failed to list tracks in album: {err}");
+                Arc::new(Vec::new())
+            });
+
+            Self {
+                show,
+                album,
+                artist,
+                tracks,
+                input,
+                focus_handle,
+                focused: false,
+                draft: String::new(),
+                state: LinkState::Idle,
+            }
+        })
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        match cx.get_album_by_id(self.album.id, AlbumMethod::Metadata) {
+            Ok(album) => self.album = album,
+            Err(err) => error!("Failed to reload album: {err}"),
+        }
+
+        self.artist = cx.get_artist_by_id(self.album.artist_id).ok();
+    }
+
+    fn link(&mut self, cx: &mut Context<Self>) {
+        let Some(mbid) = parse_mbid(&self.draft) else {
+            self.state = LinkState::Failed(t!(cx, "musicbrainz.invalid"));
+            cx.notify();
+            return;
+        };
+
+        let write_pool = cx.global::<Pool>().write.clone();
+        let album_id = self.album.id;
+        let artist_id = self.album.artist_id;
+        self.state = LinkState::Working;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result =
+                crate::RUNTIME
+                    .spawn(async move {
+                        link_album_release(&write_pool, album_id, artist_id, &mbid).await
+                    })
+                    .await;
+
+            this.update(cx, |this, cx| {
+                this.state = match result {
+                    Ok(Ok(release)) => {
+                        LinkState::Done(t!(cx, "musicbrainz.linked", title = release.title))
+                    }
+                    Ok(Err(err)) => {
+                        warn!(?err, "Could not link album to release: {err}");
+                        LinkState::Failed(t!(cx, "musicbrainz.link_failed"))
+                    }
+                    Err(err) => {
+                        warn!(?err, "Could not link album to release: {err}");
+                        LinkState::Failed(t!(cx, "musicbrainz.link_failed"))
+                    }
+                };
+
+                this.reload(cx);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn unlink(&mut self, cx: &mut Context<Self>) {
+        let write_pool = cx.global::<Pool>().write.clone();
+        let album_id = self.album.id;
+
+        if let Err(err) = crate::RUNTIME.block_on(unlink_album_release(&write_pool, album_id)) {
+            error!("Failed to unlink album from release: {err}");
+        }
+
+        self.state = LinkState::Idle;
+        self.reload(cx);
+        cx.notify();
+    }
+
+    fn fetch_cover(&mut self, cx: &mut Context<Self>) {
+        let read_pool = cx.global::<Pool>().read.clone();
+        let write_pool = cx.global::<Pool>().write.clone();
+        let art_dir = cx.global::<ActiveLibrary>().art_dir();
+        let album_id = self.album.id;
+        self.state = LinkState::Working;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move {
+                    fetch_album_cover(&read_pool, &write_pool, &art_dir, album_id).await
+                })
+                .await;
+
+            this.update(cx, |this, cx| {
+                this.state = match result {
+                    Ok(Ok(())) => LinkState::Done(t!(cx, "musicbrainz.cover_fetched")),
+                    Ok(Err(err)) => {
+                        warn!(?err, "Could not fetch cover art: {err}");
+                        LinkState::Failed(t!(cx, "musicbrainz.cover_failed"))
+                    }
+                    Err(err) => {
+                        warn!(?err, "Could not fetch cover art: {err}");
+                        LinkState::Failed(t!(cx, "musicbrainz.cover_failed"))
+                    }
+                };
+
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+/// A labelled MusicBrainz ID, which opens the entity's page on MusicBrainz when clicked.
+fn mbid_row(
+    cx: &App,
+    id: ElementId,
+    label: SharedString,
+    kind: &str,
+    mbid: Option<&str>,
+) -> impl IntoElement + use<> {
+    let theme = cx.global::<Theme>();
+    let url = mbid.map(|mbid| format!("https://musicbrainz.org/{kind}/{mbid}"));
+
+    div()
+        .flex()
+        .gap(spx(12.0))
+        .child(
+            div()
+                .w(spx(140.0))
+                .flex_shrink_0()
+                .truncate()
+                .text_color(theme.text_secondary)
+                .child(label),
+        )
+        .child(
+            div()
+                .id(id)
+                .flex_1()
+                .min_w(spx(0.0))
+                .truncate()
+                .map(|this| match (mbid, url) {
+                    (Some(mbid), Some(url)) => this
+                        .cursor_pointer()
+                        .hover(|this| this.underline())
+                        .on_click(move |_, _, cx| cx.open_url(&url))
+                        .child(SharedString::from(mbid.to_string())),
+                    _ => this
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "musicbrainz.none")),
+                }),
+        )
+}
+
+impl Render for MusicBrainzInfo {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        if !self.focused {
+            self.focused = true;
+            self.focus_handle.focus(window);
+        }
+
+        let theme = cx.global::<Theme>();
+        let show = self.show.clone();
+        let show_close = self.show.clone();
+
+        let release_mbid = self.album.release_mbid();
+        let tagged_mbid = self.album.mbid.as_deref().filter(|mbid| *mbid != "none");
+        let is_linked = self.album.mbid_linked.is_some();
+        let working = matches!(self.state, LinkState::Working);
+
+        let status = match &self.state {
+            LinkState::Idle => None,
+            LinkState::Working => Some(t!(cx, "musicbrainz.working")),
+            LinkState::Done(message) | LinkState::Failed(message) => Some(message.clone()),
+        };
+
+        let track_rows = self.tracks.iter().map(|track| {
+            let label = match track.track_number {
+                Some(number) => format!("{number}. {}", track.title.0),
+                None => track.title.0.to_string(),
+            };
+
+            mbid_row(
+                cx,
+                ("mbid-track", track.id as u64).into(),
+                label.into(),
+                "recording",
+                track.mbid.as_deref(),
+            )
+            .into_any_element()
+        });
+
+        modal()
+            .child(
+                div()
+                    .w(spx(540.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .pb(spx(6.0))
+                            .child(t!(cx, "musicbrainz.title")),
+                    )
+                    .child(mbid_row(
+                        cx,
+                        "mbid-release".into(),
+                        t!(cx, "musicbrainz.release"),
+                        "release",
+                        release_mbid,
+                    ))
+                    .when(is_linked && tagged_mbid.is_some(), |this| {
+                        this.child(mbid_row(
+                            cx,
+                            "mbid-tagged-release".into(),
+                            t!(cx, "musicbrainz.tagged_release"),
+                            "release",
+                            tagged_mbid,
+                        ))
+                    })
+                    .child(mbid_row(
+                        cx,
+                        "mbid-artist".into(),
+                        t!(cx, "musicbrainz.artist"),
+                        "artist",
+                        self.artist
+                            .as_ref()
+                            .and_then(|artist| artist.mbid.as_deref()),
+                    ))
+                    .child(
+                        div()
+                            .pt(spx(6.0))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child(t!(cx, "musicbrainz.recordings")),
+                    )
+                    .child(
+                        div()
+                            .id("mbid-tracks")
+                            .flex()
+                            .flex_col()
+                            .gap(spx(6.0))
+                            .max_h(spx(220.0))
+                            .overflow_y_scroll()
+                            .children(track_rows),
+                    )
+                    .child(
+                        div()
+                            .mt(spx(10.0))
+                            .px(spx(8.0))
+                            .py(spx(5.0))
+                            .w_full()
+                            .line_height(spx(16.0))
+                            .border_1()
+                            .border_color(theme.border_color)
+                            .rounded(spx(4.0))
+                            .child(self.input.clone()),
+                    )
+                    .when_some(status, |this, status| {
+                        this.child(div().text_color(theme.text_secondary).child(status))
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .justify_end()
+                            .gap(spx(8.0))
+                            .pt(spx(10.0))
+                            .when(release_mbid.is_some() && !working, |this| {
+                                this.child(
+                                    button()
+                                        .id("mbid-fetch-cover")
+                                        .on_click(
+                                            cx.listener(|this, _, _, cx| this.fetch_cover(cx)),
+                                        )
+                                        .child(t!(cx, "musicbrainz.fetch_cover")),
+                                )
+                            })
+                            .when(is_linked && !working, |this| {
+                                this.child(
+                                    button()
+                                        .id("mbid-unlink")
+                                        .on_click(cx.listener(|this, _, _, cx| this.unlink(cx)))
+                                        .child(t!(cx, "musicbrainz.unlink")),
+                                )
+                            })
+                            .when(!working, |this| {
+                                this.child(
+                                    button()
+                                        .id("mbid-link")
+                                        .intent(ButtonIntent::Primary)
+                                        .on_click(cx.listener(|this, _, _, cx| this.link(cx)))
+                                        .child(t!(cx, "musicbrainz.link")),
+                                )
+                            })
+                            .child(
+                                button()
+                                    .id("mbid-close")
+                                    .on_click(move |_, _, cx| show_close.write(cx, false))
+                                    .child(t!(cx, "convert.close")),
+                            ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -1,27 +1,125 @@
 use std::collections::VecDeque;
 
-use gpui::*;
+use gpui::{prelude::FluentBuilder, *};
 use tracing::debug;
 
 use crate::{
     library::db::{AlbumMethod, LibraryAccess},
-    ui::components::{icons::ARROW_LEFT, nav_button::nav_button},
+    ui::components::{
+        icons::{ARROW_LEFT, ARROW_RIGHT, SIDEBAR},
+        nav_button::nav_button,
+        tooltip::tooltip,
+    },
+    ui::{locale::t, theme::Theme, util::spx},
 };
 
-use super::ViewSwitchMessage;
+use super::{ViewSwitchMessage, year_view::decade_of};
 
 pub(super) struct NavigationView {
     view_switcher_model: Entity<VecDeque<ViewSwitchMessage>>,
-    current_message: ViewSwitchMessage,
-    description: Option<SharedString>,
+    breadcrumbs: Vec<(ViewSwitchMessage, SharedString)>,
+    show_sidebar: Entity<bool>,
+}
+
+/// Returns the path to the given view, starting from the album list, with the name of each view
+/// along the way.
+fn breadcrumbs(message: ViewSwitchMessage, cx: &mut App) -> Vec<(ViewSwitchMessage, SharedString)> {
+    let albums = (ViewSwitchMessage::Albums, t!(cx, "sidebar.albums"));
+    let artist = |artist_id: i64, cx: &mut App| {
+        cx.get_artist_name_by_id(artist_id).ok().map(|name| {
+            (
+                ViewSwitchMessage::Artist(artist_id),
+                SharedString::from(name.to_string()),
+            )
+        })
+    };
+
+    match message {
+        ViewSwitchMessage::Albums => vec![albums],
+        ViewSwitchMessage::Folders => vec![(message, t!(cx, "sidebar.folders"))],
+        ViewSwitchMessage::Years => vec![(message, t!(cx, "sidebar.years"))],
+        ViewSwitchMessage::Labels => vec![(message, t!(cx, "sidebar.labels"))],
+        ViewSwitchMessage::RecentlyDeleted => vec![(message, t!(cx, "sidebar.recently_deleted"))],
+        ViewSwitchMessage::Podcasts => vec![(message, t!(cx, "sidebar.podcasts"))],
+        ViewSwitchMessage::Audiobooks => vec![(message, t!(cx, "sidebar.audiobooks"))],
+        ViewSwitchMessage::History => vec![(message, t!(cx, "sidebar.history"))],
+        ViewSwitchMessage::Recap(year) => vec![
+            (ViewSwitchMessage::History, t!(cx, "sidebar.history")),
+            (message, t!(cx, "recap.title", year = year)),
+        ],
+        ViewSwitchMessage::Podcast(id) => {
+            let podcasts = (ViewSwitchMessage::Podcasts, t!(cx, "sidebar.podcasts"));
+            let podcast = cx
+                .get_podcast(id)
+                .ok()
+                .map(|podcast| (message, SharedString::from(podcast.title.to_string())));
+
+            [Some(podcasts), podcast].into_iter().flatten().collect()
+        }
+        ViewSwitchMessage::Label(id) => {
+            let labels = (ViewSwitchMessage::Labels, t!(cx, "sidebar.labels"));
+            let label = cx
+                .get_album_by_id(id, AlbumMethod::Metadata)
+                .ok()
+                .and_then(|album| album.label.clone())
+                .map(|label| (message, SharedString::from(label.to_string())));
+
+            [Some(labels), label].into_iter().flatten().collect()
+        }
+        ViewSwitchMessage::Decade(decade) => vec![
+            (ViewSwitchMessage::Years, t!(cx, "sidebar.years")),
+            (message, t!(cx, "years.decade", decade = decade)),
+        ],
+        ViewSwitchMessage::Year(year) => {
+            let decade = decade_of(year);
+            vec![
+                (ViewSwitchMessage::Years, t!(cx, "sidebar.years")),
+                (
+                    ViewSwitchMessage::Decade(decade),
+                    t!(cx, "years.decade", decade = decade),
+                ),
+                (message, SharedString::from(year.to_string())),
+            ]
+        }
+        ViewSwitchMessage::Artist(id) => [Some(albums), artist(id, cx)]
+            .into_iter()
+            .flatten()
+            .collect(),
+        ViewSwitchMessage::Release(id) => {
+            let Ok(album) = cx.get_album_by_id(id, AlbumMethod::Metadata) else {
+                return vec![albums];
+            };
+
+            [
+                Some(albums),
+                artist(album.artist_id, cx),
+                Some((message, SharedString::from(album.title.to_string()))),
+            ]
+            .into_iter()
+            .flatten()
+            .collect()
+        }
+        ViewSwitchMessage::Playlist(id) => cx
+            .get_playlist(id)
+            .map(|playlist| vec![(message, SharedString::from(playlist.name.to_string()))])
+            .unwrap_or_default(),
+        ViewSwitchMessage::Tag(id) => cx
+            .get_tag(id)
+            .map(|tag| vec![(message, SharedString::from(tag.name.to_string()))])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
 }
 
 impl NavigationView {
     pub(super) fn new(
         cx: &mut App,
         view_switcher_model: Entity<VecDeque<ViewSwitchMessage>>,
+        show_sidebar: Entity<bool>,
     ) -> Entity<Self> {
         cx.new(|cx| {
+            cx.observe(&show_sidebar, |_, _, cx| cx.notify()).detach();
+
             let current_message = *view_switcher_model
                 .read(cx)
                 .back()
@@ -30,25 +128,20 @@ impl NavigationView {
             cx.observe(&view_switcher_model, |this: &mut NavigationView, m, cx| {
                 debug!("{:#?}", m.read(cx));
 
-                this.current_message = *m
+                let current_message = *m
                     .read(cx)
                     .back()
                     .expect("view_switcher_model should always have one element");
 
-                this.description = match this.current_message {
-                    ViewSwitchMessage::Release(id) => cx
-                        .get_album_by_id(id, AlbumMethod::Thumbnail)
-                        .ok()
-                        .map(|v| SharedString::from(v.title.clone())),
-                    _ => None,
-                }
+                this.breadcrumbs = breadcrumbs(current_message, cx);
+                cx.notify();
             })
             .detach();
 
             Self {
                 view_switcher_model,
-                current_message,
-                description: None,
+                breadcrumbs: breadcrumbs(current_message, cx),
+                show_sidebar,
             }
         })
     }
@@ -56,41 +149,97 @@ impl NavigationView {
 
 impl Render for NavigationView {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let show_sidebar = *self.show_sidebar.read(cx);
+        let theme = cx.global::<Theme>();
+        let last = self.breadcrumbs.len().saturating_sub(1);
+
         div().flex().child(
             div()
                 .flex()
                 .w_full()
-                .max_w(px(1000.0))
+                .max_w(spx(1000.0))
                 .mr_auto()
-                .pl(px(10.0))
-                .pt(px(10.0))
+                .pl(spx(10.0))
+                .pr(spx(10.0))
+                .pt(spx(10.0))
+                .gap(spx(4.0))
+                .when(!show_sidebar, |this| {
+                    this.child(
+                        nav_button("sidebar-toggle", SIDEBAR)
+                            .tooltip(tooltip(t!(cx, "navigation.show_sidebar")))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.show_sidebar.write(cx, true);
+                            })),
+                    )
+                })
                 .child(
-                    nav_button("back", ARROW_LEFT).on_click(cx.listener(|this, _, _, cx| {
-                        this.view_switcher_model.update(cx, |_, cx| {
-                            cx.emit(ViewSwitchMessage::Back);
-                        })
-                    })),
-                ), // .child(
-                   //     div()
-                   //         .pt(px(5.0))
-                   //         .flex()
-                   //         .child(div().text_sm().child(match self.current_message {
-                   //             ViewSwitchMessage::Albums => "Albums",
-                   //             ViewSwitchMessage::Release(_) => "Release",
-                   //             ViewSwitchMessage::Back => {
-                   //                 panic!("back should not be in VecDeque<ViewSwitchMessage>")
-                   //             }
-                   //         }))
-                   //         .when_some(self.description.clone(), |this, description| {
-                   //             this.child(
-                   //                 div()
-                   //                     .ml(px(8.0))
-                   //                     .font_weight(FontWeight::BOLD)
-                   //                     .text_sm()
-                   //                     .child(description),
-                   //             )
-                   //         }),
-                   // ),
+                    nav_button("back", ARROW_LEFT)
+                        .tooltip(tooltip(t!(cx, "navigation.back")))
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.view_switcher_model.update(cx, |_, cx| {
+                                cx.emit(ViewSwitchMessage::Back);
+                            })
+                        })),
+                )
+                .child(
+                    nav_button("forward", ARROW_RIGHT)
+                        .tooltip(tooltip(t!(cx, "navigation.forward")))
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.view_switcher_model.update(cx, |_, cx| {
+                                cx.emit(ViewSwitchMessage::Forward);
+                            })
+                        })),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .ml(spx(6.0))
+                        .min_w(spx(0.0))
+                        .overflow_x_hidden()
+                        .text_sm()
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .children(self.breadcrumbs.iter().enumerate().map(
+                            |(idx, (message, name))| {
+                                let message = *message;
+                                let is_current = idx == last;
+
+                                div()
+                                    .flex()
+                                    .items_center()
+                                    .min_w(spx(0.0))
+                                    .when(idx > 0, |this| {
+                                        this.child(
+                                            div()
+                                                .px(spx(2.0))
+                                                .flex_shrink_0()
+                                                .text_color(theme.text_secondary)
+                                                .child("›"),
+                                        )
+                                    })
+                                    .child(
+                                        div()
+                                            .id(("breadcrumb", idx))
+                                            .px(spx(6.0))
+                                            .py(spx(4.0))
+                                            .rounded_sm()
+                                            .truncate()
+                                            .when(is_current, |this| this.text_color(theme.text))
+                                            .when(!is_current, |this| {
+                                                this.text_color(theme.text_secondary)
+                                                    .cursor_pointer()
+                                                    .hover(|this| this.bg(theme.nav_button_hover))
+                                                    .active(|this| this.bg(theme.nav_button_active))
+                                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                                        this.view_switcher_model
+                                                            .update(cx, |_, cx| cx.emit(message))
+                                                    }))
+                                            })
+                                            .child(name.clone()),
+                                    )
+                            },
+                        )),
+                ),
         )
     }
 }
@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use gpui::{Action, App, SharedString};
+use tracing::{error, info, warn};
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        remote::{self, is_remote},
+    },
+    ui::{
+        command_palette::{Command, CommandManager},
+        locale::t,
+    },
+};
+
+/// The tracks that are kept for offline listening together.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OfflineTracks {
+    Album(i64),
+    Playlist(i64),
+}
+
+/// Downloads the tracks in remote folders, and keeps them until they're removed with
+/// [RemoveOffline].
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = library, no_json)]
+pub struct DownloadOffline {
+    pub tracks: OfflineTracks,
+}
+
+/// Removes the copies of the tracks that were kept for offline listening.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = library, no_json)]
+pub struct RemoveOffline {
+    pub tracks: OfflineTracks,
+}
+
+pub fn bind_actions(cx: &mut App) {
+    cx.on_action(download_offline);
+    cx.on_action(remove_offline);
+}
+
+/// The remote file holding the track, which is the file its cue sheet is embedded in for tracks
+/// split from one (see [crate::media::metadata::CueTrack::location]).
+fn remote_file(location: &Path) -> Option<PathBuf> {
+    if !is_remote(location) {
+        return None;
+    }
+
+    match location.parent() {
+        Some(file) if location.extension().is_none() && file.extension().is_some() => {
+            Some(file.to_path_buf())
+        }
+        _ => Some(location.to_path_buf()),
+    }
+}
+
+/// The remote files holding the tracks.
+fn remote_files(cx: &App, tracks: OfflineTracks) -> Vec<PathBuf> {
+    let locations: Vec<PathBuf> = match tracks {
+        OfflineTracks::Album(album_id) => cx
+            .list_tracks_in_album(album_id)
+            .map(|tracks| tracks.iter().map(|track| track.location.clone()).collect())
+            .unwrap_or_else(|err| {
+                error!("Failed to list the tracks in album {album_id}: {err}");
+                Vec::new()
+            }),
+        OfflineTracks::Playlist(playlist_id) => cx
+            .get_playlist_track_files(playlist_id)
+            .map(|files| files.iter().map(PathBuf::from).collect())
+            .unwrap_or_else(|err| {
+                error!("Failed to list the tracks in playlist {playlist_id}: {err}");
+                Vec::new()
+            }),
+    };
+
+    let mut files: Vec<PathBuf> = locations
+        .iter()
+        .filter_map(|location| remote_file(location))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn download_offline(action: &DownloadOffline, cx: &mut App) {
+    let files = remote_files(cx, action.tracks);
+
+    crate::RUNTIME.spawn_blocking(move || {
+        let mut kept = 0;
+
+        for file in &files {
+            match remote::keep_offline(file) {
+                Ok(()) => kept += 1,
+                Err(err) => warn!("Failed to download {:?} for offline listening: {err}", file),
+            }
+        }
+
+        info!(
+            "Kept {kept} of {} tracks for offline listening",
+            files.len()
+        );
+    });
+}
+
+fn remove_offline(action: &RemoveOffline, cx: &mut App) {
+    for file in remote_files(cx, action.tracks) {
+        remote::remove_offline(&file);
+    }
+}
+
+/// The names the commands for the tracks are registered under, along with their ID.
+fn command_names(tracks: OfflineTracks) -> (&'static str, &'static str, i64) {
+    match tracks {
+        OfflineTracks::Album(id) => ("album::downloadoffline", "album::removeoffline", id),
+        OfflineTracks::Playlist(id) => ("playlist::downloadoffline", "playlist::removeoffline", id),
+    }
+}
+
+/// Adds the commands that download the tracks for offline listening, and remove them again, to
+/// the command palette, if any of the tracks are in remote folders.
+pub fn register_offline_commands(cx: &mut App, category: SharedString, tracks: OfflineTracks) {
+    if remote_files(cx, tracks).is_empty() {
+        return;
+    }
+
+    let (download, remove, id) = command_names(tracks);
+
+    let name = t!(cx, "command.download_offline");
+    cx.register_command(
+        (download, id),
+        Command::new(
+            Some(category.clone()),
+            name,
+            DownloadOffline { tracks },
+            None,
+        ),
+    );
+
+    let name = t!(cx, "command.remove_offline");
+    cx.register_command(
+        (remove, id),
+        Command::new(Some(category), name, RemoveOffline { tracks }, None),
+    );
+}
+
+/// Removes the commands added by [register_offline_commands].
+pub fn unregister_offline_commands(cx: &mut App, tracks: OfflineTracks) {
+    let (download, remove, id) = command_names(tracks);
+    cx.unregister_command((download, id));
+    cx.unregister_command((remove, id));
+}
@@ -0,0 +1,387 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc, format::StrftimeItems};
+use gpui::*;
+use prelude::FluentBuilder;
+use rustc_hash::FxHashSet;
+use tracing::error;
+
+use crate::{
+    library::{db::LibraryAccess, types::Play},
+    services::mmb::scrobbler::{Scrobble, queue_path, read_queue},
+    ui::{
+        app::get_dirs,
+        components::button::{ButtonStyle, InteractiveButton, button},
+        library::{ViewSwitchMessage, recap_view::latest_recap_year},
+        locale::{Locale, t},
+        models::{MMBSEvent, Models},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// The number of plays shown, most recent first.
+const PLAY_LIMIT: i64 = 500;
+
+/// A play that started less than this many seconds before the next one is treated as a track
+/// that was skipped, rather than listened to.
+const SHORT_PLAY_SECONDS: i64 = 30;
+
+/// How long to wait before reading the scrobble queues again after asking a service to change
+/// them, since the services work through their events in the background.
+const QUEUE_RELOAD_DELAY: Duration = Duration::from_secs(2);
+
+/// The plays waiting to be sent to a scrobbling service.
+struct PendingScrobbles {
+    service: String,
+    scrobbles: Vec<Scrobble>,
+}
+
+fn service_name(service: &str) -> &str {
+    match service {
+        "lastfm" => "Last.fm",
+        "listenbrainz" => "ListenBrainz",
+        "librefm" => "Libre.fm",
+        _ => service,
+    }
+}
+
+fn format_time(cx: &App, time: DateTime<Utc>) -> String {
+    let time = time.with_timezone(&Local);
+
+    // a broken format from a language pack falls back to ISO 8601
+    match StrftimeItems::new(cx.global::<Locale>().message("history.played_at_format")).parse() {
+        Ok(items) => time.format_with_items(items.iter()).to_string(),
+        Err(_) => time.format("%Y-%m-%d %H:%M").to_string(),
+    }
+}
+
+/// Lists the plays in the play history so that plays recorded by mistake can be removed before
+/// they end up in play counts and stats, along with the plays that haven't been sent to the
+/// scrobbling services yet.
+pub struct PlayHistoryView {
+    plays: Vec<Play>,
+    /// The plays that were skipped shortly after they started.
+    short_plays: FxHashSet<i64>,
+    pending: Vec<PendingScrobbles>,
+}
+
+impl PlayHistoryView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let mut view = PlayHistoryView {
+                plays: Vec::new(),
+                short_plays: FxHashSet::default(),
+                pending: Vec::new(),
+            };
+            view.load(cx);
+            view.load_pending(cx);
+            view
+        })
+    }
+
+    fn load(&mut self, cx: &mut App) {
+        self.plays = cx.list_plays(PLAY_LIMIT).unwrap_or_else(|err| {
+            error!("Failed to list plays: {err}");
+            Vec::new()
+        });
+
+        // plays are listed most recent first, so the play after each one comes before it
+        self.short_plays = self
+            .plays
+            .windows(2)
+            .filter(|pair| {
+                let gap = (pair[0].played_at - pair[1].played_at).num_seconds();
+                gap < SHORT_PLAY_SECONDS && gap < pair[1].duration
+            })
+            .map(|pair| pair[1].id)
+            .collect();
+    }
+
+    /// Reads the scrobble queues of the scrobbling services that are set up.
+    fn load_pending(&mut self, cx: &mut App) {
+        let data_dir = get_dirs().data_dir().to_path_buf();
+        let mut services: Vec<String> = cx
+            .global::<Models>()
+            .mmbs
+            .read(cx)
+            .0
+            .iter()
+            .filter(|(_, handle)| handle.scrobbles())
+            .map(|(name, _)| name.clone())
+            .collect();
+        services.sort();
+
+        self.pending = services
+            .into_iter()
+            .map(|service| PendingScrobbles {
+                scrobbles: read_queue(&queue_path(&data_dir, &service)),
+                service,
+            })
+            .filter(|pending| !pending.scrobbles.is_empty())
+            .collect();
+    }
+
+    fn reload_pending_later(&self, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(QUEUE_RELOAD_DELAY).await;
+
+            this.update(cx, |this, cx| {
+                this.load_pending(cx);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn send_to_service(&self, cx: &mut Context<Self>, service: &str, event: MMBSEvent) {
+        if let Some(handle) = cx.global::<Models>().mmbs.read(cx).0.get(service) {
+            handle.send(event);
+        }
+
+        self.reload_pending_later(cx);
+    }
+
+    fn delete_plays(&mut self, cx: &mut Context<Self>, play_ids: Vec<i64>) {
+        for play_id in play_ids {
+            if let Err(err) = cx.delete_play(play_id) {
+                error!("Failed to delete play {play_id}: {err}");
+            }
+        }
+
+        self.load(cx);
+        cx.notify();
+    }
+}
+
+fn section_heading(title: SharedString, theme: &Theme) -> Div {
+    div()
+        .flex()
+        .items_center()
+        .px(spx(18.0))
+        .pt(spx(14.0))
+        .pb(spx(6.0))
+        .text_sm()
+        .font_weight(FontWeight::BOLD)
+        .text_color(theme.text_secondary)
+        .border_b_1()
+        .border_color(theme.border_color)
+        .child(title)
+}
+
+fn history_row(
+    title: SharedString,
+    detail: SharedString,
+    action: InteractiveButton,
+    theme: &Theme,
+) -> Div {
+    div()
+        .flex()
+        .items_center()
+        .px(spx(18.0))
+        .py(spx(8.0))
+        .gap(spx(12.0))
+        .w_full()
+        .border_b_1()
+        .border_color(theme.border_color)
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .flex_shrink()
+                .overflow_x_hidden()
+                .child(
+                    div()
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .truncate()
+                        .child(title),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .truncate()
+                        .child(detail),
+                ),
+        )
+        .child(div().ml_auto().flex_shrink_0().child(action))
+}
+
+impl Render for PlayHistoryView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let remove_label = t!(cx, "history.remove");
+        let discard_label = t!(cx, "history.discard");
+        let short_label = t!(cx, "history.short_play");
+
+        div()
+            .id("history-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .flex()
+                    .items_center()
+                    .child(div().mr_auto().child(t!(cx, "sidebar.history")))
+                    .child(
+                        button()
+                            .id("history-recap")
+                            .font_weight(FontWeight::NORMAL)
+                            .child(t!(cx, "recap.heading"))
+                            .on_click(|_, _, cx| {
+                                let year = latest_recap_year(cx);
+                                let switcher = cx.global::<Models>().switcher_model.clone();
+                                switcher
+                                    .update(cx, |_, cx| cx.emit(ViewSwitchMessage::Recap(year)));
+                            }),
+                    ),
+            )
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .pb(spx(6.0))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .child(t!(cx, "history.description")),
+            )
+            .children(self.pending.iter().map(|pending| {
+                let service = pending.service.clone();
+
+                div()
+                    .child(
+                        section_heading(
+                            t!(
+                                cx,
+                                "history.pending",
+                                service = service_name(&pending.service)
+                            ),
+                            theme,
+                        )
+                        .child(
+                            div().ml_auto().child(
+                                button()
+                                    .id(SharedString::from(format!(
+                                        "history-retry-{}",
+                                        pending.service
+                                    )))
+                                    .text_sm()
+                                    .child(t!(cx, "history.retry"))
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        this.send_to_service(
+                                            cx,
+                                            &service,
+                                            MMBSEvent::RetryScrobbles,
+                                        );
+                                    })),
+                            ),
+                        ),
+                    )
+                    .children(pending.scrobbles.iter().enumerate().map(|(idx, scrobble)| {
+                        let service = pending.service.clone();
+                        let timestamp = scrobble.timestamp;
+                        let time = DateTime::from_timestamp(timestamp, 0)
+                            .map(|time| format_time(cx, time))
+                            .unwrap_or_default();
+
+                        history_row(
+                            scrobble.track.clone().into(),
+                            format!("{} · {time}", scrobble.artist).into(),
+                            button()
+                                .id(SharedString::from(format!(
+                                    "history-discard-{}-{idx}",
+                                    pending.service
+                                )))
+                                .style(ButtonStyle::Minimal)
+                                .child(discard_label.clone())
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    // hidden right away, the queue is read again once the
+                                    // service has forgotten it
+                                    if let Some(pending) = this
+                                        .pending
+                                        .iter_mut()
+                                        .find(|pending| pending.service == service)
+                                    {
+                                        pending
+                                            .scrobbles
+                                            .retain(|scrobble| scrobble.timestamp != timestamp);
+                                    }
+
+                                    this.send_to_service(
+                                        cx,
+                                        &service,
+                                        MMBSEvent::DiscardScrobble(timestamp),
+                                    );
+                                    cx.notify();
+                                })),
+                            theme,
+                        )
+                    }))
+            }))
+            .child(section_heading(t!(cx, "history.plays"), theme).when(
+                !self.short_plays.is_empty(),
+                |this| {
+                    let short_plays: Vec<i64> = self.short_plays.iter().copied().collect();
+
+                    this.child(
+                        div().ml_auto().child(
+                            button()
+                                .id("history-remove-short")
+                                .text_sm()
+                                .child(
+                                    cx.global::<Locale>()
+                                        .plural("history.remove_short", short_plays.len() as i64),
+                                )
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.delete_plays(cx, short_plays.clone());
+                                })),
+                        ),
+                    )
+                },
+            ))
+            .when(self.plays.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "history.empty")),
+                )
+            })
+            .children(self.plays.iter().map(|play| {
+                let play_id = play.id;
+                let mut detail = format_time(cx, play.played_at);
+
+                if let Some(artist_names) = &play.artist_names {
+                    detail = format!("{} · {detail}", artist_names.0);
+                }
+                if self.short_plays.contains(&play_id) {
+                    detail = format!("{detail} · {short_label}");
+                }
+
+                history_row(
+                    play.title.0.clone(),
+                    detail.into(),
+                    button()
+                        .id(("history-remove", play_id as u64))
+                        .style(ButtonStyle::Minimal)
+                        .child(remove_label.clone())
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.delete_plays(cx, vec![play_id]);
+                        })),
+                    theme,
+                )
+            }))
+            .child(div().h(spx(24.0)))
+    }
+}
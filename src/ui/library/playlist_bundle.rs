@@ -0,0 +1,306 @@
+use std::path::Path;
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    Styled, Window, div, prelude::FluentBuilder,
+};
+use tracing::warn;
+
+use crate::{
+    library::{
+        bundle::BundleWriter,
+        db::{LibraryAccess, find_track_file},
+        playlist::list_playlist_entries,
+    },
+    media::transcode::Transcoder,
+    settings::{SettingsGlobal, transcode::TranscodeProfile},
+    ui::{
+        app::Pool,
+        components::{button::button, modal::modal},
+        locale::{Locale, t},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+enum BundleState {
+    /// Waiting for the user to pick whether to convert the tracks.
+    Choosing,
+    Exporting {
+        done: usize,
+        total: usize,
+    },
+    Finished {
+        exported: usize,
+        failed: usize,
+    },
+    Error(SharedString),
+}
+
+/// A dialog that exports a playlist as a zip file holding the playlist and its tracks, optionally
+/// converted with one of the transcode profiles.
+pub struct PlaylistBundle {
+    show: Entity<bool>,
+    playlist_id: i64,
+    state: BundleState,
+}
+
+impl PlaylistBundle {
+    pub fn new(cx: &mut App, show: Entity<bool>, playlist_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                if *show.read(cx) && !matches!(this.state, BundleState::Exporting { .. }) {
+                    this.state = BundleState::Choosing;
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            Self {
+                show,
+                playlist_id,
+                state: BundleState::Choosing,
+            }
+        })
+    }
+
+    fn export(&mut self, profile: Option<TranscodeProfile>, cx: &mut Context<Self>) {
+        let transcode = match profile {
+            Some(profile) => {
+                let settings = &cx.global::<SettingsGlobal>().model.read(cx).transcode;
+                match Transcoder::find(settings) {
+                    Ok(transcoder) => Some((transcoder, profile)),
+                    Err(err) => {
+                        self.state = BundleState::Error(err.to_string().into());
+                        cx.notify();
+                        return;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let Some(dir) = directories::UserDirs::new()
+            .and_then(|dirs| dirs.document_dir().map(|dir| dir.to_path_buf()))
+        else {
+            self.state = BundleState::Error("could not find the documents folder".into());
+            cx.notify();
+            return;
+        };
+
+        let playlist = match cx.get_playlist(self.playlist_id) {
+            Ok(playlist) => playlist,
+            Err(err) => {
+                self.state = BundleState::Error(err.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+        let playlist_id = playlist.id;
+        let playlist_name = playlist.name.to_string();
+        let description = playlist.description.clone();
+
+        let suggested_name = format!("{playlist_name}.zip");
+        let path_future = cx.prompt_for_new_path(&dir, Some(&suggested_name));
+        let pool = cx.global::<Pool>().read.clone();
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path_future.await else {
+                return;
+            };
+
+            let entries = match crate::RUNTIME
+                .spawn(async move {
+                    let entries = list_playlist_entries(&pool, playlist_id).await?;
+                    let mut resolved = Vec::with_capacity(entries.len());
+                    for entry in entries {
+                        let file = find_track_file(&pool, Path::new(&entry.location)).await?;
+                        resolved.push((entry, file));
+                    }
+                    Ok::<_, sqlx::Error>(resolved)
+                })
+                .await
+            {
+                Ok(Ok(entries)) => entries,
+                Ok(Err(err)) => {
+                    warn!("Could not list the playlist's tracks: {err}");
+                    return;
+                }
+                Err(err) => {
+                    warn!("Could not list the playlist's tracks: {err}");
+                    return;
+                }
+            };
+            let total = entries.len();
+
+            let mut writer =
+                match BundleWriter::create(&path, description.as_deref(), total, transcode) {
+                    Ok(writer) => writer,
+                    Err(err) => {
+                        this.update(cx, |this, cx| {
+                            this.state = BundleState::Error(err.to_string().into());
+                            cx.notify();
+                        })
+                        .ok();
+                        return;
+                    }
+                };
+
+            this.update(cx, |this, cx| {
+                this.state = BundleState::Exporting { done: 0, total };
+                cx.notify();
+            })
+            .ok();
+
+            let mut failed = 0;
+
+            for (index, (entry, file)) in entries.into_iter().enumerate() {
+                // the writer is handed to the blocking task and back, since it can't be shared
+                let result = crate::RUNTIME
+                    .spawn_blocking(move || {
+                        let result = writer.add(&entry, &file);
+                        (writer, entry, result)
+                    })
+                    .await;
+
+                let Ok((returned, entry, result)) = result else {
+                    this.update(cx, |this, cx| {
+                        this.state = BundleState::Error(t!(cx, "bundle.failed"));
+                        cx.notify();
+                    })
+                    .ok();
+                    return;
+                };
+                writer = returned;
+
+                if let Err(err) = result {
+                    warn!("Could not add {:?} to the bundle: {err}", entry.location);
+                    failed += 1;
+                }
+
+                this.update(cx, |this, cx| {
+                    this.state = BundleState::Exporting {
+                        done: index + 1,
+                        total,
+                    };
+                    cx.notify();
+                })
+                .ok();
+            }
+
+            let result = crate::RUNTIME
+                .spawn_blocking(move || writer.finish(&playlist_name))
+                .await;
+
+            this.update(cx, |this, cx| {
+                this.state = match result {
+                    Ok(Ok(exported)) => BundleState::Finished { exported, failed },
+                    Ok(Err(err)) => BundleState::Error(err.to_string().into()),
+                    Err(_) => BundleState::Error(t!(cx, "bundle.failed")),
+                };
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl Render for PlaylistBundle {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let theme = cx.global::<Theme>();
+        let show = self.show.clone();
+        let show_close = self.show.clone();
+
+        let locale = cx.global::<Locale>();
+        let status = match &self.state {
+            BundleState::Choosing => t!(cx, "bundle.choose"),
+            BundleState::Exporting { done, total } => {
+                locale.format("bundle.progress", &[("done", done), ("total", total)])
+            }
+            BundleState::Finished {
+                exported,
+                failed: 0,
+            } => locale.plural("bundle.finished", *exported as i64),
+            BundleState::Finished { exported, failed } => locale.format(
+                "bundle.finished_with_failures",
+                &[("count", exported), ("failed", failed)],
+            ),
+            BundleState::Error(err) => err.clone(),
+        };
+
+        let profiles = match self.state {
+            BundleState::Choosing => cx
+                .global::<SettingsGlobal>()
+                .model
+                .read(cx)
+                .transcode
+                .profiles
+                .clone(),
+            _ => Vec::new(),
+        };
+        let choosing = matches!(self.state, BundleState::Choosing);
+
+        let profile_buttons = profiles.into_iter().enumerate().map(|(index, profile)| {
+            let label = profile.name.clone();
+            button()
+                .id(("bundle-profile", index))
+                .on_click(cx.listener(move |this, _, _, cx| this.export(Some(profile.clone()), cx)))
+                .child(label)
+        });
+
+        modal()
+            .child(
+                div()
+                    .w(spx(400.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .pb(spx(6.0))
+                            .child(t!(cx, "bundle.title")),
+                    )
+                    .child(div().text_color(theme.text_secondary).child(status))
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .gap(spx(8.0))
+                            .pt(spx(4.0))
+                            .when(choosing, |this| {
+                                this.child(
+                                    button()
+                                        .id("bundle-original")
+                                        .on_click(
+                                            cx.listener(|this, _, _, cx| this.export(None, cx)),
+                                        )
+                                        .child(t!(cx, "bundle.original")),
+                                )
+                            })
+                            .children(profile_buttons),
+                    )
+                    .child(
+                        div().flex().justify_end().pt(spx(10.0)).child(
+                            button()
+                                .id("bundle-close")
+                                .on_click(move |_, _, cx| show_close.write(cx, false))
+                                .child(t!(cx, "convert.close")),
+                        ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -2,7 +2,8 @@ use std::sync::Arc;
 
 use gpui::{
     App, AppContext, Context, Entity, FocusHandle, FontWeight, InteractiveElement, KeyBinding,
-    ParentElement, Render, Styled, Window, actions, div, px, rems, uniform_list,
+    ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window, actions, div,
+    img, prelude::FluentBuilder, rems, uniform_list,
 };
 use rustc_hash::FxHashMap;
 use tracing::{error, info};
@@ -17,25 +18,32 @@ use crate::{
         interface::{PlaybackInterface, replace_queue},
         queue::QueueItemData,
     },
+    settings::interface::TrackListingKind,
     ui::{
+        caching::hummingbird_cache,
         command_palette::{Command, CommandManager},
         components::{
             button::{ButtonIntent, ButtonSize, button},
             icons::{CIRCLE_PLUS, PLAY, PLAYLIST, SHUFFLE, STAR, icon},
+            input::{EnrichedInputAction, TextInput},
         },
         library::track_listing::{
-            ArtistNameVisibility,
-            track_item::{TrackItem, TrackItemLeftField},
+            ArtistNameVisibility, filter::TrackFilter, track_item::TrackItem,
         },
+        locale::t,
         models::{Models, PlaybackInfo, PlaylistEvent},
         theme::Theme,
-        util::{create_or_retrieve_view, prune_views},
+        util::{create_or_retrieve_view, prune_views, spx},
     },
 };
 
-use super::track_listing::track_item::TrackPlaylistInfo;
+use super::{
+    offline::{OfflineTracks, register_offline_commands, unregister_offline_commands},
+    playlist_bundle::PlaylistBundle,
+    track_listing::track_item::TrackPlaylistInfo,
+};
 
-actions!(playlist, [Export, Import]);
+actions!(playlist, [Export, ExportBundle, Import]);
 
 pub fn bind_actions(cx: &mut App) {
     cx.bind_keys([KeyBinding::new("secondary-s", Export, None)]);
@@ -44,10 +52,18 @@ pub fn bind_actions(cx: &mut App) {
 pub struct PlaylistView {
     playlist: Arc<Playlist>,
     playlist_track_ids: Arc<Vec<(i64, i64, i64)>>,
+    /// The items being shown, after filtering and sorting.
+    shown_track_ids: Arc<Vec<(i64, i64, i64)>>,
+    track_filter: Entity<TrackFilter>,
     views: Entity<FxHashMap<usize, Entity<TrackItem>>>,
     render_counter: Entity<usize>,
     focus_handle: FocusHandle,
     first_render: bool,
+    /// The input the description is being edited in, if it's being edited.
+    description_input: Option<Entity<TextInput>>,
+    description_draft: String,
+    bundle: Entity<PlaylistBundle>,
+    show_bundle: Entity<bool>,
 }
 
 impl PlaylistView {
@@ -62,50 +78,156 @@ impl PlaylistView {
                         && *id == this.playlist.id
                     {
                         this.playlist_track_ids = cx.get_playlist_tracks(this.playlist.id).unwrap();
+                        if let Ok(playlist) = cx.get_playlist(this.playlist.id) {
+                            this.playlist = playlist;
+                        }
 
-                        this.views = cx.new(|_| FxHashMap::default());
-                        this.render_counter = cx.new(|_| 0);
+                        this.update_shown(cx);
                     }
                 },
             )
             .detach();
 
             let focus_handle = cx.focus_handle();
+            let category = t!(cx, "command.category.playlist");
+            let name = t!(cx, "command.export_playlist");
 
             cx.register_command(
                 ("playlist::export", playlist_id),
                 Command::new(
-                    Some("Playlist"),
-                    "Export Playlist to M3U",
+                    Some(category.clone()),
+                    name,
                     Export,
                     Some(focus_handle.clone()),
                 ),
             );
 
+            let name = t!(cx, "command.export_playlist_bundle");
+
+            cx.register_command(
+                ("playlist::exportbundle", playlist_id),
+                Command::new(
+                    Some(category.clone()),
+                    name,
+                    ExportBundle,
+                    Some(focus_handle.clone()),
+                ),
+            );
+
+            register_offline_commands(cx, category, OfflineTracks::Playlist(playlist_id));
+
             cx.on_release(move |_, cx| {
                 cx.unregister_command(("playlist::export", playlist_id));
+                cx.unregister_command(("playlist::exportbundle", playlist_id));
+                unregister_offline_commands(cx, OfflineTracks::Playlist(playlist_id));
             })
             .detach();
 
+            let show_bundle = cx.new(|_| false);
+
+            let track_filter = TrackFilter::new(cx, TrackListingKind::Playlist);
+            cx.observe(&track_filter, |this: &mut Self, _, cx| {
+                this.update_shown(cx)
+            })
+            .detach();
+
+            let playlist_track_ids = cx.get_playlist_tracks(playlist_id).unwrap();
+
             Self {
                 playlist: cx.get_playlist(playlist_id).unwrap(),
-                playlist_track_ids: cx.get_playlist_tracks(playlist_id).unwrap(),
+                shown_track_ids: playlist_track_ids.clone(),
+                playlist_track_ids,
+                track_filter,
                 views: cx.new(|_| FxHashMap::default()),
                 render_counter: cx.new(|_| 0),
                 focus_handle,
                 first_render: true,
+                description_input: None,
+                description_draft: String::new(),
+                bundle: PlaylistBundle::new(cx, show_bundle.clone(), playlist_id),
+                show_bundle,
+            }
+        })
+    }
+
+    /// Works out which items to show after the playlist or the filter changes.
+    fn update_shown(&mut self, cx: &mut Context<Self>) {
+        let track_ids: Vec<i64> = self.playlist_track_ids.iter().map(|item| item.1).collect();
+        let shown = self
+            .track_filter
+            .update(cx, |filter, cx| filter.apply(cx, &track_ids));
+
+        self.shown_track_ids = match shown {
+            Some(shown) => Arc::new(
+                shown
+                    .into_iter()
+                    .map(|idx| self.playlist_track_ids[idx])
+                    .collect(),
+            ),
+            None => self.playlist_track_ids.clone(),
+        };
+
+        self.views = cx.new(|_| FxHashMap::default());
+        self.render_counter = cx.new(|_| 0);
+        cx.notify();
+    }
+
+    fn edit_description(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let handle = cx.focus_handle();
+        let this = cx.entity().downgrade();
+        let handler = move |action, _: &mut Window, cx: &mut App| {
+            if let EnrichedInputAction::Accept = action {
+                this.update(cx, |this, cx| this.save_description(cx)).ok();
             }
+        };
+
+        let description = self.playlist.description.clone().unwrap_or_default();
+        let placeholder = t!(cx, "playlist.description_placeholder");
+        let input = TextInput::new(
+            cx,
+            handle.clone(),
+            Some(description.clone().into()),
+            Some(placeholder),
+            Some(Box::new(handler)),
+        );
+
+        cx.subscribe(&input, |this: &mut Self, _, ev: &String, _| {
+            this.description_draft = ev.clone();
         })
+        .detach();
+
+        handle.focus(window);
+        self.description_draft = description;
+        self.description_input = Some(input);
+        cx.notify();
+    }
+
+    fn save_description(&mut self, cx: &mut Context<Self>) {
+        let description = self.description_draft.trim();
+        let description = (!description.is_empty()).then_some(description);
+
+        if let Err(err) = cx.set_playlist_description(self.playlist.id, description) {
+            error!("Failed to set the playlist description: {err}");
+        }
+
+        self.description_input = None;
+
+        let id = self.playlist.id;
+        let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+        playlist_tracker.update(cx, |_, cx| {
+            cx.emit(PlaylistEvent::PlaylistUpdated(id));
+        });
     }
 }
 
 impl Render for PlaylistView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl gpui::IntoElement {
-        let items_clone = self.playlist_track_ids.clone();
+        let items_clone = self.shown_track_ids.clone();
         let views_model = self.views.clone();
         let render_counter = self.render_counter.clone();
         let pl_id = self.playlist.id;
         let playlist_name = self.playlist.name.0.clone();
+        let show_bundle = self.show_bundle.clone();
 
         let theme = cx.global::<Theme>();
 
@@ -115,6 +237,7 @@ impl Render for PlaylistView {
         }
 
         div()
+            .image_cache(hummingbird_cache(("playlist-view", pl_id as u64), 100))
             .id("playlist-view")
             .track_focus(&self.focus_handle)
             .on_action(move |_: &Export, _, cx| {
@@ -123,44 +246,50 @@ impl Render for PlaylistView {
                     error!("Failed to export playlist: {}", err);
                 }
             })
-            .pt(px(10.0))
+            .on_action(move |_: &ExportBundle, _, cx| {
+                show_bundle.write(cx, true);
+            })
+            .pt(spx(10.0))
             .flex()
             .flex_col()
             .flex_shrink()
             .overflow_x_hidden()
-            .max_w(px(1000.0))
+            .max_w(spx(1000.0))
             .h_full()
             .child(
                 div()
                     .flex()
                     .overflow_x_hidden()
                     .flex_shrink()
-                    .px(px(18.0))
+                    .px(spx(18.0))
                     .w_full()
                     .child(
                         div()
                             .bg(theme.album_art_background)
                             .shadow_sm()
-                            .w(px(160.0))
-                            .h(px(160.0))
+                            .w(spx(160.0))
+                            .h(spx(160.0))
                             .flex_shrink_0()
-                            .rounded(px(4.0))
+                            .rounded(spx(4.0))
                             .overflow_hidden()
                             .flex()
                             .items_center()
                             .justify_center()
-                            .child(
-                                icon(if self.playlist.playlist_type == PlaylistType::System {
-                                    STAR
-                                } else {
-                                    PLAYLIST
-                                })
-                                .size(px(100.0)),
-                            ),
+                            .map(|this| match self.playlist.image_path() {
+                                Some(path) => this.child(img(path).size_full()),
+                                None => this.child(
+                                    icon(if self.playlist.playlist_type == PlaylistType::System {
+                                        STAR
+                                    } else {
+                                        PLAYLIST
+                                    })
+                                    .size(spx(100.0)),
+                                ),
+                            }),
                     )
                     .child(
                         div()
-                            .ml(px(18.0))
+                            .ml(spx(18.0))
                             .mt_auto()
                             .flex_shrink()
                             .flex()
@@ -173,14 +302,46 @@ impl Render for PlaylistView {
                                     .text_size(rems(2.5))
                                     .line_height(rems(2.75))
                                     .overflow_x_hidden()
-                                    .pb(px(10.0))
+                                    .pb(spx(10.0))
                                     .w_full()
                                     .text_ellipsis()
                                     .child(self.playlist.name.clone()),
                             )
+                            .map(|this| match self.description_input.clone() {
+                                Some(input) => this.child(
+                                    div()
+                                        .mb(spx(10.0))
+                                        .px(spx(8.0))
+                                        .py(spx(5.0))
+                                        .w_full()
+                                        .text_sm()
+                                        .line_height(spx(16.0))
+                                        .border_1()
+                                        .border_color(theme.border_color)
+                                        .rounded(spx(4.0))
+                                        .child(input),
+                                ),
+                                None => this.child(
+                                    div()
+                                        .id("playlist-description")
+                                        .mb(spx(10.0))
+                                        .text_sm()
+                                        .text_color(theme.text_secondary)
+                                        .cursor_pointer()
+                                        .child(match &self.playlist.description {
+                                            Some(description) => {
+                                                SharedString::from(description.clone())
+                                            }
+                                            None => t!(cx, "playlist.add_description"),
+                                        })
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.edit_description(window, cx)
+                                        })),
+                                ),
+                            })
                             .child(
                                 div()
-                                    .gap(px(10.0))
+                                    .gap(spx(10.0))
                                     .flex()
                                     .child(
                                         button()
@@ -188,8 +349,8 @@ impl Render for PlaylistView {
                                             .size(ButtonSize::Large)
                                             .font_weight(FontWeight::SEMIBOLD)
                                             .intent(ButtonIntent::Primary)
-                                            .child(icon(PLAY).size(px(16.0)).my_auto())
-                                            .child("Play")
+                                            .child(icon(PLAY).size(spx(16.0)).my_auto())
+                                            .child(t!(cx, "playlist.play"))
                                             .on_click(cx.listener(|this, _, _, cx| {
                                                 let tracks = cx
                                                     .get_playlist_track_files(this.playlist.id)
@@ -215,9 +376,10 @@ impl Render for PlaylistView {
                                     .child(
                                         button()
                                             .id("playlist-add-button")
+                                            .tooltip(t!(cx, "playlist.add_to_queue"))
                                             .size(ButtonSize::Large)
                                             .flex_none()
-                                            .child(icon(CIRCLE_PLUS).size(px(16.0)).my_auto())
+                                            .child(icon(CIRCLE_PLUS).size(spx(16.0)).my_auto())
                                             .on_click(cx.listener(|this, _, _, cx| {
                                                 let tracks = cx
                                                     .get_playlist_track_files(this.playlist.id)
@@ -244,9 +406,10 @@ impl Render for PlaylistView {
                                     .child(
                                         button()
                                             .id("playlist-shuffle-button")
+                                            .tooltip(t!(cx, "playlist.shuffle"))
                                             .size(ButtonSize::Large)
                                             .flex_none()
-                                            .child(icon(SHUFFLE).size(px(16.0)).my_auto())
+                                            .child(icon(SHUFFLE).size(spx(16.0)).my_auto())
                                             .on_click(cx.listener(|this, _, _, cx| {
                                                 let tracks = cx
                                                     .get_playlist_track_files(this.playlist.id)
@@ -281,6 +444,14 @@ impl Render for PlaylistView {
                             ),
                     ),
             )
+            .child(
+                div()
+                    .flex()
+                    .px(spx(18.0))
+                    .mt(spx(18.0))
+                    .mb(spx(6.0))
+                    .child(self.track_filter.clone()),
+            )
             .child(
                 uniform_list("playlist-list", items_clone.len(), move |range, _, cx| {
                     let start = range.start;
@@ -308,7 +479,7 @@ impl Render for PlaylistView {
                                         Arc::try_unwrap(track).unwrap(),
                                         false,
                                         ArtistNameVisibility::Always,
-                                        TrackItemLeftField::Art,
+                                        TrackListingKind::Playlist,
                                         Some(TrackPlaylistInfo {
                                             id: pl_id,
                                             item_id: item.0,
@@ -325,8 +496,8 @@ impl Render for PlaylistView {
                 .flex()
                 .flex_col()
                 .border_color(theme.border_color)
-                .border_t_1()
-                .mt(px(18.0)),
+                .border_t_1(),
             )
+            .child(self.bundle.clone())
     }
 }
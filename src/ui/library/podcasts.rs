@@ -0,0 +1,985 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use chrono::format::StrftimeItems;
+use gpui::*;
+use prelude::FluentBuilder;
+use rustc_hash::{FxHashMap, FxHashSet};
+use tracing::{error, warn};
+
+use crate::{
+    library::{
+        db::{
+            LibraryAccess, find_podcast_episode_by_file, set_podcast_episode_listened,
+            set_podcast_episode_position,
+        },
+        types::{Podcast, PodcastEpisode},
+    },
+    playback::{
+        interface::{PlaybackInterface, replace_queue},
+        queue::QueueItemData,
+    },
+    services::podcasts::{
+        delete_download, download_episode, refresh_podcast, refresh_podcasts, stream_episode,
+        subscribe, unsubscribe,
+    },
+    ui::{
+        app::{Pool, get_dirs},
+        components::{
+            button::{ButtonIntent, button},
+            input::{EnrichedInputAction, TextInput},
+        },
+        locale::{Locale, t},
+        models::{Models, PlaybackInfo},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+use super::ViewSwitchMessage;
+
+/// How far playback has to move before the position of an episode is saved again, in seconds.
+pub(super) const SAVE_INTERVAL: u64 = 10;
+/// How close to the end an episode has to be played to be marked as listened to, in seconds.
+pub(super) const LISTENED_WITHIN: u64 = 30;
+
+fn download_dir() -> PathBuf {
+    get_dirs().data_dir().join("podcasts")
+}
+
+fn stream_dir() -> PathBuf {
+    get_dirs().cache_dir().join("podcasts")
+}
+
+/// The episode that's playing, so that where it's up to can be saved.
+struct PlayingEpisode {
+    id: i64,
+    /// The position that was saved last, in seconds.
+    saved: u64,
+    listened: bool,
+}
+
+#[derive(Default)]
+struct EpisodePlayback {
+    /// Episodes that have been played, by the file they were played from. Streamed episodes
+    /// aren't in the library, so this is the only way to tell which episode they are.
+    files: FxHashMap<PathBuf, i64>,
+    playing: Option<PlayingEpisode>,
+}
+
+impl Global for EpisodePlayback {}
+
+/// Counts the new episodes again, which also tells the podcast views to reload.
+fn podcasts_changed(cx: &mut App) {
+    let count = cx.count_new_podcast_episodes().unwrap_or_else(|err| {
+        error!("Failed to count new podcast episodes: {err}");
+        0
+    });
+
+    let new_episodes = cx.global::<Models>().new_episodes.clone();
+    new_episodes.update(cx, |new_episodes, cx| {
+        *new_episodes = count;
+        cx.notify();
+    });
+}
+
+fn find_playing_episode(path: &PathBuf, cx: &mut App) -> Option<PlayingEpisode> {
+    let episode = match cx.global::<EpisodePlayback>().files.get(path) {
+        Some(id) => cx.get_podcast_episode(*id).ok()?,
+        None => {
+            let pool = cx.global::<Pool>().read.clone();
+            crate::RUNTIME
+                .block_on(find_podcast_episode_by_file(&pool, &path.to_string_lossy()))
+                .inspect_err(|err| error!("Failed to look up podcast episode: {err}"))
+                .ok()??
+        }
+    };
+
+    Some(PlayingEpisode {
+        id: episode.id,
+        saved: episode.position as u64,
+        listened: episode.listened,
+    })
+}
+
+/// Saves where each episode is up to as it plays, marks episodes as listened to when they're
+/// finished, and checks the feeds for new episodes.
+pub fn track_podcasts(cx: &mut App) {
+    cx.set_global(EpisodePlayback::default());
+
+    let current_track = cx.global::<PlaybackInfo>().current_track.clone();
+    let position = cx.global::<PlaybackInfo>().position.clone();
+
+    cx.observe(&current_track, |current_track, cx| {
+        let playing = current_track
+            .read(cx)
+            .clone()
+            .and_then(|track| find_playing_episode(track.get_path(), cx));
+
+        cx.global_mut::<EpisodePlayback>().playing = playing;
+    })
+    .detach();
+
+    cx.observe(&position, |position, cx| {
+        let position = *position.read(cx);
+        let duration = *cx.global::<PlaybackInfo>().duration.read(cx);
+        let write_pool = cx.global::<Pool>().write.clone();
+
+        let Some(playing) = cx.global_mut::<EpisodePlayback>().playing.as_mut() else {
+            return;
+        };
+
+        // the position is reset to zero when the episode is opened, before it's moved to where
+        // it was left
+        if playing.listened || position == 0 {
+            return;
+        }
+
+        let id = playing.id;
+
+        if duration > LISTENED_WITHIN && position + LISTENED_WITHIN >= duration {
+            playing.listened = true;
+            crate::RUNTIME.spawn(async move {
+                if let Err(err) = set_podcast_episode_listened(&write_pool, id, true).await {
+                    error!("Failed to mark episode as listened: {err}");
+                }
+            });
+        } else if position.abs_diff(playing.saved) >= SAVE_INTERVAL {
+            playing.saved = position;
+            crate::RUNTIME.spawn(async move {
+                if let Err(err) =
+                    set_podcast_episode_position(&write_pool, id, position as i64).await
+                {
+                    error!("Failed to save episode position: {err}");
+                }
+            });
+        }
+    })
+    .detach();
+
+    podcasts_changed(cx);
+
+    let read_pool = cx.global::<Pool>().read.clone();
+    let write_pool = cx.global::<Pool>().write.clone();
+
+    cx.spawn(async move |cx| {
+        let result = crate::RUNTIME
+            .spawn(async move { refresh_podcasts(&read_pool, &write_pool).await })
+            .await;
+
+        match result {
+            Ok(Ok(())) => {
+                cx.update(podcasts_changed).ok();
+            }
+            Ok(Err(err)) => warn!(?err, "Could not refresh podcasts: {err}"),
+            Err(err) => warn!(?err, "Could not refresh podcasts: {err}"),
+        }
+    })
+    .detach();
+}
+
+/// Plays the episode from where it was left. Episodes that haven't been downloaded are fetched
+/// first, in which case the returned task finishes once they start playing.
+fn play_episode(episode: &PodcastEpisode, cx: &mut App) -> Option<Task<()>> {
+    let downloaded = episode
+        .file_path
+        .as_ref()
+        .map(PathBuf::from)
+        .filter(|path| path.exists());
+
+    if let Some(path) = downloaded {
+        start_episode(path, episode, cx);
+        return None;
+    }
+
+    let episode = episode.clone();
+    let cache_dir = stream_dir();
+
+    Some(cx.spawn(async move |cx| {
+        let episode_clone = episode.clone();
+        let result = crate::RUNTIME
+            .spawn(async move { stream_episode(&cache_dir, &episode_clone).await })
+            .await;
+
+        match result {
+            Ok(Ok(path)) => {
+                cx.update(|cx| start_episode(path, &episode, cx)).ok();
+            }
+            Ok(Err(err)) => warn!(?err, "Could not stream episode: {err}"),
+            Err(err) => warn!(?err, "Could not stream episode: {err}"),
+        }
+    }))
+}
+
+fn start_episode(path: PathBuf, episode: &PodcastEpisode, cx: &mut App) {
+    cx.global_mut::<EpisodePlayback>()
+        .files
+        .insert(path.clone(), episode.id);
+
+    let item = QueueItemData::new(cx, path, None, None);
+    replace_queue(vec![item], cx);
+
+    if !episode.listened && episode.position > 0 {
+        cx.global::<PlaybackInterface>()
+            .seek(episode.position as f64);
+    }
+}
+
+pub(super) fn format_length(cx: &App, seconds: u64) -> SharedString {
+    let minutes = seconds / 60;
+
+    if minutes >= 60 {
+        t!(
+            cx,
+            "podcasts.hours",
+            hours = minutes / 60,
+            minutes = minutes % 60
+        )
+    } else {
+        t!(cx, "podcasts.minutes", minutes = minutes.max(1))
+    }
+}
+
+pub(super) fn new_badge(label: SharedString, theme: &Theme) -> Div {
+    div()
+        .flex_shrink_0()
+        .px(spx(6.0))
+        .rounded(spx(8.0))
+        .text_xs()
+        .font_weight(FontWeight::BOLD)
+        .bg(theme.button_primary)
+        .text_color(theme.button_primary_text)
+        .child(label)
+}
+
+pub(super) fn page_title(title: SharedString) -> Div {
+    div()
+        .px(spx(18.0))
+        .font_weight(FontWeight::EXTRA_BOLD)
+        .text_size(rems(2.5))
+        .line_height(rems(2.75))
+        .truncate()
+        .child(title)
+}
+
+/// Lists the podcasts that have been subscribed to, and subscribes to new ones.
+pub struct PodcastsView {
+    podcasts: Vec<Podcast>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+    input: Entity<TextInput>,
+    focus_handle: FocusHandle,
+    draft: String,
+    working: bool,
+    error: Option<SharedString>,
+}
+
+impl PodcastsView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let new_episodes = cx.global::<Models>().new_episodes.clone();
+            cx.observe(&new_episodes, |this: &mut Self, _, cx| {
+                this.load(cx);
+                cx.notify();
+            })
+            .detach();
+
+            let focus_handle = cx.focus_handle();
+            let this = cx.weak_entity();
+            let handler = move |action, _: &mut Window, cx: &mut App| {
+                if let EnrichedInputAction::Accept = action {
+                    this.update(cx, |this, cx| this.subscribe(cx)).ok();
+                }
+            };
+
+            let placeholder = t!(cx, "podcasts.feed_placeholder");
+            let input = TextInput::new(
+                cx,
+                focus_handle.clone(),
+                None,
+                Some(placeholder),
+                Some(Box::new(handler)),
+            );
+
+            cx.subscribe(&input, |this: &mut Self, _, ev: &String, _| {
+                this.draft = ev.clone();
+            })
+            .detach();
+
+            let mut view = PodcastsView {
+                podcasts: Vec::new(),
+                view_switcher: cx.global::<Models>().switcher_model.clone(),
+                input,
+                focus_handle,
+                draft: String::new(),
+                working: false,
+                error: None,
+            };
+            view.load(cx);
+            view
+        })
+    }
+
+    fn load(&mut self, cx: &mut App) {
+        self.podcasts = cx.list_podcasts().unwrap_or_else(|err| {
+            error!("Failed to list podcasts: {err}");
+            Vec::new()
+        });
+    }
+
+    fn subscribe(&mut self, cx: &mut Context<Self>) {
+        let url = self.draft.trim().to_string();
+        if url.is_empty() || self.working {
+            return;
+        }
+
+        let write_pool = cx.global::<Pool>().write.clone();
+        self.working = true;
+        self.error = None;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move { subscribe(&write_pool, &url).await })
+                .await;
+
+            this.update(cx, |this, cx| {
+                this.working = false;
+
+                match result {
+                    Ok(Ok(_)) => {
+                        this.draft.clear();
+                        this.input.update(cx, |input, _| input.reset());
+                        podcasts_changed(cx);
+                    }
+                    Ok(Err(err)) => {
+                        warn!(?err, "Could not subscribe to podcast: {err}");
+                        this.error = Some(t!(cx, "podcasts.subscribe_failed"));
+                    }
+                    Err(err) => {
+                        warn!(?err, "Could not subscribe to podcast: {err}");
+                        this.error = Some(t!(cx, "podcasts.subscribe_failed"));
+                    }
+                }
+
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let read_pool = cx.global::<Pool>().read.clone();
+        let write_pool = cx.global::<Pool>().write.clone();
+        self.working = true;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move { refresh_podcasts(&read_pool, &write_pool).await })
+                .await;
+
+            if let Ok(Err(err)) = result {
+                warn!(?err, "Could not refresh podcasts: {err}");
+            }
+
+            this.update(cx, |this, cx| {
+                this.working = false;
+                podcasts_changed(cx);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+impl Render for PodcastsView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let locale = cx.global::<Locale>();
+        let focus_handle = self.focus_handle.clone();
+
+        div()
+            .id("podcasts-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(page_title(t!(cx, "sidebar.podcasts")))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(spx(8.0))
+                    .px(spx(18.0))
+                    .pt(spx(6.0))
+                    .pb(spx(10.0))
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .child(
+                        div()
+                            .flex_1()
+                            .px(spx(8.0))
+                            .py(spx(5.0))
+                            .line_height(spx(16.0))
+                            .text_sm()
+                            .border_1()
+                            .border_color(theme.border_color)
+                            .rounded(spx(4.0))
+                            .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                                focus_handle.focus(window)
+                            })
+                            .child(self.input.clone()),
+                    )
+                    .when(!self.working, |this| {
+                        this.child(
+                            button()
+                                .id("podcasts-subscribe")
+                                .intent(ButtonIntent::Primary)
+                                .on_click(cx.listener(|this, _, _, cx| this.subscribe(cx)))
+                                .child(t!(cx, "podcasts.subscribe")),
+                        )
+                        .when(!self.podcasts.is_empty(), |this| {
+                            this.child(
+                                button()
+                                    .id("podcasts-refresh")
+                                    .on_click(cx.listener(|this, _, _, cx| this.refresh(cx)))
+                                    .child(t!(cx, "podcasts.refresh")),
+                            )
+                        })
+                    })
+                    .when(self.working, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.text_secondary)
+                                .child(t!(cx, "podcasts.working")),
+                        )
+                    }),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(error),
+                )
+            })
+            .when(self.podcasts.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "podcasts.empty")),
+                )
+            })
+            .children(self.podcasts.iter().map(|podcast| {
+                let podcast_id = podcast.id;
+                let view_switcher = self.view_switcher.clone();
+
+                div()
+                    .id(("podcast", podcast_id as u64))
+                    .flex()
+                    .items_center()
+                    .px(spx(18.0))
+                    .py(spx(8.0))
+                    .gap(spx(12.0))
+                    .w_full()
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.nav_button_hover))
+                    .active(|this| this.bg(theme.nav_button_active))
+                    .on_click(move |_, _, cx| {
+                        view_switcher
+                            .update(cx, |_, cx| cx.emit(ViewSwitchMessage::Podcast(podcast_id)))
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .flex_shrink()
+                            .overflow_x_hidden()
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .truncate()
+                                    .child(podcast.title.0.clone()),
+                            )
+                            .when_some(podcast.description.clone(), |this, description| {
+                                this.child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(theme.text_secondary)
+                                        .truncate()
+                                        .child(description),
+                                )
+                            }),
+                    )
+                    .when(podcast.new_count > 0, |this| {
+                        this.child(div().ml_auto().child(new_badge(
+                            locale.plural("podcasts.new_count", podcast.new_count),
+                            theme,
+                        )))
+                    })
+            }))
+            .child(div().h(spx(24.0)))
+    }
+}
+
+/// Lists the episodes of a podcast, newest first.
+pub struct PodcastView {
+    podcast: Option<Podcast>,
+    episodes: Vec<PodcastEpisode>,
+    /// The episodes that were new when the podcast was opened. They're marked as seen straight
+    /// away, but are still shown as new until the podcast is closed.
+    new: FxHashSet<i64>,
+    /// Episodes that are being downloaded or fetched to be played.
+    fetching: FxHashSet<i64>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+    error: Option<SharedString>,
+}
+
+impl PodcastView {
+    pub(super) fn new(cx: &mut App, podcast_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let new_episodes = cx.global::<Models>().new_episodes.clone();
+            cx.observe(&new_episodes, move |this: &mut Self, _, cx| {
+                this.load(podcast_id, cx);
+                cx.notify();
+            })
+            .detach();
+
+            // the position and listened state of episodes change as they're played
+            let current_track = cx.global::<PlaybackInfo>().current_track.clone();
+            cx.observe(&current_track, move |this: &mut Self, _, cx| {
+                this.load(podcast_id, cx);
+                cx.notify();
+            })
+            .detach();
+
+            let mut view = PodcastView {
+                podcast: None,
+                episodes: Vec::new(),
+                new: FxHashSet::default(),
+                fetching: FxHashSet::default(),
+                view_switcher: cx.global::<Models>().switcher_model.clone(),
+                error: None,
+            };
+            view.load(podcast_id, cx);
+            view.new = view
+                .episodes
+                .iter()
+                .filter(|episode| !episode.seen)
+                .map(|episode| episode.id)
+                .collect();
+
+            if !view.new.is_empty() {
+                if let Err(err) = cx.mark_podcast_seen(podcast_id) {
+                    error!("Failed to mark podcast as seen: {err}");
+                }
+
+                cx.defer(podcasts_changed);
+            }
+
+            view
+        })
+    }
+
+    fn load(&mut self, podcast_id: i64, cx: &mut App) {
+        self.podcast = cx
+            .get_podcast(podcast_id)
+            .inspect_err(|err| error!("Failed to get podcast: {err}"))
+            .ok();
+        self.episodes = cx.list_podcast_episodes(podcast_id).unwrap_or_else(|err| {
+            error!("Failed to list podcast episodes: {err}");
+            Vec::new()
+        });
+    }
+
+    fn play(&mut self, episode: &PodcastEpisode, cx: &mut Context<Self>) {
+        let Some(task) = play_episode(episode, cx) else {
+            return;
+        };
+
+        let id = episode.id;
+        self.fetching.insert(id);
+        self.error = None;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            task.await;
+
+            this.update(cx, |this, cx| {
+                this.fetching.remove(&id);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn download(&mut self, episode: &PodcastEpisode, cx: &mut Context<Self>) {
+        let write_pool = cx.global::<Pool>().write.clone();
+        let episode = episode.clone();
+        let id = episode.id;
+        self.fetching.insert(id);
+        self.error = None;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(
+                    async move { download_episode(&write_pool, &download_dir(), &episode).await },
+                )
+                .await;
+
+            this.update(cx, |this, cx| {
+                this.fetching.remove(&id);
+
+                match result {
+                    Ok(Ok(_)) => podcasts_changed(cx),
+                    Ok(Err(err)) => {
+                        warn!(?err, "Could not download episode: {err}");
+                        this.error = Some(t!(cx, "podcasts.download_failed"));
+                    }
+                    Err(err) => {
+                        warn!(?err, "Could not download episode: {err}");
+                        this.error = Some(t!(cx, "podcasts.download_failed"));
+                    }
+                }
+
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn delete_download(&mut self, episode: &PodcastEpisode, cx: &mut Context<Self>) {
+        let write_pool = cx.global::<Pool>().write.clone();
+
+        if let Err(err) = crate::RUNTIME.block_on(delete_download(&write_pool, episode)) {
+            error!("Failed to delete downloaded episode: {err}");
+        }
+
+        podcasts_changed(cx);
+    }
+
+    fn set_listened(&mut self, episode_id: i64, listened: bool, cx: &mut Context<Self>) {
+        if let Err(err) = cx.set_podcast_episode_listened(episode_id, listened) {
+            error!("Failed to mark episode as listened: {err}");
+        }
+
+        if let Some(playing) = cx.global_mut::<EpisodePlayback>().playing.as_mut()
+            && playing.id == episode_id
+        {
+            playing.listened = listened;
+            playing.saved = 0;
+        }
+
+        podcasts_changed(cx);
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let Some(podcast) = self.podcast.clone() else {
+            return;
+        };
+
+        let write_pool = cx.global::<Pool>().write.clone();
+        self.error = None;
+
+        cx.spawn(async move |this, cx| {
+            let result = crate::RUNTIME
+                .spawn(async move { refresh_podcast(&write_pool, &podcast).await })
+                .await;
+
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(Ok(())) => podcasts_changed(cx),
+                    Ok(Err(err)) => {
+                        warn!(?err, "Could not refresh podcast: {err}");
+                        this.error = Some(t!(cx, "podcasts.refresh_failed"));
+                    }
+                    Err(err) => {
+                        warn!(?err, "Could not refresh podcast: {err}");
+                        this.error = Some(t!(cx, "podcasts.refresh_failed"));
+                    }
+                }
+
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn unsubscribe(&mut self, cx: &mut Context<Self>) {
+        let Some(podcast_id) = self.podcast.as_ref().map(|podcast| podcast.id) else {
+            return;
+        };
+
+        let write_pool = cx.global::<Pool>().write.clone();
+        if let Err(err) =
+            crate::RUNTIME.block_on(unsubscribe(&write_pool, &download_dir(), podcast_id))
+        {
+            error!("Failed to unsubscribe from podcast: {err}");
+            return;
+        }
+
+        self.view_switcher.update(cx, |messages, cx| {
+            messages.retain(|message| *message != ViewSwitchMessage::Podcast(podcast_id));
+            cx.emit(ViewSwitchMessage::Podcasts);
+        });
+
+        podcasts_changed(cx);
+    }
+
+    fn render_episode(
+        &self,
+        episode: &PodcastEpisode,
+        date_format: &str,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement + use<> {
+        let theme = cx.global::<Theme>();
+        let id = episode.id as u64;
+        let fetching = self.fetching.contains(&episode.id);
+        let downloaded = episode.file_path.is_some();
+
+        let mut details: Vec<SharedString> = Vec::new();
+        if let Some(date) = episode.published_at {
+            // language packs can provide their own date format, but a broken one shouldn't be able
+            // to crash the app
+            let date = match StrftimeItems::new(date_format).parse() {
+                Ok(items) => date.format_with_items(items.iter()).to_string(),
+                Err(_) => date.format("%Y-%m-%d").to_string(),
+            };
+            details.push(date.into());
+        }
+        if episode.listened {
+            details.push(t!(cx, "podcasts.listened"));
+        } else if episode.position > 0
+            && let Some(duration) = episode.duration
+        {
+            let left = format_length(cx, (duration - episode.position).max(0) as u64);
+            details.push(t!(cx, "podcasts.time_left", time = left));
+        } else if let Some(duration) = episode.duration {
+            details.push(format_length(cx, duration as u64));
+        }
+        if downloaded {
+            details.push(t!(cx, "podcasts.downloaded"));
+        }
+
+        let play_label = if !episode.listened && episode.position > 0 {
+            t!(cx, "podcasts.resume")
+        } else {
+            t!(cx, "podcasts.play")
+        };
+
+        let play_episode = episode.clone();
+        let download_episode = episode.clone();
+        let episode_id = episode.id;
+        let listened = episode.listened;
+
+        div()
+            .flex()
+            .items_center()
+            .px(spx(18.0))
+            .py(spx(8.0))
+            .gap(spx(12.0))
+            .w_full()
+            .border_b_1()
+            .border_color(theme.border_color)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_shrink()
+                    .overflow_x_hidden()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap(spx(6.0))
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .truncate()
+                                    .when(listened, |this| this.text_color(theme.text_secondary))
+                                    .child(episode.title.0.clone()),
+                            )
+                            .when(self.new.contains(&episode.id), |this| {
+                                this.child(new_badge(t!(cx, "podcasts.new"), theme))
+                            }),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.text_secondary)
+                            .truncate()
+                            .child(details.join(" · ")),
+                    )
+                    .when_some(episode.description.clone(), |this, description| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.text_secondary)
+                                .truncate()
+                                .child(description),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .ml_auto()
+                    .flex()
+                    .flex_shrink_0()
+                    .gap(spx(6.0))
+                    .when(fetching, |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.text_secondary)
+                                .child(t!(cx, "podcasts.fetching")),
+                        )
+                    })
+                    .when(!fetching, |this| {
+                        this.child(
+                            button()
+                                .id(("episode-play", id))
+                                .intent(ButtonIntent::Primary)
+                                .on_click(
+                                    cx.listener(move |this, _, _, cx| this.play(&play_episode, cx)),
+                                )
+                                .child(play_label),
+                        )
+                        .child(
+                            button()
+                                .id(("episode-download", id))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    if downloaded {
+                                        this.delete_download(&download_episode, cx)
+                                    } else {
+                                        this.download(&download_episode, cx)
+                                    }
+                                }))
+                                .child(if downloaded {
+                                    t!(cx, "podcasts.delete_download")
+                                } else {
+                                    t!(cx, "podcasts.download")
+                                }),
+                        )
+                    })
+                    .child(
+                        button()
+                            .id(("episode-listened", id))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.set_listened(episode_id, !listened, cx)
+                            }))
+                            .child(if listened {
+                                t!(cx, "podcasts.mark_unplayed")
+                            } else {
+                                t!(cx, "podcasts.mark_played")
+                            }),
+                    ),
+            )
+    }
+}
+
+impl Render for PodcastView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(podcast) = self.podcast.clone() else {
+            return div().into_any_element();
+        };
+
+        let date_format = cx
+            .global::<Locale>()
+            .message("release.date_format")
+            .to_string();
+        let episodes: Vec<_> = self
+            .episodes
+            .iter()
+            .map(|episode| {
+                self.render_episode(episode, &date_format, cx)
+                    .into_any_element()
+            })
+            .collect();
+        let theme = cx.global::<Theme>();
+        let episode_count = cx
+            .global::<Locale>()
+            .plural("podcasts.episode_count", self.episodes.len() as i64);
+
+        div()
+            .id("podcast-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(page_title(podcast.title.0.clone()))
+            .when_some(podcast.description.clone(), |this, description| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(4.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .line_clamp(3)
+                        .child(description),
+                )
+            })
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(spx(8.0))
+                    .px(spx(18.0))
+                    .pt(spx(8.0))
+                    .pb(spx(10.0))
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(theme.text_secondary)
+                            .child(episode_count),
+                    )
+                    .child(
+                        button()
+                            .id("podcast-refresh")
+                            .ml_auto()
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx)))
+                            .child(t!(cx, "podcasts.refresh")),
+                    )
+                    .child(
+                        button()
+                            .id("podcast-unsubscribe")
+                            .intent(ButtonIntent::Danger)
+                            .on_click(cx.listener(|this, _, _, cx| this.unsubscribe(cx)))
+                            .child(t!(cx, "podcasts.unsubscribe")),
+                    ),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(error),
+                )
+            })
+            .children(episodes)
+            .child(div().h(spx(24.0)))
+            .into_any_element()
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, InteractiveElement, IntoElement, ParentElement,
+    Render, StatefulInteractiveElement, Styled, Window, actions, div,
+};
+use tracing::error;
+
+use crate::{
+    library::{db::LibraryAccess, types::QuarantinedFile},
+    ui::{
+        components::modal::modal,
+        locale::{Locale, t},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+actions!(library, [ShowQuarantine]);
+
+/// A dialog listing the files that couldn't be added to the library, with the reason each one
+/// failed.
+pub struct Quarantine {
+    show: Entity<bool>,
+    files: Arc<Vec<QuarantinedFile>>,
+}
+
+impl Quarantine {
+    pub fn new(cx: &mut App, show: Entity<bool>) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                // a scan could have added or removed files since the dialog was last opened
+                if *show.read(cx) {
+                    this.files = cx.list_quarantined_files().unwrap_or_else(|err| {
+                        error!("Failed to list quarantined files: {err}");
+                        Arc::new(Vec::new())
+                    });
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            Self {
+                show,
+                files: Arc::new(Vec::new()),
+            }
+        })
+    }
+}
+
+impl Render for Quarantine {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let theme = cx.global::<Theme>();
+
+        let summary = if self.files.is_empty() {
+            t!(cx, "quarantine.empty")
+        } else {
+            cx.global::<Locale>()
+                .plural("quarantine.count", self.files.len() as i64)
+        };
+
+        modal()
+            .child(
+                div()
+                    .w(spx(600.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .child(t!(cx, "quarantine.title")),
+                    )
+                    .child(
+                        div()
+                            .pb(spx(6.0))
+                            .text_color(theme.text_secondary)
+                            .child(summary),
+                    )
+                    .child(
+                        div()
+                            .id("quarantine-list")
+                            .max_h(spx(400.0))
+                            .overflow_y_scroll()
+                            .flex()
+                            .flex_col()
+                            .gap(spx(8.0))
+                            .children(self.files.iter().map(|file| {
+                                div().flex().flex_col().child(file.location.clone()).child(
+                                    div()
+                                        .text_color(theme.text_secondary)
+                                        .child(file.error.clone()),
+                                )
+                            })),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
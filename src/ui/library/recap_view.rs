@@ -0,0 +1,367 @@
+use chrono::{Datelike, Local};
+use gpui::*;
+use prelude::FluentBuilder;
+use tracing::{error, warn};
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        recap::{CardSection, Recap, RecapCard, RecapEntry, render_card},
+    },
+    ui::{
+        components::button::{ButtonIntent, button},
+        library::ViewSwitchMessage,
+        locale::{Locale, t},
+        models::Models,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// The fonts the exported card is drawn with.
+const CARD_FONTS: [&str; 3] = [
+    "!bundled:fonts/Inter-Regular.ttf",
+    "!bundled:fonts/Inter-SemiBold.ttf",
+    "!bundled:fonts/Inter-ExtraBold.ttf",
+];
+
+/// The year the recap should open on: the most recent year with plays, or this year if nothing
+/// has been played yet.
+pub fn latest_recap_year(cx: &mut App) -> i64 {
+    cx.list_recap_years()
+        .ok()
+        .and_then(|years| years.first().copied())
+        .unwrap_or_else(|| Local::now().year() as i64)
+}
+
+fn make_card(cx: &App, recap: &Recap) -> RecapCard {
+    let locale = cx.global::<Locale>();
+    let entries = |entries: &[RecapEntry]| {
+        entries
+            .iter()
+            .map(|entry| {
+                let plays = locale.plural("recap.play_count", entry.play_count);
+                let detail = match &entry.detail {
+                    Some(detail) => format!("{detail} · {plays}"),
+                    None => plays.to_string(),
+                };
+
+                (entry.name.clone(), detail)
+            })
+            .collect()
+    };
+
+    RecapCard {
+        title: t!(cx, "recap.title", year = recap.year).to_string(),
+        stats: vec![
+            (
+                (recap.total_duration / 3600).to_string(),
+                t!(cx, "recap.hours").to_string(),
+            ),
+            (
+                recap.play_count.to_string(),
+                t!(cx, "recap.plays").to_string(),
+            ),
+            (
+                recap.discovered_artists.to_string(),
+                t!(cx, "recap.discovered").to_string(),
+            ),
+        ],
+        sections: [
+            CardSection {
+                heading: t!(cx, "recap.top_artists").to_string(),
+                entries: entries(&recap.top_artists),
+            },
+            CardSection {
+                heading: t!(cx, "recap.top_albums").to_string(),
+                entries: entries(&recap.top_albums),
+            },
+            CardSection {
+                heading: t!(cx, "recap.top_tracks").to_string(),
+                entries: entries(&recap.top_tracks),
+            },
+        ],
+        footer: t!(cx, "recap.footer").to_string(),
+    }
+}
+
+/// A summary of a year of listening, shown as a card that can be exported as an image.
+pub struct RecapView {
+    year: i64,
+    /// The years with plays, most recent first.
+    years: Vec<i64>,
+    card: Option<RecapCard>,
+    export_error: Option<SharedString>,
+}
+
+impl RecapView {
+    pub(super) fn new(cx: &mut App, year: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let years = cx.list_recap_years().unwrap_or_else(|err| {
+                error!("Failed to list the years with plays: {err}");
+                Vec::new()
+            });
+
+            let card = match cx.get_recap(year) {
+                Ok(recap) => Some(make_card(cx, &recap)),
+                Err(err) => {
+                    error!("Failed to put together the recap of {year}: {err}");
+                    None
+                }
+            };
+
+            RecapView {
+                year,
+                years,
+                card,
+                export_error: None,
+            }
+        })
+    }
+
+    fn export(&mut self, cx: &mut Context<Self>) {
+        let Some(card) = self.card.clone() else {
+            return;
+        };
+
+        let Some(dir) = directories::UserDirs::new().and_then(|dirs| {
+            dirs.picture_dir()
+                .or(dirs.document_dir())
+                .map(|dir| dir.to_path_buf())
+        }) else {
+            self.export_error = Some(t!(cx, "recap.export_failed"));
+            cx.notify();
+            return;
+        };
+
+        let fonts: Vec<Vec<u8>> = CARD_FONTS
+            .iter()
+            .filter_map(|path| cx.asset_source().load(path).ok().flatten())
+            .map(|font| font.into_owned())
+            .collect();
+
+        let suggested_name = format!("{}.png", card.title);
+        let path_future = cx.prompt_for_new_path(&dir, Some(&suggested_name));
+        self.export_error = None;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path_future.await else {
+                return;
+            };
+
+            let result = crate::RUNTIME
+                .spawn_blocking(move || {
+                    let png = render_card(&card, fonts)?;
+                    std::fs::write(&path, png)?;
+                    anyhow::Ok(())
+                })
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+
+            if let Err(err) = result {
+                warn!(?err, "Could not export the recap: {err}");
+
+                this.update(cx, |this, cx| {
+                    this.export_error = Some(t!(cx, "recap.export_failed"));
+                    cx.notify();
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+}
+
+fn switch_year(cx: &mut App, year: i64) {
+    let switcher = cx.global::<Models>().switcher_model.clone();
+    switcher.update(cx, |_, cx| cx.emit(ViewSwitchMessage::Recap(year)));
+}
+
+/// Draws a list the same way as on the exported card, so it doesn't follow the theme.
+fn render_section(section: &CardSection) -> Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap(spx(8.0))
+        .flex_1()
+        .min_w(spx(0.0))
+        .child(
+            div()
+                .font_weight(FontWeight::EXTRA_BOLD)
+                .text_size(spx(18.0))
+                .child(section.heading.clone()),
+        )
+        .children(
+            section
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(idx, (name, detail))| {
+                    div()
+                        .flex()
+                        .gap(spx(10.0))
+                        .child(
+                            div()
+                                .w(spx(16.0))
+                                .flex_shrink_0()
+                                .font_weight(FontWeight::SEMIBOLD)
+                                .text_color(rgb(0xa5b4fc))
+                                .child((idx + 1).to_string()),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .min_w(spx(0.0))
+                                .child(
+                                    div()
+                                        .font_weight(FontWeight::SEMIBOLD)
+                                        .truncate()
+                                        .child(name.clone()),
+                                )
+                                .child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(rgb(0xcbd5e1))
+                                        .truncate()
+                                        .child(detail.clone()),
+                                ),
+                        )
+                }),
+        )
+}
+
+impl Render for RecapView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let position = self.years.iter().position(|year| *year == self.year);
+        let newer = position
+            .and_then(|position| position.checked_sub(1))
+            .map(|position| self.years[position]);
+        let older = match position {
+            Some(position) => self.years.get(position + 1).copied(),
+            None => self.years.iter().copied().find(|year| *year < self.year),
+        };
+
+        let header = div()
+            .flex()
+            .items_center()
+            .gap(spx(8.0))
+            .px(spx(18.0))
+            .pb(spx(12.0))
+            .child(
+                div()
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .mr_auto()
+                    .child(t!(cx, "recap.heading")),
+            )
+            .when_some(older, |this, year| {
+                this.child(
+                    button()
+                        .id("recap-older")
+                        .child(year.to_string())
+                        .on_click(move |_, _, cx| switch_year(cx, year)),
+                )
+            })
+            .when_some(newer, |this, year| {
+                this.child(
+                    button()
+                        .id("recap-newer")
+                        .child(year.to_string())
+                        .on_click(move |_, _, cx| switch_year(cx, year)),
+                )
+            })
+            .when(self.card.is_some(), |this| {
+                this.child(
+                    button()
+                        .id("recap-export")
+                        .intent(ButtonIntent::Primary)
+                        .child(t!(cx, "recap.export"))
+                        .on_click(cx.listener(|this, _, _, cx| this.export(cx))),
+                )
+            });
+
+        div()
+            .id("recap-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(header)
+            .when_some(self.export_error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pb(spx(8.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(error),
+                )
+            })
+            .when_some(self.card.as_ref(), |this, card| {
+                this.child(
+                    div().px(spx(18.0)).pb(spx(24.0)).child(
+                        div()
+                            .p(spx(28.0))
+                            .rounded(spx(8.0))
+                            .bg(linear_gradient(
+                                135.0,
+                                linear_color_stop(rgb(0x312e81), 0.0),
+                                linear_color_stop(rgb(0x0f172a), 1.0),
+                            ))
+                            .text_color(rgb(0xffffff))
+                            .flex()
+                            .flex_col()
+                            .gap(spx(24.0))
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::EXTRA_BOLD)
+                                    .text_size(rems(2.25))
+                                    .child(card.title.clone()),
+                            )
+                            .child(div().flex().gap(spx(40.0)).children(card.stats.iter().map(
+                                |(value, label)| {
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .child(
+                                            div()
+                                                .font_weight(FontWeight::EXTRA_BOLD)
+                                                .text_size(rems(2.0))
+                                                .text_color(rgb(0xa5b4fc))
+                                                .child(value.clone()),
+                                        )
+                                        .child(
+                                            div()
+                                                .text_sm()
+                                                .text_color(rgb(0xcbd5e1))
+                                                .child(label.clone()),
+                                        )
+                                },
+                            )))
+                            .child(
+                                div()
+                                    .flex()
+                                    .gap(spx(24.0))
+                                    .child(render_section(&card.sections[0]))
+                                    .child(render_section(&card.sections[1])),
+                            )
+                            .child(render_section(&card.sections[2]))
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(rgb(0x94a3b8))
+                                    .child(card.footer.clone()),
+                            ),
+                    ),
+                )
+            })
+    }
+}
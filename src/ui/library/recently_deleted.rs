@@ -0,0 +1,237 @@
+use chrono::{DateTime, Utc};
+use gpui::*;
+use prelude::FluentBuilder;
+use tracing::error;
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        types::{DeletedPlaylistItem, PlaylistWithCount},
+    },
+    ui::{
+        components::button::button,
+        locale::{Locale, t},
+        models::{Models, PlaylistEvent},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// How long deleted playlists and items are kept for. This has to match the purge query.
+const KEPT_FOR_DAYS: i64 = 30;
+
+fn days_left(deleted_at: DateTime<Utc>) -> i64 {
+    (KEPT_FOR_DAYS - (Utc::now() - deleted_at).num_days()).max(0)
+}
+
+/// Lists the playlists and playlist items that were deleted recently, so that they can be
+/// restored.
+pub struct RecentlyDeletedView {
+    playlists: Vec<PlaylistWithCount>,
+    items: Vec<DeletedPlaylistItem>,
+}
+
+impl RecentlyDeletedView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+
+            cx.subscribe(&playlist_tracker, |this: &mut Self, _, _, cx| {
+                this.load(cx);
+                cx.notify();
+            })
+            .detach();
+
+            let mut view = RecentlyDeletedView {
+                playlists: Vec::new(),
+                items: Vec::new(),
+            };
+            view.load(cx);
+            view
+        })
+    }
+
+    fn load(&mut self, cx: &mut App) {
+        self.playlists = cx.list_deleted_playlists().unwrap_or_else(|err| {
+            error!("Failed to list deleted playlists: {err}");
+            Vec::new()
+        });
+        self.items = cx.list_deleted_playlist_items().unwrap_or_else(|err| {
+            error!("Failed to list deleted playlist items: {err}");
+            Vec::new()
+        });
+    }
+}
+
+fn restore_playlist(playlist_id: i64, cx: &mut App) {
+    if let Err(err) = cx.restore_playlist(playlist_id) {
+        error!("Failed to restore playlist: {err}");
+    }
+
+    let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+    playlist_tracker.update(cx, |_, cx| {
+        cx.emit(PlaylistEvent::PlaylistUpdated(playlist_id));
+    });
+}
+
+fn restore_item(item: &DeletedPlaylistItem, cx: &mut App) {
+    if let Err(err) = cx.restore_playlist_item(item.id) {
+        error!("Failed to restore playlist item: {err}");
+    }
+
+    let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+    let playlist_id = item.playlist_id;
+    playlist_tracker.update(cx, |_, cx| {
+        cx.emit(PlaylistEvent::PlaylistUpdated(playlist_id));
+    });
+}
+
+fn section_heading(title: SharedString, theme: &Theme) -> Div {
+    div()
+        .px(spx(18.0))
+        .pt(spx(14.0))
+        .pb(spx(6.0))
+        .text_sm()
+        .font_weight(FontWeight::BOLD)
+        .text_color(theme.text_secondary)
+        .border_b_1()
+        .border_color(theme.border_color)
+        .child(title)
+}
+
+fn deleted_row(
+    id: impl Into<ElementId>,
+    title: SharedString,
+    detail: SharedString,
+    restore_label: SharedString,
+    on_restore: impl Fn(&mut App) + 'static,
+    theme: &Theme,
+) -> Div {
+    div()
+        .flex()
+        .items_center()
+        .px(spx(18.0))
+        .py(spx(8.0))
+        .gap(spx(12.0))
+        .w_full()
+        .border_b_1()
+        .border_color(theme.border_color)
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .flex_shrink()
+                .overflow_x_hidden()
+                .child(
+                    div()
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .truncate()
+                        .child(title),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .truncate()
+                        .child(detail),
+                ),
+        )
+        .child(
+            div().ml_auto().flex_shrink_0().child(
+                button()
+                    .id(id)
+                    .on_click(move |_, _, cx| on_restore(cx))
+                    .child(restore_label),
+            ),
+        )
+}
+
+impl Render for RecentlyDeletedView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let locale = cx.global::<Locale>();
+        let restore_label = t!(cx, "recently_deleted.restore");
+
+        div()
+            .id("recently-deleted-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .child(t!(cx, "sidebar.recently_deleted")),
+            )
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .pb(spx(6.0))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .child(t!(cx, "recently_deleted.description", days = KEPT_FOR_DAYS)),
+            )
+            .when(self.playlists.is_empty() && self.items.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .pt(spx(10.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "recently_deleted.empty")),
+                )
+            })
+            .when(!self.playlists.is_empty(), |this| {
+                this.child(section_heading(t!(cx, "recently_deleted.playlists"), theme))
+                    .children(self.playlists.iter().map(|playlist| {
+                        let playlist_id = playlist.id;
+                        let days = playlist.deleted_at.map(days_left).unwrap_or(KEPT_FOR_DAYS);
+                        let detail = format!(
+                            "{} · {}",
+                            locale.plural("playlist.song_count", playlist.track_count),
+                            locale.plural("recently_deleted.days_left", days)
+                        );
+
+                        deleted_row(
+                            ("deleted-playlist", playlist_id as u64),
+                            playlist.name.0.clone(),
+                            detail.into(),
+                            restore_label.clone(),
+                            move |cx| restore_playlist(playlist_id, cx),
+                            theme,
+                        )
+                    }))
+            })
+            .when(!self.items.is_empty(), |this| {
+                this.child(section_heading(t!(cx, "recently_deleted.tracks"), theme))
+                    .children(self.items.iter().map(|item| {
+                        let detail = format!(
+                            "{} · {}",
+                            t!(
+                                cx,
+                                "recently_deleted.removed_from",
+                                name = item.playlist_name
+                            ),
+                            locale.plural("recently_deleted.days_left", days_left(item.deleted_at))
+                        );
+                        let item_clone = item.clone();
+
+                        deleted_row(
+                            ("deleted-item", item.id as u64),
+                            item.track_title.0.clone(),
+                            detail.into(),
+                            restore_label.clone(),
+                            move |cx| restore_item(&item_clone, cx),
+                            theme,
+                        )
+                    }))
+            })
+            .child(div().h(spx(24.0)))
+    }
+}
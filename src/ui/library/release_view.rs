@@ -1,345 +1,693 @@
-use std::{f32, sync::Arc};
+use std::sync::Arc;
 
+use chrono::format::StrftimeItems;
 use gpui::*;
 use prelude::FluentBuilder;
+use tracing::{info, warn};
 
 use crate::{
     library::{
+        art,
         db::{AlbumMethod, LibraryAccess},
-        types::{Album, Artist, Track},
+        types::{Album, AlbumFormat, Artist},
     },
     playback::{
         interface::{PlaybackInterface, replace_queue},
         queue::QueueItemData,
         thread::PlaybackState,
     },
+    settings::{SettingsGlobal, interface::TrackListingKind},
     ui::{
+        app::{ActiveLibrary, Pool},
+        caching::HummingbirdImageCache,
+        command_palette::{Command, CommandManager},
         components::{
+            badge::badges,
             button::{ButtonIntent, ButtonSize, button},
-            icons::{CIRCLE_PLUS, PAUSE, PLAY, SHUFFLE, icon},
+            icons::{CIRCLE_PLUS, INFO, PAUSE, PLAY, PLAYLIST_ADD, SHUFFLE, STACK, icon},
         },
         global_actions::PlayPause,
-        library::track_listing::{ArtistNameVisibility, TrackListing},
-        models::PlaybackInfo,
+        library::{
+            ViewSwitchMessage,
+            add_to_playlist::AddToPlaylist,
+            album_art::AlbumArtPicker,
+            merge_albums::MergeAlbums,
+            musicbrainz_info::MusicBrainzInfo,
+            offline::{OfflineTracks, register_offline_commands, unregister_offline_commands},
+            similar_artists::SimilarArtists,
+            split_album::SplitAlbum,
+            track_listing::{ArtistNameVisibility, TrackListing, filter::TrackFilter},
+        },
+        locale::{Locale, t},
+        models::{Models, PlaybackInfo},
         theme::Theme,
+        util::spx,
     },
 };
 
+/// Embeds the album's art into its files.
+#[derive(Clone, PartialEq, Debug, Action)]
+#[action(namespace = album, no_json)]
+pub struct EmbedAlbumArt {
+    pub album_id: i64,
+}
+
+actions!(album, [Merge, Split, ChooseArt]);
+
+pub fn bind_actions(cx: &mut App) {
+    cx.on_action(embed_album_art);
+}
+
+fn embed_album_art(action: &EmbedAlbumArt, cx: &mut App) {
+    let pool = cx.global::<Pool>().read.clone();
+    let max_size = cx
+        .global::<SettingsGlobal>()
+        .model
+        .read(cx)
+        .tags
+        .embed_art_max_size;
+    let album_id = action.album_id;
+    let art_dir = cx.global::<ActiveLibrary>().art_dir();
+
+    crate::RUNTIME.spawn(async move {
+        match art::embed_album_art(&pool, &art_dir, album_id, max_size).await {
+            Ok(written) => info!("Embedded album art into {written} files"),
+            Err(err) => warn!(?err, "Could not embed album art: {err}"),
+        }
+    });
+}
+
 pub struct ReleaseView {
     album: Arc<Album>,
+    format: Option<AlbumFormat>,
     artist: Option<Arc<Artist>>,
-    tracks: Arc<Vec<Track>>,
     track_listing: TrackListing,
-    release_info: Option<SharedString>,
+    track_filter: Entity<TrackFilter>,
+    list_state: ListState,
+    play_count: i64,
     img_path: SharedString,
-    image_cache: Entity<RetainAllImageCache>,
+    image_cache: Entity<HummingbirdImageCache>,
+    similar_artists: Option<Entity<SimilarArtists>>,
+    add_to_playlist: Entity<AddToPlaylist>,
+    show_add_to_playlist: Entity<bool>,
+    musicbrainz_info: Entity<MusicBrainzInfo>,
+    show_musicbrainz_info: Entity<bool>,
+    merge_albums: Entity<MergeAlbums>,
+    show_merge_albums: Entity<bool>,
+    split_album: Entity<SplitAlbum>,
+    show_split_album: Entity<bool>,
+    album_art_picker: Entity<AlbumArtPicker>,
+    show_album_art_picker: Entity<bool>,
+    focus_handle: FocusHandle,
+    first_render: bool,
 }
 
 impl ReleaseView {
     pub(super) fn new(cx: &mut App, album_id: i64) -> Entity<Self> {
         cx.new(|cx| {
-            let image_cache = RetainAllImageCache::new(cx);
+            let image_cache = HummingbirdImageCache::new(1, cx);
             // TODO: error handling
             let album = cx
                 .get_album_by_id(album_id, AlbumMethod::FullQuality)
                 .expect("Failed to retrieve album");
-            let tracks = cx
-                .list_tracks_in_album(album_id)
+            let track_ids = cx
+                .list_track_ids_in_album(album_id)
                 .expect("Failed to retrieve tracks");
             let artist = cx.get_artist_by_id(album.artist_id).ok();
+            let play_count = cx.get_album_play_count(album_id).unwrap_or_default();
+            let format = cx
+                .list_album_formats(&[album_id])
+                .ok()
+                .and_then(|mut formats| formats.remove(&album_id));
 
-            cx.on_release(|this: &mut Self, cx: &mut App| {
-                ImageSource::Resource(Resource::Embedded(this.img_path.clone())).remove_asset(cx);
-            })
-            .detach();
+            // the header and footer are rendered as part of the list, so that only the tracks
+            // that are actually visible need to be loaded
+            let list_state = ListState::new(track_ids.len() + 2, ListAlignment::Top, px(300.0));
+
+            let show_add_to_playlist = cx.new(|_| false);
+            let add_to_playlist =
+                AddToPlaylist::new(cx, show_add_to_playlist.clone(), (*track_ids).clone());
+
+            let show_musicbrainz_info = cx.new(|_| false);
+            let musicbrainz_info =
+                MusicBrainzInfo::new(cx, show_musicbrainz_info.clone(), album.clone());
+
+            let show_merge_albums = cx.new(|_| false);
+            let merge_albums = MergeAlbums::new(
+                cx,
+                show_merge_albums.clone(),
+                album_id,
+                album.title.0.clone(),
+            );
+
+            let show_split_album = cx.new(|_| false);
+            let split_album = SplitAlbum::new(cx, show_split_album.clone(), album_id, None);
+
+            let show_album_art_picker = cx.new(|_| false);
+            let album_art_picker = AlbumArtPicker::new(cx, show_album_art_picker.clone(), album_id);
 
             let track_listing = TrackListing::new(
                 cx,
-                tracks.clone(),
-                px(f32::INFINITY), // render the whole thing
+                track_ids,
                 ArtistNameVisibility::OnlyIfDifferent(artist.as_ref().and_then(|v| v.name.clone())),
             );
 
-            let release_info = {
-                let mut info = String::default();
+            let focus_handle = cx.focus_handle();
+            let category = t!(cx, "command.category.album");
+
+            let name = t!(cx, "command.merge_albums");
+            cx.register_command(
+                ("album::merge", album_id),
+                Command::new(
+                    Some(category.clone()),
+                    name,
+                    Merge,
+                    Some(focus_handle.clone()),
+                ),
+            );
+            let name = t!(cx, "command.split_album");
+            cx.register_command(
+                ("album::split", album_id),
+                Command::new(
+                    Some(category.clone()),
+                    name,
+                    Split,
+                    Some(focus_handle.clone()),
+                ),
+            );
+            let name = t!(cx, "command.choose_album_art");
+            cx.register_command(
+                ("album::choose_art", album_id),
+                Command::new(
+                    Some(category.clone()),
+                    name,
+                    ChooseArt,
+                    Some(focus_handle.clone()),
+                ),
+            );
+
+            register_offline_commands(cx, category.clone(), OfflineTracks::Album(album_id));
+
+            cx.on_release(move |_, cx| {
+                cx.unregister_command(("album::merge", album_id));
+                cx.unregister_command(("album::split", album_id));
+                cx.unregister_command(("album::choose_art", album_id));
+                unregister_offline_commands(cx, OfflineTracks::Album(album_id));
+            })
+            .detach();
 
-                if let Some(label) = &album.label {
-                    info += &label.to_string();
-                }
+            if album.image_hash.is_some() {
+                let name = t!(cx, "command.embed_album_art");
 
-                if album.label.is_some() && album.catalog_number.is_some() {
-                    info += " • ";
-                }
+                cx.register_command(
+                    ("album::embedart", album_id),
+                    Command::new(Some(category), name, EmbedAlbumArt { album_id }, None),
+                );
 
-                if let Some(catalog_number) = &album.catalog_number {
-                    info += &catalog_number.to_string();
-                }
+                cx.on_release(move |_, cx| {
+                    cx.unregister_command(("album::embedart", album_id));
+                })
+                .detach();
+            }
 
-                if !info.is_empty() {
-                    Some(SharedString::from(info))
-                } else {
-                    None
-                }
-            };
+            let similar_artists = artist.as_ref().and_then(|artist| {
+                let name = artist.name.clone()?;
+                Some(SimilarArtists::new(cx, name.to_string(), artist.id))
+            });
+
+            if let Some(similar_artists) = &similar_artists {
+                // the footer grows once the similar artists have loaded, so it needs to be measured
+                // again
+                cx.observe(similar_artists, |this: &mut Self, _, cx| {
+                    let footer = this.track_listing.track_count() + 1;
+                    this.list_state.splice(footer..footer + 1, 1);
+                    cx.notify();
+                })
+                .detach();
+            }
+
+            let track_filter = TrackFilter::new(cx, TrackListingKind::Album);
+            cx.observe(&track_filter, |this: &mut Self, filter, cx| {
+                let old_count = this.track_listing.track_count();
+                let track_ids = this.track_listing.track_ids().clone();
+                let shown = filter.update(cx, |filter, cx| filter.apply(cx, &track_ids));
+
+                this.track_listing.set_shown(cx, shown);
+                this.list_state
+                    .splice(1..old_count + 1, this.track_listing.track_count());
+                // the header holds the filter, which grows when the columns are shown
+                this.list_state.splice(0..1, 1);
+                cx.notify();
+            })
+            .detach();
+
+            // the hash is only there so that choosing other art also changes the url, and the old
+            // art isn't shown from the cache
+            let img_path = SharedString::from(format!(
+                "!db://album/{album_id}/full?{}",
+                album.image_hash.as_deref().unwrap_or_default()
+            ));
 
             ReleaseView {
                 album,
+                format,
                 artist,
-                tracks,
                 track_listing,
-                release_info,
-                img_path: SharedString::from(format!("!db://album/{album_id}/full")),
+                track_filter,
+                list_state,
+                play_count,
+                img_path,
                 image_cache,
+                similar_artists,
+                add_to_playlist,
+                show_add_to_playlist,
+                musicbrainz_info,
+                show_musicbrainz_info,
+                merge_albums,
+                show_merge_albums,
+                split_album,
+                show_split_album,
+                album_art_picker,
+                show_album_art_picker,
+                focus_handle,
+                first_render: true,
             }
         })
     }
 }
 
+pub(crate) fn album_queue_items(cx: &mut App, album_id: i64) -> Vec<QueueItemData> {
+    cx.list_tracks_in_album(album_id)
+        .expect("Failed to retrieve tracks")
+        .iter()
+        .map(|track| QueueItemData::new(cx, track.location.clone(), Some(track.id), track.album_id))
+        .collect()
+}
+
 impl Render for ReleaseView {
-    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        let track_count = self.track_listing.track_count();
+        let render_track = self.track_listing.make_render_fn();
+        let track_filter = self.track_filter.clone();
+
+        let header = ReleaseHeader {
+            album: self.album.clone(),
+            format: self.format.clone(),
+            artist: self.artist.clone(),
+            img_path: self.img_path.clone(),
+            image_cache: self.image_cache.clone(),
+            show_add_to_playlist: self.show_add_to_playlist.clone(),
+            show_musicbrainz_info: self.show_musicbrainz_info.clone(),
+            show_merge_albums: self.show_merge_albums.clone(),
+            show_album_art_picker: self.show_album_art_picker.clone(),
+        };
+        let show_merge_albums = self.show_merge_albums.clone();
+        let show_split_album = self.show_split_album.clone();
+        let show_album_art_picker = self.show_album_art_picker.clone();
+
+        if self.first_render {
+            self.first_render = false;
+            self.focus_handle.focus(window);
+        }
+        let footer = ReleaseFooter {
+            album: self.album.clone(),
+            play_count: self.play_count,
+            similar_artists: self.similar_artists.clone(),
+        };
+
+        div()
+            .id("release-view")
+            .track_focus(&self.focus_handle)
+            .on_action(move |_: &Merge, _, cx| show_merge_albums.write(cx, true))
+            .on_action(move |_: &Split, _, cx| show_split_album.write(cx, true))
+            .on_action(move |_: &ChooseArt, _, cx| show_album_art_picker.write(cx, true))
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .max_w(spx(1000.0))
+            .child(
+                list(self.list_state.clone(), move |idx, window, cx| {
+                    if idx == 0 {
+                        div()
+                            .child(header.clone())
+                            .child(
+                                div()
+                                    .flex()
+                                    .px(spx(18.0))
+                                    .pt(spx(18.0))
+                                    .pb(spx(6.0))
+                                    .child(track_filter.clone()),
+                            )
+                            .into_any_element()
+                    } else if idx == track_count + 1 {
+                        footer.clone().into_any_element()
+                    } else {
+                        render_track(idx - 1, window, cx)
+                    }
+                })
+                .w_full()
+                .h_full(),
+            )
+            .child(self.add_to_playlist.clone())
+            .child(self.musicbrainz_info.clone())
+            .child(self.merge_albums.clone())
+            .child(self.split_album.clone())
+            .child(self.album_art_picker.clone())
+    }
+}
+
+#[derive(Clone, IntoElement)]
+struct ReleaseHeader {
+    album: Arc<Album>,
+    format: Option<AlbumFormat>,
+    artist: Option<Arc<Artist>>,
+    img_path: SharedString,
+    image_cache: Entity<HummingbirdImageCache>,
+    show_add_to_playlist: Entity<bool>,
+    show_musicbrainz_info: Entity<bool>,
+    show_merge_albums: Entity<bool>,
+    show_album_art_picker: Entity<bool>,
+}
+
+impl RenderOnce for ReleaseHeader {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
         let theme = cx.global::<Theme>();
+        let album_id = self.album.id;
+        let show_add_to_playlist = self.show_add_to_playlist.clone();
+        let show_musicbrainz_info = self.show_musicbrainz_info.clone();
+        let show_merge_albums = self.show_merge_albums.clone();
+        let show_album_art_picker = self.show_album_art_picker.clone();
 
         let is_playing =
             cx.global::<PlaybackInfo>().playback_state.read(cx) == &PlaybackState::Playing;
         // flag whether current track is part of the album
-        let current_track_in_album = cx
-            .global::<PlaybackInfo>()
-            .current_track
-            .read(cx)
-            .clone()
-            .is_some_and(|current_track| {
-                self.tracks
-                    .iter()
-                    .any(|track| current_track == track.location)
-            });
+        let current_track_in_album = cx.global::<PlaybackInfo>().current_track.read(cx).is_some()
+            && {
+                let queue = cx.global::<Models>().queue.read(cx);
+                queue
+                    .data
+                    .read()
+                    .expect("couldn't get queue")
+                    .get(queue.position)
+                    .is_some_and(|item| item.get_db_album_id() == Some(album_id))
+            };
+
+        let badges = self
+            .format
+            .and_then(|format| badges(cx, format.format_label(), format.explicit));
 
         div()
-            .id("release-view")
-            .overflow_y_scroll()
-            .pt(px(10.0))
-            .w_full()
             .flex_shrink()
+            .flex()
             .overflow_x_hidden()
-            .max_w(px(1000.0))
+            .px(spx(18.0))
+            .w_full()
             .child(
                 div()
+                    .id("release-art")
+                    .rounded(spx(4.0))
+                    .bg(theme.album_art_background)
+                    .shadow_sm()
+                    .w(spx(160.0))
+                    .h(spx(160.0))
+                    .flex_shrink_0()
+                    .overflow_hidden()
+                    .cursor_pointer()
+                    .on_click(move |_, _, cx| show_album_art_picker.write(cx, true))
+                    .child(
+                        img(self.img_path.clone())
+                            .image_cache(&self.image_cache)
+                            .min_w(spx(160.0))
+                            .min_h(spx(160.0))
+                            .max_w(spx(160.0))
+                            .max_h(spx(160.0))
+                            .overflow_hidden()
+                            .flex()
+                            // TODO: Ideally this should be ObjectFit::Cover, but this
+                            // breaks rounding
+                            // FIXME: This is a GPUI bug
+                            .object_fit(ObjectFit::Fill)
+                            .rounded(spx(4.0)),
+                    ),
+            )
+            .child(
+                div()
+                    .ml(spx(18.0))
+                    .mt_auto()
                     .flex_shrink()
                     .flex()
-                    .overflow_x_hidden()
-                    .px(px(18.0))
+                    .flex_col()
                     .w_full()
+                    .overflow_x_hidden()
                     .child(
                         div()
-                            .rounded(px(4.0))
-                            .bg(theme.album_art_background)
-                            .shadow_sm()
-                            .w(px(160.0))
-                            .h(px(160.0))
-                            .flex_shrink_0()
-                            .overflow_hidden()
-                            .child(
-                                img(self.img_path.clone())
-                                    .image_cache(&self.image_cache)
-                                    .min_w(px(160.0))
-                                    .min_h(px(160.0))
-                                    .max_w(px(160.0))
-                                    .max_h(px(160.0))
-                                    .overflow_hidden()
-                                    .flex()
-                                    // TODO: Ideally this should be ObjectFit::Cover, but this
-                                    // breaks rounding
-                                    // FIXME: This is a GPUI bug
-                                    .object_fit(ObjectFit::Fill)
-                                    .rounded(px(4.0)),
-                            ),
+                            .flex()
+                            .items_center()
+                            .gap(spx(8.0))
+                            .when_some(self.artist.as_ref(), |this, artist| {
+                                let artist_id = artist.id;
+
+                                this.child(
+                                    div()
+                                        .id("release-artist")
+                                        .cursor_pointer()
+                                        .hover(|this| this.underline())
+                                        .on_click(move |_, _, cx| {
+                                            cx.global::<Models>().switcher_model.clone().update(
+                                                cx,
+                                                |_, cx| {
+                                                    cx.emit(ViewSwitchMessage::Artist(artist_id))
+                                                },
+                                            )
+                                        })
+                                        .when_some(artist.name.clone(), |this, name| {
+                                            this.child(name)
+                                        }),
+                                )
+                            })
+                            .children(badges),
                     )
                     .child(
                         div()
-                            .ml(px(18.0))
-                            .mt_auto()
-                            .flex_shrink()
-                            .flex()
-                            .flex_col()
-                            .w_full()
+                            .font_weight(FontWeight::EXTRA_BOLD)
+                            .text_size(rems(2.5))
+                            .line_height(rems(2.75))
                             .overflow_x_hidden()
-                            .child(div().when_some(
-                                self.artist.as_ref().map(|v| v.name.clone()),
-                                |this, artist| this.child(artist.unwrap()),
-                            ))
-                            .child(
-                                div()
-                                    .font_weight(FontWeight::EXTRA_BOLD)
-                                    .text_size(rems(2.5))
-                                    .line_height(rems(2.75))
-                                    .overflow_x_hidden()
-                                    .pb(px(10.0))
-                                    .w_full()
-                                    .text_ellipsis()
-                                    .child(self.album.title.clone()),
-                            )
+                            .pb(spx(10.0))
+                            .w_full()
+                            .text_ellipsis()
+                            .child(self.album.title.clone()),
+                    )
+                    .child(
+                        div()
+                            .gap(spx(10.0))
+                            .flex()
+                            .flex_row()
                             .child(
-                                div()
-                                    .gap(px(10.0))
-                                    .flex()
-                                    .flex_row()
+                                button()
+                                    .id("release-play-button")
+                                    .size(ButtonSize::Large)
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .intent(ButtonIntent::Primary)
+                                    .when(!current_track_in_album, |this| {
+                                        this.on_click(move |_, _, cx| {
+                                            let queue_items = album_queue_items(cx, album_id);
+                                            replace_queue(queue_items, cx)
+                                        })
+                                    })
+                                    .when(current_track_in_album, |button| {
+                                        button.on_click(|_, window, cx| {
+                                            window.dispatch_action(Box::new(PlayPause), cx);
+                                        })
+                                    })
                                     .child(
-                                        button()
-                                            .id("release-play-button")
-                                            .size(ButtonSize::Large)
-                                            .font_weight(FontWeight::SEMIBOLD)
-                                            .intent(ButtonIntent::Primary)
-                                            .when(!current_track_in_album, |this| {
-                                                this.on_click(cx.listener(
-                                                    |this: &mut ReleaseView, _, _, cx| {
-                                                        let queue_items = this
-                                                            .track_listing
-                                                            .tracks()
-                                                            .iter()
-                                                            .map(|track| {
-                                                                QueueItemData::new(
-                                                                    cx,
-                                                                    track.location.clone(),
-                                                                    Some(track.id),
-                                                                    track.album_id,
-                                                                )
-                                                            })
-                                                            .collect();
-
-                                                        replace_queue(queue_items, cx)
-                                                    },
-                                                ))
-                                            })
-                                            .when(current_track_in_album, |button| {
-                                                button.on_click(|_, window, cx| {
-                                                    window.dispatch_action(Box::new(PlayPause), cx);
-                                                })
-                                            })
-                                            .child(
-                                                icon(if current_track_in_album && is_playing {
-                                                    PAUSE
-                                                } else {
-                                                    PLAY
-                                                })
-                                                .size(px(16.0))
-                                                .my_auto(),
-                                            )
-                                            .child(div().child(
-                                                if current_track_in_album && is_playing {
-                                                    "Pause"
-                                                } else {
-                                                    "Play"
-                                                },
-                                            )),
+                                        icon(if current_track_in_album && is_playing {
+                                            PAUSE
+                                        } else {
+                                            PLAY
+                                        })
+                                        .size(spx(16.0))
+                                        .my_auto(),
                                     )
-                                    .child(
-                                        button()
-                                            .id("release-add-button")
-                                            .size(ButtonSize::Large)
-                                            .flex_none()
-                                            .on_click(cx.listener(
-                                                |this: &mut ReleaseView, _, _, cx| {
-                                                    let queue_items = this
-                                                        .track_listing
-                                                        .tracks()
-                                                        .iter()
-                                                        .map(|track| {
-                                                            QueueItemData::new(
-                                                                cx,
-                                                                track.location.clone(),
-                                                                Some(track.id),
-                                                                track.album_id,
-                                                            )
-                                                        })
-                                                        .collect();
+                                    .child(div().child(if current_track_in_album && is_playing {
+                                        t!(cx, "release.pause")
+                                    } else {
+                                        t!(cx, "release.play")
+                                    })),
+                            )
+                            .child(
+                                button()
+                                    .id("release-add-button")
+                                    .tooltip(t!(cx, "release.add_to_queue"))
+                                    .size(ButtonSize::Large)
+                                    .flex_none()
+                                    .on_click(move |_, _, cx| {
+                                        let queue_items = album_queue_items(cx, album_id);
+                                        cx.global::<PlaybackInterface>().queue_list(queue_items);
+                                    })
+                                    .child(icon(CIRCLE_PLUS).size(spx(16.0)).my_auto()),
+                            )
+                            .child(
+                                button()
+                                    .id("release-shuffle-button")
+                                    .tooltip(t!(cx, "release.shuffle"))
+                                    .size(ButtonSize::Large)
+                                    .flex_none()
+                                    .on_click(move |_, _, cx| {
+                                        let queue_items = album_queue_items(cx, album_id);
 
-                                                    cx.global::<PlaybackInterface>()
-                                                        .queue_list(queue_items);
-                                                },
-                                            ))
-                                            .child(icon(CIRCLE_PLUS).size(px(16.0)).my_auto()),
-                                    )
-                                    .child(
-                                        button()
-                                            .id("release-shuffle-button")
-                                            .size(ButtonSize::Large)
-                                            .flex_none()
-                                            .on_click(cx.listener(
-                                                |this: &mut ReleaseView, _, _, cx| {
-                                                    let queue_items = this
-                                                        .track_listing
-                                                        .tracks()
-                                                        .iter()
-                                                        .map(|track| {
-                                                            QueueItemData::new(
-                                                                cx,
-                                                                track.location.clone(),
-                                                                Some(track.id),
-                                                                track.album_id,
-                                                            )
-                                                        })
-                                                        .collect();
-
-                                                    if !(*cx
-                                                        .global::<PlaybackInfo>()
-                                                        .shuffling
-                                                        .read(cx))
-                                                    {
-                                                        cx.global::<PlaybackInterface>()
-                                                            .toggle_shuffle();
-                                                    }
-
-                                                    replace_queue(queue_items, cx)
-                                                },
-                                            ))
-                                            .child(icon(SHUFFLE).size(px(16.0)).my_auto()),
-                                    ),
+                                        if !(*cx.global::<PlaybackInfo>().shuffling.read(cx)) {
+                                            cx.global::<PlaybackInterface>().toggle_shuffle();
+                                        }
+
+                                        replace_queue(queue_items, cx)
+                                    })
+                                    .child(icon(SHUFFLE).size(spx(16.0)).my_auto()),
+                            )
+                            .child(
+                                button()
+                                    .id("release-add-to-playlist-button")
+                                    .tooltip(t!(cx, "release.add_to_playlist"))
+                                    .size(ButtonSize::Large)
+                                    .flex_none()
+                                    .on_click(move |_, _, cx| {
+                                        show_add_to_playlist.write(cx, true);
+                                    })
+                                    .child(icon(PLAYLIST_ADD).size(spx(16.0)).my_auto()),
+                            )
+                            .child(
+                                button()
+                                    .id("release-musicbrainz-button")
+                                    .tooltip(t!(cx, "release.musicbrainz"))
+                                    .size(ButtonSize::Large)
+                                    .flex_none()
+                                    .on_click(move |_, _, cx| {
+                                        show_musicbrainz_info.write(cx, true);
+                                    })
+                                    .child(icon(INFO).size(spx(16.0)).my_auto()),
+                            )
+                            .child(
+                                button()
+                                    .id("release-merge-button")
+                                    .tooltip(t!(cx, "release.merge"))
+                                    .size(ButtonSize::Large)
+                                    .flex_none()
+                                    .on_click(move |_, _, cx| {
+                                        show_merge_albums.write(cx, true);
+                                    })
+                                    .child(icon(STACK).size(spx(16.0)).my_auto()),
                             ),
                     ),
             )
-            .child({
-                let render_fn = self.track_listing.make_render_fn();
-                let what = self.track_listing.track_list_state().clone();
+    }
+}
 
-                list(what, render_fn)
-                    .w_full()
-                    .flex()
-                    .flex_col()
-                    .mx_auto()
-                    .max_h_full()
-                    .with_sizing_behavior(ListSizingBehavior::Infer)
-            })
+#[derive(Clone, IntoElement)]
+struct ReleaseFooter {
+    album: Arc<Album>,
+    play_count: i64,
+    similar_artists: Option<Entity<SimilarArtists>>,
+}
+
+impl RenderOnce for ReleaseFooter {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let locale = cx.global::<Locale>();
+
+        div()
             .when(
-                self.release_info.is_some()
+                self.album.label.is_some()
+                    || self.album.catalog_number.is_some()
                     || self.album.release_date.is_some()
                     || self.album.release_year.is_some()
-                    || self.album.isrc.is_some(),
+                    || self.album.isrc.is_some()
+                    || self.play_count > 0,
                 |this| {
                     this.child(
                         div()
                             .flex()
                             .flex_col()
                             .text_sm()
-                            .ml(px(18.0))
-                            .pt(px(12.0))
-                            .pb(px(24.0))
+                            .ml(spx(18.0))
+                            .pt(spx(12.0))
+                            .pb(spx(24.0))
                             .font_weight(FontWeight::SEMIBOLD)
                             .text_color(theme.text_secondary)
-                            .when_some(self.release_info.clone(), |this, release_info| {
-                                this.child(div().child(release_info))
-                            })
+                            .when(
+                                self.album.label.is_some() || self.album.catalog_number.is_some(),
+                                |this| {
+                                    this.child(
+                                        div()
+                                            .flex()
+                                            .gap(spx(4.0))
+                                            .when_some(self.album.label.clone(), |this, label| {
+                                                let album_id = self.album.id;
+
+                                                this.child(
+                                                    div()
+                                                        .id("release-label")
+                                                        .cursor_pointer()
+                                                        .hover(|this| this.text_color(theme.text))
+                                                        .on_click(move |_, _, cx| {
+                                                            let switcher = cx
+                                                                .global::<Models>()
+                                                                .switcher_model
+                                                                .clone();
+                                                            switcher.update(cx, |_, cx| {
+                                                                cx.emit(ViewSwitchMessage::Label(
+                                                                    album_id,
+                                                                ))
+                                                            });
+                                                        })
+                                                        .child(label),
+                                                )
+                                            })
+                                            .when(
+                                                self.album.label.is_some()
+                                                    && self.album.catalog_number.is_some(),
+                                                |this| this.child("•"),
+                                            )
+                                            .when_some(
+                                                self.album.catalog_number.clone(),
+                                                |this, catalog_number| this.child(catalog_number),
+                                            ),
+                                    )
+                                },
+                            )
                             .when_some(self.album.release_date, |this, date| {
-                                this.child(
-                                    div().child(format!("Released {}", date.format("%B %-e, %Y"))),
-                                )
+                                // language packs can provide their own date format, but a broken one
+                                // shouldn't be able to crash the app
+                                let date =
+                                    match StrftimeItems::new(locale.message("release.date_format"))
+                                        .parse()
+                                    {
+                                        Ok(items) => {
+                                            date.format_with_items(items.iter()).to_string()
+                                        }
+                                        Err(_) => date.format("%Y-%m-%d").to_string(),
+                                    };
+                                this.child(div().child(t!(cx, "release.released", date = date)))
                             })
                             .when_some(self.album.release_year, |this, year| {
-                                this.child(div().child(format!("Released {year}")))
+                                this.child(div().child(t!(cx, "release.released", date = year)))
                             })
                             .when_some(self.album.isrc.as_ref(), |this, isrc| {
                                 this.child(div().child(isrc.clone()))
+                            })
+                            .when(self.play_count > 0, |this| {
+                                this.child(
+                                    div().pt(spx(6.0)).child(
+                                        locale.plural("release.play_count", self.play_count),
+                                    ),
+                                )
                             }),
                     )
                 },
             )
+            .when_some(self.similar_artists, |this, similar_artists| {
+                this.child(similar_artists).child(div().h(spx(24.0)))
+            })
     }
 }
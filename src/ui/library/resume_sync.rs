@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use gpui::{App, Global};
+use tracing::{error, info, warn};
+
+use crate::{
+    library::resume::{
+        LONG_TRACK, ResumeFile, ResumePoint, TrackKey, default_device_name, find_resume_track,
+        find_track_by_key, update_resume_file,
+    },
+    playback::{
+        interface::{PlaybackInterface, replace_queue},
+        queue::QueueItemData,
+        thread::PlaybackState,
+    },
+    settings::SettingsGlobal,
+    ui::{app::Pool, models::PlaybackInfo},
+};
+
+use super::podcasts::{LISTENED_WITHIN, SAVE_INTERVAL};
+
+/// The long track that's playing, so that where it's up to can be shared.
+struct SyncedTrack {
+    key: TrackKey,
+    /// The position that was saved last, in seconds.
+    saved: u64,
+    finished: bool,
+}
+
+#[derive(Default)]
+struct ResumeSync(Option<SyncedTrack>);
+
+impl Global for ResumeSync {}
+
+/// The synced folder and the name of this computer, or `None` if sharing is turned off.
+fn sync_settings(cx: &App) -> Option<(PathBuf, String)> {
+    let settings = &cx.global::<SettingsGlobal>().model.read(cx).resume_sync;
+    let folder = settings.folder.clone()?;
+    let device = settings
+        .device_name
+        .clone()
+        .unwrap_or_else(default_device_name);
+
+    Some((folder, device))
+}
+
+/// Changes the file in the background, so that a slow synced folder doesn't hold up playback.
+fn update_file(folder: PathBuf, change: impl FnOnce(&mut ResumeFile) + Send + 'static) {
+    crate::RUNTIME.spawn_blocking(move || {
+        if let Err(err) = update_resume_file(&folder, change) {
+            warn!("Failed to update the resume file in {:?}: {err}", folder);
+        }
+    });
+}
+
+/// Saves where the playing track is up to, if it's shared.
+fn save_point(cx: &mut App, position: u64) {
+    let Some((folder, device)) = sync_settings(cx) else {
+        return;
+    };
+    let Some(synced) = cx.global_mut::<ResumeSync>().0.as_mut() else {
+        return;
+    };
+
+    synced.saved = position;
+    let point = ResumePoint::new(synced.key.clone(), position, device);
+    let playing = *cx.global::<PlaybackInfo>().playback_state.read(cx) == PlaybackState::Playing;
+
+    update_file(folder, move |file| file.save(point, playing));
+}
+
+/// Shares where long tracks and audiobook chapters are up to with other computers through the
+/// resume file, and picks them up from where another computer left them.
+pub fn track_resume_sync(cx: &mut App) {
+    cx.set_global(ResumeSync::default());
+
+    let current_track = cx.global::<PlaybackInfo>().current_track.clone();
+    let position = cx.global::<PlaybackInfo>().position.clone();
+    let playback_state = cx.global::<PlaybackInfo>().playback_state.clone();
+
+    cx.observe(&current_track, |current_track, cx| {
+        cx.global_mut::<ResumeSync>().0 = None;
+
+        let Some(track) = current_track.read(cx).clone() else {
+            return;
+        };
+        let Some((folder, device)) = sync_settings(cx) else {
+            return;
+        };
+
+        let pool = cx.global::<Pool>().read.clone();
+        let found = crate::RUNTIME
+            .block_on(find_resume_track(
+                &pool,
+                &track.get_path().to_string_lossy(),
+            ))
+            .unwrap_or_else(|err| {
+                error!("Failed to look up track to resume: {err}");
+                None
+            });
+
+        let Some((key, duration, audiobook)) = found else {
+            return;
+        };
+        if !audiobook && duration < LONG_TRACK {
+            return;
+        }
+
+        cx.global_mut::<ResumeSync>().0 = Some(SyncedTrack {
+            key: key.clone(),
+            saved: 0,
+            finished: false,
+        });
+
+        cx.spawn(async move |cx| {
+            let file = crate::RUNTIME
+                .spawn_blocking(move || ResumeFile::read(&folder))
+                .await;
+
+            let point = match file {
+                Ok(Ok(file)) => file.point(&key).cloned(),
+                Ok(Err(err)) => {
+                    warn!("Failed to read the resume file: {err}");
+                    None
+                }
+                Err(err) => {
+                    warn!("Failed to read the resume file: {err}");
+                    None
+                }
+            };
+
+            // points saved here are already picked up by the track's own resume, if it has one
+            let Some(point) = point.filter(|point| point.device != device && point.position > 0)
+            else {
+                return;
+            };
+
+            cx.update(|cx| {
+                let Some(synced) = cx.global_mut::<ResumeSync>().0.as_mut() else {
+                    return;
+                };
+                if synced.key != point.track {
+                    return;
+                }
+
+                info!("Resuming from where {} left off", point.device);
+                synced.saved = point.position;
+                cx.global::<PlaybackInterface>().seek(point.position as f64);
+            })
+            .ok();
+        })
+        .detach();
+    })
+    .detach();
+
+    cx.observe(&position, |position, cx| {
+        let position = *position.read(cx);
+        let duration = *cx.global::<PlaybackInfo>().duration.read(cx);
+
+        let Some(synced) = cx.global_mut::<ResumeSync>().0.as_mut() else {
+            return;
+        };
+
+        // the position is reset to zero when the track is opened, before it's moved to where it
+        // was left
+        if synced.finished || position == 0 {
+            return;
+        }
+
+        if duration > LISTENED_WITHIN && position + LISTENED_WITHIN >= duration {
+            // the next time the track is played, on any computer, it starts from the beginning
+            synced.finished = true;
+            let key = synced.key.clone();
+
+            if let Some((folder, _)) = sync_settings(cx) {
+                update_file(folder, move |file| file.remove(&key));
+            }
+        } else if position.abs_diff(synced.saved) >= SAVE_INTERVAL {
+            save_point(cx, position);
+        }
+    })
+    .detach();
+
+    cx.observe(&playback_state, move |_, cx| {
+        let position = *position.read(cx);
+        let finished = cx
+            .global::<ResumeSync>()
+            .0
+            .as_ref()
+            .is_none_or(|synced| synced.finished);
+
+        if !finished && position > 0 {
+            save_point(cx, position);
+        }
+    })
+    .detach();
+}
+
+/// Plays the track that another computer played last, from where it was left.
+pub fn resume_from_other_device(cx: &mut App) {
+    let Some((folder, device)) = sync_settings(cx) else {
+        info!("Can't resume from another computer, as no resume folder is set");
+        return;
+    };
+
+    cx.spawn(async move |cx| {
+        let file = crate::RUNTIME
+            .spawn_blocking(move || ResumeFile::read(&folder))
+            .await;
+
+        let current = match file {
+            Ok(Ok(file)) => file.current,
+            Ok(Err(err)) => {
+                warn!("Failed to read the resume file: {err}");
+                return;
+            }
+            Err(err) => {
+                warn!("Failed to read the resume file: {err}");
+                return;
+            }
+        };
+
+        let Some(current) = current.filter(|current| current.device != device) else {
+            info!("No other computer has played anything since this one");
+            return;
+        };
+
+        cx.update(|cx| {
+            let pool = cx.global::<Pool>().read.clone();
+            let track = crate::RUNTIME
+                .block_on(find_track_by_key(&pool, &current.track))
+                .unwrap_or_else(|err| {
+                    error!("Failed to look up track to resume: {err}");
+                    None
+                });
+
+            let Some((id, location, album_id)) = track else {
+                info!(
+                    "{:?} from {:?} isn't in the library",
+                    current.track.title, current.track.album
+                );
+                return;
+            };
+
+            // the track is moved to where it was left once it's opened
+            let item = QueueItemData::new(cx, location, Some(id), album_id);
+            replace_queue(vec![item], cx);
+        })
+        .ok();
+    })
+    .detach();
+}
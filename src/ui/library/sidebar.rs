@@ -1,21 +1,30 @@
 use std::{collections::VecDeque, sync::Arc};
 
 use gpui::{
-    App, AppContext, Context, Entity, IntoElement, ParentElement, Render,
-    StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder, px,
+    App, AppContext, Context, Entity, FontWeight, IntoElement, ParentElement, Render, SharedString,
+    StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder,
 };
 
 use crate::{
     library::{db::LibraryAccess, types::TrackStats},
+    settings::SettingsGlobal,
     ui::{
+        app::{ActiveLibrary, switch_library},
         components::{
-            icons::{DISC, SEARCH, SIDEBAR_INACTIVE},
+            icons::{
+                BOOK, CALENDAR, DISC, FOLDER, HISTORY, MICROPHONE, PLAYLIST, SEARCH,
+                SIDEBAR_INACTIVE, STACK, VINYL,
+            },
             nav_button::nav_button,
             sidebar::{sidebar, sidebar_item, sidebar_separator},
+            tooltip::tooltip,
         },
         global_actions::Search,
         library::{ViewSwitchMessage, sidebar::playlists::PlaylistList},
+        locale::{Locale, t},
+        models::Models,
         theme::Theme,
+        util::spx,
     },
 };
 
@@ -25,16 +34,31 @@ pub struct Sidebar {
     playlists: Entity<PlaylistList>,
     track_stats: Arc<TrackStats>,
     nav_model: Entity<VecDeque<ViewSwitchMessage>>,
+    show_sidebar: Entity<bool>,
 }
 
 impl Sidebar {
-    pub fn new(cx: &mut App, nav_model: Entity<VecDeque<ViewSwitchMessage>>) -> Entity<Self> {
+    pub fn new(
+        cx: &mut App,
+        nav_model: Entity<VecDeque<ViewSwitchMessage>>,
+        show_sidebar: Entity<bool>,
+    ) -> Entity<Self> {
         cx.new(|cx| {
             cx.observe(&nav_model, |_, _, cx| cx.notify()).detach();
+
+            let new_episodes = cx.global::<Models>().new_episodes.clone();
+            cx.observe(&new_episodes, |_, _, cx| cx.notify()).detach();
+
+            let tags = cx.global::<Models>().tags.clone();
+            cx.observe(&tags, |_, _, cx| cx.notify()).detach();
+
+            let settings = cx.global::<SettingsGlobal>().model.clone();
+            cx.observe(&settings, |_, _, cx| cx.notify()).detach();
             Self {
                 playlists: PlaylistList::new(cx, nav_model.clone()),
                 track_stats: cx.get_track_stats().unwrap(),
                 nav_model,
+                show_sidebar,
             }
         })
     }
@@ -46,30 +70,71 @@ impl Render for Sidebar {
         let stats_minutes = self.track_stats.total_duration / 60;
         let stats_hours = stats_minutes / 60;
         let current_view = self.nav_model.read(cx);
+        let new_episodes = *cx.global::<Models>().new_episodes.read(cx);
+        let tags = cx.global::<Models>().tags.read(cx).clone();
+        let libraries: Vec<SharedString> = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .libraries
+            .iter()
+            .map(|profile| SharedString::from(profile.name.clone()))
+            .collect();
+        let active_library = cx.global::<ActiveLibrary>().name.clone();
 
         sidebar()
             .id("main-sidebar")
             .max_h_full()
-            .pt(px(10.0))
-            .pb(px(12.0))
-            .pl(px(12.0))
-            .pr(px(11.0))
+            .pt(spx(10.0))
+            .pb(spx(12.0))
+            .pl(spx(12.0))
+            .pr(spx(11.0))
             .border_r_1()
             .border_color(theme.border_color)
             .child(
                 div()
                     .flex()
-                    .mb(px(10.0))
-                    .mx(px(-2.0))
-                    .child(nav_button("search", SEARCH).on_click(|_, window, cx| {
-                        window.dispatch_action(Box::new(Search), cx);
-                    }))
-                    .child(nav_button("sidebar-toggle", SIDEBAR_INACTIVE).ml_auto()),
+                    .mb(spx(10.0))
+                    .mx(spx(-2.0))
+                    .child(
+                        nav_button("search", SEARCH)
+                            .tooltip(tooltip(t!(cx, "navigation.search")))
+                            .on_click(|_, window, cx| {
+                                window.dispatch_action(Box::new(Search), cx);
+                            }),
+                    )
+                    .child(
+                        nav_button("sidebar-toggle", SIDEBAR_INACTIVE)
+                            .ml_auto()
+                            .tooltip(tooltip(t!(cx, "navigation.hide_sidebar")))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.show_sidebar.write(cx, false);
+                            })),
+                    ),
             )
+            .when(!libraries.is_empty(), |this| {
+                this.child(
+                    sidebar_item("library-default")
+                        .icon(STACK)
+                        .child(t!(cx, "sidebar.default_library"))
+                        .on_click(|_, _, cx| switch_library(cx, None))
+                        .when(active_library.is_none(), |this| this.active()),
+                )
+                .children(libraries.into_iter().enumerate().map(|(idx, name)| {
+                    let active = active_library.as_deref() == Some(name.as_ref());
+
+                    sidebar_item(("library", idx))
+                        .icon(STACK)
+                        .child(name.clone())
+                        .on_click(move |_, _, cx| switch_library(cx, Some(name.to_string())))
+                        .when(active, |this| this.active())
+                }))
+                .child(sidebar_separator())
+            })
             .child(
                 sidebar_item("albums")
                     .icon(DISC)
-                    .child("Albums")
+                    .child(t!(cx, "sidebar.albums"))
                     .on_click(cx.listener(|this, _, _, cx| {
                         this.nav_model.update(cx, |_, cx| {
                             cx.emit(ViewSwitchMessage::Albums);
@@ -78,11 +143,152 @@ impl Render for Sidebar {
                     .when(
                         matches!(
                             current_view.iter().last(),
-                            Some(ViewSwitchMessage::Albums) | Some(ViewSwitchMessage::Release(_))
+                            Some(ViewSwitchMessage::Albums)
+                                | Some(ViewSwitchMessage::Release(_))
+                                | Some(ViewSwitchMessage::Artist(_))
+                        ),
+                        |this| this.active(),
+                    ),
+            )
+            .child(
+                sidebar_item("years")
+                    .icon(CALENDAR)
+                    .child(t!(cx, "sidebar.years"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::Years);
+                        });
+                    }))
+                    .when(
+                        matches!(
+                            current_view.iter().last(),
+                            Some(ViewSwitchMessage::Years)
+                                | Some(ViewSwitchMessage::Decade(_))
+                                | Some(ViewSwitchMessage::Year(_))
+                        ),
+                        |this| this.active(),
+                    ),
+            )
+            .child(
+                sidebar_item("labels")
+                    .icon(VINYL)
+                    .child(t!(cx, "sidebar.labels"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::Labels);
+                        });
+                    }))
+                    .when(
+                        matches!(
+                            current_view.iter().last(),
+                            Some(ViewSwitchMessage::Labels) | Some(ViewSwitchMessage::Label(_))
+                        ),
+                        |this| this.active(),
+                    ),
+            )
+            .child(
+                sidebar_item("folders")
+                    .icon(FOLDER)
+                    .child(t!(cx, "sidebar.folders"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::Folders);
+                        });
+                    }))
+                    .when(
+                        matches!(current_view.iter().last(), Some(ViewSwitchMessage::Folders)),
+                        |this| this.active(),
+                    ),
+            )
+            .child(
+                sidebar_item("audiobooks")
+                    .icon(BOOK)
+                    .child(t!(cx, "sidebar.audiobooks"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::Audiobooks);
+                        });
+                    }))
+                    .when(
+                        matches!(
+                            current_view.iter().last(),
+                            Some(ViewSwitchMessage::Audiobooks)
                         ),
                         |this| this.active(),
                     ),
             )
+            .child(
+                sidebar_item("podcasts")
+                    .icon(MICROPHONE)
+                    .child(
+                        div()
+                            .flex()
+                            .gap(spx(6.0))
+                            .child(
+                                div()
+                                    .flex_shrink()
+                                    .truncate()
+                                    .child(t!(cx, "sidebar.podcasts")),
+                            )
+                            .when(new_episodes > 0, |this| {
+                                this.child(
+                                    div()
+                                        .ml_auto()
+                                        .flex_shrink_0()
+                                        .px(spx(6.0))
+                                        .rounded(spx(8.0))
+                                        .text_xs()
+                                        .font_weight(FontWeight::BOLD)
+                                        .bg(theme.button_primary)
+                                        .text_color(theme.button_primary_text)
+                                        .child(SharedString::from(new_episodes.to_string())),
+                                )
+                            }),
+                    )
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::Podcasts);
+                        });
+                    }))
+                    .when(
+                        matches!(
+                            current_view.iter().last(),
+                            Some(ViewSwitchMessage::Podcasts) | Some(ViewSwitchMessage::Podcast(_))
+                        ),
+                        |this| this.active(),
+                    ),
+            )
+            .child(
+                sidebar_item("history")
+                    .icon(HISTORY)
+                    .child(t!(cx, "sidebar.history"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::History);
+                        });
+                    }))
+                    .when(
+                        matches!(current_view.iter().last(), Some(ViewSwitchMessage::History)),
+                        |this| this.active(),
+                    ),
+            )
+            .when(!tags.is_empty(), |this| this.child(sidebar_separator()))
+            .children(tags.iter().map(|tag| {
+                let tag_id = tag.id;
+
+                sidebar_item(("main-sidebar-tag", tag_id as u64))
+                    .icon(PLAYLIST)
+                    .child(tag.name.clone())
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::Tag(tag_id));
+                        });
+                    }))
+                    .when(
+                        current_view.iter().last() == Some(&ViewSwitchMessage::Tag(tag_id)),
+                        |this| this.active(),
+                    )
+            }))
             .child(sidebar_separator())
             .child(self.playlists.clone())
             .child(
@@ -91,17 +297,17 @@ impl Render for Sidebar {
                     .flex_col()
                     .mt_auto()
                     .text_xs()
-                    .pt(px(8.0))
+                    .pt(spx(8.0))
                     .text_color(theme.text_secondary)
-                    .child(if self.track_stats.track_count != 1 {
-                        format!("{} tracks", self.track_stats.track_count)
-                    } else {
-                        format!("{} track", self.track_stats.track_count)
-                    })
-                    .child(format!(
-                        "{} hours, {} minutes",
-                        stats_hours,
-                        stats_minutes % 60
+                    .child(
+                        cx.global::<Locale>()
+                            .plural("sidebar.track_count", self.track_stats.track_count),
+                    )
+                    .child(t!(
+                        cx,
+                        "sidebar.duration",
+                        hours = stats_hours,
+                        minutes = stats_minutes % 60
                     )),
             )
     }
@@ -2,30 +2,35 @@ use std::{collections::VecDeque, sync::Arc};
 
 use gpui::{
     App, AppContext, Context, Entity, FontWeight, InteractiveElement, ParentElement, Render,
-    StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder, px,
+    StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder,
 };
 use tracing::error;
 
 use crate::{
     library::{
         db::LibraryAccess,
+        playlist::{PlaylistCover, set_playlist_cover},
         types::{PlaylistType, PlaylistWithCount},
     },
     ui::{
         components::{
             context::context,
-            icons::{CROSS, PLAYLIST, STAR},
+            icons::{CROSS, LAYOUT_GRID, PHOTO, PHOTO_OFF, PLAYLIST, PLAYLIST_REMOVE, STAR, TRASH},
             menu::{menu, menu_item},
             sidebar::sidebar_item,
         },
         library::ViewSwitchMessage,
+        locale::{Locale, t},
         models::{Models, PlaylistEvent},
         theme::Theme,
+        util::spx,
     },
 };
 
 pub struct PlaylistList {
     playlists: Arc<Vec<PlaylistWithCount>>,
+    /// The number of playlists and items that can be restored from the recently deleted section.
+    deleted_count: i64,
     nav_model: Entity<VecDeque<ViewSwitchMessage>>,
 }
 
@@ -40,6 +45,7 @@ impl PlaylistList {
                 &playlist_tracker,
                 |this: &mut Self, _, _: &PlaylistEvent, cx| {
                     this.playlists = cx.get_all_playlists().unwrap();
+                    this.deleted_count = cx.count_deleted_playlist_entries().unwrap_or_default();
 
                     cx.notify();
                 },
@@ -53,6 +59,7 @@ impl PlaylistList {
 
             Self {
                 playlists: playlists.clone(),
+                deleted_count: cx.count_deleted_playlist_entries().unwrap_or_default(),
                 nav_model,
             }
         })
@@ -80,18 +87,18 @@ impl Render for PlaylistList {
                             } else {
                                 PLAYLIST
                             })
+                            .image(playlist.image_path())
                             .child(playlist.name.clone())
                             .child(
                                 div()
                                     .font_weight(FontWeight::NORMAL)
                                     .text_color(theme.text_secondary)
                                     .text_xs()
-                                    .mt(px(2.0))
-                                    .child(if playlist.track_count == 1 {
-                                        format!("{} song", playlist.track_count)
-                                    } else {
-                                        format!("{} songs", playlist.track_count)
-                                    }),
+                                    .mt(spx(2.0))
+                                    .child(
+                                        cx.global::<Locale>()
+                                            .plural("playlist.song_count", playlist.track_count),
+                                    ),
                             )
                             .on_click(cx.listener(move |this, _, _, cx| {
                                 this.nav_model.update(cx, move |_, cx| {
@@ -105,37 +112,99 @@ impl Render for PlaylistList {
                             ),
                     )
                     .child(
-                        div()
-                            .bg(theme.elevated_background)
-                            .child(menu().item(menu_item(
-                                "delete_playlist",
-                                Some(CROSS),
-                                "Delete playlist",
-                                move |_, _, cx| {
-                                    if let Err(err) = cx.delete_playlist(pl_id) {
-                                        error!("Failed to delete playlist: {}", err);
-                                    }
-
-                                    let playlist_tracker =
-                                        cx.global::<Models>().playlist_tracker.clone();
-
-                                    playlist_tracker.update(cx, |_, cx| {
-                                        cx.emit(PlaylistEvent::PlaylistDeleted(pl_id))
-                                    });
-
-                                    let switcher_model =
-                                        cx.global::<Models>().switcher_model.clone();
-
-                                    switcher_model.update(cx, |view_switch_messages, cx| {
-                                        view_switch_messages
-                                            .retain(|v| *v != ViewSwitchMessage::Playlist(pl_id));
-
-                                        cx.emit(ViewSwitchMessage::Refresh);
-
-                                        cx.notify();
-                                    })
-                                },
-                            ))),
+                        div().bg(theme.elevated_background).child(
+                            menu()
+                                .item(menu_item(
+                                    "choose_playlist_cover",
+                                    Some(PHOTO),
+                                    t!(cx, "playlist.choose_cover"),
+                                    move |_, _, cx| {
+                                        set_playlist_cover(cx, pl_id, PlaylistCover::Pick)
+                                    },
+                                ))
+                                .item(menu_item(
+                                    "collage_playlist_cover",
+                                    Some(LAYOUT_GRID),
+                                    t!(cx, "playlist.collage_cover"),
+                                    move |_, _, cx| {
+                                        set_playlist_cover(cx, pl_id, PlaylistCover::Collage)
+                                    },
+                                ))
+                                .when(playlist.image_hash.is_some(), |this| {
+                                    this.item(menu_item(
+                                        "remove_playlist_cover",
+                                        Some(PHOTO_OFF),
+                                        t!(cx, "playlist.remove_cover"),
+                                        move |_, _, cx| {
+                                            set_playlist_cover(cx, pl_id, PlaylistCover::Remove)
+                                        },
+                                    ))
+                                })
+                                .item(menu_item(
+                                    "remove_playlist_duplicates",
+                                    Some(PLAYLIST_REMOVE),
+                                    t!(cx, "playlist.remove_duplicates"),
+                                    move |_, _, cx| {
+                                        if let Err(err) = cx.remove_playlist_duplicates(pl_id) {
+                                            error!("Failed to remove duplicates: {}", err);
+                                        }
+
+                                        let playlist_tracker =
+                                            cx.global::<Models>().playlist_tracker.clone();
+
+                                        playlist_tracker.update(cx, |_, cx| {
+                                            cx.emit(PlaylistEvent::PlaylistUpdated(pl_id))
+                                        });
+                                    },
+                                ))
+                                .item(menu_item(
+                                    "delete_playlist",
+                                    Some(CROSS),
+                                    t!(cx, "playlist.delete"),
+                                    move |_, _, cx| {
+                                        if let Err(err) = cx.delete_playlist(pl_id) {
+                                            error!("Failed to delete playlist: {}", err);
+                                        }
+
+                                        let playlist_tracker =
+                                            cx.global::<Models>().playlist_tracker.clone();
+
+                                        playlist_tracker.update(cx, |_, cx| {
+                                            cx.emit(PlaylistEvent::PlaylistDeleted(pl_id))
+                                        });
+
+                                        let switcher_model =
+                                            cx.global::<Models>().switcher_model.clone();
+
+                                        switcher_model.update(cx, |view_switch_messages, cx| {
+                                            view_switch_messages.retain(|v| {
+                                                *v != ViewSwitchMessage::Playlist(pl_id)
+                                            });
+
+                                            cx.emit(ViewSwitchMessage::Refresh);
+
+                                            cx.notify();
+                                        })
+                                    },
+                                )),
+                        ),
+                    ),
+            );
+        }
+
+        if self.deleted_count > 0 {
+            main = main.child(
+                sidebar_item("recently-deleted")
+                    .icon(TRASH)
+                    .child(t!(cx, "sidebar.recently_deleted"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.nav_model.update(cx, |_, cx| {
+                            cx.emit(ViewSwitchMessage::RecentlyDeleted);
+                        });
+                    }))
+                    .when(
+                        current_view.iter().last() == Some(&ViewSwitchMessage::RecentlyDeleted),
+                        |this| this.active(),
                     ),
             );
         }
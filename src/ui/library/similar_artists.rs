@@ -0,0 +1,155 @@
+use gpui::*;
+use prelude::FluentBuilder;
+use tracing::debug;
+
+use crate::{
+    services::artist_info::{SimilarArtist, find_similar_artists},
+    ui::{
+        app::Pool,
+        components::icons::{USER, icon},
+        locale::t,
+        models::Models,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+use super::ViewSwitchMessage;
+
+/// A strip of artists from the library that are similar to the given artist, shown at the bottom
+/// of the artist and release views. Nothing is shown if no similar artists are found.
+pub struct SimilarArtists {
+    artists: Vec<SimilarArtist>,
+}
+
+impl SimilarArtists {
+    pub(super) fn new(cx: &mut App, artist_name: String, artist_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let pool = cx.global::<Pool>().read.clone();
+
+            cx.spawn(async move |this: WeakEntity<Self>, cx| {
+                let result = crate::RUNTIME
+                    .spawn(async move { find_similar_artists(&pool, &artist_name).await })
+                    .await;
+
+                match result {
+                    Ok(Ok(artists)) => {
+                        this.update(cx, |this, cx| {
+                            this.artists = artists
+                                .into_iter()
+                                .filter(|artist| artist.id != artist_id)
+                                .collect();
+                            cx.notify();
+                        })
+                        .ok();
+                    }
+                    Ok(Err(err)) => debug!(?err, "Could not find similar artists: {err}"),
+                    Err(err) => debug!(?err, "Could not find similar artists: {err}"),
+                }
+            })
+            .detach();
+
+            Self {
+                artists: Vec::new(),
+            }
+        })
+    }
+}
+
+impl Render for SimilarArtists {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.artists.is_empty() {
+            return div().into_any_element();
+        }
+
+        let theme = cx.global::<Theme>();
+        let view_switcher = cx.global::<Models>().switcher_model.clone();
+
+        div()
+            .flex()
+            .flex_col()
+            .w_full()
+            .child(
+                div()
+                    .text_color(theme.text_secondary)
+                    .text_sm()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .px(spx(18.0))
+                    .border_b_1()
+                    .w_full()
+                    .border_color(theme.border_color)
+                    .mt(spx(24.0))
+                    .pb(spx(6.0))
+                    .child(t!(cx, "artist.similar")),
+            )
+            .child(
+                div()
+                    .id("similar-artists")
+                    .flex()
+                    .gap(spx(6.0))
+                    .px(spx(12.0))
+                    .pt(spx(12.0))
+                    .overflow_x_scroll()
+                    .children(self.artists.iter().map(|artist| {
+                        let artist_id = artist.id;
+                        let view_switcher = view_switcher.clone();
+
+                        div()
+                            .id(("similar-artist", artist_id as u64))
+                            .flex()
+                            .flex_col()
+                            .items_center()
+                            .flex_shrink_0()
+                            .w(spx(96.0))
+                            .p(spx(6.0))
+                            .gap(spx(6.0))
+                            .rounded(spx(4.0))
+                            .cursor_pointer()
+                            .hover(|this| this.bg(theme.nav_button_hover))
+                            .active(|this| this.bg(theme.nav_button_active))
+                            .on_click(move |_, _, cx| {
+                                view_switcher.update(cx, |_, cx| {
+                                    cx.emit(ViewSwitchMessage::Artist(artist_id))
+                                })
+                            })
+                            .child(
+                                div()
+                                    .w(spx(72.0))
+                                    .h(spx(72.0))
+                                    .rounded_full()
+                                    .overflow_hidden()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .bg(theme.album_art_background)
+                                    .map(|this| match &artist.image_hash {
+                                        Some(hash) => this.child(
+                                            img(SharedString::from(format!(
+                                                "!db://artist/{artist_id}/image?{hash}"
+                                            )))
+                                            .w(spx(72.0))
+                                            .h(spx(72.0))
+                                            .object_fit(ObjectFit::Cover)
+                                            .rounded_full(),
+                                        ),
+                                        None => this.child(
+                                            icon(USER)
+                                                .size(spx(32.0))
+                                                .text_color(theme.text_secondary),
+                                        ),
+                                    }),
+                            )
+                            .child(
+                                div()
+                                    .w_full()
+                                    .text_sm()
+                                    .text_center()
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .truncate()
+                                    .child(artist.name.clone()),
+                            )
+                    })),
+            )
+            .into_any_element()
+    }
+}
@@ -0,0 +1,281 @@
+use gpui::{
+    App, AppContext, Context, Entity, FocusHandle, FontWeight, InteractiveElement, IntoElement,
+    MouseButton, ParentElement, Render, SharedString, StatefulInteractiveElement, Styled, Window,
+    div, prelude::FluentBuilder,
+};
+use rustc_hash::FxHashSet;
+use tracing::error;
+
+use crate::{
+    library::db::{AlbumMethod, LibraryAccess},
+    ui::{
+        components::{
+            button::{ButtonIntent, button},
+            input::{EnrichedInputAction, TextInput},
+            modal::modal,
+        },
+        library::{ViewSwitchMessage, library_changed},
+        locale::{Locale, t},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// A track that can be moved out of the album.
+struct SplitRow {
+    id: i64,
+    number: Option<i32>,
+    title: SharedString,
+}
+
+/// A dialog that moves some of an album's tracks into a new album, for tracks that were grouped
+/// into the wrong album when they were scanned.
+pub struct SplitAlbum {
+    show: Entity<bool>,
+    album_id: i64,
+    /// The track that is selected when the dialog is opened from a track's menu.
+    initial: Option<i64>,
+    rows: Vec<SplitRow>,
+    selected: FxHashSet<i64>,
+    input: Entity<TextInput>,
+    focus_handle: FocusHandle,
+    draft: String,
+    error: Option<SharedString>,
+}
+
+impl SplitAlbum {
+    pub fn new(
+        cx: &mut App,
+        show: Entity<bool>,
+        album_id: i64,
+        initial: Option<i64>,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                if *show.read(cx) {
+                    this.load(cx);
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let focus_handle = cx.focus_handle();
+            let this = cx.weak_entity();
+            let handler = move |action, _: &mut Window, cx: &mut App| {
+                if let EnrichedInputAction::Accept = action {
+                    this.update(cx, |this, cx| this.split(cx)).ok();
+                }
+            };
+
+            let placeholder = t!(cx, "split.title_placeholder");
+            let input = TextInput::new(
+                cx,
+                focus_handle.clone(),
+                None,
+                Some(placeholder),
+                Some(Box::new(handler)),
+            );
+
+            cx.subscribe(&input, |this: &mut Self, _, ev: &String, _| {
+                this.draft = ev.clone();
+            })
+            .detach();
+
+            Self {
+                show,
+                album_id,
+                initial,
+                rows: Vec::new(),
+                selected: FxHashSet::default(),
+                input,
+                focus_handle,
+                draft: String::new(),
+                error: None,
+            }
+        })
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        self.rows = cx
+            .list_tracks_in_album(self.album_id)
+            .unwrap_or_else(|err| {
+                error!(
+                    "Failed to list the tracks in album {}: {err}",
+                    self.album_id
+                );
+                Default::default()
+            })
+            .iter()
+            .map(|track| SplitRow {
+                id: track.id,
+                number: track.track_number,
+                title: track.title.clone().into(),
+            })
+            .collect();
+        self.selected = self.initial.into_iter().collect();
+        self.draft.clear();
+        self.error = None;
+        self.input.update(cx, |input, cx| {
+            input.reset();
+            cx.notify();
+        });
+    }
+
+    fn toggle(&mut self, track_id: i64, cx: &mut Context<Self>) {
+        if !self.selected.remove(&track_id) {
+            self.selected.insert(track_id);
+        }
+
+        cx.notify();
+    }
+
+    fn split(&mut self, cx: &mut Context<Self>) {
+        let title = self.draft.trim();
+
+        if title.is_empty() {
+            self.error = Some(t!(cx, "split.no_title"));
+            cx.notify();
+            return;
+        }
+
+        if self.selected.is_empty() || self.selected.len() == self.rows.len() {
+            self.error = Some(t!(cx, "split.no_tracks"));
+            cx.notify();
+            return;
+        }
+
+        let track_ids: Vec<i64> = self.selected.iter().copied().collect();
+
+        match cx.split_album(self.album_id, &track_ids, title) {
+            Ok(new_id) => {
+                self.show.write(cx, false);
+                library_changed(cx, None, ViewSwitchMessage::Release(new_id));
+            }
+            Err(err) => {
+                error!("Failed to split album {}: {err}", self.album_id);
+                self.error = Some(t!(cx, "split.failed", error = err.to_string()));
+                cx.notify();
+            }
+        }
+    }
+}
+
+impl Render for SplitAlbum {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let show_cancel = self.show.clone();
+        let focus_handle = self.focus_handle.clone();
+        let album_title = cx
+            .get_album_by_id(self.album_id, AlbumMethod::Metadata)
+            .map(|album| album.title.clone())
+            .unwrap_or_default();
+        let message = t!(cx, "split.message", title = album_title);
+        let count = self.selected.len();
+        let selected_label = cx.global::<Locale>().plural("split.selected", count as i64);
+        let theme = cx.global::<Theme>();
+
+        let rows = self.rows.iter().map(|row| {
+            let track_id = row.id;
+            let selected = self.selected.contains(&track_id);
+
+            div()
+                .id(("split-track", track_id as u64))
+                .flex()
+                .gap(spx(10.0))
+                .px(spx(8.0))
+                .py(spx(4.0))
+                .rounded(spx(4.0))
+                .cursor_pointer()
+                .when(selected, |this| this.bg(theme.queue_item_current))
+                .hover(|this| this.bg(theme.queue_item_hover))
+                .on_click(cx.listener(move |this, _, _, cx| this.toggle(track_id, cx)))
+                .child(
+                    div()
+                        .w(spx(24.0))
+                        .flex_shrink_0()
+                        .text_color(theme.text_secondary)
+                        .child(row.number.map(|v| v.to_string()).unwrap_or_default()),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w(spx(0.0))
+                        .truncate()
+                        .child(row.title.clone()),
+                )
+        });
+
+        modal()
+            .child(
+                div()
+                    .w(spx(480.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .child(t!(cx, "split.title")),
+                    )
+                    .child(div().text_color(theme.text_secondary).child(message))
+                    .child(
+                        div()
+                            .id("split-tracks")
+                            .flex()
+                            .flex_col()
+                            .max_h(spx(260.0))
+                            .overflow_y_scroll()
+                            .children(rows),
+                    )
+                    .child(div().text_color(theme.text_secondary).child(selected_label))
+                    .child(
+                        div()
+                            .px(spx(8.0))
+                            .py(spx(3.0))
+                            .line_height(spx(16.0))
+                            .border_1()
+                            .border_color(theme.border_color)
+                            .rounded(spx(4.0))
+                            .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                                focus_handle.focus(window)
+                            })
+                            .child(self.input.clone()),
+                    )
+                    .when_some(self.error.clone(), |this, error| {
+                        this.child(div().text_color(theme.text_secondary).child(error))
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap(spx(6.0))
+                            .pt(spx(10.0))
+                            .child(
+                                button()
+                                    .id("split-cancel")
+                                    .on_click(move |_, _, cx| show_cancel.write(cx, false))
+                                    .child(t!(cx, "split.cancel")),
+                            )
+                            .child(
+                                button()
+                                    .id("split-confirm")
+                                    .intent(ButtonIntent::Primary)
+                                    .on_click(cx.listener(|this, _, _, cx| this.split(cx)))
+                                    .child(t!(cx, "split.confirm")),
+                            ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,316 @@
+use std::sync::Arc;
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, InteractiveElement, IntoElement, ParentElement,
+    Render, Styled, Window, div, rems, uniform_list,
+};
+use rustc_hash::FxHashMap;
+use tracing::error;
+
+use crate::{
+    library::{db::LibraryAccess, scan::ScanEvent, types::Tag},
+    playback::{
+        interface::{PlaybackInterface, replace_queue},
+        queue::QueueItemData,
+    },
+    settings::interface::TrackListingKind,
+    ui::{
+        caching::hummingbird_cache,
+        components::{
+            button::{ButtonIntent, ButtonSize, button},
+            icons::{CIRCLE_PLUS, PLAY, PLAYLIST, SHUFFLE, icon},
+        },
+        library::track_listing::{
+            ArtistNameVisibility, filter::TrackFilter, track_item::TrackItem,
+        },
+        locale::{Locale, t},
+        models::{Models, PlaybackInfo},
+        theme::Theme,
+        util::{create_or_retrieve_view, prune_views, spx},
+    },
+};
+
+/// Loads the tags shown in the sidebar, and reloads them after each scan, since tracks that are
+/// removed from the library lose their tags.
+pub fn track_tags(cx: &mut App) {
+    tags_changed(cx);
+
+    let scan_state = cx.global::<Models>().scan_state.clone();
+    cx.observe(&scan_state, |scan_state, cx| {
+        if *scan_state.read(cx) == ScanEvent::ScanCompleteIdle {
+            tags_changed(cx);
+        }
+    })
+    .detach();
+}
+
+/// Lists the tags again, which also tells the tag views to reload.
+pub fn tags_changed(cx: &mut App) {
+    let tags = cx.list_tags().unwrap_or_else(|err| {
+        error!("Failed to list tags: {err}");
+        Vec::new()
+    });
+
+    let model = cx.global::<Models>().tags.clone();
+    model.update(cx, |model, cx| {
+        *model = Arc::new(tags);
+        cx.notify();
+    });
+}
+
+/// The tracks tagged with a mood or activity, shown like a playlist.
+pub struct TagView {
+    tag: Arc<Tag>,
+    track_ids: Arc<Vec<i64>>,
+    /// The tracks being shown, after filtering and sorting.
+    shown_track_ids: Arc<Vec<i64>>,
+    track_filter: Entity<TrackFilter>,
+    views: Entity<FxHashMap<usize, Entity<TrackItem>>>,
+    render_counter: Entity<usize>,
+}
+
+impl TagView {
+    pub fn new(cx: &mut App, tag_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let tags = cx.global::<Models>().tags.clone();
+            cx.observe(&tags, |this: &mut Self, _, cx| {
+                // the tag is deleted once no track has it, in which case the view is left empty
+                this.tag = cx.get_tag(this.tag.id).unwrap_or_else(|_| {
+                    Arc::new(Tag {
+                        track_count: 0,
+                        ..(*this.tag).clone()
+                    })
+                });
+                this.track_ids = cx.list_tag_track_ids(this.tag.id).unwrap_or_default();
+                this.update_shown(cx);
+            })
+            .detach();
+
+            let track_filter = TrackFilter::new(cx, TrackListingKind::Playlist);
+            cx.observe(&track_filter, |this: &mut Self, _, cx| {
+                this.update_shown(cx)
+            })
+            .detach();
+
+            let track_ids = cx.list_tag_track_ids(tag_id).unwrap_or_else(|err| {
+                error!("Failed to list the tracks tagged with tag {tag_id}: {err}");
+                Arc::new(Vec::new())
+            });
+
+            Self {
+                tag: cx.get_tag(tag_id).expect("failed to retrieve tag"),
+                shown_track_ids: track_ids.clone(),
+                track_ids,
+                track_filter,
+                views: cx.new(|_| FxHashMap::default()),
+                render_counter: cx.new(|_| 0),
+            }
+        })
+    }
+
+    /// Works out which tracks to show after the tracks or the filter change.
+    fn update_shown(&mut self, cx: &mut Context<Self>) {
+        let shown = self
+            .track_filter
+            .update(cx, |filter, cx| filter.apply(cx, &self.track_ids));
+
+        self.shown_track_ids = match shown {
+            Some(shown) => Arc::new(shown.into_iter().map(|idx| self.track_ids[idx]).collect()),
+            None => self.track_ids.clone(),
+        };
+
+        self.views = cx.new(|_| FxHashMap::default());
+        self.render_counter = cx.new(|_| 0);
+        cx.notify();
+    }
+
+    fn queue_items(&self, cx: &mut App) -> Vec<QueueItemData> {
+        let tracks: Vec<_> = self
+            .track_ids
+            .iter()
+            .filter_map(|id| cx.get_track_by_id(*id).ok())
+            .collect();
+
+        tracks
+            .into_iter()
+            .map(|track| {
+                QueueItemData::new(cx, track.location.clone(), Some(track.id), track.album_id)
+            })
+            .collect()
+    }
+}
+
+impl Render for TagView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let items = self.shown_track_ids.clone();
+        let views_model = self.views.clone();
+        let render_counter = self.render_counter.clone();
+        let theme = cx.global::<Theme>();
+
+        div()
+            .image_cache(hummingbird_cache(("tag-view", self.tag.id as u64), 100))
+            .id("tag-view")
+            .pt(spx(10.0))
+            .flex()
+            .flex_col()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .max_w(spx(1000.0))
+            .h_full()
+            .child(
+                div()
+                    .flex()
+                    .overflow_x_hidden()
+                    .flex_shrink()
+                    .px(spx(18.0))
+                    .w_full()
+                    .child(
+                        div()
+                            .bg(theme.album_art_background)
+                            .shadow_sm()
+                            .w(spx(160.0))
+                            .h(spx(160.0))
+                            .flex_shrink_0()
+                            .rounded(spx(4.0))
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .child(icon(PLAYLIST).size(spx(100.0))),
+                    )
+                    .child(
+                        div()
+                            .ml(spx(18.0))
+                            .mt_auto()
+                            .flex_shrink()
+                            .flex()
+                            .flex_col()
+                            .w_full()
+                            .overflow_x_hidden()
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::EXTRA_BOLD)
+                                    .text_size(rems(2.5))
+                                    .line_height(rems(2.75))
+                                    .overflow_x_hidden()
+                                    .pb(spx(4.0))
+                                    .w_full()
+                                    .text_ellipsis()
+                                    .child(self.tag.name.clone()),
+                            )
+                            .child(
+                                div()
+                                    .mb(spx(10.0))
+                                    .text_sm()
+                                    .text_color(theme.text_secondary)
+                                    .child(
+                                        cx.global::<Locale>()
+                                            .plural("playlist.song_count", self.tag.track_count),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .gap(spx(10.0))
+                                    .flex()
+                                    .child(
+                                        button()
+                                            .id("tag-play-button")
+                                            .size(ButtonSize::Large)
+                                            .font_weight(FontWeight::SEMIBOLD)
+                                            .intent(ButtonIntent::Primary)
+                                            .child(icon(PLAY).size(spx(16.0)).my_auto())
+                                            .child(t!(cx, "playlist.play"))
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                let queue_items = this.queue_items(cx);
+                                                replace_queue(queue_items, cx);
+                                            })),
+                                    )
+                                    .child(
+                                        button()
+                                            .id("tag-add-button")
+                                            .tooltip(t!(cx, "playlist.add_to_queue"))
+                                            .size(ButtonSize::Large)
+                                            .flex_none()
+                                            .child(icon(CIRCLE_PLUS).size(spx(16.0)).my_auto())
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                let queue_items = this.queue_items(cx);
+                                                cx.global::<PlaybackInterface>()
+                                                    .queue_list(queue_items);
+                                            })),
+                                    )
+                                    .child(
+                                        button()
+                                            .id("tag-shuffle-button")
+                                            .tooltip(t!(cx, "playlist.shuffle"))
+                                            .size(ButtonSize::Large)
+                                            .flex_none()
+                                            .child(icon(SHUFFLE).size(spx(16.0)).my_auto())
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                let queue_items = this.queue_items(cx);
+
+                                                if !(*cx
+                                                    .global::<PlaybackInfo>()
+                                                    .shuffling
+                                                    .read(cx))
+                                                {
+                                                    cx.global::<PlaybackInterface>()
+                                                        .toggle_shuffle();
+                                                }
+
+                                                replace_queue(queue_items, cx);
+                                            })),
+                                    ),
+                            ),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .px(spx(18.0))
+                    .mt(spx(18.0))
+                    .mb(spx(6.0))
+                    .child(self.track_filter.clone()),
+            )
+            .child(
+                uniform_list("tag-list", items.len(), move |range, _, cx| {
+                    let start = range.start;
+                    let is_templ_render = range.start == 0 && range.end == 1;
+
+                    items[range]
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, id)| {
+                            let idx = idx + start;
+                            let id = *id;
+
+                            if !is_templ_render {
+                                prune_views(&views_model, &render_counter, idx, cx);
+                            }
+
+                            div().child(create_or_retrieve_view(
+                                &views_model,
+                                idx,
+                                move |cx| {
+                                    let track = cx.get_track_by_id(id).unwrap();
+                                    TrackItem::new(
+                                        cx,
+                                        Arc::try_unwrap(track).unwrap(),
+                                        false,
+                                        ArtistNameVisibility::Always,
+                                        TrackListingKind::Playlist,
+                                        None,
+                                    )
+                                },
+                                cx,
+                            ))
+                        })
+                        .collect()
+                })
+                .w_full()
+                .h_full()
+                .flex()
+                .flex_col()
+                .border_color(theme.border_color)
+                .border_t_1(),
+            )
+    }
+}
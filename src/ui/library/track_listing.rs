@@ -1,12 +1,15 @@
+pub mod filter;
 pub mod track_item;
 
 use std::sync::Arc;
 
-use gpui::{App, Entity, IntoElement, ListAlignment, ListState, Pixels, Window};
+use gpui::{AnyElement, App, AppContext, Entity, IntoElement, Window, div};
+use rustc_hash::FxHashMap;
 
 use crate::{
-    library::types::{DBString, Track},
-    ui::library::track_listing::track_item::TrackItemLeftField,
+    library::{db::LibraryAccess, types::DBString},
+    settings::interface::TrackListingKind,
+    ui::util::prune_views,
 };
 use track_item::TrackItem;
 
@@ -19,55 +22,88 @@ pub enum ArtistNameVisibility {
 
 #[derive(Clone)]
 pub struct TrackListing {
-    // TODO: replace this with Arc<Vec<i64>>, memoize TrackItem, fetch on load instead of before
-    tracks: Arc<Vec<Entity<TrackItem>>>,
-    original_tracks: Arc<Vec<Track>>,
-    track_list_state: ListState,
+    track_ids: Arc<Vec<i64>>,
+    /// The indices of the tracks being shown, when the listing has been filtered or sorted.
+    shown: Option<Arc<Vec<usize>>>,
+    views: Entity<FxHashMap<usize, Entity<TrackItem>>>,
+    render_counter: Entity<usize>,
+    artist_name_visibility: ArtistNameVisibility,
 }
 
 impl TrackListing {
     pub fn new(
         cx: &mut App,
-        tracks: Arc<Vec<Track>>,
-        overdraw: Pixels,
+        track_ids: Arc<Vec<i64>>,
         artist_name_visibility: ArtistNameVisibility,
     ) -> Self {
-        let state = ListState::new(tracks.len(), ListAlignment::Top, overdraw);
-
         Self {
-            tracks: Arc::new(
-                tracks
-                    .iter()
-                    .enumerate()
-                    .map(move |(index, track)| {
-                        TrackItem::new(
-                            cx,
-                            track.clone(),
-                            index == 0 || track.track_number == Some(1),
-                            artist_name_visibility.clone(),
-                            TrackItemLeftField::TrackNum,
-                            None,
-                        )
-                    })
-                    .collect(),
-            ),
-            original_tracks: tracks,
-            track_list_state: state,
+            track_ids,
+            shown: None,
+            views: cx.new(|_| FxHashMap::default()),
+            render_counter: cx.new(|_| 0),
+            artist_name_visibility,
         }
     }
 
-    pub fn tracks(&self) -> &Arc<Vec<Track>> {
-        &self.original_tracks
+    pub fn track_ids(&self) -> &Arc<Vec<i64>> {
+        &self.track_ids
+    }
+
+    /// The number of tracks being shown.
+    pub fn track_count(&self) -> usize {
+        self.shown
+            .as_ref()
+            .map_or(self.track_ids.len(), |shown| shown.len())
     }
 
-    pub fn track_list_state(&self) -> &ListState {
-        &self.track_list_state
+    /// Shows only the tracks at the given indices, in the given order, or every track if `None`.
+    pub fn set_shown(&mut self, cx: &mut App, shown: Option<Vec<usize>>) {
+        self.shown = shown.map(Arc::new);
+        self.views = cx.new(|_| FxHashMap::default());
+        self.render_counter = cx.new(|_| 0);
     }
 
+    /// Returns a function that renders the track at the given index. Tracks are only retrieved
+    /// from the database when they are first rendered, and their views are dropped once they are
+    /// scrolled out of view.
     pub fn make_render_fn(
         &self,
-    ) -> impl Fn(usize, &mut Window, &mut App) -> gpui::AnyElement + Clone + 'static {
-        let tracks = self.tracks.clone();
-        move |idx, _, _| tracks[idx].clone().into_any_element()
+    ) -> impl Fn(usize, &mut Window, &mut App) -> AnyElement + Clone + 'static {
+        let track_ids = self.track_ids.clone();
+        let shown = self.shown.clone();
+        let views = self.views.clone();
+        let render_counter = self.render_counter.clone();
+        let artist_name_visibility = self.artist_name_visibility.clone();
+
+        move |idx, _, cx| {
+            prune_views(&views, &render_counter, idx, cx);
+
+            if let Some(view) = views.read(cx).get(&idx).cloned() {
+                return view.into_any_element();
+            }
+
+            let track_idx = shown.as_ref().map_or(idx, |shown| shown[idx]);
+            // the track may have been removed by a rescan since the listing was loaded
+            let Ok(track) = cx.get_track_by_id(track_ids[track_idx]) else {
+                return div().into_any_element();
+            };
+            // disc headers only make sense in the album's own order
+            let is_start = idx == 0 || (shown.is_none() && track.track_number == Some(1));
+
+            let view = TrackItem::new(
+                cx,
+                (*track).clone(),
+                is_start,
+                artist_name_visibility.clone(),
+                TrackListingKind::Album,
+                None,
+            );
+
+            views.update(cx, |m, _| {
+                m.insert(idx, view.clone());
+            });
+
+            view.into_any_element()
+        }
     }
 }
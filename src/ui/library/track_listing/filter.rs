@@ -0,0 +1,305 @@
+use std::time::Duration;
+
+use gpui::{
+    App, AppContext, Context, Entity, FocusHandle, InteractiveElement, IntoElement, MouseButton,
+    ParentElement, Render, SharedString, Styled, Task, Window, div, prelude::FluentBuilder,
+};
+use rustc_hash::FxHashMap;
+use tracing::error;
+
+use crate::{
+    library::{db::LibraryAccess, types::TrackSortKeys},
+    settings::{
+        SettingsGlobal,
+        interface::{TrackColumn, TrackListingKind},
+        save_settings,
+    },
+    ui::{
+        components::{
+            button::{ButtonIntent, ButtonStyle, button},
+            icons::{CHEVRON_DOWN, CHEVRON_UP, icon},
+            input::TextInput,
+        },
+        locale::t,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// How long typing has to stop for before the listing is filtered.
+const FILTER_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TrackSortColumn {
+    /// The order the listing was given in: track number for albums, and the playlist's order for
+    /// playlists.
+    Position,
+    Title,
+    Duration,
+    PlayCount,
+}
+
+impl TrackSortColumn {
+    const ALL: [TrackSortColumn; 4] = [
+        TrackSortColumn::Position,
+        TrackSortColumn::Title,
+        TrackSortColumn::Duration,
+        TrackSortColumn::PlayCount,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            TrackSortColumn::Position => "track.sort.position",
+            TrackSortColumn::Title => "track.sort.title",
+            TrackSortColumn::Duration => "track.sort.duration",
+            TrackSortColumn::PlayCount => "track.sort.play_count",
+        }
+    }
+}
+
+fn column_name(column: TrackColumn) -> &'static str {
+    match column {
+        TrackColumn::TrackNumber => "track.column.track_number",
+        TrackColumn::Title => "track.column.title",
+        TrackColumn::Artist => "track.column.artist",
+        TrackColumn::PlayCount => "track.column.play_count",
+        TrackColumn::LastPlayed => "track.column.last_played",
+        TrackColumn::Bpm => "track.column.bpm",
+        TrackColumn::Key => "track.column.key",
+        TrackColumn::Format => "track.column.format",
+        TrackColumn::Bitrate => "track.column.bitrate",
+        TrackColumn::Duration => "track.column.duration",
+    }
+}
+
+/// A filter box and sort buttons shown above a track listing, along with the columns that can be
+/// shown in it. The view showing the listing observes this, uses [TrackFilter::apply] to work
+/// out which tracks to show and in what order, and recreates its tracks when the columns change.
+pub struct TrackFilter {
+    kind: TrackListingKind,
+    show_columns: bool,
+    input: Entity<TextInput>,
+    focus_handle: FocusHandle,
+    /// Waits for typing to stop before updating the text. Replaced on every keystroke, which
+    /// cancels the previous wait.
+    pending: Option<Task<()>>,
+    text: SharedString,
+    sort: TrackSortColumn,
+    ascending: bool,
+    /// The sort keys of every track filtered so far. Only loaded once the listing is filtered or
+    /// sorted.
+    keys: FxHashMap<i64, TrackSortKeys>,
+}
+
+impl TrackFilter {
+    pub fn new(cx: &mut App, kind: TrackListingKind) -> Entity<Self> {
+        cx.new(|cx| {
+            let focus_handle = cx.focus_handle();
+            let placeholder = t!(cx, "track.filter_placeholder");
+            let input = TextInput::new(cx, focus_handle.clone(), None, Some(placeholder), None);
+
+            cx.subscribe(&input, |this: &mut Self, _, ev: &String, cx| {
+                let text = SharedString::from(ev.clone());
+
+                this.pending = Some(cx.spawn(async move |this, cx| {
+                    cx.background_executor().timer(FILTER_DELAY).await;
+
+                    this.update(cx, |this, cx| {
+                        if this.text != text {
+                            this.text = text;
+                            cx.notify();
+                        }
+                    })
+                    .ok();
+                }));
+            })
+            .detach();
+
+            TrackFilter {
+                kind,
+                show_columns: false,
+                input,
+                focus_handle,
+                pending: None,
+                text: SharedString::default(),
+                sort: TrackSortColumn::Position,
+                ascending: true,
+                keys: FxHashMap::default(),
+            }
+        })
+    }
+
+    /// Returns the indices of the tracks that should be shown, in the order they should be shown
+    /// in, or `None` if the listing should be shown as it is.
+    pub fn apply(&mut self, cx: &mut App, track_ids: &[i64]) -> Option<Vec<usize>> {
+        let text = self.text.trim().to_lowercase();
+
+        if text.is_empty() && self.sort == TrackSortColumn::Position && self.ascending {
+            return None;
+        }
+
+        let missing: Vec<i64> = track_ids
+            .iter()
+            .filter(|id| !self.keys.contains_key(id))
+            .copied()
+            .collect();
+
+        if !missing.is_empty() {
+            match cx.list_track_sort_keys(&missing) {
+                Ok(keys) => self
+                    .keys
+                    .extend(keys.into_iter().map(|keys| (keys.id, keys))),
+                Err(err) => error!("Failed to retrieve track sort keys: {err}"),
+            }
+        }
+
+        let mut indices: Vec<usize> = (0..track_ids.len())
+            .filter(|&idx| {
+                text.is_empty()
+                    || self.keys.get(&track_ids[idx]).is_some_and(|keys| {
+                        keys.title.0.to_lowercase().contains(&text)
+                            || keys
+                                .artist_names
+                                .as_ref()
+                                .is_some_and(|names| names.0.to_lowercase().contains(&text))
+                    })
+            })
+            .collect();
+
+        let keys = &self.keys;
+        let key = |idx: &usize| keys.get(&track_ids[*idx]);
+
+        indices.sort_by(|a, b| {
+            let ordering = match self.sort {
+                TrackSortColumn::Position => a.cmp(b),
+                TrackSortColumn::Title => key(a)
+                    .map(|keys| keys.title.0.to_lowercase())
+                    .cmp(&key(b).map(|keys| keys.title.0.to_lowercase())),
+                TrackSortColumn::Duration => key(a)
+                    .map(|keys| keys.duration)
+                    .cmp(&key(b).map(|keys| keys.duration)),
+                TrackSortColumn::PlayCount => key(a)
+                    .map(|keys| keys.play_count)
+                    .cmp(&key(b).map(|keys| keys.play_count)),
+            };
+
+            if self.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+
+        Some(indices)
+    }
+}
+
+impl Render for TrackFilter {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let focus_handle = self.focus_handle.clone();
+        let kind = self.kind;
+        let columns = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .interface
+            .track_columns(kind);
+
+        let bar = div()
+            .flex()
+            .items_center()
+            .gap(spx(4.0))
+            .child(
+                div()
+                    .w(spx(180.0))
+                    .mr(spx(4.0))
+                    .px(spx(8.0))
+                    .py(spx(3.0))
+                    .line_height(spx(16.0))
+                    .text_sm()
+                    .border_1()
+                    .border_color(theme.border_color)
+                    .rounded(spx(4.0))
+                    .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                        focus_handle.focus(window)
+                    })
+                    .child(self.input.clone()),
+            )
+            .children(TrackSortColumn::ALL.into_iter().map(|column| {
+                let active = self.sort == column;
+
+                button()
+                    .id(("track-sort", column as usize))
+                    .text_sm()
+                    .when(active, |this| this.intent(ButtonIntent::Primary))
+                    .when(!active, |this| this.style(ButtonStyle::Minimal))
+                    .child(t!(cx, column.name()))
+                    .when(active, |this| {
+                        this.child(
+                            icon(if self.ascending {
+                                CHEVRON_UP
+                            } else {
+                                CHEVRON_DOWN
+                            })
+                            .size(spx(14.0))
+                            .my_auto(),
+                        )
+                    })
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        if this.sort == column {
+                            this.ascending = !this.ascending;
+                        } else {
+                            this.sort = column;
+                            this.ascending = true;
+                        }
+
+                        cx.notify();
+                    }))
+            }))
+            .child(
+                button()
+                    .id("track-columns")
+                    .text_sm()
+                    .ml(spx(8.0))
+                    .when(self.show_columns, |this| this.intent(ButtonIntent::Primary))
+                    .when(!self.show_columns, |this| this.style(ButtonStyle::Minimal))
+                    .child(t!(cx, "track.columns"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.show_columns = !this.show_columns;
+                        cx.notify();
+                    })),
+            );
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(spx(6.0))
+            .child(bar)
+            .when(self.show_columns, |this| {
+                this.child(div().flex().flex_wrap().gap(spx(4.0)).children(
+                    TrackColumn::ALL.into_iter().map(|column| {
+                        let shown = columns.iter().any(|setting| setting.column == column);
+
+                        button()
+                            .id(("track-column", column as usize))
+                            .text_sm()
+                            .when(shown, |this| this.intent(ButtonIntent::Primary))
+                            .when(!shown, |this| this.style(ButtonStyle::Minimal))
+                            .child(t!(cx, column_name(column)))
+                            .on_click(cx.listener(move |_, _, _, cx| {
+                                let settings = cx.global::<SettingsGlobal>().model.clone();
+                                settings.update(cx, |settings, cx| {
+                                    settings.interface.toggle_track_column(kind, column);
+                                    cx.notify();
+                                });
+
+                                save_settings(cx);
+                                // the listing recreates its tracks with the new columns
+                                cx.notify();
+                            }))
+                    }),
+                ))
+            })
+    }
+}
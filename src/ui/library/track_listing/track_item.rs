@@ -1,30 +1,58 @@
+use chrono::{Local, format::StrftimeItems};
 use gpui::prelude::{FluentBuilder, *};
-use gpui::{App, Entity, FontWeight, IntoElement, SharedString, Window, div, img, px};
+use gpui::{AnyElement, App, Entity, FontWeight, IntoElement, SharedString, Window, div, img};
+use tracing::error;
 
 use crate::ui::components::icons::{
-    PLAY, PLAYLIST_ADD, PLAYLIST_REMOVE, PLUS, STAR, STAR_FILLED, icon,
+    FOLDER, INFO, PLAY, PLAYLIST_ADD, PLAYLIST_REMOVE, PLUS, STAR, STAR_FILLED, icon,
 };
 use crate::ui::components::menu::CMenuItem;
 use crate::ui::library::add_to_playlist::AddToPlaylist;
+use crate::ui::library::convert::ConvertFiles;
+use crate::ui::library::guess_tags::GuessTags;
+use crate::ui::library::split_album::SplitAlbum;
+use crate::ui::library::track_properties::TrackProperties;
+use crate::ui::locale::{Locale, t};
 use crate::ui::models::PlaylistEvent;
 use crate::{
-    library::{db::LibraryAccess, types::Track},
+    library::{
+        db::LibraryAccess,
+        playlist::set_track_liked,
+        remote::is_remote,
+        types::{PlayStats, Track},
+    },
     playback::{
         interface::{PlaybackInterface, replace_queue},
         queue::QueueItemData,
     },
+    settings::{
+        SettingsGlobal,
+        interface::{TrackColumn, TrackColumnSetting, TrackListingKind},
+    },
     ui::{
         components::{
+            badge::badges,
             context::context,
             menu::{menu, menu_item},
+            tooltip::tooltip,
         },
         models::{Models, PlaybackInfo},
         theme::Theme,
+        util::spx,
     },
 };
 
 use super::ArtistNameVisibility;
 
+/// The message for revealing a file, named after the platform's file manager.
+const SHOW_IN_FOLDER: &str = if cfg!(target_os = "macos") {
+    "track.show_in_finder"
+} else if cfg!(target_os = "windows") {
+    "track.show_in_explorer"
+} else {
+    "track.show_in_file_manager"
+};
+
 pub struct TrackPlaylistInfo {
     pub id: i64,
     pub item_id: i64,
@@ -36,17 +64,23 @@ pub struct TrackItem {
     pub artist_name_visibility: ArtistNameVisibility,
     pub is_liked: Option<i64>,
     pub hover_group: SharedString,
-    left_field: TrackItemLeftField,
+    play_stats: PlayStats,
+    /// The file's average bitrate in kbps, if its size could be read.
+    bitrate: Option<u64>,
+    kind: TrackListingKind,
     album_art: Option<SharedString>,
     pl_info: Option<TrackPlaylistInfo>,
     add_to: Entity<AddToPlaylist>,
     show_add_to: Entity<bool>,
-}
-
-#[derive(Eq, PartialEq)]
-pub enum TrackItemLeftField {
-    TrackNum,
-    Art,
+    properties: Entity<TrackProperties>,
+    show_properties: Entity<bool>,
+    guess_tags: Entity<GuessTags>,
+    show_guess_tags: Entity<bool>,
+    convert: Entity<ConvertFiles>,
+    show_convert: Entity<bool>,
+    /// Only tracks shown as part of their album can be split off into a new album.
+    split_album: Option<Entity<SplitAlbum>>,
+    show_split_album: Entity<bool>,
 }
 
 impl TrackItem {
@@ -55,12 +89,30 @@ impl TrackItem {
         track: Track,
         is_start: bool,
         anv: ArtistNameVisibility,
-        left_field: TrackItemLeftField,
+        kind: TrackListingKind,
         pl_info: Option<TrackPlaylistInfo>,
     ) -> Entity<Self> {
         cx.new(|cx| {
             let show_add_to = cx.new(|_| false);
-            let add_to = AddToPlaylist::new(cx, show_add_to.clone(), track.id);
+            let add_to = AddToPlaylist::new(cx, show_add_to.clone(), vec![track.id]);
+            let show_properties = cx.new(|_| false);
+            let properties = TrackProperties::new(
+                cx,
+                show_properties.clone(),
+                track.location.clone(),
+                track.id,
+            );
+            let show_guess_tags = cx.new(|_| false);
+            let guess_tags = GuessTags::new(cx, show_guess_tags.clone(), track.clone());
+            let show_convert = cx.new(|_| false);
+            let convert = ConvertFiles::new(cx, show_convert.clone(), vec![track.location.clone()]);
+            let show_split_album = cx.new(|_| false);
+            let split_album = track
+                .album_id
+                .filter(|_| kind == TrackListingKind::Album)
+                .map(|album_id| {
+                    SplitAlbum::new(cx, show_split_album.clone(), album_id, Some(track.id))
+                });
             let track_id = track.id;
 
             let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
@@ -76,19 +128,204 @@ impl TrackItem {
             Self {
                 hover_group: format!("track-{}", track.id).into(),
                 is_liked: cx.playlist_has_track(1, track.id).unwrap_or_default(),
+                play_stats: cx.get_track_play_stats(track.id).unwrap_or_default(),
+                bitrate: std::fs::metadata(&track.location)
+                    .ok()
+                    .filter(|_| track.duration > 0)
+                    .map(|metadata| metadata.len() * 8 / track.duration as u64 / 1000),
                 album_art: track
                     .album_id
                     .map(|v| format!("!db://album/{v}/thumb").into()),
                 add_to,
                 show_add_to,
+                properties,
+                show_properties,
+                guess_tags,
+                show_guess_tags,
+                convert,
+                show_convert,
+                split_album,
+                show_split_album,
                 track,
                 is_start,
                 artist_name_visibility: anv,
-                left_field,
+                kind,
                 pl_info,
             }
         })
     }
+
+    fn render_column(
+        &self,
+        setting: TrackColumnSetting,
+        show_artist_name: bool,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let theme = cx.global::<Theme>();
+
+        let column = div()
+            .flex_shrink_0()
+            .when_some(setting.width(), |this, width| this.w(spx(width)));
+
+        match setting.column {
+            TrackColumn::TrackNumber => column
+                .child(format!("{}", self.track.track_number.unwrap_or_default()))
+                .into_any_element(),
+            TrackColumn::Title => column
+                .when(setting.width().is_none(), |this| this.flex_shrink())
+                .font_weight(FontWeight::SEMIBOLD)
+                .overflow_x_hidden()
+                .text_ellipsis()
+                .child(self.track.title.clone())
+                .into_any_element(),
+            TrackColumn::Artist => column
+                .when(setting.width().is_none(), |this| this.flex_shrink())
+                .font_weight(FontWeight::LIGHT)
+                .text_sm()
+                .my_auto()
+                .text_color(theme.text_secondary)
+                .text_ellipsis()
+                .overflow_x_hidden()
+                .ml(spx(12.0))
+                .when(show_artist_name, |this| {
+                    this.when_some(self.track.artist_names.clone(), |this, v| this.child(v.0))
+                })
+                .into_any_element(),
+            TrackColumn::PlayCount => {
+                let count = self.play_stats.play_count;
+
+                column
+                    .id("play-count")
+                    .tooltip(tooltip(
+                        cx.global::<Locale>().plural("track.play_count", count),
+                    ))
+                    .ml(spx(12.0))
+                    .text_right()
+                    .text_color(theme.text_secondary)
+                    .child(count.to_string())
+                    .into_any_element()
+            }
+            TrackColumn::LastPlayed => column
+                .ml(spx(12.0))
+                .text_right()
+                .text_color(theme.text_secondary)
+                .when_some(self.play_stats.last_played, |this, date| {
+                    let locale = cx.global::<Locale>();
+                    let date = date.with_timezone(&Local);
+                    // same as release dates, a broken format from a language pack falls back to
+                    // ISO 8601
+                    let date = match StrftimeItems::new(locale.message("track.last_played_format"))
+                        .parse()
+                    {
+                        Ok(items) => date.format_with_items(items.iter()).to_string(),
+                        Err(_) => date.format("%Y-%m-%d").to_string(),
+                    };
+
+                    this.child(date)
+                })
+                .into_any_element(),
+            TrackColumn::Bpm => column
+                .ml(spx(12.0))
+                .text_right()
+                .text_color(theme.text_secondary)
+                .when_some(self.track.bpm, |this, bpm| this.child(format!("{bpm:.0}")))
+                .into_any_element(),
+            TrackColumn::Key => column
+                .ml(spx(12.0))
+                .text_right()
+                .text_color(theme.text_secondary)
+                .when_some(self.track.musical_key.clone(), |this, key| {
+                    this.child(key.0)
+                })
+                .into_any_element(),
+            TrackColumn::Format => column
+                .ml(spx(12.0))
+                .text_right()
+                .text_color(theme.text_secondary)
+                .when_some(self.track.location.extension(), |this, ext| {
+                    this.child(ext.to_string_lossy().to_uppercase())
+                })
+                .into_any_element(),
+            TrackColumn::Bitrate => column
+                .ml(spx(12.0))
+                .text_right()
+                .text_color(theme.text_secondary)
+                .when_some(self.bitrate, |this, kbps| {
+                    this.child(t!(cx, "track.bitrate", kbps = kbps))
+                })
+                .into_any_element(),
+            TrackColumn::Duration => column
+                .ml(spx(12.0))
+                .text_right()
+                .child(format!(
+                    "{}:{:02}",
+                    self.track.duration / 60,
+                    self.track.duration % 60
+                ))
+                .into_any_element(),
+        }
+    }
+
+    /// The header shown above the first track of a disc, such as `DISC 2 — Live in Tokyo`.
+    /// Releases that only have one disc just show the disc's title, if it has one.
+    fn disc_header(&self, cx: &App) -> Option<SharedString> {
+        let number = self
+            .track
+            .disc_number
+            .filter(|_| self.track.disc_total != Some(1));
+
+        match (number, &self.track.disc_subtitle) {
+            (Some(number), Some(subtitle)) => Some(t!(
+                cx,
+                "track.disc_subtitle",
+                number = number,
+                subtitle = subtitle
+            )),
+            (Some(number), None) => Some(t!(cx, "track.disc", number = number)),
+            (None, Some(subtitle)) => Some(subtitle.0.clone()),
+            (None, None) => None,
+        }
+    }
+
+    fn render_like_button(&self, cx: &Context<Self>) -> AnyElement {
+        let theme = cx.global::<Theme>();
+
+        div()
+            .id("like")
+            .tooltip(tooltip(if self.is_liked.is_some() {
+                t!(cx, "track.unlike")
+            } else {
+                t!(cx, "track.like")
+            }))
+            .mr(spx(-4.0))
+            .ml_auto()
+            .my_auto()
+            .rounded_sm()
+            .p(spx(4.0))
+            .child(
+                icon(if self.is_liked.is_some() {
+                    STAR_FILLED
+                } else {
+                    STAR
+                })
+                .size(spx(14.0))
+                .text_color(theme.text_secondary),
+            )
+            .invisible()
+            .group(self.hover_group.clone())
+            .group_hover(self.hover_group.clone(), |this| this.visible())
+            .hover(|this| this.bg(theme.button_secondary_hover))
+            .active(|this| this.bg(theme.button_secondary_active))
+            .on_click(cx.listener(move |this, _, _, cx| {
+                cx.stop_propagation();
+
+                this.is_liked = set_track_liked(cx, &this.track, this.is_liked.is_none())
+                    .expect("could not like song");
+
+                cx.notify();
+            }))
+            .into_any_element()
+    }
 }
 
 impl Render for TrackItem {
@@ -108,6 +345,41 @@ impl Render for TrackItem {
         let track = self.track.clone();
 
         let show_clone = self.show_add_to.clone();
+        let show_properties = self.show_properties.clone();
+        let show_guess_tags = self.show_guess_tags.clone();
+        let show_convert = self.show_convert.clone();
+        let show_split_album = self.show_split_album.clone();
+        let track_location_3 = self.track.location.clone();
+        let liked_track = self.track.clone();
+        let is_liked = self.is_liked.is_some();
+
+        let columns = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .interface
+            .track_columns(self.kind);
+
+        // the like button sits after the title, pushing the columns after it over
+        let mut row = Vec::new();
+        for setting in &columns {
+            row.push(self.render_column(*setting, show_artist_name, cx));
+
+            if setting.column == TrackColumn::Title {
+                row.extend(
+                    badges(cx, self.track.format_label(), self.track.explicit)
+                        .map(|badges| badges.ml(spx(8.0)).my_auto().into_any_element()),
+                );
+                row.push(self.render_like_button(cx));
+            }
+        }
+
+        if !columns
+            .iter()
+            .any(|setting| setting.column == TrackColumn::Title)
+        {
+            row.insert(0, self.render_like_button(cx));
+        }
 
         context(("context", self.track.id as usize))
             .with(
@@ -122,21 +394,23 @@ impl Render for TrackItem {
                         move |_, _, cx| play_from_track(cx, &track, plid)
                     })
                     .child(self.add_to.clone())
+                    .child(self.properties.clone())
+                    .child(self.guess_tags.clone())
+                    .child(self.convert.clone())
+                    .children(self.split_album.clone())
                     .when(self.is_start, |this| {
                         this.child(
                             div()
                                 .text_color(theme.text_secondary)
                                 .text_sm()
                                 .font_weight(FontWeight::SEMIBOLD)
-                                .px(px(18.0))
+                                .px(spx(18.0))
                                 .border_b_1()
                                 .w_full()
                                 .border_color(theme.border_color)
-                                .mt(px(24.0))
-                                .pb(px(6.0))
-                                .when_some(self.track.disc_number, |this, num| {
-                                    this.child(format!("DISC {num}"))
-                                }),
+                                .mt(spx(24.0))
+                                .pb(spx(6.0))
+                                .when_some(self.disc_header(cx), |this, header| this.child(header)),
                         )
                     })
                     .child(
@@ -148,8 +422,8 @@ impl Render for TrackItem {
                             .w_full()
                             .border_color(theme.border_color)
                             .cursor_pointer()
-                            .px(px(18.0))
-                            .py(px(6.0))
+                            .px(spx(18.0))
+                            .py(spx(6.0))
                             .group(self.hover_group.clone())
                             .hover(|this| this.bg(theme.nav_button_hover))
                             .active(|this| this.bg(theme.nav_button_active))
@@ -161,104 +435,27 @@ impl Render for TrackItem {
                                 })
                             })
                             .max_w_full()
-                            .when(self.left_field == TrackItemLeftField::TrackNum, |this| {
-                                this.child(div().w(px(62.0)).flex_shrink_0().child(format!(
-                                    "{}",
-                                    self.track.track_number.unwrap_or_default()
-                                )))
-                            })
-                            .when(self.left_field == TrackItemLeftField::Art, |this| {
+                            .when(self.kind == TrackListingKind::Playlist, |this| {
                                 this.child(
                                     div()
-                                        .w(px(22.0))
-                                        .h(px(22.0))
-                                        .mr(px(12.0))
+                                        .w(spx(22.0))
+                                        .h(spx(22.0))
+                                        .mr(spx(12.0))
                                         .my_auto()
-                                        .rounded(px(3.0))
+                                        .flex_shrink_0()
+                                        .rounded(spx(3.0))
                                         .bg(theme.album_art_background)
                                         .when_some(self.album_art.clone(), |this, art| {
                                             this.child(
-                                                img(art).w(px(22.0)).h(px(22.0)).rounded(px(3.0)),
+                                                img(art)
+                                                    .w(spx(22.0))
+                                                    .h(spx(22.0))
+                                                    .rounded(spx(3.0)),
                                             )
                                         }),
                                 )
                             })
-                            .child(
-                                div()
-                                    .font_weight(FontWeight::SEMIBOLD)
-                                    .overflow_x_hidden()
-                                    .text_ellipsis()
-                                    .child(self.track.title.clone()),
-                            )
-                            .child(
-                                div()
-                                    .id("like")
-                                    .mr(px(-4.0))
-                                    .ml_auto()
-                                    .my_auto()
-                                    .rounded_sm()
-                                    .p(px(4.0))
-                                    .child(
-                                        icon(if self.is_liked.is_some() {
-                                            STAR_FILLED
-                                        } else {
-                                            STAR
-                                        })
-                                        .size(px(14.0))
-                                        .text_color(theme.text_secondary),
-                                    )
-                                    .invisible()
-                                    .group(self.hover_group.clone())
-                                    .group_hover(self.hover_group.clone(), |this| this.visible())
-                                    .hover(|this| this.bg(theme.button_secondary_hover))
-                                    .active(|this| this.bg(theme.button_secondary_active))
-                                    .on_click(cx.listener(move |this, _, _, cx| {
-                                        cx.stop_propagation();
-
-                                        if let Some(id) = this.is_liked {
-                                            cx.remove_playlist_item(id)
-                                                .expect("could not unlike song");
-
-                                            this.is_liked = None;
-                                        } else {
-                                            this.is_liked = Some(
-                                                cx.add_playlist_item(1, track_id)
-                                                    .expect("could not like song"),
-                                            );
-                                        }
-
-                                        let playlist_tracker =
-                                            cx.global::<Models>().playlist_tracker.clone();
-
-                                        playlist_tracker.update(cx, |_, cx| {
-                                            cx.emit(PlaylistEvent::PlaylistUpdated(1));
-                                        });
-
-                                        cx.notify();
-                                    })),
-                            )
-                            .child(
-                                div()
-                                    .font_weight(FontWeight::LIGHT)
-                                    .text_sm()
-                                    .my_auto()
-                                    .text_color(theme.text_secondary)
-                                    .text_ellipsis()
-                                    .overflow_x_hidden()
-                                    .flex_shrink()
-                                    .ml(px(12.0))
-                                    .when(show_artist_name, |this| {
-                                        this.when_some(
-                                            self.track.artist_names.clone(),
-                                            |this, v| this.child(v.0),
-                                        )
-                                    }),
-                            )
-                            .child(div().ml(px(12.0)).flex_shrink_0().child(format!(
-                                "{}:{:02}",
-                                self.track.duration / 60,
-                                self.track.duration % 60
-                            ))),
+                            .children(row),
                     ),
             )
             .child(
@@ -267,7 +464,7 @@ impl Render for TrackItem {
                         .item(menu_item(
                             "track_play",
                             Some(PLAY),
-                            "Play",
+                            t!(cx, "track.play"),
                             move |_, _, cx| {
                                 let data = QueueItemData::new(
                                     cx,
@@ -291,16 +488,22 @@ impl Render for TrackItem {
                         .item(menu_item(
                             "track_play_from_here",
                             None::<&str>,
-                            "Play from here",
+                            t!(cx, "track.play_from_here"),
                             {
                                 let plid = self.pl_info.as_ref().map(|pl| pl.id);
                                 move |_, _, cx| play_from_track(cx, &track, plid)
                             },
                         ))
+                        .item(menu_item(
+                            "track_start_radio",
+                            None::<&str>,
+                            t!(cx, "track.start_radio"),
+                            move |_, _, cx| start_radio(cx, track_id),
+                        ))
                         .item(menu_item(
                             "track_add_to_queue",
                             Some(PLUS),
-                            "Add to queue",
+                            t!(cx, "track.add_to_queue"),
                             move |_, _, cx| {
                                 let data = QueueItemData::new(
                                     cx,
@@ -316,9 +519,22 @@ impl Render for TrackItem {
                         .item(menu_item(
                             "track_add_to_playlist",
                             Some(PLAYLIST_ADD),
-                            "Add to playlist",
+                            t!(cx, "track.add_to_playlist"),
                             move |_, _, cx| show_clone.write(cx, true),
                         ))
+                        .item(menu_item(
+                            "track_like",
+                            Some(if is_liked { STAR_FILLED } else { STAR }),
+                            if is_liked {
+                                t!(cx, "track.unlike")
+                            } else {
+                                t!(cx, "track.like")
+                            },
+                            move |_, _, cx| {
+                                set_track_liked(cx, &liked_track, !is_liked)
+                                    .expect("could not like song");
+                            },
+                        ))
                         .when_some(self.pl_info.as_ref(), |menu, info| {
                             let playlist_id = info.id;
                             let item_id = info.item_id;
@@ -327,7 +543,7 @@ impl Render for TrackItem {
                             menu.item(menu_item(
                                 "track_remove_from_playlist",
                                 Some(PLAYLIST_REMOVE),
-                                "Remove from playlist",
+                                t!(cx, "track.remove_from_playlist"),
                                 move |_, _, cx| {
                                     cx.remove_playlist_item(item_id).unwrap();
                                     playlist_tracker.update(cx, |_, cx| {
@@ -335,12 +551,88 @@ impl Render for TrackItem {
                                     })
                                 },
                             ))
+                        })
+                        .item(CMenuItem::Seperator)
+                        // files in remote folders aren't in any folder on this computer
+                        .when(!is_remote(&track_location_3), |menu| {
+                            menu.item(menu_item(
+                                "track_show_in_folder",
+                                Some(FOLDER),
+                                t!(cx, SHOW_IN_FOLDER),
+                                move |_, _, cx| match cx.find_track_file(&track_location_3) {
+                                    Ok(file) => cx.reveal_path(&file.path),
+                                    Err(_) => cx.reveal_path(&track_location_3),
+                                },
+                            ))
+                        })
+                        .item(menu_item(
+                            "track_properties",
+                            Some(INFO),
+                            t!(cx, "track.properties"),
+                            move |_, _, cx| show_properties.write(cx, true),
+                        ))
+                        .item(menu_item(
+                            "track_guess_tags",
+                            None::<&str>,
+                            t!(cx, "track.guess_tags"),
+                            move |_, _, cx| show_guess_tags.write(cx, true),
+                        ))
+                        .item(menu_item(
+                            "track_convert",
+                            None::<&str>,
+                            t!(cx, "track.convert"),
+                            move |_, _, cx| show_convert.write(cx, true),
+                        ))
+                        .when(self.split_album.is_some(), |menu| {
+                            menu.item(menu_item(
+                                "track_split_album",
+                                None::<&str>,
+                                t!(cx, "track.split_album"),
+                                move |_, _, cx| show_split_album.write(cx, true),
+                            ))
                         }),
                 ),
             )
     }
 }
 
+/// Replaces the queue with the track followed by similar tracks from the library, and plays it.
+pub fn start_radio(cx: &mut App, track_id: i64) {
+    let track = match cx.get_track_by_id(track_id) {
+        Ok(track) => track,
+        Err(err) => {
+            error!("Failed to retrieve track {track_id}: {err}");
+            return;
+        }
+    };
+
+    let similar = cx.list_radio_tracks(track_id).unwrap_or_else(|err| {
+        error!("Failed to find tracks similar to track {track_id}: {err}");
+        Vec::new()
+    });
+
+    let mut queue_items = vec![QueueItemData::new(
+        cx,
+        track.location.clone(),
+        Some(track.id),
+        track.album_id,
+    )];
+
+    for similar in similar {
+        queue_items.push(QueueItemData::new(
+            cx,
+            similar.location,
+            Some(similar.id),
+            similar.album_id,
+        ));
+    }
+
+    replace_queue(queue_items, cx);
+
+    // when shuffling, the track could otherwise end up anywhere in the queue
+    cx.global::<PlaybackInterface>().jump_unshuffled(0);
+}
+
 pub fn play_from_track(cx: &mut App, track: &Track, pl_id: Option<i64>) {
     let queue_items = if let Some(pl_id) = pl_id {
         let ids = cx
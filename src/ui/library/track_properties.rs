@@ -0,0 +1,616 @@
+use std::{path::PathBuf, sync::Arc};
+
+use gpui::{
+    App, AppContext, Context, Entity, FocusHandle, FontWeight, InteractiveElement, IntoElement,
+    MouseButton, ParentElement, Render, SharedString, Styled, Window, div,
+};
+use tracing::{error, warn};
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        remote::{self, is_remote},
+        types::{Tag, Track, TrackSource},
+    },
+    media::properties::{FileProperties, read_file_properties},
+    playback::interface::PlaybackInterface,
+    settings::SettingsGlobal,
+    ui::{
+        components::{
+            button::{ButtonIntent, ButtonStyle, button},
+            icons::{CROSS, icon},
+            input::{EnrichedInputAction, TextInput},
+            modal::modal,
+        },
+        library::tags_changed,
+        locale::{Locale, t},
+        models::{Models, PlaybackInfo},
+        theme::Theme,
+        util::spx,
+    },
+};
+
+/// Moods and activities offered for every track, even before any track has been given them.
+const SUGGESTED_TAGS: [&str; 6] = ["Focus", "Workout", "Chill", "Party", "Sleep", "Commute"];
+
+/// How much each press of the volume offset buttons changes it by, in decibels.
+const VOLUME_OFFSET_STEP: f64 = 0.5;
+
+/// The furthest a track's volume can be offset either way, in decibels.
+const MAX_VOLUME_OFFSET: f64 = 12.0;
+
+enum PropertiesState {
+    Loading,
+    Loaded(FileProperties),
+    Failed,
+}
+
+/// A dialog showing the technical information of a track's file, along with how it entered the
+/// library and the moods and activities the track is tagged with.
+pub struct TrackProperties {
+    show: Entity<bool>,
+    path: PathBuf,
+    track_id: i64,
+    state: PropertiesState,
+    track: Option<Arc<Track>>,
+    tags: Vec<Tag>,
+    tag_input: Entity<TextInput>,
+    tag_focus_handle: FocusHandle,
+    tag_draft: String,
+}
+
+impl TrackProperties {
+    pub fn new(cx: &mut App, show: Entity<bool>, path: PathBuf, track_id: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            cx.observe(&show, |this: &mut Self, show, cx| {
+                // the file could have changed since the dialog was last opened
+                if *show.read(cx) {
+                    this.load(cx);
+                    this.load_track(cx);
+                    this.load_tags(cx);
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let tag_focus_handle = cx.focus_handle();
+            let this = cx.weak_entity();
+            let handler = move |action, _: &mut Window, cx: &mut App| {
+                if let EnrichedInputAction::Accept = action {
+                    this.update(cx, |this, cx| {
+                        let name = this.tag_draft.clone();
+                        this.add_tag(cx, &name);
+                    })
+                    .ok();
+                }
+            };
+
+            let placeholder = t!(cx, "properties.add_tag_placeholder");
+            let tag_input = TextInput::new(
+                cx,
+                tag_focus_handle.clone(),
+                None,
+                Some(placeholder),
+                Some(Box::new(handler)),
+            );
+
+            cx.subscribe(&tag_input, |this: &mut Self, _, ev: &String, _| {
+                this.tag_draft = ev.clone();
+            })
+            .detach();
+
+            Self {
+                show,
+                path,
+                track_id,
+                state: PropertiesState::Loading,
+                track: None,
+                tags: Vec::new(),
+                tag_input,
+                tag_focus_handle,
+                tag_draft: String::new(),
+            }
+        })
+    }
+
+    fn load_track(&mut self, cx: &mut Context<Self>) {
+        self.track = cx
+            .get_track_by_id(self.track_id)
+            .inspect_err(|err| error!("Failed to load track {}: {err}", self.track_id))
+            .ok();
+    }
+
+    fn set_locked(&mut self, cx: &mut Context<Self>, locked: bool) {
+        if let Err(err) = cx.set_metadata_locked(self.track_id, locked) {
+            error!(
+                "Failed to lock the metadata of track {}: {err}",
+                self.track_id
+            );
+            return;
+        }
+
+        self.load_track(cx);
+        cx.notify();
+    }
+
+    fn set_volume_offset(&mut self, cx: &mut Context<Self>, offset: f64) {
+        let offset = offset.clamp(-MAX_VOLUME_OFFSET, MAX_VOLUME_OFFSET);
+        let stored = (offset != 0.0).then_some(offset);
+
+        if let Err(err) = cx.set_volume_offset(self.track_id, stored) {
+            error!(
+                "Failed to set the volume offset of track {}: {err}",
+                self.track_id
+            );
+            return;
+        }
+
+        // the track that's playing picks up the change straight away
+        let playing = cx
+            .global::<PlaybackInfo>()
+            .current_track
+            .read(cx)
+            .as_ref()
+            .is_some_and(|current| *current == self.path);
+        if playing {
+            cx.global::<PlaybackInterface>().set_volume_offset(offset);
+        }
+
+        self.load_track(cx);
+        cx.notify();
+    }
+
+    fn render_library(&self, cx: &mut Context<Self>) -> Option<impl IntoElement + use<>> {
+        let track = self.track.as_ref()?;
+        let theme = cx.global::<Theme>();
+        let locked = track.metadata_locked;
+
+        let source = match track.source {
+            Some(TrackSource::Scan) => t!(cx, "properties.source_scan"),
+            Some(TrackSource::Import) => t!(cx, "properties.source_import"),
+            Some(TrackSource::Device) => t!(cx, "properties.source_device"),
+            Some(TrackSource::Remote) => t!(cx, "properties.source_remote"),
+            None => t!(cx, "properties.unknown"),
+        };
+        let source = match &track.source_path {
+            Some(path) => t!(cx, "properties.source_value", source = source, path = path),
+            None => source,
+        };
+
+        let lock_state = if locked {
+            t!(cx, "properties.locked")
+        } else {
+            t!(cx, "properties.unlocked")
+        };
+        let lock_label = if locked {
+            t!(cx, "properties.unlock")
+        } else {
+            t!(cx, "properties.lock")
+        };
+
+        let offset = track.volume_offset.unwrap_or_default();
+        let offset_label = if offset == 0.0 {
+            t!(cx, "properties.volume_offset_none")
+        } else {
+            t!(
+                cx,
+                "properties.volume_offset_value",
+                db = format!("{offset:+.1}")
+            )
+        };
+
+        Some(
+            div()
+                .flex()
+                .flex_col()
+                .gap(spx(6.0))
+                .pt(spx(10.0))
+                .child(
+                    div()
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .child(t!(cx, "properties.library_section")),
+                )
+                .child(property_row(cx, t!(cx, "properties.source"), source))
+                .child(
+                    div()
+                        .flex()
+                        .gap(spx(12.0))
+                        .items_center()
+                        .child(
+                            div()
+                                .w(spx(110.0))
+                                .flex_shrink_0()
+                                .text_color(theme.text_secondary)
+                                .child(t!(cx, "properties.metadata")),
+                        )
+                        .child(div().flex_1().child(lock_state))
+                        .child(
+                            button()
+                                .id("track-metadata-lock")
+                                .text_sm()
+                                .child(lock_label)
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    this.set_locked(cx, !locked);
+                                })),
+                        ),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "properties.lock_explanation")),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .gap(spx(12.0))
+                        .items_center()
+                        .child(
+                            div()
+                                .w(spx(110.0))
+                                .flex_shrink_0()
+                                .text_color(theme.text_secondary)
+                                .child(t!(cx, "properties.volume_offset")),
+                        )
+                        .child(div().flex_1().child(offset_label))
+                        .child(
+                            div()
+                                .flex()
+                                .gap(spx(4.0))
+                                .child(
+                                    button()
+                                        .id("track-volume-offset-down")
+                                        .text_sm()
+                                        .child("−")
+                                        .on_click(cx.listener(move |this, _, _, cx| {
+                                            this.set_volume_offset(cx, offset - VOLUME_OFFSET_STEP);
+                                        })),
+                                )
+                                .child(
+                                    button()
+                                        .id("track-volume-offset-up")
+                                        .text_sm()
+                                        .child("+")
+                                        .on_click(cx.listener(move |this, _, _, cx| {
+                                            this.set_volume_offset(cx, offset + VOLUME_OFFSET_STEP);
+                                        })),
+                                )
+                                .child(
+                                    button()
+                                        .id("track-volume-offset-reset")
+                                        .text_sm()
+                                        .child(t!(cx, "properties.volume_offset_reset"))
+                                        .on_click(cx.listener(move |this, _, _, cx| {
+                                            this.set_volume_offset(cx, 0.0);
+                                        })),
+                                ),
+                        ),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "properties.volume_offset_explanation")),
+                ),
+        )
+    }
+
+    fn load_tags(&mut self, cx: &mut Context<Self>) {
+        self.tags = cx.list_track_tags(self.track_id).unwrap_or_else(|err| {
+            error!("Failed to list the tags of track {}: {err}", self.track_id);
+            Vec::new()
+        });
+    }
+
+    fn add_tag(&mut self, cx: &mut Context<Self>, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+
+        if let Err(err) = cx.add_track_tag(self.track_id, name) {
+            error!("Failed to tag track {} with {name}: {err}", self.track_id);
+            return;
+        }
+
+        self.tag_draft.clear();
+        // the input is still being updated when this is called from its handler
+        let input = self.tag_input.clone();
+        cx.defer(move |cx| {
+            input.update(cx, |input, cx| {
+                input.reset();
+                cx.notify();
+            });
+        });
+
+        self.load_tags(cx);
+        tags_changed(cx);
+        cx.notify();
+    }
+
+    fn remove_tag(&mut self, cx: &mut Context<Self>, tag_id: i64) {
+        if let Err(err) = cx.remove_track_tag(self.track_id, tag_id) {
+            error!(
+                "Failed to remove tag {tag_id} from track {}: {err}",
+                self.track_id
+            );
+            return;
+        }
+
+        self.load_tags(cx);
+        tags_changed(cx);
+        cx.notify();
+    }
+
+    fn render_tags(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let focus_handle = self.tag_focus_handle.clone();
+
+        // suggestions are the usual moods and activities, followed by any others already in use
+        let library_tags = cx.global::<Models>().tags.read(cx).clone();
+        let mut suggestions: Vec<SharedString> = Vec::new();
+        for name in SUGGESTED_TAGS
+            .into_iter()
+            .map(SharedString::from)
+            .chain(library_tags.iter().map(|tag| tag.name.0.clone()))
+        {
+            let taken = |other: &SharedString| other.eq_ignore_ascii_case(&name);
+            let on_track = self
+                .tags
+                .iter()
+                .any(|tag| tag.name.0.eq_ignore_ascii_case(&name));
+
+            if !on_track && !suggestions.iter().any(taken) {
+                suggestions.push(name);
+            }
+        }
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(spx(6.0))
+            .pt(spx(10.0))
+            .child(
+                div()
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .child(t!(cx, "properties.tags_section")),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap(spx(4.0))
+                    .children(self.tags.iter().map(|tag| {
+                        let tag_id = tag.id;
+
+                        button()
+                            .id(("track-tag", tag_id as u64))
+                            .text_sm()
+                            .intent(ButtonIntent::Primary)
+                            .child(tag.name.clone())
+                            .child(icon(CROSS).size(spx(12.0)).my_auto())
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.remove_tag(cx, tag_id);
+                            }))
+                    }))
+                    .children(suggestions.into_iter().enumerate().map(|(idx, name)| {
+                        button()
+                            .id(("track-tag-suggestion", idx))
+                            .text_sm()
+                            .style(ButtonStyle::Minimal)
+                            .child(format!("+ {name}"))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.add_tag(cx, &name);
+                            }))
+                    })),
+            )
+            .child(
+                div()
+                    .w(spx(220.0))
+                    .px(spx(8.0))
+                    .py(spx(3.0))
+                    .line_height(spx(16.0))
+                    .border_1()
+                    .border_color(theme.border_color)
+                    .rounded(spx(4.0))
+                    .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                        focus_handle.focus(window)
+                    })
+                    .child(self.tag_input.clone()),
+            )
+    }
+
+    fn load(&mut self, cx: &mut Context<Self>) {
+        // tracks split from a cue sheet are stored under a location inside their file
+        let path = match cx.find_track_file(&self.path) {
+            Ok(file) => file.path,
+            Err(err) => {
+                warn!("Could not find the file of {:?}: {err}", self.path);
+                self.path.clone()
+            }
+        };
+        let cache_size = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .playback
+            .remote_cache_size
+            * 1024
+            * 1024;
+        self.state = PropertiesState::Loading;
+
+        cx.spawn(async move |this, cx| {
+            // files in remote folders are read from their copy on this computer
+            let result = crate::RUNTIME
+                .spawn_blocking(move || {
+                    let path = if is_remote(&path) {
+                        remote::fetch(&path, cache_size)?
+                    } else {
+                        path
+                    };
+                    read_file_properties(&path)
+                })
+                .await;
+
+            let state = match result {
+                Ok(Ok(properties)) => PropertiesState::Loaded(properties),
+                Ok(Err(err)) => {
+                    error!("Failed to read file properties: {err}");
+                    PropertiesState::Failed
+                }
+                Err(err) => {
+                    error!("Failed to read file properties: {err}");
+                    PropertiesState::Failed
+                }
+            };
+
+            this.update(cx, |this, cx| {
+                this.state = state;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+}
+
+fn property_row(cx: &App, label: SharedString, value: SharedString) -> impl IntoElement + use<> {
+    let theme = cx.global::<Theme>();
+
+    div()
+        .flex()
+        .gap(spx(12.0))
+        .child(
+            div()
+                .w(spx(110.0))
+                .flex_shrink_0()
+                .text_color(theme.text_secondary)
+                .child(label),
+        )
+        .child(div().flex_1().min_w(spx(0.0)).child(value))
+}
+
+fn property_rows(cx: &App, properties: &FileProperties) -> Vec<gpui::AnyElement> {
+    let locale = cx.global::<Locale>();
+    let stream = &properties.stream;
+    let unknown = t!(cx, "properties.unknown");
+
+    let format = stream
+        .codec
+        .as_ref()
+        .map(|codec| SharedString::from(codec.to_uppercase()))
+        .unwrap_or_else(|| unknown.clone());
+    let sample_rate = stream
+        .sample_rate
+        .map(|rate| {
+            t!(
+                cx,
+                "properties.sample_rate_value",
+                khz = rate as f64 / 1000.0
+            )
+        })
+        .unwrap_or_else(|| unknown.clone());
+    let bit_depth = stream
+        .bits_per_sample
+        .map(|bits| t!(cx, "properties.bit_depth_value", bits = bits))
+        .unwrap_or_else(|| unknown.clone());
+    let channels = stream
+        .channels
+        .map(|channels| match channels {
+            1 => t!(cx, "properties.mono"),
+            2 => t!(cx, "properties.stereo"),
+            _ => locale.plural("properties.channel_count", channels as i64),
+        })
+        .unwrap_or_else(|| unknown.clone());
+    let bitrate = properties
+        .bitrate
+        .map(|kbps| t!(cx, "properties.bitrate_value", kbps = kbps))
+        .unwrap_or_else(|| unknown.clone());
+    let size = t!(
+        cx,
+        "properties.size_value",
+        mb = format!("{:.1}", properties.size as f64 / 1000.0 / 1000.0)
+    );
+    let tags = if properties.tag_formats.is_empty() {
+        t!(cx, "properties.no_tags")
+    } else {
+        properties.tag_formats.join(", ").into()
+    };
+
+    let mut rows = vec![
+        property_row(
+            cx,
+            t!(cx, "properties.path"),
+            properties.path.to_string_lossy().to_string().into(),
+        )
+        .into_any_element(),
+        property_row(cx, t!(cx, "properties.format"), format).into_any_element(),
+        property_row(cx, t!(cx, "properties.sample_rate"), sample_rate).into_any_element(),
+        property_row(cx, t!(cx, "properties.bit_depth"), bit_depth).into_any_element(),
+        property_row(cx, t!(cx, "properties.channels"), channels).into_any_element(),
+        property_row(cx, t!(cx, "properties.bitrate"), bitrate).into_any_element(),
+        property_row(cx, t!(cx, "properties.size"), size).into_any_element(),
+        property_row(cx, t!(cx, "properties.tags"), tags).into_any_element(),
+    ];
+
+    if !properties.custom_tags.is_empty() {
+        rows.push(
+            div()
+                .pt(spx(10.0))
+                .font_weight(FontWeight::SEMIBOLD)
+                .child(t!(cx, "properties.custom_tags"))
+                .into_any_element(),
+        );
+        rows.extend(properties.custom_tags.iter().map(|(name, value)| {
+            property_row(cx, name.clone().into(), value.clone().into()).into_any_element()
+        }));
+    }
+
+    rows
+}
+
+impl Render for TrackProperties {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+
+        let body = match &self.state {
+            PropertiesState::Loading => {
+                vec![div().child(t!(cx, "properties.loading")).into_any_element()]
+            }
+            PropertiesState::Loaded(properties) => property_rows(cx, properties),
+            PropertiesState::Failed => {
+                vec![div().child(t!(cx, "properties.failed")).into_any_element()]
+            }
+        };
+
+        modal()
+            .child(
+                div()
+                    .w(spx(500.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .pb(spx(6.0))
+                            .child(t!(cx, "properties.title")),
+                    )
+                    .children(body)
+                    .children(self.render_library(cx))
+                    .child(self.render_tags(cx)),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use gpui::{App, PathPromptOptions};
+use tracing::{error, info};
+
+use crate::{
+    library::transfer::{export_library_data, import_library_data},
+    settings::SettingsGlobal,
+    ui::{
+        app::{ActiveLibrary, Pool},
+        locale::t,
+        models::{Models, PlaylistEvent},
+    },
+};
+
+/// The scan folders of the active library, which paths in exported files are made relative to.
+fn scan_roots(cx: &App) -> Vec<PathBuf> {
+    let settings = cx.global::<SettingsGlobal>().model.read(cx);
+
+    cx.global::<ActiveLibrary>()
+        .scan_settings(settings)
+        .paths
+        .into_iter()
+        .map(|path| path.path)
+        .collect()
+}
+
+/// Asks where to save the library's playlists, liked tracks and play history, and writes them
+/// there.
+pub fn export_data(cx: &mut App) {
+    let Some(dir) =
+        directories::UserDirs::new().and_then(|dirs| dirs.document_dir().map(PathBuf::from))
+    else {
+        error!("Failed to get documents directory");
+        return;
+    };
+
+    let path_future = cx.prompt_for_new_path(&dir, Some("hummingbird-library.json"));
+    let pool = cx.global::<Pool>().read.clone();
+    let roots = scan_roots(cx);
+
+    crate::RUNTIME.spawn(async move {
+        let result = async {
+            if let Some(path) = path_future.await?? {
+                let summary = export_library_data(&pool, &roots, &path).await?;
+                info!(
+                    "Exported {} plays and {} playlists to {:?}",
+                    summary.plays, summary.playlists, path
+                );
+            }
+
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(err) = result {
+            error!("Failed to export library data: {err}");
+        }
+    });
+}
+
+/// Asks for a file written by [export_data] on another computer, and merges it into the library.
+pub fn import_data(cx: &mut App) {
+    let path_future = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: false,
+        prompt: Some(t!(cx, "library_data.import_prompt")),
+    });
+
+    let pool = cx.global::<Pool>().write.clone();
+    let roots = scan_roots(cx);
+    let playlist_tracker = cx.global::<Models>().playlist_tracker.clone();
+
+    cx.spawn(async move |cx| {
+        let result = crate::RUNTIME
+            .spawn(async move {
+                let Some(path) = path_future
+                    .await??
+                    .and_then(|paths| paths.into_iter().next())
+                else {
+                    return anyhow::Ok(None);
+                };
+
+                Ok(Some(import_library_data(&pool, &roots, &path).await?))
+            })
+            .await;
+
+        let summary = match result {
+            Ok(Ok(Some(summary))) => summary,
+            Ok(Ok(None)) => return,
+            Ok(Err(err)) => {
+                error!("Failed to import library data: {err}");
+                return;
+            }
+            Err(err) => {
+                error!("Failed to import library data: {err}");
+                return;
+            }
+        };
+
+        info!(
+            "Imported {} plays and {} playlist tracks, {} tracks weren't found",
+            summary.plays, summary.playlist_items, summary.missing_tracks
+        );
+
+        playlist_tracker
+            .update(cx, |_, cx| {
+                for id in summary.changed_playlists {
+                    cx.emit(PlaylistEvent::PlaylistUpdated(id));
+                }
+            })
+            .ok();
+    })
+    .detach();
+}
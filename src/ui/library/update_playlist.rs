@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use gpui::{
     App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
-    Window, div, px,
+    Window, div,
 };
 use nucleo::Utf32String;
 use tracing::error;
@@ -19,7 +19,9 @@ use crate::{
             modal::modal,
             palette::{ExtraItem, ExtraItemProvider, FinderItemLeft, Palette, PaletteItem},
         },
+        locale::{Locale, fill, t},
         models::{Models, PlaylistEvent},
+        util::spx,
     },
 };
 
@@ -31,8 +33,8 @@ impl PaletteItem for PlaylistWithCount {
         }))
     }
 
-    fn middle_content(&self, _: &mut App) -> SharedString {
-        format!("Update {}", self.name).into()
+    fn middle_content(&self, cx: &mut App) -> SharedString {
+        t!(cx, "playlist.update", name = self.name)
     }
 
     fn right_content(&self, _: &mut App) -> Option<SharedString> {
@@ -89,6 +91,7 @@ impl UpdatePlaylist {
             let palette = Palette::new(cx, items, matcher, on_accept);
 
             let show_for_create = show.clone();
+            let create_message = cx.global::<Locale>().message("playlist.create").to_string();
             let provider: ExtraItemProvider = Arc::new(move |query: &str| {
                 let name = query.trim();
                 if name.is_empty() {
@@ -96,13 +99,13 @@ impl UpdatePlaylist {
                 }
 
                 let name_string = name.to_string();
-                let display = format!("Create new playlist '{}'", name_string);
+                let display = fill(&create_message, &[("name", &name_string)]);
 
                 let show_clone2 = show_for_create.clone();
 
                 vec![ExtraItem {
                     left: Some(FinderItemLeft::Icon(PLAYLIST_ADD.into())),
-                    middle: display.into(),
+                    middle: display,
                     right: None,
                     on_accept: Arc::new(move |cx| {
                         let playlist_id = cx.create_playlist(&name_string).unwrap();
@@ -137,7 +140,7 @@ impl Render for UpdatePlaylist {
             });
 
             modal()
-                .child(div().w(px(550.0)).h(px(300.0)).child(palette.clone()))
+                .child(div().w(spx(550.0)).h(spx(300.0)).child(palette.clone()))
                 .on_exit(move |_, cx| {
                     show.update(cx, |show, cx| {
                         *show = false;
@@ -0,0 +1,260 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use gpui::*;
+use prelude::FluentBuilder;
+use rustc_hash::FxHashMap;
+use tracing::error;
+
+use crate::{
+    library::{
+        db::LibraryAccess,
+        types::{Album, AlbumFormat},
+    },
+    ui::{
+        locale::{Locale, t},
+        models::Models,
+        theme::Theme,
+        util::spx,
+    },
+};
+
+use super::{
+    ViewSwitchMessage,
+    artist_view::{album_formats, album_row},
+};
+
+/// The decade the year is in, as its first year.
+pub fn decade_of(year: i64) -> i64 {
+    year.div_euclid(10) * 10
+}
+
+pub struct Decade {
+    /// The first year of the decade.
+    pub start: i64,
+    pub album_count: i64,
+    /// The years in the decade that albums were released in, newest first, with the number of
+    /// albums released in each.
+    pub years: Vec<(i64, i64)>,
+}
+
+/// Groups the years albums were released in by decade, newest first.
+pub fn decades(cx: &App) -> Vec<Decade> {
+    let years = cx.list_album_years().unwrap_or_else(|err| {
+        error!("Failed to list album years: {err}");
+        Vec::new()
+    });
+
+    let mut decades: Vec<Decade> = Vec::new();
+    for (year, count) in years {
+        let start = decade_of(year);
+        match decades.last_mut() {
+            Some(decade) if decade.start == start => {
+                decade.album_count += count;
+                decade.years.push((year, count));
+            }
+            _ => decades.push(Decade {
+                start,
+                album_count: count,
+                years: vec![(year, count)],
+            }),
+        }
+    }
+
+    decades
+}
+
+/// Lists the decades and years albums in the library were released in.
+pub struct YearsView {
+    decades: Vec<Decade>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+}
+
+impl YearsView {
+    pub(super) fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| YearsView {
+            decades: decades(cx),
+            view_switcher: cx.global::<Models>().switcher_model.clone(),
+        })
+    }
+}
+
+impl Render for YearsView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let locale = cx.global::<Locale>();
+
+        div()
+            .id("years-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .pb(spx(10.0))
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .child(t!(cx, "sidebar.years")),
+            )
+            .when(self.decades.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px(spx(18.0))
+                        .text_sm()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "years.empty")),
+                )
+            })
+            .children(self.decades.iter().map(|decade| {
+                let years = &decade.years;
+                let count = decade.album_count;
+                let decade = decade.start;
+                let view_switcher = self.view_switcher.clone();
+
+                div()
+                    .flex()
+                    .flex_col()
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .child(
+                        div()
+                            .id(("decade", decade as u64))
+                            .flex()
+                            .items_center()
+                            .px(spx(18.0))
+                            .pt(spx(10.0))
+                            .pb(spx(6.0))
+                            .cursor_pointer()
+                            .hover(|this| this.bg(theme.nav_button_hover))
+                            .active(|this| this.bg(theme.nav_button_active))
+                            .on_click(move |_, _, cx| {
+                                view_switcher
+                                    .update(cx, |_, cx| cx.emit(ViewSwitchMessage::Decade(decade)))
+                            })
+                            .child(
+                                div()
+                                    .font_weight(FontWeight::BOLD)
+                                    .text_size(spx(18.0))
+                                    .child(t!(cx, "years.decade", decade = decade)),
+                            )
+                            .child(
+                                div()
+                                    .ml_auto()
+                                    .text_sm()
+                                    .text_color(theme.text_secondary)
+                                    .child(locale.plural("library.album_count", count)),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .gap(spx(6.0))
+                            .px(spx(18.0))
+                            .pb(spx(10.0))
+                            .children(years.iter().rev().map(|(year, count)| {
+                                let year = *year;
+                                let view_switcher = self.view_switcher.clone();
+
+                                div()
+                                    .id(("year", year as u64))
+                                    .flex()
+                                    .gap(spx(4.0))
+                                    .px(spx(8.0))
+                                    .py(spx(2.0))
+                                    .rounded(spx(3.0))
+                                    .text_sm()
+                                    .bg(theme.button_secondary)
+                                    .hover(|this| this.bg(theme.button_secondary_hover))
+                                    .active(|this| this.bg(theme.button_secondary_active))
+                                    .cursor_pointer()
+                                    .on_click(move |_, _, cx| {
+                                        view_switcher.update(cx, |_, cx| {
+                                            cx.emit(ViewSwitchMessage::Year(year))
+                                        })
+                                    })
+                                    .child(year.to_string())
+                                    .child(
+                                        div()
+                                            .text_color(theme.text_secondary)
+                                            .child(count.to_string()),
+                                    )
+                            })),
+                    )
+            }))
+            .child(div().h(spx(24.0)))
+    }
+}
+
+/// Lists the albums released in a year or a decade.
+pub struct ReleasedView {
+    title: SharedString,
+    albums: Arc<Vec<Album>>,
+    formats: FxHashMap<i64, AlbumFormat>,
+    view_switcher: Entity<VecDeque<ViewSwitchMessage>>,
+}
+
+impl ReleasedView {
+    pub(super) fn new(cx: &mut App, title: SharedString, start: i64, end: i64) -> Entity<Self> {
+        cx.new(|cx| {
+            let albums = cx.list_albums_in_years(start, end).unwrap_or_else(|err| {
+                error!("Failed to list albums released in {start}-{end}: {err}");
+                Arc::new(Vec::new())
+            });
+
+            ReleasedView {
+                title,
+                formats: album_formats(cx, &albums),
+                albums,
+                view_switcher: cx.global::<Models>().switcher_model.clone(),
+            }
+        })
+    }
+}
+
+impl Render for ReleasedView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        div()
+            .id("released-view")
+            .pt(spx(10.0))
+            .w_full()
+            .h_full()
+            .flex_shrink()
+            .overflow_x_hidden()
+            .overflow_y_scroll()
+            .max_w(spx(1000.0))
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .font_weight(FontWeight::EXTRA_BOLD)
+                    .text_size(rems(2.5))
+                    .line_height(rems(2.75))
+                    .child(self.title.clone()),
+            )
+            .child(
+                div()
+                    .px(spx(18.0))
+                    .pb(spx(6.0))
+                    .text_sm()
+                    .text_color(theme.text_secondary)
+                    .border_b_1()
+                    .border_color(theme.border_color)
+                    .child(
+                        cx.global::<Locale>()
+                            .plural("library.album_count", self.albums.len() as i64),
+                    ),
+            )
+            .children(self.albums.iter().map(|album| {
+                let format = self.formats.get(&album.id);
+                album_row(album, format, self.view_switcher.clone(), false, cx)
+            }))
+            .child(div().h(spx(24.0)))
+    }
+}
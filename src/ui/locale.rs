@@ -0,0 +1,151 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use gpui::{App, Global, SharedString};
+use rustc_hash::FxHashMap;
+use tracing::{debug, warn};
+
+use crate::{
+    settings::SettingsGlobal,
+    ui::{assets::bundled::BundledAssets, global_actions::set_menus},
+};
+
+/// The language used for any message that is missing from the selected language pack.
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+type Messages = FxHashMap<String, String>;
+
+/// The messages for the current language. Language packs are JSON objects mapping message ids to
+/// strings, bundled in `assets/locales` or placed in the `locales` folder of the data directory.
+/// Messages may contain `{name}` placeholders, which are filled in by [t].
+pub struct Locale {
+    language: Option<String>,
+    messages: Messages,
+    fallback: Messages,
+    packs_dir: PathBuf,
+}
+
+impl Global for Locale {}
+
+impl Locale {
+    fn load(language: Option<String>, packs_dir: PathBuf) -> Self {
+        let fallback = load_pack(&packs_dir, FALLBACK_LANGUAGE).unwrap_or_default();
+
+        let requested = language.clone().or_else(sys_locale::get_locale);
+        let messages = requested
+            .as_deref()
+            .and_then(|tag| {
+                // try the full tag first (e.g. pt-BR), then just the language (pt)
+                let tag = tag.replace('_', "-");
+                let primary = tag.split('-').next().unwrap_or(&tag).to_string();
+
+                load_pack(&packs_dir, &tag).or_else(|| load_pack(&packs_dir, &primary))
+            })
+            .unwrap_or_default();
+
+        debug!("loaded language pack for {:?}", requested);
+
+        Locale {
+            language,
+            messages,
+            fallback,
+            packs_dir,
+        }
+    }
+
+    /// Returns the message with the given id, or the id itself if no language pack contains it.
+    pub fn message<'a>(&'a self, id: &'a str) -> &'a str {
+        self.messages
+            .get(id)
+            .or_else(|| self.fallback.get(id))
+            .map(String::as_str)
+            .unwrap_or_else(|| {
+                warn!("missing message '{id}'");
+                id
+            })
+    }
+
+    /// Returns the message with the given id with its placeholders filled in.
+    pub fn format(&self, id: &str, args: &[(&str, &dyn Display)]) -> SharedString {
+        fill(self.message(id), args)
+    }
+
+    /// Returns the plural form of a message for the given count. Plural messages are stored as
+    /// `<id>.one` and `<id>.other`, and can use the `{count}` placeholder.
+    pub fn plural(&self, id: &str, count: i64) -> SharedString {
+        let form = if count == 1 { "one" } else { "other" };
+
+        self.format(&format!("{id}.{form}"), &[("count", &count)])
+    }
+}
+
+/// Fills in the `{name}` placeholders of a message. This is useful when a message has to be filled
+/// in somewhere that the [Locale] can't be accessed from, such as a palette item provider.
+pub fn fill(message: &str, args: &[(&str, &dyn Display)]) -> SharedString {
+    let mut result = message.to_string();
+
+    for (name, value) in args {
+        result = result.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+
+    result.into()
+}
+
+fn load_pack(packs_dir: &Path, language: &str) -> Option<Messages> {
+    let file_name = format!("{language}.json");
+
+    let data = match fs::read(packs_dir.join(&file_name)) {
+        Ok(data) => data,
+        Err(_) => BundledAssets::get(&format!("locales/{file_name}"))?
+            .data
+            .into_owned(),
+    };
+
+    serde_json::from_slice(&data)
+        .inspect_err(|e| warn!("could not parse language pack {file_name}: {e}"))
+        .ok()
+}
+
+/// Loads the language selected in the settings (or the system language), and reloads it whenever
+/// the selection changes.
+pub fn setup_locale(cx: &mut App, packs_dir: PathBuf) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+    let language = settings.read(cx).interface.language.clone();
+
+    cx.set_global(Locale::load(language, packs_dir));
+    set_menus(cx);
+
+    cx.observe(&settings, |settings, cx| {
+        let language = settings.read(cx).interface.language.clone();
+        let locale = cx.global::<Locale>();
+
+        if locale.language != language {
+            let packs_dir = locale.packs_dir.clone();
+            cx.set_global(Locale::load(language, packs_dir));
+            set_menus(cx);
+            cx.refresh_windows();
+        }
+    })
+    .detach();
+}
+
+/// Looks up a translated message by id, optionally filling in placeholders:
+///
+/// ```ignore
+/// t!(cx, "queue.title");
+/// t!(cx, "header.scanning", percent = 50);
+/// ```
+macro_rules! t {
+    ($cx:expr, $id:expr) => {
+        $cx.global::<$crate::ui::locale::Locale>().format($id, &[])
+    };
+    ($cx:expr, $id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $cx.global::<$crate::ui::locale::Locale>()
+            .format($id, &[$((stringify!($name), &$value)),+])
+    };
+}
+
+pub(crate) use t;
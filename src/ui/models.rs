@@ -1,30 +1,55 @@
 use std::{
+    cell::RefCell,
     collections::VecDeque,
     fs::{File, OpenOptions},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    rc::Rc,
     sync::{Arc, RwLock},
 };
 
-use gpui::{App, AppContext, Entity, EventEmitter, Global, RenderImage};
+use gpui::{
+    AnyWindowHandle, App, AppContext, Entity, EventEmitter, Global, RenderImage, SharedString,
+};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
 use tracing::{debug, error, warn};
 
 use crate::{
-    library::scan::ScanEvent,
-    media::metadata::Metadata,
+    library::{
+        db::{AlbumFilter, LibraryAccess},
+        scan::ScanEvent,
+        types::Tag,
+    },
+    media::metadata::{Metadata, StreamInfo},
     playback::{
-        events::RepeatState,
+        events::{OutputDevice, OutputPath, PlaybackEvent, RepeatState, Segment},
         queue::{QueueItemData, QueueItemUIData},
         thread::PlaybackState,
     },
-    services::mmb::{
-        MediaMetadataBroadcastService,
-        lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET, LastFM, client::LastFMClient, types::Session},
+    services::{
+        controllers::ControllerHealth,
+        mmb::{
+            MediaMetadataBroadcastService,
+            history::PlayHistory,
+            hooks::HookRunner,
+            lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET, client::LastFMClient, types::Session},
+            librefm::LibreFm,
+            listenbrainz::ListenBrainz,
+            now_playing::NowPlayingWriter,
+            scrobbler::{ScrobbleQueue, Scrobbler, queue_path},
+        },
+    },
+    settings::{
+        Settings, SettingsGlobal,
+        scrobbling::{LibreFmSettings, ListenBrainzSettings},
+        storage::StorageData,
+    },
+    ui::{
+        app::{Pool, get_dirs},
+        data::Decode,
+        library::ViewSwitchMessage,
     },
-    settings::{SettingsGlobal, storage::StorageData},
-    ui::{app::get_dirs, data::Decode, library::ViewSwitchMessage},
 };
 
 // yes this looks a little silly
@@ -54,6 +79,29 @@ pub struct Models {
     pub switcher_model: Entity<VecDeque<ViewSwitchMessage>>,
     pub show_about: Entity<bool>,
     pub playlist_tracker: Entity<PlaylistInfoTransfer>,
+    /// The moods and activities that tracks have been tagged with. Reloaded whenever a track's
+    /// tags change, which also tells the tag views to reload.
+    pub tags: Entity<Arc<Vec<Tag>>>,
+    /// The window the queue has been popped out into, if any.
+    pub queue_window: Entity<Option<AnyWindowHandle>>,
+    /// Whether private listening is on, which stops plays from being scrobbled, added to the
+    /// history or shown to others. Isn't kept between restarts.
+    pub private_listening: Entity<bool>,
+    /// Whether the computer is locked, as last checked. Always false where locking can't be
+    /// detected.
+    pub session_locked: Entity<bool>,
+    /// Which albums the album list shows, based on when they were last played.
+    pub album_filter: Entity<AlbumFilter>,
+    /// Text the album list is narrowed down to, matched against album titles and artist names.
+    /// Empty when the list isn't being filtered.
+    pub album_filter_text: Entity<SharedString>,
+    /// The number of podcast episodes that haven't been seen yet. Notified whenever podcasts
+    /// change.
+    pub new_episodes: Entity<i64>,
+    /// The playback event bus. See [`PlaybackEvents`].
+    pub playback_events: Entity<PlaybackEvents>,
+    /// The health of each playback controller (the OS media controls), by name.
+    pub controller_health: Entity<FxHashMap<String, ControllerHealth>>,
 }
 
 impl Global for Models {}
@@ -77,6 +125,17 @@ impl PartialEq<std::path::PathBuf> for CurrentTrack {
     }
 }
 
+/// Re-emits every event sent by the playback thread, in the order it was sent, once the playback
+/// models have been updated for it.
+///
+/// Anything that reacts to playback outside of rendering (the media services, the OS media
+/// controls) should subscribe to this rather than observing the models in [`PlaybackInfo`].
+/// Observers are only told that a model changed, so changes made close together can be merged,
+/// and changes to different models can't be put back in order.
+pub struct PlaybackEvents;
+
+impl EventEmitter<PlaybackEvent> for PlaybackEvents {}
+
 #[derive(Clone)]
 pub struct PlaybackInfo {
     pub position: Entity<u64>,
@@ -87,6 +146,21 @@ pub struct PlaybackInfo {
     pub repeating: Entity<RepeatState>,
     pub volume: Entity<f64>,
     pub prev_volume: Entity<f64>,
+    pub stream_info: Entity<Option<StreamInfo>>,
+    /// How the current track's audio reaches the local output device.
+    pub output_path: Entity<Option<OutputPath>>,
+    /// Where each chapter of the current track starts, in seconds.
+    pub chapters: Entity<Vec<f64>>,
+    /// The name of the network device that audio is being sent to, if any.
+    pub renderer: Entity<Option<SharedString>>,
+    /// The output devices the playback thread last listed.
+    pub output_devices: Entity<Vec<OutputDevice>>,
+    /// The UID of the output device picked by the user, or `None` for the default device.
+    pub output_device: Entity<Option<String>>,
+    /// The volume last used on each output, saved for the next time the app is opened.
+    pub output_volumes: Entity<FxHashMap<String, f64>>,
+    /// Whether playback is waiting for more of the current track to download.
+    pub buffering: Entity<bool>,
 }
 
 impl Global for PlaybackInfo {}
@@ -99,13 +173,94 @@ impl Global for PlaybackInfo {}
 #[derive(Debug, Clone)]
 pub struct Queue {
     pub data: Arc<RwLock<Vec<QueueItemData>>>,
+    /// If the queue is shuffled, the queue in the order it was added in. Empty otherwise.
+    ///
+    /// The playback thread locks this after [Queue::data], so this shouldn't be locked while
+    /// waiting to lock [Queue::data].
+    pub original: Arc<RwLock<Vec<QueueItemData>>>,
     pub position: usize,
 }
 
+impl Queue {
+    /// Returns the index of the current item in the queue, or in the order the queue was added in
+    /// if `added_order` is true and the queue is shuffled.
+    pub fn current(&self, added_order: bool) -> Option<usize> {
+        let data = self.data.read().expect("could not read queue");
+        let current = data.get(self.position)?;
+
+        if !added_order {
+            return Some(self.position);
+        }
+
+        let original = self.original.read().expect("could not read original queue");
+
+        if original.is_empty() {
+            Some(self.position)
+        } else {
+            original
+                .iter()
+                .position(|item| item.get_path() == current.get_path())
+        }
+    }
+}
+
 impl EventEmitter<(PathBuf, QueueItemUIData)> for Queue {}
 
 #[derive(Clone)]
-pub struct MMBSList(pub FxHashMap<String, Arc<Mutex<dyn MediaMetadataBroadcastService + Send>>>);
+pub struct MMBSList(pub FxHashMap<String, MMBSHandle>);
+
+/// A running [MediaMetadataBroadcastService]. Events are handled one at a time on the async
+/// runtime, in the order they were sent, so that a service is never told about a position before
+/// the track it belongs to.
+#[derive(Clone)]
+pub struct MMBSHandle {
+    events_tx: UnboundedSender<MMBSEvent>,
+    shares_listening: bool,
+    scrobbles: bool,
+}
+
+impl MMBSHandle {
+    /// Starts handling events for the service. It's stopped once every handle to it is dropped
+    /// and the events sent before then have been handled.
+    pub fn start(mut mmbs: impl MediaMetadataBroadcastService + Send + 'static) -> Self {
+        let (events_tx, mut events_rx) = unbounded_channel();
+        let shares_listening = mmbs.shares_listening();
+        let scrobbles = mmbs.scrobbles();
+
+        crate::RUNTIME.spawn(async move {
+            while let Some(event) = events_rx.recv().await {
+                match event {
+                    MMBSEvent::NewTrack(path) => mmbs.new_track(path),
+                    MMBSEvent::MetadataRecieved(metadata) => mmbs.metadata_recieved(metadata),
+                    MMBSEvent::StateChanged(state) => mmbs.state_changed(state),
+                    MMBSEvent::PositionChanged(position) => mmbs.position_changed(position),
+                    MMBSEvent::DurationChanged(duration) => mmbs.duration_changed(duration),
+                    MMBSEvent::SegmentChanged(segment) => {
+                        mmbs.segment_changed(segment.start, segment.duration)
+                    }
+                    MMBSEvent::RetryScrobbles => mmbs.retry_scrobbles(),
+                    MMBSEvent::DiscardScrobble(timestamp) => mmbs.discard_scrobble(timestamp),
+                }
+                .await;
+            }
+        });
+
+        MMBSHandle {
+            events_tx,
+            shares_listening,
+            scrobbles,
+        }
+    }
+
+    pub fn scrobbles(&self) -> bool {
+        self.scrobbles
+    }
+
+    /// Sends an event to the service. Events sent after the service has stopped are ignored.
+    pub fn send(&self, event: MMBSEvent) {
+        let _ = self.events_tx.send(event);
+    }
+}
 
 #[derive(Clone)]
 pub enum MMBSEvent {
@@ -114,9 +269,30 @@ pub enum MMBSEvent {
     StateChanged(PlaybackState),
     PositionChanged(u64),
     DurationChanged(u64),
+    SegmentChanged(Segment),
+    /// Sends the plays that are waiting to be scrobbled. Only sent when asked for, from the play
+    /// history.
+    RetryScrobbles,
+    /// Forgets a play that's waiting to be scrobbled, identified by when it started.
+    DiscardScrobble(i64),
 }
 
-impl EventEmitter<MMBSEvent> for MMBSList {}
+impl MMBSEvent {
+    /// Returns the event media services should be sent for the playback event, if any.
+    fn from_playback(event: &PlaybackEvent) -> Option<Self> {
+        Some(match event {
+            PlaybackEvent::SongChanged(path) => MMBSEvent::NewTrack(path.clone()),
+            PlaybackEvent::MetadataUpdate(metadata) => {
+                MMBSEvent::MetadataRecieved(Arc::new(*metadata.clone()))
+            }
+            PlaybackEvent::StateChanged(state) => MMBSEvent::StateChanged(*state),
+            PlaybackEvent::PositionChanged(position) => MMBSEvent::PositionChanged(*position),
+            PlaybackEvent::DurationChanged(duration) => MMBSEvent::DurationChanged(*duration),
+            PlaybackEvent::SegmentChanged(segment) => MMBSEvent::SegmentChanged(segment.clone()),
+            _ => return None,
+        })
+    }
+}
 
 pub struct PlaylistInfoTransfer;
 
@@ -135,8 +311,11 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
     let queue: Entity<Queue> = cx.new(move |_| queue);
     let scan_state: Entity<ScanEvent> = cx.new(|_| ScanEvent::ScanCompleteIdle);
     let mmbs: Entity<MMBSList> = cx.new(|_| MMBSList(FxHashMap::default()));
+    let playback_events: Entity<PlaybackEvents> = cx.new(|_| PlaybackEvents);
+    let controller_health: Entity<FxHashMap<String, ControllerHealth>> =
+        cx.new(|_| FxHashMap::default());
     let show_about: Entity<bool> = cx.new(|_| false);
-    let lastfm: Entity<LastFMState> = cx.new(|cx| {
+    let lastfm: Entity<LastFMState> = cx.new(|_| {
         let dirs = get_dirs();
         let directory = dirs.data_dir().to_path_buf();
         let path = directory.join("lastfm.json");
@@ -145,7 +324,6 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
             let reader = std::io::BufReader::new(file);
 
             if let Ok(session) = serde_json::from_reader::<std::io::BufReader<File>, Session>(reader) {
-                create_last_fm_mmbs(cx, &mmbs, session.key.clone());
                 LastFMState::Connected(session)
             } else {
                 error!("The last.fm session information is stored on disk but the file could not be opened.");
@@ -158,6 +336,13 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
     });
 
     let playlist_tracker: Entity<PlaylistInfoTransfer> = cx.new(|_| PlaylistInfoTransfer);
+    let queue_window: Entity<Option<AnyWindowHandle>> = cx.new(|_| None);
+    let private_listening: Entity<bool> = cx.new(|_| false);
+    let session_locked: Entity<bool> = cx.new(|_| false);
+    let album_filter: Entity<AlbumFilter> = cx.new(|_| AlbumFilter::All);
+    let album_filter_text: Entity<SharedString> = cx.new(|_| SharedString::default());
+    let new_episodes: Entity<i64> = cx.new(|_| 0);
+    let tags: Entity<Arc<Vec<Tag>>> = cx.new(|_| Arc::new(Vec::new()));
 
     cx.subscribe(&albumart, |e, ev, cx| {
         let img = ev.0.clone();
@@ -165,11 +350,8 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
     })
     .detach();
 
-    let mmbs_clone = mmbs.clone();
-
     cx.subscribe(&lastfm, move |m, ev, cx| {
         let session_clone = ev.clone();
-        create_last_fm_mmbs(cx, &mmbs_clone, session_clone.key.clone());
         m.update(cx, |m, cx| {
             *m = LastFMState::Connected(session_clone);
             cx.notify();
@@ -197,31 +379,62 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
     })
     .detach();
 
-    cx.subscribe(&mmbs, |m, ev, cx| {
-        let list = m.read(cx);
-
-        // cloning actually is neccesary because of the async move closure
-        #[allow(clippy::unnecessary_to_owned)]
-        for mmbs in list.0.values().cloned() {
-            let ev = ev.clone();
-            crate::RUNTIME.spawn(async move {
-                let mut borrow = mmbs.lock().await;
-                match ev {
-                    MMBSEvent::NewTrack(path) => borrow.new_track(path),
-                    MMBSEvent::MetadataRecieved(metadata) => borrow.metadata_recieved(metadata),
-                    MMBSEvent::StateChanged(state) => borrow.state_changed(state),
-                    MMBSEvent::PositionChanged(position) => borrow.position_changed(position),
-                    MMBSEvent::DurationChanged(duration) => borrow.duration_changed(duration),
-                }
-                .await;
+    // once private listening is turned off, the track that was playing stays private, and the
+    // same goes for tracks played while the computer is locked
+    let private_model = private_listening.clone();
+    let locked_model = session_locked.clone();
+    let mut suspended = false;
+    let mut audiobook = false;
+
+    let mmbs_list = mmbs.clone();
+
+    cx.subscribe(&playback_events, move |_, ev, cx| {
+        let Some(ev) = MMBSEvent::from_playback(ev) else {
+            return;
+        };
+
+        let private_when_locked = cx
+            .global::<SettingsGlobal>()
+            .model
+            .read(cx)
+            .playback
+            .private_when_locked;
+
+        if *private_model.read(cx) || (private_when_locked && *locked_model.read(cx)) {
+            suspended = true;
+        } else if let MMBSEvent::NewTrack(_) = ev {
+            suspended = false;
+        }
+
+        if let MMBSEvent::NewTrack(path) = &ev {
+            audiobook = cx.is_audiobook(path).unwrap_or_else(|err| {
+                error!("Failed to check whether the track is an audiobook: {err}");
+                false
             });
         }
+
+        for mmbs in mmbs_list.read(cx).0.values() {
+            if (suspended && mmbs.shares_listening) || (audiobook && mmbs.scrobbles) {
+                continue;
+            }
+
+            // the service's task only stops once its handle has been dropped
+            let _ = mmbs.events_tx.send(ev.clone());
+        }
     })
     .detach();
 
     let switcher_model = cx.new(|_| {
         let mut deque = VecDeque::new();
         deque.push_back(ViewSwitchMessage::Albums);
+
+        // going back from the restored view leads to the album list
+        if let Some(view) = storage_data.view
+            && view != ViewSwitchMessage::Albums
+        {
+            deque.push_back(view);
+        }
+
         deque
     });
 
@@ -235,6 +448,15 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
         switcher_model,
         show_about,
         playlist_tracker,
+        tags,
+        queue_window,
+        private_listening,
+        session_locked,
+        album_filter,
+        album_filter_text,
+        new_episodes,
+        playback_events,
+        controller_health,
     });
 
     const DEFAULT_VOLUME: f64 = 1.0;
@@ -256,6 +478,15 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
     });
     let volume: Entity<f64> = cx.new(|_| DEFAULT_VOLUME);
     let prev_volume: Entity<f64> = cx.new(|_| DEFAULT_VOLUME);
+    let stream_info: Entity<Option<StreamInfo>> = cx.new(|_| None);
+    let output_path: Entity<Option<OutputPath>> = cx.new(|_| None);
+    let chapters: Entity<Vec<f64>> = cx.new(|_| Vec::new());
+    let renderer: Entity<Option<SharedString>> = cx.new(|_| None);
+    let output_devices: Entity<Vec<OutputDevice>> = cx.new(|_| Vec::new());
+    let output_device: Entity<Option<String>> = cx.new(|_| None);
+    let output_volumes: Entity<FxHashMap<String, f64>> =
+        cx.new(|_| storage_data.output_volumes.clone());
+    let buffering: Entity<bool> = cx.new(|_| false);
 
     cx.set_global(PlaybackInfo {
         position,
@@ -266,16 +497,216 @@ pub fn build_models(cx: &mut App, queue: Queue, storage_data: &StorageData) {
         repeating,
         volume,
         prev_volume,
+        stream_info,
+        output_path,
+        chapters,
+        renderer,
+        output_devices,
+        output_device,
+        output_volumes,
+        buffering,
     });
 }
 
-pub fn create_last_fm_mmbs(cx: &mut App, mmbs_list: &Entity<MMBSList>, session: String) {
-    if let (Some(key), Some(secret)) = (LASTFM_API_KEY, LASTFM_API_SECRET) {
+/// How each scrobbling service is set up. A service's scrobbler is recreated when its part of this
+/// changes.
+#[derive(Clone, Default, PartialEq)]
+struct ScrobblerConfig {
+    /// The Last.fm session, if signed in and enabled.
+    lastfm: Option<String>,
+    listenbrainz: Option<ListenBrainzSettings>,
+    librefm: Option<LibreFmSettings>,
+}
+
+impl ScrobblerConfig {
+    fn read(cx: &App) -> Self {
+        let scrobbling = &cx.global::<SettingsGlobal>().model.read(cx).scrobbling;
+        let lastfm = match cx.global::<Models>().lastfm.read(cx) {
+            LastFMState::Connected(session) if scrobbling.lastfm.enabled => {
+                Some(session.key.clone())
+            }
+            _ => None,
+        };
+
+        ScrobblerConfig {
+            lastfm,
+            listenbrainz: Some(scrobbling.listenbrainz.clone())
+                .filter(|listenbrainz| listenbrainz.token.is_some()),
+            librefm: Some(scrobbling.librefm.clone()).filter(|librefm| librefm.username.is_some()),
+        }
+    }
+
+    fn lastfm(&self) -> Option<Box<dyn Scrobbler>> {
+        let session = self.lastfm.clone()?;
+        let (key, secret) = LASTFM_API_KEY.zip(LASTFM_API_SECRET)?;
+
         let mut client = LastFMClient::new(key.to_string(), secret.to_string());
         client.set_session(session);
-        let mmbs = LastFM::new(client);
-        mmbs_list.update(cx, |m, _| {
-            m.0.insert("lastfm".to_string(), Arc::new(Mutex::new(mmbs)));
-        })
+        Some(Box::new(client))
+    }
+
+    fn listenbrainz(&self) -> Option<Box<dyn Scrobbler>> {
+        let settings = self.listenbrainz.as_ref()?;
+
+        match ListenBrainz::new(&settings.api_url, settings.token.clone()?) {
+            Ok(client) => Some(Box::new(client)),
+            Err(err) => {
+                error!(?err, "Could not set up ListenBrainz: {err}");
+                None
+            }
+        }
     }
+
+    fn librefm(&self, data_dir: &Path) -> Option<Box<dyn Scrobbler>> {
+        let settings = self.librefm.as_ref()?;
+
+        Some(Box::new(LibreFm::new(
+            settings.username.clone()?,
+            settings.password.clone(),
+            data_dir.join("librefm.json"),
+        )))
+    }
+}
+
+/// Starts sending plays to the scrobbling services that are set up, and keeps them up to date
+/// when the settings change or Last.fm is signed in to. Each service has its own queue, so a
+/// service being unreachable doesn't hold up the others.
+pub fn create_scrobbler_mmbs(cx: &mut App) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+    let lastfm = cx.global::<Models>().lastfm.clone();
+    let mmbs_list = cx.global::<Models>().mmbs.clone();
+    let data_dir = get_dirs().data_dir().to_path_buf();
+    let current = Rc::new(RefCell::new(ScrobblerConfig::default()));
+
+    let update = move |cx: &mut App| {
+        let config = ScrobblerConfig::read(cx);
+        let previous = current.replace(config.clone());
+
+        let mut changed: Vec<(&str, Option<Box<dyn Scrobbler>>)> = Vec::new();
+        if config.lastfm != previous.lastfm {
+            changed.push(("lastfm", config.lastfm()));
+        }
+        if config.listenbrainz != previous.listenbrainz {
+            changed.push(("listenbrainz", config.listenbrainz()));
+        }
+        if config.librefm != previous.librefm {
+            changed.push(("librefm", config.librefm(&data_dir)));
+        }
+
+        if changed.is_empty() {
+            return;
+        }
+
+        mmbs_list.update(cx, |m, _| {
+            for (name, scrobbler) in changed {
+                if let Some(scrobbler) = scrobbler {
+                    let queue_path = queue_path(&data_dir, name);
+                    let mmbs = ScrobbleQueue::new(name, scrobbler, queue_path);
+                    m.0.insert(name.to_string(), MMBSHandle::start(mmbs));
+                } else {
+                    m.0.remove(name);
+                }
+            }
+        });
+    };
+
+    update(cx);
+
+    let update = Rc::new(RefCell::new(update));
+    let on_settings = update.clone();
+    cx.observe(&settings, move |_, cx| (on_settings.borrow_mut())(cx))
+        .detach();
+    cx.observe(&lastfm, move |_, cx| (update.borrow_mut())(cx))
+        .detach();
+}
+
+/// Starts recording plays to the play history. The database pool must already be set up.
+pub fn create_history_mmbs(cx: &mut App) {
+    let pool = cx.global::<Pool>();
+    let mmbs = PlayHistory::new(pool.read.clone(), pool.write.clone());
+    let mmbs_list = cx.global::<Models>().mmbs.clone();
+
+    mmbs_list.update(cx, |m, _| {
+        m.0.insert("history".to_string(), MMBSHandle::start(mmbs));
+    })
+}
+
+/// Starts running the script hooks from the settings, and keeps them up to date when the settings
+/// change.
+pub fn create_hooks_mmbs(cx: &mut App) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+    let mmbs_list = cx.global::<Models>().mmbs.clone();
+    let mut current = None;
+
+    let mut update = move |settings: Entity<Settings>, cx: &mut App| {
+        let hooks = settings.read(cx).scripts.hooks.clone();
+        if current.as_ref() == Some(&hooks) {
+            return;
+        }
+        current = Some(hooks.clone());
+
+        mmbs_list.update(cx, |m, _| {
+            if hooks.track_changed.is_empty() && hooks.scrobbled.is_empty() {
+                m.0.remove("hooks");
+            } else {
+                let mmbs = HookRunner::new(hooks);
+                m.0.insert("hooks".to_string(), MMBSHandle::start(mmbs));
+            }
+        });
+    };
+
+    update(settings.clone(), cx);
+    cx.observe(&settings, update).detach();
+}
+
+/// Starts writing the current track to the now playing file, if one is set in the settings.
+pub fn create_now_playing_mmbs(cx: &mut App) {
+    let settings = cx.global::<SettingsGlobal>().model.clone();
+    let mmbs_list = cx.global::<Models>().mmbs.clone();
+    let mut current = None;
+
+    let mut update = move |settings: Entity<Settings>, cx: &mut App| {
+        let now_playing = settings.read(cx).now_playing.clone();
+        if current.as_ref() == Some(&now_playing) {
+            return;
+        }
+        current = Some(now_playing.clone());
+
+        mmbs_list.update(cx, |m, _| {
+            if let Some(path) = now_playing.path.clone() {
+                let mmbs = NowPlayingWriter::new(now_playing, path);
+                m.0.insert("now_playing".to_string(), MMBSHandle::start(mmbs));
+            } else {
+                m.0.remove("now_playing");
+            }
+        });
+    };
+
+    update(settings.clone(), cx);
+    cx.observe(&settings, update).detach();
+}
+
+/// Loves (or unloves) a track on Last.fm, if an account is connected.
+pub fn set_lastfm_loved(cx: &App, artist: String, title: String, loved: bool) {
+    let LastFMState::Connected(session) = cx.global::<Models>().lastfm.read(cx) else {
+        return;
+    };
+    let (Some(key), Some(secret)) = (LASTFM_API_KEY, LASTFM_API_SECRET) else {
+        return;
+    };
+
+    let mut client = LastFMClient::new(key.to_string(), secret.to_string());
+    client.set_session(session.key.clone());
+
+    crate::RUNTIME.spawn(async move {
+        let result = if loved {
+            client.love(&artist, &title).await
+        } else {
+            client.unlove(&artist, &title).await
+        };
+
+        if let Err(err) = result {
+            warn!(?err, "Could not update loved track on last.fm: {err}");
+        }
+    });
 }
@@ -0,0 +1,474 @@
+use std::{fs, path::Path};
+
+use gpui::{
+    App, AppContext, Context, Entity, FontWeight, InteractiveElement, IntoElement, ParentElement,
+    PathPromptOptions, Render, SharedString, StatefulInteractiveElement, Styled, Window, div,
+    prelude::FluentBuilder,
+};
+use tracing::error;
+
+use crate::{
+    library::scan::{ScanEvent, ScanInterface},
+    services::mmb::lastfm::{LASTFM_API_KEY, LASTFM_API_SECRET},
+    settings::{SettingsGlobal, libraries, save_settings},
+    ui::{
+        app::{ActiveLibrary, get_dirs},
+        assets::bundled::BundledAssets,
+        components::{
+            button::{ButtonIntent, ButtonStyle, button},
+            icons::{FOLDER, TRASH, icon},
+            modal::modal,
+        },
+        header::lastfm::{confirm, get_token},
+        locale::t,
+        models::{LastFMState, Models},
+        theme::{Theme, create_theme},
+        util::spx,
+    },
+};
+
+/// The bundled theme offered alongside the default dark theme.
+const LIGHT_THEME: &str = "themes/light.json";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Folders,
+    Theme,
+    LastFM,
+    Scan,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ThemeChoice {
+    Dark,
+    Light,
+}
+
+/// A wizard shown on the first launch, which walks through picking the folders to scan, choosing
+/// a theme and connecting Last.fm before starting the first scan.
+pub struct Onboarding {
+    show: Entity<bool>,
+    steps: Vec<Step>,
+    current: usize,
+    theme: Option<ThemeChoice>,
+    /// Whether the scanner has reported progress since the scan step started the scan, so that the
+    /// idle state left over from startup isn't mistaken for a finished scan.
+    scan_seen: bool,
+}
+
+impl Onboarding {
+    pub fn new(cx: &mut App, show: Entity<bool>) -> Entity<Self> {
+        cx.new(|cx| {
+            let models = cx.global::<Models>();
+            let scan_state = models.scan_state.clone();
+            let lastfm = models.lastfm.clone();
+            let settings = cx.global::<SettingsGlobal>().model.clone();
+
+            cx.observe(&show, |_, _, cx| cx.notify()).detach();
+            cx.observe(&settings, |_, _, cx| cx.notify()).detach();
+            cx.observe(&lastfm, |_, _, cx| cx.notify()).detach();
+
+            cx.observe(&scan_state, |this: &mut Self, state, cx| {
+                if !matches!(
+                    state.read(cx),
+                    ScanEvent::ScanCompleteIdle | ScanEvent::ScanCompleteWatching
+                ) {
+                    this.scan_seen = true;
+                }
+
+                cx.notify();
+            })
+            .detach();
+
+            let mut steps = vec![Step::Folders, Step::Theme];
+
+            if LASTFM_API_KEY.is_some() && LASTFM_API_SECRET.is_some() {
+                steps.push(Step::LastFM);
+            }
+
+            steps.push(Step::Scan);
+
+            Self {
+                show,
+                steps,
+                current: 0,
+                theme: None,
+                scan_seen: false,
+            }
+        })
+    }
+
+    fn step(&self) -> Step {
+        self.steps[self.current]
+    }
+
+    fn back(&mut self, cx: &mut Context<Self>) {
+        self.current = self.current.saturating_sub(1);
+        cx.notify();
+    }
+
+    fn next(&mut self, cx: &mut Context<Self>) {
+        if self.current + 1 == self.steps.len() {
+            self.show.write(cx, false);
+            return;
+        }
+
+        self.current += 1;
+
+        if self.step() == Step::Scan {
+            self.scan_seen = false;
+            cx.global::<ScanInterface>().scan();
+        }
+
+        cx.notify();
+    }
+
+    /// Asks for a folder to add to the scan paths, in the same way as the folder view.
+    fn add_folder(&mut self, cx: &mut Context<Self>) {
+        let path_future = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some(t!(cx, "folders.add_prompt")),
+        });
+
+        cx.spawn(async move |_, cx| {
+            let Ok(Ok(Some(paths))) = path_future.await else {
+                return;
+            };
+            let Some(folder) = paths.into_iter().next() else {
+                return;
+            };
+
+            cx.update(|cx| {
+                let name = cx.global::<ActiveLibrary>().name.clone();
+                let settings = cx.global::<SettingsGlobal>().model.clone();
+
+                settings.update(cx, |settings, cx| {
+                    libraries::add_scan_path(settings, name.as_deref(), folder);
+                    cx.notify();
+                });
+
+                save_settings(cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn remove_folder(&mut self, folder: &Path, cx: &mut Context<Self>) {
+        let library = cx.global::<ActiveLibrary>();
+        let directory = library.directory.clone();
+        let data_dir = library.data_dir.clone();
+        let settings = cx.global::<SettingsGlobal>().model.clone();
+
+        settings.update(cx, |settings, cx| {
+            libraries::remove_scan_path(settings, &directory, &data_dir, folder);
+            cx.notify();
+        });
+
+        save_settings(cx);
+    }
+
+    /// Writes the chosen theme to `theme.json` and applies it straight away. The dark theme is
+    /// the default, so choosing it removes the file, unless it holds a theme the user wrote.
+    fn set_theme(&mut self, choice: ThemeChoice, cx: &mut Context<Self>) {
+        let path = get_dirs().config_dir().join("theme.json");
+        let light = BundledAssets::get(LIGHT_THEME).map(|file| file.data);
+
+        let result = match (choice, light) {
+            (ThemeChoice::Light, Some(light)) => fs::write(&path, light),
+            (ThemeChoice::Light, None) => return,
+            (ThemeChoice::Dark, light) => {
+                let existing = fs::read(&path).ok();
+
+                if existing.is_some() && existing.as_deref() == light.as_deref() {
+                    fs::remove_file(&path)
+                } else {
+                    Ok(())
+                }
+            }
+        };
+
+        if let Err(err) = result {
+            error!("Failed to write theme to '{}': {err}", path.display());
+            return;
+        }
+
+        self.theme = Some(choice);
+        cx.set_global(create_theme(&path));
+        cx.refresh_windows();
+    }
+
+    fn render_folders(&self, cx: &mut Context<Self>) -> impl IntoElement + use<> {
+        let theme = cx.global::<Theme>();
+        let settings = cx.global::<SettingsGlobal>().model.read(cx);
+        let folders = cx
+            .global::<ActiveLibrary>()
+            .scan_settings(settings)
+            .folders();
+        let empty = folders.is_empty();
+
+        let rows = folders.into_iter().enumerate().map(|(idx, folder)| {
+            div()
+                .flex()
+                .items_center()
+                .gap(spx(8.0))
+                .px(spx(8.0))
+                .py(spx(4.0))
+                .rounded(spx(4.0))
+                .hover(|this| this.bg(theme.queue_item_hover))
+                .child(
+                    icon(FOLDER)
+                        .size(spx(14.0))
+                        .flex_shrink_0()
+                        .text_color(theme.text_secondary),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w(spx(0.0))
+                        .truncate()
+                        .child(folder.display().to_string()),
+                )
+                .child(
+                    button()
+                        .id(("onboarding-remove-folder", idx))
+                        .style(ButtonStyle::Minimal)
+                        .on_click(
+                            cx.listener(move |this, _, _, cx| this.remove_folder(&folder, cx)),
+                        )
+                        .child(icon(TRASH).size(spx(14.0))),
+                )
+        });
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(spx(6.0))
+            .child(
+                div()
+                    .id("onboarding-folders")
+                    .flex()
+                    .flex_col()
+                    .max_h(spx(220.0))
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+            .when(empty, |this| {
+                this.child(
+                    div()
+                        .text_color(theme.text_secondary)
+                        .child(t!(cx, "onboarding.folders_empty")),
+                )
+            })
+            .child(
+                div().flex().child(
+                    button()
+                        .id("onboarding-add-folder")
+                        .on_click(cx.listener(|this, _, _, cx| this.add_folder(cx)))
+                        .child(t!(cx, "onboarding.folders_add")),
+                ),
+            )
+    }
+
+    fn render_theme(&self, cx: &mut Context<Self>) -> impl IntoElement + use<> {
+        let choices = [
+            (ThemeChoice::Dark, t!(cx, "onboarding.theme_dark")),
+            (ThemeChoice::Light, t!(cx, "onboarding.theme_light")),
+        ];
+
+        div()
+            .flex()
+            .gap(spx(6.0))
+            .children(choices.into_iter().map(|(choice, label)| {
+                button()
+                    .id(match choice {
+                        ThemeChoice::Dark => "onboarding-theme-dark",
+                        ThemeChoice::Light => "onboarding-theme-light",
+                    })
+                    .when(self.theme == Some(choice), |this| {
+                        this.intent(ButtonIntent::Primary)
+                    })
+                    .on_click(cx.listener(move |this, _, _, cx| this.set_theme(choice, cx)))
+                    .child(label)
+            }))
+    }
+
+    fn render_lastfm(&self, cx: &mut Context<Self>) -> impl IntoElement + use<> {
+        let theme = cx.global::<Theme>();
+        let state = cx.global::<Models>().lastfm.clone();
+
+        let action = match state.read(cx).clone() {
+            LastFMState::Disconnected => Some((t!(cx, "onboarding.lastfm_connect"), None)),
+            LastFMState::AwaitingFinalization(token) => {
+                Some((t!(cx, "onboarding.lastfm_confirm"), Some(token)))
+            }
+            LastFMState::Connected(_) => None,
+        };
+        let connected = match state.read(cx) {
+            LastFMState::Connected(session) => Some(session.name.clone()),
+            _ => None,
+        };
+
+        div()
+            .flex()
+            .when_some(action, |this, (label, token)| {
+                this.child(
+                    button()
+                        .id("onboarding-lastfm")
+                        .on_click(move |_, _, cx| match token.clone() {
+                            Some(token) => confirm(cx, state.clone(), token),
+                            None => get_token(cx, state.clone()),
+                        })
+                        .child(label),
+                )
+            })
+            .when_some(connected, |this, name| {
+                this.child(div().text_color(theme.text_secondary).child(t!(
+                    cx,
+                    "onboarding.lastfm_connected",
+                    name = name
+                )))
+            })
+    }
+
+    fn render_scan(&self, cx: &mut Context<Self>) -> impl IntoElement + use<> {
+        let theme = cx.global::<Theme>();
+        let settings = cx.global::<SettingsGlobal>().model.read(cx);
+        let no_folders = cx
+            .global::<ActiveLibrary>()
+            .scan_settings(settings)
+            .folders()
+            .is_empty();
+
+        let status: SharedString = if no_folders {
+            t!(cx, "onboarding.scan_no_folders")
+        } else if !self.scan_seen {
+            t!(cx, "onboarding.scan_starting")
+        } else {
+            match cx.global::<Models>().scan_state.read(cx) {
+                ScanEvent::ScanProgress { current, total } => t!(
+                    cx,
+                    "scan.scanning",
+                    percent = (*current as f64 / *total as f64 * 100.0).round()
+                ),
+                ScanEvent::DiscoverProgress(progress) => {
+                    t!(cx, "scan.discovering", count = progress)
+                }
+                ScanEvent::Cleaning => t!(cx, "onboarding.scan_starting"),
+                ScanEvent::ScanCompleteIdle | ScanEvent::ScanCompleteWatching => {
+                    t!(cx, "onboarding.scan_done")
+                }
+            }
+        };
+
+        div().text_color(theme.text_secondary).child(status)
+    }
+}
+
+impl Render for Onboarding {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !*self.show.read(cx) {
+            return div().into_any_element();
+        }
+
+        let show = self.show.clone();
+        let show_skip = self.show.clone();
+        let step = self.step();
+        let last = self.current + 1 == self.steps.len();
+        let progress = t!(
+            cx,
+            "onboarding.step",
+            current = self.current + 1,
+            total = self.steps.len()
+        );
+
+        let (title, message) = match step {
+            Step::Folders => (
+                t!(cx, "onboarding.folders_title"),
+                t!(cx, "onboarding.folders_message"),
+            ),
+            Step::Theme => (
+                t!(cx, "onboarding.theme_title"),
+                t!(cx, "onboarding.theme_message"),
+            ),
+            Step::LastFM => (
+                t!(cx, "onboarding.lastfm_title"),
+                t!(cx, "onboarding.lastfm_message"),
+            ),
+            Step::Scan => (
+                t!(cx, "onboarding.scan_title"),
+                t!(cx, "onboarding.scan_message"),
+            ),
+        };
+
+        let body = match step {
+            Step::Folders => self.render_folders(cx).into_any_element(),
+            Step::Theme => self.render_theme(cx).into_any_element(),
+            Step::LastFM => self.render_lastfm(cx).into_any_element(),
+            Step::Scan => self.render_scan(cx).into_any_element(),
+        };
+
+        let theme = cx.global::<Theme>();
+
+        modal()
+            .child(
+                div()
+                    .w(spx(480.0))
+                    .p(spx(16.0))
+                    .flex()
+                    .flex_col()
+                    .gap(spx(6.0))
+                    .text_sm()
+                    .child(div().text_color(theme.text_secondary).child(progress))
+                    .child(
+                        div()
+                            .font_weight(FontWeight::BOLD)
+                            .text_size(spx(18.0))
+                            .child(title),
+                    )
+                    .child(div().text_color(theme.text_secondary).child(message))
+                    .child(div().pt(spx(4.0)).child(body))
+                    .child(
+                        div()
+                            .flex()
+                            .gap(spx(6.0))
+                            .pt(spx(10.0))
+                            .when(!last, |this| {
+                                this.child(
+                                    button()
+                                        .id("onboarding-skip")
+                                        .style(ButtonStyle::Minimal)
+                                        .on_click(move |_, _, cx| show_skip.write(cx, false))
+                                        .child(t!(cx, "onboarding.skip")),
+                                )
+                            })
+                            .child(div().flex_1())
+                            .when(self.current > 0, |this| {
+                                this.child(
+                                    button()
+                                        .id("onboarding-back")
+                                        .on_click(cx.listener(|this, _, _, cx| this.back(cx)))
+                                        .child(t!(cx, "onboarding.back")),
+                                )
+                            })
+                            .child(
+                                button()
+                                    .id("onboarding-next")
+                                    .intent(ButtonIntent::Primary)
+                                    .on_click(cx.listener(|this, _, _, cx| this.next(cx)))
+                                    .child(if last {
+                                        t!(cx, "onboarding.finish")
+                                    } else {
+                                        t!(cx, "onboarding.next")
+                                    }),
+                            ),
+                    ),
+            )
+            .on_exit(move |_, cx| {
+                show.write(cx, false);
+            })
+            .into_any_element()
+    }
+}
@@ -1,32 +1,56 @@
 use crate::{
+    library::db::LibraryAccess,
     playback::{
         interface::PlaybackInterface,
         queue::{DataSource, QueueItemData},
     },
+    settings::SettingsGlobal,
     ui::components::{
-        icons::{CROSS, SHUFFLE, TRASH, icon},
+        icons::{CLEAR_ALL, CROSS, EXTERNAL_LINK, PLAYLIST_REMOVE, SHUFFLE, TRASH, icon},
         nav_button::nav_button,
+        tooltip::tooltip,
     },
 };
 use gpui::*;
 use prelude::FluentBuilder;
 use rustc_hash::FxHashMap;
+use tracing::error;
 
 use super::{
-    components::button::{ButtonSize, ButtonStyle, button},
+    components::button::{ButtonIntent, ButtonSize, ButtonStyle, button},
+    locale::{Locale, t},
     models::{Models, PlaybackInfo},
     theme::Theme,
-    util::{create_or_retrieve_view, drop_image_from_app, prune_views},
+    util::{create_or_retrieve_view, drop_image_from_app, prune_views, scaled_rem_size, spx},
 };
 
+actions!(queue, [CloseQueueWindow]);
+
+pub fn bind_actions(cx: &mut App) {
+    // takes precedence over quitting with ctrl-w, which isn't bound to a context
+    cx.bind_keys([KeyBinding::new(
+        "secondary-w",
+        CloseQueueWindow,
+        Some("QueueWindow"),
+    )]);
+}
+
 pub struct QueueItem {
     item: Option<QueueItemData>,
-    current: usize,
+    current: Option<usize>,
     idx: usize,
+    /// Whether the item is listed in the order the queue was added in, rather than the order it
+    /// will be played in.
+    added_order: bool,
 }
 
 impl QueueItem {
-    pub fn new(cx: &mut App, item: Option<QueueItemData>, idx: usize) -> Entity<Self> {
+    pub fn new(
+        cx: &mut App,
+        item: Option<QueueItemData>,
+        idx: usize,
+        added_order: bool,
+    ) -> Entity<Self> {
         cx.new(move |cx| {
             cx.on_release(|m: &mut QueueItem, cx| {
                 if let Some(item) = m.item.as_mut() {
@@ -43,8 +67,8 @@ impl QueueItem {
 
             let queue = cx.global::<Models>().queue.clone();
 
-            cx.observe(&queue, |this: &mut QueueItem, queue, cx| {
-                this.current = queue.read(cx).position;
+            cx.observe(&queue, move |this: &mut QueueItem, queue, cx| {
+                this.current = queue.read(cx).current(added_order);
             })
             .detach();
 
@@ -58,7 +82,8 @@ impl QueueItem {
             Self {
                 item,
                 idx,
-                current: queue.read(cx).position,
+                current: queue.read(cx).current(added_order),
+                added_order,
             }
         })
     }
@@ -80,11 +105,12 @@ impl Render for QueueItem {
             //     .map(|v| v == &item.file_path)
             //     .unwrap_or(false);
 
-            let is_current = self.current == self.idx;
+            let is_current = self.current == Some(self.idx);
 
             let album_art = item.image.as_ref().cloned();
 
             let idx = self.idx;
+            let added_order = self.added_order;
 
             div()
                 .w_full()
@@ -92,33 +118,37 @@ impl Render for QueueItem {
                 .flex()
                 .flex_shrink_0()
                 .overflow_x_hidden()
-                .gap(px(11.0))
-                .h(px(59.0))
-                .p(px(11.0))
-                .border_b(px(1.0))
+                .gap(spx(11.0))
+                .h(spx(59.0))
+                .p(spx(11.0))
+                .border_b(spx(1.0))
                 .cursor_pointer()
                 .border_color(theme.border_color)
                 .when(is_current, |div| div.bg(theme.queue_item_current))
                 .on_click(move |_, _, cx| {
-                    cx.global::<PlaybackInterface>().jump(idx);
+                    if added_order {
+                        cx.global::<PlaybackInterface>().jump_unshuffled(idx);
+                    } else {
+                        cx.global::<PlaybackInterface>().jump(idx);
+                    }
                 })
                 .hover(|div| div.bg(theme.queue_item_hover))
                 .active(|div| div.bg(theme.queue_item_active))
                 .child(
                     div()
                         .id("album-art")
-                        .rounded(px(4.0))
+                        .rounded(spx(4.0))
                         .bg(theme.album_art_background)
                         .shadow_sm()
-                        .w(px(36.0))
-                        .h(px(36.0))
+                        .w(spx(36.0))
+                        .h(spx(36.0))
                         .flex_shrink_0()
                         .when(album_art.is_some(), |div| {
                             div.child(
                                 img(album_art.unwrap())
-                                    .w(px(36.0))
-                                    .h(px(36.0))
-                                    .rounded(px(4.0)),
+                                    .w(spx(36.0))
+                                    .h(spx(36.0))
+                                    .rounded(spx(4.0)),
                             )
                         }),
                 )
@@ -127,7 +157,7 @@ impl Render for QueueItem {
                         .flex()
                         .flex_col()
                         .line_height(rems(1.0))
-                        .text_size(px(15.0))
+                        .text_size(spx(15.0))
                         .gap_1()
                         .overflow_x_hidden()
                         .child(
@@ -147,8 +177,8 @@ impl Render for QueueItem {
         } else {
             // TODO: Skeleton for this
             div()
-                .h(px(59.0))
-                .border_t(px(1.0))
+                .h(spx(59.0))
+                .border_t(spx(1.0))
                 .border_color(theme.border_color)
                 .w_full()
                 .id(ElementId::View(cx.entity_id()))
@@ -156,15 +186,143 @@ impl Render for QueueItem {
     }
 }
 
+/// Shows how long the queue is, how much of it is left, and when it will finish.
+pub struct QueueStats {
+    /// The duration of each item in the queue, in seconds, if it's in the library.
+    durations: Vec<Option<u64>>,
+}
+
+impl QueueStats {
+    fn new(cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let queue = cx.global::<Models>().queue.clone();
+
+            cx.observe(&queue, |this: &mut QueueStats, _, cx| {
+                this.durations = Self::read_durations(cx);
+                cx.notify();
+            })
+            .detach();
+
+            let playback_info = cx.global::<PlaybackInfo>();
+            let position = playback_info.position.clone();
+            let duration = playback_info.duration.clone();
+
+            cx.observe(&position, |_, _, cx| cx.notify()).detach();
+            cx.observe(&duration, |_, _, cx| cx.notify()).detach();
+
+            QueueStats {
+                durations: Self::read_durations(cx),
+            }
+        })
+    }
+
+    fn read_durations(cx: &mut App) -> Vec<Option<u64>> {
+        let ids: Vec<Option<i64>> = cx
+            .global::<Models>()
+            .queue
+            .read(cx)
+            .data
+            .read()
+            .expect("could not read queue")
+            .iter()
+            .map(|item| item.get_db_id())
+            .collect();
+
+        let known: Vec<i64> = ids.iter().flatten().copied().collect();
+        let durations: FxHashMap<i64, i64> = cx
+            .get_track_durations(&known)
+            .unwrap_or_else(|err| {
+                error!("Failed to get queue durations: {err}");
+                Vec::new()
+            })
+            .into_iter()
+            .collect();
+
+        ids.into_iter()
+            .map(|id| durations.get(&id?).map(|duration| *duration as u64))
+            .collect()
+    }
+}
+
+fn format_duration(cx: &App, seconds: u64) -> SharedString {
+    let minutes = seconds / 60;
+
+    if minutes >= 60 {
+        t!(
+            cx,
+            "queue.stats.hours",
+            hours = minutes / 60,
+            minutes = minutes % 60
+        )
+    } else {
+        t!(cx, "queue.stats.minutes", minutes = minutes)
+    }
+}
+
+impl Render for QueueStats {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let current = cx.global::<Models>().queue.read(cx).position;
+        let playback_info = cx.global::<PlaybackInfo>();
+        let playing = playback_info.current_track.read(cx).is_some();
+        let position = *playback_info.position.read(cx);
+        let current_duration = *playback_info.duration.read(cx);
+
+        let total: u64 = self.durations.iter().flatten().sum();
+
+        // the current track might not be in the library, but its length is known once it's loaded
+        let remaining = if playing && current < self.durations.len() {
+            let current_duration = self.durations[current].unwrap_or(current_duration);
+            current_duration.saturating_sub(position)
+                + self.durations[current + 1..].iter().flatten().sum::<u64>()
+        } else {
+            self.durations
+                .get(current..)
+                .map(|rest| rest.iter().flatten().sum())
+                .unwrap_or(0)
+        };
+
+        let ends_at = chrono::Local::now() + chrono::Duration::seconds(remaining as i64);
+
+        div()
+            .flex()
+            .flex_col()
+            .text_sm()
+            .text_color(theme.text_secondary)
+            .child(t!(
+                cx,
+                "queue.stats.total",
+                tracks = cx
+                    .global::<Locale>()
+                    .plural("sidebar.track_count", self.durations.len() as i64),
+                duration = format_duration(cx, total)
+            ))
+            .when(remaining > 0, |this| {
+                this.child(t!(
+                    cx,
+                    "queue.stats.remaining",
+                    duration = format_duration(cx, remaining),
+                    time = ends_at.format("%H:%M")
+                ))
+            })
+    }
+}
+
 pub struct Queue {
     views_model: Entity<FxHashMap<usize, Entity<QueueItem>>>,
     render_counter: Entity<usize>,
     shuffling: Entity<bool>,
-    show_queue: Entity<bool>,
+    stats: Entity<QueueStats>,
+    /// Whether a shuffled queue is listed in the order it was added in, rather than the order it
+    /// will be played in.
+    added_order: bool,
+    /// Controls whether the queue is shown in the main window. This is `None` when the queue is
+    /// displayed in its own window (see [open_queue_window]).
+    show_queue: Option<Entity<bool>>,
 }
 
 impl Queue {
-    pub fn new(cx: &mut App, show_queue: Entity<bool>) -> Entity<Self> {
+    pub fn new(cx: &mut App, show_queue: Option<Entity<bool>>) -> Entity<Self> {
         cx.new(|cx| {
             let views_model = cx.new(|_| FxHashMap::default());
             let render_counter = cx.new(|_| 0);
@@ -189,6 +347,8 @@ impl Queue {
                 views_model,
                 render_counter,
                 shuffling,
+                stats: QueueStats::new(cx),
+                added_order: false,
                 show_queue,
             }
         })
@@ -198,59 +358,105 @@ impl Queue {
 impl Render for Queue {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = cx.global::<Theme>();
-        let queue = cx
-            .global::<Models>()
-            .queue
-            .clone()
-            .read(cx)
-            .data
-            .read()
-            .expect("could not read queue");
         let shuffling = self.shuffling.read(cx);
+        let added_order = self.added_order && *shuffling;
+        let queue_model = cx.global::<Models>().queue.read(cx);
+        let queue_len = if added_order {
+            queue_model
+                .original
+                .read()
+                .expect("could not read original queue")
+                .len()
+        } else {
+            queue_model.data.read().expect("could not read queue").len()
+        };
         let views_model = self.views_model.clone();
         let render_counter = self.render_counter.clone();
+        let detached = self.show_queue.is_none();
 
         div()
             // .absolute()
             // .top_0()
             // .right_0()
             .h_full()
-            .min_w(px(275.0))
-            .max_w(px(275.0))
-            .w(px(275.0))
-            .border_l(px(1.0))
+            .when(!detached, |this| {
+                this.min_w(spx(275.0))
+                    .max_w(spx(275.0))
+                    .w(spx(275.0))
+                    .border_l(spx(1.0))
+            })
+            .when(detached, |this| this.w_full())
             .flex_shrink_0()
             .border_color(theme.border_color)
-            .pb(px(0.0))
+            .pb(spx(0.0))
             .flex()
             .flex_col()
             .child(
                 div().flex().child(
-                    div().flex().w_full().child(
-                        nav_button("close", CROSS)
-                            .mt(px(9.0))
-                            .mr(px(9.0))
-                            .ml_auto()
-                            .on_click(cx.listener(|this: &mut Self, _, _, cx| {
-                                this.show_queue.update(cx, |v, _| *v = !(*v))
-                            })),
-                    ),
+                    div()
+                        .flex()
+                        .w_full()
+                        .mt(spx(9.0))
+                        .mr(spx(9.0))
+                        .gap(spx(4.0))
+                        .justify_end()
+                        .child(
+                            nav_button("remove-played", PLAYLIST_REMOVE)
+                                .tooltip(tooltip(t!(cx, "queue.remove_played")))
+                                .on_click(|_, _, cx| {
+                                    cx.global::<PlaybackInterface>().remove_played();
+                                }),
+                        )
+                        .child(
+                            nav_button("clear-except-current", CLEAR_ALL)
+                                .tooltip(tooltip(t!(cx, "queue.clear_except_current")))
+                                .on_click(|_, _, cx| {
+                                    cx.global::<PlaybackInterface>()
+                                        .clear_queue_except_current();
+                                }),
+                        )
+                        .when(!detached, |this| {
+                            this.child(
+                                nav_button("pop-out", EXTERNAL_LINK)
+                                    .tooltip(tooltip(t!(cx, "queue.pop_out")))
+                                    .on_click(|_, _, cx| {
+                                        open_queue_window(cx);
+                                    }),
+                            )
+                        })
+                        .child(
+                            nav_button("close", CROSS)
+                                .tooltip(tooltip(t!(cx, "queue.close")))
+                                .on_click(cx.listener(|this: &mut Self, _, window, cx| {
+                                    match &this.show_queue {
+                                        Some(show_queue) => {
+                                            show_queue.update(cx, |v, _| *v = !(*v))
+                                        }
+                                        None => window.remove_window(),
+                                    }
+                                })),
+                        ),
                 ),
             )
             .child(
                 div()
                     .w_full()
-                    .pt(px(9.0))
-                    .pb(px(12.0))
-                    .px(px(12.0))
+                    .pt(spx(9.0))
+                    .pb(spx(12.0))
+                    .px(spx(12.0))
                     .flex()
+                    .flex_col()
+                    .gap(spx(4.0))
                     .child(
                         div()
-                            .line_height(px(26.0))
+                            .line_height(spx(26.0))
                             .font_weight(FontWeight::BOLD)
-                            .text_size(px(26.0))
-                            .child("Queue"),
-                    ),
+                            .text_size(spx(26.0))
+                            .overflow_x_hidden()
+                            .text_ellipsis()
+                            .child(t!(cx, "queue.title")),
+                    )
+                    .child(self.stats.clone()),
             )
             .child(
                 div()
@@ -263,8 +469,8 @@ impl Render for Queue {
                         button()
                             .style(ButtonStyle::MinimalNoRounding)
                             .size(ButtonSize::Large)
-                            .child(icon(TRASH).size(px(14.0)).my_auto())
-                            .child("Clear")
+                            .child(icon(TRASH).size(spx(14.0)).my_auto())
+                            .child(t!(cx, "queue.clear"))
                             .w_full()
                             .id("clear-queue")
                             .on_click(|_, _, cx| {
@@ -276,27 +482,70 @@ impl Render for Queue {
                         button()
                             .style(ButtonStyle::MinimalNoRounding)
                             .size(ButtonSize::Large)
-                            .child(icon(SHUFFLE).size(px(14.0)).my_auto())
-                            .when(*shuffling, |this| this.child("Shuffling"))
-                            .when(!shuffling, |this| this.child("Shuffle"))
+                            .child(icon(SHUFFLE).size(spx(14.0)).my_auto())
+                            .child(if *shuffling {
+                                t!(cx, "queue.shuffling")
+                            } else {
+                                t!(cx, "queue.shuffle")
+                            })
                             .w_full()
                             .id("queue-shuffle")
                             .on_click(|_, _, cx| cx.global::<PlaybackInterface>().toggle_shuffle()),
                     ),
             )
+            .when(*shuffling, |this| {
+                this.child(
+                    div()
+                        .w_full()
+                        .flex()
+                        .gap(spx(4.0))
+                        .px(spx(12.0))
+                        .py(spx(6.0))
+                        .border_b_1()
+                        .border_color(theme.border_color)
+                        .children(
+                            [
+                                (false, t!(cx, "queue.order.play")),
+                                (true, t!(cx, "queue.order.added")),
+                            ]
+                            .into_iter()
+                            .map(|(option, label)| {
+                                button()
+                                    .id(("queue-order", option as usize))
+                                    .text_sm()
+                                    .when(option == added_order, |this| {
+                                        this.intent(ButtonIntent::Primary)
+                                    })
+                                    .when(option != added_order, |this| {
+                                        this.style(ButtonStyle::Minimal)
+                                    })
+                                    .child(label)
+                                    .on_click(cx.listener(move |this, _, _, cx| {
+                                        if this.added_order != option {
+                                            this.added_order = option;
+                                            this.views_model = cx.new(|_| FxHashMap::default());
+                                            this.render_counter = cx.new(|_| 0);
+                                            cx.notify();
+                                        }
+                                    }))
+                            }),
+                        ),
+                )
+            })
             .child(
-                uniform_list("queue", queue.len(), move |range, _, cx| {
+                uniform_list("queue", queue_len, move |range, _, cx| {
                     let start = range.start;
                     let is_templ_render = range.start == 0 && range.end == 1;
 
-                    let queue = cx
-                        .global::<Models>()
-                        .queue
-                        .clone()
-                        .read(cx)
-                        .data
-                        .read()
-                        .expect("could not read queue");
+                    let queue_model = cx.global::<Models>().queue.clone().read(cx);
+                    let queue = if added_order {
+                        queue_model
+                            .original
+                            .read()
+                            .expect("could not read original queue")
+                    } else {
+                        queue_model.data.read().expect("could not read queue")
+                    };
 
                     if range.end <= queue.len() {
                         let items = queue[range].to_vec();
@@ -316,7 +565,7 @@ impl Render for Queue {
                                 div().child(create_or_retrieve_view(
                                     &views_model,
                                     idx,
-                                    move |cx| QueueItem::new(cx, Some(item), idx),
+                                    move |cx| QueueItem::new(cx, Some(item), idx, added_order),
                                     cx,
                                 ))
                             })
@@ -332,3 +581,85 @@ impl Render for Queue {
             )
     }
 }
+
+/// The root view of the window the queue is displayed in after being popped out.
+pub struct QueueWindow {
+    queue: Entity<Queue>,
+    focus_handle: FocusHandle,
+}
+
+impl Render for QueueWindow {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        window.set_rem_size(scaled_rem_size(cx));
+        let theme = cx.global::<Theme>();
+
+        div()
+            .key_context("QueueWindow")
+            .track_focus(&self.focus_handle)
+            .on_action(|_: &CloseQueueWindow, window, _| window.remove_window())
+            .font_family("Inter")
+            .text_color(theme.text)
+            .bg(theme.background_primary)
+            .size_full()
+            .flex()
+            .child(self.queue.clone())
+    }
+}
+
+/// Opens the queue in its own window, or focuses the window if the queue has already been popped
+/// out. The queue is hidden in the main window for as long as the queue window is open.
+pub fn open_queue_window(cx: &mut App) {
+    let queue_window = cx.global::<Models>().queue_window.clone();
+
+    if let Some(handle) = *queue_window.read(cx) {
+        handle
+            .update(cx, |_, window, _| window.activate_window())
+            .ok();
+        return;
+    }
+
+    let bounds = Bounds::centered(None, size(px(320.0), px(640.0)), cx);
+
+    let handle = cx
+        .open_window(
+            WindowOptions {
+                window_bounds: Some(WindowBounds::Windowed(bounds)),
+                window_min_size: Some(size(px(275.0), px(300.0))),
+                titlebar: Some(TitlebarOptions {
+                    title: Some(t!(cx, "queue.title")),
+                    ..Default::default()
+                }),
+                app_id: Some("org.mailliw.hummingbird".to_string()),
+                ..Default::default()
+            },
+            |window, cx| {
+                cx.new(|cx| {
+                    cx.on_release(|_, cx| {
+                        let queue_window = cx.global::<Models>().queue_window.clone();
+                        queue_window.write(cx, None);
+                    })
+                    .detach();
+
+                    let settings = cx.global::<SettingsGlobal>().model.clone();
+
+                    cx.observe(&settings, |_, _, cx| {
+                        cx.notify();
+                    })
+                    .detach();
+
+                    // nothing else in the window takes focus, and key bindings only apply to
+                    // what's focused
+                    let focus_handle = cx.focus_handle();
+                    window.focus(&focus_handle);
+
+                    QueueWindow {
+                        queue: Queue::new(cx, None),
+                        focus_handle,
+                    }
+                })
+            },
+        )
+        .expect("could not open queue window");
+
+    queue_window.write(cx, Some(handle.into()));
+}
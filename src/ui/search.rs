@@ -1,4 +1,5 @@
 pub mod album_item;
+pub mod artist_item;
 pub mod model;
 
 use std::collections::VecDeque;
@@ -8,6 +9,7 @@ use model::SearchModel;
 
 use super::{
     components::modal::modal, global_actions::Search, library::ViewSwitchMessage, models::Models,
+    util::spx,
 };
 
 pub struct SearchView {
@@ -85,7 +87,7 @@ impl Render for SearchView {
                     })
                     .expect("failed to update search view")
                 })
-                .child(div().w(px(550.0)).h(px(500.0)).child(self.search.clone()))
+                .child(div().w(spx(550.0)).h(spx(500.0)).child(self.search.clone()))
                 .into_any_element()
         } else {
             div().into_any_element()
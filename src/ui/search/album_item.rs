@@ -1,8 +1,12 @@
-use std::sync::Arc;
-
 use gpui::{App, SharedString};
 
-use crate::ui::components::palette::{FinderItemLeft, PaletteItem};
+use crate::{
+    playback::interface::PlaybackInterface,
+    ui::{
+        components::palette::{FinderItemLeft, PaletteItem},
+        library::album_queue_items,
+    },
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct AlbumPaletteItem {
@@ -16,13 +20,6 @@ impl AlbumPaletteItem {
         Self { id, title, artist }
     }
 
-    pub fn from_search_results(results: Vec<(u32, String, String)>) -> Vec<Arc<AlbumPaletteItem>> {
-        results
-            .into_iter()
-            .map(|(id, title, artist)| Arc::new(AlbumPaletteItem::new(id, title, artist)))
-            .collect()
-    }
-
     pub fn thumbnail_path(&self) -> String {
         format!("!db://album/{}/thumb", self.id)
     }
@@ -40,4 +37,12 @@ impl PaletteItem for AlbumPaletteItem {
     fn right_content(&self, _cx: &mut App) -> Option<SharedString> {
         Some(self.artist.clone().into())
     }
+
+    /// Adds the album to the end of the queue, leaving the search open so that more albums can
+    /// be queued.
+    fn secondary_accept(&self, cx: &mut App) -> bool {
+        let queue_items = album_queue_items(cx, self.id as i64);
+        cx.global::<PlaybackInterface>().queue_list(queue_items);
+        true
+    }
 }
@@ -0,0 +1,46 @@
+use gpui::{App, SharedString};
+
+use crate::ui::{
+    components::{
+        icons::USER,
+        palette::{FinderItemLeft, PaletteItem},
+    },
+    locale::t,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtistPaletteItem {
+    pub id: u32,
+    pub name: String,
+    pub image_hash: Option<String>,
+}
+
+impl ArtistPaletteItem {
+    pub fn new(id: u32, name: String, image_hash: Option<String>) -> Self {
+        Self {
+            id,
+            name,
+            image_hash,
+        }
+    }
+}
+
+impl PaletteItem for ArtistPaletteItem {
+    fn left_content(&self, _cx: &mut App) -> Option<FinderItemLeft> {
+        // the hash is included so that a newly fetched image isn't hidden by a cached failure
+        Some(match &self.image_hash {
+            Some(hash) => {
+                FinderItemLeft::Image(format!("!db://artist/{}/image?{hash}", self.id).into())
+            }
+            None => FinderItemLeft::Icon(USER.into()),
+        })
+    }
+
+    fn middle_content(&self, _cx: &mut App) -> SharedString {
+        self.name.clone().into()
+    }
+
+    fn right_content(&self, cx: &mut App) -> Option<SharedString> {
+        Some(t!(cx, "search.artist"))
+    }
+}
@@ -1,32 +1,90 @@
 use std::sync::Arc;
 
-use gpui::{App, AppContext, Context, Entity, EventEmitter, IntoElement, Render, Window};
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, IntoElement, Render, SharedString, Window,
+};
 use nucleo::Utf32String;
 use tracing::debug;
 
 use crate::{
     library::{db::LibraryAccess, scan::ScanEvent},
     ui::{
-        components::{input::EnrichedInputAction, palette::Palette},
+        components::{
+            input::EnrichedInputAction,
+            palette::{FinderItemLeft, Palette, PaletteItem},
+        },
         library::ViewSwitchMessage,
         models::Models,
     },
 };
 
-use super::album_item::AlbumPaletteItem;
+use super::{album_item::AlbumPaletteItem, artist_item::ArtistPaletteItem};
+
+/// A search result, which can be either an album or an artist.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchItem {
+    Album(AlbumPaletteItem),
+    Artist(ArtistPaletteItem),
+}
+
+impl PaletteItem for SearchItem {
+    fn left_content(&self, cx: &mut App) -> Option<FinderItemLeft> {
+        match self {
+            SearchItem::Album(album) => album.left_content(cx),
+            SearchItem::Artist(artist) => artist.left_content(cx),
+        }
+    }
+
+    fn middle_content(&self, cx: &mut App) -> SharedString {
+        match self {
+            SearchItem::Album(album) => album.middle_content(cx),
+            SearchItem::Artist(artist) => artist.middle_content(cx),
+        }
+    }
+
+    fn right_content(&self, cx: &mut App) -> Option<SharedString> {
+        match self {
+            SearchItem::Album(album) => album.right_content(cx),
+            SearchItem::Artist(artist) => artist.right_content(cx),
+        }
+    }
+
+    fn secondary_accept(&self, cx: &mut App) -> bool {
+        match self {
+            SearchItem::Album(album) => album.secondary_accept(cx),
+            SearchItem::Artist(artist) => artist.secondary_accept(cx),
+        }
+    }
+}
 
-type MatcherFunc = Box<dyn Fn(&Arc<AlbumPaletteItem>, &mut App) -> Utf32String + 'static>;
-type OnAccept = Box<dyn Fn(&Arc<AlbumPaletteItem>, &mut App) + 'static>;
+/// Loads every album and artist in the library as search results.
+fn load_items(cx: &App) -> anyhow::Result<Vec<Arc<SearchItem>>> {
+    let artists = cx
+        .list_artists_search()?
+        .into_iter()
+        .map(|(id, name, image_hash)| {
+            SearchItem::Artist(ArtistPaletteItem::new(id, name, image_hash))
+        });
+    let albums = cx
+        .list_albums_search()?
+        .into_iter()
+        .map(|(id, title, artist)| SearchItem::Album(AlbumPaletteItem::new(id, title, artist)));
+
+    Ok(artists.chain(albums).map(Arc::new).collect())
+}
+
+type MatcherFunc = Box<dyn Fn(&Arc<SearchItem>, &mut App) -> Utf32String + 'static>;
+type OnAccept = Box<dyn Fn(&Arc<SearchItem>, &mut App) + 'static>;
 
 pub struct SearchModel {
-    palette: Entity<Palette<AlbumPaletteItem, MatcherFunc, OnAccept>>,
+    palette: Entity<Palette<SearchItem, MatcherFunc, OnAccept>>,
 }
 
 impl SearchModel {
     pub fn new(cx: &mut App) -> Entity<SearchModel> {
         cx.new(|cx| {
-            let albums = match cx.list_albums_search() {
-                Ok(album_data) => AlbumPaletteItem::from_search_results(album_data),
+            let items = match load_items(cx) {
+                Ok(items) => items,
                 Err(e) => {
                     debug!("Failed to load albums for search: {:?}", e);
                     Vec::new()
@@ -35,11 +93,18 @@ impl SearchModel {
 
             let weak_self = cx.weak_entity();
 
-            let matcher: MatcherFunc =
-                Box::new(|album, _| Utf32String::from(format!("{} {}", album.title, album.artist)));
+            let matcher: MatcherFunc = Box::new(|item, _| match &**item {
+                SearchItem::Album(album) => {
+                    Utf32String::from(format!("{} {}", album.title, album.artist))
+                }
+                SearchItem::Artist(artist) => Utf32String::from(artist.name.as_str()),
+            });
 
-            let on_accept: OnAccept = Box::new(move |album, cx| {
-                let event = ViewSwitchMessage::Release(album.id as i64);
+            let on_accept: OnAccept = Box::new(move |item, cx| {
+                let event = match &**item {
+                    SearchItem::Album(album) => ViewSwitchMessage::Release(album.id as i64),
+                    SearchItem::Artist(artist) => ViewSwitchMessage::Artist(artist.id as i64),
+                };
 
                 if let Some(search_model) = weak_self.upgrade() {
                     search_model.update(cx, |_: &mut SearchModel, cx| {
@@ -48,7 +113,7 @@ impl SearchModel {
                 }
             });
 
-            let palette = Palette::new(cx, albums, matcher, on_accept);
+            let palette = Palette::new(cx, items, matcher, on_accept);
 
             let search_model = SearchModel { palette };
 
@@ -63,8 +128,8 @@ impl SearchModel {
                 {
                     debug!("Scan complete, refreshing album list for search");
 
-                    let new_albums = match cx.list_albums_search() {
-                        Ok(album_data) => AlbumPaletteItem::from_search_results(album_data),
+                    let new_items = match load_items(cx) {
+                        Ok(items) => items,
                         Err(e) => {
                             debug!("Failed to reload albums after scan: {:?}", e);
                             return;
@@ -73,7 +138,7 @@ impl SearchModel {
 
                     if let Some(palette) = palette_weak.upgrade() {
                         palette.update(cx, |_, cx| {
-                            cx.emit(new_albums);
+                            cx.emit(new_items);
                         });
                     }
                 }
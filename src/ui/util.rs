@@ -2,12 +2,35 @@ use std::sync::Arc;
 
 use gpui::{
     AnyElement, App, Bounds, Element, ElementId, Entity, GlobalElementId, InspectorElementId,
-    IntoElement, LayoutId, ParentElement, Pixels, Render, RenderImage, Stateful, StyleRefinement,
-    Styled, Window,
+    IntoElement, LayoutId, ParentElement, Pixels, Rems, Render, RenderImage, Stateful,
+    StyleRefinement, Styled, Window, px,
 };
 use rustc_hash::FxHashMap;
 use tracing::debug;
 
+use crate::settings::SettingsGlobal;
+
+/// The rem size at an interface scale of 1.0.
+pub const BASE_REM_SIZE: f32 = 16.0;
+
+/// Converts a size in pixels at the default interface scale into [Rems], so that it follows the
+/// interface scale setting. Use [gpui::px] for sizes that should stay the same at every scale.
+pub const fn spx(value: f32) -> Rems {
+    Rems(value / BASE_REM_SIZE)
+}
+
+/// Returns the rem size for the current interface scale. Every window should apply this with
+/// [Window::set_rem_size] before rendering its contents.
+pub fn scaled_rem_size(cx: &App) -> Pixels {
+    let scale = cx
+        .global::<SettingsGlobal>()
+        .model
+        .read(cx)
+        .interface
+        .scale();
+    px(BASE_REM_SIZE * scale)
+}
+
 pub fn prune_views<T>(
     views_model: &Entity<FxHashMap<usize, Entity<T>>>,
     render_counter: &Entity<usize>,